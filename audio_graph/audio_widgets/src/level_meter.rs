@@ -0,0 +1,240 @@
+use {
+    crate::{
+        makepad_draw::*,
+        makepad_widgets::*,
+        makepad_audio_graph::effects::gain_meter::MeterLevels,
+    }
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+
+    DrawLevelMeter = {{DrawLevelMeter}} {
+        instance color_bg: #1a1a1a
+        uniform color_low: #3fb950
+        uniform color_mid: #d8c03a
+        uniform color_high: #e5453a
+        uniform gap: 2.0
+
+        fn meter_color(self, level: float) -> vec4 {
+            return mix(
+                mix(self.color_low, self.color_mid, smoothstep(0.6, 0.75, level)),
+                self.color_high,
+                smoothstep(0.85, 0.95, level)
+            )
+        }
+
+        fn pixel(self) -> vec4 {
+            // left channel on top, right channel below
+            let is_right = step(0.5, self.pos.y);
+            let rms = mix(self.rms_left, self.rms_right, is_right);
+            let peak = mix(self.peak_left, self.peak_right, is_right);
+            let hold = mix(self.hold_left, self.hold_right, is_right);
+            let width = self.rect_size.x;
+            let half = self.rect_size.y * 0.5;
+            let y = is_right * (half + self.gap * 0.5);
+            let height = half - self.gap * 0.5;
+            let color = self.meter_color(self.pos.x);
+
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.rect(0., y, width, height);
+            sdf.fill(self.color_bg);
+            sdf.rect(0., y, width * peak, height);
+            sdf.fill(vec4(color.xyz * 0.5, 1.0));
+            sdf.rect(0., y, width * rms, height);
+            sdf.fill(color);
+            if hold > 0.0 {
+                sdf.rect(max(width * hold - 2.0, 0.), y, 2.0, height);
+                sdf.fill(self.meter_color(hold));
+            }
+            return sdf.result
+        }
+    }
+
+    AudioLevelMeter = {{AudioLevelMeter}} {
+        width: Fill,
+        height: 16
+    }
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+pub struct DrawLevelMeter {
+    #[deref] draw_super: DrawQuad,
+    #[live] rms_left: f32,
+    #[live] rms_right: f32,
+    #[live] peak_left: f32,
+    #[live] peak_right: f32,
+    #[live] hold_left: f32,
+    #[live] hold_right: f32,
+}
+
+/// The decaying state of one channel, in dB
+#[derive(Clone, Copy, Default)]
+struct ChannelLevels {
+    rms: f32,
+    peak: f32,
+    hold: f32,
+    /// Seconds until the hold line starts to fall
+    hold_remaining: f64,
+}
+
+/// A stereo peak/rms meter with peak hold. Feed it with `set_levels`, for instance with the
+/// readings of an `AudioRecorder` or a `GainMeter`.
+#[derive(Live, Widget)]
+pub struct AudioLevelMeter {
+    #[walk] walk: Walk,
+    #[redraw] #[live] draw_meter: DrawLevelMeter,
+    /// The dB value at the left edge of the meter
+    #[live(-60.0)] min_db: f32,
+    /// Seconds the peak hold line stays before it falls
+    #[live(1.5)] hold_time: f64,
+    /// How fast the bars fall back, in dB per second
+    #[live(24.0)] fall_rate: f32,
+    #[rust] channels: [ChannelLevels; 2],
+    #[rust] next_frame: NextFrame,
+    #[rust] last_time: Option<f64>,
+}
+
+impl LiveHook for AudioLevelMeter {
+    fn after_new_from_doc(&mut self, _cx: &mut Cx) {
+        for channel in &mut self.channels {
+            channel.rms = self.min_db;
+            channel.peak = self.min_db;
+            channel.hold = self.min_db;
+        }
+    }
+}
+
+impl Widget for AudioLevelMeter {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, _scope: &mut Scope) {
+        if let Some(ne) = self.next_frame.is_event(event) {
+            let dt = ne.time - self.last_time.unwrap_or(ne.time);
+            self.last_time = Some(ne.time);
+            if self.decay(dt) {
+                self.next_frame = cx.new_next_frame();
+            }
+            else {
+                self.last_time = None;
+            }
+            self.draw_meter.redraw(cx);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        let range = -self.min_db.min(-1.0);
+        let norm = | db: f32 | ((db + range) / range).clamp(0.0, 1.0);
+        let [left, right] = self.channels;
+        self.draw_meter.rms_left = norm(left.rms);
+        self.draw_meter.rms_right = norm(right.rms);
+        self.draw_meter.peak_left = norm(left.peak);
+        self.draw_meter.peak_right = norm(right.peak);
+        self.draw_meter.hold_left = norm(left.hold);
+        self.draw_meter.hold_right = norm(right.hold);
+        self.draw_meter.draw_walk(cx, walk);
+        DrawStep::done()
+    }
+}
+
+impl AudioLevelMeter {
+    /// Lets the bars fall for `dt` seconds, returns whether anything is still above the floor
+    fn decay(&mut self, dt: f64) -> bool {
+        let fall = self.fall_rate * dt as f32;
+        let mut is_active = false;
+        for channel in &mut self.channels {
+            channel.rms = (channel.rms - fall).max(self.min_db);
+            channel.peak = (channel.peak - fall).max(self.min_db);
+            channel.hold_remaining -= dt;
+            if channel.hold_remaining < 0.0 {
+                channel.hold = (channel.hold - fall).max(self.min_db);
+            }
+            is_active |= channel.peak > self.min_db || channel.hold > self.min_db;
+        }
+        is_active
+    }
+
+    /// Shows a new reading, the bars jump up to it and fall back smoothly
+    pub fn set_levels(&mut self, cx: &mut Cx, levels: MeterLevels) {
+        for (c, channel) in self.channels.iter_mut().enumerate() {
+            channel.rms = channel.rms.max(levels.rms_db(c));
+            channel.peak = channel.peak.max(levels.peak_db(c));
+            if levels.peak_db(c) >= channel.hold {
+                channel.hold = levels.peak_db(c);
+                channel.hold_remaining = self.hold_time;
+            }
+        }
+        if self.last_time.is_none() {
+            self.next_frame = cx.new_next_frame();
+        }
+        self.draw_meter.redraw(cx);
+    }
+}
+
+impl AudioLevelMeterRef {
+    pub fn set_levels(&self, cx: &mut Cx, levels: MeterLevels) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_levels(cx, levels);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_meter(f: impl FnOnce(&mut Cx, &mut AudioLevelMeter)) {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let mut meter = AudioLevelMeter::new(&mut cx);
+        meter.after_new_from_doc(&mut cx);
+        f(&mut cx, &mut meter);
+    }
+
+    fn levels(peak_db: f32, rms_db: f32) -> MeterLevels {
+        let gain = | db: f32 | 10.0f32.powf(db / 20.0);
+        MeterLevels {peak: [gain(peak_db); 2], rms: [gain(rms_db); 2]}
+    }
+
+    fn assert_db(got: f32, db: f32) {
+        assert!((got - db).abs() < 0.01, "expected {db} dB, got {got} dB");
+    }
+
+    #[test]
+    fn readings_jump_up_and_fall_back() {
+        with_meter( | cx, meter | {
+            meter.set_levels(cx, levels(-6.0, -12.0));
+            assert_db(meter.channels[0].peak, -6.0);
+            assert_db(meter.channels[0].rms, -12.0);
+            // a quieter reading doesn't pull the bars down, they fall at the fall rate
+            meter.set_levels(cx, levels(-30.0, -36.0));
+            assert_db(meter.channels[0].peak, -6.0);
+            assert!(meter.decay(0.5));
+            assert_db(meter.channels[0].peak, -18.0);
+            assert_db(meter.channels[1].rms, -24.0);
+            // until they reach the bottom of the scale
+            meter.decay(10.0);
+            assert_db(meter.channels[0].peak, -60.0);
+            assert_db(meter.channels[0].rms, -60.0);
+        })
+    }
+
+    #[test]
+    fn peak_hold_waits_then_falls() {
+        with_meter( | cx, meter | {
+            meter.set_levels(cx, levels(-3.0, -10.0));
+            assert!(meter.decay(1.0));
+            assert_db(meter.channels[0].hold, -3.0);
+            // a lower peak leaves the hold alone, a higher one restarts it
+            meter.set_levels(cx, levels(-20.0, -30.0));
+            assert_db(meter.channels[0].hold, -3.0);
+            meter.set_levels(cx, levels(-1.0, -10.0));
+            assert!(meter.decay(1.0));
+            assert_db(meter.channels[0].hold, -1.0);
+            // past the hold time it falls like the bars
+            meter.decay(1.0);
+            assert_db(meter.channels[0].hold, -1.0 - 24.0 * 1.0);
+            assert!(!meter.decay(10.0));
+            assert_db(meter.channels[1].hold, -60.0);
+        })
+    }
+}
@@ -1,9 +1,11 @@
 
 pub mod piano;
 pub mod display_audio;
+pub mod level_meter;
 
 use makepad_platform::Cx;
 pub use makepad_widgets;
+pub use makepad_audio_graph;
 pub use makepad_widgets::makepad_draw;
 pub use makepad_widgets::makepad_platform; 
 pub use makepad_platform::makepad_math;
@@ -12,4 +14,5 @@ pub fn live_design(cx:&mut Cx){
     makepad_widgets::live_design(cx);
     self::piano::live_design(cx);
     self::display_audio::live_design(cx);
+    self::level_meter::live_design(cx);
 }
@@ -2,9 +2,13 @@ use {
     crate::{
         makepad_platform::*,
         audio_traits::*,
+        offline_render::*,
+        automation::*,
     },
     std::any::TypeId,
+    std::collections::VecDeque,
     std::sync::{Arc, Mutex},
+    std::sync::atomic::{AtomicU64, Ordering},
 };
 
 live_design!{
@@ -15,6 +19,9 @@ live_design!{
 pub enum FromUI {
     AllNotesOff,
     MidiData(MidiData),
+    /// A message for a frame on the graph clock
+    MidiDataAt(f64, MidiData),
+    Automation(AutomationEvent),
     NewRoot(Box<dyn AudioGraphNode + Send>),
     DisplayAudio(AudioBuffer),
 }
@@ -33,11 +40,13 @@ pub struct AudioGraph {
     #[live] root: AudioComponentRef,
     #[rust] from_ui: FromUISender<FromUI>,
     #[rust] to_ui: ToUIReceiver<ToUIDisplayMsg>,
+    /// The frame the audio thread renders next, as f64 bits
+    #[rust] sample_time: Arc<AtomicU64>,
 }
 
 impl LiveHook for AudioGraph {
     fn after_new_from_doc(&mut self, cx: &mut Cx) {
-        Self::start_audio_output(cx, self.from_ui.receiver(), self.to_ui.sender());
+        Self::start_audio_output(cx, self.from_ui.receiver(), self.to_ui.sender(), self.sample_time.clone());
         // we should have a component
         
         if let Some(root) = self.root.as_mut() {
@@ -53,10 +62,40 @@ impl LiveHook for AudioGraph {
     }
 }
 
+/// The most frames and channels a block split at a timed message can have, the buffer
+/// for the split is reserved up front so the audio callback never allocates
+const SPLIT_BLOCK_FRAMES: usize = 4096;
+const SPLIT_BLOCK_CHANNELS: usize = 8;
+
 struct Node {
     from_ui: FromUIReceiver<FromUI>,
     display_buffers: Vec<AudioBuffer>,
-    root: Option<Box<dyn AudioGraphNode + Send >>
+    root: Option<Box<dyn AudioGraphNode + Send >>,
+    clock: f64,
+    sample_time: Arc<AtomicU64>,
+    /// Timed messages in time order, blocks are split where they fall
+    scheduled_midi: VecDeque<(f64, MidiData)>,
+    block: AudioBuffer,
+}
+
+impl Node {
+    fn new(from_ui: FromUIReceiver<FromUI>, sample_time: Arc<AtomicU64>) -> Self {
+        let mut buffers = Vec::new();
+        for _ in 0..512 {
+            buffers.push(AudioBuffer::new_with_size(512, 2));
+        }
+        let mut block = AudioBuffer::new_with_size(SPLIT_BLOCK_FRAMES, SPLIT_BLOCK_CHANNELS);
+        block.resize(0, 2);
+        Self {
+            from_ui,
+            display_buffers: buffers,
+            root: None,
+            clock: 0.0,
+            sample_time,
+            scheduled_midi: VecDeque::new(),
+            block,
+        }
+    }
 }
 
 impl AudioGraph {
@@ -73,9 +112,38 @@ impl AudioGraph {
     }
     
     
+    /// Plays `data` on the frame `time` of the graph clock, late messages play at the start of the next block
+    pub fn send_midi_data_at(&self, time: f64, data: MidiData) {
+        let _ = self.from_ui.send(FromUI::MidiDataAt(time, data));
+    }
+    
     pub fn all_notes_off(&self) {
         let _ =  self.from_ui.send(FromUI::AllNotesOff);
     }
+    
+    /// The graph clock in frames, it moves a block at a time. Schedule automation
+    /// a bit ahead of it so events don't arrive after their frame has been rendered
+    pub fn sample_time(&self) -> f64 {
+        f64::from_bits(self.sample_time.load(Ordering::Relaxed))
+    }
+    
+    pub fn schedule_automation(&self, event: AutomationEvent) {
+        let _ = self.from_ui.send(FromUI::Automation(event));
+    }
+    
+    /// Plays a recorded lane with its start at `start` on the graph clock
+    pub fn play_automation(&self, lane: &AutomationLane, start: f64) {
+        for event in lane.events_at(start) {
+            self.schedule_automation(event);
+        }
+    }
+    
+    /// Renders a fresh copy of the root component, the live output is left alone
+    pub fn render_offline(&mut self, cx: &mut Cx, render: &OfflineRender, events: &[OfflineMidiEvent], frame_count: usize) -> Option<AudioBuffer> {
+        let root = self.root.as_mut()?;
+        let mut graph_node = root.get_graph_node(cx);
+        Some(render.render(graph_node.as_mut(), events, frame_count))
+    }
      
     fn render_to_output_buffer(node: &mut Node, to_ui: &ToUISender<ToUIDisplayMsg>, info: AudioInfo, output: &mut AudioBuffer) {
         
@@ -95,6 +163,15 @@ impl AudioGraph {
                     }
                     // }
                 }
+                FromUI::MidiDataAt(time, data) => {
+                    let index = node.scheduled_midi.iter().position( | (t, _) | *t > time).unwrap_or(node.scheduled_midi.len());
+                    node.scheduled_midi.insert(index, (time, data));
+                }
+                FromUI::Automation(event) => {
+                    if let Some(root) = node.root.as_mut() {
+                        root.handle_automation(event);
+                    }
+                }
                 FromUI::AllNotesOff=>{
                     if let Some(root) = node.root.as_mut() {
                         root.all_notes_off();
@@ -102,14 +179,61 @@ impl AudioGraph {
                 }
             }
         }
-        if let Some(root) = node.root.as_mut() {
-            // we should create a real output buffer
-            //node.buffer.resize_like_output(output);
+        // devices without a clock of their own get one counted here, so every node sees the same time
+        let sample_time = block_time(&info, &mut node.clock, output.frame_count());
+        node.sample_time.store(node.clock.to_bits(), Ordering::Relaxed);
+        let info = AudioInfo {
+            time: Some(info.time.map( | t | AudioTime {sample_time, ..t}).unwrap_or(AudioTime {
+                sample_time,
+                host_time: 0,
+                rate_scalar: 1.0
+            })),
+            ..info
+        };
+        let frame_count = output.frame_count();
+        let mut frame = 0;
+        while frame < frame_count {
+            let frame_time = sample_time + frame as f64;
+            while let Some((_, data)) = node.scheduled_midi.front().filter( | (time, _) | *time <= frame_time) {
+                if let Some(root) = node.root.as_mut() {
+                    root.handle_midi_data(*data);
+                }
+                node.scheduled_midi.pop_front();
+            }
+            let channel_count = output.channel_count();
+            let mut end = node.scheduled_midi.front().map( | (time, _) | {
+                ((time - sample_time).ceil() as usize).clamp(frame + 1, frame_count)
+            }).unwrap_or(frame_count);
+            if channel_count > SPLIT_BLOCK_CHANNELS {
+                // too wide to split, messages inside the block play at the start of the next one
+                end = frame_count;
+            }
+            let Some(root) = node.root.as_mut() else {
+                break
+            };
             let mut dg = DisplayAudioGraph {
                 to_ui,
                 buffers: &mut node.display_buffers
             };
-            root.render_to_audio_buffer(info, &mut [output], &[], &mut dg);
+            if frame == 0 && end == frame_count {
+                root.render_to_audio_buffer(info, &mut [&mut *output], &[], &mut dg);
+            }
+            else {
+                // a message falls inside the block, render up to it on its own. This stays
+                // within the space reserved in Node::new so the resize doesn't allocate
+                end = end.min(frame + SPLIT_BLOCK_FRAMES);
+                node.block.resize(end - frame, channel_count);
+                node.block.zero();
+                let info = AudioInfo {
+                    time: info.time.map( | t | AudioTime {sample_time: frame_time, ..t}),
+                    ..info
+                };
+                root.render_to_audio_buffer(info, &mut [&mut node.block], &[], &mut dg);
+                for c in 0..channel_count {
+                    output.channel_mut(c)[frame..end].copy_from_slice(node.block.channel(c));
+                }
+            }
+            frame = end;
             // lets output this buffer to the UI
             //if let Some(mut display_buffer) = dg.pop_buffer() {
             //    display_buffer.copy_from(&node.buffer);
@@ -119,17 +243,8 @@ impl AudioGraph {
         }
     }
     
-    fn start_audio_output(cx: &mut Cx, from_ui: FromUIReceiver<FromUI>, to_ui: ToUISender<ToUIDisplayMsg>) {
-        let mut buffers = Vec::new();
-        for _ in 0..512 {
-            buffers.push(AudioBuffer::new_with_size(512, 2));
-        }
-        
-        let state = Arc::new(Mutex::new(Node {
-            from_ui,
-            display_buffers: buffers,
-            root: None
-        }));
+    fn start_audio_output(cx: &mut Cx, from_ui: FromUIReceiver<FromUI>, to_ui: ToUISender<ToUIDisplayMsg>, sample_time: Arc<AtomicU64>) {
+        let state = Arc::new(Mutex::new(Node::new(from_ui, sample_time)));
         
         let to_ui = Arc::new(Mutex::new(to_ui));
         
@@ -168,3 +283,47 @@ impl AudioGraph {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // records the size of every block it renders
+    struct Blocks(Arc<Mutex<Vec<usize>>>);
+
+    impl AudioGraphNode for Blocks {
+        fn handle_midi_data(&mut self, _data: MidiData) {}
+        fn all_notes_off(&mut self) {}
+
+        fn render_to_audio_buffer(&mut self, _info: AudioInfo, outputs: &mut [&mut AudioBuffer], _inputs: &[&AudioBuffer], _display: &mut DisplayAudioGraph) {
+            self.0.lock().unwrap().push(outputs[0].frame_count());
+        }
+    }
+
+    fn render_block(times: &[f64], frame_count: usize) -> Vec<usize> {
+        let mut from_ui = FromUISender::default();
+        let mut node = Node::new(from_ui.receiver(), Arc::default());
+        let blocks = Arc::new(Mutex::new(Vec::new()));
+        node.root = Some(Box::new(Blocks(blocks.clone())));
+        for time in times {
+            let _ = from_ui.send(FromUI::MidiDataAt(*time, MidiData {data: [0x90, 60, 100]}));
+        }
+        let to_ui = ToUIReceiver::<ToUIDisplayMsg>::default();
+        let info = AudioInfo {device_id: AudioDeviceId::default(), time: None, sample_rate: 48000.0};
+        AudioGraph::render_to_output_buffer(&mut node, &to_ui.sender(), info, &mut AudioBuffer::new_with_size(frame_count, 2));
+        let blocks = blocks.lock().unwrap().clone();
+        blocks
+    }
+
+    #[test]
+    fn blocks_split_at_timed_messages() {
+        assert_eq!(render_block(&[], 512), vec![512]);
+        assert_eq!(render_block(&[100.0, 300.0], 512), vec![100, 200, 212]);
+    }
+
+    #[test]
+    fn split_blocks_stay_within_the_reserved_buffer() {
+        let frame_count = SPLIT_BLOCK_FRAMES * 2 + 100;
+        assert_eq!(render_block(&[10.0], frame_count), vec![10, SPLIT_BLOCK_FRAMES, SPLIT_BLOCK_FRAMES, 90]);
+    }
+}
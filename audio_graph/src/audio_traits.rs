@@ -2,6 +2,7 @@ use {
     std::collections::BTreeMap,
     crate::{
         makepad_platform::*,
+        automation::AutomationEvent,
     }
 };
 
@@ -23,6 +24,12 @@ pub trait AudioComponent: LiveApply {
 pub trait AudioGraphNode {
     fn handle_midi_data(&mut self, data: MidiData);
     fn all_notes_off(&mut self);
+    /// Scheduled parameter changes, they arrive ahead of the block they fall in.
+    /// Nodes with children pass them on with `AutomationEvent::for_child`
+    fn handle_automation(&mut self, _event: AutomationEvent) {}
+    /// The buffers a node reads and writes, a router sums every connection into its port
+    fn input_port_count(&self) -> usize {1}
+    fn output_port_count(&self) -> usize {1}
     fn render_to_audio_buffer(
         &mut self,
         info: AudioInfo,
@@ -0,0 +1,359 @@
+use {
+    crate::{
+        makepad_platform::*,
+    },
+    std::collections::VecDeque,
+};
+
+/// How a parameter gets to the value of an event. Step jumps at the event time (with a short
+/// smoothing against clicks), Linear and Exponential ramp from the previous point so they arrive
+/// exactly at the event time. Exponential falls back to Linear when the ramp crosses or touches zero
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AutomationCurve {
+    Step,
+    Linear,
+    Exponential,
+}
+
+/// A point on a parameter timeline, in frames of the audio graph clock.
+/// `node` addresses a child of a Router, Mixer or Instrument by its id, it is
+/// cleared on the way down so the addressed node sees an empty one. An empty
+/// `node` goes to every node that has the parameter
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutomationEvent {
+    pub node: LiveId,
+    pub param: LiveId,
+    pub time: f64,
+    pub value: f32,
+    pub curve: AutomationCurve,
+}
+
+impl AutomationEvent {
+    pub fn set_at(param: LiveId, time: f64, value: f32) -> Self {
+        Self {node: LiveId::empty(), param, time, value, curve: AutomationCurve::Step}
+    }
+
+    pub fn linear_to(param: LiveId, time: f64, value: f32) -> Self {
+        Self {curve: AutomationCurve::Linear, ..Self::set_at(param, time, value)}
+    }
+
+    pub fn exponential_to(param: LiveId, time: f64, value: f32) -> Self {
+        Self {curve: AutomationCurve::Exponential, ..Self::set_at(param, time, value)}
+    }
+
+    pub fn on_node(self, node: LiveId) -> Self {
+        Self {node, ..self}
+    }
+
+    /// The event as a container passes it to its child `id`
+    pub fn for_child(&self, id: LiveId) -> Self {
+        if self.node == id {
+            Self {node: LiveId::empty(), ..*self}
+        }
+        else {
+            *self
+        }
+    }
+
+    /// Whether a node without children should apply this event to its parameter `param`
+    pub fn is_for(&self, param: LiveId) -> bool {
+        self.node.is_empty() && self.param == param
+    }
+}
+
+/// A parameter value that can be set directly (smoothed) and automated with scheduled events.
+/// It keeps its own frame clock which moves one frame per `tick`, `set_time` lines it up
+/// with the graph clock at the start of a block
+#[derive(Clone, Debug)]
+pub struct AutomatedParam {
+    value: f32,
+    target: f32,
+    coef: f32,
+    time: f64,
+    /// Where the ramp towards the next event starts
+    from: (f64, f32),
+    events: VecDeque<AutomationEvent>,
+}
+
+impl AutomatedParam {
+    /// `smoothing` is the seconds a direct change takes to get about two thirds of the way there, 0 jumps
+    pub fn new(value: f32, smoothing: f32, sample_rate: f32) -> Self {
+        let smoothing_frames = smoothing * sample_rate;
+        Self {
+            value,
+            target: value,
+            coef: if smoothing_frames < 1.0 {1.0} else {1.0 - (-1.0 / smoothing_frames).exp()},
+            time: 0.0,
+            from: (0.0, value),
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Glides to `value` right away, scheduled events still take over at their time
+    pub fn set(&mut self, value: f32) {
+        self.target = value;
+    }
+
+    /// Jumps to `value` without smoothing
+    pub fn reset(&mut self, value: f32) {
+        self.value = value;
+        self.target = value;
+    }
+
+    pub fn set_time(&mut self, time: f64) {
+        self.time = time;
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.events.is_empty() && (self.target - self.value).abs() < 1e-6
+    }
+
+    pub fn is_automating(&self) -> bool {
+        !self.events.is_empty()
+    }
+
+    /// Queues an event, events at the same time keep their order. A ramp runs from the event
+    /// before it, or from the current value and time when nothing else is queued
+    pub fn schedule(&mut self, event: AutomationEvent) {
+        if self.events.is_empty() {
+            self.from = (self.time, self.value);
+        }
+        let index = self.events.iter().position( | e | e.time > event.time).unwrap_or(self.events.len());
+        self.events.insert(index, event);
+    }
+
+    /// Drops everything that hasn't happened yet, the value stays where it is
+    pub fn cancel_scheduled(&mut self) {
+        self.events.clear();
+        self.target = self.value;
+    }
+
+    /// The value for the current frame, then moves the clock on by one frame
+    pub fn tick(&mut self) -> f32 {
+        let time = self.time;
+        self.time += 1.0;
+        while let Some(event) = self.events.front() {
+            if event.time > time {
+                break;
+            }
+            self.from = (event.time, event.value);
+            self.target = event.value;
+            if event.curve != AutomationCurve::Step {
+                self.value = event.value;
+            }
+            self.events.pop_front();
+        }
+        if let Some(event) = self.events.front() {
+            if event.curve != AutomationCurve::Step {
+                let (from_time, from_value) = self.from;
+                let t = ((time - from_time) / (event.time - from_time).max(1.0)).clamp(0.0, 1.0) as f32;
+                self.value = if event.curve == AutomationCurve::Exponential && from_value * event.value > 0.0 {
+                    from_value * (event.value / from_value).powf(t)
+                }
+                else {
+                    from_value + (event.value - from_value) * t
+                };
+                self.target = self.value;
+                return self.value
+            }
+        }
+        self.value += (self.target - self.value) * self.coef;
+        self.value
+    }
+}
+
+/// The graph time at the start of a block. Devices that don't report one get `clock`,
+/// which nodes carry from block to block
+pub fn block_time(info: &AudioInfo, clock: &mut f64, frame_count: usize) -> f64 {
+    let time = info.time.map( | t | t.sample_time).unwrap_or(*clock);
+    *clock = time + frame_count as f64;
+    time
+}
+
+/// Recorded automation with times relative to the start of the recording
+#[derive(Clone, Debug, Default)]
+pub struct AutomationLane {
+    pub events: Vec<AutomationEvent>,
+}
+
+impl AutomationLane {
+    pub fn duration(&self) -> f64 {
+        self.events.last().map( | e | e.time).unwrap_or(0.0)
+    }
+
+    /// The events moved to start at `start` on the graph clock
+    pub fn events_at(&self, start: f64) -> impl Iterator<Item = AutomationEvent> + '_ {
+        self.events.iter().map(move | e | AutomationEvent {time: e.time + start, ..*e})
+    }
+}
+
+/// Turns UI gestures into an AutomationLane. The first point of a gesture jumps, the
+/// points after it ramp linearly so the playback follows the hand without steps
+#[derive(Clone, Debug, Default)]
+pub struct AutomationRecorder {
+    start: Option<f64>,
+    lane: AutomationLane,
+    /// Node and parameter pairs in the middle of a gesture
+    gestures: Vec<(LiveId, LiveId, f32)>,
+}
+
+impl AutomationRecorder {
+    pub fn start(&mut self, time: f64) {
+        self.start = Some(time);
+        self.lane.events.clear();
+        self.gestures.clear();
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.start.is_some()
+    }
+
+    pub fn record(&mut self, node: LiveId, param: LiveId, value: f32, time: f64) {
+        let Some(start) = self.start else {return};
+        let time = (time - start).max(0.0);
+        let curve = match self.gestures.iter_mut().find( | (n, p, _) | *n == node && *p == param) {
+            Some((_, _, last)) => {
+                if *last == value {
+                    return
+                }
+                *last = value;
+                AutomationCurve::Linear
+            }
+            None => {
+                self.gestures.push((node, param, value));
+                AutomationCurve::Step
+            }
+        };
+        self.lane.events.push(AutomationEvent {node, param, time, value, curve});
+    }
+
+    /// Ends a gesture, the value holds until the next gesture on `param` jumps to its first point
+    pub fn end_gesture(&mut self, node: LiveId, param: LiveId) {
+        self.gestures.retain( | (n, p, _) | *n != node || *p != param);
+    }
+
+    pub fn stop(&mut self) -> AutomationLane {
+        self.start = None;
+        self.gestures.clear();
+        let mut lane = std::mem::take(&mut self.lane);
+        // a stable sort keeps the order of points recorded at the same time
+        lane.events.sort_by( | a, b | a.time.total_cmp(&b.time));
+        lane
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticks(param: &mut AutomatedParam, count: usize) -> Vec<f32> {
+        (0..count).map( | _ | param.tick()).collect()
+    }
+
+    #[test]
+    fn linear_ramp_arrives_on_time() {
+        let mut param = AutomatedParam::new(0.0, 0.005, 48000.0);
+        param.schedule(AutomationEvent::set_at(live_id!(gain), 100.0, 0.0));
+        param.schedule(AutomationEvent::linear_to(live_id!(gain), 200.0, 1.0));
+        let values = ticks(&mut param, 300);
+        assert_eq!(values[100], 0.0);
+        assert!((values[150] - 0.5).abs() < 1e-6);
+        assert_eq!(values[200], 1.0);
+        assert_eq!(values[299], 1.0);
+        assert!(!param.is_automating());
+    }
+
+    #[test]
+    fn ramp_starts_where_it_was_scheduled() {
+        let mut param = AutomatedParam::new(1.0, 0.005, 48000.0);
+        param.set_time(1000.0);
+        param.schedule(AutomationEvent::linear_to(live_id!(gain), 1100.0, 0.0));
+        let values = ticks(&mut param, 101);
+        assert!((values[50] - 0.5).abs() < 1e-6);
+        assert_eq!(values[100], 0.0);
+    }
+
+    #[test]
+    fn exponential_ramp() {
+        let mut param = AutomatedParam::new(100.0, 0.005, 48000.0);
+        param.schedule(AutomationEvent::exponential_to(live_id!(cutoff), 100.0, 10000.0));
+        let values = ticks(&mut param, 101);
+        assert!((values[50] - 1000.0).abs() < 0.1);
+        assert_eq!(values[100], 10000.0);
+        // through zero there is no exponential, it ramps linearly
+        let mut param = AutomatedParam::new(-1.0, 0.005, 48000.0);
+        param.schedule(AutomationEvent::exponential_to(live_id!(pan), 100.0, 1.0));
+        let values = ticks(&mut param, 101);
+        assert!(values[50].abs() < 1e-6);
+    }
+
+    #[test]
+    fn steps_are_smoothed() {
+        let mut param = AutomatedParam::new(0.0, 0.001, 48000.0);
+        param.schedule(AutomationEvent::set_at(live_id!(gain), 10.0, 1.0));
+        let values = ticks(&mut param, 1000);
+        assert_eq!(values[9], 0.0);
+        assert!(values[10] > 0.0 && values[10] < 0.1);
+        assert!(values.windows(2).all( | w | w[1] >= w[0]));
+        assert!((values[999] - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn events_are_kept_in_time_order() {
+        let mut param = AutomatedParam::new(0.0, 0.005, 48000.0);
+        param.schedule(AutomationEvent::linear_to(live_id!(gain), 200.0, 0.0));
+        param.schedule(AutomationEvent::linear_to(live_id!(gain), 100.0, 1.0));
+        let values = ticks(&mut param, 201);
+        assert_eq!(values[100], 1.0);
+        assert!((values[150] - 0.5).abs() < 1e-6);
+        assert_eq!(values[200], 0.0);
+    }
+
+    #[test]
+    fn events_are_routed_by_node() {
+        let event = AutomationEvent::set_at(live_id!(gain), 0.0, 1.0).on_node(live_id!(bus));
+        assert!(!event.is_for(live_id!(gain)));
+        assert!(event.for_child(live_id!(bus)).is_for(live_id!(gain)));
+        assert!(!event.for_child(live_id!(other)).is_for(live_id!(gain)));
+        assert!(!event.for_child(live_id!(bus)).is_for(live_id!(pan)));
+    }
+
+    #[test]
+    fn recorded_gestures_play_back() {
+        let mut recorder = AutomationRecorder::default();
+        recorder.record(live_id!(fx), live_id!(mix), 0.1, 0.0);
+        recorder.start(1000.0);
+        recorder.record(live_id!(fx), live_id!(mix), 0.2, 1000.0);
+        recorder.record(live_id!(fx), live_id!(mix), 0.2, 1100.0);
+        recorder.record(live_id!(fx), live_id!(mix), 0.6, 1200.0);
+        recorder.end_gesture(live_id!(fx), live_id!(mix));
+        recorder.record(live_id!(fx), live_id!(mix), 0.3, 1400.0);
+        let lane = recorder.stop();
+        assert!(!recorder.is_recording());
+        let curves: Vec<_> = lane.events.iter().map( | e | (e.time, e.value, e.curve)).collect();
+        assert_eq!(curves, vec![
+            (0.0, 0.2, AutomationCurve::Step),
+            (200.0, 0.6, AutomationCurve::Linear),
+            (400.0, 0.3, AutomationCurve::Step),
+        ]);
+        assert_eq!(lane.duration(), 400.0);
+
+        let mut param = AutomatedParam::new(0.2, 0.0, 48000.0);
+        param.set_time(5000.0);
+        for event in lane.events_at(5000.0) {
+            param.schedule(event.for_child(live_id!(fx)));
+        }
+        let values = ticks(&mut param, 401);
+        assert!((values[100] - 0.4).abs() < 1e-6);
+        assert_eq!(values[399], 0.6);
+        assert_eq!(values[400], 0.3);
+    }
+}
@@ -0,0 +1,256 @@
+use {
+    crate::{
+        makepad_platform::*,
+        register_audio_component,
+        audio_traits::*,
+        automation::AutomatedParam,
+        effects::{EffectDsp, EffectNode, effect_component},
+    },
+};
+
+live_design!{
+    BiquadEq = {{BiquadEq}} {}
+}
+
+#[derive(Live, LiveHook, Clone, Copy, Debug, PartialEq)]
+#[live_ignore]
+/// The filter shapes of the RBJ audio EQ cookbook, `gain` only affects Peak and the shelves
+pub enum BiquadType {
+    #[pick] LowPass,
+    HighPass,
+    BandPass,
+    Notch,
+    Peak,
+    LowShelf,
+    HighShelf,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BiquadParams {
+    pub filter_type: BiquadType,
+    pub frequency: f32,
+    pub q: f32,
+    pub gain: f32,
+}
+
+/// Normalized coefficients, a0 is divided out
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BiquadCoefs {
+    pub b0: f32,
+    pub b1: f32,
+    pub b2: f32,
+    pub a1: f32,
+    pub a2: f32,
+}
+
+impl BiquadCoefs {
+    pub fn new(filter_type: BiquadType, frequency: f32, q: f32, gain_db: f32, sample_rate: f32) -> Self {
+        let w0 = std::f32::consts::TAU * frequency.clamp(1.0, sample_rate * 0.49) / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q.max(0.01));
+        let a = 10.0f32.powf(gain_db / 40.0);
+        let (b0, b1, b2, a0, a1, a2) = match filter_type {
+            BiquadType::LowPass => ((1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            BiquadType::HighPass => ((1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            // constant 0 dB peak gain
+            BiquadType::BandPass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            BiquadType::Notch => (1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+            BiquadType::Peak => (1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a, 1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a),
+            BiquadType::LowShelf => {
+                let sq = 2.0 * a.sqrt() * alpha;
+                (
+                    a * ((a + 1.0) - (a - 1.0) * cos + sq),
+                    2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                    a * ((a + 1.0) - (a - 1.0) * cos - sq),
+                    (a + 1.0) + (a - 1.0) * cos + sq,
+                    -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                    (a + 1.0) + (a - 1.0) * cos - sq,
+                )
+            }
+            BiquadType::HighShelf => {
+                let sq = 2.0 * a.sqrt() * alpha;
+                (
+                    a * ((a + 1.0) + (a - 1.0) * cos + sq),
+                    -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                    a * ((a + 1.0) + (a - 1.0) * cos - sq),
+                    (a + 1.0) - (a - 1.0) * cos + sq,
+                    2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                    (a + 1.0) - (a - 1.0) * cos - sq,
+                )
+            }
+        };
+        Self {b0: b0 / a0, b1: b1 / a0, b2: b2 / a0, a1: a1 / a0, a2: a2 / a0}
+    }
+}
+
+/// One channel of filter memory in transposed direct form II
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BiquadState {
+    z1: f32,
+    z2: f32,
+}
+
+impl BiquadState {
+    pub fn process(&mut self, c: &BiquadCoefs, x: f32) -> f32 {
+        let y = c.b0 * x + self.z1;
+        self.z1 = c.b1 * x - c.a1 * y + self.z2;
+        self.z2 = c.b2 * x - c.a2 * y;
+        y
+    }
+}
+
+pub struct BiquadDsp {
+    sample_rate: f32,
+    filter_type: BiquadType,
+    frequency: AutomatedParam,
+    q: AutomatedParam,
+    gain: AutomatedParam,
+    coefs: BiquadCoefs,
+    state: [BiquadState; 2],
+}
+
+impl BiquadDsp {
+    fn update_coefs(&mut self) {
+        self.coefs = BiquadCoefs::new(self.filter_type, self.frequency.value(), self.q.value(), self.gain.value(), self.sample_rate);
+    }
+}
+
+impl EffectDsp for BiquadDsp {
+    type Params = BiquadParams;
+
+    fn new(sample_rate: f32, params: &BiquadParams) -> Self {
+        let mut dsp = Self {
+            sample_rate,
+            filter_type: params.filter_type,
+            frequency: AutomatedParam::new(params.frequency, 0.01, sample_rate),
+            q: AutomatedParam::new(params.q, 0.01, sample_rate),
+            gain: AutomatedParam::new(params.gain, 0.01, sample_rate),
+            coefs: BiquadCoefs::default(),
+            state: Default::default(),
+        };
+        dsp.update_coefs();
+        dsp
+    }
+
+    fn set_params(&mut self, params: &BiquadParams) {
+        self.frequency.set(params.frequency);
+        self.q.set(params.q);
+        self.gain.set(params.gain);
+        if self.filter_type != params.filter_type {
+            self.filter_type = params.filter_type;
+            self.update_coefs();
+        }
+    }
+
+    fn for_each_param(&mut self, f: &mut dyn FnMut(LiveId, &mut AutomatedParam)) {
+        f(live_id!(frequency), &mut self.frequency);
+        f(live_id!(q), &mut self.q);
+        f(live_id!(gain), &mut self.gain);
+    }
+
+    fn process(&mut self, left: &mut [f32], right: &mut [f32], _key: Option<(&[f32], &[f32])>) {
+        for i in 0..left.len() {
+            // the coefficients only need the trig when something moved
+            let settled = self.frequency.is_settled() && self.q.is_settled() && self.gain.is_settled();
+            self.frequency.tick();
+            self.q.tick();
+            self.gain.tick();
+            if !settled {
+                self.update_coefs();
+            }
+            left[i] = self.state[0].process(&self.coefs, left[i]);
+            right[i] = self.state[1].process(&self.coefs, right[i]);
+        }
+    }
+}
+
+/// One band of parametric EQ, chain several for a full equalizer
+#[derive(Live)]
+pub struct BiquadEq {
+    #[live] filter_type: BiquadType,
+    /// Cutoff or centre frequency in Hz
+    #[live(1000.0)] frequency: f32,
+    #[live(0.707)] q: f32,
+    /// Boost or cut in dB for Peak and the shelves
+    #[live(0.0)] gain: f32,
+    #[rust] from_ui: FromUISender<BiquadParams>,
+}
+
+impl BiquadEq {
+    fn params(&self) -> BiquadParams {
+        BiquadParams {
+            filter_type: self.filter_type,
+            frequency: self.frequency,
+            q: self.q,
+            gain: self.gain,
+        }
+    }
+}
+
+effect_component!(BiquadEq, BiquadDsp);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::{gain_to_db, test_util::*};
+
+    fn dsp(filter_type: BiquadType, frequency: f32, q: f32, gain: f32) -> BiquadDsp {
+        BiquadDsp::new(SAMPLE_RATE, &BiquadParams {filter_type, frequency, q, gain})
+    }
+
+    #[test]
+    fn lowpass_impulse_response_has_unity_dc_gain() {
+        let response = impulse_response(&mut dsp(BiquadType::LowPass, 1000.0, 0.707, 0.0), 4800);
+        let dc: f32 = response.iter().sum();
+        assert!((dc - 1.0).abs() < 1e-3, "dc gain {dc}");
+        assert!(response[4000..].iter().all( | v | v.abs() < 1e-6));
+    }
+
+    #[test]
+    fn lowpass_frequency_response() {
+        assert_db(sine_gain(&mut dsp(BiquadType::LowPass, 1000.0, 0.707, 0.0), 100.0, 0.5), 0.0, 0.1);
+        assert_db(sine_gain(&mut dsp(BiquadType::LowPass, 1000.0, 0.707, 0.0), 1000.0, 0.5), -3.0, 0.2);
+        // at least 12 dB per octave, the bilinear transform adds more towards nyquist
+        assert!(gain_to_db(sine_gain(&mut dsp(BiquadType::LowPass, 1000.0, 0.707, 0.0), 8000.0, 0.5)) < -36.0);
+    }
+
+    #[test]
+    fn highpass_frequency_response() {
+        assert_db(sine_gain(&mut dsp(BiquadType::HighPass, 1000.0, 0.707, 0.0), 8000.0, 0.5), 0.0, 0.2);
+        assert_db(sine_gain(&mut dsp(BiquadType::HighPass, 1000.0, 0.707, 0.0), 1000.0, 0.5), -3.0, 0.2);
+        assert_db(sine_gain(&mut dsp(BiquadType::HighPass, 1000.0, 0.707, 0.0), 125.0, 0.5), -36.0, 1.0);
+    }
+
+    #[test]
+    fn bandpass_and_notch_frequency_response() {
+        assert_db(sine_gain(&mut dsp(BiquadType::BandPass, 1000.0, 2.0, 0.0), 1000.0, 0.5), 0.0, 0.1);
+        assert!(sine_gain(&mut dsp(BiquadType::BandPass, 1000.0, 2.0, 0.0), 100.0, 0.5) < 0.1);
+        assert!(sine_gain(&mut dsp(BiquadType::Notch, 1000.0, 2.0, 0.0), 1000.0, 0.5) < 0.01);
+        assert_db(sine_gain(&mut dsp(BiquadType::Notch, 1000.0, 2.0, 0.0), 100.0, 0.5), 0.0, 0.1);
+    }
+
+    #[test]
+    fn peak_and_shelf_gains() {
+        assert_db(sine_gain(&mut dsp(BiquadType::Peak, 1000.0, 1.0, 6.0), 1000.0, 0.25), 6.0, 0.1);
+        assert_db(sine_gain(&mut dsp(BiquadType::Peak, 1000.0, 1.0, -6.0), 1000.0, 0.25), -6.0, 0.1);
+        assert_db(sine_gain(&mut dsp(BiquadType::Peak, 1000.0, 1.0, 6.0), 20.0, 0.25), 0.0, 0.1);
+        assert_db(sine_gain(&mut dsp(BiquadType::LowShelf, 500.0, 0.707, 6.0), 30.0, 0.25), 6.0, 0.2);
+        assert_db(sine_gain(&mut dsp(BiquadType::LowShelf, 500.0, 0.707, 6.0), 10000.0, 0.25), 0.0, 0.2);
+        assert_db(sine_gain(&mut dsp(BiquadType::HighShelf, 2000.0, 0.707, -6.0), 15000.0, 0.25), -6.0, 0.3);
+        assert_db(sine_gain(&mut dsp(BiquadType::HighShelf, 2000.0, 0.707, -6.0), 50.0, 0.25), 0.0, 0.2);
+    }
+
+    #[test]
+    fn frequency_changes_are_smoothed() {
+        let mut dsp = dsp(BiquadType::LowPass, 200.0, 0.707, 0.0);
+        dsp.set_params(&BiquadParams {filter_type: BiquadType::LowPass, frequency: 10000.0, q: 0.707, gain: 0.0});
+        let mut left = vec![0.0; 4];
+        let mut right = vec![0.0; 4];
+        dsp.process(&mut left, &mut right, None);
+        assert!(dsp.frequency.value() < 1500.0);
+        let mut left = vec![0.0; 4800];
+        let mut right = vec![0.0; 4800];
+        dsp.process(&mut left, &mut right, None);
+        assert!((dsp.frequency.value() - 10000.0).abs() < 1.0);
+    }
+}
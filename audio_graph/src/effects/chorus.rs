@@ -0,0 +1,176 @@
+use {
+    crate::{
+        makepad_platform::*,
+        register_audio_component,
+        audio_traits::*,
+        automation::AutomatedParam,
+        effects::{EffectDsp, EffectNode, effect_component},
+        effects::delay::DelayLine,
+    },
+};
+
+live_design!{
+    Chorus = {{Chorus}} {}
+}
+
+/// Taps per channel, spread evenly over the lfo cycle
+const CHORUS_VOICES: usize = 3;
+const MAX_CHORUS_DELAY: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ChorusParams {
+    pub rate: f32,
+    pub depth: f32,
+    pub delay: f32,
+    pub feedback: f32,
+    pub spread: f32,
+    pub mix: f32,
+}
+
+/// Several taps swept by one lfo per channel, the right channel runs `spread` of a cycle ahead
+pub struct ChorusDsp {
+    sample_rate: f32,
+    rate: AutomatedParam,
+    depth: AutomatedParam,
+    delay: AutomatedParam,
+    feedback: AutomatedParam,
+    spread: AutomatedParam,
+    mix: AutomatedParam,
+    phase: f32,
+    lines: [DelayLine; 2],
+}
+
+impl EffectDsp for ChorusDsp {
+    type Params = ChorusParams;
+
+    fn new(sample_rate: f32, params: &ChorusParams) -> Self {
+        let len = (MAX_CHORUS_DELAY * sample_rate) as usize + 2;
+        Self {
+            sample_rate,
+            rate: AutomatedParam::new(params.rate, 0.02, sample_rate),
+            depth: AutomatedParam::new(params.depth, 0.02, sample_rate),
+            delay: AutomatedParam::new(params.delay, 0.05, sample_rate),
+            feedback: AutomatedParam::new(params.feedback, 0.02, sample_rate),
+            spread: AutomatedParam::new(params.spread, 0.02, sample_rate),
+            mix: AutomatedParam::new(params.mix, 0.02, sample_rate),
+            phase: 0.0,
+            lines: [DelayLine::new(len), DelayLine::new(len)],
+        }
+    }
+
+    fn set_params(&mut self, params: &ChorusParams) {
+        self.rate.set(params.rate);
+        self.depth.set(params.depth);
+        self.delay.set(params.delay);
+        self.feedback.set(params.feedback);
+        self.spread.set(params.spread);
+        self.mix.set(params.mix);
+    }
+
+    fn for_each_param(&mut self, f: &mut dyn FnMut(LiveId, &mut AutomatedParam)) {
+        f(live_id!(rate), &mut self.rate);
+        f(live_id!(depth), &mut self.depth);
+        f(live_id!(delay), &mut self.delay);
+        f(live_id!(feedback), &mut self.feedback);
+        f(live_id!(spread), &mut self.spread);
+        f(live_id!(mix), &mut self.mix);
+    }
+
+    fn process(&mut self, left: &mut [f32], right: &mut [f32], _key: Option<(&[f32], &[f32])>) {
+        for i in 0..left.len() {
+            self.phase = (self.phase + self.rate.tick() / self.sample_rate).fract();
+            let depth = self.depth.tick() * self.sample_rate;
+            let delay = self.delay.tick() * self.sample_rate;
+            let feedback = self.feedback.tick().clamp(-0.95, 0.95);
+            let spread = self.spread.tick();
+            let mix = self.mix.tick();
+            let max = MAX_CHORUS_DELAY * self.sample_rate - 2.0;
+            let dry = [left[i], right[i]];
+            let mut wet = [0.0; 2];
+            for (c, line) in self.lines.iter_mut().enumerate() {
+                let phase = self.phase + spread * c as f32;
+                for v in 0..CHORUS_VOICES {
+                    let lfo = ((phase + v as f32 / CHORUS_VOICES as f32) * std::f32::consts::TAU).sin();
+                    wet[c] += line.read((delay + depth * 0.5 * (1.0 + lfo)).min(max));
+                }
+                wet[c] /= CHORUS_VOICES as f32;
+                line.write(dry[c] + wet[c] * feedback);
+            }
+            left[i] = dry[0] * (1.0 - mix) + wet[0] * mix;
+            right[i] = dry[1] * (1.0 - mix) + wet[1] * mix;
+        }
+    }
+}
+
+#[derive(Live)]
+pub struct Chorus {
+    /// Lfo speed in Hz
+    #[live(0.8)] rate: f32,
+    /// Seconds the taps sweep over
+    #[live(0.004)] depth: f32,
+    /// Seconds of the shortest tap
+    #[live(0.012)] delay: f32,
+    #[live(0.0)] feedback: f32,
+    /// Lfo phase offset of the right channel in cycles
+    #[live(0.25)] spread: f32,
+    #[live(0.5)] mix: f32,
+    #[rust] from_ui: FromUISender<ChorusParams>,
+}
+
+impl Chorus {
+    fn params(&self) -> ChorusParams {
+        ChorusParams {
+            rate: self.rate,
+            depth: self.depth,
+            delay: self.delay,
+            feedback: self.feedback,
+            spread: self.spread,
+            mix: self.mix,
+        }
+    }
+}
+
+effect_component!(Chorus, ChorusDsp);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::test_util::*;
+
+    fn params() -> ChorusParams {
+        ChorusParams {rate: 1.0, depth: 0.004, delay: 0.01, feedback: 0.0, spread: 0.25, mix: 1.0}
+    }
+
+    #[test]
+    fn without_depth_it_is_a_plain_delay() {
+        let response = impulse_response(&mut ChorusDsp::new(SAMPLE_RATE, &ChorusParams {depth: 0.0, ..params()}), 1000);
+        assert!((response[480] - 1.0).abs() < 1e-5);
+        let rest: f32 = response.iter().enumerate().filter( | (i, _) | *i != 480).map( | (_, v) | v.abs()).sum();
+        assert!(rest < 1e-5);
+    }
+
+    #[test]
+    fn taps_spread_over_the_sweep() {
+        let response = impulse_response(&mut ChorusDsp::new(SAMPLE_RATE, &params()), 1000);
+        let first = response.iter().position( | v | v.abs() > 1e-4).unwrap();
+        let last = response.iter().rposition( | v | v.abs() > 1e-4).unwrap();
+        assert!(first >= 480 && last <= 480 + 192 + 1, "{first} {last}");
+        assert!(last - first > 50);
+        let sum: f32 = response.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn modulation_moves_the_taps() {
+        let mut dsp = ChorusDsp::new(SAMPLE_RATE, &params());
+        let first = impulse_response(&mut dsp, 12000);
+        let later = impulse_response(&mut dsp, 12000);
+        assert!(first[..1000].iter().zip(&later[..1000]).any( | (a, b) | (a - b).abs() > 0.01));
+    }
+
+    #[test]
+    fn low_frequencies_pass_at_unity() {
+        assert_db(sine_gain(&mut ChorusDsp::new(SAMPLE_RATE, &params()), 30.0, 0.5), 0.0, 0.5);
+        assert_db(sine_gain(&mut ChorusDsp::new(SAMPLE_RATE, &ChorusParams {mix: 0.0, ..params()}), 3000.0, 0.5), 0.0, 0.01);
+    }
+}
@@ -0,0 +1,243 @@
+use {
+    crate::{
+        makepad_platform::*,
+        register_audio_component,
+        audio_traits::*,
+        automation::AutomatedParam,
+        effects::{EffectDsp, EffectNode, effect_component, db_to_gain, gain_to_db},
+    },
+};
+
+live_design!{
+    Compressor = {{Compressor}} {}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CompressorParams {
+    pub threshold: f32,
+    pub ratio: f32,
+    pub knee: f32,
+    pub attack: f32,
+    pub release: f32,
+    pub makeup: f32,
+    pub limit: bool,
+}
+
+/// Feed forward compressor with a stereo linked peak detector, a `key` signal replaces the input
+/// at the detector. In limit mode the ratio is infinite and the attack instant, so peaks never pass the threshold
+pub struct CompressorDsp {
+    sample_rate: f32,
+    params: CompressorParams,
+    threshold: AutomatedParam,
+    ratio: AutomatedParam,
+    makeup: AutomatedParam,
+    attack_coef: f32,
+    release_coef: f32,
+    /// Current gain reduction in dB, 0 or negative
+    reduction: f32,
+}
+
+impl CompressorDsp {
+    fn time_coef(&self, time: f32) -> f32 {
+        if time <= 0.0 {
+            return 1.0
+        }
+        1.0 - (-1.0 / (time * self.sample_rate)).exp()
+    }
+
+    fn update_times(&mut self) {
+        self.attack_coef = if self.params.limit {1.0} else {self.time_coef(self.params.attack)};
+        self.release_coef = self.time_coef(self.params.release);
+    }
+
+    pub fn gain_reduction(&self) -> f32 {
+        self.reduction
+    }
+
+    /// The static curve, returns the gain change in dB for a detector level in dB
+    fn gain_computer(&self, level: f32, threshold: f32, ratio: f32) -> f32 {
+        let slope = if self.params.limit {1.0} else {1.0 - 1.0 / ratio.max(1.0)};
+        let over = level - threshold;
+        let knee = if self.params.limit {0.0} else {self.params.knee.max(0.0)};
+        if 2.0 * over <= -knee {
+            0.0
+        }
+        else if 2.0 * over.abs() <= knee {
+            -slope * (over + knee / 2.0).powi(2) / (2.0 * knee)
+        }
+        else {
+            -slope * over
+        }
+    }
+}
+
+impl EffectDsp for CompressorDsp {
+    type Params = CompressorParams;
+
+    fn new(sample_rate: f32, params: &CompressorParams) -> Self {
+        let mut dsp = Self {
+            sample_rate,
+            params: *params,
+            threshold: AutomatedParam::new(params.threshold, 0.01, sample_rate),
+            ratio: AutomatedParam::new(params.ratio, 0.01, sample_rate),
+            makeup: AutomatedParam::new(params.makeup, 0.01, sample_rate),
+            attack_coef: 1.0,
+            release_coef: 1.0,
+            reduction: 0.0,
+        };
+        dsp.update_times();
+        dsp
+    }
+
+    fn set_params(&mut self, params: &CompressorParams) {
+        self.params = *params;
+        self.threshold.set(params.threshold);
+        self.ratio.set(params.ratio);
+        self.makeup.set(params.makeup);
+        self.update_times();
+    }
+
+    fn for_each_param(&mut self, f: &mut dyn FnMut(LiveId, &mut AutomatedParam)) {
+        f(live_id!(threshold), &mut self.threshold);
+        f(live_id!(ratio), &mut self.ratio);
+        f(live_id!(makeup), &mut self.makeup);
+    }
+
+    fn process(&mut self, left: &mut [f32], right: &mut [f32], key: Option<(&[f32], &[f32])>) {
+        for i in 0..left.len() {
+            let peak = match key {
+                Some((key_left, key_right)) => key_left[i].abs().max(key_right[i].abs()),
+                None => left[i].abs().max(right[i].abs())
+            };
+            let (threshold, ratio) = (self.threshold.tick(), self.ratio.tick());
+            let target = self.gain_computer(gain_to_db(peak), threshold, ratio);
+            let coef = if target < self.reduction {self.attack_coef} else {self.release_coef};
+            self.reduction += (target - self.reduction) * coef;
+            let gain = db_to_gain(self.reduction + self.makeup.tick());
+            left[i] *= gain;
+            right[i] *= gain;
+        }
+    }
+}
+
+#[derive(Live)]
+pub struct Compressor {
+    /// Level in dBFS above which the gain is reduced
+    #[live(-18.0)] threshold: f32,
+    #[live(4.0)] ratio: f32,
+    /// Width in dB of the soft knee around the threshold
+    #[live(6.0)] knee: f32,
+    /// Seconds to react to rising and falling levels
+    #[live(0.005)] attack: f32,
+    #[live(0.1)] release: f32,
+    /// Gain in dB added after compression
+    #[live(0.0)] makeup: f32,
+    /// Turns the compressor into a brickwall limiter at the threshold
+    #[live] limit: bool,
+    /// Adds a second input that drives the detector instead of the signal itself
+    #[live] sidechain: bool,
+    #[rust] from_ui: FromUISender<CompressorParams>,
+}
+
+impl Compressor {
+    fn input_ports(&self) -> usize {
+        if self.sidechain {2} else {1}
+    }
+
+    fn params(&self) -> CompressorParams {
+        CompressorParams {
+            threshold: self.threshold,
+            ratio: self.ratio,
+            knee: self.knee,
+            attack: self.attack,
+            release: self.release,
+            makeup: self.makeup,
+            limit: self.limit,
+        }
+    }
+}
+
+effect_component!(Compressor, CompressorDsp, input_ports);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::test_util::*;
+
+    fn params() -> CompressorParams {
+        CompressorParams {threshold: -20.0, ratio: 4.0, knee: 0.0, attack: 0.001, release: 0.05, makeup: 0.0, limit: false}
+    }
+
+    #[test]
+    fn below_threshold_passes_unchanged() {
+        let mut dsp = CompressorDsp::new(SAMPLE_RATE, &params());
+        // -26 dBFS peak
+        assert_db(sine_gain(&mut dsp, 1000.0, 0.05), 0.0, 0.05);
+        let mut dsp = CompressorDsp::new(SAMPLE_RATE, &params());
+        let mut left = vec![0.0; 16];
+        left[0] = 0.05;
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        assert_eq!(left[0], 0.05);
+    }
+
+    #[test]
+    fn above_threshold_follows_the_ratio() {
+        let mut dsp = CompressorDsp::new(SAMPLE_RATE, &CompressorParams {attack: 0.0, release: 0.0, ..params()});
+        // a constant level makes the peak detector exact, 0 dBFS is 20 dB over, 4:1 leaves 5 dB
+        let mut left = vec![1.0; 4800];
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        assert_db(left[4799], -15.0, 0.01);
+        assert!((dsp.gain_reduction() + 15.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn soft_knee_is_continuous() {
+        let dsp = CompressorDsp::new(SAMPLE_RATE, &CompressorParams {knee: 10.0, ..params()});
+        assert_eq!(dsp.gain_computer(-25.0, -20.0, 4.0), 0.0);
+        assert!((dsp.gain_computer(-15.0, -20.0, 4.0) + 3.75).abs() < 1e-4);
+        let at_threshold = dsp.gain_computer(-20.0, -20.0, 4.0);
+        assert!(at_threshold < 0.0 && at_threshold > -3.75);
+    }
+
+    #[test]
+    fn attack_and_release_times() {
+        let mut dsp = CompressorDsp::new(SAMPLE_RATE, &CompressorParams {attack: 0.01, release: 0.1, ..params()});
+        let mut left = vec![1.0; 480];
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        // one time constant in, about two thirds of the 15 dB are reached
+        assert!((dsp.gain_reduction() + 15.0 * (1.0 - (-1.0f32).exp())).abs() < 0.2);
+        let mut left = vec![1.0; 48000];
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        let mut left = vec![0.0; 4800];
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        assert!((dsp.gain_reduction() + 15.0 * (-1.0f32).exp()).abs() < 0.2);
+    }
+
+    #[test]
+    fn limiter_holds_the_ceiling() {
+        let mut dsp = CompressorDsp::new(SAMPLE_RATE, &CompressorParams {threshold: -6.0, limit: true, ..params()});
+        let mut left: Vec<f32> = (0..48000).map( | i | 2.0 * (i as f32 * 0.05).sin()).collect();
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        let ceiling = db_to_gain(-6.0) * 1.0001;
+        assert!(left.iter().all( | v | v.abs() <= ceiling));
+        let mut dsp = CompressorDsp::new(SAMPLE_RATE, &CompressorParams {threshold: -6.0, limit: true, ..params()});
+        let response = impulse_response(&mut dsp, 4);
+        assert!((response[0] - db_to_gain(-6.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sidechain_drives_the_gain() {
+        let mut dsp = CompressorDsp::new(SAMPLE_RATE, &CompressorParams {attack: 0.0, release: 0.0, ..params()});
+        let key = vec![1.0; 64];
+        let mut left = vec![0.01; 64];
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, Some((&key, &key)));
+        assert!((left[63] - 0.01 * db_to_gain(-15.0)).abs() < 1e-5);
+    }
+}
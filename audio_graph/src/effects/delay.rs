@@ -0,0 +1,210 @@
+use {
+    crate::{
+        makepad_platform::*,
+        register_audio_component,
+        audio_traits::*,
+        automation::AutomatedParam,
+        effects::{EffectDsp, EffectNode, effect_component},
+    },
+};
+
+live_design!{
+    Delay = {{Delay}} {}
+}
+
+/// The longest delay time in seconds
+pub const MAX_DELAY: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct DelayParams {
+    pub time: f32,
+    pub feedback: f32,
+    pub damping: f32,
+    pub mix: f32,
+    pub ping_pong: bool,
+}
+
+/// A ring buffer read at a fractional distance behind the write position
+pub struct DelayLine {
+    buffer: Vec<f32>,
+    pos: usize,
+}
+
+impl DelayLine {
+    pub fn new(len: usize) -> Self {
+        Self {buffer: vec![0.0; len.max(2)], pos: 0}
+    }
+
+    pub fn write(&mut self, value: f32) {
+        self.buffer[self.pos] = value;
+        self.pos = (self.pos + 1) % self.buffer.len();
+    }
+
+    /// Reads `delay` samples ago with linear interpolation, 1 is the last written sample
+    pub fn read(&self, delay: f32) -> f32 {
+        let len = self.buffer.len();
+        let delay = delay.clamp(1.0, (len - 1) as f32);
+        let whole = delay as usize;
+        let fract = delay - whole as f32;
+        let a = self.buffer[(self.pos + len - whole) % len];
+        let b = self.buffer[(self.pos + len - whole - 1) % len];
+        a + (b - a) * fract
+    }
+}
+
+pub struct DelayDsp {
+    sample_rate: f32,
+    ping_pong: bool,
+    time: AutomatedParam,
+    feedback: AutomatedParam,
+    damping: AutomatedParam,
+    mix: AutomatedParam,
+    lines: [DelayLine; 2],
+    lowpass: [f32; 2],
+}
+
+impl EffectDsp for DelayDsp {
+    type Params = DelayParams;
+
+    fn new(sample_rate: f32, params: &DelayParams) -> Self {
+        let len = (MAX_DELAY * sample_rate) as usize + 2;
+        Self {
+            sample_rate,
+            ping_pong: params.ping_pong,
+            // a slower glide on the time gives the tape style pitch bend instead of zipper noise
+            time: AutomatedParam::new(params.time, 0.05, sample_rate),
+            feedback: AutomatedParam::new(params.feedback, 0.01, sample_rate),
+            damping: AutomatedParam::new(params.damping, 0.01, sample_rate),
+            mix: AutomatedParam::new(params.mix, 0.01, sample_rate),
+            lines: [DelayLine::new(len), DelayLine::new(len)],
+            lowpass: [0.0; 2],
+        }
+    }
+
+    fn set_params(&mut self, params: &DelayParams) {
+        self.ping_pong = params.ping_pong;
+        self.time.set(params.time.clamp(0.0, MAX_DELAY));
+        self.feedback.set(params.feedback);
+        self.damping.set(params.damping);
+        self.mix.set(params.mix);
+    }
+
+    fn for_each_param(&mut self, f: &mut dyn FnMut(LiveId, &mut AutomatedParam)) {
+        f(live_id!(time), &mut self.time);
+        f(live_id!(feedback), &mut self.feedback);
+        f(live_id!(damping), &mut self.damping);
+        f(live_id!(mix), &mut self.mix);
+    }
+
+    fn process(&mut self, left: &mut [f32], right: &mut [f32], _key: Option<(&[f32], &[f32])>) {
+        for i in 0..left.len() {
+            let delay = self.time.tick() * self.sample_rate;
+            let feedback = self.feedback.tick().clamp(-0.99, 0.99);
+            let damping = self.damping.tick().clamp(0.0, 0.99);
+            let mix = self.mix.tick();
+            let wet = [self.lines[0].read(delay), self.lines[1].read(delay)];
+            for (lowpass, wet) in self.lowpass.iter_mut().zip(wet) {
+                *lowpass += (wet - *lowpass) * (1.0 - damping);
+            }
+            let (dry_left, dry_right) = (left[i], right[i]);
+            if self.ping_pong {
+                // the input starts on the left and every repeat swaps sides
+                self.lines[0].write((dry_left + dry_right) * 0.5 + self.lowpass[1] * feedback);
+                self.lines[1].write(self.lowpass[0] * feedback);
+            }
+            else {
+                self.lines[0].write(dry_left + self.lowpass[0] * feedback);
+                self.lines[1].write(dry_right + self.lowpass[1] * feedback);
+            }
+            left[i] = dry_left * (1.0 - mix) + wet[0] * mix;
+            right[i] = dry_right * (1.0 - mix) + wet[1] * mix;
+        }
+    }
+}
+
+/// Echo with damped feedback and an optional ping pong between the channels
+#[derive(Live)]
+pub struct Delay {
+    /// Seconds between repeats, up to MAX_DELAY
+    #[live(0.25)] time: f32,
+    #[live(0.4)] feedback: f32,
+    /// 0 keeps repeats bright, towards 1 each repeat gets darker
+    #[live(0.2)] damping: f32,
+    #[live(0.3)] mix: f32,
+    #[live] ping_pong: bool,
+    #[rust] from_ui: FromUISender<DelayParams>,
+}
+
+impl Delay {
+    fn params(&self) -> DelayParams {
+        DelayParams {
+            time: self.time,
+            feedback: self.feedback,
+            damping: self.damping,
+            mix: self.mix,
+            ping_pong: self.ping_pong,
+        }
+    }
+}
+
+effect_component!(Delay, DelayDsp);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::test_util::*;
+
+    fn params() -> DelayParams {
+        // a power of two fraction of a second lands the taps on whole samples
+        DelayParams {time: 0.015625, feedback: 0.5, damping: 0.0, mix: 0.5, ping_pong: false}
+    }
+
+    #[test]
+    fn impulse_repeats_and_decays() {
+        let response = impulse_response(&mut DelayDsp::new(SAMPLE_RATE, &params()), 3000);
+        assert!((response[0] - 0.5).abs() < 1e-6);
+        assert!((response[750] - 0.5).abs() < 1e-6);
+        assert!((response[1500] - 0.25).abs() < 1e-6);
+        assert!((response[2250] - 0.125).abs() < 1e-6);
+        let energy: f32 = response.iter().enumerate()
+            .filter( | (i, _) | ![0, 750, 1500, 2250].contains(i))
+            .map( | (_, v) | v * v).sum();
+        assert!(energy < 1e-10);
+    }
+
+    #[test]
+    fn damping_darkens_the_repeats() {
+        let bright = DelayDsp::new(SAMPLE_RATE, &params());
+        let dark = DelayDsp::new(SAMPLE_RATE, &DelayParams {damping: 0.8, ..params()});
+        let bright_gain = sine_gain(&mut {bright}, 8000.0, 0.5);
+        let dark_gain = sine_gain(&mut {dark}, 8000.0, 0.5);
+        assert!(dark_gain < bright_gain * 0.8);
+        // low frequencies keep their level through the damping
+        let dark_low = sine_gain(&mut DelayDsp::new(SAMPLE_RATE, &DelayParams {damping: 0.8, ..params()}), 50.0, 0.5);
+        let bright_low = sine_gain(&mut DelayDsp::new(SAMPLE_RATE, &params()), 50.0, 0.5);
+        assert!((dark_low - bright_low).abs() < 0.05);
+    }
+
+    #[test]
+    fn ping_pong_alternates_sides() {
+        let mut dsp = DelayDsp::new(SAMPLE_RATE, &DelayParams {ping_pong: true, mix: 1.0, feedback: 0.5, ..params()});
+        let mut left = vec![0.0; 2300];
+        let mut right = vec![0.0; 2300];
+        left[0] = 1.0;
+        right[0] = 1.0;
+        dsp.process(&mut left, &mut right, None);
+        assert!((left[750] - 1.0).abs() < 1e-6 && right[750] == 0.0);
+        assert!((right[1500] - 0.5).abs() < 1e-6 && left[1500] == 0.0);
+        assert!((left[2250] - 0.25).abs() < 1e-6 && right[2250] == 0.0);
+    }
+
+    #[test]
+    fn time_changes_glide() {
+        let mut dsp = DelayDsp::new(SAMPLE_RATE, &params());
+        dsp.set_params(&DelayParams {time: 0.5, ..params()});
+        let mut left = vec![0.0; 48];
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        assert!(dsp.time.value() > 0.015625 && dsp.time.value() < 0.1);
+    }
+}
@@ -0,0 +1,199 @@
+use {
+    crate::{
+        makepad_platform::*,
+        register_audio_component,
+        audio_traits::*,
+        automation::AutomatedParam,
+        effects::{EffectDsp, EffectNode, effect_component, db_to_gain},
+    },
+};
+
+live_design!{
+    Distortion = {{Distortion}} {}
+}
+
+#[derive(Live, LiveHook, Clone, Copy, Debug, PartialEq)]
+#[live_ignore]
+/// SoftClip saturates through tanh, HardClip cuts at full scale and Foldback mirrors
+/// everything over full scale back down
+pub enum DistortionShape {
+    #[pick] SoftClip,
+    HardClip,
+    Foldback,
+}
+
+impl DistortionShape {
+    pub fn shape(&self, x: f32) -> f32 {
+        match self {
+            Self::SoftClip => x.tanh(),
+            Self::HardClip => x.clamp(-1.0, 1.0),
+            Self::Foldback => {
+                // a triangle wave of the input, linear between -1 and 1
+                let t = (x + 1.0).rem_euclid(4.0);
+                if t < 2.0 {t - 1.0} else {3.0 - t}
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DistortionParams {
+    pub shape: DistortionShape,
+    pub drive: f32,
+    pub tone: f32,
+    pub output: f32,
+    pub mix: f32,
+}
+
+pub struct DistortionDsp {
+    sample_rate: f32,
+    shape: DistortionShape,
+    drive: AutomatedParam,
+    tone: AutomatedParam,
+    output: AutomatedParam,
+    mix: AutomatedParam,
+    lowpass: [f32; 2],
+}
+
+impl EffectDsp for DistortionDsp {
+    type Params = DistortionParams;
+
+    fn new(sample_rate: f32, params: &DistortionParams) -> Self {
+        Self {
+            sample_rate,
+            shape: params.shape,
+            drive: AutomatedParam::new(params.drive, 0.01, sample_rate),
+            tone: AutomatedParam::new(params.tone, 0.01, sample_rate),
+            output: AutomatedParam::new(params.output, 0.01, sample_rate),
+            mix: AutomatedParam::new(params.mix, 0.01, sample_rate),
+            lowpass: [0.0; 2],
+        }
+    }
+
+    fn set_params(&mut self, params: &DistortionParams) {
+        self.shape = params.shape;
+        self.drive.set(params.drive);
+        self.tone.set(params.tone);
+        self.output.set(params.output);
+        self.mix.set(params.mix);
+    }
+
+    fn for_each_param(&mut self, f: &mut dyn FnMut(LiveId, &mut AutomatedParam)) {
+        f(live_id!(drive), &mut self.drive);
+        f(live_id!(tone), &mut self.tone);
+        f(live_id!(output), &mut self.output);
+        f(live_id!(mix), &mut self.mix);
+    }
+
+    fn process(&mut self, left: &mut [f32], right: &mut [f32], _key: Option<(&[f32], &[f32])>) {
+        for i in 0..left.len() {
+            let drive = db_to_gain(self.drive.tick());
+            let tone = self.tone.tick().clamp(10.0, self.sample_rate * 0.49);
+            let coef = 1.0 - (-std::f32::consts::TAU * tone / self.sample_rate).exp();
+            let output = db_to_gain(self.output.tick());
+            let mix = self.mix.tick();
+            for (c, s) in [&mut left[i], &mut right[i]].into_iter().enumerate() {
+                let wet = self.shape.shape(*s * drive);
+                // the tone lowpass takes the fizz off the new harmonics
+                self.lowpass[c] += (wet - self.lowpass[c]) * coef;
+                *s = (*s * (1.0 - mix) + self.lowpass[c] * mix) * output;
+            }
+        }
+    }
+}
+
+#[derive(Live)]
+pub struct Distortion {
+    #[live] shape: DistortionShape,
+    /// Gain in dB in front of the shaper
+    #[live(12.0)] drive: f32,
+    /// Cutoff in Hz of the lowpass after the shaper
+    #[live(8000.0)] tone: f32,
+    /// Gain in dB after the mix
+    #[live(-6.0)] output: f32,
+    #[live(1.0)] mix: f32,
+    #[rust] from_ui: FromUISender<DistortionParams>,
+}
+
+impl Distortion {
+    fn params(&self) -> DistortionParams {
+        DistortionParams {
+            shape: self.shape,
+            drive: self.drive,
+            tone: self.tone,
+            output: self.output,
+            mix: self.mix,
+        }
+    }
+}
+
+effect_component!(Distortion, DistortionDsp);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::test_util::*;
+
+    fn params(shape: DistortionShape, drive: f32) -> DistortionParams {
+        DistortionParams {shape, drive, tone: 20000.0, output: 0.0, mix: 1.0}
+    }
+
+    /// Amplitude of one frequency in the second half of the signal
+    fn harmonic(samples: &[f32], freq: f32) -> f32 {
+        let tail = &samples[samples.len() / 2..];
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for (i, s) in tail.iter().enumerate() {
+            let w = std::f64::consts::TAU * freq as f64 * i as f64 / SAMPLE_RATE as f64;
+            re += *s as f64 * w.cos();
+            im += *s as f64 * w.sin();
+        }
+        (2.0 * (re * re + im * im).sqrt() / tail.len() as f64) as f32
+    }
+
+    fn distort(params: &DistortionParams, freq: f32, amplitude: f32) -> Vec<f32> {
+        let mut dsp = DistortionDsp::new(SAMPLE_RATE, params);
+        let mut left: Vec<f32> = (0..48000).map( | i | amplitude * (i as f32 * freq * std::f32::consts::TAU / SAMPLE_RATE).sin()).collect();
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        left
+    }
+
+    #[test]
+    fn quiet_signals_stay_linear() {
+        let response = impulse_response(&mut DistortionDsp::new(SAMPLE_RATE, &params(DistortionShape::SoftClip, 0.0)), 4800);
+        let dc: f32 = response.iter().sum();
+        assert!((dc - 0.76).abs() < 0.01, "{dc}");
+        assert_db(sine_gain(&mut DistortionDsp::new(SAMPLE_RATE, &params(DistortionShape::SoftClip, 0.0)), 100.0, 0.01), 0.0, 0.05);
+        assert_db(sine_gain(&mut DistortionDsp::new(SAMPLE_RATE, &params(DistortionShape::HardClip, 6.0)), 100.0, 0.1), 6.0, 0.05);
+    }
+
+    #[test]
+    fn drive_adds_odd_harmonics() {
+        for shape in [DistortionShape::SoftClip, DistortionShape::HardClip, DistortionShape::Foldback] {
+            let out = distort(&params(shape, 12.0), 200.0, 0.9);
+            let fundamental = harmonic(&out, 200.0);
+            assert!(harmonic(&out, 600.0) > fundamental * 0.05, "{shape:?}");
+            // symmetric shapers leave the even harmonics alone
+            assert!(harmonic(&out, 400.0) < fundamental * 1e-3, "{shape:?}");
+        }
+    }
+
+    #[test]
+    fn output_stays_bounded() {
+        for shape in [DistortionShape::SoftClip, DistortionShape::HardClip, DistortionShape::Foldback] {
+            let out = distort(&params(shape, 40.0), 200.0, 1.0);
+            assert!(out.iter().all( | v | v.abs() <= 1.0001), "{shape:?}");
+        }
+        assert_eq!(DistortionShape::Foldback.shape(1.5), 0.5);
+        assert_eq!(DistortionShape::Foldback.shape(-2.5), 0.5);
+    }
+
+    #[test]
+    fn tone_rolls_off_the_top() {
+        let bright = DistortionParams {drive: 0.0, ..params(DistortionShape::SoftClip, 0.0)};
+        let dark = DistortionParams {tone: 1000.0, ..bright};
+        assert_db(sine_gain(&mut DistortionDsp::new(SAMPLE_RATE, &dark), 1000.0, 0.01), -3.0, 0.5);
+        assert!(sine_gain(&mut DistortionDsp::new(SAMPLE_RATE, &dark), 8000.0, 0.01) < 0.2);
+        assert_db(sine_gain(&mut DistortionDsp::new(SAMPLE_RATE, &bright), 8000.0, 0.01), 0.0, 0.5);
+    }
+}
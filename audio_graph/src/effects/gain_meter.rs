@@ -0,0 +1,246 @@
+use {
+    crate::{
+        makepad_platform::*,
+        register_audio_component,
+        audio_traits::*,
+        automation::AutomatedParam,
+        effects::{EffectDsp, EffectNode, db_to_gain, gain_to_db},
+    },
+};
+
+live_design!{
+    GainMeter = {{GainMeter}} {}
+}
+
+/// Linear peak and rms levels of the left and right channel over one metering interval
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MeterLevels {
+    pub peak: [f32; 2],
+    pub rms: [f32; 2],
+}
+
+impl MeterLevels {
+    pub fn peak_db(&self, channel: usize) -> f32 {
+        gain_to_db(self.peak[channel])
+    }
+
+    pub fn rms_db(&self, channel: usize) -> f32 {
+        gain_to_db(self.rms[channel])
+    }
+}
+
+/// Collects peak and rms over windows of `interval` seconds, independent of the block size
+pub struct LevelMeter {
+    window: usize,
+    count: usize,
+    peak: [f32; 2],
+    sum: [f64; 2],
+}
+
+impl LevelMeter {
+    pub fn new(sample_rate: f32, interval: f32) -> Self {
+        Self {
+            window: ((sample_rate * interval) as usize).max(1),
+            count: 0,
+            peak: [0.0; 2],
+            sum: [0.0; 2],
+        }
+    }
+
+    /// Calls `levels` for every window that completes inside this block
+    pub fn process(&mut self, left: &[f32], right: &[f32], mut levels: impl FnMut(MeterLevels)) {
+        for i in 0..left.len() {
+            for (c, s) in [left[i], right[i]].into_iter().enumerate() {
+                self.peak[c] = self.peak[c].max(s.abs());
+                self.sum[c] += (s * s) as f64;
+            }
+            self.count += 1;
+            if self.count == self.window {
+                levels(MeterLevels {
+                    peak: self.peak,
+                    rms: [
+                        (self.sum[0] / self.count as f64).sqrt() as f32,
+                        (self.sum[1] / self.count as f64).sqrt() as f32,
+                    ],
+                });
+                self.count = 0;
+                self.peak = [0.0; 2];
+                self.sum = [0.0; 2];
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GainMeterParams {
+    pub gain: f32,
+}
+
+pub struct GainMeterDsp {
+    /// In dB like the live value, so automation ramps in dB
+    gain: AutomatedParam,
+    gain_db: f32,
+    gain_linear: f32,
+    meter: LevelMeter,
+    pub to_ui: Option<ToUISender<MeterLevels>>,
+    /// Read back by tests that run without a UI
+    pub last_levels: MeterLevels,
+}
+
+impl EffectDsp for GainMeterDsp {
+    type Params = GainMeterParams;
+
+    fn new(sample_rate: f32, params: &GainMeterParams) -> Self {
+        Self {
+            gain: AutomatedParam::new(params.gain, 0.02, sample_rate),
+            gain_db: params.gain,
+            gain_linear: db_to_gain(params.gain),
+            meter: LevelMeter::new(sample_rate, 0.05),
+            to_ui: None,
+            last_levels: MeterLevels::default(),
+        }
+    }
+
+    fn set_params(&mut self, params: &GainMeterParams) {
+        self.gain.set(params.gain);
+    }
+
+    fn for_each_param(&mut self, f: &mut dyn FnMut(LiveId, &mut AutomatedParam)) {
+        f(live_id!(gain), &mut self.gain);
+    }
+
+    fn process(&mut self, left: &mut [f32], right: &mut [f32], _key: Option<(&[f32], &[f32])>) {
+        for i in 0..left.len() {
+            let gain_db = self.gain.tick();
+            if gain_db != self.gain_db {
+                self.gain_db = gain_db;
+                self.gain_linear = db_to_gain(gain_db);
+            }
+            left[i] *= self.gain_linear;
+            right[i] *= self.gain_linear;
+        }
+        let to_ui = &self.to_ui;
+        let last_levels = &mut self.last_levels;
+        self.meter.process(left, right, | levels | {
+            *last_levels = levels;
+            if let Some(to_ui) = to_ui {
+                let _ = to_ui.send(levels);
+            }
+        });
+    }
+}
+
+/// A gain stage that reports the peak and rms of its output to the UI
+#[derive(Live)]
+pub struct GainMeter {
+    /// Gain in dB
+    #[live(0.0)] gain: f32,
+    /// Seconds per meter reading
+    #[live(0.05)] interval: f32,
+    #[rust] levels: MeterLevels,
+    #[rust] to_ui: ToUIReceiver<MeterLevels>,
+    #[rust] from_ui: FromUISender<GainMeterParams>,
+}
+
+impl GainMeter {
+    fn params(&self) -> GainMeterParams {
+        GainMeterParams {gain: self.gain}
+    }
+
+    /// The latest reading, updated as the events of the audio thread come in
+    pub fn levels(&self) -> MeterLevels {
+        self.levels
+    }
+}
+
+impl LiveRegister for GainMeter {
+    fn live_register(cx: &mut Cx) {
+        register_audio_component!(cx, GainMeter)
+    }
+}
+
+impl LiveHook for GainMeter {
+    fn after_apply(&mut self, _cx: &mut Cx, _apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        let _ = self.from_ui.send(self.params());
+    }
+}
+
+impl AudioComponent for GainMeter {
+    fn get_graph_node(&mut self, _cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
+        self.from_ui.new_channel();
+        let interval = self.interval;
+        let to_ui = self.to_ui.sender();
+        let build = move | sample_rate, params: &GainMeterParams | {
+            let mut dsp = GainMeterDsp::new(sample_rate, params);
+            dsp.meter = LevelMeter::new(sample_rate, interval);
+            dsp.to_ui = Some(to_ui.clone());
+            dsp
+        };
+        Box::new(EffectNode::new(Box::new(build), self.params(), self.from_ui.receiver(), 1))
+    }
+
+    fn handle_event_with(&mut self, _cx: &mut Cx, _event: &Event, _dispatch_action: &mut dyn FnMut(&mut Cx, AudioComponentAction)) {
+        while let Ok(levels) = self.to_ui.try_recv() {
+            self.levels = levels;
+        }
+    }
+
+    fn audio_query(&mut self, _query: &AudioQuery, _callback: &mut Option<AudioQueryCb>) -> AudioResult<'_> {
+        AudioResult::not_found()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::test_util::*;
+
+    #[test]
+    fn sine_levels() {
+        let mut dsp = GainMeterDsp::new(SAMPLE_RATE, &GainMeterParams {gain: 0.0});
+        // 1 kHz fits the 50 ms window exactly
+        let mut left: Vec<f32> = (0..4800).map( | i | (i as f32 * 1000.0 * std::f32::consts::TAU / SAMPLE_RATE).sin()).collect();
+        let mut right: Vec<f32> = left.iter().map( | v | v * 0.5).collect();
+        dsp.process(&mut left, &mut right, None);
+        let levels = dsp.last_levels;
+        assert!((levels.peak_db(0) - 0.0).abs() < 0.01);
+        assert!((levels.rms_db(0) + 3.01).abs() < 0.01);
+        assert!((levels.peak_db(1) + 6.02).abs() < 0.01);
+        assert!((levels.rms_db(1) + 9.03).abs() < 0.01);
+    }
+
+    #[test]
+    fn impulse_levels_and_gain() {
+        let mut dsp = GainMeterDsp::new(SAMPLE_RATE, &GainMeterParams {gain: -6.0});
+        let response = impulse_response(&mut dsp, 2400);
+        assert!((response[0] - db_to_gain(-6.0)).abs() < 1e-6);
+        assert!((dsp.last_levels.peak[0] - db_to_gain(-6.0)).abs() < 1e-6);
+        assert!((dsp.last_levels.rms[0] - db_to_gain(-6.0) / 2400f32.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn readings_arrive_per_interval() {
+        let mut meter = LevelMeter::new(SAMPLE_RATE, 0.01);
+        let mut count = 0;
+        let block = vec![0.5; 100];
+        for _ in 0..48 {
+            meter.process(&block, &block, | levels | {
+                assert_eq!(levels.peak, [0.5, 0.5]);
+                count += 1;
+            });
+        }
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    fn gain_changes_are_smoothed() {
+        let mut dsp = GainMeterDsp::new(SAMPLE_RATE, &GainMeterParams {gain: 0.0});
+        dsp.set_params(&GainMeterParams {gain: -60.0});
+        let mut left = vec![1.0; 4800];
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        assert!(left[0] > 0.9);
+        assert!(left.windows(2).all( | w | (w[0] - w[1]).abs() < 0.01));
+        assert!(left[4799] < 0.01);
+    }
+}
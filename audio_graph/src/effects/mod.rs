@@ -0,0 +1,293 @@
+use {
+    crate::{
+        makepad_platform::*,
+        audio_traits::*,
+        automation::*,
+    },
+};
+
+pub mod biquad;
+pub mod compressor;
+pub mod delay;
+pub mod reverb;
+pub mod chorus;
+pub mod distortion;
+pub mod gain_meter;
+
+pub fn live_design(cx: &mut Cx) {
+    self::biquad::live_design(cx);
+    self::compressor::live_design(cx);
+    self::delay::live_design(cx);
+    self::reverb::live_design(cx);
+    self::chorus::live_design(cx);
+    self::distortion::live_design(cx);
+    self::gain_meter::live_design(cx);
+}
+
+pub fn db_to_gain(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
+
+pub fn gain_to_db(gain: f32) -> f32 {
+    20.0 * gain.max(1e-10).log10()
+}
+
+/// The signal processing of an effect, kept apart from the live component so it can be tested on its own
+pub trait EffectDsp: Send + 'static {
+    type Params: Copy + Send + 'static;
+    fn new(sample_rate: f32, params: &Self::Params) -> Self;
+    /// New targets, the dsp smooths its way there
+    fn set_params(&mut self, params: &Self::Params);
+    /// Every automatable parameter with the id it is automated by, they all tick once per frame
+    fn for_each_param(&mut self, f: &mut dyn FnMut(LiveId, &mut AutomatedParam));
+    /// Processes one stereo block in place, `key` is the second input when the node has one
+    fn process(&mut self, left: &mut [f32], right: &mut [f32], key: Option<(&[f32], &[f32])>);
+}
+
+/// Builds the dsp of a node for the sample rate of the device it runs on
+pub type EffectBuilder<D> = Box<dyn FnMut(f32, &<D as EffectDsp>::Params) -> D + Send>;
+
+pub struct EffectNode<D: EffectDsp> {
+    pub from_ui: FromUIReceiver<D::Params>,
+    /// Built on the first block, when the sample rate of the device is known
+    pub dsp: Option<D>,
+    pub build: EffectBuilder<D>,
+    /// The latest params, a rebuilt dsp starts from these
+    pub params: D::Params,
+    /// The rate the dsp was built for
+    pub sample_rate: f64,
+    pub input_ports: usize,
+    /// Stands in for the right channel of a mono output
+    pub scratch: Vec<f32>,
+    /// The sidechain input padded to the block, or silence when it has no channels
+    pub key: Vec<f32>,
+    /// The graph clock at the next block, when the device doesn't give one
+    pub clock: f64,
+}
+
+impl<D: EffectDsp> EffectNode<D> {
+    pub fn new(build: EffectBuilder<D>, params: D::Params, from_ui: FromUIReceiver<D::Params>, input_ports: usize) -> Self {
+        Self {
+            from_ui,
+            dsp: None,
+            build,
+            params,
+            sample_rate: 0.0,
+            input_ports,
+            scratch: Vec::new(),
+            key: Vec::new(),
+            clock: 0.0
+        }
+    }
+}
+
+impl<D: EffectDsp> AudioGraphNode for EffectNode<D> {
+    fn handle_midi_data(&mut self, _data: MidiData) {}
+    fn all_notes_off(&mut self) {}
+
+    fn handle_automation(&mut self, event: AutomationEvent) {
+        if let Some(dsp) = &mut self.dsp {
+            dsp.for_each_param(&mut | id, param | if event.is_for(id) {
+                param.schedule(event)
+            });
+        }
+    }
+
+    fn input_port_count(&self) -> usize {
+        self.input_ports
+    }
+
+    fn render_to_audio_buffer(&mut self, info: AudioInfo, outputs: &mut [&mut AudioBuffer], inputs: &[&AudioBuffer], _display: &mut DisplayAudioGraph) {
+        while let Ok(params) = self.from_ui.try_recv() {
+            self.params = params;
+            if let Some(dsp) = &mut self.dsp {
+                dsp.set_params(&params);
+            }
+        }
+        if self.dsp.is_none() || info.sample_rate != self.sample_rate {
+            self.sample_rate = info.sample_rate;
+            self.dsp = Some((self.build)(info.sample_rate as f32, &self.params));
+        }
+        let dsp = self.dsp.as_mut().unwrap();
+        let output = &mut outputs[0];
+        let frame_count = output.frame_count();
+        let time = block_time(&info, &mut self.clock, frame_count);
+        dsp.for_each_param(&mut | _, param | param.set_time(time));
+        if let Some(input) = inputs.first().filter( | input | input.channel_count() > 0) {
+            let frames = frame_count.min(input.frame_count());
+            for c in 0..output.channel_count() {
+                let from = input.channel(c.min(input.channel_count() - 1));
+                let to = output.channel_mut(c);
+                to[0..frames].copy_from_slice(&from[0..frames]);
+                to[frames..].fill(0.0);
+            }
+        }
+        let key = if let Some(key) = inputs.get(1).filter( | _ | self.input_ports > 1) {
+            self.key.clear();
+            self.key.resize(frame_count * 2, 0.0);
+            if key.channel_count() > 0 {
+                let frames = frame_count.min(key.frame_count());
+                for c in 0..2 {
+                    let from = key.channel(c.min(key.channel_count().saturating_sub(1)));
+                    self.key[c * frame_count..c * frame_count + frames].copy_from_slice(&from[0..frames]);
+                }
+            }
+            let (left, right) = self.key.split_at(frame_count);
+            Some((left, right))
+        }
+        else {
+            None
+        };
+        if output.channel_count() >= 2 {
+            let (left, right) = output.stereo_mut();
+            dsp.process(left, right, key);
+        }
+        else {
+            self.scratch.clear();
+            self.scratch.extend_from_slice(output.channel(0));
+            dsp.process(output.channel_mut(0), &mut self.scratch, key);
+        }
+    }
+}
+
+/// Implements the live and audio component traits of an effect whose dsp takes its params from `params()`,
+/// the optional third argument names a method returning the number of input ports
+macro_rules! effect_component {
+    (@input_ports $this:ident) => {1};
+    (@input_ports $this:ident, $input_ports:ident) => {$this.$input_ports()};
+    ($ty:ident, $dsp:ident $(, $input_ports:ident)?) => {
+        impl LiveRegister for $ty {
+            fn live_register(cx: &mut Cx) {
+                register_audio_component!(cx, $ty)
+            }
+        }
+
+        impl LiveHook for $ty {
+            fn after_apply(&mut self, _cx: &mut Cx, _apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+                // applies from the UI (apply_over) move the running node to the new values
+                let _ = self.from_ui.send(self.params());
+            }
+        }
+
+        impl AudioComponent for $ty {
+            fn get_graph_node(&mut self, _cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
+                self.from_ui.new_channel();
+                Box::new(EffectNode::new(
+                    Box::new($dsp::new),
+                    self.params(),
+                    self.from_ui.receiver(),
+                    effect_component!(@input_ports self $(, $input_ports)?),
+                ))
+            }
+
+            fn handle_event_with(&mut self, _cx: &mut Cx, _event: &Event, _dispatch_action: &mut dyn FnMut(&mut Cx, AudioComponentAction)) {
+            }
+
+            fn audio_query(&mut self, _query: &AudioQuery, _callback: &mut Option<AudioQueryCb>) -> AudioResult<'_> {
+                AudioResult::not_found()
+            }
+        }
+    }
+}
+pub(crate) use effect_component;
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use super::*;
+
+    pub const SAMPLE_RATE: f32 = 48000.0;
+
+    /// Runs a mono impulse through both channels and returns the left response
+    pub fn impulse_response<D: EffectDsp>(dsp: &mut D, len: usize) -> Vec<f32> {
+        let mut left = vec![0.0; len];
+        left[0] = 1.0;
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        left
+    }
+
+    /// Runs a sine through the dsp and returns the output amplitude over the input amplitude, after a settling time
+    pub fn sine_gain<D: EffectDsp>(dsp: &mut D, freq: f32, amplitude: f32) -> f32 {
+        let len = SAMPLE_RATE as usize;
+        let mut left: Vec<f32> = (0..len).map( | i | amplitude * (i as f32 * freq * std::f32::consts::TAU / SAMPLE_RATE).sin()).collect();
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        let tail = &left[len / 2..];
+        let rms = (tail.iter().map( | v | v * v).sum::<f32>() / tail.len() as f32).sqrt();
+        rms * std::f32::consts::SQRT_2 / amplitude
+    }
+
+    pub fn assert_db(gain: f32, db: f32, tolerance: f32) {
+        let got = gain_to_db(gain);
+        assert!((got - db).abs() <= tolerance, "expected {db} dB, got {got} dB");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::sync::{Arc, Mutex},
+        crate::effects::{
+            compressor::{CompressorDsp, CompressorParams},
+            gain_meter::{GainMeterDsp, GainMeterParams},
+        },
+    };
+
+    fn node<D: EffectDsp>(build: EffectBuilder<D>, params: D::Params, input_ports: usize) -> EffectNode<D> {
+        EffectNode::new(build, params, FromUISender::default().receiver(), input_ports)
+    }
+
+    fn render<D: EffectDsp>(node: &mut EffectNode<D>, sample_rate: f64, output: &mut AudioBuffer, inputs: &[&AudioBuffer]) {
+        let to_ui = ToUIReceiver::<ToUIDisplayMsg>::default();
+        let info = AudioInfo {device_id: AudioDeviceId::default(), time: None, sample_rate};
+        node.render_to_audio_buffer(info, &mut [output], inputs, &mut DisplayAudioGraph {
+            to_ui: &to_ui.sender(),
+            buffers: &mut Vec::new()
+        });
+    }
+
+    #[test]
+    fn dsp_follows_the_device_rate() {
+        let rates = Arc::new(Mutex::new(Vec::new()));
+        let build_rates = rates.clone();
+        let build = move | sample_rate, params: &GainMeterParams | {
+            build_rates.lock().unwrap().push(sample_rate);
+            GainMeterDsp::new(sample_rate, params)
+        };
+        let mut node = node(Box::new(build), GainMeterParams {gain: 0.0}, 1);
+        let mut output = AudioBuffer::new_with_size(64, 2);
+        render(&mut node, 44100.0, &mut output, &[]);
+        render(&mut node, 44100.0, &mut output, &[]);
+        render(&mut node, 96000.0, &mut output, &[]);
+        assert_eq!(*rates.lock().unwrap(), vec![44100.0, 96000.0]);
+    }
+
+    #[test]
+    fn short_input_is_zero_filled() {
+        let mut node = node(Box::new(GainMeterDsp::new), GainMeterParams {gain: 0.0}, 1);
+        let mut input = AudioBuffer::new_with_size(32, 1);
+        input.channel_mut(0).fill(0.5);
+        let mut output = AudioBuffer::new_with_size(64, 2);
+        output.channel_mut(0).fill(1.0);
+        render(&mut node, 48000.0, &mut output, &[&input]);
+        for c in 0..2 {
+            assert!(output.channel(c)[0..32].iter().all( | v | *v == 0.5));
+            assert!(output.channel(c)[32..].iter().all( | v | *v == 0.0));
+        }
+    }
+
+    #[test]
+    fn empty_sidechain_is_silence() {
+        let params = CompressorParams {threshold: -20.0, ratio: 4.0, knee: 0.0, attack: 0.001, release: 0.05, makeup: 0.0, limit: false};
+        let mut node = node(Box::new(CompressorDsp::new), params, 2);
+        let mut input = AudioBuffer::new_with_size(64, 2);
+        input.channel_mut(0).fill(1.0);
+        input.channel_mut(1).fill(1.0);
+        let key = AudioBuffer::new_with_size(64, 0);
+        let mut output = AudioBuffer::new_with_size(64, 2);
+        render(&mut node, 48000.0, &mut output, &[&input, &key]);
+        // a silent key never pushes the detector over the threshold
+        assert!(output.channel(0).iter().all( | v | *v == 1.0));
+    }
+}
@@ -0,0 +1,234 @@
+use {
+    crate::{
+        makepad_platform::*,
+        register_audio_component,
+        audio_traits::*,
+        automation::AutomatedParam,
+        effects::{EffectDsp, EffectNode, effect_component},
+        effects::delay::DelayLine,
+    },
+};
+
+live_design!{
+    Reverb = {{Reverb}} {}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ReverbParams {
+    pub decay: f32,
+    pub damping: f32,
+    pub width: f32,
+    pub mix: f32,
+}
+
+/// Schroeder allpass, the length in samples can be modulated for a less metallic tail
+struct Allpass {
+    line: DelayLine,
+    len: f32,
+    coef: f32,
+}
+
+impl Allpass {
+    fn new(len: f32, coef: f32) -> Self {
+        Self {line: DelayLine::new(len as usize + 16), len, coef}
+    }
+
+    fn process(&mut self, x: f32, wobble: f32) -> f32 {
+        let d = self.line.read(self.len + wobble);
+        let v = x - d * self.coef;
+        self.line.write(v);
+        v * self.coef + d
+    }
+}
+
+/// Griesinger style plate: four input diffusers feed a figure of eight tank of two
+/// modulated allpass and delay stages. It is the reverb of the IronFish synth with
+/// its lengths scaled to the sample rate so it sounds the same everywhere
+pub struct ReverbDsp {
+    decay: AutomatedParam,
+    damping: AutomatedParam,
+    width: AutomatedParam,
+    mix: AutomatedParam,
+    diffusers: [Allpass; 4],
+    tank: [Allpass; 4],
+    delays: [DelayLine; 2],
+    delay_lens: [f32; 2],
+    lfo_phase: [f32; 2],
+    lfo_step: [f32; 2],
+    wobble_depth: f32,
+    lowpass: f32,
+    feedback: f32,
+}
+
+impl EffectDsp for ReverbDsp {
+    type Params = ReverbParams;
+
+    fn new(sample_rate: f32, params: &ReverbParams) -> Self {
+        let scale = sample_rate / 48000.0;
+        let ap = | len: f32 | Allpass::new(len * scale, 0.5);
+        let delay_lens = [4453.0 * scale, 3163.0 * scale];
+        Self {
+            decay: AutomatedParam::new(params.decay, 0.02, sample_rate),
+            damping: AutomatedParam::new(params.damping, 0.02, sample_rate),
+            width: AutomatedParam::new(params.width, 0.02, sample_rate),
+            mix: AutomatedParam::new(params.mix, 0.02, sample_rate),
+            diffusers: [ap(142.0), ap(379.0), ap(107.0), ap(277.0)],
+            tank: [ap(672.0), ap(1800.0), ap(908.0), ap(2656.0)],
+            delays: [DelayLine::new(delay_lens[0] as usize + 2), DelayLine::new(delay_lens[1] as usize + 2)],
+            delay_lens,
+            lfo_phase: [0.0, 0.25],
+            lfo_step: [0.5 / sample_rate, 0.37 / sample_rate],
+            wobble_depth: 8.0 * scale,
+            lowpass: 0.0,
+            feedback: 0.0,
+        }
+    }
+
+    fn set_params(&mut self, params: &ReverbParams) {
+        self.decay.set(params.decay);
+        self.damping.set(params.damping);
+        self.width.set(params.width);
+        self.mix.set(params.mix);
+    }
+
+    fn for_each_param(&mut self, f: &mut dyn FnMut(LiveId, &mut AutomatedParam)) {
+        f(live_id!(decay), &mut self.decay);
+        f(live_id!(damping), &mut self.damping);
+        f(live_id!(width), &mut self.width);
+        f(live_id!(mix), &mut self.mix);
+    }
+
+    fn process(&mut self, left: &mut [f32], right: &mut [f32], _key: Option<(&[f32], &[f32])>) {
+        for i in 0..left.len() {
+            let decay = self.decay.tick().clamp(0.0, 0.98);
+            let damping = self.damping.tick().clamp(0.0, 0.99);
+            let width = self.width.tick();
+            let mix = self.mix.tick();
+
+            let mut wobble = [0.0; 2];
+            for ((phase, step), wobble) in self.lfo_phase.iter_mut().zip(self.lfo_step).zip(&mut wobble) {
+                *phase = (*phase + step).fract();
+                *wobble = (*phase * std::f32::consts::TAU).sin() * self.wobble_depth;
+            }
+
+            let mut acc = (left[i] + right[i]) * 0.5;
+            for diffuser in &mut self.diffusers {
+                acc = diffuser.process(acc, 0.0);
+            }
+            let reinject = acc;
+
+            acc = self.tank[0].process(acc + self.feedback, wobble[0]);
+            acc = self.tank[1].process(acc, 0.0);
+            let wet_left = self.delays[0].read(self.delay_lens[0]);
+            self.delays[0].write(acc);
+
+            acc = self.tank[2].process(wet_left + reinject, wobble[1]);
+            acc = self.tank[3].process(acc, 0.0);
+            let wet_right = self.delays[1].read(self.delay_lens[1]);
+            self.delays[1].write(acc);
+
+            self.lowpass += (wet_right - self.lowpass) * (1.0 - damping);
+            self.feedback = self.lowpass * decay;
+
+            let mid = (wet_left + wet_right) * 0.5;
+            let side = (wet_left - wet_right) * 0.5 * width;
+            left[i] = left[i] * (1.0 - mix) + (mid + side) * mix;
+            right[i] = right[i] * (1.0 - mix) + (mid - side) * mix;
+        }
+    }
+}
+
+#[derive(Live)]
+pub struct Reverb {
+    /// Feedback around the tank, towards 1 the tail gets longer
+    #[live(0.7)] decay: f32,
+    /// Lowpass in the tank, towards 1 the tail gets darker
+    #[live(0.05)] damping: f32,
+    /// 0 is a mono tail, 1 the full stereo spread
+    #[live(1.0)] width: f32,
+    #[live(0.25)] mix: f32,
+    #[rust] from_ui: FromUISender<ReverbParams>,
+}
+
+impl Reverb {
+    fn params(&self) -> ReverbParams {
+        ReverbParams {
+            decay: self.decay,
+            damping: self.damping,
+            width: self.width,
+            mix: self.mix,
+        }
+    }
+}
+
+effect_component!(Reverb, ReverbDsp);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::test_util::*;
+
+    fn params() -> ReverbParams {
+        ReverbParams {decay: 0.7, damping: 0.05, width: 1.0, mix: 1.0}
+    }
+
+    fn energy(samples: &[f32]) -> f32 {
+        samples.iter().map( | v | v * v).sum()
+    }
+
+    #[test]
+    fn impulse_tail_decays() {
+        let response = impulse_response(&mut ReverbDsp::new(SAMPLE_RATE, &params()), 48000 * 4);
+        let windows: Vec<f32> = response.chunks(24000).map(energy).collect();
+        assert!(windows[0] > 0.01);
+        for pair in windows.windows(2) {
+            assert!(pair[1] < pair[0], "{windows:?}");
+        }
+        assert!(*windows.last().unwrap() < windows[0] * 1e-3);
+        assert!(response.iter().all( | v | v.abs() < 1.0));
+    }
+
+    #[test]
+    fn decay_sets_the_tail_length() {
+        let short = impulse_response(&mut ReverbDsp::new(SAMPLE_RATE, &ReverbParams {decay: 0.3, ..params()}), 96000);
+        let long = impulse_response(&mut ReverbDsp::new(SAMPLE_RATE, &ReverbParams {decay: 0.9, ..params()}), 96000);
+        assert!(energy(&long[48000..]) > energy(&short[48000..]) * 100.0);
+    }
+
+    #[test]
+    fn damping_darkens_the_tail() {
+        // the energy of the first difference over the total energy is a rough measure of brightness
+        let brightness = | damping: f32 | {
+            let response = impulse_response(&mut ReverbDsp::new(SAMPLE_RATE, &ReverbParams {damping, ..params()}), 48000);
+            let tail = &response[24000..];
+            let diff: Vec<f32> = tail.windows(2).map( | w | w[1] - w[0]).collect();
+            energy(&diff) / energy(tail)
+        };
+        assert!(brightness(0.8) < brightness(0.0) * 0.5);
+    }
+
+    #[test]
+    fn width_spreads_the_tail() {
+        let mut dsp = ReverbDsp::new(SAMPLE_RATE, &params());
+        let mut left = vec![0.0; 24000];
+        left[0] = 1.0;
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        let side: Vec<f32> = left.iter().zip(&right).map( | (l, r) | l - r).collect();
+        assert!(energy(&side) > 0.01);
+
+        let mut dsp = ReverbDsp::new(SAMPLE_RATE, &ReverbParams {width: 0.0, ..params()});
+        let mut left = vec![0.0; 24000];
+        left[0] = 1.0;
+        let mut right = left.clone();
+        dsp.process(&mut left, &mut right, None);
+        assert!(left.iter().zip(&right).all( | (l, r) | l == r));
+    }
+
+    #[test]
+    fn dry_mix_passes_through() {
+        let response = impulse_response(&mut ReverbDsp::new(SAMPLE_RATE, &ReverbParams {mix: 0.0, ..params()}), 9600);
+        assert_eq!(response[0], 1.0);
+        assert!(response[1..].iter().all( | v | *v == 0.0));
+    }
+}
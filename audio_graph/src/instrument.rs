@@ -2,7 +2,8 @@ use {
     crate::{
         makepad_platform::*,
         register_audio_component,
-        audio_traits::*
+        audio_traits::*,
+        automation::*,
     },
 };
 
@@ -24,6 +25,7 @@ struct Instrument {
 
 
 struct Step {
+    id: LiveId,
     graph_node: Box<dyn AudioGraphNode + Send >,
     input_buffer: AudioBuffer,
 }
@@ -45,6 +47,12 @@ impl AudioGraphNode for Node {
         }
     }
     
+    fn handle_automation(&mut self, event: AutomationEvent) {
+        for step in &mut self.steps {
+            step.graph_node.handle_automation(event.for_child(step.id));
+        }
+    }
+    
     fn render_to_audio_buffer(&mut self, info: AudioInfo, outputs: &mut [&mut AudioBuffer], inputs: &[&AudioBuffer], display:&mut DisplayAudioGraph) {
         // reverse over the steps chaining the audio nodes
         let steps = &mut self.steps;
@@ -105,6 +113,7 @@ impl AudioComponent for Instrument {
         for step_id in &self.step_order {
             if let Some(input) = self.steps.get_mut(step_id).unwrap().as_mut() {
                 steps.push(Step {
+                    id: *step_id,
                     graph_node: input.get_graph_node(cx),
                     input_buffer: AudioBuffer::default()
                 });
@@ -8,15 +8,34 @@ pub mod audio_unit_instrument;
 pub mod mixer;
 pub mod instrument;
 pub mod audio_stream;
+pub mod offline_render;
+pub mod sample_player;
+pub mod router;
+pub mod routing_nodes;
+pub mod effects;
+pub mod automation;
+pub mod midi_file;
+pub mod sequencer;
+pub mod recorder;
 
 use makepad_platform::Cx;
 pub use makepad_platform;
+pub use makepad_audio_formats;
 pub use makepad_platform::makepad_math;
 pub use crate::audio_graph::*;
 pub use crate::audio_traits::*;
+pub use crate::offline_render::*;
+pub use crate::automation::*;
+pub use crate::midi_file::*;
+pub use crate::sequencer::*;
+pub use crate::recorder::*;
 
 pub fn live_design(cx:&mut Cx){
     self::audio_graph::live_design(cx);
     self::mixer::live_design(cx);
     self::instrument::live_design(cx);
+    self::sample_player::live_design(cx);
+    self::router::live_design(cx);
+    self::routing_nodes::live_design(cx);
+    self::effects::live_design(cx);
 }
@@ -0,0 +1,563 @@
+use {
+    crate::makepad_platform::*,
+    std::{
+        fmt,
+        fs::File,
+        io::{self, Read, Write, BufWriter},
+        path::Path,
+    },
+};
+
+/// The possible errors that can occur when reading a Standard MIDI File.
+#[derive(Debug)]
+pub enum MidiFileError {
+    /// Reading from the underlying stream failed.
+    Io(io::Error),
+    /// The data did not start with an `MThd` header.
+    NotAMidiFile,
+    /// The file is damaged, the reason is included.
+    InvalidData(&'static str),
+}
+
+impl fmt::Display for MidiFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for MidiFileError {}
+
+impl From<io::Error> for MidiFileError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// How the ticks of a file map to time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MidiTiming {
+    /// Ticks per quarter note, the tempo events set the length of a quarter
+    PerQuarter(u16),
+    /// Frames per second (24, 25, 29 for 29.97 drop frame, 30) and ticks per frame, tempo events don't apply
+    Smpte {fps: u8, ticks_per_frame: u8},
+}
+
+impl MidiTiming {
+    fn from_division(division: u16) -> Self {
+        if division & 0x8000 != 0 {
+            Self::Smpte {
+                fps: (-((division >> 8) as u8 as i8)) as u8,
+                ticks_per_frame: (division & 0xff) as u8
+            }
+        }
+        else {
+            Self::PerQuarter(division.max(1))
+        }
+    }
+
+    fn to_division(self) -> u16 {
+        match self {
+            Self::PerQuarter(ppq) => ppq & 0x7fff,
+            Self::Smpte {fps, ticks_per_frame} => (((-(fps as i8)) as u8 as u16) << 8) | ticks_per_frame as u16,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MidiFileEvent {
+    /// A channel message, program change and channel pressure leave the last byte 0
+    Midi(MidiData),
+    /// Microseconds per quarter note
+    Tempo(u32),
+    TimeSignature {numerator: u8, denominator_pow2: u8, clocks_per_click: u8, notated_32nds_per_quarter: u8},
+    TrackName(String),
+    EndOfTrack,
+    /// Any other meta event with its type byte
+    Meta(u8, Vec<u8>),
+    /// System exclusive data without the leading F0, `escaped` for F7 packets
+    SysEx {data: Vec<u8>, escaped: bool},
+}
+
+/// An event with its time in ticks from the start of the track
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiTrackEvent {
+    pub tick: u64,
+    pub event: MidiFileEvent,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MidiTrack {
+    pub events: Vec<MidiTrackEvent>,
+}
+
+impl MidiTrack {
+    /// Inserts after events with the same tick so the order things were added in stays
+    pub fn push(&mut self, tick: u64, event: MidiFileEvent) {
+        let index = self.events.iter().rposition( | e | e.tick <= tick).map( | i | i + 1).unwrap_or(0);
+        self.events.insert(index, MidiTrackEvent {tick, event});
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.events.iter().find_map( | e | match &e.event {
+            MidiFileEvent::TrackName(name) => Some(name.as_str()),
+            _ => None
+        })
+    }
+
+    pub fn end_tick(&self) -> u64 {
+        self.events.last().map( | e | e.tick).unwrap_or(0)
+    }
+}
+
+/// A Standard MIDI File, format 0 holds everything in one track,
+/// format 1 plays its tracks together with the tempo map in the first one
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiFile {
+    pub format: u16,
+    pub timing: MidiTiming,
+    pub tracks: Vec<MidiTrack>,
+}
+
+impl Default for MidiFile {
+    fn default() -> Self {
+        Self {
+            format: 1,
+            timing: MidiTiming::PerQuarter(480),
+            tracks: Vec::new(),
+        }
+    }
+}
+
+fn read_u16(b: &[u8]) -> u16 {
+    u16::from_be_bytes([b[0], b[1]])
+}
+
+fn read_u32(b: &[u8]) -> u32 {
+    u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+}
+
+/// Reads a variable length quantity, at most 4 bytes of 7 bits
+fn read_vlq(data: &[u8], pos: &mut usize) -> Result<u32, MidiFileError> {
+    let mut value = 0u32;
+    for _ in 0..4 {
+        let byte = *data.get(*pos).ok_or(MidiFileError::InvalidData("track ends in a number"))?;
+        *pos += 1;
+        value = (value << 7) | (byte & 0x7f) as u32;
+        if byte & 0x80 == 0 {
+            return Ok(value)
+        }
+    }
+    Err(MidiFileError::InvalidData("number longer than 4 bytes"))
+}
+
+/// Numbers in a file have at most 4 bytes of 7 bits, larger ones can't be written
+fn write_vlq(out: &mut Vec<u8>, value: u64) -> io::Result<()> {
+    if value > 0x0fff_ffff {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "number longer than 4 bytes"))
+    }
+    let value = value as u32;
+    let mut shift = 21;
+    while shift > 0 && value >> shift == 0 {
+        shift -= 7;
+    }
+    while shift > 0 {
+        out.push(0x80 | ((value >> shift) & 0x7f) as u8);
+        shift -= 7;
+    }
+    out.push((value & 0x7f) as u8);
+    Ok(())
+}
+
+/// The number of data bytes after a channel status byte
+fn channel_data_len(status: u8) -> usize {
+    match status >> 4 {
+        0xC | 0xD => 1,
+        _ => 2
+    }
+}
+
+fn write_meta(out: &mut Vec<u8>, kind: u8, body: &[u8]) -> io::Result<()> {
+    out.extend_from_slice(&[0xFF, kind]);
+    write_vlq(out, body.len() as u64)?;
+    out.extend_from_slice(body);
+    Ok(())
+}
+
+fn take<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8], MidiFileError> {
+    let bytes = data.get(*pos..*pos + len).ok_or(MidiFileError::InvalidData("event runs past the end of its track"))?;
+    *pos += len;
+    Ok(bytes)
+}
+
+fn parse_track(data: &[u8]) -> Result<MidiTrack, MidiFileError> {
+    let mut track = MidiTrack::default();
+    let mut pos = 0;
+    let mut tick = 0u64;
+    let mut running_status = None;
+    while pos < data.len() {
+        tick += read_vlq(data, &mut pos)? as u64;
+        let mut status = *data.get(pos).ok_or(MidiFileError::InvalidData("track ends after a delta time"))?;
+        if status & 0x80 == 0 {
+            // running status, the byte is data of the same message type as before
+            status = running_status.ok_or(MidiFileError::InvalidData("data byte without a status"))?;
+        }
+        else {
+            pos += 1;
+        }
+        let event = match status {
+            0xFF => {
+                running_status = None;
+                let kind = take(data, &mut pos, 1)?[0];
+                let len = read_vlq(data, &mut pos)? as usize;
+                let body = take(data, &mut pos, len)?;
+                match kind {
+                    0x51 if len == 3 => MidiFileEvent::Tempo(((body[0] as u32) << 16) | ((body[1] as u32) << 8) | body[2] as u32),
+                    0x58 if len == 4 => MidiFileEvent::TimeSignature {
+                        numerator: body[0],
+                        denominator_pow2: body[1],
+                        clocks_per_click: body[2],
+                        notated_32nds_per_quarter: body[3]
+                    },
+                    0x03 => MidiFileEvent::TrackName(String::from_utf8_lossy(body).into_owned()),
+                    0x2F => MidiFileEvent::EndOfTrack,
+                    _ => MidiFileEvent::Meta(kind, body.to_vec()),
+                }
+            }
+            0xF0 | 0xF7 => {
+                running_status = None;
+                let len = read_vlq(data, &mut pos)? as usize;
+                MidiFileEvent::SysEx {data: take(data, &mut pos, len)?.to_vec(), escaped: status == 0xF7}
+            }
+            0x80..=0xEF => {
+                running_status = Some(status);
+                let bytes = take(data, &mut pos, channel_data_len(status))?;
+                MidiFileEvent::Midi(MidiData {data: [status, bytes[0], *bytes.get(1).unwrap_or(&0)]})
+            }
+            _ => return Err(MidiFileError::InvalidData("system message in a track")),
+        };
+        let end = event == MidiFileEvent::EndOfTrack;
+        track.events.push(MidiTrackEvent {tick, event});
+        if end {
+            break;
+        }
+    }
+    Ok(track)
+}
+
+impl MidiFile {
+    pub fn parse(data: &[u8]) -> Result<Self, MidiFileError> {
+        if data.len() < 14 || &data[0..4] != b"MThd" {
+            return Err(MidiFileError::NotAMidiFile)
+        }
+        let header_len = read_u32(&data[4..]) as usize;
+        if header_len < 6 || data.len() < 8 + header_len {
+            return Err(MidiFileError::InvalidData("header too short"))
+        }
+        let format = read_u16(&data[8..]);
+        let track_count = read_u16(&data[10..]) as usize;
+        let timing = MidiTiming::from_division(read_u16(&data[12..]));
+        if format > 2 {
+            return Err(MidiFileError::InvalidData("unknown format"))
+        }
+
+        let mut tracks = Vec::new();
+        let mut pos = 8 + header_len;
+        // chunks of other types are skipped as the spec asks
+        while tracks.len() < track_count && pos + 8 <= data.len() {
+            let len = read_u32(&data[pos + 4..]) as usize;
+            let body = data.get(pos + 8..pos + 8 + len).ok_or(MidiFileError::InvalidData("chunk runs past the end of the file"))?;
+            if &data[pos..pos + 4] == b"MTrk" {
+                tracks.push(parse_track(body)?);
+            }
+            pos += 8 + len;
+        }
+        Ok(Self {format, timing, tracks})
+    }
+
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, MidiFileError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        Self::parse(&data)
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MidiFileError> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Writes the file without running status, each track ends with an end of track event.
+    /// Events more than 0x0fffffff ticks apart and oversized meta or sysex bodies are an `InvalidInput` error
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b"MThd")?;
+        writer.write_all(&6u32.to_be_bytes())?;
+        writer.write_all(&self.format.to_be_bytes())?;
+        writer.write_all(&(self.tracks.len() as u16).to_be_bytes())?;
+        writer.write_all(&self.timing.to_division().to_be_bytes())?;
+
+        for track in &self.tracks {
+            let mut out = Vec::new();
+            let mut last_tick = 0;
+            let mut has_end = false;
+            for event in &track.events {
+                // an end of track before the last event would cut the track short
+                if event.event == MidiFileEvent::EndOfTrack && event.tick < track.end_tick() {
+                    continue;
+                }
+                write_vlq(&mut out, event.tick.saturating_sub(last_tick))?;
+                last_tick = last_tick.max(event.tick);
+                match &event.event {
+                    MidiFileEvent::Midi(data) => {
+                        let len = channel_data_len(data.data[0]);
+                        out.extend_from_slice(&data.data[0..1 + len]);
+                    }
+                    MidiFileEvent::Tempo(tempo) => write_meta(&mut out, 0x51, &tempo.to_be_bytes()[1..4])?,
+                    MidiFileEvent::TimeSignature {numerator, denominator_pow2, clocks_per_click, notated_32nds_per_quarter} => {
+                        write_meta(&mut out, 0x58, &[*numerator, *denominator_pow2, *clocks_per_click, *notated_32nds_per_quarter])?
+                    }
+                    MidiFileEvent::TrackName(name) => write_meta(&mut out, 0x03, name.as_bytes())?,
+                    MidiFileEvent::EndOfTrack => {
+                        has_end = true;
+                        write_meta(&mut out, 0x2F, &[])?
+                    }
+                    MidiFileEvent::Meta(kind, body) => write_meta(&mut out, *kind, body)?,
+                    MidiFileEvent::SysEx {data, escaped} => {
+                        out.push(if *escaped {0xF7} else {0xF0});
+                        write_vlq(&mut out, data.len() as u64)?;
+                        out.extend_from_slice(data);
+                    }
+                }
+            }
+            if !has_end {
+                out.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]);
+            }
+            writer.write_all(b"MTrk")?;
+            writer.write_all(&(out.len() as u32).to_be_bytes())?;
+            writer.write_all(&out)?;
+        }
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// The channel messages of all tracks in time order, events at the same tick keep their track order
+    pub fn midi_events(&self) -> Vec<(u64, MidiData)> {
+        let mut events = Vec::new();
+        for track in &self.tracks {
+            for event in &track.events {
+                if let MidiFileEvent::Midi(data) = event.event {
+                    events.push((event.tick, data));
+                }
+            }
+        }
+        events.sort_by_key( | (tick, _) | *tick);
+        events
+    }
+
+    pub fn end_tick(&self) -> u64 {
+        self.tracks.iter().map( | t | t.end_tick()).max().unwrap_or(0)
+    }
+
+    pub fn tempo_map(&self) -> TempoMap {
+        match self.timing {
+            MidiTiming::PerQuarter(ppq) => {
+                let mut changes: Vec<(u64, u32)> = self.tracks.iter().flat_map( | t | t.events.iter()).filter_map( | e | match e.event {
+                    MidiFileEvent::Tempo(tempo) => Some((e.tick, tempo)),
+                    _ => None
+                }).collect();
+                changes.sort_by_key( | (tick, _) | *tick);
+                TempoMap::new(ppq, &changes)
+            }
+            MidiTiming::Smpte {fps, ticks_per_frame} => {
+                // a fixed rate, expressed as a 'quarter' of one second
+                let ticks_per_second = if fps == 29 {29.97} else {fps as f64} * ticks_per_frame as f64;
+                TempoMap {ticks_per_quarter: ticks_per_second, segments: vec![TempoSegment {tick: 0, seconds: 0.0, tempo: 1_000_000}]}
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TempoSegment {
+    pub tick: u64,
+    pub seconds: f64,
+    /// Microseconds per quarter note
+    pub tempo: u32,
+}
+
+/// Converts between ticks and seconds over a list of tempo changes
+#[derive(Clone, Debug, PartialEq)]
+pub struct TempoMap {
+    pub ticks_per_quarter: f64,
+    /// Always starts at tick 0
+    pub segments: Vec<TempoSegment>,
+}
+
+pub const DEFAULT_TEMPO: u32 = 500_000;
+
+pub fn bpm_to_tempo(bpm: f64) -> u32 {
+    (60_000_000.0 / bpm.max(1.0)).round() as u32
+}
+
+pub fn tempo_to_bpm(tempo: u32) -> f64 {
+    60_000_000.0 / tempo.max(1) as f64
+}
+
+impl TempoMap {
+    /// `changes` are tick and microseconds per quarter pairs in time order, without one at tick 0 the file plays at 120 bpm
+    pub fn new(ticks_per_quarter: u16, changes: &[(u64, u32)]) -> Self {
+        let mut map = Self {
+            ticks_per_quarter: ticks_per_quarter.max(1) as f64,
+            segments: vec![TempoSegment {tick: 0, seconds: 0.0, tempo: DEFAULT_TEMPO}]
+        };
+        for (tick, tempo) in changes {
+            let seconds = map.tick_to_seconds(*tick as f64);
+            let last = map.segments.last_mut().unwrap();
+            if last.tick == *tick {
+                last.tempo = *tempo;
+            }
+            else {
+                map.segments.push(TempoSegment {tick: *tick, seconds, tempo: *tempo});
+            }
+        }
+        map
+    }
+
+    fn segment_at_tick(&self, tick: f64) -> &TempoSegment {
+        let index = self.segments.partition_point( | s | s.tick as f64 <= tick);
+        &self.segments[index.saturating_sub(1)]
+    }
+
+    pub fn tempo_at(&self, tick: f64) -> u32 {
+        self.segment_at_tick(tick).tempo
+    }
+
+    pub fn seconds_per_tick(&self, tick: f64) -> f64 {
+        self.tempo_at(tick) as f64 / 1_000_000.0 / self.ticks_per_quarter
+    }
+
+    pub fn tick_to_seconds(&self, tick: f64) -> f64 {
+        let segment = self.segment_at_tick(tick);
+        segment.seconds + (tick - segment.tick as f64) * segment.tempo as f64 / 1_000_000.0 / self.ticks_per_quarter
+    }
+
+    pub fn seconds_to_tick(&self, seconds: f64) -> f64 {
+        let index = self.segments.partition_point( | s | s.seconds <= seconds);
+        let segment = &self.segments[index.saturating_sub(1)];
+        segment.tick as f64 + (seconds - segment.seconds) * 1_000_000.0 * self.ticks_per_quarter / segment.tempo as f64
+    }
+
+    /// The first tempo change after `tick`
+    pub fn next_change(&self, tick: f64) -> Option<u64> {
+        self.segments.iter().map( | s | s.tick).find( | t | *t as f64 > tick)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_on(note: u8) -> MidiFileEvent {
+        MidiFileEvent::Midi(MidiData {data: [0x90, note, 100]})
+    }
+
+    #[test]
+    fn variable_length_quantities() {
+        for value in [0, 0x40, 0x7f, 0x80, 0x2000, 0x3fff, 0x4000, 0x1fffff, 0x200000, 0x0fffffff] {
+            let mut out = Vec::new();
+            write_vlq(&mut out, value as u64).unwrap();
+            let mut pos = 0;
+            assert_eq!(read_vlq(&out, &mut pos).unwrap(), value);
+            assert_eq!(pos, out.len());
+        }
+        let mut out = Vec::new();
+        write_vlq(&mut out, 0x4000).unwrap();
+        assert_eq!(out, [0x81, 0x80, 0x00]);
+        assert_eq!(write_vlq(&mut out, 0x1000_0000).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn parses_running_status_and_meta() {
+        let mut data = b"MThd\x00\x00\x00\x06\x00\x00\x00\x01\x00\x60MTrk".to_vec();
+        let track = [
+            0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, // 120 bpm
+            0x00, 0x90, 0x3C, 0x64,
+            0x60, 0x3C, 0x00, // running status note off by velocity 0
+            0x00, 0xC1, 0x05, // program change has one data byte
+            0x81, 0x00, 0xF0, 0x02, 0x7E, 0xF7,
+            0x00, 0xFF, 0x2F, 0x00,
+        ];
+        data.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        data.extend_from_slice(&track);
+        let file = MidiFile::parse(&data).unwrap();
+        assert_eq!(file.format, 0);
+        assert_eq!(file.timing, MidiTiming::PerQuarter(96));
+        let events = &file.tracks[0].events;
+        assert_eq!(events[0].event, MidiFileEvent::Tempo(500_000));
+        assert_eq!(events[2], MidiTrackEvent {tick: 96, event: MidiFileEvent::Midi(MidiData {data: [0x90, 0x3C, 0x00]})});
+        assert_eq!(events[3].event, MidiFileEvent::Midi(MidiData {data: [0xC1, 0x05, 0x00]}));
+        assert_eq!(events[4], MidiTrackEvent {tick: 224, event: MidiFileEvent::SysEx {data: vec![0x7E, 0xF7], escaped: false}});
+        assert_eq!(events[5].event, MidiFileEvent::EndOfTrack);
+        assert!(matches!(MidiFile::parse(b"RIFF0000WAVEfmt "), Err(MidiFileError::NotAMidiFile)));
+        assert!(matches!(MidiFile::parse(&data[0..data.len() - 3]), Err(MidiFileError::InvalidData(_))));
+    }
+
+    #[test]
+    fn write_read_round_trip() {
+        let mut tempo = MidiTrack::default();
+        tempo.push(0, MidiFileEvent::TrackName("tempo".into()));
+        tempo.push(0, MidiFileEvent::Tempo(bpm_to_tempo(90.0)));
+        tempo.push(0, MidiFileEvent::TimeSignature {numerator: 3, denominator_pow2: 2, clocks_per_click: 24, notated_32nds_per_quarter: 8});
+        let mut notes = MidiTrack::default();
+        notes.push(480, MidiFileEvent::Midi(MidiData {data: [0x80, 60, 0]}));
+        notes.push(0, note_on(60));
+        notes.push(480, MidiFileEvent::Midi(MidiData {data: [0xD2, 40, 0]}));
+        notes.push(100000, MidiFileEvent::Meta(0x7F, vec![1, 2, 3]));
+        let file = MidiFile {format: 1, timing: MidiTiming::PerQuarter(480), tracks: vec![tempo, notes]};
+
+        let mut data = Vec::new();
+        file.write(&mut data).unwrap();
+        let read = MidiFile::parse(&data).unwrap();
+        assert_eq!(read.tracks[0].name(), Some("tempo"));
+        // the writer closes every track
+        for (written, read) in file.tracks.iter().zip(&read.tracks) {
+            assert_eq!(&read.events[0..written.events.len()], &written.events[..]);
+            assert_eq!(read.events.last().unwrap().event, MidiFileEvent::EndOfTrack);
+        }
+        assert_eq!(read.midi_events()[1], (480, MidiData {data: [0x80, 60, 0]}));
+
+        let smpte = MidiFile {timing: MidiTiming::Smpte {fps: 25, ticks_per_frame: 40}, ..MidiFile::default()};
+        let mut data = Vec::new();
+        smpte.write(&mut data).unwrap();
+        assert_eq!(&data[12..14], &[0xE7, 40]);
+        assert_eq!(MidiFile::parse(&data).unwrap().timing, smpte.timing);
+    }
+
+    #[test]
+    fn write_rejects_long_deltas() {
+        let mut track = MidiTrack::default();
+        track.push(0x1000_0000, note_on(60));
+        let file = MidiFile {tracks: vec![track], ..MidiFile::default()};
+        let err = file.write(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn tempo_map_conversions() {
+        // a quarter at 120 bpm, then a quarter takes a second
+        let map = TempoMap::new(480, &[(0, 500_000), (480, 1_000_000)]);
+        assert_eq!(map.tick_to_seconds(480.0), 0.5);
+        assert_eq!(map.tick_to_seconds(960.0), 1.5);
+        assert_eq!(map.seconds_to_tick(1.0), 720.0);
+        assert_eq!(map.seconds_to_tick(0.25), 240.0);
+        assert_eq!(map.next_change(0.0), Some(480));
+        assert_eq!(map.next_change(480.0), None);
+        assert_eq!(tempo_to_bpm(map.tempo_at(600.0)), 60.0);
+        // without tempo events files play at 120 bpm
+        assert_eq!(TempoMap::new(96, &[]).tick_to_seconds(96.0), 0.5);
+        let smpte = MidiFile {timing: MidiTiming::Smpte {fps: 25, ticks_per_frame: 40}, ..MidiFile::default()};
+        assert_eq!(smpte.tempo_map().tick_to_seconds(1000.0), 1.0);
+    }
+}
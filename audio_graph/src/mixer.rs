@@ -2,7 +2,8 @@ use {
     crate::{
         makepad_platform::*,
         register_audio_component,
-        audio_traits::*
+        audio_traits::*,
+        automation::*,
     },
 };
 
@@ -43,6 +44,7 @@ impl LiveHook for Mixer {
 struct Node {
     _from_ui: FromUIReceiver<FromUI>,
     buffer: AudioBuffer,
+    ids: Vec<LiveId>,
     inputs: Vec<Box<dyn AudioGraphNode + Send >>
 }
 
@@ -62,6 +64,12 @@ impl AudioGraphNode for Node {
         }
     }
     
+    fn handle_automation(&mut self, event: AutomationEvent) {
+        for (id, input) in self.ids.iter().zip(&mut self.inputs) {
+            input.handle_automation(event.for_child(*id));
+        }
+    }
+    
     fn render_to_audio_buffer(
         &mut self,
         info: AudioInfo,
@@ -91,13 +99,16 @@ impl AudioComponent for Mixer {
     fn get_graph_node(&mut self, cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
         
         self.from_ui.new_channel();
+        let mut ids = Vec::new();
         let mut inputs = Vec::new();
-        for input in self.inputs.values_mut() {
+        for (id, input) in self.inputs.iter_mut() {
             if let Some(input) = input.as_mut() {
+                ids.push(*id);
                 inputs.push(input.get_graph_node(cx));
             }
         }
         Box::new(Node {
+            ids,
             inputs,
             buffer: AudioBuffer::default(),
             _from_ui: self.from_ui.receiver()
@@ -0,0 +1,372 @@
+use {
+    crate::{
+        makepad_platform::*,
+        audio_traits::*,
+        automation::*,
+    },
+    std::{
+        fs::File,
+        io::{self, Write, BufWriter},
+        path::Path,
+    },
+};
+
+/// A midi message with the frame it should be played on
+#[derive(Clone, Copy, Debug)]
+pub struct OfflineMidiEvent {
+    pub frame: usize,
+    pub data: MidiData,
+}
+
+impl OfflineMidiEvent {
+    pub fn at_frame(frame: usize, data: MidiData) -> Self {
+        Self {frame, data}
+    }
+
+    pub fn at_seconds(seconds: f64, sample_rate: f64, data: MidiData) -> Self {
+        Self {frame: (seconds * sample_rate).round().max(0.0) as usize, data}
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WavFormat {
+    Int16,
+    Int24,
+    Float32,
+}
+
+impl WavFormat {
+    fn bits_per_sample(&self) -> u16 {
+        match self {
+            Self::Int16 => 16,
+            Self::Int24 => 24,
+            Self::Float32 => 32,
+        }
+    }
+
+    fn format_tag(&self) -> u16 {
+        match self {
+            Self::Int16 | Self::Int24 => 1, // PCM
+            Self::Float32 => 3, // IEEE float
+        }
+    }
+}
+
+/// Renders an audio graph without a device, pulling blocks as fast as the graph can produce them.
+/// The sample rate goes into event timing, the wav header and `AudioInfo::sample_rate`,
+/// components with their own rate setting (like IronFish `sample_rate`) have to be set to match.
+#[derive(Clone, Debug)]
+pub struct OfflineRender {
+    pub sample_rate: f64,
+    pub block_size: usize,
+    pub channel_count: usize,
+}
+
+impl Default for OfflineRender {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48000.0,
+            block_size: 512,
+            channel_count: 2,
+        }
+    }
+}
+
+impl OfflineRender {
+    pub fn new(sample_rate: f64, block_size: usize) -> Self {
+        Self {
+            sample_rate,
+            block_size,
+            ..Self::default()
+        }
+    }
+
+    pub fn seconds_to_frames(&self, seconds: f64) -> usize {
+        (seconds * self.sample_rate).round().max(0.0) as usize
+    }
+
+    /// Renders `frame_count` frames, events are sent to the node right before their frame.
+    /// Blocks are cut short at event frames so notes start exactly where they are placed.
+    pub fn render(&self, node: &mut dyn AudioGraphNode, events: &[OfflineMidiEvent], frame_count: usize) -> AudioBuffer {
+        self.render_with_automation(node, events, &[], frame_count)
+    }
+
+    /// Like `render`, with automation events in frames from the start of the render.
+    /// They are all queued up front, a ramp starts at the event before it so both have to be there
+    pub fn render_with_automation(&self, node: &mut dyn AudioGraphNode, events: &[OfflineMidiEvent], automation: &[AutomationEvent], frame_count: usize) -> AudioBuffer {
+        for event in automation {
+            node.handle_automation(*event);
+        }
+
+        let mut events = events.to_vec();
+        events.sort_by_key( | e | e.frame);
+
+        let mut output = AudioBuffer::new_with_size(frame_count, self.channel_count);
+        let mut block = AudioBuffer::new_with_size(self.block_size, self.channel_count);
+
+        // display buffers sent by the graph are handed straight back, nobody is watching
+        let to_ui = ToUIReceiver::<ToUIDisplayMsg>::default();
+        let to_ui_sender = to_ui.sender();
+        let mut display_buffers = Vec::new();
+        for _ in 0..64 {
+            display_buffers.push(AudioBuffer::new_with_size(self.block_size, 2));
+        }
+
+        let mut next_event = 0;
+        let mut frame = 0;
+        while frame < frame_count {
+            while next_event < events.len() && events[next_event].frame <= frame {
+                node.handle_midi_data(events[next_event].data);
+                next_event += 1;
+            }
+            let mut block_end = (frame + self.block_size.max(1)).min(frame_count);
+            if next_event < events.len() {
+                block_end = block_end.min(events[next_event].frame);
+            }
+            let block_frames = block_end - frame;
+
+            block.resize(block_frames, self.channel_count);
+            block.zero();
+            let info = AudioInfo {
+                device_id: AudioDeviceId::default(),
+                time: Some(AudioTime {
+                    sample_time: frame as f64,
+                    host_time: 0,
+                    rate_scalar: 1.0,
+                }),
+                sample_rate: self.sample_rate,
+            };
+            node.render_to_audio_buffer(info, &mut [&mut block], &[], &mut DisplayAudioGraph {
+                to_ui: &to_ui_sender,
+                buffers: &mut display_buffers
+            });
+            for c in 0..self.channel_count {
+                output.channel_mut(c)[frame..block_end].copy_from_slice(block.channel(c));
+            }
+
+            while let Ok(msg) = to_ui.try_recv() {
+                if let ToUIDisplayMsg::DisplayAudio {buffer, ..} = msg {
+                    display_buffers.push(buffer);
+                }
+            }
+            frame = block_end;
+        }
+        output
+    }
+}
+
+/// Writes the buffer as a wav file. Fails before writing anything if it holds more than `max_wav_frames`.
+pub fn write_wav<W: Write>(writer: &mut W, buffer: &AudioBuffer, sample_rate: u32, format: WavFormat) -> io::Result<()> {
+    write_wav_header(writer, buffer.channel_count(), sample_rate, format, buffer.frame_count())?;
+    let mut interleaved = vec![0.0; buffer.frame_count() * buffer.channel_count()];
+    buffer.copy_to_interleaved(&mut interleaved);
+    write_wav_samples(writer, &interleaved, format)?;
+    write_wav_padding(writer, buffer.channel_count(), format, buffer.frame_count())
+}
+
+// the riff size counts the chunks around the samples too, at most the float fmt and fact chunks and a pad byte
+const WAV_MAX_OVERHEAD: u64 = 4 + 8 + 18 + 12 + 8 + 1;
+
+/// The most frames a wav file can hold, the sizes in its header are 32 bit so it stops just short of 4GB
+pub fn max_wav_frames(channel_count: usize, format: WavFormat) -> usize {
+    let block_align = channel_count.max(1) as u64 * (format.bits_per_sample() as u64 / 8);
+    ((u32::MAX as u64 - WAV_MAX_OVERHEAD) / block_align) as usize
+}
+
+fn wav_data_size(channel_count: usize, format: WavFormat, frame_count: usize) -> io::Result<u32> {
+    if frame_count > max_wav_frames(channel_count, format) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} frames don't fit in a wav file", frame_count)))
+    }
+    Ok((frame_count * channel_count) as u32 * (format.bits_per_sample() as u32 / 8))
+}
+
+/// Writes the riff header up to the start of the sample data. Streaming writers write it
+/// once up front and again with the final frame count when done.
+pub(crate) fn write_wav_header<W: Write>(writer: &mut W, channel_count: usize, sample_rate: u32, format: WavFormat, frame_count: usize) -> io::Result<()> {
+    let bytes_per_sample = format.bits_per_sample() as u32 / 8;
+    let block_align = channel_count as u32 * bytes_per_sample;
+    let data_size = wav_data_size(channel_count, format, frame_count)?;
+    let is_float = format == WavFormat::Float32;
+    // non-pcm formats carry a fact chunk and an extension size in the fmt chunk
+    let fmt_size: u32 = if is_float {18} else {16};
+    let fact_size: u32 = if is_float {12} else {0};
+    // riff chunks are padded to an even size
+    let pad_size = data_size & 1;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(4 + 8 + fmt_size + fact_size + 8 + data_size + pad_size).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&fmt_size.to_le_bytes())?;
+    writer.write_all(&format.format_tag().to_le_bytes())?;
+    writer.write_all(&(channel_count as u16).to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&(sample_rate * block_align).to_le_bytes())?;
+    writer.write_all(&(block_align as u16).to_le_bytes())?;
+    writer.write_all(&format.bits_per_sample().to_le_bytes())?;
+    if is_float {
+        writer.write_all(&0u16.to_le_bytes())?;
+        writer.write_all(b"fact")?;
+        writer.write_all(&4u32.to_le_bytes())?;
+        writer.write_all(&(frame_count as u32).to_le_bytes())?;
+    }
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data_size.to_le_bytes())
+}
+
+/// Writes interleaved samples in the sample format of the wav file
+pub(crate) fn write_wav_samples<W: Write>(writer: &mut W, interleaved: &[f32], format: WavFormat) -> io::Result<()> {
+    for sample in interleaved {
+        match format {
+            WavFormat::Int16 => {
+                let s = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+                writer.write_all(&s.to_le_bytes())?;
+            }
+            WavFormat::Int24 => {
+                let s = (sample.clamp(-1.0, 1.0) * 8388607.0) as i32;
+                writer.write_all(&s.to_le_bytes()[0..3])?;
+            }
+            WavFormat::Float32 => {
+                writer.write_all(&sample.to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pads the sample data to an even size, after the last samples are written
+pub(crate) fn write_wav_padding<W: Write>(writer: &mut W, channel_count: usize, format: WavFormat, frame_count: usize) -> io::Result<()> {
+    if wav_data_size(channel_count, format, frame_count)? & 1 == 1 {
+        writer.write_all(&[0])?;
+    }
+    Ok(())
+}
+
+pub fn save_wav(path: impl AsRef<Path>, buffer: &AudioBuffer, sample_rate: u32, format: WavFormat) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_wav(&mut writer, buffer, sample_rate, format)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // plays a constant 0.5 from its note on, so the output shows where the note landed
+    #[derive(Default)]
+    struct Tone {
+        is_on: bool,
+    }
+
+    impl AudioGraphNode for Tone {
+        fn handle_midi_data(&mut self, data: MidiData) {
+            if let MidiEvent::Note(note) = data.decode() {
+                self.is_on = note.is_on;
+            }
+        }
+
+        fn all_notes_off(&mut self) {
+            self.is_on = false;
+        }
+
+        fn render_to_audio_buffer(&mut self, _info: AudioInfo, outputs: &mut [&mut AudioBuffer], _inputs: &[&AudioBuffer], _display: &mut DisplayAudioGraph) {
+            let level = if self.is_on {0.5} else {0.0};
+            for c in 0..outputs[0].channel_count() {
+                outputs[0].channel_mut(c).fill(level);
+            }
+        }
+    }
+
+    fn note(is_on: bool) -> MidiData {
+        MidiNote {is_on, channel: 0, note_number: 60, velocity: 100}.into()
+    }
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]])
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+    }
+
+    #[test]
+    fn events_land_on_their_frame() {
+        let render = OfflineRender {sample_rate: 48000.0, block_size: 64, channel_count: 2};
+        let events = [
+            OfflineMidiEvent::at_frame(100, note(true)),
+            OfflineMidiEvent::at_seconds(250.0 / 48000.0, 48000.0, note(false)),
+        ];
+        let output = render.render(&mut Tone::default(), &events, 300);
+        assert_eq!(output.frame_count(), 300);
+        for c in 0..2 {
+            let channel = output.channel(c);
+            assert!(channel[0..100].iter().all( | s | *s == 0.0));
+            assert!(channel[100..250].iter().all( | s | *s == 0.5));
+            assert!(channel[250..300].iter().all( | s | *s == 0.0));
+        }
+    }
+
+    #[test]
+    fn writes_a_pcm_wav_of_the_render() {
+        let render = OfflineRender {sample_rate: 44100.0, block_size: 2, channel_count: 2};
+        let output = render.render(&mut Tone::default(), &[OfflineMidiEvent::at_frame(1, note(true))], 3);
+        let mut bytes = Vec::new();
+        write_wav(&mut bytes, &output, 44100, WavFormat::Int16).unwrap();
+
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(&bytes, 4), 36 + 12);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u32_at(&bytes, 16), 16);
+        assert_eq!(u16_at(&bytes, 20), 1);
+        assert_eq!(u16_at(&bytes, 22), 2);
+        assert_eq!(u32_at(&bytes, 24), 44100);
+        assert_eq!(u32_at(&bytes, 28), 44100 * 4);
+        assert_eq!(u16_at(&bytes, 32), 4);
+        assert_eq!(u16_at(&bytes, 34), 16);
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32_at(&bytes, 40), 12);
+        assert_eq!(bytes.len(), 44 + 12);
+        let half = (0.5 * i16::MAX as f32) as i16;
+        let samples: Vec<i16> = bytes[44..].chunks(2).map( | b | i16::from_le_bytes([b[0], b[1]])).collect();
+        assert_eq!(samples, [0, 0, half, half, half, half]);
+    }
+
+    #[test]
+    fn float_wavs_carry_a_fact_chunk_and_odd_data_is_padded() {
+        let mut buffer = AudioBuffer::new_with_size(5, 1);
+        let mut bytes = Vec::new();
+        write_wav(&mut bytes, &buffer, 48000, WavFormat::Float32).unwrap();
+        assert_eq!(u32_at(&bytes, 16), 18);
+        assert_eq!(u16_at(&bytes, 20), 3);
+        assert_eq!(u16_at(&bytes, 36), 0);
+        assert_eq!(&bytes[38..42], b"fact");
+        assert_eq!(u32_at(&bytes, 46), 5);
+        assert_eq!(&bytes[50..54], b"data");
+        assert_eq!(u32_at(&bytes, 54), 20);
+        assert_eq!(u32_at(&bytes, 4) as usize, bytes.len() - 8);
+
+        buffer.resize(3, 1);
+        bytes.clear();
+        write_wav(&mut bytes, &buffer, 48000, WavFormat::Int24).unwrap();
+        assert_eq!(u32_at(&bytes, 40), 9);
+        assert_eq!(bytes.len(), 44 + 9 + 1);
+        assert_eq!(u32_at(&bytes, 4) as usize, bytes.len() - 8);
+    }
+
+    #[test]
+    fn refuses_frame_counts_past_the_32_bit_sizes() {
+        for format in [WavFormat::Int16, WavFormat::Int24, WavFormat::Float32] {
+            let max = max_wav_frames(2, format);
+            let mut bytes = Vec::new();
+            write_wav_header(&mut bytes, 2, 48000, format, max).unwrap();
+            // the riff size still fits
+            assert!(u32_at(&bytes, 4) as u64 >= max as u64 * 2 * (format.bits_per_sample() as u64 / 8));
+            bytes.clear();
+            let err = write_wav_header(&mut bytes, 2, 48000, format, max + 1).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert!(bytes.is_empty());
+        }
+    }
+}
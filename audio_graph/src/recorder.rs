@@ -0,0 +1,423 @@
+use {
+    crate::{
+        makepad_platform::*,
+        offline_render::{WavFormat, max_wav_frames, write_wav_header, write_wav_samples, write_wav_padding},
+        effects::gain_meter::{LevelMeter, MeterLevels},
+    },
+    std::{
+        cell::UnsafeCell,
+        fs::File,
+        io::{self, Write, Seek, SeekFrom, BufWriter},
+        path::Path,
+        sync::Arc,
+        sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        thread::JoinHandle,
+        time::Duration,
+    },
+};
+
+/// How a recording is written and metered
+#[derive(Clone, Debug)]
+pub struct RecorderSettings {
+    pub sample_rate: u32,
+    pub channel_count: usize,
+    pub format: WavFormat,
+    /// Seconds of audio the audio thread can run ahead of the writer thread before blocks get dropped
+    pub buffer_seconds: f32,
+    /// Seconds per meter reading
+    pub meter_interval: f32,
+}
+
+impl RecorderSettings {
+    pub fn new(sample_rate: u32, channel_count: usize) -> Self {
+        Self {
+            sample_rate,
+            channel_count,
+            format: WavFormat::Float32,
+            buffer_seconds: 2.0,
+            meter_interval: 0.05,
+        }
+    }
+}
+
+/// What ended up in the file when a recording stops
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecordingSummary {
+    pub frame_count: u64,
+    /// Frames that did not fit the buffer because the writer fell behind
+    pub dropped_frames: u64,
+}
+
+/// A single producer single consumer ring of samples. The audio thread only ever moves the
+/// write position and the writer thread the read position, so neither side takes a lock.
+struct SampleRing {
+    data: Box<[UnsafeCell<f32>]>,
+    write: AtomicUsize,
+    read: AtomicUsize,
+}
+
+unsafe impl Sync for SampleRing {}
+
+impl SampleRing {
+    fn new(capacity: usize) -> Self {
+        Self {
+            data: (0..capacity.max(1)).map( | _ | UnsafeCell::new(0.0)).collect(),
+            write: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+        }
+    }
+
+    /// Only called from the producer. Writes all samples or none of them.
+    fn push(&self, samples: &[f32]) -> bool {
+        let write = self.write.load(Ordering::Relaxed);
+        let read = self.read.load(Ordering::Acquire);
+        let capacity = self.data.len();
+        if samples.len() > capacity - write.wrapping_sub(read) {
+            return false
+        }
+        for (i, sample) in samples.iter().enumerate() {
+            unsafe {*self.data[write.wrapping_add(i) % capacity].get() = *sample;}
+        }
+        self.write.store(write.wrapping_add(samples.len()), Ordering::Release);
+        true
+    }
+
+    /// Only called from the consumer. Appends everything that is available.
+    fn pop(&self, out: &mut Vec<f32>) {
+        let read = self.read.load(Ordering::Relaxed);
+        let write = self.write.load(Ordering::Acquire);
+        let capacity = self.data.len();
+        let mut pos = read;
+        while pos != write {
+            out.push(unsafe {*self.data[pos % capacity].get()});
+            pos = pos.wrapping_add(1);
+        }
+        self.read.store(write, Ordering::Release);
+    }
+}
+
+struct RecorderShared {
+    ring: SampleRing,
+    stop: AtomicBool,
+    dropped_frames: AtomicU64,
+    written_frames: AtomicU64,
+}
+
+/// The audio thread end of a recording, meant to be moved into a `CxMediaApi::audio_input` callback.
+/// Pushing never blocks or allocates after the first block.
+pub struct RecorderInput {
+    shared: Arc<RecorderShared>,
+    channel_count: usize,
+    interleaved: Vec<f32>,
+    meter: LevelMeter,
+    to_ui: ToUISender<MeterLevels>,
+}
+
+impl RecorderInput {
+    /// Records a block of input. Missing channels are recorded as silence and extra channels are left out.
+    pub fn push(&mut self, buffer: &AudioBuffer) {
+        if self.shared.stop.load(Ordering::Relaxed) || buffer.channel_count() == 0 {
+            return
+        }
+        let frame_count = buffer.frame_count();
+        self.interleaved.resize(frame_count * self.channel_count, 0.0);
+        for c in 0..self.channel_count {
+            let frames = self.interleaved.chunks_exact_mut(self.channel_count);
+            if c < buffer.channel_count() {
+                for (frame, sample) in frames.zip(buffer.channel(c)) {
+                    frame[c] = *sample;
+                }
+            }
+            else {
+                for frame in frames {
+                    frame[c] = 0.0;
+                }
+            }
+        }
+        if !self.shared.ring.push(&self.interleaved) {
+            self.shared.dropped_frames.fetch_add(frame_count as u64, Ordering::Relaxed);
+        }
+        // mono input shows up on both sides of the meter
+        let left = buffer.channel(0);
+        let right = buffer.channel(1.min(buffer.channel_count() - 1));
+        let to_ui = &self.to_ui;
+        self.meter.process(left, right, | levels | {
+            let _ = to_ui.send(levels);
+        });
+    }
+}
+
+/// Streams audio input to a wav file on a writer thread, and collects the meter readings of the input.
+/// The file is complete once the recorder is stopped or dropped.
+/// A wav file stops short of 4GB, frames past `max_wav_frames` are counted as dropped.
+pub struct AudioRecorder {
+    shared: Arc<RecorderShared>,
+    writer: Option<JoinHandle<io::Result<u64 >>>,
+    to_ui: ToUIReceiver<MeterLevels>,
+    levels: MeterLevels,
+}
+
+impl AudioRecorder {
+    /// Creates the wav file and starts the writer thread
+    pub fn start(path: impl AsRef<Path>, settings: RecorderSettings) -> io::Result<(Self, RecorderInput)> {
+        Self::start_with_writer(BufWriter::new(File::create(path)?), settings)
+    }
+
+    pub fn start_with_writer<W: Write + Seek + Send + 'static>(mut writer: W, settings: RecorderSettings) -> io::Result<(Self, RecorderInput)> {
+        if settings.channel_count == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "a recording needs at least one channel"))
+        }
+        write_wav_header(&mut writer, settings.channel_count, settings.sample_rate, settings.format, 0)?;
+        let ring_frames = (settings.sample_rate as f32 * settings.buffer_seconds) as usize;
+        let shared = Arc::new(RecorderShared {
+            ring: SampleRing::new(ring_frames * settings.channel_count),
+            stop: AtomicBool::new(false),
+            dropped_frames: AtomicU64::new(0),
+            written_frames: AtomicU64::new(0),
+        });
+        let to_ui = ToUIReceiver::default();
+        let input = RecorderInput {
+            shared: shared.clone(),
+            channel_count: settings.channel_count,
+            interleaved: Vec::new(),
+            meter: LevelMeter::new(settings.sample_rate as f32, settings.meter_interval),
+            to_ui: to_ui.sender(),
+        };
+        let writer_shared = shared.clone();
+        let writer = std::thread::spawn(move || write_recording(writer, &settings, &writer_shared));
+        Ok((Self {
+            shared,
+            writer: Some(writer),
+            to_ui,
+            levels: MeterLevels::default(),
+        }, input))
+    }
+
+    /// Takes in the meter readings that came in from the audio thread, returns true if there were any.
+    /// When several readings queued up the loudest peak is kept, so short peaks still show.
+    /// Call it on `Event::Signal`.
+    pub fn update_levels(&mut self) -> bool {
+        let mut merged: Option<MeterLevels> = None;
+        while let Ok(levels) = self.to_ui.try_recv() {
+            merged = Some(match merged {
+                Some(prev) => MeterLevels {
+                    peak: [prev.peak[0].max(levels.peak[0]), prev.peak[1].max(levels.peak[1])],
+                    rms: levels.rms,
+                },
+                None => levels
+            });
+        }
+        if let Some(levels) = merged {
+            self.levels = levels;
+            return true
+        }
+        false
+    }
+
+    /// The latest meter reading
+    pub fn levels(&self) -> MeterLevels {
+        self.levels
+    }
+
+    /// Frames on disk so far
+    pub fn written_frames(&self) -> u64 {
+        self.shared.written_frames.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped_frames(&self) -> u64 {
+        self.shared.dropped_frames.load(Ordering::Relaxed)
+    }
+
+    /// Writes out what is still buffered and finishes the wav header.
+    /// Blocks pushed after this are ignored.
+    pub fn stop(mut self) -> io::Result<RecordingSummary> {
+        let frame_count = self.finish()?;
+        Ok(RecordingSummary {
+            frame_count,
+            dropped_frames: self.dropped_frames(),
+        })
+    }
+
+    fn finish(&mut self) -> io::Result<u64> {
+        self.shared.stop.store(true, Ordering::SeqCst);
+        match self.writer.take() {
+            Some(writer) => writer.join().unwrap_or_else( | _ | Err(io::Error::other("recorder writer thread panicked"))),
+            None => Ok(self.written_frames())
+        }
+    }
+}
+
+impl Drop for AudioRecorder {
+    fn drop(&mut self) {
+        if let Err(err) = self.finish() {
+            error!("Audio recording not finished: {}", err);
+        }
+    }
+}
+
+fn write_recording<W: Write + Seek>(mut writer: W, settings: &RecorderSettings, shared: &RecorderShared) -> io::Result<u64> {
+    let mut samples = Vec::new();
+    let mut frame_count = 0;
+    let max_frames = max_wav_frames(settings.channel_count, settings.format);
+    loop {
+        // check before draining, so everything pushed before the stop still gets written
+        let stopping = shared.stop.load(Ordering::SeqCst);
+        shared.ring.pop(&mut samples);
+        if !samples.is_empty() {
+            let frames = samples.len() / settings.channel_count;
+            let fits = frames.min(max_frames - frame_count);
+            if fits < frames {
+                shared.dropped_frames.fetch_add((frames - fits) as u64, Ordering::Relaxed);
+            }
+            write_wav_samples(&mut writer, &samples[0..fits * settings.channel_count], settings.format)?;
+            frame_count += fits;
+            shared.written_frames.store(frame_count as u64, Ordering::Relaxed);
+            samples.clear();
+        }
+        if stopping {
+            break
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    write_wav_padding(&mut writer, settings.channel_count, settings.format, frame_count)?;
+    writer.seek(SeekFrom::Start(0))?;
+    write_wav_header(&mut writer, settings.channel_count, settings.sample_rate, settings.format, frame_count)?;
+    writer.flush()?;
+    Ok(frame_count as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::makepad_audio_formats::decode_audio;
+
+    const SAMPLE_RATE: u32 = 48000;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("makepad_recorder_{}_{}.wav", name, std::process::id()))
+    }
+
+    fn sine_block(offset: usize, frame_count: usize, channel_count: usize, amplitude: f32) -> AudioBuffer {
+        let mut buffer = AudioBuffer::new_with_size(frame_count, channel_count);
+        for c in 0..channel_count {
+            for (i, s) in buffer.channel_mut(c).iter_mut().enumerate() {
+                let t = (offset + i) as f32 / SAMPLE_RATE as f32;
+                *s = amplitude * (t * 1000.0 * std::f32::consts::TAU).sin() / (c + 1) as f32;
+            }
+        }
+        buffer
+    }
+
+    #[test]
+    fn records_synthetic_input_to_wav() {
+        let path = temp_path("sine");
+        let (recorder, mut input) = AudioRecorder::start(&path, RecorderSettings::new(SAMPLE_RATE, 2)).unwrap();
+        let mut expected = Vec::new();
+        for block in 0..100 {
+            let buffer = sine_block(block * 480, 480, 2, 0.5);
+            let mut interleaved = vec![0.0; 960];
+            buffer.copy_to_interleaved(&mut interleaved);
+            expected.extend(interleaved);
+            input.push(&buffer);
+        }
+        let summary = recorder.stop().unwrap();
+        assert_eq!(summary, RecordingSummary {frame_count: 48000, dropped_frames: 0});
+
+        let (info, samples) = decode_audio(&std::fs::read(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(info.sample_rate, SAMPLE_RATE);
+        assert_eq!(info.channel_count, 2);
+        assert_eq!(info.frame_count, Some(48000));
+        assert_eq!(samples, expected);
+    }
+
+    #[test]
+    fn int16_recording_of_odd_length() {
+        let path = temp_path("int16");
+        let mut settings = RecorderSettings::new(SAMPLE_RATE, 1);
+        settings.format = WavFormat::Int16;
+        let (recorder, mut input) = AudioRecorder::start(&path, settings).unwrap();
+        input.push(&sine_block(0, 333, 1, 0.25));
+        // a stereo block into a mono recording keeps the first channel
+        input.push(&sine_block(333, 100, 2, 0.25));
+        assert_eq!(recorder.stop().unwrap().frame_count, 433);
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // 44 byte header, 866 bytes of samples
+        assert_eq!(data.len(), 44 + 866);
+        let (info, samples) = decode_audio(&data).unwrap();
+        assert_eq!(info.bits_per_sample, 16);
+        assert_eq!(samples.len(), 433);
+        let reference = sine_block(0, 433, 1, 0.25);
+        for (s, r) in samples.iter().zip(reference.channel(0)) {
+            assert!((s - r).abs() < 1.0 / 16384.0);
+        }
+    }
+
+    #[test]
+    fn full_buffer_drops_whole_blocks() {
+        // no writer thread, the test drains the ring itself so nothing depends on timing
+        let shared = Arc::new(RecorderShared {
+            ring: SampleRing::new(480 * 2),
+            stop: AtomicBool::new(false),
+            dropped_frames: AtomicU64::new(0),
+            written_frames: AtomicU64::new(0),
+        });
+        let to_ui = ToUIReceiver::default();
+        let mut input = RecorderInput {
+            shared: shared.clone(),
+            channel_count: 2,
+            interleaved: Vec::new(),
+            meter: LevelMeter::new(SAMPLE_RATE as f32, 0.05),
+            to_ui: to_ui.sender(),
+        };
+        let mut out = Vec::new();
+        input.push(&sine_block(0, 256, 2, 0.5));
+        input.push(&sine_block(256, 256, 2, 0.5));
+        assert_eq!(shared.dropped_frames.load(Ordering::Relaxed), 256);
+        shared.ring.pop(&mut out);
+        assert_eq!(out.len(), 256 * 2);
+        // once drained the next block fits again
+        input.push(&sine_block(512, 256, 2, 0.5));
+        input.push(&sine_block(768, 256, 2, 0.5));
+        assert_eq!(shared.dropped_frames.load(Ordering::Relaxed), 512);
+        shared.ring.pop(&mut out);
+        assert_eq!(out.len(), 512 * 2);
+    }
+
+    #[test]
+    fn meter_levels_reach_the_ui_side() {
+        let (mut recorder, mut input) = AudioRecorder::start_with_writer(io::Cursor::new(Vec::new()), RecorderSettings::new(SAMPLE_RATE, 2)).unwrap();
+        assert!(!recorder.update_levels());
+        // 1 kHz fits the 50 ms window exactly
+        input.push(&sine_block(0, 2400, 2, 1.0));
+        assert!(recorder.update_levels());
+        let levels = recorder.levels();
+        assert!((levels.peak_db(0) - 0.0).abs() < 0.01);
+        assert!((levels.rms_db(0) + 3.01).abs() < 0.01);
+        assert!((levels.peak_db(1) + 6.02).abs() < 0.01);
+        assert!((levels.rms_db(1) + 9.03).abs() < 0.01);
+        // a loud window followed by a quiet one keeps the loud peak and the quiet rms
+        input.push(&sine_block(0, 2400, 2, 1.0));
+        input.push(&sine_block(0, 2400, 2, 0.1));
+        assert!(recorder.update_levels());
+        let levels = recorder.levels();
+        assert!((levels.peak_db(0) - 0.0).abs() < 0.01);
+        assert!((levels.rms_db(0) + 23.01).abs() < 0.01);
+        recorder.stop().unwrap();
+    }
+
+    #[test]
+    fn ring_keeps_order_across_the_wrap() {
+        let ring = SampleRing::new(8);
+        let mut out = Vec::new();
+        for round in 0..10 {
+            let samples: Vec<f32> = (0..5).map( | i | (round * 5 + i) as f32).collect();
+            assert!(ring.push(&samples));
+            assert!(!ring.push(&samples));
+            ring.pop(&mut out);
+        }
+        assert_eq!(out, (0..50).map( | i | i as f32).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,474 @@
+use {
+    crate::{
+        makepad_platform::*,
+        register_audio_component,
+        audio_traits::*,
+        automation::*,
+    },
+};
+
+live_design!{
+    AudioConnection = {{AudioConnection}} {}
+    Router = {{Router}} {}
+}
+
+/// One port on a node in a router, `input` and `output` name the ports of the router itself
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AudioPort {
+    pub node: LiveId,
+    pub port: usize,
+}
+
+impl AudioPort {
+    pub fn new(node: LiveId, port: usize) -> Self {
+        Self {node, port}
+    }
+}
+
+impl From<LiveId> for AudioPort {
+    fn from(node: LiveId) -> Self {
+        Self {node, port: 0}
+    }
+}
+
+/// A connection declared in the live design of a router
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct AudioConnection {
+    #[live] from: LiveId,
+    #[live] from_port: usize,
+    #[live] to: LiveId,
+    #[live] to_port: usize,
+    #[live(1.0)] gain: f32,
+}
+
+enum FromUI {
+    Connect {from: AudioPort, to: AudioPort, gain: f32},
+    Disconnect {from: AudioPort, to: AudioPort},
+}
+
+/// Routes audio between its child components along arbitrary connections.
+/// Nodes run in dependency order every block, connections that close a cycle
+/// hear the previous block of their source so feedback loops stay stable.
+#[derive(Live)]
+pub struct Router {
+    #[rust] nodes: ComponentMap<LiveId, AudioComponentRef>,
+    #[rust] connections: ComponentMap<LiveId, AudioConnection>,
+    #[rust] from_ui: FromUISender<FromUI>,
+}
+
+impl LiveRegister for Router {
+    fn live_register(cx: &mut Cx) {
+        register_audio_component!(cx, Router)
+    }
+}
+
+impl LiveHook for Router {
+    fn apply_value_instance(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        let id = nodes[index].id;
+        if let LiveValue::Class {live_type, ..} = nodes[index].value {
+            if live_type == LiveType::of::<AudioConnection>() {
+                return self.connections.get_or_insert(cx, id, | cx | {AudioConnection::new(cx)})
+                    .apply(cx, apply, index, nodes)
+            }
+        }
+        self.nodes.get_or_insert(cx, id, | cx | {AudioComponentRef::new(cx)})
+            .apply(cx, apply, index, nodes)
+    }
+
+    fn after_apply(&mut self, _cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if apply.from.is_from_doc() {
+            self.nodes.retain_visible();
+            self.connections.retain_visible();
+        }
+    }
+}
+
+impl Router {
+    pub fn node(&mut self, id: LiveId) -> Option<&mut Box<dyn AudioComponent >> {
+        self.nodes.get_mut(&id).and_then( | node | node.as_mut())
+    }
+
+    /// Adds a connection, or changes the gain of an existing one. The gain ramps over one block
+    pub fn connect(&self, from: impl Into<AudioPort>, to: impl Into<AudioPort>, gain: f32) {
+        let _ = self.from_ui.send(FromUI::Connect {from: from.into(), to: to.into(), gain});
+    }
+
+    /// Fades the connection out over one block before removing it
+    pub fn disconnect(&self, from: impl Into<AudioPort>, to: impl Into<AudioPort>) {
+        let _ = self.from_ui.send(FromUI::Disconnect {from: from.into(), to: to.into()});
+    }
+}
+
+struct Slot {
+    id: LiveId,
+    node: Box<dyn AudioGraphNode + Send>,
+    inputs: Vec<AudioBuffer>,
+    outputs: Vec<AudioBuffer>,
+}
+
+struct Connection {
+    from: AudioPort,
+    to: AudioPort,
+    gain: f32,
+    current: f32,
+    removing: bool,
+}
+
+enum Source {
+    Input(usize),
+    Slot(usize, usize),
+}
+
+struct Node {
+    from_ui: FromUIReceiver<FromUI>,
+    slots: Vec<Slot>,
+    connections: Vec<Connection>,
+    /// The slots in the order they run and where each connection reads from,
+    /// both worked out again only when the routing changes
+    order: Vec<usize>,
+    sources: Vec<Option<Source >>,
+    dirty: bool,
+    // scratch space for `schedule` and the per slot port lists, kept so blocks don't allocate
+    edges: Vec<(usize, usize)>,
+    in_degree: Vec<usize>,
+    done: Vec<bool>,
+    input_refs: Vec<&'static AudioBuffer>,
+    output_refs: Vec<&'static mut AudioBuffer>,
+}
+
+/// Empties `scratch` into a vec of another element type that keeps its allocation. `Vec` collects
+/// in place when the layouts match, which they always do for references to the same type, so the
+/// port lists can borrow a slot for one block without allocating
+fn recycle<T, U>(mut scratch: Vec<T>) -> Vec<U> {
+    scratch.clear();
+    scratch.into_iter().map( | _ | unreachable!()).collect()
+}
+
+impl Node {
+    fn new(from_ui: FromUIReceiver<FromUI>, slots: Vec<Slot>, connections: Vec<Connection>) -> Self {
+        let slot_count = slots.len();
+        let edge_count = connections.len();
+        let port_count = slots.iter().map( | s | s.inputs.len().max(s.outputs.len())).max().unwrap_or(0);
+        Self {
+            from_ui,
+            order: Vec::with_capacity(slot_count),
+            sources: Vec::with_capacity(edge_count),
+            dirty: true,
+            edges: Vec::with_capacity(edge_count),
+            in_degree: Vec::with_capacity(slot_count),
+            done: Vec::with_capacity(slot_count),
+            input_refs: Vec::with_capacity(port_count),
+            output_refs: Vec::with_capacity(port_count),
+            slots,
+            connections,
+        }
+    }
+
+    fn slot_index(&self, id: LiveId) -> Option<usize> {
+        self.slots.iter().position( | s | s.id == id)
+    }
+
+    fn handle_messages(&mut self) {
+        while let Ok(msg) = self.from_ui.try_recv() {
+            match msg {
+                FromUI::Connect {from, to, gain} => {
+                    if let Some(c) = self.connections.iter_mut().find( | c | c.from == from && c.to == to) {
+                        c.gain = gain;
+                        c.removing = false;
+                    }
+                    else {
+                        self.connections.push(Connection {from, to, gain, current: 0.0, removing: false});
+                        self.dirty = true;
+                    }
+                }
+                FromUI::Disconnect {from, to} => {
+                    if let Some(c) = self.connections.iter_mut().find( | c | c.from == from && c.to == to) {
+                        c.gain = 0.0;
+                        c.removing = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Orders the slots so every node runs after its sources, breaking cycles at the earliest slot,
+    /// and looks up the source of every connection
+    fn schedule(&mut self) {
+        let count = self.slots.len();
+        self.edges.clear();
+        self.in_degree.clear();
+        self.in_degree.resize(count, 0);
+        for c in &self.connections {
+            if let (Some(from), Some(to)) = (self.slot_index(c.from.node), self.slot_index(c.to.node)) {
+                self.edges.push((from, to));
+                self.in_degree[to] += 1;
+            }
+        }
+        self.order.clear();
+        self.done.clear();
+        self.done.resize(count, false);
+        while self.order.len() < count {
+            let next = (0..count).find( | &i | !self.done[i] && self.in_degree[i] == 0)
+                .or_else( || (0..count).find( | &i | !self.done[i]))
+                .unwrap();
+            self.done[next] = true;
+            self.order.push(next);
+            for (from, to) in &self.edges {
+                if *from == next && self.in_degree[*to] > 0 {
+                    self.in_degree[*to] -= 1;
+                }
+            }
+        }
+        let mut sources = std::mem::take(&mut self.sources);
+        sources.clear();
+        sources.extend(self.connections.iter().map( | c | self.source(c.from)));
+        self.sources = sources;
+        self.dirty = false;
+    }
+
+    fn source(&self, port: AudioPort) -> Option<Source> {
+        if port.node == live_id!(input) {
+            Some(Source::Input(port.port))
+        }
+        else {
+            self.slot_index(port.node).map( | slot | Source::Slot(slot, port.port))
+        }
+    }
+
+    /// Sums every connection into `to` with the gain ramping from its current to its target value
+    fn mix_into(slots: &[Slot], connections: &mut [Connection], sources: &[Option<Source>], inputs: &[&AudioBuffer], to: LiveId, buffers: &mut [AudioBuffer]) {
+        for buffer in buffers.iter_mut() {
+            buffer.zero();
+        }
+        for (c, source) in connections.iter_mut().zip(sources) {
+            if c.to.node != to || c.to.port >= buffers.len() {
+                continue;
+            }
+            let source = match source {
+                Some(Source::Input(port)) => inputs.get(*port).copied(),
+                Some(Source::Slot(slot, port)) => slots[*slot].outputs.get(*port),
+                None => None
+            };
+            let target = &mut buffers[c.to.port];
+            if let Some(source) = source {
+                let frame_count = target.frame_count().min(source.frame_count());
+                let step = (c.gain - c.current) / frame_count.max(1) as f32;
+                for ch in 0..target.channel_count() {
+                    let from = source.channel(ch.min(source.channel_count().saturating_sub(1)));
+                    let into = target.channel_mut(ch);
+                    let mut gain = c.current;
+                    for i in 0..frame_count {
+                        gain += step;
+                        into[i] += from[i] * gain;
+                    }
+                }
+            }
+            c.current = c.gain;
+        }
+    }
+}
+
+impl AudioGraphNode for Node {
+    fn all_notes_off(&mut self) {
+        for slot in &mut self.slots {
+            slot.node.all_notes_off();
+        }
+    }
+
+    fn handle_midi_data(&mut self, data: MidiData) {
+        for slot in &mut self.slots {
+            slot.node.handle_midi_data(data);
+        }
+    }
+
+    fn handle_automation(&mut self, event: AutomationEvent) {
+        for slot in &mut self.slots {
+            slot.node.handle_automation(event.for_child(slot.id));
+        }
+    }
+
+    fn render_to_audio_buffer(
+        &mut self,
+        info: AudioInfo,
+        outputs: &mut [&mut AudioBuffer],
+        inputs: &[&AudioBuffer],
+        display: &mut DisplayAudioGraph
+    ) {
+        self.handle_messages();
+        if self.dirty {
+            self.schedule();
+        }
+        let like: &AudioBuffer = outputs[0];
+        for slot in &mut self.slots {
+            for buffer in slot.inputs.iter_mut().chain(slot.outputs.iter_mut()) {
+                // a new block size drops the feedback history, which is silence anyway
+                if buffer.frame_count() != like.frame_count() || buffer.channel_count() != like.channel_count() {
+                    buffer.resize_like(like);
+                    buffer.zero();
+                }
+            }
+        }
+        // each connection mixes exactly once per block, from the sources `schedule` looked up
+        for &index in &self.order {
+            let mut slot_inputs = std::mem::take(&mut self.slots[index].inputs);
+            Self::mix_into(&self.slots, &mut self.connections, &self.sources, inputs, self.slots[index].id, &mut slot_inputs);
+            let slot = &mut self.slots[index];
+            slot.inputs = slot_inputs;
+            let mut input_refs = recycle(std::mem::take(&mut self.input_refs));
+            input_refs.extend(slot.inputs.iter());
+            let mut output_refs = recycle(std::mem::take(&mut self.output_refs));
+            output_refs.extend(slot.outputs.iter_mut());
+            slot.node.render_to_audio_buffer(info, &mut output_refs, &input_refs, display);
+            self.input_refs = recycle(input_refs);
+            self.output_refs = recycle(output_refs);
+        }
+        let mut output = std::mem::take(outputs[0]);
+        Self::mix_into(&self.slots, &mut self.connections, &self.sources, inputs, live_id!(output), std::slice::from_mut(&mut output));
+        *outputs[0] = output;
+
+        // connections into nowhere never mixed, they still settle on their gain
+        for c in &mut self.connections {
+            c.current = c.gain;
+        }
+        let before = self.connections.len();
+        self.connections.retain( | c | !c.removing || c.current != 0.0);
+        if self.connections.len() != before {
+            self.dirty = true;
+        }
+    }
+}
+
+impl AudioComponent for Router {
+    fn get_graph_node(&mut self, cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
+        self.from_ui.new_channel();
+        let mut slots = Vec::new();
+        for (id, node) in self.nodes.iter_mut() {
+            if let Some(node) = node.as_mut() {
+                let node = node.get_graph_node(cx);
+                slots.push(Slot {
+                    id: *id,
+                    inputs: (0..node.input_port_count()).map( | _ | AudioBuffer::default()).collect(),
+                    outputs: (0..node.output_port_count()).map( | _ | AudioBuffer::default()).collect(),
+                    node,
+                });
+            }
+        }
+        let connections = self.connections.values().map( | c | Connection {
+            from: AudioPort::new(c.from, c.from_port),
+            to: AudioPort::new(c.to, c.to_port),
+            gain: c.gain,
+            current: c.gain,
+            removing: false,
+        }).collect();
+        Box::new(Node::new(self.from_ui.receiver(), slots, connections))
+    }
+
+    fn handle_event_with(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, AudioComponentAction)) {
+        for node in self.nodes.values_mut() {
+            if let Some(node) = node.as_mut() {
+                node.handle_event_with(cx, event, dispatch_action)
+            }
+        }
+    }
+
+    fn audio_query(&mut self, query: &AudioQuery, callback: &mut Option<AudioQueryCb>) -> AudioResult<'_> {
+        for node in self.nodes.values_mut() {
+            node.audio_query(query, callback) ?;
+        }
+        AudioResult::not_found()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline_render::OfflineRender;
+
+    /// Adds a constant to its input, so the output spells out the path the signal took
+    struct Offset(f32);
+
+    impl AudioGraphNode for Offset {
+        fn handle_midi_data(&mut self, _data: MidiData) {}
+        fn all_notes_off(&mut self) {}
+        fn render_to_audio_buffer(&mut self, _info: AudioInfo, outputs: &mut [&mut AudioBuffer], inputs: &[&AudioBuffer], _display: &mut DisplayAudioGraph) {
+            for (out, input) in outputs[0].channel_mut(0).iter_mut().zip(inputs[0].channel(0)) {
+                *out = self.0 + input;
+            }
+        }
+    }
+
+    fn slot(id: LiveId, offset: f32) -> Slot {
+        Slot {id, node: Box::new(Offset(offset)), inputs: vec![AudioBuffer::default()], outputs: vec![AudioBuffer::default()]}
+    }
+
+    fn connection(from: LiveId, to: LiveId) -> Connection {
+        Connection {from: from.into(), to: to.into(), gain: 1.0, current: 1.0, removing: false}
+    }
+
+    fn router(slots: Vec<Slot>, connections: Vec<Connection>) -> (FromUISender<FromUI>, Node) {
+        let mut from_ui = FromUISender::default();
+        let node = Node::new(from_ui.receiver(), slots, connections);
+        (from_ui, node)
+    }
+
+    fn render(node: &mut Node, frame_count: usize) -> Vec<f32> {
+        let render = OfflineRender {sample_rate: 48000.0, block_size: 4, channel_count: 1};
+        render.render(node, &[], frame_count).channel(0).to_vec()
+    }
+
+    #[test]
+    fn runs_nodes_after_their_sources() {
+        let (_from_ui, mut node) = router(
+            vec![slot(live_id!(c), 100.0), slot(live_id!(b), 10.0), slot(live_id!(a), 1.0)],
+            vec![connection(live_id!(a), live_id!(b)), connection(live_id!(b), live_id!(c)), connection(live_id!(c), live_id!(output))],
+        );
+        assert_eq!(render(&mut node, 4), [111.0; 4]);
+        assert_eq!(node.order, [2, 1, 0]);
+        assert!(!node.dirty);
+    }
+
+    #[test]
+    fn breaks_cycles_with_the_previous_block() {
+        let (_from_ui, mut node) = router(
+            vec![slot(live_id!(a), 1.0), slot(live_id!(b), 10.0)],
+            vec![connection(live_id!(a), live_id!(b)), connection(live_id!(b), live_id!(a)), connection(live_id!(b), live_id!(output))],
+        );
+        // a runs first and hears b one block late: 0 + 1 + 10, then 11 + 1 + 10
+        assert_eq!(render(&mut node, 8), [11.0, 11.0, 11.0, 11.0, 22.0, 22.0, 22.0, 22.0]);
+        assert_eq!(node.order, [0, 1]);
+    }
+
+    #[test]
+    fn gain_ramps_over_one_block() {
+        let (from_ui, mut node) = router(vec![slot(live_id!(a), 1.0)], vec![]);
+        assert_eq!(render(&mut node, 4), [0.0; 4]);
+        from_ui.send(FromUI::Connect {from: live_id!(a).into(), to: live_id!(output).into(), gain: 1.0}).unwrap();
+        assert_eq!(render(&mut node, 8), [0.25, 0.5, 0.75, 1.0, 1.0, 1.0, 1.0, 1.0]);
+        from_ui.send(FromUI::Connect {from: live_id!(a).into(), to: live_id!(output).into(), gain: 0.5}).unwrap();
+        assert_eq!(render(&mut node, 4), [0.875, 0.75, 0.625, 0.5]);
+    }
+
+    #[test]
+    fn disconnect_fades_out_then_removes() {
+        let (from_ui, mut node) = router(vec![slot(live_id!(a), 1.0)], vec![connection(live_id!(a), live_id!(output))]);
+        assert_eq!(render(&mut node, 4), [1.0; 4]);
+        from_ui.send(FromUI::Disconnect {from: live_id!(a).into(), to: live_id!(output).into()}).unwrap();
+        assert_eq!(render(&mut node, 4), [0.75, 0.5, 0.25, 0.0]);
+        assert!(node.connections.is_empty());
+        assert!(node.dirty);
+        assert_eq!(render(&mut node, 4), [0.0; 4]);
+        assert!(node.sources.is_empty());
+    }
+
+    #[test]
+    fn recycle_keeps_the_allocation() {
+        let buffer = AudioBuffer::default();
+        let mut refs: Vec<&AudioBuffer> = Vec::with_capacity(8);
+        refs.push(&buffer);
+        let ptr = refs.as_ptr() as usize;
+        let scratch: Vec<&'static AudioBuffer> = recycle(refs);
+        assert!(scratch.is_empty());
+        assert_eq!(scratch.capacity(), 8);
+        assert_eq!(scratch.as_ptr() as usize, ptr);
+    }
+}
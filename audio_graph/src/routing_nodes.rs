@@ -0,0 +1,281 @@
+use {
+    crate::{
+        makepad_platform::*,
+        register_audio_component,
+        audio_traits::*,
+        automation::*,
+    },
+};
+
+live_design!{
+    Gain = {{Gain}} {}
+    Pan = {{Pan}} {}
+    Bus = {{Bus}} {}
+}
+
+enum FromUI {
+    Gain(f32),
+    Pan(f32),
+    Mute(bool),
+}
+
+/// Gain changes glide over about 5ms, these nodes don't know the device rate so it is counted at 48kHz
+fn smoothed(value: f32) -> AutomatedParam {
+    AutomatedParam::new(value, 0.005, 48000.0)
+}
+
+/// Scales each channel by `gain(channel, value)` with a new value from `param` every frame
+fn apply_param(param: &mut AutomatedParam, info: &AudioInfo, clock: &mut f64, buffer: &mut AudioBuffer, gain: impl Fn(usize, f32) -> f32) {
+    param.set_time(block_time(info, clock, buffer.frame_count()));
+    let channel_count = buffer.channel_count();
+    for i in 0..buffer.frame_count() {
+        let value = param.tick();
+        for c in 0..channel_count {
+            buffer.channel_mut(c)[i] *= gain(c, value);
+        }
+    }
+}
+
+/// Copies the first input to the first output, the router sums all connections into that input
+fn copy_input(outputs: &mut [&mut AudioBuffer], inputs: &[&AudioBuffer]) {
+    let output = &mut outputs[0];
+    match inputs.first() {
+        Some(input) => {
+            for c in 0..output.channel_count() {
+                let from = input.channel(c.min(input.channel_count().saturating_sub(1)));
+                let frame_count = output.frame_count();
+                output.channel_mut(c).copy_from_slice(&from[0..frame_count]);
+            }
+        }
+        None => output.zero()
+    }
+}
+
+#[derive(Live)]
+pub struct Gain {
+    #[live(1.0)] gain: f32,
+    #[rust] from_ui: FromUISender<FromUI>,
+}
+
+impl LiveHook for Gain {}
+
+impl Gain {
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+        let _ = self.from_ui.send(FromUI::Gain(gain));
+    }
+}
+
+struct GainNode {
+    from_ui: FromUIReceiver<FromUI>,
+    gain: AutomatedParam,
+    clock: f64,
+}
+
+impl AudioGraphNode for GainNode {
+    fn handle_midi_data(&mut self, _data: MidiData) {}
+    fn all_notes_off(&mut self) {}
+
+    fn handle_automation(&mut self, event: AutomationEvent) {
+        if event.is_for(live_id!(gain)) {
+            self.gain.schedule(event);
+        }
+    }
+
+    fn render_to_audio_buffer(&mut self, info: AudioInfo, outputs: &mut [&mut AudioBuffer], inputs: &[&AudioBuffer], _display: &mut DisplayAudioGraph) {
+        while let Ok(msg) = self.from_ui.try_recv() {
+            if let FromUI::Gain(gain) = msg {
+                self.gain.set(gain);
+            }
+        }
+        copy_input(outputs, inputs);
+        apply_param(&mut self.gain, &info, &mut self.clock, outputs[0], | _, gain | gain);
+    }
+}
+
+impl LiveRegister for Gain {
+    fn live_register(cx: &mut Cx) {
+        register_audio_component!(cx, Gain)
+    }
+}
+
+impl AudioComponent for Gain {
+    fn get_graph_node(&mut self, _cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
+        self.from_ui.new_channel();
+        Box::new(GainNode {
+            from_ui: self.from_ui.receiver(),
+            gain: smoothed(self.gain),
+            clock: 0.0,
+        })
+    }
+
+    fn handle_event_with(&mut self, _cx: &mut Cx, _event: &Event, _dispatch_action: &mut dyn FnMut(&mut Cx, AudioComponentAction)) {
+    }
+
+    fn audio_query(&mut self, _query: &AudioQuery, _callback: &mut Option<AudioQueryCb>) -> AudioResult<'_> {
+        AudioResult::not_found()
+    }
+}
+
+/// Places a stereo signal between the speakers, the far side fades along an equal power curve
+#[derive(Live)]
+pub struct Pan {
+    /// -1 is hard left, 1 is hard right
+    #[live] pan: f32,
+    #[rust] from_ui: FromUISender<FromUI>,
+}
+
+impl LiveHook for Pan {}
+
+impl Pan {
+    pub fn set_pan(&mut self, pan: f32) {
+        self.pan = pan;
+        let _ = self.from_ui.send(FromUI::Pan(pan));
+    }
+}
+
+struct PanNode {
+    from_ui: FromUIReceiver<FromUI>,
+    pan: AutomatedParam,
+    clock: f64,
+}
+
+impl AudioGraphNode for PanNode {
+    fn handle_midi_data(&mut self, _data: MidiData) {}
+    fn all_notes_off(&mut self) {}
+
+    fn handle_automation(&mut self, event: AutomationEvent) {
+        if event.is_for(live_id!(pan)) {
+            self.pan.schedule(event);
+        }
+    }
+
+    fn render_to_audio_buffer(&mut self, info: AudioInfo, outputs: &mut [&mut AudioBuffer], inputs: &[&AudioBuffer], _display: &mut DisplayAudioGraph) {
+        while let Ok(msg) = self.from_ui.try_recv() {
+            if let FromUI::Pan(pan) = msg {
+                self.pan.set(pan.clamp(-1.0, 1.0));
+            }
+        }
+        copy_input(outputs, inputs);
+        apply_param(&mut self.pan, &info, &mut self.clock, outputs[0], | channel, pan | {
+            // the centre leaves both sides alone
+            let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+            match channel {
+                0 => (angle.cos() * std::f32::consts::SQRT_2).min(1.0),
+                1 => (angle.sin() * std::f32::consts::SQRT_2).min(1.0),
+                _ => 1.0
+            }
+        });
+    }
+}
+
+impl LiveRegister for Pan {
+    fn live_register(cx: &mut Cx) {
+        register_audio_component!(cx, Pan)
+    }
+}
+
+impl AudioComponent for Pan {
+    fn get_graph_node(&mut self, _cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
+        self.from_ui.new_channel();
+        Box::new(PanNode {
+            from_ui: self.from_ui.receiver(),
+            pan: smoothed(self.pan.clamp(-1.0, 1.0)),
+            clock: 0.0,
+        })
+    }
+
+    fn handle_event_with(&mut self, _cx: &mut Cx, _event: &Event, _dispatch_action: &mut dyn FnMut(&mut Cx, AudioComponentAction)) {
+    }
+
+    fn audio_query(&mut self, _query: &AudioQuery, _callback: &mut Option<AudioQueryCb>) -> AudioResult<'_> {
+        AudioResult::not_found()
+    }
+}
+
+/// A named summing point for sends and submixes, with a master gain and mute
+#[derive(Live)]
+pub struct Bus {
+    #[live(1.0)] gain: f32,
+    #[live] mute: bool,
+    #[rust] from_ui: FromUISender<FromUI>,
+}
+
+impl LiveHook for Bus {}
+
+impl Bus {
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+        let _ = self.from_ui.send(FromUI::Gain(gain));
+    }
+
+    pub fn set_mute(&mut self, mute: bool) {
+        self.mute = mute;
+        let _ = self.from_ui.send(FromUI::Mute(mute));
+    }
+}
+
+struct BusNode {
+    from_ui: FromUIReceiver<FromUI>,
+    gain: AutomatedParam,
+    /// 0 when muted, 1 otherwise
+    level: AutomatedParam,
+    clock: f64,
+}
+
+impl AudioGraphNode for BusNode {
+    fn handle_midi_data(&mut self, _data: MidiData) {}
+    fn all_notes_off(&mut self) {}
+
+    fn handle_automation(&mut self, event: AutomationEvent) {
+        if event.is_for(live_id!(gain)) {
+            self.gain.schedule(event);
+        }
+    }
+
+    fn render_to_audio_buffer(&mut self, info: AudioInfo, outputs: &mut [&mut AudioBuffer], inputs: &[&AudioBuffer], _display: &mut DisplayAudioGraph) {
+        while let Ok(msg) = self.from_ui.try_recv() {
+            match msg {
+                FromUI::Gain(gain) => self.gain.set(gain),
+                FromUI::Mute(mute) => self.level.set(if mute {0.0} else {1.0}),
+                FromUI::Pan(_) => ()
+            }
+        }
+        copy_input(outputs, inputs);
+        let output = &mut outputs[0];
+        let time = block_time(&info, &mut self.clock, output.frame_count());
+        self.gain.set_time(time);
+        self.level.set_time(time);
+        for i in 0..output.frame_count() {
+            let gain = self.gain.tick() * self.level.tick();
+            for c in 0..output.channel_count() {
+                output.channel_mut(c)[i] *= gain;
+            }
+        }
+    }
+}
+
+impl LiveRegister for Bus {
+    fn live_register(cx: &mut Cx) {
+        register_audio_component!(cx, Bus)
+    }
+}
+
+impl AudioComponent for Bus {
+    fn get_graph_node(&mut self, _cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
+        self.from_ui.new_channel();
+        Box::new(BusNode {
+            from_ui: self.from_ui.receiver(),
+            gain: smoothed(self.gain),
+            level: smoothed(if self.mute {0.0} else {1.0}),
+            clock: 0.0,
+        })
+    }
+
+    fn handle_event_with(&mut self, _cx: &mut Cx, _event: &Event, _dispatch_action: &mut dyn FnMut(&mut Cx, AudioComponentAction)) {
+    }
+
+    fn audio_query(&mut self, _query: &AudioQuery, _callback: &mut Option<AudioQueryCb>) -> AudioResult<'_> {
+        AudioResult::not_found()
+    }
+}
@@ -0,0 +1,408 @@
+use {
+    crate::{
+        makepad_platform::*,
+        makepad_audio_formats::decode_audio,
+        register_audio_component,
+        audio_traits::*
+    },
+    std::sync::Arc,
+};
+
+live_design!{
+    SampleZone = {{SampleZone}} {}
+    SamplePlayer = {{SamplePlayer}} {}
+}
+
+#[derive(Live, LiveHook, Clone, Copy, Debug, PartialEq)]
+#[live_ignore]
+/// OneShot plays to the end and ignores note off, Sustain fades out on note off
+/// and Loop repeats between the loop points until the release has faded out
+pub enum SampleLoopMode {
+    #[pick] OneShot,
+    Sustain,
+    Loop,
+}
+
+/// The decoded file, interleaved at its own rate
+struct SampleSource {
+    sample_rate: u32,
+    channel_count: usize,
+    data: Vec<f32>,
+}
+
+/// One sample mapped onto a key and velocity range, overlapping zones play together
+#[derive(Live, LiveRegister)]
+pub struct SampleZone {
+    #[live] source: LiveDependency,
+    #[live(60)] root_key: i64,
+    #[live(0)] low_key: i64,
+    #[live(127)] high_key: i64,
+    #[live(1)] low_velocity: i64,
+    #[live(127)] high_velocity: i64,
+    #[live(1.0)] gain: f32,
+    #[live] loop_mode: SampleLoopMode,
+    /// Loop points in frames of the source file, an end of 0 loops to the end of the sample
+    #[live(0)] loop_start: i64,
+    #[live(0)] loop_end: i64,
+
+    #[rust] loaded: String,
+    #[rust] sample: Option<Arc<SampleSource>>,
+}
+
+impl LiveHook for SampleZone {
+    fn after_apply(&mut self, cx: &mut Cx, _apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        let path = self.source.as_str();
+        if path == self.loaded {
+            return
+        }
+        self.loaded = path.to_string();
+        self.sample = None;
+        if path.is_empty() {
+            return
+        }
+        match cx.get_dependency(path) {
+            Ok(data) => match decode_audio(&data) {
+                Ok((info, data)) => {
+                    self.sample = Some(Arc::new(SampleSource {
+                        sample_rate: info.sample_rate,
+                        channel_count: info.channel_count,
+                        data
+                    }));
+                }
+                Err(err) => error!("SampleZone: cannot decode {}: {}", path, err)
+            }
+            Err(err) => error!("SampleZone: cannot load {}: {}", path, err)
+        }
+    }
+}
+
+#[derive(Live)]
+pub struct SamplePlayer {
+    #[live(16)] max_voices: i64,
+    /// Seconds to fade out after note off
+    #[live(0.05)] release: f64,
+    /// 0 plays every velocity at full gain, 1 scales the gain linearly with velocity
+    #[live(1.0)] velocity_sensitivity: f32,
+    #[rust] zones: ComponentMap<LiveId, SampleZone>,
+}
+
+impl LiveRegister for SamplePlayer {
+    fn live_register(cx: &mut Cx) {
+        register_audio_component!(cx, SamplePlayer)
+    }
+}
+
+impl LiveHook for SamplePlayer {
+    fn apply_value_instance(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        self.zones.get_or_insert(cx, nodes[index].id, | cx | {SampleZone::new(cx)})
+            .apply(cx, apply, index, nodes)
+    }
+
+    fn after_apply(&mut self, _cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if apply.from.is_from_doc() {
+            self.zones.retain_visible();
+        }
+    }
+}
+
+struct Zone {
+    keys: (u8, u8),
+    velocities: (u8, u8),
+    root_key: u8,
+    gain: f32,
+    loop_mode: SampleLoopMode,
+    loop_start: f64,
+    loop_end: f64,
+    /// The rate of the file, playback converts it to the device rate as it goes
+    sample_rate: f64,
+    buffer: Arc<AudioBuffer>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Voice {
+    active: bool,
+    /// Counts up with every note on, the lowest active one is the oldest
+    started: u64,
+    zone: usize,
+    note: u8,
+    pos: f64,
+    /// Playback speed from the key, before the rate conversion
+    pitch: f64,
+    gain: f32,
+    /// Fades from 1 to 0 after note off
+    level: f32,
+    releasing: bool,
+}
+
+struct Node {
+    zones: Vec<Zone>,
+    /// A fixed pool of `max_voices`, so the audio thread never allocates
+    voices: Vec<Voice>,
+    voice_counter: u64,
+    release: f64,
+    velocity_sensitivity: f32,
+}
+
+impl Node {
+    fn note_on(&mut self, note: u8, velocity: u8) {
+        self.note_off(note);
+        for (index, zone) in self.zones.iter().enumerate() {
+            if note < zone.keys.0 || note > zone.keys.1 || velocity < zone.velocities.0 || velocity > zone.velocities.1 {
+                continue;
+            }
+            // take a free voice or steal the oldest
+            let slot = self.voices.iter().position( | voice | !voice.active).unwrap_or_else( || {
+                self.voices.iter().enumerate().min_by_key( | (_, voice) | voice.started).map( | (slot, _) | slot).unwrap()
+            });
+            let velocity_gain = 1.0 - self.velocity_sensitivity + self.velocity_sensitivity * velocity as f32 / 127.0;
+            self.voice_counter += 1;
+            self.voices[slot] = Voice {
+                active: true,
+                started: self.voice_counter,
+                zone: index,
+                note,
+                pos: 0.0,
+                pitch: 2.0f64.powf((note as f64 - zone.root_key as f64) / 12.0),
+                gain: zone.gain * velocity_gain,
+                level: 1.0,
+                releasing: false,
+            };
+        }
+    }
+
+    fn note_off(&mut self, note: u8) {
+        for voice in &mut self.voices {
+            if voice.active && voice.note == note && self.zones[voice.zone].loop_mode != SampleLoopMode::OneShot {
+                voice.releasing = true;
+            }
+        }
+    }
+}
+
+// cubic hermite between y1 and y2, the same curve the resampler uses
+fn hermite(y0: f32, y1: f32, y2: f32, y3: f32, t: f32) -> f32 {
+    let a = -0.5 * y0 + 1.5 * y1 - 1.5 * y2 + 0.5 * y3;
+    let b = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+    let c = -0.5 * y0 + 0.5 * y2;
+    ((a * t + b) * t + c) * t + y1
+}
+
+impl AudioGraphNode for Node {
+    fn all_notes_off(&mut self) {
+        for voice in &mut self.voices {
+            voice.active = false;
+        }
+    }
+
+    fn handle_midi_data(&mut self, data: MidiData) {
+        if let MidiEvent::Note(note) = data.decode() {
+            if note.is_on {
+                self.note_on(note.note_number, note.velocity);
+            }
+            else {
+                self.note_off(note.note_number);
+            }
+        }
+    }
+
+    fn render_to_audio_buffer(
+        &mut self,
+        info: AudioInfo,
+        outputs: &mut [&mut AudioBuffer],
+        _inputs: &[&AudioBuffer],
+        _display: &mut DisplayAudioGraph
+    ) {
+        let output = &mut outputs[0];
+        output.zero();
+        let device_rate = info.sample_rate.max(1.0);
+        let release_step = (1.0 / (self.release * device_rate).max(1.0)) as f32;
+        for voice in self.voices.iter_mut().filter( | voice | voice.active) {
+            let zone = &self.zones[voice.zone];
+            let buffer = &zone.buffer;
+            let last = buffer.frame_count().saturating_sub(1);
+            let step = voice.pitch * zone.sample_rate / device_rate;
+            for i in 0..output.frame_count() {
+                if zone.loop_mode == SampleLoopMode::Loop && voice.pos >= zone.loop_end {
+                    voice.pos -= zone.loop_end - zone.loop_start;
+                }
+                if voice.pos >= last as f64 || voice.level <= 0.0 {
+                    voice.active = false;
+                    break
+                }
+                let index = voice.pos as usize;
+                let t = (voice.pos - index as f64) as f32;
+                let gain = voice.gain * voice.level;
+                for c in 0..output.channel_count() {
+                    let channel = buffer.channel(c.min(buffer.channel_count() - 1));
+                    let s = hermite(
+                        channel[index.saturating_sub(1)],
+                        channel[index],
+                        channel[index + 1],
+                        channel[(index + 2).min(last)],
+                        t
+                    );
+                    output.channel_mut(c)[i] += s * gain;
+                }
+                voice.pos += step;
+                if voice.releasing {
+                    voice.level -= release_step;
+                }
+            }
+        }
+    }
+}
+
+impl AudioComponent for SamplePlayer {
+    fn get_graph_node(&mut self, _cx: &mut Cx) -> Box<dyn AudioGraphNode + Send> {
+        let mut zones = Vec::new();
+        for zone in self.zones.values() {
+            let Some(sample) = &zone.sample else {continue};
+            let mut buffer = AudioBuffer::new_with_size(sample.data.len() / sample.channel_count, sample.channel_count);
+            buffer.copy_from_interleaved(sample.channel_count, &sample.data);
+            let frame_count = buffer.frame_count() as f64;
+            let loop_start = (zone.loop_start as f64).clamp(0.0, frame_count);
+            let loop_end = if zone.loop_end > 0 {(zone.loop_end as f64).min(frame_count - 1.0)} else {frame_count - 1.0};
+            zones.push(Zone {
+                keys: (zone.low_key.clamp(0, 127) as u8, zone.high_key.clamp(0, 127) as u8),
+                velocities: (zone.low_velocity.clamp(0, 127) as u8, zone.high_velocity.clamp(0, 127) as u8),
+                root_key: zone.root_key.clamp(0, 127) as u8,
+                gain: zone.gain,
+                loop_mode: zone.loop_mode,
+                loop_start,
+                // an empty loop would never advance
+                loop_end: if loop_end > loop_start {loop_end} else {f64::INFINITY},
+                sample_rate: sample.sample_rate as f64,
+                buffer: Arc::new(buffer),
+            });
+        }
+        Box::new(Node {
+            zones,
+            voices: vec![Voice::default(); self.max_voices.max(1) as usize],
+            voice_counter: 0,
+            release: self.release,
+            velocity_sensitivity: self.velocity_sensitivity,
+        })
+    }
+
+    fn handle_event_with(&mut self, _cx: &mut Cx, _event: &Event, _dispatch_action: &mut dyn FnMut(&mut Cx, AudioComponentAction)) {
+    }
+
+    fn audio_query(&mut self, _query: &AudioQuery, _callback: &mut Option<AudioQueryCb>) -> AudioResult<'_> {
+        AudioResult::not_found()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::offline_render::{OfflineRender, OfflineMidiEvent};
+
+    // a mono ramp 0, 1, 2, .. so the output reads back the playback position
+    fn ramp_zone(frame_count: usize, sample_rate: f64, loop_mode: SampleLoopMode) -> Zone {
+        let mut buffer = AudioBuffer::new_with_size(frame_count, 1);
+        for (i, s) in buffer.channel_mut(0).iter_mut().enumerate() {
+            *s = i as f32;
+        }
+        Zone {
+            keys: (0, 127),
+            velocities: (1, 127),
+            root_key: 60,
+            gain: 1.0,
+            loop_mode,
+            loop_start: 0.0,
+            loop_end: f64::INFINITY,
+            sample_rate,
+            buffer: Arc::new(buffer),
+        }
+    }
+
+    fn node(zones: Vec<Zone>, max_voices: usize) -> Node {
+        Node {
+            zones,
+            voices: vec![Voice::default(); max_voices],
+            voice_counter: 0,
+            release: 0.0,
+            velocity_sensitivity: 0.0,
+        }
+    }
+
+    fn note(is_on: bool, note_number: u8) -> MidiData {
+        MidiNote {is_on, channel: 0, note_number, velocity: 100}.into()
+    }
+
+    fn render(node: &mut Node, sample_rate: f64, events: &[OfflineMidiEvent], frame_count: usize) -> AudioBuffer {
+        let render = OfflineRender {sample_rate, block_size: 64, channel_count: 1};
+        render.render(node, events, frame_count)
+    }
+
+    #[test]
+    fn plays_at_the_device_rate() {
+        // a 24 kHz sample on a 48 kHz device moves half a source frame per output frame
+        let mut player = node(vec![ramp_zone(1000, 24000.0, SampleLoopMode::OneShot)], 4);
+        let output = render(&mut player, 48000.0, &[OfflineMidiEvent::at_frame(0, note(true, 60))], 100);
+        // the interpolation clamps at the sample start, past that the ramp comes back exact
+        for (i, s) in output.channel(0).iter().enumerate().skip(2) {
+            assert!((s - i as f32 * 0.5).abs() < 1e-4, "frame {} is {}", i, s);
+        }
+        // and an octave up doubles that
+        let mut player = node(vec![ramp_zone(1000, 24000.0, SampleLoopMode::OneShot)], 4);
+        let output = render(&mut player, 48000.0, &[OfflineMidiEvent::at_frame(0, note(true, 72))], 100);
+        assert!((output.channel(0)[10] - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn one_shot_frees_its_voice_at_the_end() {
+        let mut player = node(vec![ramp_zone(50, 48000.0, SampleLoopMode::OneShot)], 2);
+        let output = render(&mut player, 48000.0, &[
+            OfflineMidiEvent::at_frame(0, note(true, 60)),
+            OfflineMidiEvent::at_frame(10, note(false, 60)),
+        ], 100);
+        // note off doesn't stop a one-shot, the end of the sample does
+        assert_eq!(output.channel(0)[20], 20.0);
+        assert_eq!(output.channel(0)[60], 0.0);
+        assert!(player.voices.iter().all( | voice | !voice.active));
+    }
+
+    #[test]
+    fn loops_between_loop_points() {
+        let mut zone = ramp_zone(100, 48000.0, SampleLoopMode::Loop);
+        zone.loop_start = 10.0;
+        zone.loop_end = 20.0;
+        let mut player = node(vec![zone], 1);
+        let output = render(&mut player, 48000.0, &[OfflineMidiEvent::at_frame(0, note(true, 60))], 200);
+        assert_eq!(output.channel(0)[19], 19.0);
+        assert_eq!(output.channel(0)[20], 10.0);
+        assert_eq!(output.channel(0)[195], 15.0);
+    }
+
+    #[test]
+    fn steals_the_oldest_voice_from_a_fixed_pool() {
+        let mut player = node(vec![ramp_zone(10000, 48000.0, SampleLoopMode::Sustain)], 2);
+        let capacity = player.voices.capacity();
+        for note_number in [60, 62, 64] {
+            player.note_on(note_number, 100);
+        }
+        assert_eq!(player.voices.len(), 2);
+        assert_eq!(player.voices.capacity(), capacity);
+        let mut notes: Vec<u8> = player.voices.iter().filter( | voice | voice.active).map( | voice | voice.note).collect();
+        notes.sort();
+        assert_eq!(notes, [62, 64]);
+        player.all_notes_off();
+        assert!(player.voices.iter().all( | voice | !voice.active));
+    }
+
+    #[test]
+    fn release_fades_out_sustained_notes() {
+        let mut player = node(vec![ramp_zone(10000, 48000.0, SampleLoopMode::Sustain)], 1);
+        // 100 frames of release at 48 kHz
+        player.release = 100.0 / 48000.0;
+        let output = render(&mut player, 48000.0, &[
+            OfflineMidiEvent::at_frame(0, note(true, 60)),
+            OfflineMidiEvent::at_frame(50, note(false, 60)),
+        ], 300);
+        assert_eq!(output.channel(0)[50], 50.0);
+        assert!(output.channel(0)[100] < 100.0 * 0.6);
+        assert_eq!(output.channel(0)[200], 0.0);
+        assert!(!player.voices[0].active);
+    }
+}
@@ -0,0 +1,379 @@
+use {
+    crate::{
+        makepad_platform::*,
+        audio_graph::AudioGraph,
+        offline_render::OfflineMidiEvent,
+        midi_file::*,
+    },
+};
+
+fn note_off(channel: u8, note: u8) -> MidiData {
+    MidiData {data: [0x80 | channel, note, 0]}
+}
+
+/// Notes that are down, so a stop or a loop can let go of them
+#[derive(Clone, Debug, Default)]
+struct HeldNotes(Vec<(u8, u8)>);
+
+impl HeldNotes {
+    fn track(&mut self, data: MidiData) {
+        if let MidiEvent::Note(note) = data.decode() {
+            self.0.retain( | n | *n != (note.channel, note.note_number));
+            if note.is_on {
+                self.0.push((note.channel, note.note_number));
+            }
+        }
+    }
+
+    fn release(&mut self, mut out: impl FnMut(MidiData)) {
+        for (channel, note) in self.0.drain(..) {
+            out(note_off(channel, note));
+        }
+    }
+}
+
+/// Plays the channel messages of a MidiFile on the audio graph clock. It runs on the UI thread
+/// a little ahead of the audio, turning ticks into frames with the tempo map as it goes, so
+/// tempo changes and loops land on the exact frame. Call `update` regularly while it plays
+#[derive(Clone, Debug)]
+pub struct Sequencer {
+    pub sample_rate: f64,
+    /// Seconds ahead of the graph clock messages are sent, it has to cover the time between updates
+    pub lookahead: f64,
+    events: Vec<(u64, MidiData)>,
+    tempo_map: TempoMap,
+    end_tick: u64,
+    tempo_scale: f64,
+    loop_range: Option<(u64, u64)>,
+    playing: bool,
+    /// The tick scheduled up to and the frame it falls on
+    cursor_tick: f64,
+    cursor_frame: f64,
+    next_event: usize,
+    held: HeldNotes,
+}
+
+impl Sequencer {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            lookahead: 0.1,
+            events: Vec::new(),
+            tempo_map: TempoMap::new(480, &[]),
+            end_tick: 0,
+            tempo_scale: 1.0,
+            loop_range: None,
+            playing: false,
+            cursor_tick: 0.0,
+            cursor_frame: 0.0,
+            next_event: 0,
+            held: HeldNotes::default(),
+        }
+    }
+
+    pub fn from_file(file: &MidiFile, sample_rate: f64) -> Self {
+        let mut sequencer = Self::new(sample_rate);
+        sequencer.load(file);
+        sequencer
+    }
+
+    /// Replaces what is playing, the position goes back to the start
+    pub fn load(&mut self, file: &MidiFile) {
+        self.events = file.midi_events();
+        self.tempo_map = file.tempo_map();
+        self.end_tick = file.end_tick();
+        self.seek(0);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn tempo_map(&self) -> &TempoMap {
+        &self.tempo_map
+    }
+
+    /// The tick the sequencer has scheduled up to, it runs ahead of what is audible by the lookahead
+    pub fn position(&self) -> f64 {
+        self.cursor_tick
+    }
+
+    /// The tempo at the position in beats per minute, with the tempo scale applied
+    pub fn bpm(&self) -> f64 {
+        tempo_to_bpm(self.tempo_map.tempo_at(self.cursor_tick)) * self.tempo_scale
+    }
+
+    /// Speeds up (above 1) or slows down the file tempo from the position on
+    pub fn set_tempo_scale(&mut self, scale: f64) {
+        self.tempo_scale = scale.max(0.01);
+    }
+
+    /// Loops the ticks from `start` up to `end`, events on `end` belong to the next pass
+    pub fn set_loop(&mut self, range: Option<(u64, u64)>) {
+        self.loop_range = range.filter( | (start, end) | end > start);
+    }
+
+    pub fn seek(&mut self, tick: u64) {
+        self.cursor_tick = tick as f64;
+        self.next_event = self.events.partition_point( | (t, _) | *t < tick);
+    }
+
+    /// Starts playing the position at `frame` of the graph clock
+    pub fn play(&mut self, frame: f64) {
+        self.playing = true;
+        self.cursor_frame = frame;
+    }
+
+    /// Stops with note offs for the notes that are down on `frame`
+    pub fn stop(&mut self, frame: f64, mut out: impl FnMut(f64, MidiData)) {
+        self.playing = false;
+        self.held.release( | data | out(frame, data));
+    }
+
+    /// Schedules everything that falls before `until` on the graph clock with `out`
+    pub fn advance(&mut self, until: f64, mut out: impl FnMut(f64, MidiData)) {
+        while self.playing && self.cursor_frame < until {
+            let loop_end = self.loop_range.map( | (_, end) | end as f64);
+            let mut target = self.events.get(self.next_event).map( | (tick, _) | *tick as f64).unwrap_or(self.end_tick as f64);
+            if let Some(loop_end) = loop_end {
+                target = target.min(loop_end);
+            }
+            if let Some(change) = self.tempo_map.next_change(self.cursor_tick) {
+                target = target.min(change as f64);
+            }
+            let frames_per_tick = self.tempo_map.seconds_per_tick(self.cursor_tick) / self.tempo_scale * self.sample_rate;
+            let target_frame = self.cursor_frame + (target - self.cursor_tick).max(0.0) * frames_per_tick;
+            if target_frame >= until {
+                // move up to `until` so a new tempo scale takes over from there
+                self.cursor_tick += (until - self.cursor_frame) / frames_per_tick;
+                self.cursor_frame = until;
+                break;
+            }
+            self.cursor_tick = target;
+            self.cursor_frame = target_frame;
+
+            while let Some((tick, data)) = self.events.get(self.next_event) {
+                if *tick as f64 > self.cursor_tick || loop_end.is_some_and( | end | *tick as f64 >= end) {
+                    break;
+                }
+                self.held.track(*data);
+                out(self.cursor_frame, *data);
+                self.next_event += 1;
+            }
+
+            if let Some((start, end)) = self.loop_range {
+                if self.cursor_tick >= end as f64 {
+                    let frame = self.cursor_frame;
+                    self.held.release( | data | out(frame, data));
+                    self.seek(start);
+                    continue;
+                }
+            }
+            if self.next_event >= self.events.len() && self.cursor_tick >= self.end_tick as f64 {
+                let frame = self.cursor_frame;
+                self.stop(frame, &mut out);
+            }
+        }
+    }
+
+    /// Keeps the graph fed up to the lookahead past its clock
+    pub fn update(&mut self, graph: &AudioGraph) {
+        let until = graph.sample_time() + self.lookahead * self.sample_rate;
+        self.advance(until, | time, data | graph.send_midi_data_at(time, data));
+    }
+
+    /// Plays `frame_count` frames from the position into events for an OfflineRender
+    pub fn render_events(&mut self, frame_count: usize) -> Vec<OfflineMidiEvent> {
+        let mut events = Vec::new();
+        self.play(0.0);
+        self.advance(frame_count as f64, | time, data | events.push(OfflineMidiEvent::at_frame(time.round() as usize, data)));
+        events
+    }
+}
+
+/// Records channel messages, for instance from a MidiInput, into a new MidiFile.
+/// Times are frames on the graph clock and become ticks at a fixed tempo
+#[derive(Clone, Debug)]
+pub struct MidiRecorder {
+    pub sample_rate: f64,
+    pub ticks_per_quarter: u16,
+    /// Microseconds per quarter note
+    pub tempo: u32,
+    start: Option<f64>,
+    track: MidiTrack,
+    held: HeldNotes,
+}
+
+impl MidiRecorder {
+    pub fn new(sample_rate: f64, bpm: f64) -> Self {
+        Self {
+            sample_rate,
+            ticks_per_quarter: 480,
+            tempo: bpm_to_tempo(bpm),
+            start: None,
+            track: MidiTrack::default(),
+            held: HeldNotes::default(),
+        }
+    }
+
+    pub fn start(&mut self, frame: f64) {
+        self.start = Some(frame);
+        self.track = MidiTrack::default();
+        self.held = HeldNotes::default();
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.start.is_some()
+    }
+
+    fn frame_to_tick(&self, start: f64, frame: f64) -> u64 {
+        let seconds = (frame - start).max(0.0) / self.sample_rate;
+        (seconds * 1_000_000.0 / self.tempo as f64 * self.ticks_per_quarter as f64).round() as u64
+    }
+
+    pub fn record(&mut self, frame: f64, data: MidiData) {
+        let Some(start) = self.start else {return};
+        // system messages like clock and active sensing don't go in the file
+        if data.data[0] >= 0xF0 {
+            return
+        }
+        self.held.track(data);
+        let tick = self.frame_to_tick(start, frame);
+        self.track.push(tick, MidiFileEvent::Midi(data));
+    }
+
+    /// Records everything that arrived on `input` at `frame`, usually the graph clock when the UI polls
+    pub fn record_input(&mut self, input: &mut MidiInput, frame: f64) {
+        while let Some((_, data)) = input.receive() {
+            self.record(frame, data);
+        }
+    }
+
+    /// Ends the recording, notes still down are released at `frame`
+    pub fn stop(&mut self, frame: f64) -> MidiFile {
+        let start = self.start.take().unwrap_or(frame);
+        let tick = self.frame_to_tick(start, frame);
+        let mut track = std::mem::take(&mut self.track);
+        self.held.release( | data | track.push(tick, MidiFileEvent::Midi(data)));
+        // the tempo goes before the first note
+        track.events.insert(0, MidiTrackEvent {tick: 0, event: MidiFileEvent::Tempo(self.tempo)});
+        track.push(tick, MidiFileEvent::EndOfTrack);
+        MidiFile {
+            format: 0,
+            timing: MidiTiming::PerQuarter(self.ticks_per_quarter),
+            tracks: vec![track],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on(note: u8) -> MidiFileEvent {
+        MidiFileEvent::Midi(MidiData {data: [0x90, note, 100]})
+    }
+
+    fn off(note: u8) -> MidiFileEvent {
+        MidiFileEvent::Midi(MidiData {data: [0x80, note, 0]})
+    }
+
+    /// Quarter notes 60, 62 at 120 bpm with 480 ticks per quarter
+    fn two_notes() -> MidiFile {
+        let mut track = MidiTrack::default();
+        track.push(0, MidiFileEvent::Tempo(500_000));
+        track.push(0, on(60));
+        track.push(480, off(60));
+        track.push(480, on(62));
+        track.push(960, off(62));
+        MidiFile {format: 0, tracks: vec![track], ..MidiFile::default()}
+    }
+
+    fn frames(events: &[OfflineMidiEvent]) -> Vec<(usize, u8, u8)> {
+        events.iter().map( | e | (e.frame, e.data.data[0], e.data.data[1])).collect()
+    }
+
+    #[test]
+    fn plays_on_exact_frames() {
+        let mut sequencer = Sequencer::from_file(&two_notes(), 48000.0);
+        let events = sequencer.render_events(96000);
+        assert_eq!(frames(&events), vec![(0, 0x90, 60), (24000, 0x80, 60), (24000, 0x90, 62), (48000, 0x80, 62)]);
+        assert!(!sequencer.is_playing());
+    }
+
+    #[test]
+    fn advancing_in_small_steps_matches() {
+        let mut sequencer = Sequencer::from_file(&two_notes(), 44100.0);
+        sequencer.play(1000.0);
+        let mut events = Vec::new();
+        let mut until = 1000.0;
+        while sequencer.is_playing() {
+            until += 300.0;
+            sequencer.advance(until, | time, data | events.push((time.round(), data.data[1])));
+        }
+        assert_eq!(events, vec![(1000.0, 60), (23050.0, 60), (23050.0, 62), (45100.0, 62)]);
+    }
+
+    #[test]
+    fn follows_tempo_changes() {
+        let mut file = two_notes();
+        // the second quarter at 60 bpm
+        file.tracks[0].push(480, MidiFileEvent::Tempo(1_000_000));
+        let mut sequencer = Sequencer::from_file(&file, 48000.0);
+        let events = sequencer.render_events(200000);
+        assert_eq!(events.last().unwrap().frame, 24000 + 48000);
+
+        let mut sequencer = Sequencer::from_file(&two_notes(), 48000.0);
+        sequencer.set_tempo_scale(2.0);
+        assert_eq!(sequencer.bpm(), 240.0);
+        let events = sequencer.render_events(200000);
+        assert_eq!(events.last().unwrap().frame, 24000);
+    }
+
+    #[test]
+    fn loops_and_releases_notes() {
+        let mut file = two_notes();
+        file.tracks[0].push(2000, off(70));
+        let mut sequencer = Sequencer::from_file(&file, 48000.0);
+        // loop the first quarter and a half, the second note is cut by the loop
+        sequencer.set_loop(Some((0, 720)));
+        let events = sequencer.render_events(36000 * 2 + 1);
+        assert_eq!(frames(&events), vec![
+            (0, 0x90, 60), (24000, 0x80, 60), (24000, 0x90, 62),
+            (36000, 0x80, 62),
+            (36000, 0x90, 60), (60000, 0x80, 60), (60000, 0x90, 62),
+            (72000, 0x80, 62),
+            (72000, 0x90, 60),
+        ]);
+        assert!(sequencer.is_playing());
+        let mut released = Vec::new();
+        sequencer.stop(80000.0, | time, data | released.push((time, data.data)));
+        assert_eq!(released, vec![(80000.0, [0x80, 60, 0])]);
+    }
+
+    #[test]
+    fn records_into_a_file() {
+        let mut recorder = MidiRecorder::new(48000.0, 120.0);
+        recorder.record(0.0, MidiData {data: [0x90, 10, 100]});
+        recorder.start(48000.0);
+        recorder.record(48000.0, MidiData {data: [0x90, 60, 100]});
+        recorder.record(60000.0, MidiData {data: [0xF8, 0, 0]});
+        recorder.record(72000.0, MidiData {data: [0x90, 60, 0]});
+        recorder.record(72000.0, MidiData {data: [0x91, 64, 90]});
+        let file = recorder.stop(96000.0);
+        assert!(!recorder.is_recording());
+
+        let mut data = Vec::new();
+        file.write(&mut data).unwrap();
+        let file = MidiFile::parse(&data).unwrap();
+        assert_eq!(file.tracks[0].events[0].event, MidiFileEvent::Tempo(500_000));
+        assert_eq!(file.midi_events(), vec![
+            (0, MidiData {data: [0x90, 60, 100]}),
+            (480, MidiData {data: [0x90, 60, 0]}),
+            (480, MidiData {data: [0x91, 64, 90]}),
+            (960, MidiData {data: [0x81, 64, 0]}),
+        ]);
+        let mut sequencer = Sequencer::from_file(&file, 48000.0);
+        assert_eq!(frames(&sequencer.render_events(48001)).len(), 4);
+    }
+}
@@ -0,0 +1,27 @@
+use {
+    crate::{
+        cx_2d::Cx2d,
+        makepad_platform::{AccessItem, AccessNode, DrawListId},
+    }
+};
+
+impl<'a> Cx2d<'a> {
+    pub fn access_list_clear(&mut self, draw_list_id: DrawListId) {
+        self.cx.accessibility.clear_list(draw_list_id);
+    }
+    
+    pub fn access_list_item_push(&mut self, draw_list_id: DrawListId, item: AccessItem) {
+        self.cx.accessibility.push_item(draw_list_id, item);
+    }
+    
+    /// Opens an accessibility node in the current drawlist, everything that opens before
+    /// the matching `end_access_node` becomes its child.
+    pub fn begin_access_node(&mut self, id: u64, node: AccessNode) {
+        let draw_list_id = self.draw_list_stack.last().cloned();
+        self.cx.accessibility.begin_node(draw_list_id, id, node);
+    }
+    
+    pub fn end_access_node(&mut self, id: u64, node: AccessNode) {
+        self.cx.accessibility.end_node(id, node);
+    }
+}
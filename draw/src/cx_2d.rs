@@ -75,6 +75,7 @@ impl<'a> Cx2d<'a> {
         Self::lazy_construct_nav_tree(cx);
         Self::lazy_construct_icon_atlas(cx);
         cx.redraw_id += 1;
+        cx.accessibility.begin_draw(cx.redraw_id);
         let fonts_atlas_rc = cx.get_global::<CxFontsAtlasRc>().clone();
         let nav_tree_rc = cx.get_global::<CxNavTreeRc>().clone();
         let icon_atlas_rc = cx.get_global::<CxIconAtlasRc>().clone();
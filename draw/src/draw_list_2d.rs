@@ -109,6 +109,7 @@ impl DrawList2d {
         }
         
         cx.nav_list_item_push(codeflow_parent_id, NavItem::Child(self.draw_list.id()));
+        cx.access_list_item_push(codeflow_parent_id, AccessItem::Child(self.draw_list.id()));
         
         cx.cx.draw_lists[self.draw_list.id()].codeflow_parent_id = Some(codeflow_parent_id);
         if cx.passes[pass_id].main_draw_list_id.unwrap() == self.draw_list.id() {
@@ -118,6 +119,7 @@ impl DrawList2d {
         cx.cx.draw_lists[self.draw_list.id()].clear_draw_items(redraw_id);
         
         cx.nav_list_clear(self.draw_list.id());
+        cx.access_list_clear(self.draw_list.id());
         
         cx.draw_list_stack.push(self.draw_list.id());
     }
@@ -159,6 +161,11 @@ impl DrawList2d {
                 
                 cx.nav_list_item_push(parent_id, NavItem::Child(self.draw_list.id()));
             }
+            // views cached into a texture pass still belong in the accessibility tree
+            cx.access_list_item_push(parent_id, AccessItem::Child(self.draw_list.id()));
+        }
+        else {
+            cx.accessibility.attach_root_list(self.draw_list.id());
         }
         
         // set nesting draw list id for incremental repaint scanning
@@ -184,6 +191,7 @@ impl DrawList2d {
         cx.cx.draw_lists[self.draw_list.id()].clear_draw_items(redraw_id);
         
         cx.nav_list_clear(self.draw_list.id());
+        cx.access_list_clear(self.draw_list.id());
         
         cx.draw_list_stack.push(self.draw_list.id());
         
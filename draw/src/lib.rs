@@ -11,6 +11,7 @@ pub mod turtle;
 pub mod font_atlas;
 pub mod geometry;
 pub mod nav;
+pub mod accessibility;
 pub mod icon_atlas;
 mod owned_font_face;
  
@@ -23,7 +24,11 @@ pub use crate::{
         Align,
         Padding,
         Flow,
+        LayoutDirection,
         Size,
+        GridTrack,
+        GridTracks,
+        GridCell,
         TurtleAlignRange,
         DeferWalk
     },
@@ -37,7 +37,9 @@ impl std::ops::IndexMut<DrawListId> for CxNavTree {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Where a nav stop sits in the Tab order. `Top` stops come first and `Bottom` stops last,
+/// each sorted by their number; `Default` and `Middle` stops sit in between in draw order.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NavOrder {
     Default,
     Top(u64),
@@ -45,6 +47,32 @@ pub enum NavOrder {
     Bottom(u64),
 }
 
+impl NavOrder {
+    /// Maps the `tab_index` live property of focusable widgets onto an order the way html does:
+    /// negative indices take the widget out of the Tab order, zero (the default) keeps draw order
+    /// and positive indices go before all of those, lowest first.
+    pub fn from_tab_index(tab_index: i64) -> Option<NavOrder> {
+        if tab_index < 0 {
+            None
+        }
+        else if tab_index == 0 {
+            Some(NavOrder::Default)
+        }
+        else {
+            Some(NavOrder::Top(tab_index as u64))
+        }
+    }
+    
+    fn sort_key(&self) -> (u8, u64) {
+        match self {
+            NavOrder::Top(i) => (0, *i),
+            NavOrder::Default => (1, 0),
+            NavOrder::Middle(i) => (1, *i),
+            NavOrder::Bottom(i) => (2, *i),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct NavStop {
     pub role: NavRole,
@@ -61,11 +89,16 @@ pub enum NavItem {
     EndScroll(Area)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NavRole {
     TextInput,
     DropDown,
     Slider,
+    Button,
+    CheckBox,
+    RadioButton,
+    List,
+    Tree,
 }
 
 impl<'a> Cx2d<'a> {
@@ -114,6 +147,41 @@ impl<'a> Cx2d<'a> {
         }
     }
     
+    /// All nav stops under `root` in Tab order, each with the stack of scroll areas it sits in
+    /// (ending with the stop itself) so the caller can scroll it into view.
+    pub fn nav_stops_in_tab_order(cx: &mut Cx, root: DrawListId) -> Vec<(NavStop, Vec<Area>)> {
+        let nav_tree_rc = cx.get_global::<CxNavTreeRc>().clone();
+        let nav_tree = &*nav_tree_rc.0.borrow();
+        fn collect_nav_stops(cx: &Cx, scroll_stack: &mut Vec<Area>, nav_tree: &CxNavTree, draw_list_id: DrawListId, out: &mut Vec<(NavStop, Vec<Area>)>) {
+            if draw_list_id.index() >= nav_tree.nav_lists.len() {
+                return
+            }
+            for nav_item in &nav_tree[draw_list_id].nav_list {
+                match nav_item {
+                    NavItem::Child(draw_list_id) => {
+                        collect_nav_stops(cx, scroll_stack, nav_tree, *draw_list_id, out);
+                    }
+                    NavItem::Stop(stop) => if stop.area.is_valid(cx) {
+                        let mut stack = scroll_stack.clone();
+                        stack.push(stop.area);
+                        out.push((stop.clone(), stack));
+                    }
+                    NavItem::BeginScroll(area) => {
+                        scroll_stack.push(*area);
+                    }
+                    NavItem::EndScroll(_) => {
+                        scroll_stack.pop();
+                    }
+                }
+            }
+        }
+        let mut stops = Vec::new();
+        collect_nav_stops(cx, &mut Vec::new(), nav_tree, root, &mut stops);
+        // sort is stable so stops with the same order keep their draw order
+        stops.sort_by_key( | (stop, _) | stop.order.sort_key());
+        stops
+    }
+    
     pub fn nav_list_clear(&mut self, draw_list_id: DrawListId) {
         let mut nav_tree = self.nav_tree_rc.0.borrow_mut();
         if draw_list_id.index() >= nav_tree.nav_lists.len() {
@@ -128,15 +196,26 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn add_nav_stop(&mut self, area: Area, role: NavRole, margin: Margin) {
+        self.add_nav_stop_with_order(area, role, margin, NavOrder::Default);
+    }
+    
+    pub fn add_nav_stop_with_order(&mut self, area: Area, role: NavRole, margin: Margin, order: NavOrder) {
         let draw_list_id = *self.draw_list_stack.last().unwrap();
         self.nav_list_item_push(draw_list_id, NavItem::Stop(NavStop {
             role,
             area,
-            order: NavOrder::Default,
+            order,
             margin
         }));
     }
     
+    /// Adds a nav stop ordered by a widget's `tab_index`, see [`NavOrder::from_tab_index`].
+    pub fn add_nav_stop_with_tab_index(&mut self, area: Area, role: NavRole, tab_index: i64) {
+        if let Some(order) = NavOrder::from_tab_index(tab_index) {
+            self.add_nav_stop_with_order(area, role, Margin::default(), order);
+        }
+    }
+    
     pub fn add_begin_scroll(&mut self)->NavScrollIndex{
         let mut nav_tree = self.nav_tree_rc.0.borrow_mut();
         let draw_list_id = *self.draw_list_stack.last().unwrap();
@@ -153,4 +232,68 @@ impl<'a> Cx2d<'a> {
     }
 }
 
-pub struct NavScrollIndex(usize);
\ No newline at end of file
+pub struct NavScrollIndex(usize);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::makepad_platform::{DrawEvent, RectArea};
+
+    #[test]
+    fn tab_index_maps_like_html() {
+        assert_eq!(NavOrder::from_tab_index(-1), None);
+        assert_eq!(NavOrder::from_tab_index(0), Some(NavOrder::Default));
+        assert_eq!(NavOrder::from_tab_index(3), Some(NavOrder::Top(3)));
+    }
+
+    #[test]
+    fn sort_keys_put_top_first_and_bottom_last() {
+        let mut orders = vec![
+            NavOrder::Bottom(1),
+            NavOrder::Middle(2),
+            NavOrder::Top(5),
+            NavOrder::Default,
+            NavOrder::Top(1),
+        ];
+        orders.sort_by_key( | order | order.sort_key());
+        assert_eq!(orders, vec![
+            NavOrder::Top(1),
+            NavOrder::Top(5),
+            NavOrder::Default,
+            NavOrder::Middle(2),
+            NavOrder::Bottom(1),
+        ]);
+    }
+
+    #[test]
+    fn stops_come_out_in_tab_order() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let draw_list = cx.draw_lists.alloc();
+        let draw_list_id = draw_list.id();
+        let redraw_id = cx.draw_lists[draw_list_id].redraw_id;
+        let area = | rect_id | Area::Rect(RectArea {draw_list_id, rect_id, redraw_id});
+        {
+            let draw_event = DrawEvent::default();
+            let mut cx = Cx2d::new(&mut cx, &draw_event);
+            cx.nav_list_clear(draw_list_id);
+            cx.draw_list_stack.push(draw_list_id);
+            cx.add_nav_stop_with_tab_index(area(0), NavRole::Button, 0);
+            cx.add_nav_stop_with_tab_index(area(1), NavRole::Button, 2);
+            cx.add_nav_stop_with_tab_index(area(2), NavRole::Button, -1);
+            let scroll = cx.add_begin_scroll();
+            cx.add_nav_stop_with_tab_index(area(3), NavRole::List, 0);
+            cx.add_end_scroll(scroll, area(9));
+            cx.add_nav_stop_with_order(area(4), NavRole::Button, Margin::default(), NavOrder::Bottom(0));
+            cx.add_nav_stop_with_tab_index(area(5), NavRole::Button, 1);
+            // a stop whose area went stale is left out
+            cx.add_nav_stop(Area::Empty, NavRole::Button, Margin::default());
+        }
+        let stops = Cx2d::nav_stops_in_tab_order(&mut cx, draw_list_id);
+        let order: Vec<Area> = stops.iter().map( | (stop, _) | stop.area).collect();
+        assert_eq!(order, vec![area(5), area(1), area(0), area(3), area(4)]);
+        // stops inside a scroll area carry it so they can be scrolled into view
+        assert_eq!(stops[3].1, vec![area(9), area(3)]);
+        assert_eq!(stops[0].1, vec![area(5)]);
+    }
+}
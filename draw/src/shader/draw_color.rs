@@ -19,3 +19,109 @@ pub struct DrawColor {
     #[deref] pub draw_super: DrawQuad,
     #[live] pub color: Vec4
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        std::collections::BTreeMap,
+        super::*,
+        crate::{
+            makepad_platform::makepad_shader_compiler::{
+                shader_ast::Ident,
+                interpret::{ShaderInterpreter, ShaderSampler},
+            },
+            cx_2d::Cx2d,
+            draw_list_2d::DrawList2d,
+            shader::draw_line::DrawLine,
+            turtle::Layout,
+        },
+    };
+    
+    const GOLDEN: &str = include_str!("golden/software_render.txt");
+    
+    fn to_golden(image: &SoftwareImage) -> String {
+        let rgba = image.to_rgba_u8();
+        let mut out = String::new();
+        for row in rgba.chunks(image.width * 4) {
+            let pixels: Vec<String> = row.chunks(4).map( | p | format!("{:02x}{:02x}{:02x}{:02x}", p[0], p[1], p[2], p[3])).collect();
+            out.push_str(&pixels.join(" "));
+            out.push('\n');
+        }
+        out
+    }
+    
+    // red with a half transparent blue blended over it, and an antialiased line
+    // that runs builtins and self methods through the interpreter
+    fn render_scene() -> SoftwareImage {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let pass = Pass::new_with_name(&mut cx, "golden");
+        pass.set_size(&mut cx, dvec2(16.0, 16.0));
+        pass.set_window_clear_color(&mut cx, vec4(0.0, 0.0, 0.0, 1.0));
+        let mut draw_list = DrawList2d::new(&mut cx);
+        let mut red = DrawColor::new_local(&mut cx);
+        red.color = vec4(1.0, 0.0, 0.0, 1.0);
+        let mut blue = DrawColor::new_local(&mut cx);
+        blue.color = vec4(0.0, 0.0, 1.0, 0.5);
+        let mut line = DrawLine::new_local(&mut cx);
+        {
+            let draw_event = DrawEvent::default();
+            let mut cx = Cx2d::new(&mut cx, &draw_event);
+            cx.begin_pass(&pass, Some(1.0));
+            draw_list.begin_always(&mut cx);
+            cx.begin_pass_sized_turtle(Layout::default());
+            red.draw_abs(&mut cx, Rect {pos: dvec2(2.0, 2.0), size: dvec2(6.0, 6.0)});
+            blue.draw_abs(&mut cx, Rect {pos: dvec2(5.0, 5.0), size: dvec2(6.0, 6.0)});
+            line.draw_line_abs(&mut cx, dvec2(1.0, 14.0), dvec2(14.0, 9.0), vec4(0.0, 1.0, 0.0, 1.0), 2.0);
+            cx.end_pass_sized_turtle();
+            draw_list.end(&mut cx);
+            cx.end_pass(&pass);
+        }
+        cx.software_render_pass(pass.pass_id())
+    }
+    
+    #[test]
+    fn software_render_matches_golden_image() {
+        let rendered = to_golden(&render_scene());
+        // MAKEPAD_BLESS_GOLDEN=1 writes the current output as the new golden image
+        if std::env::var("MAKEPAD_BLESS_GOLDEN").is_ok() {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader/golden/software_render.txt");
+            std::fs::write(path, &rendered).unwrap();
+            return
+        }
+        let parse = | text: &str | -> Vec<u8> {
+            text.split_whitespace().flat_map( | pixel | {
+                (0..4).map( | i | u8::from_str_radix(&pixel[i * 2..i * 2 + 2], 16).unwrap()).collect::<Vec<_ >> ()
+            }).collect()
+        };
+        let (expected, actual) = (parse(GOLDEN), parse(&rendered));
+        assert_eq!(expected.len(), actual.len());
+        // a step of rounding either way is fine, anything more is a change in the renderer
+        for (i, (e, a)) in expected.iter().zip(&actual).enumerate() {
+            assert!(e.abs_diff(*a) <= 1, "pixel {} channel {} is {} not {}\n{}", i / 4, i % 4, a, e, rendered);
+        }
+    }
+    
+    struct NoTextures;
+    
+    impl ShaderSampler for NoTextures {
+        fn sample2d(&self, _texture: usize, _pos: Vec2) -> Vec4 {Vec4::default()}
+        fn sample2d_rt(&self, _texture: usize, _pos: Vec2) -> Vec4 {Vec4::default()}
+    }
+    
+    #[test]
+    fn interpreter_returns_errors_instead_of_panicking() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let color = DrawColor::new_local(&mut cx);
+        let draw_shader_ptr = color.draw_vars.draw_shader.unwrap().draw_shader_ptr;
+        let draw_shader_def = cx.shader_registry.draw_shader_defs.get(&draw_shader_ptr).unwrap();
+        let live_values = BTreeMap::new();
+        let mut interpreter = ShaderInterpreter::new(&cx.shader_registry, draw_shader_def, None, &live_values, &NoTextures);
+        interpreter.set_field(Ident(live_id!(color)), vec4(0.0, 1.0, 0.0, 0.5).into());
+        assert_eq!(interpreter.pixel().unwrap(), vec4(0.0, 0.5, 0.0, 0.5));
+        assert!(interpreter.call_draw_shader_method(Ident(live_id!(not_a_method)), Vec::new()).is_err());
+    }
+}
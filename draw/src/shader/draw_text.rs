@@ -681,7 +681,8 @@ impl DrawText {
                         abs_pos: None,
                         margin: Margin::default(),
                         width: Size::Fixed(width),
-                        height: Size::Fixed(line_drop)
+                        height: Size::Fixed(line_drop),
+                        ..Default::default()
                     });
                     if last_rect.is_none(){
                         last_rect = Some(walk_rect)
@@ -709,13 +710,14 @@ impl DrawText {
         }
     }
     
-    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk, align: Align, text: &str) {
+    /// Draws the text into the turtle and returns the rect it walked, empty text doesn't walk.
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk, align: Align, text: &str) -> Rect {
         if text.len() == 0 {
-            return
+            return Rect::default()
         }        
         let font_id = if let Some(font_id) = self.text_style.font.font_id{font_id}else{
             //log!("Draw text without font");
-            return
+            return Rect::default()
         };
         let fonts_atlas_rc = cx.fonts_atlas_rc.clone();
         let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
@@ -730,6 +732,7 @@ impl DrawText {
         //if !in_many {
         //    self.begin_many_instances_internal(cx, fonts_atlas);
         //}
+        let mut walked = Rect::default();
         if let Some(geom) = self.compute_geom_inner(cx, walk, text, fonts_atlas) {
             let height = if walk.height.is_fit() {
                 geom.measured_height
@@ -747,8 +750,10 @@ impl DrawText {
                             abs_pos: walk.abs_pos,
                             margin: walk.margin,
                             width: Size::Fixed(geom.eval_width),
-                            height: Size::Fixed(height)
+                            height: Size::Fixed(height),
+                            ..walk
                         });
+                        walked = rect;
                         
                         // Ensure the chunk before the ellipsis is aligned down to a char boundary
                         let chunk = text.get(0..ellip).unwrap_or_else(|| {
@@ -775,8 +780,10 @@ impl DrawText {
                                 } else {
                                     geom.eval_height
                                 }
-                            )
+                            ),
+                            ..walk
                         });
+                        walked = rect;
                         let x_align = (geom.eval_width - geom.measured_width) * align.x;
                         self.draw_inner(cx, rect.pos + dvec2(x_align, y_align), text, fonts_atlas);
                     }
@@ -786,8 +793,10 @@ impl DrawText {
                         abs_pos: walk.abs_pos,
                         margin: walk.margin,
                         width: Size::Fixed(geom.eval_width),
-                        height: Size::Fixed(geom.measured_height)
+                        height: Size::Fixed(geom.measured_height),
+                        ..walk
                     });
+                    walked = rect;
                     let mut pos = dvec2(0.0, 0.0);
                     
                     let mut iter = WordIterator::new(
@@ -820,8 +829,10 @@ impl DrawText {
                         abs_pos: walk.abs_pos,
                         margin: walk.margin,
                         width: Size::Fixed(geom.measured_width),
-                        height: Size::Fixed(height)
+                        height: Size::Fixed(height),
+                        ..walk
                     });
+                    walked = rect;
                     // lets do our y alignment
                     let mut ypos = 0.0;
                     for line in text.split('\n') {
@@ -836,6 +847,7 @@ impl DrawText {
         if self.many_instances.is_some() {
             self.end_many_instances(cx)
         }
+        walked
     }
     
     pub fn closest_offset(&self, cx: &Cx, newline_indexes: Vec<usize>, pos: DVec2) -> Option<usize> {
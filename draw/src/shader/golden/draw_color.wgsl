@@ -0,0 +1,231 @@
+@group(0) @binding(0) var<uniform> const_table: array<vec4<f32>, 1>;
+@group(0) @binding(2) var<uniform> view_table: array<vec4<f32>, 4>;
+@group(0) @binding(3) var<uniform> pass_table: array<vec4<f32>, 13>;
+@group(0) @binding(4) var<uniform> draw_table: array<vec4<f32>, 1>;
+
+fn clamp_vec2_float_float(x: vec2<f32>, a: f32, b: f32) -> vec2<f32> {return clamp(x, vec2<f32>(a), vec2<f32>(b));}
+fn clamp_vec3_float_float(x: vec3<f32>, a: f32, b: f32) -> vec3<f32> {return clamp(x, vec3<f32>(a), vec3<f32>(b));}
+fn clamp_vec4_float_float(x: vec4<f32>, a: f32, b: f32) -> vec4<f32> {return clamp(x, vec4<f32>(a), vec4<f32>(b));}
+
+var<private> ds_camera_projection: mat4x4<f32>;
+var<private> ds_camera_view: mat4x4<f32>;
+var<private> ds_camera_inv: mat4x4<f32>;
+var<private> ds_dpi_factor: f32;
+var<private> ds_dpi_dilate: f32;
+var<private> ds_time: f32;
+var<private> ds_view_transform: mat4x4<f32>;
+var<private> ds_draw_zbias: f32;
+var<private> ds_rect_pos: vec2<f32>;
+var<private> ds_rect_size: vec2<f32>;
+var<private> ds_draw_clip: vec4<f32>;
+var<private> ds_draw_depth: f32;
+var<private> ds_0: f32;
+var<private> ds_color: vec4<f32>;
+var<private> ds_geom_pos: vec2<f32>;
+var<private> ds_pos: vec2<f32>;
+
+
+fn fn_1_12_pixel() -> vec4<f32> {
+    return vec4<f32>((ds_color.rgb * ds_color.a), ds_color.a);
+}
+
+fn fn_1_9_clip_and_transform_vertex(var_rect_pos_0: vec2<f32>, var_rect_size_0: vec2<f32>) -> vec4<f32> {
+    var var_clipped_0: vec2<f32> = clamp(((ds_geom_pos * var_rect_size_0) + var_rect_pos_0), ds_draw_clip.xy, ds_draw_clip.zw);
+    ds_pos = ((var_clipped_0 - var_rect_pos_0) / var_rect_size_0);
+    return (ds_camera_projection * (ds_camera_view * (ds_view_transform * vec4<f32>(var_clipped_0.x, var_clipped_0.y, (ds_draw_depth + ds_draw_zbias), const_table[0].x))));
+}
+
+fn fn_1_11_vertex() -> vec4<f32> {
+    return fn_1_9_clip_and_transform_vertex (ds_rect_pos, ds_rect_size);
+}
+
+struct VertexInput {
+    @location(0) packed_geometry_0: vec2<f32>,
+    @location(1) packed_instance_0: vec4<f32>,
+    @location(2) packed_instance_1: vec4<f32>,
+    @location(3) packed_instance_2: vec4<f32>,
+    @location(4) packed_instance_3: vec2<f32>,
+}
+struct Varyings {
+    @builtin(position) position: vec4<f32>,
+    @location(0) packed_varying_0: vec4<f32>,
+    @location(1) packed_varying_1: vec2<f32>,
+}
+
+@vertex
+fn vertex_main(input: VertexInput) -> Varyings {
+    ds_view_transform[0][0] = (view_table[0].x);
+    ds_view_transform[0][1] = (view_table[0].y);
+    ds_view_transform[0][2] = (view_table[0].z);
+    ds_view_transform[0][3] = (view_table[0].w);
+    ds_view_transform[1][0] = (view_table[1].x);
+    ds_view_transform[1][1] = (view_table[1].y);
+    ds_view_transform[1][2] = (view_table[1].z);
+    ds_view_transform[1][3] = (view_table[1].w);
+    ds_view_transform[2][0] = (view_table[2].x);
+    ds_view_transform[2][1] = (view_table[2].y);
+    ds_view_transform[2][2] = (view_table[2].z);
+    ds_view_transform[2][3] = (view_table[2].w);
+    ds_view_transform[3][0] = (view_table[3].x);
+    ds_view_transform[3][1] = (view_table[3].y);
+    ds_view_transform[3][2] = (view_table[3].z);
+    ds_view_transform[3][3] = (view_table[3].w);
+    ds_camera_projection[0][0] = (pass_table[0].x);
+    ds_camera_projection[0][1] = (pass_table[0].y);
+    ds_camera_projection[0][2] = (pass_table[0].z);
+    ds_camera_projection[0][3] = (pass_table[0].w);
+    ds_camera_projection[1][0] = (pass_table[1].x);
+    ds_camera_projection[1][1] = (pass_table[1].y);
+    ds_camera_projection[1][2] = (pass_table[1].z);
+    ds_camera_projection[1][3] = (pass_table[1].w);
+    ds_camera_projection[2][0] = (pass_table[2].x);
+    ds_camera_projection[2][1] = (pass_table[2].y);
+    ds_camera_projection[2][2] = (pass_table[2].z);
+    ds_camera_projection[2][3] = (pass_table[2].w);
+    ds_camera_projection[3][0] = (pass_table[3].x);
+    ds_camera_projection[3][1] = (pass_table[3].y);
+    ds_camera_projection[3][2] = (pass_table[3].z);
+    ds_camera_projection[3][3] = (pass_table[3].w);
+    ds_camera_view[0][0] = (pass_table[4].x);
+    ds_camera_view[0][1] = (pass_table[4].y);
+    ds_camera_view[0][2] = (pass_table[4].z);
+    ds_camera_view[0][3] = (pass_table[4].w);
+    ds_camera_view[1][0] = (pass_table[5].x);
+    ds_camera_view[1][1] = (pass_table[5].y);
+    ds_camera_view[1][2] = (pass_table[5].z);
+    ds_camera_view[1][3] = (pass_table[5].w);
+    ds_camera_view[2][0] = (pass_table[6].x);
+    ds_camera_view[2][1] = (pass_table[6].y);
+    ds_camera_view[2][2] = (pass_table[6].z);
+    ds_camera_view[2][3] = (pass_table[6].w);
+    ds_camera_view[3][0] = (pass_table[7].x);
+    ds_camera_view[3][1] = (pass_table[7].y);
+    ds_camera_view[3][2] = (pass_table[7].z);
+    ds_camera_view[3][3] = (pass_table[7].w);
+    ds_camera_inv[0][0] = (pass_table[8].x);
+    ds_camera_inv[0][1] = (pass_table[8].y);
+    ds_camera_inv[0][2] = (pass_table[8].z);
+    ds_camera_inv[0][3] = (pass_table[8].w);
+    ds_camera_inv[1][0] = (pass_table[9].x);
+    ds_camera_inv[1][1] = (pass_table[9].y);
+    ds_camera_inv[1][2] = (pass_table[9].z);
+    ds_camera_inv[1][3] = (pass_table[9].w);
+    ds_camera_inv[2][0] = (pass_table[10].x);
+    ds_camera_inv[2][1] = (pass_table[10].y);
+    ds_camera_inv[2][2] = (pass_table[10].z);
+    ds_camera_inv[2][3] = (pass_table[10].w);
+    ds_camera_inv[3][0] = (pass_table[11].x);
+    ds_camera_inv[3][1] = (pass_table[11].y);
+    ds_camera_inv[3][2] = (pass_table[11].z);
+    ds_camera_inv[3][3] = (pass_table[11].w);
+    ds_dpi_factor = (pass_table[12].x);
+    ds_dpi_dilate = (pass_table[12].y);
+    ds_time = (pass_table[12].z);
+    ds_draw_zbias = (draw_table[0].x);
+    ds_geom_pos.x = input.packed_geometry_0.x;
+    ds_geom_pos.y = input.packed_geometry_0.y;
+    ds_rect_pos.x = input.packed_instance_0.x;
+    ds_rect_pos.y = input.packed_instance_0.y;
+    ds_rect_size.x = input.packed_instance_0.z;
+    ds_rect_size.y = input.packed_instance_0.w;
+    ds_draw_clip.x = input.packed_instance_1.x;
+    ds_draw_clip.y = input.packed_instance_1.y;
+    ds_draw_clip.z = input.packed_instance_1.z;
+    ds_draw_clip.w = input.packed_instance_1.w;
+    ds_draw_depth = input.packed_instance_2.x;
+    ds_0 = input.packed_instance_2.y;
+    ds_color.x = input.packed_instance_2.z;
+    ds_color.y = input.packed_instance_2.w;
+    ds_color.z = input.packed_instance_3.x;
+    ds_color.w = input.packed_instance_3.y;
+
+    var varyings: Varyings;
+    varyings.position = fn_1_11_vertex();
+    varyings.packed_varying_0.x = ds_color.x;
+    varyings.packed_varying_0.y = ds_color.y;
+    varyings.packed_varying_0.z = ds_color.z;
+    varyings.packed_varying_0.w = ds_color.w;
+    varyings.packed_varying_1.x = ds_pos.x;
+    varyings.packed_varying_1.y = ds_pos.y;
+    return varyings;
+}
+
+@fragment
+fn pixel_main(varyings: Varyings) -> @location(0) vec4<f32> {
+    ds_view_transform[0][0] = (view_table[0].x);
+    ds_view_transform[0][1] = (view_table[0].y);
+    ds_view_transform[0][2] = (view_table[0].z);
+    ds_view_transform[0][3] = (view_table[0].w);
+    ds_view_transform[1][0] = (view_table[1].x);
+    ds_view_transform[1][1] = (view_table[1].y);
+    ds_view_transform[1][2] = (view_table[1].z);
+    ds_view_transform[1][3] = (view_table[1].w);
+    ds_view_transform[2][0] = (view_table[2].x);
+    ds_view_transform[2][1] = (view_table[2].y);
+    ds_view_transform[2][2] = (view_table[2].z);
+    ds_view_transform[2][3] = (view_table[2].w);
+    ds_view_transform[3][0] = (view_table[3].x);
+    ds_view_transform[3][1] = (view_table[3].y);
+    ds_view_transform[3][2] = (view_table[3].z);
+    ds_view_transform[3][3] = (view_table[3].w);
+    ds_camera_projection[0][0] = (pass_table[0].x);
+    ds_camera_projection[0][1] = (pass_table[0].y);
+    ds_camera_projection[0][2] = (pass_table[0].z);
+    ds_camera_projection[0][3] = (pass_table[0].w);
+    ds_camera_projection[1][0] = (pass_table[1].x);
+    ds_camera_projection[1][1] = (pass_table[1].y);
+    ds_camera_projection[1][2] = (pass_table[1].z);
+    ds_camera_projection[1][3] = (pass_table[1].w);
+    ds_camera_projection[2][0] = (pass_table[2].x);
+    ds_camera_projection[2][1] = (pass_table[2].y);
+    ds_camera_projection[2][2] = (pass_table[2].z);
+    ds_camera_projection[2][3] = (pass_table[2].w);
+    ds_camera_projection[3][0] = (pass_table[3].x);
+    ds_camera_projection[3][1] = (pass_table[3].y);
+    ds_camera_projection[3][2] = (pass_table[3].z);
+    ds_camera_projection[3][3] = (pass_table[3].w);
+    ds_camera_view[0][0] = (pass_table[4].x);
+    ds_camera_view[0][1] = (pass_table[4].y);
+    ds_camera_view[0][2] = (pass_table[4].z);
+    ds_camera_view[0][3] = (pass_table[4].w);
+    ds_camera_view[1][0] = (pass_table[5].x);
+    ds_camera_view[1][1] = (pass_table[5].y);
+    ds_camera_view[1][2] = (pass_table[5].z);
+    ds_camera_view[1][3] = (pass_table[5].w);
+    ds_camera_view[2][0] = (pass_table[6].x);
+    ds_camera_view[2][1] = (pass_table[6].y);
+    ds_camera_view[2][2] = (pass_table[6].z);
+    ds_camera_view[2][3] = (pass_table[6].w);
+    ds_camera_view[3][0] = (pass_table[7].x);
+    ds_camera_view[3][1] = (pass_table[7].y);
+    ds_camera_view[3][2] = (pass_table[7].z);
+    ds_camera_view[3][3] = (pass_table[7].w);
+    ds_camera_inv[0][0] = (pass_table[8].x);
+    ds_camera_inv[0][1] = (pass_table[8].y);
+    ds_camera_inv[0][2] = (pass_table[8].z);
+    ds_camera_inv[0][3] = (pass_table[8].w);
+    ds_camera_inv[1][0] = (pass_table[9].x);
+    ds_camera_inv[1][1] = (pass_table[9].y);
+    ds_camera_inv[1][2] = (pass_table[9].z);
+    ds_camera_inv[1][3] = (pass_table[9].w);
+    ds_camera_inv[2][0] = (pass_table[10].x);
+    ds_camera_inv[2][1] = (pass_table[10].y);
+    ds_camera_inv[2][2] = (pass_table[10].z);
+    ds_camera_inv[2][3] = (pass_table[10].w);
+    ds_camera_inv[3][0] = (pass_table[11].x);
+    ds_camera_inv[3][1] = (pass_table[11].y);
+    ds_camera_inv[3][2] = (pass_table[11].z);
+    ds_camera_inv[3][3] = (pass_table[11].w);
+    ds_dpi_factor = (pass_table[12].x);
+    ds_dpi_dilate = (pass_table[12].y);
+    ds_time = (pass_table[12].z);
+    ds_draw_zbias = (draw_table[0].x);
+    ds_color.x = varyings.packed_varying_0.x;
+    ds_color.y = varyings.packed_varying_0.y;
+    ds_color.z = varyings.packed_varying_0.z;
+    ds_color.w = varyings.packed_varying_0.w;
+    ds_pos.x = varyings.packed_varying_1.x;
+    ds_pos.y = varyings.packed_varying_1.y;
+
+    return fn_1_12_pixel();
+}
@@ -452,3 +452,264 @@ live_design!{
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            makepad_platform::makepad_shader_compiler::{
+                generate_wgsl,
+                shader_registry::LiveNodeFindResult,
+            },
+            shader::{
+                draw_color::DrawColor,
+                draw_icon::DrawIcon,
+                draw_line::DrawLine,
+                draw_quad::DrawQuad,
+                draw_text::DrawText,
+                draw_trapezoid::DrawTrapezoidVector,
+            },
+        },
+    };
+    
+    fn wgsl_of(cx: &Cx, draw_vars: &DrawVars) -> String {
+        let draw_shader = draw_vars.draw_shader.unwrap();
+        let draw_shader_def = cx.shader_registry.draw_shader_defs.get(&draw_shader.draw_shader_ptr).unwrap();
+        let const_table = &cx.draw_shaders.shaders[draw_shader.draw_shader_id].mapping.const_table;
+        generate_wgsl::generate_shader(draw_shader_def, const_table, &cx.shader_registry).wgsl
+    }
+    
+    // instances and uniforms, the Sdf2d struct with its methods, and a texture with its sampler
+    #[test]
+    fn wgsl_matches_snapshots() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let color = DrawColor::new_local(&mut cx);
+        let line = DrawLine::new_local(&mut cx);
+        let text = DrawText::new_local(&mut cx);
+        let shaders = [
+            ("draw_color", wgsl_of(&cx, &color.draw_vars)),
+            ("draw_line", wgsl_of(&cx, &line.draw_vars)),
+            ("draw_text", wgsl_of(&cx, &text.draw_vars)),
+        ];
+        for (name, wgsl) in shaders {
+            let path = format!("{}/src/shader/golden/{}.wgsl", env!("CARGO_MANIFEST_DIR"), name);
+            // MAKEPAD_BLESS_GOLDEN=1 takes the current output as the new snapshot,
+            // wgsl_validates_with_naga makes sure it is valid WGSL
+            if std::env::var("MAKEPAD_BLESS_GOLDEN").is_ok() {
+                std::fs::write(&path, &wgsl).unwrap();
+                continue
+            }
+            let snapshot = std::fs::read_to_string(&path).unwrap();
+            assert!(snapshot == wgsl, "{} changed, the new WGSL is:\n{}", name, wgsl);
+        }
+    }
+    
+    // a quad that calls every std fn so the whole std lib goes through the backend,
+    // and samples a texture both in uniform and in non-uniform control flow
+    const STD_LIB_QUAD: &str = r#"
+        import makepad_draw::shader::std::*;
+        import makepad_draw::shader::draw_quad::DrawQuad;
+        StdLibQuad = <DrawQuad> {
+            texture image: texture2d
+            
+            fn sample_image(self, pos: vec2) -> vec4 {
+                return sample2d(self.image, pos);
+            }
+            
+            fn pixel(self) -> vec4 {
+                let color = sample2d(self.image, self.pos);
+                if color.a > 0.5 {
+                    color = sample2d(self.image, self.pos * 0.5) + self.sample_image(self.pos);
+                }
+                color = color + self.sample_image(self.pos.yx);
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.translate(1.0, 1.0);
+                sdf.rotate(0.1, 5.0, 5.0);
+                sdf.scale(1.1, 5.0, 5.0);
+                sdf.clear(#0000);
+                sdf.box(1.0, 1.0, 10.0, 10.0, 2.0);
+                sdf.fill_keep(#f00);
+                sdf.stroke_keep(#0f0, 1.0);
+                sdf.circle(5.0, 5.0, 3.0);
+                sdf.union();
+                sdf.hexagon(5.0, 5.0, 3.0);
+                sdf.intersect();
+                sdf.rect(2.0, 2.0, 4.0, 4.0);
+                sdf.subtract();
+                sdf.box_x(1.0, 1.0, 8.0, 8.0, 1.0, 2.0);
+                sdf.gloop(2.0);
+                sdf.box_y(1.0, 1.0, 8.0, 8.0, 1.0, 2.0);
+                sdf.blend(0.5);
+                sdf.box_all(1.0, 1.0, 8.0, 8.0, 1.0, 2.0, 3.0, 4.0);
+                sdf.fill_premul(#00f8);
+                sdf.hline(3.0, 1.0);
+                sdf.glow_keep(#fff, 2.0);
+                sdf.move_to(1.0, 1.0);
+                sdf.line_to(8.0, 2.0);
+                sdf.close_path();
+                sdf.glow(#ff0, 1.0);
+                sdf.stroke(#0ff, 1.0);
+                let arc = sdf.arc2(5.0, 5.0, 4.0, 0.0, PI);
+                sdf.fill(arc);
+                let shadow = GaussShadow::rounded_box_shadow(vec2(1.0), vec2(9.0), self.pos * 10.0, 2.0, 1.0)
+                    + GaussShadow::box_shadow(vec2(1.0), vec2(9.0), self.pos * 10.0, 2.0);
+                let spin = Math::rotate_2d(self.pos, TORAD * 45.0) * Math::random_2d(self.pos);
+                let pal = Pal::iq0(spin.x) + Pal::iq1(spin.y) + Pal::iq2(0.2) + Pal::iq3(0.3)
+                    + Pal::iq4(0.4) + Pal::iq5(0.5) + Pal::iq6(0.6) + Pal::iq7(0.7);
+                let hsv = Pal::rgb2hsv(Pal::hsv2rgb(vec4(pal, 1.0)));
+                return Pal::premul(sdf.result * shadow + hsv + color);
+            }
+        }
+    "#;
+    
+    fn validate_wgsl(name: &str, wgsl: &str) {
+        let module = match naga::front::wgsl::parse_str(wgsl) {
+            Ok(module) => module,
+            Err(err) => panic!("{} doesn't parse: {}", name, err.emit_to_string(wgsl))
+        };
+        let mut validator = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty());
+        if let Err(err) = validator.validate(&module) {
+            panic!("{} doesn't validate: {}", name, err.emit_to_string(wgsl));
+        }
+    }
+    
+    // the mip level only gets picked by textureSample, which is only valid in uniform control flow.
+    // naga doesn't enforce that for the fragment stage yet, so it is checked here
+    #[test]
+    fn wgsl_samples_mips_where_control_flow_is_uniform() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        let std_lib = std_lib_quad(&mut cx);
+        let wgsl = wgsl_of(&cx, &std_lib.draw_vars);
+        let pixel = &wgsl[wgsl.find("_pixel(").unwrap()..];
+        let pixel = &pixel[..pixel.find("\n}").unwrap()];
+        let uniform = pixel.matches("sample2d(").count();
+        let level_0 = pixel.matches("sample2d_level(").count();
+        // the first sample, then in the branch, then the helper that is called from both places
+        assert_eq!((uniform, level_0), (1, 1));
+        assert!(wgsl.contains("return sample2d_level(ds_image, ds_image_sampler, var_pos_0);"));
+    }
+    
+    fn std_lib_quad(cx: &mut Cx) -> DrawQuad {
+        crate::live_design(cx);
+        cx.register_live_body(LiveBody {
+            file: "std_lib_quad.rs".to_string(),
+            cargo_manifest_path: env!("CARGO_MANIFEST_DIR").to_string(),
+            module_path: "std_lib_quad".to_string(),
+            line: 0,
+            column: 0,
+            code: STD_LIB_QUAD.to_string(),
+            live_type_infos: Vec::new(),
+        });
+        cx.live_expand();
+        let std_lib_ptr = {
+            let live_registry = cx.live_registry.borrow();
+            let module_id = LiveModuleId::from_str("std_lib_quad").unwrap();
+            live_registry.module_id_and_name_to_ptr(module_id, live_id!(StdLibQuad)).unwrap()
+        };
+        DrawQuad::new_from_ptr(cx, Some(std_lib_ptr))
+    }
+    
+    #[test]
+    fn wgsl_validates_with_naga() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        let std_lib = std_lib_quad(&mut cx);
+        let quad = DrawQuad::new_local(&mut cx);
+        let color = DrawColor::new_local(&mut cx);
+        let icon = DrawIcon::new_local(&mut cx);
+        let line = DrawLine::new_local(&mut cx);
+        let text = DrawText::new_local(&mut cx);
+        let trapezoid = DrawTrapezoidVector::new_local(&mut cx);
+        for (name, draw_vars) in [
+            ("std_lib_quad", &std_lib.draw_vars),
+            ("draw_quad", &quad.draw_vars),
+            ("draw_color", &color.draw_vars),
+            ("draw_icon", &icon.draw_vars),
+            ("draw_line", &line.draw_vars),
+            ("draw_text", &text.draw_vars),
+            ("draw_trapezoid", &trapezoid.draw_vars),
+        ] {
+            validate_wgsl(name, &wgsl_of(&cx, draw_vars));
+        }
+    }
+    
+    // one error of each kind that carries notes, at known lines of this source
+    const SHADER_TYPOS: &str = r#"
+        ShaderTypos = {
+            fn misspelled_local(x: float) -> float {
+                let shade = x * 2.0;
+                return shdae;
+            }
+            fn mismatched_operands(x: float) -> vec2 {
+                let a = vec2(x, x);
+                let b = vec3(x, x, x);
+                return a + b;
+            }
+            fn misspelled_builtin(x: vec2) -> float {
+                return lenght(x);
+            }
+            fn assigns_a_loop_index(x: float) -> float {
+                for i in 0..4 {
+                    i = 2;
+                }
+                return x;
+            }
+        }
+    "#;
+    
+    // the error and its notes as "line: message"
+    fn analyse_error(cx: &mut Cx, name: LiveId) -> Vec<String> {
+        let live_registry_rc = cx.live_registry.clone();
+        let live_registry = live_registry_rc.borrow();
+        let module_id = LiveModuleId::from_str("shader_typos").unwrap();
+        let typos = live_registry.module_id_and_name_to_ptr(module_id, live_id!(ShaderTypos)).unwrap();
+        let fn_ptr = match cx.shader_registry.find_live_node_by_path(&live_registry, typos, &[name]) {
+            LiveNodeFindResult::Function(fn_ptr) => fn_ptr,
+            _ => panic!("{} is not a fn", name)
+        };
+        let err = cx.shader_registry.analyse_plain_fn(&live_registry, None, fn_ptr).err().unwrap();
+        let err = live_registry.live_error_to_live_file_error(err);
+        let mut lines = vec![format!("{}: {}", err.span.start.line, err.message)];
+        for note in err.notes {
+            lines.push(format!("{}: {}", note.span.start.line, note.message));
+        }
+        lines
+    }
+    
+    #[test]
+    fn shader_errors_carry_notes() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.register_live_body(LiveBody {
+            file: "shader_typos.rs".to_string(),
+            cargo_manifest_path: env!("CARGO_MANIFEST_DIR").to_string(),
+            module_path: "shader_typos".to_string(),
+            line: 0,
+            column: 0,
+            code: SHADER_TYPOS.to_string(),
+            live_type_infos: Vec::new(),
+        });
+        cx.live_expand();
+        assert_eq!(analyse_error(&mut cx, live_id!(misspelled_local)), [
+            "4: `shdae` is not defined in this scope",
+            "4: did you mean `shade`?",
+        ]);
+        assert_eq!(analyse_error(&mut cx, live_id!(mismatched_operands)), [
+            "9: can't apply binary operator `+` to operands of type `vec2` and `vec3`",
+            "7: `a` declared here",
+            "9: this is `vec2`",
+            "9: this is `vec3`",
+        ]);
+        // builtins are only suggested by name once their name was interned, here by the std shaders
+        assert_eq!(analyse_error(&mut cx, live_id!(misspelled_builtin)), [
+            "12: Function not found lenght",
+            "12: did you mean `length`?",
+        ]);
+        assert_eq!(analyse_error(&mut cx, live_id!(assigns_a_loop_index)), [
+            "16: expression is not a valid left hand side",
+            "15: `i` is declared here and can't be assigned to",
+        ]);
+    }
+}
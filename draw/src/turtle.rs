@@ -15,7 +15,8 @@ pub struct Layout {
     #[live] pub align: Align,
     #[live] pub flow: Flow,
     #[live] pub spacing: f64,
-    #[live] pub line_spacing: f64
+    #[live] pub line_spacing: f64,
+    #[live] pub direction: LayoutDirection
 }
 
 impl Default for Layout{
@@ -28,18 +29,44 @@ impl Default for Layout{
             align: Align{x:0.0,y:0.0},
             flow: Flow::Right,
             spacing: 0.0,
-            line_spacing: 0.0
+            line_spacing: 0.0,
+            direction: LayoutDirection::Inherit
         }
     }
 }
 
-#[derive(Copy, Clone, Default, Debug, Live, LiveHook, LiveRegister)]
+/// How a child takes space in its parent. `min_*`/`max_*` clamp whatever the size evaluates to,
+/// `fill_weight` is the share of the leftover space a deferred `Fill` child gets along the flow.
+#[derive(Copy, Clone, Debug, Live, LiveHook, LiveRegister)]
 #[live_ignore]
 pub struct Walk {
     #[live] pub abs_pos: Option<DVec2>,
     #[live] pub margin: Margin,
     #[live] pub width: Size,
     #[live] pub height: Size,
+    #[live] pub cell: GridCell,
+    #[live] pub min_width: Option<f64>,
+    #[live] pub max_width: Option<f64>,
+    #[live] pub min_height: Option<f64>,
+    #[live] pub max_height: Option<f64>,
+    #[live(1.0)] pub fill_weight: f64,
+}
+
+impl Default for Walk {
+    fn default() -> Self {
+        Self {
+            abs_pos: None,
+            margin: Margin::default(),
+            width: Size::default(),
+            height: Size::default(),
+            cell: GridCell::default(),
+            min_width: None,
+            max_width: None,
+            min_height: None,
+            max_height: None,
+            fill_weight: 1.0,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Default, Debug, Live, LiveHook, LiveRegister)]
@@ -71,6 +98,16 @@ impl Default for Axis2 {
     }
 }
 
+/// `RightToLeft` lays the children of a turtle out as usual and then mirrors them within it,
+/// which flips `Flow::Right`, `align.x`, padding and margins. `Inherit` follows the parent turtle.
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum LayoutDirection {
+    #[pick] Inherit,
+    LeftToRight,
+    RightToLeft
+}
+
 #[derive(Copy, Clone, Debug, Live, LiveHook)]
 #[live_ignore]
 pub enum Flow {
@@ -79,7 +116,49 @@ pub enum Flow {
     //Left,
     //Up,
     Overlay, 
-    RightWrap
+    RightWrap,
+    #[live(columns: GridTracks::default(), rows: GridTracks::default())]
+    Grid{columns: GridTracks, rows: GridTracks}
+}
+
+/// A column or row of a `Flow::Grid`. Plain numbers in the DSL are `Fixed`.
+#[derive(Copy, Clone, Debug, PartialEq, Live)]
+#[live_ignore]
+pub enum GridTrack {
+    #[pick(1.0)] Fr(f64),
+    #[live(100.0)] Fixed(f64),
+    Fit
+}
+
+pub const GRID_MAX_TRACKS: usize = 12;
+
+/// The tracks of one grid axis, stored inline so `Layout` stays `Copy`.
+/// In the DSL this is an array like `[120, Fit, Fr(2.0)]`, or a count of equal `Fr(1.0)` tracks.
+#[derive(Copy, Clone, Debug)]
+pub struct GridTracks {
+    len: usize,
+    tracks: [GridTrack; GRID_MAX_TRACKS]
+}
+
+/// Where a child goes in a `Flow::Grid`. Without a column and row it takes the next free cell,
+/// with only one of them it takes the first free cell in that column or row.
+#[derive(Copy, Clone, Debug, Live, LiveHook, LiveRegister)]
+#[live_ignore]
+pub struct GridCell {
+    #[live] pub column: Option<usize>,
+    #[live] pub row: Option<usize>,
+    #[live(1usize)] pub column_span: usize,
+    #[live(1usize)] pub row_span: usize,
+    #[live] pub align: Option<Align>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct GridPlacement {
+    column: usize,
+    row: usize,
+    column_span: usize,
+    row_span: usize,
+    align: Align
 }
 
 #[derive(Copy, Clone, Debug, Live)]
@@ -95,8 +174,7 @@ pub enum Size {
 pub enum DeferWalk{
     Unresolved{
         defer_index: usize,
-        margin: Margin,
-        other_axis: Size,
+        walk: Walk,
         pos: DVec2
     },
     Resolved(Walk)
@@ -117,6 +195,17 @@ pub struct TurtleWalk {
     align_start: usize,
     defer_index: usize,
     rect: Rect,
+    grid: Option<GridPlacement>,
+    margin: Margin,
+    abs: bool,
+    unaligned: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct DeferFill {
+    weight: f64,
+    min: Option<f64>,
+    max: Option<f64>
 }
 
 #[derive(Clone, Default, Debug)]
@@ -126,6 +215,7 @@ pub struct Turtle {
     align_start: usize,
     turtle_walks_start: usize,
     defer_count: usize,
+    defer_fills: Vec<DeferFill>,
     shift: DVec2,
     pos: DVec2,
     origin: DVec2,
@@ -133,7 +223,10 @@ pub struct Turtle {
     height: f64,
     width_used: f64,
     height_used: f64,
-    guard_area: Area
+    guard_area: Area,
+    grid_cursor: usize,
+    grid_occupied: Vec<bool>,
+    rtl: bool,
 }
 
 impl<'a> Cx2d<'a> {
@@ -154,12 +247,17 @@ impl<'a> Cx2d<'a> {
             return None
         }
         let turtle = self.turtles.last_mut().unwrap();
+        if let Flow::Grid{..} = turtle.layout.flow {
+            // fill children take their cell now but are sized after the others have measured the tracks
+            if walk.width.is_fill() || walk.height.is_fill() {
+                let placement = turtle.grid_place(&walk.cell);
+                return Some(DeferWalk::Resolved(Walk {cell: placement.to_cell(), ..walk}))
+            }
+            return None
+        }
         let defer_index = turtle.defer_count;
         let pos = turtle.pos;
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk(&walk);
         let margin_size = walk.margin.size();
         match turtle.layout.flow {
             Flow::Right if walk.width.is_fill() => {
@@ -168,10 +266,10 @@ impl<'a> Cx2d<'a> {
                 turtle.update_width_max(turtle.pos.x, 0.0);
                 turtle.update_height_max(turtle.pos.y, size.y + margin_size.y);
                 turtle.defer_count += 1;
+                turtle.defer_fills.push(DeferFill {weight: walk.fill_weight, min: walk.min_width, max: walk.max_width});
                 Some(DeferWalk::Unresolved{
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.height,
+                    walk,
                     pos: pos + spacing
                 })
             },
@@ -181,10 +279,10 @@ impl<'a> Cx2d<'a> {
                 turtle.update_width_max(turtle.pos.x, size.x + margin_size.x);
                 turtle.update_height_max(turtle.pos.y, 0.0);
                 turtle.defer_count += 1;
+                turtle.defer_fills.push(DeferFill {weight: walk.fill_weight, min: walk.min_height, max: walk.max_height});
                 Some(DeferWalk::Unresolved {
                     defer_index,
-                    margin: walk.margin,
-                    other_axis: walk.width,
+                    walk,
                     pos: pos + spacing
                 })
             },
@@ -205,6 +303,7 @@ impl<'a> Cx2d<'a> {
             
     pub fn begin_pass_sized_turtle(&mut self, layout: Layout) {
         let pass_size = self.current_pass_size();
+        let rtl = self.layout_is_rtl(&layout);
         self.align_list.push(AlignEntry::BeginTurtle(dvec2(0.0,0.0),pass_size));
         let turtle = Turtle {
             walk: Walk::fill(),
@@ -212,6 +311,7 @@ impl<'a> Cx2d<'a> {
             align_start: self.align_list.len() - 1,
             turtle_walks_start: self.turtle_walks.len(),
             defer_count: 0,
+            defer_fills: Vec::new(),
             pos: DVec2 {
                 x: layout.padding.left,
                 y: layout.padding.top
@@ -223,6 +323,9 @@ impl<'a> Cx2d<'a> {
             width_used: layout.padding.left,
             height_used: layout.padding.top,
             guard_area: Area::Empty,
+            grid_cursor: 0,
+            grid_occupied: Vec::new(),
+            rtl,
         };
         self.turtles.push(turtle);
     }
@@ -262,15 +365,26 @@ impl<'a> Cx2d<'a> {
         self.turtle_walks.truncate(turtle.turtle_walks_start);
     }
     
-    pub fn begin_turtle_with_guard(&mut self, walk: Walk, layout: Layout, guard_area: Area) {
+    pub fn begin_turtle_with_guard(&mut self, walk: Walk, mut layout: Layout, guard_area: Area) {
+        let rtl = self.layout_is_rtl(&layout);
+        if rtl {
+            // scrolling a mirrored turtle moves its content the other way
+            layout.scroll.x = -layout.scroll.x;
+        }
         let (origin, width, height, draw_clip) = if let Some(parent) = self.turtles.last() {
             
             let o = walk.margin.left_top() + if let Some(pos) = walk.abs_pos {pos} else {
                 parent.pos + parent.child_spacing(self.turtle_walks.len())
             };
             
-            let w = parent.eval_width(walk.width, walk.margin, parent.layout.flow);
-            let h = parent.eval_height(walk.height, walk.margin, parent.layout.flow);
+            let (w, h) = if let (Flow::Grid{..}, None) = (parent.layout.flow, walk.abs_pos) {
+                let size = self.grid_walk_size(&walk, &parent.grid_find(&walk.cell));
+                (size.x, size.y)
+            }
+            else {
+                let size = parent.eval_walk(&walk);
+                (size.x, size.y)
+            };
             
             // figure out new clipping rect
             let (x0, x1) = if layout.clip_x {
@@ -296,8 +410,8 @@ impl<'a> Cx2d<'a> {
         }
         else {
             let o = DVec2 {x: walk.margin.left, y: walk.margin.top};
-            let w = walk.width.fixed_or_nan();
-            let h = walk.height.fixed_or_nan();
+            let w = walk.clamp_width(walk.width.fixed_or_nan());
+            let h = walk.clamp_height(walk.height.fixed_or_nan());
             
             (o, w, h, (dvec2(o.x, o.y), dvec2(o.x + w, o.y + h)))
         };
@@ -308,6 +422,7 @@ impl<'a> Cx2d<'a> {
             align_start: self.align_list.len()-1,
             turtle_walks_start: self.turtle_walks.len(),
             defer_count: 0,
+            defer_fills: Vec::new(),
             pos: DVec2 {
                 x: origin.x + layout.padding.left,
                 y: origin.y + layout.padding.top
@@ -319,11 +434,23 @@ impl<'a> Cx2d<'a> {
             width_used: layout.padding.left,
             height_used: layout.padding.top,
             guard_area,
+            grid_cursor: 0,
+            grid_occupied: Vec::new(),
+            rtl,
         };
         
         self.turtles.push(turtle);
     }
     
+    /// Whether a turtle begun with this layout in the current turtle would lay out right to left.
+    pub fn layout_is_rtl(&self, layout: &Layout) -> bool {
+        match layout.direction {
+            LayoutDirection::Inherit => self.turtles.last().map(|parent| parent.rtl).unwrap_or(false),
+            LayoutDirection::LeftToRight => false,
+            LayoutDirection::RightToLeft => true
+        }
+    }
+    
     pub fn turtle_has_align_items(&mut self)->bool{
         self.align_list.len() != self.turtle().align_start + 1
     }
@@ -338,11 +465,37 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn end_turtle_with_guard(&mut self, guard_area: Area) -> Rect {
-        let turtle = self.turtles.pop().unwrap();
+        let mut turtle = self.turtles.pop().unwrap();
         if guard_area != turtle.guard_area {
             panic!("End turtle guard area misaligned!, begin/end pair not matched begin {:?} end {:?}", turtle.guard_area, guard_area)
         }
         
+        let grid_tracks = if let Flow::Grid{..} = turtle.layout.flow {
+            let (columns, rows) = self.grid_track_sizes(&turtle);
+            turtle.width_used = turtle.layout.padding.left + grid_tracks_extent(&columns, turtle.layout.spacing);
+            turtle.height_used = turtle.layout.padding.top + grid_tracks_extent(&rows, turtle.layout.line_spacing);
+            Some((columns, rows))
+        }
+        else {
+            None
+        };
+        
+        // a fit turtle that runs into its min or max size aligns its children as if it was fixed to it
+        if turtle.width.is_nan() {
+            let used = turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x;
+            let clamped = turtle.walk.clamp_width(used);
+            if clamped != used {
+                turtle.width = clamped;
+            }
+        }
+        if turtle.height.is_nan() {
+            let used = turtle.height_used + turtle.layout.padding.bottom - turtle.layout.scroll.y;
+            let clamped = turtle.walk.clamp_height(used);
+            if clamped != used {
+                turtle.height = clamped;
+            }
+        }
+        
         // computed width / height
         let w = if turtle.width.is_nan() {
             Size::Fixed(turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x)
@@ -358,18 +511,18 @@ impl<'a> Cx2d<'a> {
             Size::Fixed(turtle.height)
         };
         
+        let mirror_width = w.fixed_or_zero();
         match turtle.layout.flow {
             Flow::Right => {
                 if turtle.defer_count > 0 {
                     let left = turtle.width_left();
-                    let part = left / turtle.defer_count as f64;
+                    let fills = resolve_fill_sizes(left, &turtle.defer_fills);
+                    let leftover = max_zero_keep_nan(left - fills.iter().sum::<f64>());
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
-                        let shift_x = walk.defer_index as f64 * part;
+                        let shift_x = fills[0..walk.defer_index].iter().sum::<f64>() + turtle.layout.align.x * leftover;
                         let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
-                        let align_start = walk.align_start;
-                        let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                        self.move_turtle_walk(&turtle, i, shift_x, shift_y, mirror_width);
                     }
                 }
                 else {
@@ -377,27 +530,29 @@ impl<'a> Cx2d<'a> {
                         let walk = &self.turtle_walks[i];
                         let shift_x = turtle.layout.align.x * turtle.width_left();
                         let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
-                        let align_start = walk.align_start;
-                        let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                        self.move_turtle_walk(&turtle, i, shift_x, shift_y, mirror_width);
                     }
                 }
             },
             Flow::RightWrap=>{
                 if turtle.defer_count > 0{panic!()}
                 // for now we only support align:0,0
+                if turtle.rtl {
+                    for i in turtle.turtle_walks_start..self.turtle_walks.len() {
+                        self.move_turtle_walk(&turtle, i, 0.0, 0.0, mirror_width);
+                    }
+                }
             }
             Flow::Down => {
                 if turtle.defer_count > 0 {
                     let left = turtle.height_left();
-                    let part = left / turtle.defer_count as f64;
+                    let fills = resolve_fill_sizes(left, &turtle.defer_fills);
+                    let leftover = max_zero_keep_nan(left - fills.iter().sum::<f64>());
                     for i in turtle.turtle_walks_start..self.turtle_walks.len() {
                         let walk = &self.turtle_walks[i];
                         let shift_x = turtle.layout.align.x * (turtle.padded_width_or_used() - walk.rect.size.x);
-                        let shift_y = walk.defer_index as f64 * part;
-                        let align_start = walk.align_start;
-                        let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                        let shift_y = fills[0..walk.defer_index].iter().sum::<f64>() + turtle.layout.align.y * leftover;
+                        self.move_turtle_walk(&turtle, i, shift_x, shift_y, mirror_width);
                     }
                 }
                 else {
@@ -405,9 +560,7 @@ impl<'a> Cx2d<'a> {
                         let walk = &self.turtle_walks[i];
                         let shift_x = turtle.layout.align.x * (turtle.padded_width_or_used() - walk.rect.size.x);
                         let shift_y = turtle.layout.align.y * turtle.height_left();
-                        let align_start = walk.align_start;
-                        let align_end = self.get_turtle_walk_align_end(i);
-                        self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                        self.move_turtle_walk(&turtle, i, shift_x, shift_y, mirror_width);
                     }
                 }
             },
@@ -416,9 +569,26 @@ impl<'a> Cx2d<'a> {
                     let walk = &self.turtle_walks[i];
                     let shift_x = turtle.layout.align.x * (turtle.padded_width_or_used() - walk.rect.size.x);
                     let shift_y = turtle.layout.align.y * (turtle.padded_height_or_used() - walk.rect.size.y);
-                    let align_start = walk.align_start;
-                    let align_end = self.get_turtle_walk_align_end(i);
-                    self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+                    self.move_turtle_walk(&turtle, i, shift_x, shift_y, mirror_width);
+                }
+            }
+            Flow::Grid{..} => {
+                // every cell was walked at the padded origin, now that all tracks are known move them into place
+                let (columns, rows) = grid_tracks.unwrap();
+                let origin = turtle.origin + turtle.layout.padding.left_top();
+                for i in turtle.turtle_walks_start..self.turtle_walks.len() {
+                    let walk = &self.turtle_walks[i];
+                    let (shift_x, shift_y) = if let Some(placement) = &walk.grid {
+                        let cell = grid_cell_rect(&columns, &rows, &turtle.layout, placement);
+                        (
+                            origin.x + cell.pos.x + placement.align.x * (cell.size.x - walk.rect.size.x) - walk.rect.pos.x,
+                            origin.y + cell.pos.y + placement.align.y * (cell.size.y - walk.rect.size.y) - walk.rect.pos.y
+                        )
+                    }
+                    else {
+                        (0.0, 0.0)
+                    };
+                    self.move_turtle_walk(&turtle, i, shift_x, shift_y, mirror_width);
                 }
             }
         }
@@ -479,24 +649,28 @@ impl<'a> Cx2d<'a> {
     }
     
      fn walk_turtle_move(&mut self, walk: Walk, align_start: usize) -> Rect {
+        if let (Flow::Grid{..}, None) = (self.turtle().layout.flow, walk.abs_pos) {
+            return self.walk_turtle_move_grid(walk, align_start)
+        }
         
         let turtle = self.turtles.last_mut().unwrap();
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        let size = turtle.eval_walk(&walk);
         
         if let Some(pos) = walk.abs_pos {
             self.turtle_walks.push(TurtleWalk {
                 align_start,
                 defer_index: 0,
-                rect: Rect {pos, size: size + walk.margin.size()}
+                rect: Rect {pos, size: size + walk.margin.size()},
+                grid: None,
+                margin: walk.margin,
+                abs: true,
+                unaligned: false,
             });
             
             match turtle.layout.flow {
                 Flow::Right=>turtle.update_height_max(pos.y, size.y + walk.margin.size().y),
                 Flow::Down=>turtle.update_width_max(pos.x, size.x + walk.margin.size().x),
-                Flow::Overlay => { // do not walk
+                Flow::Overlay | Flow::Grid{..} => { // do not walk
                     turtle.update_width_max(pos.x, size.x);
                     turtle.update_height_max(pos.y,size.y);
                 }
@@ -508,6 +682,7 @@ impl<'a> Cx2d<'a> {
         }
         else {
             let spacing = turtle.child_spacing(self.turtle_walks.len());
+            let rtl = turtle.rtl;
             let mut pos = turtle.pos;
             let margin_size = walk.margin.size();
             let defer_index = turtle.defer_count;
@@ -567,12 +742,19 @@ impl<'a> Cx2d<'a> {
                     turtle.update_width_max(turtle.pos.x, size.x);
                     turtle.update_height_max(turtle.pos.y,size.y);
                 }
+                Flow::Grid{..} => unreachable!()
             };
             
+            // only mirroring reads back where a flowed child went, and it has to see the spacing before it
+            let walked_pos = if rtl {pos + spacing} else {pos};
             self.turtle_walks.push(TurtleWalk {
                 align_start,
                 defer_index,
-                rect: Rect {pos, size: size + margin_size}
+                rect: Rect {pos: walked_pos, size: size + margin_size},
+                grid: None,
+                margin: walk.margin,
+                abs: false,
+                unaligned: false,
             });
             Rect {pos: pos + walk.margin.left_top() + spacing, size}
         }
@@ -583,10 +765,14 @@ impl<'a> Cx2d<'a> {
             return Rect::default()
         }
         let turtle = self.turtles.last().unwrap();
-        let size = dvec2(
-            turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
-            turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
-        );
+        if let (Flow::Grid{..}, None) = (turtle.layout.flow, walk.abs_pos) {
+            let placement = turtle.grid_find(&walk.cell);
+            let (columns, rows) = self.grid_track_sizes(turtle);
+            let cell = grid_cell_rect(&columns, &rows, &turtle.layout, &placement);
+            let size = self.grid_walk_size(&walk, &placement);
+            return Rect {pos: turtle.pos + cell.pos + walk.margin.left_top(), size}
+        }
+        let size = turtle.eval_walk(&walk);
         
         if let Some(pos) = walk.abs_pos {
             Rect {pos: pos + walk.margin.left_top(), size}
@@ -739,6 +925,48 @@ impl<'a> Cx2d<'a> {
         }
     }
     
+    // moves the draw items of a child by its alignment shift. in a right to left turtle the child is
+    // mirrored within the turtle instead, absolute children only get their shift flipped
+    fn move_turtle_walk(&mut self, turtle: &Turtle, i: usize, shift_x: f64, shift_y: f64, width: f64) {
+        let walk = &self.turtle_walks[i];
+        let (shift_x, shift_y) = if walk.unaligned {
+            (0.0, 0.0)
+        }
+        else if turtle.rtl {
+            let shift_x = if shift_x.is_nan() {0.0} else {shift_x};
+            if walk.abs {
+                (-shift_x, shift_y)
+            }
+            else {
+                // mirror the content rather than the margin box so the margins swap sides too
+                let x = walk.rect.pos.x + walk.margin.left - turtle.origin.x;
+                let w = walk.rect.size.x - walk.margin.width();
+                (width - 2.0 * x - w - shift_x, shift_y)
+            }
+        }
+        else {
+            (shift_x, shift_y)
+        };
+        let align_start = walk.align_start;
+        let align_end = self.get_turtle_walk_align_end(i);
+        self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+    }
+    
+    /// Anything drawn in the current turtle after this stays where it was drawn when the turtle
+    /// aligns or mirrors its children, for decorations like scroll bars drawn at the end of a turtle.
+    pub fn mark_unaligned(&mut self) {
+        let defer_index = self.turtle().defer_count;
+        self.turtle_walks.push(TurtleWalk {
+            align_start: self.align_list.len(),
+            defer_index,
+            rect: Rect::default(),
+            grid: None,
+            margin: Margin::default(),
+            abs: false,
+            unaligned: true,
+        });
+    }
+    
     fn get_turtle_walk_align_end(&self, i: usize) -> usize {
         if i < self.turtle_walks.len() - 1 {
             self.turtle_walks[i + 1].align_start
@@ -763,6 +991,189 @@ impl<'a> Cx2d<'a> {
         //let turtle = self.turtle();
         self.add_aligned_rect_area(area, rect)
     }
+    
+    fn walk_turtle_move_grid(&mut self, walk: Walk, align_start: usize) -> Rect {
+        let placement = self.turtle_mut().grid_place(&walk.cell);
+        let size = self.grid_walk_size(&walk, &placement);
+        let turtle = self.turtles.last_mut().unwrap();
+        let pos = turtle.pos;
+        let margin_size = walk.margin.size();
+        turtle.update_width_max(pos.x, size.x + margin_size.x);
+        turtle.update_height_max(pos.y, size.y + margin_size.y);
+        self.turtle_walks.push(TurtleWalk {
+            align_start,
+            defer_index: 0,
+            rect: Rect {pos, size: size + margin_size},
+            grid: Some(placement),
+            margin: walk.margin,
+            abs: false,
+            unaligned: false,
+        });
+        Rect {pos: pos + walk.margin.left_top(), size}
+    }
+    
+    // fill sizes come from the cell as far as the tracks are known, the rest evaluates as in an overlay
+    fn grid_walk_size(&self, walk: &Walk, placement: &GridPlacement) -> DVec2 {
+        let turtle = self.turtle();
+        let cell = if walk.width.is_fill() || walk.height.is_fill() {
+            let (columns, rows) = self.grid_track_sizes(turtle);
+            grid_cell_rect(&columns, &rows, &turtle.layout, placement).size
+        }
+        else {
+            dvec2(0.0, 0.0)
+        };
+        dvec2(
+            walk.clamp_width(if walk.width.is_fill() {
+                max_zero_keep_nan(cell.x - walk.margin.width())
+            }
+            else {
+                turtle.eval_width(walk.width, walk.margin, turtle.layout.flow)
+            }),
+            walk.clamp_height(if walk.height.is_fill() {
+                max_zero_keep_nan(cell.y - walk.margin.height())
+            }
+            else {
+                turtle.eval_height(walk.height, walk.margin, turtle.layout.flow)
+            })
+        )
+    }
+    
+    // measures the column and row sizes of a grid turtle from the cells walked so far
+    fn grid_track_sizes(&self, turtle: &Turtle) -> (Vec<f64>, Vec<f64>) {
+        let (columns, rows) = if let Flow::Grid{columns, rows} = &turtle.layout.flow {
+            (columns, rows)
+        }
+        else {
+            panic!("grid_track_sizes on a turtle without Flow::Grid")
+        };
+        let walks = &self.turtle_walks[turtle.turtle_walks_start..];
+        let placements = || walks.iter().filter_map( | walk | walk.grid.map( | p | (p, walk.rect.size)));
+        
+        let column_count = placements().fold(columns.len().max(1), | n, (p, _) | n.max(p.column + p.column_span));
+        let row_count = placements().fold(rows.len(), | n, (p, _) | n.max(p.row + p.row_span));
+        
+        let column_sizes = grid_track_sizes(
+            columns,
+            column_count,
+            turtle.width - turtle.layout.padding.width(),
+            turtle.layout.spacing,
+            placements().map( | (p, size) | (p.column, p.column_span, size.x))
+        );
+        let row_sizes = grid_track_sizes(
+            rows,
+            row_count,
+            turtle.height - turtle.layout.padding.height(),
+            turtle.layout.line_spacing,
+            placements().map( | (p, size) | (p.row, p.row_span, size.y))
+        );
+        (column_sizes, row_sizes)
+    }
+}
+
+// resolves the sizes of one grid axis. items are (track, span, size), fit tracks grow to hold them
+// and fractional tracks share what is left, or behave as fit when the turtle itself fits its content
+fn grid_track_sizes(tracks: &GridTracks, count: usize, available: f64, gap: f64, items: impl Iterator<Item = (usize, usize, f64)> + Clone) -> Vec<f64> {
+    let is_fit = | i: usize | match tracks.get(i) {
+        None | Some(GridTrack::Fit) => true,
+        Some(GridTrack::Fr(_)) => available.is_nan(),
+        Some(GridTrack::Fixed(_)) => false
+    };
+    let mut sizes = vec![0.0; count];
+    for (i, size) in sizes.iter_mut().enumerate() {
+        if let Some(GridTrack::Fixed(v)) = tracks.get(i) {
+            *size = max_zero_keep_nan(v);
+        }
+    }
+    for (track, span, size) in items.clone() {
+        if span == 1 && track < count && is_fit(track) {
+            sizes[track] = f64::max(sizes[track], size);
+        }
+    }
+    // spanning items only grow the last fit track they cover
+    for (track, span, size) in items {
+        if span > 1 && track < count {
+            let end = (track + span).min(count);
+            if let Some(last_fit) = (track..end).rev().find( | i | is_fit(*i)) {
+                let spanned = grid_tracks_extent(&sizes[track..end], gap);
+                if spanned < size {
+                    sizes[last_fit] += size - spanned;
+                }
+            }
+        }
+    }
+    if !available.is_nan() {
+        let fr_total: f64 = (0..count).filter_map( | i | match tracks.get(i) {
+            Some(GridTrack::Fr(fr)) => Some(fr.max(0.0)),
+            _ => None
+        }).sum();
+        if fr_total > 0.0 {
+            let left = (available - grid_tracks_extent(&sizes, gap)).max(0.0);
+            for (i, size) in sizes.iter_mut().enumerate() {
+                if let Some(GridTrack::Fr(fr)) = tracks.get(i) {
+                    *size = left * fr.max(0.0) / fr_total;
+                }
+            }
+        }
+    }
+    sizes
+}
+
+fn grid_tracks_extent(sizes: &[f64], gap: f64) -> f64 {
+    sizes.iter().sum::<f64>() + gap * (sizes.len().max(1) - 1) as f64
+}
+
+// shares `space` between deferred fill children by weight. children that hit their min or max are
+// frozen at it and the rest is shared again between the others, until nothing is clamped anymore
+fn resolve_fill_sizes(space: f64, fills: &[DeferFill]) -> Vec<f64> {
+    if space.is_nan() {
+        return vec![f64::NAN; fills.len()]
+    }
+    let mut sizes = vec![0.0; fills.len()];
+    let mut targets = vec![0.0; fills.len()];
+    let mut frozen = vec![false; fills.len()];
+    loop {
+        let mut left = space;
+        let mut weight = 0.0;
+        for (i, fill) in fills.iter().enumerate() {
+            if frozen[i] {
+                left -= sizes[i];
+            }
+            else {
+                weight += fill.weight.max(0.0);
+            }
+        }
+        let left = left.max(0.0);
+        let mut violation = 0.0;
+        for (i, fill) in fills.iter().enumerate() {
+            if !frozen[i] {
+                targets[i] = if weight > 0.0 {left * fill.weight.max(0.0) / weight} else {0.0};
+                sizes[i] = clamp_keep_nan(targets[i], fill.min, fill.max);
+                violation += sizes[i] - targets[i];
+            }
+        }
+        if violation.abs() < 0.000001 {
+            return sizes
+        }
+        for i in 0..fills.len() {
+            if !frozen[i] && (violation > 0.0 && sizes[i] > targets[i] || violation < 0.0 && sizes[i] < targets[i]) {
+                frozen[i] = true;
+            }
+        }
+    }
+}
+
+// the rect of a cell relative to the padded origin of its turtle
+fn grid_cell_rect(columns: &[f64], rows: &[f64], layout: &Layout, placement: &GridPlacement) -> Rect {
+    let span = | sizes: &[f64], start: usize, len: usize, gap: f64 | {
+        let start = start.min(sizes.len());
+        let end = (start + len).min(sizes.len());
+        let pos = grid_tracks_extent(&sizes[0..start], gap) + if start > 0 {gap} else {0.0};
+        let size = grid_tracks_extent(&sizes[start..end], gap);
+        (pos, size)
+    };
+    let (x, w) = span(columns, placement.column, placement.column_span, layout.spacing);
+    let (y, h) = span(rows, placement.row, placement.row_span, layout.line_spacing);
+    Rect {pos: dvec2(x, y), size: dvec2(w, h)}
 }
 
 pub struct TurtleAlignRange{
@@ -832,6 +1243,9 @@ impl Turtle {
                 Flow::RightWrap=>{
                     dvec2(self.layout.spacing, 0.0)
                 }
+                Flow::Grid{..} => {
+                    dvec2(0.0, 0.0)
+                }
             }
         }
         else {
@@ -839,6 +1253,97 @@ impl Turtle {
         }
     }
         
+    fn grid_column_count(&self) -> usize {
+        if let Flow::Grid{columns, ..} = &self.layout.flow {columns.len().max(1)} else {1}
+    }
+    
+    fn grid_is_free(&self, column: usize, row: usize, column_span: usize, row_span: usize) -> bool {
+        let columns = self.grid_column_count();
+        for r in row..row + row_span {
+            for c in column..(column + column_span).min(columns) {
+                if self.grid_occupied.get(r * columns + c).copied().unwrap_or(false) {
+                    return false
+                }
+            }
+        }
+        true
+    }
+    
+    // finds the cell a child would take without claiming it
+    fn grid_find(&self, cell: &GridCell) -> GridPlacement {
+        let columns = self.grid_column_count();
+        let column_span = cell.column_span.max(1);
+        let row_span = cell.row_span.max(1);
+        let (column, row) = match (cell.column, cell.row) {
+            (Some(column), Some(row)) => (column, row),
+            (Some(column), None) => {
+                let mut row = self.grid_cursor / columns;
+                while !self.grid_is_free(column, row, column_span, row_span) {
+                    row += 1;
+                }
+                (column, row)
+            }
+            (None, Some(row)) => {
+                let last = columns.saturating_sub(column_span);
+                ((0..=last).find( | c | self.grid_is_free(*c, row, column_span, row_span)).unwrap_or(0), row)
+            }
+            (None, None) => {
+                let mut index = self.grid_cursor;
+                loop {
+                    let (column, row) = (index % columns, index / columns);
+                    if (column == 0 || column + column_span <= columns) && self.grid_is_free(column, row, column_span, row_span) {
+                        break (column, row)
+                    }
+                    index += 1;
+                }
+            }
+        };
+        GridPlacement {
+            column,
+            row,
+            column_span,
+            row_span,
+            align: cell.align.unwrap_or(self.layout.align)
+        }
+    }
+    
+    fn grid_place(&mut self, cell: &GridCell) -> GridPlacement {
+        let placement = self.grid_find(cell);
+        let columns = self.grid_column_count();
+        for r in placement.row..placement.row + placement.row_span {
+            for c in placement.column..(placement.column + placement.column_span).min(columns) {
+                let index = r * columns + c;
+                if index >= self.grid_occupied.len() {
+                    self.grid_occupied.resize(index + 1, false);
+                }
+                self.grid_occupied[index] = true;
+            }
+        }
+        if cell.column.is_none() && cell.row.is_none() {
+            self.grid_cursor = placement.row * columns + placement.column + placement.column_span;
+        }
+        placement
+    }
+    
+    pub fn is_rtl(&self) -> bool {
+        self.rtl
+    }
+    
+    // resolved fill children are walked at an absolute position, so in a right to left turtle
+    // they are mirrored here rather than when the turtle ends
+    fn mirror_resolved(&self, walk: Walk) -> Walk {
+        let width = self.eval_walk(&walk).x;
+        if !self.rtl || self.width.is_nan() || width.is_nan() {
+            return walk
+        }
+        let pos = walk.abs_pos.unwrap();
+        Walk {
+            abs_pos: Some(dvec2(2.0 * self.origin.x + self.width - pos.x - width - walk.margin.width(), pos.y)),
+            margin: Margin {left: walk.margin.right, right: walk.margin.left, ..walk.margin},
+            ..walk
+        }
+    }
+    
     pub fn rect_is_visible(&self,  geom: Rect) -> bool {
         let view = Rect {pos: self.origin + self.layout.scroll, size: dvec2(self.width, self.height)};
         return view.intersects(geom)
@@ -871,6 +1376,14 @@ impl Turtle {
         self.layout.scroll
     }
     
+    /// The size a walk gets in this turtle, clamped to the walk's min and max.
+    pub fn eval_walk(&self, walk: &Walk) -> DVec2 {
+        dvec2(
+            walk.clamp_width(self.eval_width(walk.width, walk.margin, self.layout.flow)),
+            walk.clamp_height(self.eval_height(walk.height, walk.margin, self.layout.flow))
+        )
+    }
+    
     pub fn eval_width(&self, width: Size, margin: Margin, flow: Flow) -> f64 {
         return match width {
             Size::Fit => std::f64::NAN,
@@ -883,7 +1396,7 @@ impl Turtle {
                     Flow::Right => {
                         max_zero_keep_nan(self.width_left() - margin.width())
                     },
-                    Flow::Down | Flow::Overlay => {
+                    Flow::Down | Flow::Overlay | Flow::Grid{..} => {
                         let r = max_zero_keep_nan(self.width - self.layout.padding.width() - margin.width());
                         if r.is_nan() {
                             return self.width_used - margin.width() - self.layout.padding.right
@@ -902,7 +1415,7 @@ impl Turtle {
             Size::Fixed(v) => max_zero_keep_nan(v),
             Size::Fill => {
                 match flow {
-                    Flow::RightWrap | Flow::Right | Flow::Overlay => {
+                    Flow::RightWrap | Flow::Right | Flow::Overlay | Flow::Grid{..} => {
                         let r = max_zero_keep_nan(self.height - self.layout.padding.height() - margin.height());
                         if r.is_nan() {
                             return self.height_used - margin.height() - self.layout.padding.bottom
@@ -990,33 +1503,31 @@ impl DeferWalk {
     pub fn resolve(&mut self, cx: &Cx2d) -> Walk {
         match self{
             Self::Resolved(walk)=>{*walk},
-            Self::Unresolved{pos, defer_index, margin, other_axis}=>{
+            Self::Unresolved{pos, defer_index, walk}=>{
                 let turtle = cx.turtles.last().unwrap();
                 let walk = match turtle.layout.flow {
                     Flow::Right => {
-                        let left = turtle.width_left();
-                        let part = left / turtle.defer_count as f64;
-                        Walk {
-                            abs_pos: Some(*pos + dvec2(part * *defer_index as f64, 0.)),
-                            margin: *margin,
-                            width: Size::Fixed(part),
-                            height: *other_axis
-                        }
+                        let fills = resolve_fill_sizes(turtle.width_left(), &turtle.defer_fills);
+                        let offset = fills[0..*defer_index].iter().sum::<f64>();
+                        turtle.mirror_resolved(Walk {
+                            abs_pos: Some(*pos + dvec2(offset, 0.)),
+                            width: Size::Fixed(fills[*defer_index]),
+                            ..*walk
+                        })
                     },
                     Flow::RightWrap => {
                         panic!()
                     }
                     Flow::Down => { 
-                        let left = turtle.height_left();
-                        let part = left / turtle.defer_count as f64;
-                        Walk {
-                            abs_pos: Some(*pos + dvec2(0., part * *defer_index as f64)),
-                            margin: *margin,
-                            height: Size::Fixed(part),
-                            width: *other_axis
-                        }
+                        let fills = resolve_fill_sizes(turtle.height_left(), &turtle.defer_fills);
+                        let offset = fills[0..*defer_index].iter().sum::<f64>();
+                        turtle.mirror_resolved(Walk {
+                            abs_pos: Some(*pos + dvec2(0., offset)),
+                            height: Size::Fixed(fills[*defer_index]),
+                            ..*walk
+                        })
                     }
-                    Flow::Overlay => panic!()
+                    Flow::Overlay | Flow::Grid{..} => panic!()
                 };
                 *self = DeferWalk::Resolved(walk);
                 walk
@@ -1047,12 +1558,24 @@ impl Layout {
             ..Self::default()
         }
     }
+    
+    pub fn flow_grid(columns: &[GridTrack], rows: &[GridTrack]) -> Self {
+        Self {
+            flow: Flow::Grid{columns: GridTracks::new(columns), rows: GridTracks::new(rows)},
+            ..Self::default()
+        }
+    }
 
     pub fn with_scroll(mut self, v: DVec2) -> Self {
         self.scroll = v;
         self
     }
     
+    pub fn with_direction(mut self, v: LayoutDirection) -> Self {
+        self.direction = v;
+        self
+    }
+    
     pub fn with_align_x(mut self, v: f64) -> Self {
         self.align.x = v;
         self
@@ -1107,6 +1630,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(0.0),
             height: Size::Fixed(0.0),
+            ..Self::default()
         }
     }
     
@@ -1116,6 +1640,7 @@ impl Walk {
             margin: Margin::default(),
             width: w,
             height: h,
+            ..Self::default()
         }
     }
 
@@ -1125,6 +1650,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(rect.size.x),
             height: Size::Fixed(rect.size.y),
+            ..Self::default()
         }
     }
     
@@ -1134,6 +1660,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(w),
             height: Size::Fixed(h),
+            ..Self::default()
         }
     }
         
@@ -1143,6 +1670,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fixed(size.x),
             height: Size::Fixed(size.y),
+            ..Self::default()
         }
     }
     
@@ -1152,6 +1680,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fit,
             height: Size::Fit,
+            ..Self::default()
         }
     }
     
@@ -1161,6 +1690,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fill,
             height: Size::Fill,
+            ..Self::default()
         }
     }
     
@@ -1170,6 +1700,7 @@ impl Walk {
             margin: Margin::default(),
             width: Size::Fill,
             height: Size::Fit,
+            ..Self::default()
         }
     }
     
@@ -1207,6 +1738,36 @@ impl Walk {
         self
     }
     
+    pub fn with_cell(mut self, v: GridCell) -> Self {
+        self.cell = v;
+        self
+    }
+    
+    pub fn with_min_size(mut self, width: Option<f64>, height: Option<f64>) -> Self {
+        self.min_width = width;
+        self.min_height = height;
+        self
+    }
+    
+    pub fn with_max_size(mut self, width: Option<f64>, height: Option<f64>) -> Self {
+        self.max_width = width;
+        self.max_height = height;
+        self
+    }
+    
+    pub fn with_fill_weight(mut self, v: f64) -> Self {
+        self.fill_weight = v;
+        self
+    }
+    
+    pub fn clamp_width(&self, v: f64) -> f64 {
+        clamp_keep_nan(v, self.min_width, self.max_width)
+    }
+    
+    pub fn clamp_height(&self, v: f64) -> f64 {
+        clamp_keep_nan(v, self.min_height, self.max_height)
+    }
+    
     pub fn with_add_padding(mut self, v: Padding) -> Self {
         self.margin.top += v.top;
         self.margin.left += v.left;
@@ -1216,6 +1777,13 @@ impl Walk {
     }
 }
 
+impl Align {
+    /// The same alignment seen from the other side, used to place content in right to left turtles
+    pub fn mirrored_x(self) -> Self {
+        Self {x: 1.0 - self.x, y: self.y}
+    }
+}
+
 impl Padding {
     pub fn left_top(&self) -> DVec2 {
         dvec2(self.left, self.top)
@@ -1250,6 +1818,136 @@ impl Default for Flow {
     fn default() -> Self {Self::Down}
 }
 
+impl Default for LayoutDirection {
+    fn default() -> Self {Self::Inherit}
+}
+
+impl LiveHook for GridTrack {
+    fn skip_apply(&mut self, _cx: &mut Cx, _apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> Option<usize> {
+        match &nodes[index].value {
+            LiveValue::Float32(v) => {
+                *self = Self::Fixed(*v as f64);
+                Some(index + 1)
+            }
+            LiveValue::Float64(v) => {
+                *self = Self::Fixed(*v);
+                Some(index + 1)
+            }
+            LiveValue::Int64(v) => {
+                *self = Self::Fixed(*v as f64);
+                Some(index + 1)
+            }
+            _ => None
+        }
+    }
+}
+
+impl Default for GridTracks {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            tracks: [GridTrack::Fr(1.0); GRID_MAX_TRACKS]
+        }
+    }
+}
+
+impl GridTracks {
+    pub fn new(tracks: &[GridTrack]) -> Self {
+        let mut ret = Self::default();
+        for track in tracks {
+            ret.push(*track);
+        }
+        ret
+    }
+    
+    pub fn push(&mut self, track: GridTrack) {
+        if self.len == GRID_MAX_TRACKS {
+            error!("Grid supports at most {} tracks per axis", GRID_MAX_TRACKS);
+            return
+        }
+        self.tracks[self.len] = track;
+        self.len += 1;
+    }
+    
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    pub fn get(&self, index: usize) -> Option<GridTrack> {
+        self.tracks[0..self.len].get(index).copied()
+    }
+}
+
+impl LiveHook for GridTracks {}
+impl LiveApply for GridTracks {
+    fn apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        match &nodes[index].value {
+            LiveValue::Array => {
+                *self = Self::default();
+                let mut index = index + 1;
+                while !nodes[index].is_close() {
+                    let mut track = GridTrack::Fr(1.0);
+                    index = track.apply(cx, apply, index, nodes);
+                    self.push(track);
+                }
+                index + 1
+            }
+            LiveValue::Int64(v) => {
+                *self = Self::new(&vec![GridTrack::Fr(1.0); (*v).max(0) as usize]);
+                index + 1
+            }
+            _ => {
+                cx.apply_error_expected_array(live_error_origin!(), index, nodes);
+                nodes.skip_node(index)
+            }
+        }
+    }
+}
+
+impl Default for GridCell {
+    fn default() -> Self {
+        Self {
+            column: None,
+            row: None,
+            column_span: 1,
+            row_span: 1,
+            align: None
+        }
+    }
+}
+
+impl GridCell {
+    pub fn at(column: usize, row: usize) -> Self {
+        Self {
+            column: Some(column),
+            row: Some(row),
+            ..Self::default()
+        }
+    }
+    
+    pub fn with_span(mut self, column_span: usize, row_span: usize) -> Self {
+        self.column_span = column_span;
+        self.row_span = row_span;
+        self
+    }
+}
+
+impl GridPlacement {
+    fn to_cell(self) -> GridCell {
+        GridCell {
+            column: Some(self.column),
+            row: Some(self.row),
+            column_span: self.column_span,
+            row_span: self.row_span,
+            align: Some(self.align)
+        }
+    }
+}
+
 
 impl LiveHook for Size {
     fn skip_apply(&mut self, cx: &mut Cx, _apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> Option<usize> {
@@ -1362,6 +2060,15 @@ impl Size {
     }
 }
 
+// min wins over max when they cross, nan stays nan so fit sizes are clamped once known
+fn clamp_keep_nan(v: f64, min: Option<f64>, max: Option<f64>) -> f64 {
+    if v.is_nan() {
+        return v
+    }
+    let v = if let Some(max) = max {v.min(max)} else {v};
+    if let Some(min) = min {v.max(min)} else {v}
+}
+
 fn max_zero_keep_nan(v: f64) -> f64 {
     if v.is_nan() {
         v
@@ -1370,3 +2077,295 @@ fn max_zero_keep_nan(v: f64) -> f64 {
         f64::max(v, 0.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn fill(weight: f64, min: Option<f64>, max: Option<f64>) -> DeferFill {
+        DeferFill {weight, min, max}
+    }
+    
+    fn with_turtle(f: impl FnOnce(&mut Cx2d)) {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let draw_event = DrawEvent::default();
+        let mut cx = Cx2d::new(&mut cx, &draw_event);
+        f(&mut cx);
+    }
+    
+    #[test]
+    fn clamp_keeps_nan_and_prefers_min() {
+        assert_eq!(clamp_keep_nan(50.0, Some(10.0), Some(40.0)), 40.0);
+        assert_eq!(clamp_keep_nan(5.0, Some(10.0), Some(40.0)), 10.0);
+        assert_eq!(clamp_keep_nan(20.0, None, None), 20.0);
+        assert_eq!(clamp_keep_nan(20.0, Some(30.0), Some(10.0)), 30.0);
+        assert!(clamp_keep_nan(f64::NAN, Some(10.0), Some(40.0)).is_nan());
+    }
+    
+    #[test]
+    fn fills_share_by_weight() {
+        let sizes = resolve_fill_sizes(300.0, &[fill(1.0, None, None), fill(2.0, None, None)]);
+        assert_eq!(sizes, vec![100.0, 200.0]);
+        let sizes = resolve_fill_sizes(300.0, &[fill(1.0, None, None), fill(0.0, None, None), fill(1.0, None, None)]);
+        assert_eq!(sizes, vec![150.0, 0.0, 150.0]);
+    }
+    
+    #[test]
+    fn fills_redistribute_around_limits() {
+        // the first fill is capped at 50, the other two share the remaining 250 by weight
+        let sizes = resolve_fill_sizes(300.0, &[fill(1.0, None, Some(50.0)), fill(1.0, None, None), fill(4.0, None, None)]);
+        assert_eq!(sizes, vec![50.0, 50.0, 200.0]);
+        // a min larger than its share takes space from the others
+        let sizes = resolve_fill_sizes(100.0, &[fill(1.0, Some(80.0), None), fill(1.0, None, None)]);
+        assert_eq!(sizes, vec![80.0, 20.0]);
+        // when every fill is capped the space is left over
+        let sizes = resolve_fill_sizes(300.0, &[fill(1.0, None, Some(50.0)), fill(1.0, None, Some(70.0))]);
+        assert_eq!(sizes, vec![50.0, 70.0]);
+        // mins that do not fit overflow rather than shrink
+        let sizes = resolve_fill_sizes(100.0, &[fill(1.0, Some(80.0), None), fill(1.0, Some(60.0), None)]);
+        assert_eq!(sizes, vec![80.0, 60.0]);
+    }
+    
+    #[test]
+    fn fills_in_a_fit_turtle_stay_nan() {
+        let sizes = resolve_fill_sizes(f64::NAN, &[fill(1.0, Some(10.0), None)]);
+        assert!(sizes[0].is_nan());
+    }
+    
+    #[test]
+    fn deferred_fills_resolve_weighted_within_limits() {
+        with_turtle( | cx | {
+            cx.begin_turtle(Walk::fixed(400.0, 100.0), Layout::flow_right().with_padding_all(10.0));
+            let mut a = cx.defer_walk(Walk::fill().with_fill_weight(2.0)).unwrap();
+            cx.walk_turtle(Walk::fixed(80.0, 20.0));
+            let mut b = cx.defer_walk(Walk::fill().with_max_size(Some(60.0), Some(50.0))).unwrap();
+            let mut c = cx.defer_walk(Walk::fill()).unwrap();
+            let a = cx.walk_turtle(a.resolve(cx));
+            let b = cx.walk_turtle(b.resolve(cx));
+            let c = cx.walk_turtle(c.resolve(cx));
+            // 300 left over the fixed child, b is capped at 60 and a and c share 240 as 2:1
+            assert_eq!(a, Rect {pos: dvec2(10.0, 10.0), size: dvec2(160.0, 80.0)});
+            assert_eq!(b, Rect {pos: dvec2(250.0, 10.0), size: dvec2(60.0, 50.0)});
+            assert_eq!(c, Rect {pos: dvec2(310.0, 10.0), size: dvec2(80.0, 80.0)});
+            cx.end_turtle();
+        });
+    }
+    
+    #[test]
+    fn deferred_fills_down_with_min() {
+        with_turtle( | cx | {
+            cx.begin_turtle(Walk::fixed(100.0, 200.0), Layout::flow_down());
+            let mut a = cx.defer_walk(Walk::fill().with_min_size(None, Some(150.0))).unwrap();
+            let mut b = cx.defer_walk(Walk::fill()).unwrap();
+            let a = cx.walk_turtle(a.resolve(cx));
+            let b = cx.walk_turtle(b.resolve(cx));
+            assert_eq!(a.size, dvec2(100.0, 150.0));
+            assert_eq!(b, Rect {pos: dvec2(0.0, 150.0), size: dvec2(100.0, 50.0)});
+            cx.end_turtle();
+        });
+    }
+    
+    #[test]
+    fn fit_and_fixed_sizes_clamp() {
+        with_turtle( | cx | {
+            cx.begin_turtle(Walk::fixed(400.0, 400.0), Layout::flow_down());
+            // a fit turtle grows to its min
+            cx.begin_turtle(Walk::fit().with_min_size(Some(100.0), Some(40.0)), Layout::default());
+            cx.walk_turtle(Walk::fixed(30.0, 10.0));
+            let rect = cx.end_turtle();
+            assert_eq!(rect.size, dvec2(100.0, 40.0));
+            // and is capped at its max
+            cx.begin_turtle(Walk::fit().with_max_size(Some(50.0), None), Layout::default());
+            cx.walk_turtle(Walk::fixed(80.0, 10.0));
+            let rect = cx.end_turtle();
+            assert_eq!(rect, Rect {pos: dvec2(0.0, 40.0), size: dvec2(50.0, 10.0)});
+            // fixed and fill sizes are clamped when walked
+            let rect = cx.walk_turtle(Walk::fixed(300.0, 5.0).with_max_size(Some(120.0), None).with_min_size(None, Some(20.0)));
+            assert_eq!(rect.size, dvec2(120.0, 20.0));
+            let rect = cx.walk_turtle(Walk::fill().with_max_size(Some(250.0), Some(30.0)));
+            assert_eq!(rect.size, dvec2(250.0, 30.0));
+            cx.end_turtle();
+        });
+    }
+    
+    // lays out fixed children in a 300 wide turtle and returns their content rects after it ended
+    fn laid_out_children(layout: Layout, children: &[Walk]) -> Vec<(DVec2, DVec2)> {
+        let mut rects = Vec::new();
+        with_turtle( | cx | {
+            cx.begin_turtle(Walk::fixed(300.0, 200.0), layout);
+            let align_start = cx.align_list.len();
+            for walk in children {
+                cx.begin_turtle(*walk, Layout::default());
+                cx.end_turtle();
+            }
+            cx.end_turtle();
+            for entry in &cx.align_list[align_start..] {
+                if let AlignEntry::BeginTurtle(p0, p1) = entry {
+                    rects.push((*p0, *p1));
+                }
+            }
+        });
+        rects
+    }
+    
+    #[test]
+    fn right_to_left_mirrors_children() {
+        let children = [
+            Walk::fixed(50.0, 20.0).with_margin_left(4.0),
+            Walk::fixed(30.0, 40.0).with_margin_right(7.0),
+            Walk::fixed(20.0, 10.0),
+        ];
+        let layouts = [
+            Layout::flow_right().with_padding(Padding {left: 10.0, top: 3.0, right: 20.0, bottom: 0.0}),
+            Layout {spacing: 5.0, ..Layout::flow_right()},
+            Layout {spacing: 5.0, align: Align {x: 0.5, y: 1.0}, ..Layout::flow_right()},
+            Layout {spacing: 5.0, align: Align {x: 1.0, y: 0.0}, ..Layout::flow_down()}.with_padding_all(6.0),
+            Layout {align: Align {x: 0.25, y: 0.5}, ..Layout::flow_overlay()},
+        ];
+        for layout in layouts {
+            let ltr = laid_out_children(layout.with_direction(LayoutDirection::LeftToRight), &children);
+            let rtl = laid_out_children(layout.with_direction(LayoutDirection::RightToLeft), &children);
+            assert_eq!(ltr.len(), children.len());
+            for ((l0, l1), (r0, r1)) in ltr.iter().zip(&rtl) {
+                assert_eq!((r0.x, r1.x), (300.0 - l1.x, 300.0 - l0.x), "{:?}", layout.flow);
+                assert_eq!((r0.y, r1.y), (l0.y, l1.y), "{:?}", layout.flow);
+            }
+        }
+    }
+    
+    #[test]
+    fn spacing_in_left_to_right_turtles_is_unchanged() {
+        let layout = Layout {spacing: 5.0, ..Layout::flow_right()}.with_padding_all(10.0);
+        let rects = laid_out_children(layout, &[Walk::fixed(50.0, 20.0).with_margin_left(4.0), Walk::fixed(30.0, 20.0)]);
+        assert_eq!(rects[0], (dvec2(14.0, 10.0), dvec2(64.0, 30.0)));
+        assert_eq!(rects[1], (dvec2(69.0, 10.0), dvec2(99.0, 30.0)));
+        // the mirrored turtle keeps the same gaps from the right edge
+        let rects = laid_out_children(layout.with_direction(LayoutDirection::RightToLeft), &[Walk::fixed(50.0, 20.0).with_margin_left(4.0), Walk::fixed(30.0, 20.0)]);
+        assert_eq!(rects[0], (dvec2(236.0, 10.0), dvec2(286.0, 30.0)));
+        assert_eq!(rects[1], (dvec2(201.0, 10.0), dvec2(231.0, 30.0)));
+    }
+    
+    fn tracks(tracks: &[GridTrack]) -> GridTracks {
+        GridTracks::new(tracks)
+    }
+    
+    fn placement(column: usize, row: usize, column_span: usize, row_span: usize) -> GridPlacement {
+        GridPlacement {column, row, column_span, row_span, align: Align::default()}
+    }
+    
+    fn at(p: GridPlacement) -> (usize, usize, usize, usize) {
+        (p.column, p.row, p.column_span, p.row_span)
+    }
+    
+    #[test]
+    fn grid_tracks_fixed_fit_and_fr() {
+        // the fixed track ignores its item, fit takes the largest and the fr tracks share what is left 1:3
+        let items = [(1, 1, 40.0), (1, 1, 60.0), (0, 1, 300.0)];
+        let sizes = grid_track_sizes(&tracks(&[GridTrack::Fixed(100.0), GridTrack::Fit, GridTrack::Fr(1.0), GridTrack::Fr(3.0)]), 4, 500.0, 10.0, items.into_iter());
+        assert_eq!(sizes, vec![100.0, 60.0, 77.5, 232.5]);
+        // fr tracks with no space left collapse to zero
+        let sizes = grid_track_sizes(&tracks(&[GridTrack::Fixed(100.0), GridTrack::Fr(1.0)]), 2, 80.0, 10.0, [].into_iter());
+        assert_eq!(sizes, vec![100.0, 0.0]);
+        // tracks past the defined ones are fit
+        let sizes = grid_track_sizes(&tracks(&[GridTrack::Fixed(10.0)]), 3, 500.0, 0.0, [(2, 1, 7.0)].into_iter());
+        assert_eq!(sizes, vec![10.0, 0.0, 7.0]);
+    }
+    
+    #[test]
+    fn grid_tracks_in_a_fit_turtle() {
+        // without available space fr tracks fit their content
+        let sizes = grid_track_sizes(&tracks(&[GridTrack::Fr(1.0), GridTrack::Fr(2.0)]), 2, f64::NAN, 10.0, [(0, 1, 30.0), (1, 1, 50.0)].into_iter());
+        assert_eq!(sizes, vec![30.0, 50.0]);
+    }
+    
+    #[test]
+    fn grid_spans_grow_their_last_fit_track() {
+        // 200 over 50 + 20 + 0 and two gaps leaves 110 for the last fit track
+        let t = tracks(&[GridTrack::Fixed(50.0), GridTrack::Fit, GridTrack::Fit]);
+        let sizes = grid_track_sizes(&t, 3, f64::NAN, 10.0, [(0, 3, 200.0), (1, 1, 20.0)].into_iter());
+        assert_eq!(sizes, vec![50.0, 20.0, 110.0]);
+        // a span that already fits grows nothing
+        let sizes = grid_track_sizes(&t, 3, f64::NAN, 10.0, [(0, 2, 60.0), (1, 1, 20.0)].into_iter());
+        assert_eq!(sizes, vec![50.0, 20.0, 0.0]);
+        // and one over fixed tracks only stays at their sizes
+        let t = tracks(&[GridTrack::Fixed(50.0), GridTrack::Fixed(50.0)]);
+        let sizes = grid_track_sizes(&t, 2, f64::NAN, 10.0, [(0, 2, 500.0)].into_iter());
+        assert_eq!(sizes, vec![50.0, 50.0]);
+    }
+    
+    #[test]
+    fn grid_cell_rects_include_gaps() {
+        let columns = [100.0, 60.0, 77.5];
+        let rows = [20.0, 30.0];
+        let layout = Layout {spacing: 10.0, line_spacing: 5.0, ..Layout::default()};
+        let rect = grid_cell_rect(&columns, &rows, &layout, &placement(0, 0, 1, 1));
+        assert_eq!(rect, Rect {pos: dvec2(0.0, 0.0), size: dvec2(100.0, 20.0)});
+        let rect = grid_cell_rect(&columns, &rows, &layout, &placement(1, 1, 2, 1));
+        assert_eq!(rect, Rect {pos: dvec2(110.0, 25.0), size: dvec2(147.5, 30.0)});
+        // spans past the last track are cut off
+        let rect = grid_cell_rect(&columns, &rows, &layout, &placement(2, 0, 3, 2));
+        assert_eq!(rect, Rect {pos: dvec2(180.0, 0.0), size: dvec2(77.5, 55.0)});
+    }
+    
+    #[test]
+    fn grid_find_and_place_skip_taken_cells() {
+        with_turtle( | cx | {
+            cx.begin_turtle(Walk::fixed(300.0, 300.0), Layout::flow_grid(&[GridTrack::Fit; 3], &[]));
+            let auto = GridCell::default();
+            // finding does not claim the cell
+            assert_eq!(at(cx.turtle().grid_find(&auto)), (0, 0, 1, 1));
+            assert_eq!(at(cx.turtle().grid_find(&auto)), (0, 0, 1, 1));
+            
+            assert_eq!(at(cx.turtle_mut().grid_place(&GridCell::at(1, 1).with_span(2, 1))), (1, 1, 2, 1));
+            assert_eq!(at(cx.turtle_mut().grid_place(&auto)), (0, 0, 1, 1));
+            assert_eq!(at(cx.turtle_mut().grid_place(&auto)), (1, 0, 1, 1));
+            // a span that does not fit the rest of the row goes to the next free row
+            assert_eq!(at(cx.turtle_mut().grid_place(&auto.with_span(2, 1))), (0, 2, 2, 1));
+            assert_eq!(at(cx.turtle_mut().grid_place(&auto)), (2, 2, 1, 1));
+            // a column alone is searched from the row auto placement got to, a row alone from its first column
+            let column = GridCell {column: Some(1), ..auto};
+            assert_eq!(at(cx.turtle().grid_find(&column)), (1, 3, 1, 1));
+            let row = GridCell {row: Some(1), ..auto};
+            assert_eq!(at(cx.turtle().grid_find(&row)), (0, 1, 1, 1));
+            let row = GridCell {row: Some(0), ..auto};
+            assert_eq!(at(cx.turtle().grid_find(&row)), (2, 0, 1, 1));
+            assert_eq!(at(cx.turtle().grid_find(&auto)), (0, 3, 1, 1));
+            cx.end_turtle();
+        });
+    }
+    
+    #[test]
+    fn grid_children_move_into_their_cells() {
+        let mut rects = Vec::new();
+        with_turtle( | cx | {
+            let layout = Layout {spacing: 10.0, line_spacing: 5.0, ..Layout::flow_grid(&[GridTrack::Fixed(100.0), GridTrack::Fr(1.0), GridTrack::Fit], &[])};
+            cx.begin_turtle(Walk::fixed(320.0, 200.0), layout.with_padding_all(10.0));
+            let align_start = cx.align_list.len();
+            let centered = GridCell {align: Some(Align {x: 1.0, y: 0.5}), ..GridCell::default()};
+            for walk in [
+                Walk::fixed(50.0, 40.0),
+                Walk::fixed(30.0, 20.0).with_cell(centered),
+                Walk::fixed(70.0, 10.0),
+                Walk::fixed(60.0, 30.0).with_cell(GridCell::default().with_span(2, 1)),
+            ] {
+                cx.begin_turtle(walk, Layout::default());
+                cx.end_turtle();
+            }
+            cx.end_turtle();
+            for entry in &cx.align_list[align_start..] {
+                if let AlignEntry::BeginTurtle(p0, p1) = entry {
+                    rects.push((*p0, *p1));
+                }
+            }
+        });
+        // the fit column holds 70, so the fr column gets 300 - 100 - 70 - 2 * 10
+        assert_eq!(rects, vec![
+            (dvec2(10.0, 10.0), dvec2(60.0, 50.0)),
+            (dvec2(200.0, 20.0), dvec2(230.0, 40.0)),
+            (dvec2(240.0, 10.0), dvec2(310.0, 20.0)),
+            (dvec2(10.0, 55.0), dvec2(70.0, 85.0)),
+        ]);
+    }
+}
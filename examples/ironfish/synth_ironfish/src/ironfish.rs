@@ -325,6 +325,79 @@ pub struct IronFishSettings {
 //  shadow: ShadowSettings,
 }
 
+const FILTER1_CUTOFF: LiveId = LiveId::from_str("filter1.cutoff");
+const FILTER1_RESONANCE: LiveId = LiveId::from_str("filter1.resonance");
+const FILTER1_ENVELOPE_AMOUNT: LiveId = LiveId::from_str("filter1.envelope_amount");
+const FILTER1_LFO_AMOUNT: LiveId = LiveId::from_str("filter1.lfo_amount");
+const OSC1_DETUNE: LiveId = LiveId::from_str("osc1.detune");
+const OSC1_HARMONIC: LiveId = LiveId::from_str("osc1.harmonic");
+const OSC2_DETUNE: LiveId = LiveId::from_str("osc2.detune");
+const OSC2_HARMONIC: LiveId = LiveId::from_str("osc2.harmonic");
+const LFO_RATE: LiveId = LiveId::from_str("lfo.rate");
+const OSC_BALANCE: LiveId = LiveId::from_str("osc_balance");
+const SUB_OSC: LiveId = LiveId::from_str("sub_osc");
+const NOISE: LiveId = LiveId::from_str("noise");
+const PORTAMENTO: LiveId = LiveId::from_str("portamento");
+const DELAY_SEND: LiveId = LiveId::from_str("delay.delaysend");
+const DELAY_FEEDBACK: LiveId = LiveId::from_str("delay.delayfeedback");
+const CHORUS_MIX: LiveId = LiveId::from_str("chorus.mix");
+const REVERB_MIX: LiveId = LiveId::from_str("reverb.mix");
+const BITCRUSH_AMOUNT: LiveId = LiveId::from_str("bitcrush.amount");
+
+impl IronFishSettings {
+    /// The parameters automation can reach, by their path in the settings like `filter1.cutoff`
+    pub fn automatable(&self) -> [(LiveId, &f32a); 18] {
+        [
+            (FILTER1_CUTOFF, &self.filter1.cutoff),
+            (FILTER1_RESONANCE, &self.filter1.resonance),
+            (FILTER1_ENVELOPE_AMOUNT, &self.filter1.envelope_amount),
+            (FILTER1_LFO_AMOUNT, &self.filter1.lfo_amount),
+            (OSC1_DETUNE, &self.osc1.detune),
+            (OSC1_HARMONIC, &self.osc1.harmonic),
+            (OSC2_DETUNE, &self.osc2.detune),
+            (OSC2_HARMONIC, &self.osc2.harmonic),
+            (LFO_RATE, &self.lfo.rate),
+            (OSC_BALANCE, &self.osc_balance),
+            (SUB_OSC, &self.sub_osc),
+            (NOISE, &self.noise),
+            (PORTAMENTO, &self.portamento),
+            (DELAY_SEND, &self.delay.delaysend),
+            (DELAY_FEEDBACK, &self.delay.delayfeedback),
+            (CHORUS_MIX, &self.chorus.mix),
+            (REVERB_MIX, &self.reverb.mix),
+            (BITCRUSH_AMOUNT, &self.bitcrush.amount),
+        ]
+    }
+}
+
+/// An automated setting, rendered per frame for the block being played. The settings
+/// the UI shares are never written, the DSP reads these values through `Automated`
+pub struct IronFishAutomation {
+    id: LiveId,
+    param: AutomatedParam,
+    values: Vec<f32>,
+    /// The UI value when the automation started, moving it takes the setting back
+    ui_value: f32,
+}
+
+/// The settings as the DSP sees them, running automation overrides the UI values
+#[derive(Clone, Copy)]
+pub struct Automated<'a>(&'a [IronFishAutomation]);
+
+impl Automated<'_> {
+    pub fn has(&self, id: LiveId) -> bool {
+        self.0.iter().any( | a | a.id == id)
+    }
+
+    /// The value of `setting` at `frame` of the block, past the block it is the latest automated value
+    pub fn get(&self, id: LiveId, setting: &f32a, frame: usize) -> f32 {
+        match self.0.iter().find( | a | a.id == id) {
+            Some(a) => a.values.get(frame).copied().unwrap_or(a.param.value()),
+            None => setting.get()
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct SequencerState {
     currentstep: usize,
@@ -776,6 +849,7 @@ impl OscillatorState {
         hyper: HyperSawGlobalState,
         env: f32,
         lfo: f32,
+        harmonic: f32,
     ) -> f32 {
         match settings.osc_type.get() {
             OscType::Pure => self.pure(),
@@ -785,7 +859,7 @@ impl OscillatorState {
             OscType::SuperSaw => self.supersaw(),
             OscType::HyperSaw => self.hypersaw.get(hyper),
             OscType::HarmonicSeries => self.harmonic(
-                (settings.harmonic.get()
+                (harmonic
                     + env * settings.harmonicenv.get()
                     + lfo * settings.harmoniclfo.get())
                 .clamp(0.0, 1.0),
@@ -801,12 +875,13 @@ impl OscillatorState {
         supersaw: &SupersawSettings,
         hypersaw: &HyperSawGlobalState,
         sps_detune_tab: &[f32; 1024],
+        detune: f32,
         _update: bool,
     ) {
         let freq = (440.0 / 6.28318530718)
             * f32::powf(
                 2.0,
-                ((note as f32) - 69.0 + settings.transpose.get() as f32 + settings.detune.get())
+                ((note as f32) - 69.0 + settings.transpose.get() as f32 + detune)
                     / 12.0,
             );
         self.delta_phase = (6.28318530718 * freq) / samplerate;
@@ -1032,9 +1107,11 @@ impl ChorusState {
         &mut self,
         buffer: &mut AudioBuffer,
         settings: &ChorusSettings,
+        automated: Automated,
         sample_rate: f32,
     ) {
-        if settings.mix.get() == 0.0 {
+        let mix_automated = automated.has(CHORUS_MIX);
+        if settings.mix.get() == 0.0 && !mix_automated {
             return;
         }
 
@@ -1062,9 +1139,12 @@ impl ChorusState {
                 mindelay + ((self.phase + (i as f32) * phasediff).sin() + 1.0) * moddepth;
         }
         let fb = self.feedbacksmooth.get(settings.feedback.get()) * 0.86;
-        let mix = self.mixsmooth.get(settings.mix.get());
-        let imix = 1.0 - mix;
+        let mut mix = self.mixsmooth.get(settings.mix.get());
         for i in 0..frame_count {
+            if mix_automated {
+                mix = automated.get(CHORUS_MIX, &settings.mix, i);
+            }
+            let imix = 1.0 - mix;
             let l1 = self.lines[0].feed(left[i], fb, self.linephase[0]);
             let l2 = self.lines[1].feed(right[i], fb, self.linephase[1]);
             let l3 = self.lines[2].feed(left[i], fb, self.linephase[2]);
@@ -1293,18 +1373,20 @@ impl FilterState {
     fn set_cutoff(
         &mut self,
         settings: &FilterSettings,
+        automated: Automated,
+        frame: usize,
         envelope: f32,
         _sample_rate: f32,
         touch: f32,
         lfo: f32,
     ) {
-        self.fc = (settings.cutoff.get()
+        self.fc = (automated.get(FILTER1_CUTOFF, &settings.cutoff, frame)
             + touch * settings.touch_amount.get()
-            + lfo * settings.lfo_amount.get() * 0.35
-            + envelope * settings.envelope_amount.get() * 0.5)
+            + lfo * automated.get(FILTER1_LFO_AMOUNT, &settings.lfo_amount, frame) * 0.35
+            + envelope * automated.get(FILTER1_ENVELOPE_AMOUNT, &settings.envelope_amount, frame) * 0.5)
             .clamp(0.0, 1.0);
         self.fc *= self.fc * 0.5;
-        self.damp = 1.0 - settings.resonance.get();
+        self.damp = 1.0 - automated.get(FILTER1_RESONANCE, &settings.resonance, frame);
         let preclamp = 2.0 * ((3.1415 * self.fc).sin());
         self.phi = (preclamp).clamp(0.0, 1.0);
         self.gamma = (2.0 * self.damp).clamp(0.0, 1.0);
@@ -1354,6 +1436,9 @@ pub struct IronFishVoice {
     tonote: f32,
     notetime: f32,
     notetimetotal: f32,
+    // the midi channel that started this note, with MPE its pitch bend, pressure and timbre are per note
+    channel: u8,
+    expression: MpeExpression,
     //sequencer: SequencerState
 }
 
@@ -1394,30 +1479,35 @@ impl IronFishVoice {
     pub fn update_note(
         &mut self,
         settings: &IronFishSettings,
+        automated: Automated,
+        frame: usize,
         h: &IronFishGlobalVoiceState,
         sps_detune_tab: &[f32; 1024],
         update: bool,
     ) {
+        let notefreq = self.current_notefreq + self.expression.pitch_bend;
         self.osc1.set_note(
-            self.current_notefreq,
+            notefreq,
             settings.sample_rate.get(),
             &settings.osc1,
             &settings.supersaw1,
             &h.hypersaw1,
             sps_detune_tab,
+            automated.get(OSC1_DETUNE, &settings.osc1.detune, frame),
             update,
         );
         self.osc2.set_note(
-            self.current_notefreq,
+            notefreq,
             settings.sample_rate.get(),
             &settings.osc2,
             &settings.supersaw2,
             &h.hypersaw2,
             sps_detune_tab,
+            automated.get(OSC2_DETUNE, &settings.osc2.detune, frame),
             update,
         );
         self.subosc
-            .set_note(self.current_notefreq, settings.sample_rate.get());
+            .set_note(notefreq, settings.sample_rate.get());
     }
 
     pub fn note_on(
@@ -1426,16 +1516,19 @@ impl IronFishVoice {
         prev: u8,
         b2: u8,
         settings: &IronFishSettings,
+        automated: Automated,
+        frame: usize,
         h: &IronFishGlobalVoiceState,
         sps_detune_tab: &[f32; 1024],
     ) {
         let velocity = (b2 as f32) / 127.0;
 
-        if settings.portamento.get() > 0.0
+        let portamento = automated.get(PORTAMENTO, &settings.portamento, frame);
+        if portamento > 0.0
         //&& prev < 128
         {
             self.notetimetotal =
-                EnvelopeState::nicerange(settings.portamento.get(), settings.sample_rate.get());
+                EnvelopeState::nicerange(portamento, settings.sample_rate.get());
             self.notetime = self.notetimetotal;
             self.fromnote = prev as f32;
             self.tonote = b1 as f32;
@@ -1455,13 +1548,15 @@ impl IronFishVoice {
             .trigger_on(velocity, &settings.mod_envelope, settings.sample_rate.get());
         self.current_notefreq = self.fromnote;
         self.current_note = b1 as i16;
-        self.update_note(settings, h, sps_detune_tab, false);
+        self.update_note(settings, automated, frame, h, sps_detune_tab, false);
     }
 
     pub fn compute_one(
         &mut self,
         state: &IronFishGlobalVoiceState,
         settings: &IronFishSettings,
+        automated: Automated,
+        frame: usize,
         _touch: f32,
         lfo: f32,
         osc1_gain: f32,
@@ -1485,6 +1580,7 @@ impl IronFishVoice {
             state.hypersaw1,
             mod_envelope,
             lfo,
+            automated.get(OSC1_HARMONIC, &settings.osc1.harmonic, frame),
         );
         let osc2 = self.osc2.get(
             &settings.osc2,
@@ -1492,14 +1588,15 @@ impl IronFishVoice {
             state.hypersaw2,
             mod_envelope,
             lfo,
+            automated.get(OSC2_HARMONIC, &settings.osc2.harmonic, frame),
         );
         let noise = random_f32(&mut self.seed) * 2.0 - 1.0;
 
         // mix signal
         let oscinput = osc1 * osc1_gain
             + osc2 * osc2_gain
-            + settings.sub_osc.get() * sub
-            + noise * settings.noise.get();
+            + automated.get(SUB_OSC, &settings.sub_osc, frame) * sub
+            + noise * automated.get(NOISE, &settings.noise, frame);
 
         // apply filter
         let filter = self.filter1.get(oscinput, &settings.filter1);
@@ -1514,6 +1611,8 @@ impl IronFishVoice {
         &mut self,
         frame_count: usize,
         settings: &IronFishSettings,
+        automated: Automated,
+        frame: usize,
         h: &IronFishGlobalVoiceState,
         sps_detune_tab: &[f32; 1024],
     ) {
@@ -1525,7 +1624,7 @@ impl IronFishVoice {
             let d = self.notetime / self.notetimetotal;
             self.current_notefreq = self.tonote + (self.fromnote - self.tonote) * d;
             //log!("up - {}", self.current_notefreq);
-            self.update_note(settings, h, sps_detune_tab, true);
+            self.update_note(settings, automated, frame, h, sps_detune_tab, true);
         }
     }
 
@@ -1536,94 +1635,89 @@ impl IronFishVoice {
         frame_count: usize,
         display_buffer: Option<&mut AudioBuffer>,
         settings: &IronFishSettings,
+        automated: Automated,
         state: &IronFishGlobalVoiceState,
         touch: f32,
         lfo: f32,
         sps_detune_tab: &[f32; 1024],
     ) {
         let (left, right) = mix_buffer.stereo_mut();
+        let mut display = display_buffer.map( | display_buffer | display_buffer.stereo_mut());
+        let touch = (touch + self.expression.pressure + self.expression.timbre - 0.5).clamp(-1.0, 1.0);
 
         // FIXME: like many parameters this one is also not interpolated per sample; I have an idea for a proper interpolation object
         //        and have used it before but right now I'm just going to avoid those sqrt() calls per sample and pray nobody touches that slider mid-note
-        let balance = settings.osc_balance.get();
-        let osc1_gain = (1.0 - balance).sqrt();
-        let osc2_gain = balance.sqrt();
-
-        if let Some(display_buffer) = display_buffer {
-            let (left_disp, right_disp) = display_buffer.stereo_mut();
-            let mut remaining = frame_count;
-            let mut startidxmut = startidx;
-            while remaining > 0 {
-                let proc = remaining.min(8);
-                let mod_envelope = self.mod_envelope.get_n(
-                    &settings.mod_envelope,
-                    settings.sample_rate.get(),
-                    proc,
-                );
+        let balance_gains = | frame | {
+            let balance = automated.get(OSC_BALANCE, &settings.osc_balance, frame);
+            ((1.0 - balance).sqrt(), balance.sqrt())
+        };
+        let (mut osc1_gain, mut osc2_gain) = balance_gains(startidx);
 
-                // set up filter
-                self.filter1.set_cutoff(
-                    &settings.filter1,
-                    mod_envelope,
-                    settings.sample_rate.get(),
-                    touch,
-                    lfo,
-                );
-                self.updatenote(proc, settings, state, sps_detune_tab);
-                for i in startidxmut..proc + startidxmut {
-                    let output = self.compute_one(
-                        state,
-                        &settings,
+        // automated settings move per frame, the rest at the control rate of the voice
+        let balance_automated = automated.has(OSC_BALANCE);
+        let filter_automated = [FILTER1_CUTOFF, FILTER1_RESONANCE, FILTER1_ENVELOPE_AMOUNT, FILTER1_LFO_AMOUNT]
+            .into_iter().any( | id | automated.has(id));
+        let pitch_automated = automated.has(OSC1_DETUNE) || automated.has(OSC2_DETUNE);
+
+        let mut remaining = frame_count;
+        let mut startidxmut = startidx;
+        while remaining > 0 {
+            let proc = remaining.min(8);
+            let mod_envelope = self.mod_envelope.get_n(
+                &settings.mod_envelope,
+                settings.sample_rate.get(),
+                proc,
+            );
+
+            // set up filter
+            self.filter1.set_cutoff(
+                &settings.filter1,
+                automated,
+                startidxmut,
+                mod_envelope,
+                settings.sample_rate.get(),
+                touch,
+                lfo,
+            );
+            self.updatenote(proc, settings, automated, startidxmut, state, sps_detune_tab);
+            for i in startidxmut..proc + startidxmut {
+                if balance_automated {
+                    (osc1_gain, osc2_gain) = balance_gains(i);
+                }
+                if filter_automated && i != startidxmut {
+                    self.filter1.set_cutoff(
+                        &settings.filter1,
+                        automated,
+                        i,
+                        mod_envelope,
+                        settings.sample_rate.get(),
                         touch,
                         lfo,
-                        osc1_gain,
-                        osc2_gain,
-                        mod_envelope,
-                    ) * (6.28 * 0.02);
-                    left_disp[i] = output as f32;
-                    right_disp[i] = output as f32;
-                    left[i] += output as f32;
-                    right[i] += output as f32;
+                    );
                 }
-                startidxmut += proc;
-                remaining -= proc;
-            }
-        } else {
-            let mut remaining = frame_count;
-            let mut startidxmut = startidx;
-            while remaining > 0 {
-                let proc = remaining.min(8);
-                let mod_envelope = self.mod_envelope.get_n(
-                    &settings.mod_envelope,
-                    settings.sample_rate.get(),
-                    proc,
-                );
-
-                // set up filter
-                self.filter1.set_cutoff(
-                    &settings.filter1,
-                    mod_envelope,
-                    settings.sample_rate.get(),
+                if pitch_automated {
+                    self.update_note(settings, automated, i, state, sps_detune_tab, true);
+                }
+                let output = self.compute_one(
+                    state,
+                    settings,
+                    automated,
+                    i,
                     touch,
                     lfo,
-                );
-                self.updatenote(proc, settings, state, sps_detune_tab);
-                for i in startidxmut..proc + startidxmut {
-                    let output = self.compute_one(
-                        state,
-                        &settings,
-                        touch,
-                        lfo,
-                        osc1_gain,
-                        osc2_gain,
-                        mod_envelope,
-                    ) * (6.28 * 0.02);
-                    left[i] += output as f32;
-                    right[i] += output as f32;
+                    osc1_gain,
+                    osc2_gain,
+                    mod_envelope,
+                ) * (6.28 * 0.02);
+                if let Some((left_disp, right_disp)) = &mut display {
+                    left_disp[i] = output;
+                    right_disp[i] = output;
                 }
-                startidxmut += proc;
-                remaining -= proc;
+                left[i] += output;
+                right[i] += output;
             }
+            startidxmut += proc;
+            remaining -= proc;
         }
     }
 }
@@ -1662,7 +1756,14 @@ pub struct IronFishState {
     g: IronFishGlobalVoiceState,
     chorus: ChorusState,
     reverb: ReverbState,
-    actual_delay_length: f32
+    actual_delay_length: f32,
+    automation: Vec<IronFishAutomation>,
+    /// The frame of the block being rendered, notes started between blocks see the latest automated values
+    frame: usize,
+    clock: f64,
+    mpe: MpeState,
+    // the channel of the last midi note, voices started for it follow that channel's expression
+    midi_channel: u8,
 }
 
 impl IronFishState {
@@ -1718,11 +1819,15 @@ impl IronFishState {
     pub fn internal_note_on(&mut self, b1: u8, b2: u8) {
         for i in 0..self.voices.len() {
             if self.voices[i].active() == -1 {
+                self.voices[i].channel = self.midi_channel;
+                self.voices[i].expression = self.mpe.expression(self.midi_channel);
                 self.voices[i].note_on(
                     b1,
                     self.lastnote,
                     b2,
                     &self.settings,
+                    Automated(&self.automation),
+                    self.frame,
                     &self.g,
                     &self.sps_detune_tab,
                 );
@@ -1734,6 +1839,20 @@ impl IronFishState {
         }
     }
 
+    pub fn update_expression(&mut self) {
+        for voice in &mut self.voices {
+            if voice.active() == -1 {
+                continue;
+            }
+            let expression = self.mpe.expression(voice.channel);
+            let bent = expression.pitch_bend != voice.expression.pitch_bend;
+            voice.expression = expression;
+            if bent {
+                voice.update_note(&self.settings, Automated(&self.automation), self.frame, &self.g, &self.sps_detune_tab, true);
+            }
+        }
+    }
+
     pub fn rebuildarp(&mut self) {
         let mut current = 0;
         for i in 0..128 {
@@ -1774,13 +1893,14 @@ impl IronFishState {
             return;
         };
 
-        let amount = self.settings.bitcrush.amount.get();
-        let crushbits = (amount * 22.0) as i32;
-        let precrushmult = 65536.0 * 8.0;
-        let postcrushmult = (1 << crushbits) as f32 / precrushmult;
+        let automated = Automated(&self.automation);
         let frame_count = buffer.frame_count();
         let (left, right) = buffer.stereo_mut();
+        let precrushmult = 65536.0 * 8.0;
         for i in 0..frame_count {
+            let amount = automated.get(BITCRUSH_AMOUNT, &self.settings.bitcrush.amount, i);
+            let crushbits = (amount * 22.0) as i32;
+            let postcrushmult = (1 << crushbits) as f32 / precrushmult;
             let intermediate_left = (left[i] * precrushmult) as i32;
             let crushed_left = intermediate_left >> crushbits;
 
@@ -1792,7 +1912,8 @@ impl IronFishState {
         }
     }
     pub fn apply_reverb(&mut self, buffer: &mut AudioBuffer) {
-        if self.settings.reverb.mix.get() == 0.0 {
+        let automated = Automated(&self.automation);
+        if self.settings.reverb.mix.get() == 0.0 && !automated.has(REVERB_MIX) {
             return;
         };
         let frame_count = buffer.frame_count();
@@ -1802,7 +1923,7 @@ impl IronFishState {
             let verb = self.reverb.toy.griesinger_reverb(
                 left[i],
                 right[i],
-                self.settings.reverb.mix.get(),
+                automated.get(REVERB_MIX, &self.settings.reverb.mix, i),
                 self.settings.reverb.feedback.get(),
             );
             left[i] = verb.0;
@@ -1840,10 +1961,10 @@ impl IronFishState {
             delayreadposr += 48000;
         }
 
-        let fb = self.settings.delay.delayfeedback.get() * 0.98;
-        let send = self.settings.delay.delaysend.get();
-
+        let automated = Automated(&self.automation);
         for i in 0..frame_count {
+            let fb = automated.get(DELAY_FEEDBACK, &self.settings.delay.delayfeedback, i) * 0.98;
+            let send = automated.get(DELAY_SEND, &self.settings.delay.delaysend, i);
             let rr = self.delaylineright[delayreadposr as  usize];
             let ll = self.delaylineleft[delayreadposl as usize];
 
@@ -1876,6 +1997,23 @@ impl IronFishState {
         }
     }
 
+    /// Renders the running automation for the next `frame_count` frames. A finished
+    /// automation holds its value until the UI moves the setting away from where it was
+    pub fn render_automation(&mut self, frame_count: usize) {
+        if self.automation.is_empty() {
+            return;
+        }
+        let targets = self.settings.automatable();
+        self.automation.retain( | a | !a.param.is_settled() || targets.iter().any( | (id, target) | {
+            *id == a.id && target.get() == a.ui_value
+        }));
+        for automation in &mut self.automation {
+            let param = &mut automation.param;
+            automation.values.clear();
+            automation.values.extend((0..frame_count).map( | _ | param.tick()));
+        }
+    }
+
     pub fn get_sequencer_step(&mut self, step: usize) -> u32 {
         return self.settings.sequencer.steps[step].get();
     }
@@ -1890,6 +2028,7 @@ impl IronFishState {
         }
 
         buffer.zero();
+        self.render_automation(buffer.frame_count());
 
         let mut pitchdirty: bool = false;
         if self.osc1cache.transpose.get() != self.settings.osc1.transpose.get() {
@@ -1981,7 +2120,7 @@ impl IronFishState {
 
             for i in 0..self.voices.len() {
                 if self.voices[i].active() > -1 {
-                    self.voices[i].update_note(&self.settings, &self.g, &self.sps_detune_tab, true);
+                    self.voices[i].update_note(&self.settings, Automated(&self.automation), 0, &self.g, &self.sps_detune_tab, true);
                 }
             }
         }
@@ -1989,10 +2128,16 @@ impl IronFishState {
         let mut remaining = buffer.frame_count();
         let mut bufferidx = 0;
 
-        let lfofreq = 0.5 * (2.0).powf(self.settings.lfo.rate.get() * 8.0 - 4.0);
-        let lfodphase = 1.0 / (self.settings.sample_rate.get() / lfofreq);
-
-        self.lfo.phase += remaining as f32 * lfodphase;
+        let automated = Automated(&self.automation);
+        let lfodphase = | frame | {
+            let lfofreq = 0.5 * (2.0).powf(automated.get(LFO_RATE, &self.settings.lfo.rate, frame) * 8.0 - 4.0);
+            1.0 / (self.settings.sample_rate.get() / lfofreq)
+        };
+        if automated.has(LFO_RATE) {
+            self.lfo.phase += (0..remaining).map(lfodphase).sum::<f32>();
+        } else {
+            self.lfo.phase += remaining as f32 * lfodphase(0);
+        }
 
         while self.lfo.phase > 1.0 {
             self.lfo.phase -= 1.0;
@@ -2003,6 +2148,7 @@ impl IronFishState {
         while remaining > 0 {
             //log!("b{}", remaining);
             let mut toprocess = remaining;
+            self.frame = bufferidx;
             if self.sequencer.samplesleftinstep == 0 {
                 if self.settings.sequencer.playing.get() {
                     if self.lastplaying == false {
@@ -2144,6 +2290,7 @@ impl IronFishState {
                         toprocess,
                         self.display_buffers[i].as_mut(),
                         &self.settings,
+                        Automated(&self.automation),
                         &self.g,
                         self.touch,
                         self.lfovalue,
@@ -2154,6 +2301,7 @@ impl IronFishState {
             bufferidx += toprocess;
             remaining -= toprocess;
         }
+        self.frame = bufferidx;
 
         for (i, dp) in self.display_buffers.iter_mut().enumerate() {
             if let Some(dp) = dp.take() {
@@ -2164,6 +2312,7 @@ impl IronFishState {
         self.chorus.apply_chorus(
             buffer,
             &self.settings.chorus,
+            Automated(&self.automation),
             self.settings.sample_rate.get(),
         );
         self.apply_delay(buffer);
@@ -2199,6 +2348,8 @@ impl Default for IronFishVoice {
             notetime: 0.0,
             notetimetotal: 0.0,
             seed: 1234,
+            channel: 0,
+            expression: MpeExpression::default(),
         }
     }
 }
@@ -2253,8 +2404,12 @@ impl AudioGraphNode for IronFishState {
     }
 
     fn handle_midi_data(&mut self, data: MidiData) {
+        if self.mpe.handle(&data.into()) {
+            self.update_expression();
+        }
         match data.decode() {
             MidiEvent::Note(note) => {
+                self.midi_channel = note.channel;
                 if note.is_on {
                     self.note_on(note.note_number, note.velocity);
                 } else {
@@ -2276,13 +2431,36 @@ impl AudioGraphNode for IronFishState {
         }
     }
 
+    fn handle_automation(&mut self, event: AutomationEvent) {
+        if !event.node.is_empty() {
+            return;
+        }
+        let Some((_, target)) = self.settings.automatable().into_iter().find( | (id, _) | *id == event.param) else {
+            return;
+        };
+        let automation = match self.automation.iter_mut().position( | a | a.id == event.param) {
+            Some(index) => &mut self.automation[index],
+            None => {
+                let mut param = AutomatedParam::new(target.get(), 0.005, self.settings.sample_rate.get());
+                param.set_time(self.clock);
+                self.automation.push(IronFishAutomation {id: event.param, param, values: Vec::new(), ui_value: target.get()});
+                self.automation.last_mut().unwrap()
+            }
+        };
+        automation.param.schedule(event);
+    }
+
     fn render_to_audio_buffer(
         &mut self,
-        _info: AudioInfo,
+        info: AudioInfo,
         outputs: &mut [&mut AudioBuffer],
         _inputs: &[&AudioBuffer],
         display: &mut DisplayAudioGraph,
     ) {
+        let time = block_time(&info, &mut self.clock, outputs[0].frame_count());
+        for automation in &mut self.automation {
+            automation.param.set_time(time);
+        }
         self.fill_buffer(outputs[0], display)
     }
 }
@@ -2341,7 +2519,12 @@ impl AudioComponent for IronFish {
             g: Default::default(),
             chorus: Default::default(),
             reverb: Default::default(),
-            actual_delay_length: 1.0
+            actual_delay_length: 1.0,
+            automation: Vec::new(),
+            frame: 0,
+            mpe: MpeState::new(),
+            midi_channel: 0,
+            clock: 0.0,
         })
     }
 
@@ -579,8 +579,8 @@ live_design!{
         img = <Image> {
             width: Fill,
             height: Fill
-            min_width: 1920,
-            min_height: 1080,
+            fallback_width: 1920,
+            fallback_height: 1080,
             fit: Horizontal,
             draw_bg: {
                 instance hover: 0.0
@@ -546,9 +546,9 @@ live_design!{
                             width: Fit, height: Fit, flow: Down,
                             <View> {
                                 show_bg: true, draw_bg: { color: (THEME_COLOR_BG_CONTAINER)}, width: 125, height: 250,
-                                <Image> { height: Fill, source: dep("crate://self/resources/ducky.png" ), min_height: 100 }
+                                <Image> { height: Fill, source: dep("crate://self/resources/ducky.png" ), fallback_height: 100 }
                             }
-                            <P> { text: "min_height: 100" } // TODO: get this to work correctly
+                            <P> { text: "fallback_height: 100" } // TODO: get this to work correctly
                         }
                         <View> {
                             width: Fit, height: Fit, flow: Down,
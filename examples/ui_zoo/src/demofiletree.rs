@@ -5,14 +5,13 @@ use makepad_widgets::makepad_micro_serde::*;
 
 use crate::{
         makepad_widgets::*,
-        makepad_widgets::file_tree::*,
     };
 
 live_design!{
     import makepad_widgets::theme_desktop_dark::*;
         
     DemoFileTree = {{DemoFileTree}}{
-        file_tree: <FileTree>{}
+        file_tree: <FileTreeView>{}
     }
 } 
 
@@ -69,31 +68,75 @@ impl FileNode {
     }
 }
 
+/// Shows the file nodes in a `TreeView`, the entries of the root are the top level
+pub struct FileTreeProvider<'a> {
+    pub file_nodes: &'a LiveIdMap<LiveId, FileNode>,
+}
+
+impl TreeViewProvider for FileTreeProvider<'_> {
+    fn children(&mut self, _cx: &mut Cx, parent: Option<LiveId>, out: &mut Vec<LiveId>) -> bool {
+        let parent = parent.unwrap_or(live_id!(root));
+        if let Some(child_edges) = self.file_nodes.get(&parent).and_then( | node | node.child_edges.as_ref()) {
+            out.extend(child_edges.iter().map( | edge | edge.file_node_id));
+        }
+        true
+    }
+
+    fn has_children(&self, node: LiveId) -> bool {
+        self.file_nodes.get(&node).is_some_and( | node | !node.is_file())
+    }
+
+    fn row_template(&self, node: LiveId) -> LiveId {
+        if self.has_children(node) {live_id!(Folder)} else {live_id!(File)}
+    }
+
+    fn fill_row(&mut self, _cx: &mut Cx, node: LiveId, row: &WidgetRef) {
+        if let Some(file_node) = self.file_nodes.get(&node) {
+            row.label(id!(name)).set_text(&file_node.name);
+        }
+    }
+}
+
 #[derive(Live, LiveHook, Widget)] 
 pub struct DemoFileTree{
-    #[wrap] #[live] pub file_tree: FileTree,
+    #[wrap] #[live] pub file_tree: TreeView,
     #[rust] pub file_nodes: LiveIdMap<LiveId, FileNode>,
     #[rust] pub root_path: String,
     #[rust] pub path_to_file_node_id:  HashMap<String, LiveId>
 }
 
 impl DemoFileTree{
-    pub fn draw_file_node(cx: &mut Cx2d, file_node_id: LiveId, file_tree:&mut FileTree, file_nodes: &LiveIdMap<LiveId, FileNode>) {
-        if let Some(file_node) = file_nodes.get(&file_node_id) {
-            match &file_node.child_edges {
-                Some(child_edges) => {
-                    if file_tree.begin_folder(cx, file_node_id, &file_node.name).is_ok() {
-                        for child_edge in child_edges {
-                            Self::draw_file_node(cx, child_edge.file_node_id, file_tree, file_nodes);
-                        }
-                        file_tree.end_folder();
-                    }
-                }
-                None => {
-                    file_tree.file(cx, file_node_id, &file_node.name);
+    /// Moves the dragged nodes in the demo data, the files on disk stay where they are
+    pub fn move_file_nodes(&mut self, nodes: &[LiveId], target: LiveId, position: TreeViewDropPosition) {
+        let parent_of = | file_nodes: &LiveIdMap<LiveId, FileNode>, node: LiveId | {
+            file_nodes.get(&node)
+                .and_then( | node | node.parent_edge.as_ref())
+                .map( | edge | edge.file_node_id)
+        };
+        let new_parent = match position {
+            TreeViewDropPosition::Into => Some(target),
+            _ => parent_of(&self.file_nodes, target)
+        };
+        let Some(new_parent) = new_parent else {return};
+        let mut moved = Vec::new();
+        for node in nodes {
+            let Some(old_parent) = parent_of(&self.file_nodes, *node) else {continue};
+            if let Some(child_edges) = self.file_nodes.get_mut(&old_parent).and_then( | node | node.child_edges.as_mut()) {
+                if let Some(index) = child_edges.iter().position( | edge | edge.file_node_id == *node) {
+                    moved.push(child_edges.remove(index));
                 }
             }
+            if let Some(file_node) = self.file_nodes.get_mut(node) {
+                file_node.parent_edge = Some(FileEdge {name: file_node.name.clone(), file_node_id: new_parent});
+            }
         }
+        let Some(child_edges) = self.file_nodes.get_mut(&new_parent).and_then( | node | node.child_edges.as_mut()) else {return};
+        let index = match position {
+            TreeViewDropPosition::Into => child_edges.len(),
+            TreeViewDropPosition::Before => child_edges.iter().position( | edge | edge.file_node_id == target).unwrap_or(0),
+            TreeViewDropPosition::After => child_edges.iter().position( | edge | edge.file_node_id == target).map_or(child_edges.len(), | index | index + 1),
+        };
+        child_edges.splice(index..index, moved);
     }
 
 
@@ -173,13 +216,8 @@ pub enum FileError {
 impl Widget for DemoFileTree {
     fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
         while self.file_tree.draw_walk(cx, scope, walk).is_step() {
-            self.file_tree.set_folder_is_open(cx, live_id!(root).into(), true, Animate::No);
-             Self::draw_file_node(
-                cx,
-                live_id!(root).into(),
-                &mut self.file_tree,
-                &self.file_nodes
-            );
+            let mut provider = FileTreeProvider {file_nodes: &self.file_nodes};
+            self.file_tree.draw_nodes(cx, scope, &mut provider);
         }
         DrawStep::done()
     }
@@ -273,6 +311,11 @@ impl Widget for DemoFileTree {
             _ => {}
         }
 
-        self.file_tree.handle_event(cx, event, scope);
+        let actions = cx.capture_actions( | cx | self.file_tree.handle_event(cx, event, scope));
+        if let TreeViewAction::Move {nodes, target, position} = actions.find_widget_action_cast(self.file_tree.widget_uid()) {
+            self.move_file_nodes(&nodes, target, position);
+            self.file_tree.redraw(cx);
+        }
+        cx.extend_actions(actions);
     }
 }
\ No newline at end of file
@@ -75,6 +75,7 @@ impl MatchEvent for App{
                     width: vfb.format.width / 2,
                     height: vfb.format.height
                 });
+                self.video_input[id].set_streaming(cx, true);
             }
             if let Some(buf) = vfb.as_vec_u32() {
                 self.video_input[id].swap_vec_u32(cx, buf);
@@ -0,0 +1,113 @@
+use {
+    std::{
+        fmt,
+        fs::File,
+        io::{self, BufRead, BufReader, Cursor},
+        path::Path,
+    },
+    crate::{
+        wav::WavDecoder,
+        flac::FlacDecoder,
+    }
+};
+
+/// The possible errors that can occur when decoding an audio file.
+#[derive(Debug)]
+pub enum AudioDecodeError {
+    /// Reading from the underlying stream failed.
+    Io(io::Error),
+    /// The data did not start with a known file signature.
+    UnsupportedFormat,
+    /// The file is valid but uses an encoding we can't decode, the reason is included.
+    UnsupportedEncoding(&'static str),
+    /// The file is damaged, the reason is included.
+    InvalidData(&'static str),
+}
+
+impl fmt::Display for AudioDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for AudioDecodeError {}
+
+impl From<io::Error> for AudioDecodeError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AudioStreamInfo {
+    pub sample_rate: u32,
+    pub channel_count: usize,
+    pub bits_per_sample: u32,
+    /// Not every stream knows its length up front
+    pub frame_count: Option<u64>,
+}
+
+/// Picks the decoder from the file signature, all decoders produce interleaved f32 frames in -1..1
+pub enum AudioDecoder<R: BufRead> {
+    Wav(WavDecoder<R>),
+    Flac(FlacDecoder<R>),
+}
+
+impl<R: BufRead> AudioDecoder<R> {
+    pub fn new(mut reader: R) -> Result<Self, AudioDecodeError> {
+        let magic = reader.fill_buf()?;
+        if magic.starts_with(b"RIFF") {
+            Ok(Self::Wav(WavDecoder::new(reader)?))
+        }
+        else if magic.starts_with(b"fLaC") {
+            Ok(Self::Flac(FlacDecoder::new(reader)?))
+        }
+        else {
+            Err(AudioDecodeError::UnsupportedFormat)
+        }
+    }
+
+    pub fn info(&self) -> AudioStreamInfo {
+        match self {
+            Self::Wav(d) => d.info(),
+            Self::Flac(d) => d.info(),
+        }
+    }
+
+    /// Fills `output` with whole interleaved frames and returns the number of frames, 0 at the end of the stream
+    pub fn read_frames(&mut self, output: &mut [f32]) -> Result<usize, AudioDecodeError> {
+        match self {
+            Self::Wav(d) => d.read_frames(output),
+            Self::Flac(d) => d.read_frames(output),
+        }
+    }
+
+    pub fn read_to_end(&mut self) -> Result<Vec<f32>, AudioDecodeError> {
+        let info = self.info();
+        let mut data = Vec::new();
+        if let Some(frame_count) = info.frame_count {
+            data.reserve(frame_count as usize * info.channel_count);
+        }
+        let mut chunk = vec![0.0; 4096 * info.channel_count.max(1)];
+        loop {
+            let frames = self.read_frames(&mut chunk)?;
+            if frames == 0 {
+                return Ok(data)
+            }
+            data.extend_from_slice(&chunk[0..frames * info.channel_count]);
+        }
+    }
+}
+
+impl AudioDecoder<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AudioDecodeError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Decodes a whole file held in memory into interleaved frames
+pub fn decode_audio(data: &[u8]) -> Result<(AudioStreamInfo, Vec<f32>), AudioDecodeError> {
+    let mut decoder = AudioDecoder::new(Cursor::new(data))?;
+    let samples = decoder.read_to_end()?;
+    Ok((decoder.info(), samples))
+}
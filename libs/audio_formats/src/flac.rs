@@ -0,0 +1,505 @@
+use {
+    std::io::{self, Read},
+    crate::decoder::{AudioDecodeError, AudioStreamInfo},
+};
+
+/// Big-endian bit reader, FLAC never reads more than 32 bits at once
+struct BitReader<R: Read> {
+    reader: R,
+    buf: Vec<u8>,
+    pos: usize,
+    len: usize,
+    bit_buf: u64,
+    bit_count: u32,
+}
+
+impl<R: Read> BitReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: vec![0; 4096],
+            pos: 0,
+            len: 0,
+            bit_buf: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.pos == self.len {
+            self.pos = 0;
+            self.len = loop {
+                match self.reader.read(&mut self.buf) {
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                    res => break res?
+                }
+            };
+        }
+        Ok(self.len > 0)
+    }
+
+    fn read_byte(&mut self) -> io::Result<u8> {
+        if !self.fill()? {
+            return Err(io::ErrorKind::UnexpectedEof.into())
+        }
+        self.pos += 1;
+        Ok(self.buf[self.pos - 1])
+    }
+
+    fn is_eof(&mut self) -> io::Result<bool> {
+        Ok(self.bit_count == 0 && !self.fill()?)
+    }
+
+    fn read_bits(&mut self, count: u32) -> io::Result<u32> {
+        if count == 0 {
+            return Ok(0)
+        }
+        while self.bit_count < count {
+            self.bit_buf = (self.bit_buf << 8) | self.read_byte()? as u64;
+            self.bit_count += 8;
+        }
+        self.bit_count -= count;
+        Ok(((self.bit_buf >> self.bit_count) & ((1u64 << count) - 1)) as u32)
+    }
+
+    fn read_signed(&mut self, count: u32) -> io::Result<i32> {
+        if count == 0 {
+            return Ok(0)
+        }
+        let value = self.read_bits(count)?;
+        Ok(((value << (32 - count)) as i32) >> (32 - count))
+    }
+
+    /// Counts zero bits up to and including the terminating one bit
+    fn read_unary(&mut self) -> io::Result<u32> {
+        let mut count = 0;
+        loop {
+            if self.bit_count == 0 {
+                self.bit_buf = self.read_byte()? as u64;
+                self.bit_count = 8;
+            }
+            let bits = self.bit_buf & ((1u64 << self.bit_count) - 1);
+            if bits == 0 {
+                count += self.bit_count;
+                self.bit_count = 0;
+                continue;
+            }
+            let top = 63 - bits.leading_zeros();
+            count += self.bit_count - 1 - top;
+            self.bit_count = top;
+            return Ok(count)
+        }
+    }
+
+    fn align_to_byte(&mut self) {
+        self.bit_count -= self.bit_count % 8;
+    }
+}
+
+/// Streams samples out of a native FLAC file, decoding one frame at a time
+pub struct FlacDecoder<R: Read> {
+    reader: BitReader<R>,
+    info: AudioStreamInfo,
+    /// The decoded frame, one vec per channel
+    block: Vec<Vec<i32>>,
+    block_len: usize,
+    block_pos: usize,
+    residual: Vec<i32>,
+}
+
+const BLOCK_STREAMINFO: u32 = 0;
+
+impl<R: Read> FlacDecoder<R> {
+    pub fn new(reader: R) -> Result<Self, AudioDecodeError> {
+        let mut reader = BitReader::new(reader);
+        if reader.read_bits(32)? != u32::from_be_bytes(*b"fLaC") {
+            return Err(AudioDecodeError::UnsupportedFormat)
+        }
+        let mut info = None;
+        loop {
+            let last = reader.read_bits(1)? == 1;
+            let block_type = reader.read_bits(7)?;
+            let length = reader.read_bits(24)?;
+            if block_type == BLOCK_STREAMINFO {
+                if length < 34 {
+                    return Err(AudioDecodeError::InvalidData("streaminfo too small"))
+                }
+                let _min_block_size = reader.read_bits(16)?;
+                let _max_block_size = reader.read_bits(16)?;
+                let _min_frame_size = reader.read_bits(24)?;
+                let _max_frame_size = reader.read_bits(24)?;
+                let sample_rate = reader.read_bits(20)?;
+                let channel_count = reader.read_bits(3)? as usize + 1;
+                let bits_per_sample = reader.read_bits(5)? + 1;
+                let total = ((reader.read_bits(4)? as u64) << 32) | reader.read_bits(32)? as u64;
+                if sample_rate == 0 {
+                    return Err(AudioDecodeError::InvalidData("zero sample rate"))
+                }
+                for _ in 0..(length - 18) {
+                    reader.read_bits(8)?; // md5 and any padding
+                }
+                info = Some(AudioStreamInfo {
+                    sample_rate,
+                    channel_count,
+                    bits_per_sample,
+                    frame_count: if total == 0 {None} else {Some(total)},
+                });
+            }
+            else {
+                for _ in 0..length {
+                    reader.read_bits(8)?;
+                }
+            }
+            if last {
+                break;
+            }
+        }
+        let info = info.ok_or(AudioDecodeError::InvalidData("missing streaminfo"))?;
+        if info.bits_per_sample > 32 {
+            return Err(AudioDecodeError::UnsupportedEncoding("bits per sample"))
+        }
+        Ok(Self {
+            reader,
+            info,
+            block: vec![Vec::new(); info.channel_count],
+            block_len: 0,
+            block_pos: 0,
+            residual: Vec::new(),
+        })
+    }
+
+    pub fn info(&self) -> AudioStreamInfo {
+        self.info
+    }
+
+    pub fn read_frames(&mut self, output: &mut [f32]) -> Result<usize, AudioDecodeError> {
+        let channel_count = self.info.channel_count;
+        let scale = 1.0 / (1u64 << (self.info.bits_per_sample - 1)) as f32;
+        let frames = output.len() / channel_count;
+        let mut written = 0;
+        while written < frames {
+            if self.block_pos == self.block_len && !self.decode_frame()? {
+                break;
+            }
+            let count = (frames - written).min(self.block_len - self.block_pos);
+            for i in 0..count {
+                for c in 0..channel_count {
+                    output[(written + i) * channel_count + c] = self.block[c][self.block_pos + i] as f32 * scale;
+                }
+            }
+            self.block_pos += count;
+            written += count;
+        }
+        Ok(written)
+    }
+
+    fn decode_frame(&mut self) -> Result<bool, AudioDecodeError> {
+        let r = &mut self.reader;
+        if r.is_eof()? {
+            return Ok(false)
+        }
+        if r.read_bits(14)? != 0x3ffe {
+            return Err(AudioDecodeError::InvalidData("lost frame sync"))
+        }
+        r.read_bits(2)?; // reserved and blocking strategy
+        let block_size_code = r.read_bits(4)?;
+        let sample_rate_code = r.read_bits(4)?;
+        let channel_assignment = r.read_bits(4)?;
+        let sample_size_code = r.read_bits(3)?;
+        r.read_bits(1)?;
+        // frame or sample number, utf8 style coded
+        let first = r.read_bits(8)?;
+        for _ in 1..(first as u8).leading_ones().max(1) {
+            r.read_bits(8)?;
+        }
+        let block_size = match block_size_code {
+            1 => 192,
+            2..=5 => 576 << (block_size_code - 2),
+            6 => r.read_bits(8)? as usize + 1,
+            7 => r.read_bits(16)? as usize + 1,
+            8..=15 => 256 << (block_size_code - 8),
+            _ => return Err(AudioDecodeError::InvalidData("reserved block size"))
+        };
+        match sample_rate_code {
+            12 => {r.read_bits(8)?;}
+            13 | 14 => {r.read_bits(16)?;}
+            15 => return Err(AudioDecodeError::InvalidData("invalid sample rate")),
+            _ => ()
+        }
+        let bits_per_sample = match sample_size_code {
+            0 => self.info.bits_per_sample,
+            1 => 8,
+            2 => 12,
+            4 => 16,
+            5 => 20,
+            6 => 24,
+            7 => 32,
+            _ => return Err(AudioDecodeError::InvalidData("reserved sample size"))
+        };
+        r.read_bits(8)?; // crc-8
+
+        let channel_count = match channel_assignment {
+            0..=7 => channel_assignment as usize + 1,
+            8..=10 => 2,
+            _ => return Err(AudioDecodeError::InvalidData("reserved channel assignment"))
+        };
+        if channel_count != self.info.channel_count {
+            return Err(AudioDecodeError::UnsupportedEncoding("channel count changes mid stream"))
+        }
+        for c in 0..channel_count {
+            // the side channel carries one extra bit
+            let side = matches!((channel_assignment, c), (8, 1) | (9, 0) | (10, 1));
+            let bits = bits_per_sample + side as u32;
+            if bits > 32 {
+                return Err(AudioDecodeError::UnsupportedEncoding("33 bit side channel"))
+            }
+            let mut samples = std::mem::take(&mut self.block[c]);
+            samples.resize(block_size, 0);
+            self.decode_subframe(&mut samples, bits)?;
+            self.block[c] = samples;
+        }
+        self.reader.align_to_byte();
+        self.reader.read_bits(16)?; // crc-16
+
+        if channel_count == 2 {
+            let (left, right) = self.block.split_at_mut(1);
+            let (a, b) = (&mut left[0], &mut right[0]);
+            for i in 0..block_size {
+                match channel_assignment {
+                    8 => b[i] = a[i].wrapping_sub(b[i]), // left, side
+                    9 => a[i] = a[i].wrapping_add(b[i]), // side, right
+                    10 => { // mid, side
+                        let side = b[i];
+                        let mid = (a[i].wrapping_shl(1)) | (side & 1);
+                        a[i] = mid.wrapping_add(side) >> 1;
+                        b[i] = mid.wrapping_sub(side) >> 1;
+                    }
+                    _ => ()
+                }
+            }
+        }
+        self.block_len = block_size;
+        self.block_pos = 0;
+        Ok(true)
+    }
+
+    fn decode_subframe(&mut self, samples: &mut [i32], bits: u32) -> Result<(), AudioDecodeError> {
+        let r = &mut self.reader;
+        if r.read_bits(1)? != 0 {
+            return Err(AudioDecodeError::InvalidData("subframe padding bit set"))
+        }
+        let subframe_type = r.read_bits(6)?;
+        let wasted = if r.read_bits(1)? == 1 {r.read_unary()? + 1} else {0};
+        if wasted >= bits {
+            return Err(AudioDecodeError::InvalidData("too many wasted bits"))
+        }
+        let bits = bits - wasted;
+        match subframe_type {
+            0 => {
+                let value = r.read_signed(bits)?;
+                samples.fill(value);
+            }
+            1 => {
+                for s in samples.iter_mut() {
+                    *s = r.read_signed(bits)?;
+                }
+            }
+            8..=12 => {
+                let order = (subframe_type - 8) as usize;
+                self.decode_fixed(samples, bits, order)?;
+            }
+            32..=63 => {
+                let order = (subframe_type - 31) as usize;
+                self.decode_lpc(samples, bits, order)?;
+            }
+            _ => return Err(AudioDecodeError::InvalidData("reserved subframe type"))
+        }
+        if wasted > 0 {
+            for s in samples.iter_mut() {
+                *s <<= wasted;
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_fixed(&mut self, samples: &mut [i32], bits: u32, order: usize) -> Result<(), AudioDecodeError> {
+        if order > samples.len() {
+            return Err(AudioDecodeError::InvalidData("predictor order exceeds block size"))
+        }
+        for s in samples[0..order].iter_mut() {
+            *s = self.reader.read_signed(bits)?;
+        }
+        self.decode_residual(samples.len(), order)?;
+        for i in order..samples.len() {
+            let s = |j: usize| samples[i - j] as i64;
+            let prediction = match order {
+                0 => 0,
+                1 => s(1),
+                2 => 2 * s(1) - s(2),
+                3 => 3 * s(1) - 3 * s(2) + s(3),
+                _ => 4 * s(1) - 6 * s(2) + 4 * s(3) - s(4),
+            };
+            samples[i] = (prediction + self.residual[i - order] as i64) as i32;
+        }
+        Ok(())
+    }
+
+    fn decode_lpc(&mut self, samples: &mut [i32], bits: u32, order: usize) -> Result<(), AudioDecodeError> {
+        if order > samples.len() {
+            return Err(AudioDecodeError::InvalidData("predictor order exceeds block size"))
+        }
+        let r = &mut self.reader;
+        for s in samples[0..order].iter_mut() {
+            *s = r.read_signed(bits)?;
+        }
+        let precision = r.read_bits(4)? + 1;
+        if precision == 16 {
+            return Err(AudioDecodeError::InvalidData("invalid coefficient precision"))
+        }
+        let shift = r.read_signed(5)?;
+        if shift < 0 {
+            return Err(AudioDecodeError::InvalidData("negative lpc shift"))
+        }
+        let mut coefs = [0i64; 32];
+        for c in coefs[0..order].iter_mut() {
+            *c = r.read_signed(precision)? as i64;
+        }
+        self.decode_residual(samples.len(), order)?;
+        for i in order..samples.len() {
+            let mut sum = 0i64;
+            for (j, c) in coefs[0..order].iter().enumerate() {
+                sum += c * samples[i - 1 - j] as i64;
+            }
+            samples[i] = ((sum >> shift) + self.residual[i - order] as i64) as i32;
+        }
+        Ok(())
+    }
+
+    fn decode_residual(&mut self, block_size: usize, order: usize) -> Result<(), AudioDecodeError> {
+        let r = &mut self.reader;
+        let param_bits = match r.read_bits(2)? {
+            0 => 4,
+            1 => 5,
+            _ => return Err(AudioDecodeError::InvalidData("reserved residual coding"))
+        };
+        let escape = (1 << param_bits) - 1;
+        let partition_order = r.read_bits(4)?;
+        let partitions = 1usize << partition_order;
+        if !block_size.is_multiple_of(partitions) || (block_size >> partition_order) < order {
+            return Err(AudioDecodeError::InvalidData("bad partition order"))
+        }
+        self.residual.clear();
+        for p in 0..partitions {
+            let count = (block_size >> partition_order) - if p == 0 {order} else {0};
+            let param = r.read_bits(param_bits)?;
+            if param == escape {
+                let raw_bits = r.read_bits(5)?;
+                for _ in 0..count {
+                    self.residual.push(r.read_signed(raw_bits)?);
+                }
+            }
+            else {
+                for _ in 0..count {
+                    let high = r.read_unary()?;
+                    let value = (high << param) | r.read_bits(param)?;
+                    self.residual.push((value >> 1) as i32 ^ -((value & 1) as i32));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::decoder::decode_audio,
+    };
+
+    const PATTERN16_STEREO: &[u8] = include_bytes!("../fixtures/pattern16_stereo.flac");
+    const PATTERN24_MONO: &[u8] = include_bytes!("../fixtures/pattern24_mono.flac");
+
+    // the samples the fixtures were encoded from, a wrapping ramp with a constant run and a run with
+    // wasted low bits, spread over frames of several sizes and every subframe type
+    fn pattern(bits: u32, channel: i64, index: i64) -> i64 {
+        let modulo = (1i64 << bits) - 3;
+        let offset = (1i64 << (bits - 1)) - 2;
+        let value = (index * 7919 + channel * 104729) % modulo - offset;
+        match index {
+            100..=299 => 1234,
+            400..=499 => value & !3,
+            _ => value
+        }
+    }
+
+    fn check_fixture(file: &[u8], bits: u32, channel_count: usize, frame_count: usize, sample_rate: u32) {
+        let (info, samples) = decode_audio(file).unwrap();
+        assert_eq!(info, AudioStreamInfo {
+            sample_rate,
+            channel_count,
+            bits_per_sample: bits,
+            frame_count: Some(frame_count as u64),
+        });
+        assert_eq!(samples.len(), frame_count * channel_count);
+        let scale = (1i64 << (bits - 1)) as f32;
+        for (i, sample) in samples.iter().enumerate() {
+            let expected = pattern(bits, (i % channel_count) as i64, (i / channel_count) as i64) as f32 / scale;
+            assert_eq!(*sample, expected, "sample {}", i);
+        }
+    }
+
+    #[test]
+    fn decodes_16_bit_stereo() {
+        check_fixture(PATTERN16_STEREO, 16, 2, 1000, 44100);
+    }
+
+    #[test]
+    fn decodes_24_bit_mono() {
+        check_fixture(PATTERN24_MONO, 24, 1, 700, 48000);
+    }
+
+    #[test]
+    fn reads_in_small_pieces() {
+        let mut decoder = FlacDecoder::new(PATTERN16_STEREO).unwrap();
+        let mut output = [0.0; 2 * 37];
+        let mut total = 0;
+        loop {
+            let frames = decoder.read_frames(&mut output).unwrap();
+            if frames == 0 {
+                break;
+            }
+            assert_eq!(output[0], pattern(16, 0, total as i64) as f32 / 32768.0);
+            total += frames;
+        }
+        assert_eq!(total, 1000);
+    }
+
+    #[test]
+    fn truncated_file_is_an_error() {
+        for len in (0..PATTERN16_STEREO.len()).step_by(97) {
+            // a cut right between two frames reads as a shorter stream
+            if let Ok((_, samples)) = decode_audio(&PATTERN16_STEREO[..len]) {
+                assert!(samples.len() < 2000, "decoded all of a file cut at {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_zero_sample_rate() {
+        let mut file = PATTERN16_STEREO.to_vec();
+        // the 20 bit rate follows the block and frame sizes in streaminfo
+        file[18] = 0;
+        file[19] = 0;
+        file[20] &= 0x0f;
+        assert!(matches!(FlacDecoder::new(&file[..]), Err(AudioDecodeError::InvalidData(_))));
+    }
+
+    #[test]
+    fn lost_sync_is_an_error() {
+        let mut file = PATTERN24_MONO.to_vec();
+        let first_frame = 4 + 4 + 34 + 4 + 10;
+        file[first_frame] = 0;
+        assert!(matches!(decode_audio(&file), Err(AudioDecodeError::InvalidData(_))));
+    }
+}
@@ -0,0 +1,9 @@
+mod decoder;
+pub mod wav;
+pub mod flac;
+pub mod resample;
+
+pub use crate::decoder::*;
+pub use crate::wav::WavDecoder;
+pub use crate::flac::FlacDecoder;
+pub use crate::resample::{Resampler, resample};
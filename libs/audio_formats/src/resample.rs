@@ -0,0 +1,144 @@
+/// Streaming sample rate converter using cubic hermite interpolation on interleaved frames.
+/// There is no anti-alias filter, so large downward ratios will alias a bit.
+pub struct Resampler {
+    channel_count: usize,
+    /// Input frames per output frame
+    step: f64,
+    /// Read position in `history`, in frames
+    pos: f64,
+    history: Vec<f32>,
+}
+
+impl Resampler {
+    /// A zero rate on either side passes the input through unchanged, it has no meaningful ratio
+    pub fn new(from_rate: u32, to_rate: u32, channel_count: usize) -> Self {
+        Self {
+            channel_count,
+            step: if from_rate == 0 || to_rate == 0 {1.0} else {from_rate as f64 / to_rate as f64},
+            pos: 1.0,
+            // one frame of silence in front so the first frame has a left neighbour
+            history: vec![0.0; channel_count],
+        }
+    }
+
+    /// Appends the resampled frames for `input` to `output`, frames near the end are held back until more input arrives
+    pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        let ch = self.channel_count;
+        self.history.extend_from_slice(input);
+        let frames = self.history.len() / ch;
+        while self.pos + 2.0 < frames as f64 {
+            let i = self.pos as usize;
+            let t = (self.pos - i as f64) as f32;
+            for c in 0..ch {
+                let y0 = self.history[(i - 1) * ch + c];
+                let y1 = self.history[i * ch + c];
+                let y2 = self.history[(i + 1) * ch + c];
+                let y3 = self.history[(i + 2) * ch + c];
+                let a = -0.5 * y0 + 1.5 * y1 - 1.5 * y2 + 0.5 * y3;
+                let b = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+                let c1 = -0.5 * y0 + 0.5 * y2;
+                output.push(((a * t + b) * t + c1) * t + y1);
+            }
+            self.pos += self.step;
+        }
+        let drop = (self.pos as usize).saturating_sub(1).min(frames);
+        self.history.drain(0..drop * ch);
+        self.pos -= drop as f64;
+    }
+
+    /// Pushes out the frames held back at the end of the stream
+    pub fn flush(&mut self, output: &mut Vec<f32>) {
+        let ch = self.channel_count;
+        // only emit frames that still land inside the real input
+        let end = (self.history.len() / ch) as f64;
+        let mut pos = self.pos;
+        let mut tail = Vec::new();
+        self.process(&vec![0.0; ch * 2], &mut tail);
+        for frame in tail.chunks(ch) {
+            if pos >= end {
+                break;
+            }
+            output.extend_from_slice(frame);
+            pos += self.step;
+        }
+    }
+}
+
+/// Converts a whole interleaved buffer to another rate
+pub fn resample(input: &[f32], channel_count: usize, from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || from_rate == 0 || to_rate == 0 {
+        return input.to_vec()
+    }
+    let mut resampler = Resampler::new(from_rate, to_rate, channel_count);
+    let mut output = Vec::with_capacity((input.len() as f64 * to_rate as f64 / from_rate as f64) as usize + channel_count);
+    resampler.process(input, &mut output);
+    resampler.flush(&mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f64, rate: u32, frames: usize) -> Vec<f32> {
+        (0..frames).map( | i | (std::f64::consts::TAU * frequency * i as f64 / rate as f64).sin() as f32).collect()
+    }
+
+    fn zero_crossings(data: &[f32]) -> usize {
+        data.windows(2).filter( | w | w[0] < 0.0 && w[1] >= 0.0).count()
+    }
+
+    #[test]
+    fn output_length_follows_the_ratio() {
+        for (from, to) in [(48000, 24000), (44100, 48000), (48000, 44100), (22050, 96000)] {
+            let input = vec![0.0; 2 * 9600];
+            let output = resample(&input, 2, from, to);
+            let expected = 9600.0 * to as f64 / from as f64;
+            let frames = (output.len() / 2) as f64;
+            assert!((frames - expected).abs() <= 2.0, "{} -> {} gave {} frames, expected {}", from, to, frames, expected);
+        }
+    }
+
+    #[test]
+    fn keeps_the_pitch() {
+        let input = sine(440.0, 44100, 44100);
+        let output = resample(&input, 1, 44100, 48000);
+        // one second of 440 Hz has 440 upward zero crossings at any rate
+        assert!((zero_crossings(&output) as i32 - 440).abs() <= 1);
+        let expected = sine(440.0, 48000, output.len());
+        let error = output.iter().zip(&expected).map( | (a, b) | (a - b).abs()).fold(0.0, f32::max);
+        assert!(error < 0.01, "max error {}", error);
+    }
+
+    #[test]
+    fn same_rate_is_a_copy() {
+        let input = sine(1000.0, 48000, 100);
+        assert_eq!(resample(&input, 1, 48000, 48000), input);
+    }
+
+    #[test]
+    fn zero_rate_passes_through() {
+        let input = sine(1000.0, 48000, 100);
+        assert_eq!(resample(&input, 1, 0, 48000), input);
+        assert_eq!(resample(&input, 1, 48000, 0), input);
+        // the streaming resampler must not spin on a zero step
+        let mut output = Vec::new();
+        Resampler::new(0, 48000, 1).process(&input, &mut output);
+        assert!(output.len() <= input.len());
+    }
+
+    #[test]
+    fn streaming_matches_whole_buffer() {
+        let input: Vec<f32> = sine(300.0, 44100, 2000).iter().flat_map( | s | [*s, -*s]).collect();
+        let whole = resample(&input, 2, 44100, 48000);
+        let mut resampler = Resampler::new(44100, 48000, 2);
+        let mut streamed = Vec::new();
+        for chunk in input.chunks(2 * 77) {
+            resampler.process(chunk, &mut streamed);
+        }
+        resampler.flush(&mut streamed);
+        // the read position is rebased per chunk, so only rounding may differ
+        assert_eq!(streamed.len(), whole.len());
+        assert!(streamed.iter().zip(&whole).all( | (a, b) | (a - b).abs() < 1e-4));
+    }
+}
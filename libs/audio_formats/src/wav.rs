@@ -0,0 +1,306 @@
+use {
+    std::io::{Read, ErrorKind},
+    crate::decoder::{AudioDecodeError, AudioStreamInfo},
+};
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SampleFormat {
+    UInt8,
+    Int16,
+    Int24,
+    Int32,
+    Float32,
+    Float64,
+}
+
+impl SampleFormat {
+    fn bytes(&self) -> usize {
+        match self {
+            Self::UInt8 => 1,
+            Self::Int16 => 2,
+            Self::Int24 => 3,
+            Self::Int32 | Self::Float32 => 4,
+            Self::Float64 => 8,
+        }
+    }
+}
+
+/// Streams PCM and float samples out of a RIFF WAVE file
+pub struct WavDecoder<R: Read> {
+    reader: R,
+    info: AudioStreamInfo,
+    format: SampleFormat,
+    block_align: usize,
+    /// Bytes left in the data chunk, `None` for streams written without a final size
+    data_left: Option<u64>,
+    bytes: Vec<u8>,
+}
+
+fn read_u16(b: &[u8]) -> u16 {
+    u16::from_le_bytes([b[0], b[1]])
+}
+
+fn read_u32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+/// Reads until `buf` is full or the stream ends, returning the bytes read
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, AudioDecodeError> {
+    let mut pos = 0;
+    while pos < buf.len() {
+        match reader.read(&mut buf[pos..]) {
+            Ok(0) => break,
+            Ok(n) => pos += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => (),
+            Err(e) => return Err(e.into())
+        }
+    }
+    Ok(pos)
+}
+
+fn skip<R: Read>(reader: &mut R, count: u64) -> Result<(), AudioDecodeError> {
+    let skipped = std::io::copy(&mut reader.take(count), &mut std::io::sink())?;
+    if skipped != count {
+        return Err(AudioDecodeError::InvalidData("chunk runs past the end of the file"))
+    }
+    Ok(())
+}
+
+impl<R: Read> WavDecoder<R> {
+    pub fn new(mut reader: R) -> Result<Self, AudioDecodeError> {
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header)?;
+        if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+            return Err(AudioDecodeError::UnsupportedFormat)
+        }
+        let mut fmt = None;
+        loop {
+            let mut chunk = [0u8; 8];
+            reader.read_exact(&mut chunk)?;
+            let size = read_u32(&chunk[4..8]);
+            match &chunk[0..4] {
+                b"fmt " => {
+                    if size < 16 {
+                        return Err(AudioDecodeError::InvalidData("fmt chunk too small"))
+                    }
+                    let mut data = vec![0u8; size as usize];
+                    reader.read_exact(&mut data)?;
+                    if size & 1 == 1 {
+                        skip(&mut reader, 1)?;
+                    }
+                    fmt = Some(data);
+                }
+                b"data" => {
+                    let fmt = fmt.ok_or(AudioDecodeError::InvalidData("data chunk before fmt chunk"))?;
+                    let mut format_tag = read_u16(&fmt[0..2]);
+                    let channel_count = read_u16(&fmt[2..4]) as usize;
+                    let sample_rate = read_u32(&fmt[4..8]);
+                    let block_align = read_u16(&fmt[12..14]) as usize;
+                    let bits_per_sample = read_u16(&fmt[14..16]) as u32;
+                    if format_tag == FORMAT_EXTENSIBLE {
+                        if fmt.len() < 26 {
+                            return Err(AudioDecodeError::InvalidData("extensible fmt chunk too small"))
+                        }
+                        // the subformat guid starts with the classic format tag
+                        format_tag = read_u16(&fmt[24..26]);
+                    }
+                    if channel_count == 0 || block_align == 0 || !block_align.is_multiple_of(channel_count) {
+                        return Err(AudioDecodeError::InvalidData("bad channel count or block align"))
+                    }
+                    if sample_rate == 0 {
+                        return Err(AudioDecodeError::InvalidData("zero sample rate"))
+                    }
+                    // samples with fewer valid bits are left-justified in their container
+                    let container = block_align / channel_count;
+                    let format = match (format_tag, container) {
+                        (FORMAT_PCM, 1) => SampleFormat::UInt8,
+                        (FORMAT_PCM, 2) => SampleFormat::Int16,
+                        (FORMAT_PCM, 3) => SampleFormat::Int24,
+                        (FORMAT_PCM, 4) => SampleFormat::Int32,
+                        (FORMAT_FLOAT, 4) => SampleFormat::Float32,
+                        (FORMAT_FLOAT, 8) => SampleFormat::Float64,
+                        (FORMAT_PCM, _) | (FORMAT_FLOAT, _) => return Err(AudioDecodeError::UnsupportedEncoding("sample size")),
+                        _ => return Err(AudioDecodeError::UnsupportedEncoding("compressed wav"))
+                    };
+                    // writers that stream to a pipe leave the size open
+                    let data_left = if size == 0 || size == u32::MAX {None} else {Some(size as u64)};
+                    return Ok(Self {
+                        reader,
+                        info: AudioStreamInfo {
+                            sample_rate,
+                            channel_count,
+                            bits_per_sample,
+                            frame_count: data_left.map( | d | d / block_align as u64),
+                        },
+                        format,
+                        block_align,
+                        data_left,
+                        bytes: Vec::new(),
+                    })
+                }
+                _ => {
+                    skip(&mut reader, size as u64 + (size & 1) as u64)?;
+                }
+            }
+        }
+    }
+
+    pub fn info(&self) -> AudioStreamInfo {
+        self.info
+    }
+
+    pub fn read_frames(&mut self, output: &mut [f32]) -> Result<usize, AudioDecodeError> {
+        let channel_count = self.info.channel_count;
+        let mut frames = output.len() / channel_count;
+        if let Some(data_left) = self.data_left {
+            frames = frames.min((data_left / self.block_align as u64) as usize);
+        }
+        self.bytes.resize(frames * self.block_align, 0);
+        let read = read_full(&mut self.reader, &mut self.bytes)?;
+        // a truncated last frame is dropped
+        let frames = read / self.block_align;
+        if let Some(data_left) = &mut self.data_left {
+            *data_left -= read as u64;
+        }
+
+        let sample_bytes = self.format.bytes();
+        for (i, out) in output[0..frames * channel_count].iter_mut().enumerate() {
+            let b = &self.bytes[i * sample_bytes..];
+            *out = match self.format {
+                SampleFormat::UInt8 => (b[0] as f32 - 128.0) / 128.0,
+                SampleFormat::Int16 => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+                SampleFormat::Int24 => (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8388608.0,
+                SampleFormat::Int32 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2147483648.0,
+                SampleFormat::Float32 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+                SampleFormat::Float64 => f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32,
+            };
+        }
+        Ok(frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::decoder::decode_audio,
+    };
+
+    fn wav(format_tag: u16, channel_count: u16, sample_rate: u32, container: u16, data: &[u8]) -> Vec<u8> {
+        let block_align = channel_count * container;
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&format_tag.to_le_bytes());
+        fmt.extend_from_slice(&channel_count.to_le_bytes());
+        fmt.extend_from_slice(&sample_rate.to_le_bytes());
+        fmt.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+        fmt.extend_from_slice(&block_align.to_le_bytes());
+        fmt.extend_from_slice(&(container * 8).to_le_bytes());
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(4 + 8 + fmt.len() as u32 + 8 + data.len() as u32).to_le_bytes());
+        out.extend_from_slice(b"WAVE");
+        out.extend_from_slice(b"fmt ");
+        out.extend_from_slice(&(fmt.len() as u32).to_le_bytes());
+        out.extend_from_slice(&fmt);
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    fn decode(file: &[u8]) -> (AudioStreamInfo, Vec<f32>) {
+        decode_audio(file).unwrap()
+    }
+
+    #[test]
+    fn reads_8_bit() {
+        let (info, samples) = decode(&wav(FORMAT_PCM, 1, 8000, 1, &[0, 128, 192]));
+        assert_eq!(info, AudioStreamInfo {sample_rate: 8000, channel_count: 1, bits_per_sample: 8, frame_count: Some(3)});
+        assert_eq!(samples, [-1.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn reads_16_bit_stereo() {
+        let data: Vec<u8> = [i16::MIN, 16384, 0, -16384].iter().flat_map( | s | s.to_le_bytes()).collect();
+        let (info, samples) = decode(&wav(FORMAT_PCM, 2, 44100, 2, &data));
+        assert_eq!((info.channel_count, info.frame_count), (2, Some(2)));
+        assert_eq!(samples, [-1.0, 0.5, 0.0, -0.5]);
+    }
+
+    #[test]
+    fn reads_24_bit() {
+        let data: Vec<u8> = [-8388608i32, 4194304, -1].iter().flat_map( | s | s.to_le_bytes()[0..3].to_vec()).collect();
+        let (info, samples) = decode(&wav(FORMAT_PCM, 1, 48000, 3, &data));
+        assert_eq!(info.bits_per_sample, 24);
+        assert_eq!(samples, [-1.0, 0.5, -1.0 / 8388608.0]);
+    }
+
+    #[test]
+    fn reads_32_bit() {
+        let data: Vec<u8> = [i32::MIN, 1 << 30].iter().flat_map( | s | s.to_le_bytes()).collect();
+        let (_, samples) = decode(&wav(FORMAT_PCM, 1, 48000, 4, &data));
+        assert_eq!(samples, [-1.0, 0.5]);
+    }
+
+    #[test]
+    fn reads_float() {
+        let data: Vec<u8> = [0.25f32, -0.75].iter().flat_map( | s | s.to_le_bytes()).collect();
+        assert_eq!(decode(&wav(FORMAT_FLOAT, 1, 48000, 4, &data)).1, [0.25, -0.75]);
+        let data: Vec<u8> = [0.125f64, -1.0].iter().flat_map( | s | s.to_le_bytes()).collect();
+        assert_eq!(decode(&wav(FORMAT_FLOAT, 1, 48000, 8, &data)).1, [0.125, -1.0]);
+    }
+
+    #[test]
+    fn reads_extensible() {
+        let data: Vec<u8> = [16384i16, -16384].iter().flat_map( | s | s.to_le_bytes()).collect();
+        let mut file = wav(FORMAT_EXTENSIBLE, 1, 48000, 2, &data);
+        // grow the fmt chunk by the 24 byte extension, the subformat guid starts with the format tag
+        let mut extension = vec![0u8; 24];
+        extension[0..2].copy_from_slice(&22u16.to_le_bytes());
+        extension[8..10].copy_from_slice(&FORMAT_PCM.to_le_bytes());
+        file.splice(36..36, extension);
+        file[16..20].copy_from_slice(&40u32.to_le_bytes());
+        assert_eq!(decode(&file).1, [0.5, -0.5]);
+    }
+
+    #[test]
+    fn skips_unknown_chunks_and_truncated_frames() {
+        let data: Vec<u8> = [16384i16, -16384, 1].iter().flat_map( | s | s.to_le_bytes()).collect();
+        let mut file = wav(FORMAT_PCM, 2, 48000, 2, &data);
+        // an odd sized chunk ahead of fmt is padded to an even length
+        file.splice(12..12, b"LIST\x03\x00\x00\x00abc\x00".iter().copied());
+        let (info, samples) = decode(&file);
+        assert_eq!(info.frame_count, Some(1));
+        assert_eq!(samples, [0.5, -0.5]);
+    }
+
+    #[test]
+    fn streams_without_size() {
+        let data: Vec<u8> = [16384i16, -16384].iter().flat_map( | s | s.to_le_bytes()).collect();
+        let mut file = wav(FORMAT_PCM, 1, 48000, 2, &data);
+        let size_at = file.len() - data.len() - 4;
+        file[size_at..size_at + 4].copy_from_slice(&0u32.to_le_bytes());
+        let (info, samples) = decode(&file);
+        assert_eq!(info.frame_count, None);
+        assert_eq!(samples, [0.5, -0.5]);
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        let data = [0u8; 4];
+        assert!(matches!(decode_audio(b"RIFX\0\0\0\0WAVE"), Err(AudioDecodeError::UnsupportedFormat)));
+        assert!(matches!(WavDecoder::new(&wav(FORMAT_PCM, 1, 0, 2, &data)[..]), Err(AudioDecodeError::InvalidData(_))));
+        assert!(matches!(WavDecoder::new(&wav(FORMAT_PCM, 0, 48000, 2, &data)[..]), Err(AudioDecodeError::InvalidData(_))));
+        assert!(matches!(WavDecoder::new(&wav(FORMAT_PCM, 1, 48000, 5, &data)[..]), Err(AudioDecodeError::UnsupportedEncoding(_))));
+        assert!(matches!(WavDecoder::new(&wav(2, 1, 48000, 2, &data)[..]), Err(AudioDecodeError::UnsupportedEncoding(_))));
+        // cut off in the middle of the header
+        let file = wav(FORMAT_PCM, 1, 48000, 2, &data);
+        for len in 0..44 {
+            assert!(WavDecoder::new(&file[..len]).is_err());
+        }
+    }
+}
@@ -10,7 +10,8 @@ pub struct PrettyPrintedF32(pub f32);
 
 impl fmt::Display for PrettyPrintedF32 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.0.abs().fract() < 0.00000001 {
+        // only whole numbers print without a dot, tiny fractions like 1e-10 must not get one appended
+        if self.0.fract() == 0.0 {
             write!(f, "{}.0", self.0)
         } else {
             write!(f, "{}", self.0)
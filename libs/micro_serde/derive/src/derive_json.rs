@@ -203,8 +203,7 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
                 for field in fields{
                     tb.ident(&field.name).add(":");
                     if field.ty.into_iter().next().unwrap().to_string() == "Option"{
-                        tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
-                        tb.add("{ t } else { None } ,");
+                        tb.ident(&format!("_{}",field.name)).add(". unwrap_or ( None ) ,");
                     }
                     else{
                         tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
@@ -273,8 +272,7 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
                         for field in fields{
                             tb.ident(&field.name).add(":");
                             if field.ty.into_iter().next().unwrap().to_string() == "Option"{
-                                tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
-                                tb.add("{ t } else { None } ,");
+                                tb.ident(&format!("_{}",field.name)).add(". unwrap_or ( None ) ,");
                             }
                             else{
                                 tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
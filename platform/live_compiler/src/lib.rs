@@ -98,7 +98,9 @@ pub use {
         makepad_live_tokenizer::{LiveErrorOrigin, live_error_origin},
         live_error::{
             LiveError,
-            LiveFileError
+            LiveErrorNote,
+            LiveFileError,
+            LiveFileErrorNote
         },
         live_document::{LiveOriginal, LiveExpanded}
     }
@@ -38,11 +38,26 @@ impl From<LiveTokenId> for LiveErrorSpan {
     }
 }
 
+// a secondary span attached to an error, like where a variable was declared
+#[derive(Clone)]
+pub struct LiveErrorNote {
+    pub span: LiveErrorSpan,
+    pub message: String,
+}
+
 #[derive(Clone)]
 pub struct LiveError {
     pub origin: LiveErrorOrigin,
     pub span: LiveErrorSpan,
     pub message: String,
+    pub notes: Vec<LiveErrorNote>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct LiveFileErrorNote {
+    pub file: String,
+    pub span: TextSpan,
+    pub message: String,
 }
 
 #[derive(Clone, PartialEq)]
@@ -51,6 +66,7 @@ pub struct LiveFileError {
     pub file: String,
     pub span: TextSpan,
     pub message: String,
+    pub notes: Vec<LiveFileErrorNote>,
 }
 
 impl fmt::Display for LiveFileError {
@@ -64,6 +80,23 @@ impl fmt::Display for LiveFileError {
             self.span.start.column+1,
             self.message,
             self.origin
+        )?;
+        for note in &self.notes {
+            write!(f, "\n   {}", note)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for LiveFileErrorNote {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "note: {}:{}:{} - {}",
+            self.file,
+            self.span.start.line+1,
+            self.span.start.column+1,
+            self.message,
         )
     }
 }
@@ -72,19 +105,35 @@ impl fmt::Display for LiveFileError {
 
 impl LiveError{
     
+    pub fn with_note(mut self, span: impl Into<LiveErrorSpan>, message: String) -> Self {
+        self.notes.push(LiveErrorNote {span: span.into(), message});
+        self
+    }
+    
     pub fn into_live_file_error(self, file:&str)->LiveFileError{
         LiveFileError {
             origin: self.origin.clone(),
             file: file.to_string(),
             span: self.span.into_text_span().unwrap(),
             message: self.message,
+            notes: self.notes.into_iter().filter_map( | note | {
+                Some(LiveFileErrorNote {
+                    file: file.to_string(),
+                    span: note.span.into_text_span()?,
+                    message: note.message
+                })
+            }).collect()
         }
     }
 }
 
 impl fmt::Display for LiveError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} - origin: {} ", self.message, self.origin)
+        write!(f, "{} - origin: {} ", self.message, self.origin)?;
+        for note in &self.notes {
+            write!(f, "\n   note: {}", note.message)?;
+        }
+        Ok(())
     }
 }
 
@@ -5,7 +5,7 @@ use {
         makepad_live_id::*,
        // makepad_error_log::*,
         makepad_live_tokenizer::{TokenWithLen, Delim, FullToken, State, Cursor, live_error_origin, LiveErrorOrigin},
-        live_error::{LiveError, LiveErrorSpan, LiveFileError},
+        live_error::{LiveError, LiveErrorSpan, LiveFileError, LiveFileErrorNote},
         live_parser::LiveParser,
         live_document::{LiveOriginal, LiveExpanded},
         live_node::{LiveNodeOrigin, LiveNode, LiveValue, LiveType, LiveTypeInfo, LiveIdAsProp, LiveDesignInfo, LiveDesignInfoIndex},
@@ -495,29 +495,36 @@ impl LiveRegistry {
     }
     
     pub fn live_error_to_live_file_error(&self, live_error: LiveError) -> LiveFileError {
-        match live_error.span {
+        let (file, span) = self.live_error_span_to_file_span(live_error.span);
+        LiveFileError {
+            origin: live_error.origin,
+            file,
+            span,
+            message: live_error.message,
+            notes: live_error.notes.into_iter().map( | note | {
+                let (file, span) = self.live_error_span_to_file_span(note.span);
+                LiveFileErrorNote {
+                    file,
+                    span,
+                    message: note.message
+                }
+            }).collect()
+        }
+    }
+    
+    fn live_error_span_to_file_span(&self, span: LiveErrorSpan) -> (String, TextSpan) {
+        match span {
             LiveErrorSpan::Text(text_span) => {
                 let live_file = &self.live_files[text_span.file_id.to_index()];
-                LiveFileError {
-                    origin: live_error.origin,
-                    file: live_file.file_name.clone(),
-                    span: text_span,
-                    message: live_error.message
-                }
+                (live_file.file_name.clone(), text_span)
             }
             LiveErrorSpan::Token(token_span) => {
-                let (file_name, span) = if let Some(file_id) = token_span.token_id.file_id() {
+                if let Some(file_id) = token_span.token_id.file_id() {
                     let live_file = &self.live_files[file_id.to_index()];
-                    (live_file.file_name.as_str(), live_file.original.tokens[token_span.token_id.token_index()].span)
+                    (live_file.file_name.clone(), live_file.original.tokens[token_span.token_id.token_index()].span)
                 }
                 else {
-                    ("<file id is not defined>", TextSpan::default())
-                };
-                LiveFileError {
-                    origin: live_error.origin,
-                    file: file_name.to_string(),
-                    span,
-                    message: live_error.message
+                    ("<file id is not defined>".to_string(), TextSpan::default())
                 }
             }
         }
@@ -559,7 +566,8 @@ impl LiveRegistry {
                         return Err(LiveError {
                             origin: live_error_origin!(),
                             span: span.into(),
-                            message: "Error tokenizing".to_string()
+                            message: "Error tokenizing".to_string(),
+                            notes: Vec::new()
                         })
                     },
                     _ => if let Some(live_token) = LiveToken::from_full_token(&full_token.token) {
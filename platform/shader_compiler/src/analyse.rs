@@ -19,7 +19,8 @@ use{
         const_eval::ConstEvaluator,
         const_gather::ConstGatherer,
         dep_analyse::DepAnalyser,
-        ty_check::TyChecker,
+        ty_check::{TyChecker, with_suggestion},
+        util::did_you_mean,
         shader_registry::ShaderRegistry
     }
 };
@@ -304,6 +305,7 @@ impl<'a> DrawShaderAnalyser<'a> {
                             message: String::from(
                                 "attribute must be either a floating-point scalar or vector or mat4",
                             ),
+                            notes: Vec::new(),
                         })
                     }
                 }
@@ -320,6 +322,7 @@ impl<'a> DrawShaderAnalyser<'a> {
                             message: String::from(
                                 "attribute must be either a floating-point scalar or vector or mat4",
                             ),
+                            notes: Vec::new(),
                         })
                     }
                 }
@@ -334,6 +337,7 @@ impl<'a> DrawShaderAnalyser<'a> {
                             origin: live_error_origin!(),
                             span: decl.span.into(),
                             message: String::from("texture must be either a texture2D or a textureOES (android)"),
+                            notes: Vec::new(),
                         })
                     }
                 }
@@ -354,6 +358,7 @@ impl<'a> DrawShaderAnalyser<'a> {
                             message: String::from(
                                 "varying must be either a floating-point scalar or vector",
                             ),
+                            notes: Vec::new(),
                         })
                     }
                 }
@@ -385,6 +390,7 @@ impl<'a> DrawShaderAnalyser<'a> {
                         message: String::from(
                             "function `vertex` must return a value of type `vec4`",
                         ),
+                        notes: Vec::new(),
                     })
                 }
             }
@@ -398,6 +404,7 @@ impl<'a> DrawShaderAnalyser<'a> {
                         message: String::from(
                             "function `fragment` must return a value of type `vec4`",
                         ),
+                        notes: Vec::new(),
                     })
                 }
             }
@@ -408,6 +415,7 @@ impl<'a> DrawShaderAnalyser<'a> {
                         origin: live_error_origin!(),
                         span: def.span.into(),
                         message: String::from("functions can't return arrays"),
+                        notes: Vec::new(),
                     })
                 }
                 _ => {}
@@ -447,6 +455,7 @@ impl<'a> DrawShaderAnalyser<'a> {
                     origin: live_error_origin!(),
                     span: sub_decl.span.into(),
                     message: format!("Struct has recursively dependency"),
+                    notes: Vec::new(),
                 });
             }
             
@@ -485,6 +494,7 @@ impl<'a> DrawShaderAnalyser<'a> {
                     origin: live_error_origin!(),
                     span: def.span.into(),
                     message: format!("function `{}` recursively calls `{}`", def.ident, callee_decl.ident),
+                    notes: Vec::new(),
                 });
             }
             
@@ -525,12 +535,14 @@ impl<'a> ConstAnalyser<'a> {
             self.const_def.span,
             &self.const_def.expr,
             &expected_ty,
+            Some(self.const_def.ty_expr.span),
         ) ?;
         if expected_ty != actual_ty {
             return Err(LiveError {
                 origin: live_error_origin!(),
                 span: self.const_def.span.into(),
                 message: String::from("Declared type and inferred type not the same"),
+                notes: Vec::new(),
             } .into());
         }
         self.const_evaluator().const_eval_expr(&self.const_def.expr) ?;
@@ -649,6 +661,7 @@ impl<'a> FnDefAnalyser<'a> {
                         message: format!(
                             "Function has no return",
                         ),
+                        notes: Vec::new(),
                     });
                 }
             }
@@ -691,6 +704,7 @@ impl<'a> FnDefAnalyser<'a> {
                                 closure_def.params.len(),
                                 params.len()
                             ),
+                            notes: Vec::new(),
                         });
                     }
                     // lets now push the argument idents on the scope
@@ -721,6 +735,7 @@ impl<'a> FnDefAnalyser<'a> {
                                         expr.ty.borrow().as_ref().unwrap(),
                                         return_ty.borrow().as_ref().unwrap()
                                     ),
+                                    notes: Vec::new(),
                                 });
                             }
                         }
@@ -765,6 +780,7 @@ impl<'a> FnDefAnalyser<'a> {
                 origin: live_error_origin!(),
                 span: self.fn_def.span.into(),
                 message: format!("Nesting closures is not supported at the moment"),
+                notes: Vec::new(),
             });
             
         }
@@ -821,6 +837,7 @@ impl<'a> FnDefAnalyser<'a> {
                 origin: live_error_origin!(),
                 span:span.into(),
                 message: String::from("break outside loop"),
+                notes: Vec::new(),
             } .into());
         }
         Ok(())
@@ -832,6 +849,7 @@ impl<'a> FnDefAnalyser<'a> {
                 origin: live_error_origin!(),
                 span:span.into(),
                 message: String::from("continue outside loop"),
+                notes: Vec::new(),
             } .into());
         }
         Ok(())
@@ -848,7 +866,7 @@ impl<'a> FnDefAnalyser<'a> {
     ) -> Result<(), LiveError> {
         
         self.ty_checker()
-            .ty_check_expr_with_expected_ty(span, from_expr, &Ty::Float) ?;
+            .ty_check_expr_with_expected_ty(span, from_expr, &Ty::Float, None) ?;
         
         let from = self
         .const_evaluator()
@@ -859,7 +877,7 @@ impl<'a> FnDefAnalyser<'a> {
         self.dep_analyser().dep_analyse_expr(from_expr);
         
         self.ty_checker()
-            .ty_check_expr_with_expected_ty(span, to_expr, &Ty::Float) ?;
+            .ty_check_expr_with_expected_ty(span, to_expr, &Ty::Float, None) ?;
             
         let to = self
         .const_evaluator()
@@ -869,7 +887,7 @@ impl<'a> FnDefAnalyser<'a> {
         self.dep_analyser().dep_analyse_expr(to_expr);
         if let Some(step_expr) = step_expr {
             self.ty_checker()
-                .ty_check_expr_with_expected_ty(span, step_expr, &Ty::Float) ?;
+                .ty_check_expr_with_expected_ty(span, step_expr, &Ty::Float, None) ?;
             let step = self
             .const_evaluator()
                 .const_eval_expr(step_expr) ?
@@ -880,6 +898,7 @@ impl<'a> FnDefAnalyser<'a> {
                     origin: live_error_origin!(),
                     span:span.into(),
                     message: String::from("step must not be zero"),
+                    notes: Vec::new(),
                 } .into());
             }
             if from < to && step < 0 {
@@ -887,6 +906,7 @@ impl<'a> FnDefAnalyser<'a> {
                     origin: live_error_origin!(),
                     span:span.into(),
                     message: String::from("step must not be positive"),
+                    notes: Vec::new(),
                 } .into());
             }
             if from > to && step > 0 {
@@ -894,6 +914,7 @@ impl<'a> FnDefAnalyser<'a> {
                     origin: live_error_origin!(),
                     span:span.into(),
                     message: String::from("step must not be negative"),
+                    notes: Vec::new(),
                 } .into());
             }
             self.dep_analyser().dep_analyse_expr(step_expr);
@@ -921,7 +942,7 @@ impl<'a> FnDefAnalyser<'a> {
         block_if_false: &Option<Box<Block >>,
     ) -> Result<(), LiveError> {
         self.ty_checker()
-            .ty_check_expr_with_expected_ty(span, expr, &Ty::Bool) ?;
+            .ty_check_expr_with_expected_ty(span, expr, &Ty::Bool, None) ?;
         self.const_evaluator().try_const_eval_expr(expr);
         self.const_gatherer().const_gather_expr(expr);
         self.dep_analyser().dep_analyse_expr(expr);
@@ -959,6 +980,7 @@ impl<'a> FnDefAnalyser<'a> {
                         origin: live_error_origin!(),
                         span:span.into(),
                         message: format!("Enum name mismatched, expected {} got {}", shader_enum.enum_name, match_item.enum_name.0),
+                        notes: Vec::new(),
                     } .into())
                 } 
                 
@@ -966,11 +988,14 @@ impl<'a> FnDefAnalyser<'a> {
                     match_item.enum_value.set(Some(pos + 1));
                 }
                 else{
-                    return Err(LiveError {
+                    let suggestion = did_you_mean(match_item.enum_variant.0, shader_enum.variants.iter().cloned());
+                    let err = LiveError {
                         origin: live_error_origin!(),
                         span:span.into(),
                         message: format!("Variant not found on enum {}::{}", match_item.enum_name.0, match_item.enum_variant.0),
-                    } .into())
+                        notes: Vec::new(),
+                    };
+                    return Err(with_suggestion(err, match_item.span, suggestion))
                 }
                 
                 // lets see if we have the right name
@@ -985,6 +1010,7 @@ impl<'a> FnDefAnalyser<'a> {
                 origin: live_error_origin!(),
                 span:span.into(),
                 message: String::from("Can only match on enum types"),
+                notes: Vec::new(),
             } .into())
         }
     }
@@ -1004,13 +1030,14 @@ impl<'a> FnDefAnalyser<'a> {
                     origin: live_error_origin!(),
                     span:span.into(),
                     message: format!("cannot define an uninitialised variable `{}`", ident),
+                    notes: Vec::new(),
                 });
             }
             let expected_ty = self.ty_checker().ty_check_ty_expr(ty_expr) ?;
             if let Some(expr) = expr {
                 let actual_ty =
                 self.ty_checker()
-                    .ty_check_expr_with_expected_ty(span, expr, &expected_ty) ?;
+                    .ty_check_expr_with_expected_ty(span, expr, &expected_ty, Some(ty_expr.span)) ?;
                 self.dep_analyser().dep_analyse_expr(expr);
                 actual_ty
             } else {
@@ -1023,7 +1050,8 @@ impl<'a> FnDefAnalyser<'a> {
                 return Err(LiveError {
                     origin: live_error_origin!(),
                     span:span.into(),
-                    message: String::from("init expression cannot be void")
+                    message: String::from("init expression cannot be void"),
+                    notes: Vec::new()
                 });
             }
             self.const_evaluator().try_const_eval_expr(expr);
@@ -1035,6 +1063,7 @@ impl<'a> FnDefAnalyser<'a> {
                 origin: live_error_origin!(),
                 span:span.into(),
                 message: format!("can't infer type of variable `{}`", ident),
+                notes: Vec::new(),
             });
         });
         let new_shadow = self.scopes.insert_sym(
@@ -1055,14 +1084,16 @@ impl<'a> FnDefAnalyser<'a> {
                 self.ty_checker().ty_check_expr_with_expected_ty(
                     span,
                     expr,
-                    ty.borrow().as_ref().unwrap()
+                    ty.borrow().as_ref().unwrap(),
+                    None
                 ) ?;
             }
             else {
                 self.ty_checker().ty_check_expr_with_expected_ty(
                     span,
                     expr,
-                    self.fn_def.return_ty.borrow().as_ref().unwrap()
+                    self.fn_def.return_ty.borrow().as_ref().unwrap(),
+                    self.fn_def.return_ty_expr.as_ref().map( | ty_expr | ty_expr.span)
                 ) ?;
             }
             
@@ -1074,6 +1105,7 @@ impl<'a> FnDefAnalyser<'a> {
                 origin: live_error_origin!(),
                 span:span.into(),
                 message: String::from("missing return expression"),
+                notes: Vec::new(),
             } .into());
         }
         Ok(())
@@ -22,6 +22,7 @@ impl ConstEvaluator {
             origin:live_error_origin!(),
             span: expr.span.into(),
             message: String::from("expression is not const"),
+            notes: Vec::new(),
         })
     }
 
@@ -77,6 +77,42 @@ pub trait BackendWriter {
     fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit);
     fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]);
     
+    // backends that bind samplers separately from textures write ", <sampler>" here,
+    // it follows every texture passed to a builtin so sample2d gets the texture's own sampler
+    fn write_texture_sampler_arg(&self, _string: &mut String, _field_ident: Ident) {
+    }
+    
+    // the C-like backends declare functions and locals the same way as variables,
+    // WGSL needs its own syntax so these can be overridden
+    fn write_fn_def_start(&self, string: &mut String, ident: &dyn fmt::Display, return_ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, return_ty);
+        write!(string, "(").unwrap();
+    }
+    
+    fn write_fn_def_end(&self, string: &mut String, _return_ty: &Ty) {
+        write!(string, ") ").unwrap();
+    }
+    
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+    
+    fn use_select_for_cond_expr(&self) -> bool {
+        false
+    }
+    
+    fn inout_params_are_pointers(&self) -> bool {
+        false
+    }
+    
+    fn assign_is_stmt(&self) -> bool {
+        false
+    }
+    
+    // WGSL params can't be assigned to, so the ones a body writes get copied into a local first
+    fn params_are_immutable(&self) -> bool {
+        false
+    }
 }
 
 pub struct BlockGenerator<'a> {
@@ -100,8 +136,8 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
     if !backend_writer.use_cons_fn(&cons_name) {
         return
     }
-    backend_writer.write_ty_lit(string, ty_lit);
-    write!(string, " {}(", cons_name).unwrap();
+    let ty = ty_lit.to_ty();
+    backend_writer.write_fn_def_start(string, &cons_name, &ty);
     
     let mut sep = "";
     if param_tys.len() == 1 {
@@ -114,11 +150,11 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
         }
     }
     
-    writeln!(string, ") {{").unwrap();
+    backend_writer.write_fn_def_end(string, &ty);
+    writeln!(string, "{{").unwrap();
     write!(string, "    return ").unwrap();
     backend_writer.write_ty_lit(string, ty_lit);
     write!(string, "(").unwrap();
-    let ty = ty_lit.to_ty();
     if param_tys.len() == 1 {
         let param_ty = &param_tys[0];
         match param_ty {
@@ -285,9 +321,11 @@ impl<'a> BlockGenerator<'a> {
         } else {
             -1
         };
+        write!(self.string, "for (").unwrap();
+        self.backend_writer.write_let_decl(&mut self.string, &ident, &Ty::Int);
         write!(
             self.string,
-            "for (int {0} = {1}; {0} {2} {3}; {0} {4} {5}) ",
+            " = {1}; {0} {2} {3}; {0} {4} {5}) ",
             ident,
             if from <= to {from} else {from - 1},
             if from <= to {"<"} else {">="},
@@ -358,11 +396,8 @@ impl<'a> BlockGenerator<'a> {
         expr: &Option<Expr>,
         shadow: &Cell<Option<ScopeSymShadow >>
     ) {
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_let_decl(
             &mut self.string,
-            "",
-            false, 
-            false,
             &DisplayVarName(ident, shadow.get().unwrap()),
             ty.borrow().as_ref().unwrap()
         );
@@ -388,10 +423,45 @@ impl<'a> BlockGenerator<'a> {
     }
     
     fn generate_expr_stmt(&mut self, _span: TokenSpan, expr: &Expr) {
+        if self.backend_writer.assign_is_stmt() && Self::is_assign_expr(expr) {
+            self.generate_assign_stmt(expr);
+            return
+        }
         self.generate_expr(expr);
         writeln!(self.string, ";").unwrap();
     }
     
+    fn is_assign_expr(expr: &Expr) -> bool {
+        match expr.kind {
+            ExprKind::Bin {op: BinOp::Assign, ..} |
+            ExprKind::Bin {op: BinOp::AddAssign, ..} |
+            ExprKind::Bin {op: BinOp::SubAssign, ..} |
+            ExprKind::Bin {op: BinOp::MulAssign, ..} |
+            ExprKind::Bin {op: BinOp::DivAssign, ..} => true,
+            _ => false
+        }
+    }
+    
+    // chained assignments (a = b = c) get split into one statement per assignment
+    fn generate_assign_stmt<'e>(&mut self, expr: &'e Expr) -> &'e Expr {
+        if let ExprKind::Bin {op, ref left_expr, ref right_expr, ..} = expr.kind {
+            let right_expr = if Self::is_assign_expr(right_expr) {
+                let right_expr = self.generate_assign_stmt(right_expr);
+                self.write_indent();
+                right_expr
+            }
+            else {
+                right_expr
+            };
+            self.generate_expr(left_expr);
+            write!(self.string, " {} ", op).unwrap();
+            self.generate_expr(right_expr);
+            writeln!(self.string, ";").unwrap();
+            return left_expr
+        }
+        panic!()
+    }
+    
     fn generate_expr(&mut self, expr: &Expr) {
         ExprGenerator {
             closure_site_info: self.closure_site_info.clone(),
@@ -566,6 +636,16 @@ impl<'a> ExprGenerator<'a> {
         expr_if_true: &Expr,
         expr_if_false: &Expr,
     ) {
+        if self.backend_writer.use_select_for_cond_expr() {
+            write!(self.string, "select(").unwrap();
+            self.generate_expr(expr_if_false);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr_if_true);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr);
+            write!(self.string, ")").unwrap();
+            return
+        }
         write!(self.string, "(").unwrap();
         self.generate_expr(expr);
         write!(self.string, " ? ").unwrap();
@@ -711,7 +791,7 @@ impl<'a> ExprGenerator<'a> {
             )).unwrap();
             
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
                 // check if the args is a closure, ifso skip it
                 match arg_expr.ty.borrow().as_ref().unwrap(){
                     Ty::ClosureDef(_)=>{
@@ -721,7 +801,7 @@ impl<'a> ExprGenerator<'a> {
                 }
                 
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_call_arg(fn_def, arg_exprs.len(), index, arg_expr);
                 sep = ", ";
             }
             // and now the closed over values
@@ -734,7 +814,7 @@ impl<'a> ExprGenerator<'a> {
                     _=>()
                 }
                 write!(self.string, "{}", sep).unwrap();
-                write!(self.string, "{}", DisplayVarName(sym.ident, sym.shadow)).unwrap();
+                self.write_var_name(sym.ident, sym.shadow);
                 sep = ", ";
             }
 
@@ -748,9 +828,9 @@ impl<'a> ExprGenerator<'a> {
         else {
             write!(self.string, "{}_{} (", fn_def.fn_ptr, fn_def.ident).unwrap();
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_call_arg(fn_def, arg_exprs.len(), index, arg_expr);
                 sep = ", ";
             }
 
@@ -760,6 +840,27 @@ impl<'a> ExprGenerator<'a> {
         }
     }
     
+    fn generate_call_arg(&mut self, fn_def: &FnDef, arg_count: usize, index: usize, arg_expr: &Expr) {
+        // draw shader methods get called without their self arg
+        let param_index = fn_def.params.len() - arg_count + index;
+        if self.backend_writer.inout_params_are_pointers() && fn_def.params[param_index].is_inout {
+            write!(self.string, "&").unwrap();
+        }
+        self.generate_expr(arg_expr);
+    }
+    
+    fn write_var_name(&mut self, ident: Ident, shadow: ScopeSymShadow) {
+        if self.backend_writer.inout_params_are_pointers() {
+            if let Some(fn_def) = self.fn_def {
+                if fn_def.params.iter().any( | param | param.is_inout && param.ident == ident && param.shadow.get() == Some(shadow)) {
+                    write!(self.string, "(*{})", DisplayVarName(ident, shadow)).unwrap();
+                    return
+                }
+            }
+        }
+        write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+    }
+    
     fn generate_field_expr(&mut self, _span: TokenSpan, expr: &Expr, field_ident: Ident, ty:&Ty) {
         match expr.ty.borrow().as_ref() {
             Some(Ty::DrawShader(_)) => {
@@ -830,6 +931,15 @@ impl<'a> ExprGenerator<'a> {
             
             self.generate_expr(arg_expr);
             
+            // textures can only be draw shader fields, so the field names the sampler
+            if let ExprKind::Field {ref expr, field_ident, ..} = arg_expr.kind {
+                if let Some(Ty::DrawShader(_)) = expr.ty.borrow().as_ref() {
+                    if let Some(Ty::Texture2D) | Some(Ty::TextureOES) = arg_expr.ty.borrow().as_ref() {
+                        self.backend_writer.write_texture_sampler_arg(&mut self.string, field_ident);
+                    }
+                }
+            }
+            
             sep = ", ";
         }
         
@@ -923,10 +1033,10 @@ impl<'a> ExprGenerator<'a> {
         // ok so we have a few varkinds
         match kind.get().unwrap() {
             VarKind::Local {ident, shadow} => {
-                write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+                self.write_var_name(ident, shadow);
             }
             VarKind::MutLocal {ident, shadow} => {
-                write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+                self.write_var_name(ident, shadow);
             }
             VarKind::LiveValue(value_node_ptr) => {
                 // this is a live value.. also prefix needed
@@ -949,6 +1059,179 @@ impl<'a> ExprGenerator<'a> {
     }
 }
 
+// the vars a body assigns to or passes on as inout args, by-value params among them get copied into a local
+fn copied_params(backend_writer: &dyn BackendWriter, shader_registry: &ShaderRegistry, fn_def: &FnDef, block: &Block) -> Vec<(Ident, ScopeSymShadow)> {
+    if !backend_writer.params_are_immutable() {
+        return Vec::new()
+    }
+    let mut collector = WrittenVarCollector {shader_registry, fn_def, written: Vec::new()};
+    collector.collect_block(block);
+    collector.written
+}
+
+// the copies go right after the opening brace of the body
+fn write_param_copies(
+    backend_writer: &dyn BackendWriter,
+    string: &mut String,
+    body_start: usize,
+    params: impl Iterator<Item = (Ident, ScopeSymShadow, bool, Ty)>,
+    copied_params: &[(Ident, ScopeSymShadow)]
+) {
+    let mut copies = String::new();
+    for (ident, shadow, is_inout, ty) in params {
+        if is_inout || !copied_params.contains(&(ident, shadow)) {
+            continue
+        }
+        write!(copies, "    ").unwrap();
+        backend_writer.write_let_decl(&mut copies, &DisplayVarName(ident, shadow), &ty);
+        write!(copies, " = {};\n", DisplayParamName(ident, shadow, true)).unwrap();
+    }
+    string.insert_str(body_start + "{\n".len(), &copies);
+}
+
+fn fn_params(fn_def: &FnDef) -> impl Iterator<Item = (Ident, ScopeSymShadow, bool, Ty)> + '_ {
+    fn_def.params.iter().filter_map( | param | {
+        Some((param.ident, param.shadow.get()?, param.is_inout, param.ty_expr.ty.borrow().clone()?))
+    })
+}
+
+/// The fn a method call ends up in, with the args it gets passed (draw shader methods drop their self arg)
+pub fn method_call_callee<'a>(shader_registry: &'a ShaderRegistry, ident: Ident, arg_exprs: &'a [Expr]) -> Option<(&'a FnDef, &'a [Expr])> {
+    match arg_exprs[0].ty.borrow().as_ref() {
+        Some(Ty::Struct(struct_ptr)) => shader_registry.struct_method_decl_from_ident(
+            shader_registry.structs.get(struct_ptr).unwrap(),
+            ident
+        ).map( | fn_def | (fn_def, arg_exprs)),
+        Some(Ty::DrawShader(shader_ptr)) => shader_registry.draw_shader_method_decl_from_ident(
+            shader_registry.draw_shader_defs.get(shader_ptr).unwrap(),
+            ident
+        ).map( | fn_def | (fn_def, &arg_exprs[1..])),
+        _ => None
+    }
+}
+
+struct WrittenVarCollector<'a> {
+    shader_registry: &'a ShaderRegistry,
+    fn_def: &'a FnDef,
+    written: Vec<(Ident, ScopeSymShadow)>,
+}
+
+impl<'a> WrittenVarCollector<'a> {
+    fn collect_block(&mut self, block: &Block) {
+        for stmt in &block.stmts {
+            match stmt {
+                Stmt::Break {..} | Stmt::Continue {..} => (),
+                Stmt::For {from_expr, to_expr, step_expr, block, ..} => {
+                    self.collect_expr(from_expr);
+                    self.collect_expr(to_expr);
+                    if let Some(step_expr) = step_expr {
+                        self.collect_expr(step_expr);
+                    }
+                    self.collect_block(block);
+                }
+                Stmt::If {expr, block_if_true, block_if_false, ..} => {
+                    self.collect_expr(expr);
+                    self.collect_block(block_if_true);
+                    if let Some(block_if_false) = block_if_false {
+                        self.collect_block(block_if_false);
+                    }
+                }
+                Stmt::Match {expr, matches, ..} => {
+                    self.collect_expr(expr);
+                    for m in matches {
+                        self.collect_block(&m.block);
+                    }
+                }
+                Stmt::Let {expr, ..} | Stmt::Return {expr, ..} => if let Some(expr) = expr {
+                    self.collect_expr(expr);
+                }
+                Stmt::Block {block, ..} => self.collect_block(block),
+                Stmt::Expr {expr, ..} => self.collect_expr(expr),
+            }
+        }
+    }
+    
+    fn collect_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => {
+                self.collect_expr(expr);
+                self.collect_expr(expr_if_true);
+                self.collect_expr(expr_if_false);
+            }
+            ExprKind::Bin {op, left_expr, right_expr, ..} => {
+                if let BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign = op {
+                    self.mark_written(left_expr);
+                }
+                self.collect_expr(left_expr);
+                self.collect_expr(right_expr);
+            }
+            ExprKind::Un {expr, ..} | ExprKind::Field {expr, ..} => self.collect_expr(expr),
+            ExprKind::Index {expr, index_expr, ..} => {
+                self.collect_expr(expr);
+                self.collect_expr(index_expr);
+            }
+            ExprKind::MethodCall {ident, arg_exprs, ..} => {
+                if let Some((callee, call_args)) = method_call_callee(self.shader_registry, *ident, arg_exprs) {
+                    self.collect_call_args(&callee.params, call_args);
+                }
+                for arg_expr in arg_exprs {
+                    self.collect_expr(arg_expr);
+                }
+            }
+            ExprKind::PlainCall {fn_ptr, param_index, arg_exprs, ..} => {
+                if let Some(param_index) = param_index.get() {
+                    if let Some(TyExprKind::ClosureDecl {params, ..}) = self.fn_def.params.get(param_index).map( | param | &param.ty_expr.kind) {
+                        self.collect_call_args(params, arg_exprs);
+                    }
+                }
+                else if let Some(callee) = fn_ptr.and_then( | fn_ptr | self.shader_registry.all_fns.get(&fn_ptr)) {
+                    self.collect_call_args(&callee.params, arg_exprs);
+                }
+                for arg_expr in arg_exprs {
+                    self.collect_expr(arg_expr);
+                }
+            }
+            ExprKind::BuiltinCall {arg_exprs, ..} | ExprKind::ConsCall {arg_exprs, ..} => {
+                for arg_expr in arg_exprs {
+                    self.collect_expr(arg_expr);
+                }
+            }
+            ExprKind::StructCons {args, ..} => {
+                for (_, arg_expr) in args {
+                    self.collect_expr(arg_expr);
+                }
+            }
+            ExprKind::ClosureDef(_) | ExprKind::Var {..} | ExprKind::Lit {..} => ()
+        }
+    }
+    
+    // draw shader methods get called without their self arg, so the args line up with the last params
+    fn collect_call_args(&mut self, params: &[Param], arg_exprs: &[Expr]) {
+        let first_param = params.len().saturating_sub(arg_exprs.len());
+        for (param, arg_expr) in params[first_param..].iter().zip(arg_exprs) {
+            if param.is_inout {
+                self.mark_written(arg_expr);
+            }
+        }
+    }
+    
+    fn mark_written(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Field {expr, ..} | ExprKind::Index {expr, ..} => self.mark_written(expr),
+            ExprKind::Var {kind, ..} => if let Some(VarKind::Local {ident, shadow} | VarKind::MutLocal {ident, shadow}) = kind.get() {
+                // writing through a draw shader self goes to its fields, not to a param
+                if let Some(Ty::DrawShader(_)) = expr.ty.borrow().as_ref() {
+                    return
+                }
+                if !self.written.contains(&(ident, shadow)) {
+                    self.written.push((ident, shadow));
+                }
+            }
+            _ => ()
+        }
+    }
+}
+
 pub struct FnDefGenerator<'a> {
     pub fn_def: &'a FnDef,
     pub shader_registry: &'a ShaderRegistry,
@@ -960,24 +1243,22 @@ pub struct FnDefGenerator<'a> {
 impl<'a> FnDefGenerator<'a> {
     pub fn generate_fn_def(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_fn_def_start(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnName(self.fn_def.fn_ptr, self.fn_def.ident), // here we must expand IdentPath to something
             self.fn_def.return_ty.borrow().as_ref().unwrap()
         );
-        write!(self.string, "(").unwrap();
+        let copied_params = copied_params(self.backend_writer, self.shader_registry, self.fn_def, &self.fn_def.block);
         let mut sep = "";
         for param in &self.fn_def.params {
             if !param.shadow.get().is_none() {
+                let shadow = param.shadow.get().unwrap();
                 if self.backend_writer.write_var_decl(
                     &mut self.string,
                     sep,
                     param.is_inout,
                     false,
-                    &DisplayVarName(param.ident, param.shadow.get().unwrap()),
+                    &DisplayParamName(param.ident, shadow, !param.is_inout && copied_params.contains(&(param.ident, shadow))),
                     param.ty_expr.ty.borrow().as_ref().unwrap(),
                 ) {
                     sep = ", ";
@@ -985,8 +1266,10 @@ impl<'a> FnDefGenerator<'a> {
             }
         }
         self.backend_writer.write_fn_def_hidden_params(self.string, self.fn_def.hidden_args.borrow().as_ref().unwrap(), sep);
-        write!(self.string, ") ").unwrap();
+        self.backend_writer.write_fn_def_end(self.string, self.fn_def.return_ty.borrow().as_ref().unwrap());
+        let body_start = self.string.len();
         self.generate_block(&self.fn_def.block);
+        write_param_copies(self.backend_writer, self.string, body_start, fn_params(self.fn_def), &copied_params);
         writeln!(self.string).unwrap();
         //self.visited.insert(self.decl.ident_path);
     }
@@ -1077,11 +1360,8 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
     
     pub fn generate_fn_def_with_closure_args(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_fn_def_start(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnNameWithClosureArgs(
                 self.closure_site_info.site_index,
                 self.call_def.fn_ptr,
@@ -1089,16 +1369,17 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
             ), // here we must expand IdentPath to something
             self.fn_def.return_ty.borrow().as_ref().unwrap()
         );
-        write!(self.string, "(").unwrap();
+        let copied_params = copied_params(self.backend_writer, self.shader_registry, self.fn_def, &self.fn_def.block);
         let mut sep = "";
         for param in &self.fn_def.params {
             if !param.shadow.get().is_none() {
+                let shadow = param.shadow.get().unwrap();
                 if self.backend_writer.write_var_decl(
                     &mut self.string,
                     sep,
                     param.is_inout,
                     false,
-                    &DisplayVarName(param.ident, param.shadow.get().unwrap()),
+                    &DisplayParamName(param.ident, shadow, !param.is_inout && copied_params.contains(&(param.ident, shadow))),
                     param.ty_expr.ty.borrow().as_ref().unwrap(),
                 ) {
                     sep = ", ";
@@ -1125,10 +1406,12 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        write!(self.string, ") ").unwrap();
+        self.backend_writer.write_fn_def_end(self.string, self.fn_def.return_ty.borrow().as_ref().unwrap());
         // alright so here the block is generated.. however
         // we need to know the names and the closed-over-args passthrough
+        let body_start = self.string.len();
         self.generate_block(&self.fn_def.block);
+        write_param_copies(self.backend_writer, self.string, body_start, fn_params(self.fn_def), &copied_params);
         
         
         writeln!(self.string).unwrap();
@@ -1167,18 +1450,19 @@ impl<'a> ClosureDefGenerator<'a> {
         let fn_param = &self.fn_def.params[self.closure_site_arg.param_index];
         
         let mut sep = "";
+        let copied_params = match &self.closure_def.kind {
+            // the closure body was written in the calling fn, so its calls resolve there
+            ClosureDefKind::Block(block) => copied_params(self.backend_writer, self.shader_registry, self.call_def, block),
+            ClosureDefKind::Expr(_) => Vec::new()
+        };
         
-        if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
+        let closure_return_ty = if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
             
-            self.backend_writer.write_var_decl(
+            self.backend_writer.write_fn_def_start(
                 &mut self.string,
-                "",
-                false,
-                false,
                 &DisplayClosureName(self.call_def.fn_ptr, self.closure_site_arg.closure_def_index), // here we must expand IdentPath to something
                 return_ty.borrow().as_ref().unwrap(),
             );
-            write!(self.string, "(").unwrap();
             
             // ok we have now params and names
             for (param_index, param) in params.iter().enumerate() {
@@ -1190,16 +1474,17 @@ impl<'a> ClosureDefGenerator<'a> {
                     sep,
                     param.is_inout,
                     false,
-                    &DisplayVarName(closure_param.ident, shadow),
+                    &DisplayParamName(closure_param.ident, shadow, !param.is_inout && copied_params.contains(&(closure_param.ident, shadow))),
                     param.ty_expr.ty.borrow().as_ref().unwrap(),
                 ) {
                     sep = ", ";
                 }
             }
+            return_ty.borrow().clone().unwrap()
         }
         else {
             panic!()
-        }
+        };
         
         for sym in self.closure_def.closed_over_syms.borrow().as_ref().unwrap() {
             if self.backend_writer.write_var_decl(
@@ -1219,7 +1504,8 @@ impl<'a> ClosureDefGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        writeln!(self.string, ") {{").unwrap();
+        self.backend_writer.write_fn_def_end(self.string, &closure_return_ty);
+        writeln!(self.string, "{{").unwrap();
         
         match &self.closure_def.kind {
             ClosureDefKind::Expr(expr) => {
@@ -1229,7 +1515,14 @@ impl<'a> ClosureDefGenerator<'a> {
                 writeln!(self.string, "}}").unwrap();
             }
             ClosureDefKind::Block(block) => {
+                let body_start = self.string.len();
                 self.generate_block(block);
+                if let TyExprKind::ClosureDecl {params, ..} = &fn_param.ty_expr.kind {
+                    let params = params.iter().zip(&self.closure_def.params).filter_map( | (param, closure_param) | {
+                        Some((closure_param.ident, closure_param.shadow.get()?, param.is_inout, param.ty_expr.ty.borrow().clone()?))
+                    });
+                    write_param_copies(self.backend_writer, self.string, body_start, params, &copied_params);
+                }
                 writeln!(self.string).unwrap();
             }
         }
@@ -1321,6 +1614,20 @@ impl fmt::Display for DisplayVarName {
     }
 }
 
+// a param that gets copied into a local is renamed so the local can keep the usual var name
+pub struct DisplayParamName(pub Ident, pub ScopeSymShadow, pub bool);
+impl fmt::Display for DisplayParamName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.2 {
+            write!(f, "param_{}_{}", self.0, self.1.0);
+        }
+        else {
+            write!(f, "var_{}_{}", self.0, self.1.0);
+        }
+        fmt::Result::Ok(())
+    }
+}
+
 pub struct DisplayClosedOverArg(pub Ident, pub ScopeSymShadow);
 impl fmt::Display for DisplayClosedOverArg {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -50,20 +50,15 @@ impl<'a> DrawShaderGenerator<'a> {
             all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
         }
 
-        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
-            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d))) {
-                writeln!(self.string, "SamplerState default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
-                writeln!(self.string, "float4 sample2d(Texture2D tex, float2 pos){{return tex.Sample(default_texture_sampler,pos);}}").unwrap();
-                break;
-            }
-
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d_rt))) {
-                writeln!(self.string, "SamplerState default_texture_sampler{{Filter=MIN_MAX_MIP_LINEAR;AddressU = Wrap;AddressV=Wrap;}};").unwrap();
-                writeln!(self.string, "float4 sample2d_rt(Texture2D tex, float2 pos){{return tex.Sample(default_texture_sampler,pos);}}").unwrap();
-                break;
-            }
-        };
+        let uses_builtin = | ident | self.draw_shader_def.all_fns.borrow().iter().any( | fn_iter | {
+            self.shader_registry.all_fns.get(fn_iter).unwrap().builtin_deps.borrow().as_ref().unwrap().contains(&Ident(ident))
+        });
+        if uses_builtin(live_id!(sample2d)) {
+            writeln!(self.string, "float4 sample2d(Texture2D tex, SamplerState smp, float2 pos){{return tex.Sample(smp,pos);}}").unwrap();
+        }
+        if uses_builtin(live_id!(sample2d_rt)) {
+            writeln!(self.string, "float4 sample2d_rt(Texture2D tex, SamplerState smp, float2 pos){{return tex.Sample(smp,pos);}}").unwrap();
+        }
         
         self.generate_struct_decls();
         let fields_as_uniform_blocks = self.draw_shader_def.fields_as_uniform_blocks();
@@ -196,6 +191,7 @@ impl<'a> DrawShaderGenerator<'a> {
                     assert_ne!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::TextureOES, "TextureOES is only available on Android");
                     assert_eq!(*field.ty_expr.ty.borrow().as_ref().unwrap(), Ty::Texture2D);
                     write!(self.string, "Texture2D {}: register(t{});", DisplayDsIdent(field.ident), index).unwrap();
+                    write!(self.string, "SamplerState {}_sampler: register(s{});", DisplayDsIdent(field.ident), index).unwrap();
                     index += 1;
                 }
                 _ => {}
@@ -808,6 +804,10 @@ impl<'a> BackendWriter for HlslBackendWriter<'a> {
     }
     
     
+    fn write_texture_sampler_arg(&self, string: &mut String, field_ident: Ident) {
+        write!(string, ", {}_sampler", DisplayDsIdent(field_ident)).unwrap();
+    }
+    
     fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
         match ident {
             Ident(live_id!(atan)) => {
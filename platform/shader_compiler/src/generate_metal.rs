@@ -57,17 +57,15 @@ impl<'a> DrawShaderGenerator<'a> {
             all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
         }
         
-        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
-            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d))) {
-                writeln!(self.string, "float4 sample2d(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}}").unwrap();
-                break;
-            }
-            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(live_id!(sample2d_rt))) {
-                writeln!(self.string, "float4 sample2d_rt(texture2d<float> tex, float2 pos){{return tex.sample(sampler(mag_filter::linear,min_filter::linear),pos);}}").unwrap();
-                break;
-            }
-        };
+        let uses_builtin = | ident | self.draw_shader_def.all_fns.borrow().iter().any( | fn_iter | {
+            self.shader_registry.all_fns.get(fn_iter).unwrap().builtin_deps.borrow().as_ref().unwrap().contains(&Ident(ident))
+        });
+        if uses_builtin(live_id!(sample2d)) {
+            writeln!(self.string, "float4 sample2d(texture2d<float> tex, sampler smp, float2 pos){{return tex.sample(smp,pos);}}").unwrap();
+        }
+        if uses_builtin(live_id!(sample2d_rt)) {
+            writeln!(self.string, "float4 sample2d_rt(texture2d<float> tex, sampler smp, float2 pos){{return tex.sample(smp,pos);}}").unwrap();
+        }
         
         self.generate_struct_defs();
         //let fields_as_uniform_blocks = self.draw_shader_def.fields_as_uniform_blocks();
@@ -171,6 +169,7 @@ impl<'a> DrawShaderGenerator<'a> {
                     write!(self.string, "    texture2d<float> ").unwrap();
                     write!(self.string, "{}", &DisplayDsIdent(field.ident)).unwrap();
                     write!(self.string, " [[texture({})]];", index).unwrap();
+                    write!(self.string, " sampler {}_sampler [[sampler({})]];", &DisplayDsIdent(field.ident), index).unwrap();
                     index += 1;
                 }
                 _ => {}
@@ -798,6 +797,10 @@ impl<'a> BackendWriter for MetalBackendWriter<'a> {
             .unwrap();
     }
     
+    fn write_texture_sampler_arg(&self, string: &mut String, field_ident: Ident) {
+        write!(string, ", textures.{}_sampler", &DisplayDsIdent(field_ident)).unwrap();
+    }
+    
     fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
         match ident {
             Ident(live_id!(atan)) => {
@@ -0,0 +1,955 @@
+use {
+    std::{
+        fmt,
+        fmt::Write,
+        collections::{BTreeMap, BTreeSet},
+    },
+    crate::{
+        makepad_live_id::{
+            live_id,
+            LiveId,
+        },
+        makepad_live_compiler::TokenSpan,
+        generate::*,
+        shader_ast::*,
+        shader_registry::ShaderRegistry
+    }
+};
+
+// Binding layout of the generated module:
+// group 0: const_table, live_table and one table per uniform block (in fields_as_uniform_blocks order)
+// group 1: the textures in field order, followed by one sampler per texture in the same order
+pub struct WgslGeneratedShader {
+    pub wgsl: String,
+    pub fields_as_uniform_blocks: BTreeMap<Ident, Vec<(usize, Ident) >>
+}
+
+pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> WgslGeneratedShader {
+    let mut string = String::new();
+    let fields_as_uniform_blocks = draw_shader_def.fields_as_uniform_blocks();
+    let uniform_samples = uniform_samples(draw_shader_def, shader_registry);
+    DrawShaderGenerator {
+        draw_shader_def,
+        shader_registry,
+        const_table,
+        string: &mut string,
+        fields_as_uniform_blocks: &fields_as_uniform_blocks,
+        backend_writer: &WgslBackendWriter {shader_registry, const_table, uniform_samples: &uniform_samples}
+    }
+    .generate_shader();
+    WgslGeneratedShader {
+        wgsl: string,
+        fields_as_uniform_blocks
+    }
+}
+
+// textureSample picks the mip level from derivatives, so WGSL only allows it in uniform control flow
+// of the fragment stage. This finds the sample calls that are certainly there: at the top level of
+// pixel-only fns that are themselves only called from the top level of such fns. Any branch, loop,
+// short circuit or earlier conditional exit counts as non-uniform, those samples use level 0.
+fn uniform_samples(draw_shader_def: &DrawShaderDef, shader_registry: &ShaderRegistry) -> BTreeSet<TokenSpan> {
+    let vertex_fns = draw_shader_def.vertex_fns.borrow();
+    let all_fns = draw_shader_def.all_fns.borrow();
+    let mut uniform_fns: BTreeSet<FnPtr> = draw_shader_def.pixel_fns.borrow().iter().filter( | fn_ptr | {
+        !vertex_fns.contains(fn_ptr) && !shader_registry.all_fns.get(fn_ptr).unwrap().has_closure_args()
+    }).cloned().collect();
+    let mut calls = Vec::new();
+    let mut samples = Vec::new();
+    for fn_ptr in all_fns.iter() {
+        let fn_def = shader_registry.all_fns.get(fn_ptr).unwrap();
+        let mut collector = UniformityCollector {shader_registry, caller: *fn_ptr, calls: &mut calls, samples: &mut samples};
+        collector.collect_block(&fn_def.block, true);
+    }
+    // a fn stays uniform only while every call to it is uniform and made from a uniform fn
+    loop {
+        let demoted: Vec<FnPtr> = calls.iter().filter( | (caller, callee, uniform) | {
+            uniform_fns.contains(callee) && (!uniform || !uniform_fns.contains(caller))
+        }).map( | (_, callee, _) | *callee).collect();
+        if demoted.is_empty() {
+            break
+        }
+        for callee in demoted {
+            uniform_fns.remove(&callee);
+        }
+    }
+    samples.iter().filter( | (caller, _, uniform) | *uniform && uniform_fns.contains(caller)).map( | (_, span, _) | *span).collect()
+}
+
+struct UniformityCollector<'a> {
+    shader_registry: &'a ShaderRegistry,
+    caller: FnPtr,
+    // (caller, callee, is in uniform control flow)
+    calls: &'a mut Vec<(FnPtr, FnPtr, bool)>,
+    // (caller, span of the texture arg, is in uniform control flow)
+    samples: &'a mut Vec<(FnPtr, TokenSpan, bool)>,
+}
+
+impl<'a> UniformityCollector<'a> {
+    // returns whether the block may leave early, which makes everything after it non-uniform
+    fn collect_block(&mut self, block: &Block, mut uniform: bool) -> bool {
+        let mut may_exit = false;
+        for stmt in &block.stmts {
+            let stmt_exits = match stmt {
+                Stmt::Break {..} | Stmt::Continue {..} => true,
+                Stmt::Return {expr, ..} => {
+                    if let Some(expr) = expr {
+                        self.collect_expr(expr, uniform);
+                    }
+                    true
+                }
+                Stmt::For {from_expr, to_expr, step_expr, block, ..} => {
+                    self.collect_expr(from_expr, uniform);
+                    self.collect_expr(to_expr, uniform);
+                    if let Some(step_expr) = step_expr {
+                        self.collect_expr(step_expr, uniform);
+                    }
+                    self.collect_block(block, false)
+                }
+                Stmt::If {expr, block_if_true, block_if_false, ..} => {
+                    self.collect_expr(expr, uniform);
+                    let mut exits = self.collect_block(block_if_true, false);
+                    if let Some(block_if_false) = block_if_false {
+                        exits |= self.collect_block(block_if_false, false);
+                    }
+                    exits
+                }
+                Stmt::Match {expr, matches, ..} => {
+                    self.collect_expr(expr, uniform);
+                    let mut exits = false;
+                    for m in matches {
+                        exits |= self.collect_block(&m.block, false);
+                    }
+                    exits
+                }
+                Stmt::Let {expr, ..} => {
+                    if let Some(expr) = expr {
+                        self.collect_expr(expr, uniform);
+                    }
+                    false
+                }
+                Stmt::Block {block, ..} => self.collect_block(block, uniform),
+                Stmt::Expr {expr, ..} => {
+                    self.collect_expr(expr, uniform);
+                    false
+                }
+            };
+            if stmt_exits {
+                may_exit = true;
+                uniform = false;
+            }
+        }
+        may_exit
+    }
+
+    fn collect_expr(&mut self, expr: &Expr, uniform: bool) {
+        match &expr.kind {
+            // conditional expressions become a select that evaluates both sides
+            ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => {
+                self.collect_expr(expr, uniform);
+                self.collect_expr(expr_if_true, uniform);
+                self.collect_expr(expr_if_false, uniform);
+            }
+            ExprKind::Bin {op, left_expr, right_expr, ..} => {
+                self.collect_expr(left_expr, uniform);
+                let short_circuits = matches!(op, BinOp::Or | BinOp::And);
+                self.collect_expr(right_expr, uniform && !short_circuits);
+            }
+            ExprKind::Un {expr, ..} | ExprKind::Field {expr, ..} => self.collect_expr(expr, uniform),
+            ExprKind::Index {expr, index_expr, ..} => {
+                self.collect_expr(expr, uniform);
+                self.collect_expr(index_expr, uniform);
+            }
+            ExprKind::MethodCall {ident, arg_exprs, ..} => {
+                if let Some((callee, _)) = method_call_callee(self.shader_registry, *ident, arg_exprs) {
+                    self.calls.push((self.caller, callee.fn_ptr, uniform));
+                }
+                for arg_expr in arg_exprs {
+                    self.collect_expr(arg_expr, uniform);
+                }
+            }
+            ExprKind::PlainCall {fn_ptr, arg_exprs, ..} => {
+                if let Some(fn_ptr) = fn_ptr {
+                    self.calls.push((self.caller, *fn_ptr, uniform));
+                }
+                for arg_expr in arg_exprs {
+                    self.collect_expr(arg_expr, uniform);
+                }
+            }
+            ExprKind::BuiltinCall {ident, arg_exprs, ..} => {
+                if let Ident(live_id!(sample2d)) | Ident(live_id!(sample2d_rt)) | Ident(live_id!(sample2dOES)) = ident {
+                    self.samples.push((self.caller, arg_exprs[0].span, uniform));
+                }
+                for arg_expr in arg_exprs {
+                    self.collect_expr(arg_expr, uniform);
+                }
+            }
+            ExprKind::ConsCall {arg_exprs, ..} => {
+                for arg_expr in arg_exprs {
+                    self.collect_expr(arg_expr, uniform);
+                }
+            }
+            ExprKind::StructCons {args, ..} => {
+                for (_, arg_expr) in args {
+                    self.collect_expr(arg_expr, uniform);
+                }
+            }
+            ExprKind::ClosureDef(_) | ExprKind::Var {..} | ExprKind::Lit {..} => ()
+        }
+    }
+}
+
+struct DrawShaderGenerator<'a> {
+    draw_shader_def: &'a DrawShaderDef,
+    shader_registry: &'a ShaderRegistry,
+    string: &'a mut String,
+    const_table: &'a DrawShaderConstTable,
+    fields_as_uniform_blocks: &'a BTreeMap<Ident, Vec<(usize, Ident) >>,
+    backend_writer: &'a dyn BackendWriter
+}
+
+impl<'a> DrawShaderGenerator<'a> {
+    fn generate_shader(&mut self) {
+        let packed_geometries_slots = self.compute_packed_geometries_slots();
+        let packed_instances_slots = self.compute_packed_instances_slots();
+        let packed_varyings_slots = self.compute_packed_varyings_slots();
+
+        self.generate_bindings();
+        self.generate_builtin_helpers();
+        self.generate_private_decls();
+
+        let mut all_constructor_fns = BTreeSet::new();
+        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
+        }
+
+        for struct_ptr in self.draw_shader_def.all_structs.borrow().iter().rev() {
+            let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+            self.generate_struct_def(*struct_ptr, struct_def);
+        }
+
+        for (ty_lit, param_tys) in all_constructor_fns {
+            generate_cons_fn(self.backend_writer, self.string, ty_lit, &param_tys);
+        }
+        writeln!(self.string).unwrap();
+
+        let all_fns = self.draw_shader_def.all_fns.borrow();
+        for fn_iter in all_fns.iter().rev() {
+            let const_table_offset = self.const_table.offsets.get(fn_iter).cloned();
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            if fn_def.has_closure_args() {
+                for call_iter in all_fns.iter().rev() {
+                    // any function that depends on us, will have the closures we need
+                    let call_def = self.shader_registry.all_fns.get(call_iter).unwrap();
+                    if call_def.callees.borrow().as_ref().unwrap().contains(fn_iter) {
+                        FnDefWithClosureArgsGenerator::generate_fn_def_with_all_closures(
+                            self.string,
+                            self.shader_registry,
+                            fn_def,
+                            call_def,
+                            self.backend_writer,
+                            const_table_offset
+                        );
+                    }
+                }
+                continue
+            }
+            FnDefGenerator {
+                fn_def,
+                const_table_offset,
+                shader_registry: self.shader_registry,
+                backend_writer: self.backend_writer,
+                string: self.string,
+            }
+            .generate_fn_def();
+            writeln!(self.string).unwrap();
+        }
+
+        self.generate_io_structs(packed_geometries_slots, packed_instances_slots, packed_varyings_slots);
+        self.generate_vertex_main(packed_geometries_slots, packed_instances_slots, packed_varyings_slots);
+        self.generate_pixel_main(packed_varyings_slots);
+    }
+
+    fn generate_bindings(&mut self) {
+        // uniform buffers need a 16 byte array stride, so all tables are vec4 arrays
+        if !self.const_table.table.is_empty() {
+            writeln!(
+                self.string,
+                "@group(0) @binding(0) var<uniform> const_table: array<vec4<f32>, {}>;",
+                vec4_count(self.const_table.table.len())
+            ).unwrap();
+        }
+        let live_slots = self.calc_live_slots();
+        if live_slots > 0 {
+            writeln!(
+                self.string,
+                "@group(0) @binding(1) var<uniform> live_table: array<vec4<f32>, {}>;",
+                vec4_count(live_slots)
+            ).unwrap();
+        }
+        for (index, (ident, vec)) in self.fields_as_uniform_blocks.iter().enumerate() {
+            let mut slots = 0;
+            for (field_index, _item) in vec {
+                let field = &self.draw_shader_def.fields[*field_index];
+                slots += field.ty_expr.ty.borrow().as_ref().unwrap().slots();
+            }
+            writeln!(
+                self.string,
+                "@group(0) @binding({}) var<uniform> {}_table: array<vec4<f32>, {}>;",
+                index + 2,
+                ident,
+                vec4_count(slots)
+            ).unwrap();
+        }
+
+        let mut texture_index = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Texture {..} = field.kind {
+                write!(self.string, "@group(1) @binding({}) var ", texture_index).unwrap();
+                self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ";").unwrap();
+                texture_index += 1;
+            }
+        }
+        let mut sampler_index = texture_index;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Texture {..} = field.kind {
+                writeln!(self.string, "@group(1) @binding({}) var {}_sampler: sampler;", sampler_index, DisplayDsIdent(field.ident)).unwrap();
+                sampler_index += 1;
+            }
+        }
+        writeln!(self.string).unwrap();
+    }
+
+    fn uses_builtin(&self, ident: LiveId) -> bool {
+        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            if fn_def.builtin_deps.borrow().as_ref().unwrap().contains(&Ident(ident)) {
+                return true
+            }
+        }
+        false
+    }
+
+    fn generate_builtin_helpers(&mut self) {
+        // WGSL has no user overloading, so the GLSL builtins without a direct equivalent
+        // are emitted as helpers with the argument types suffixed to the name
+        // the _level variants sample mip 0, for the vertex stage and non-uniform control flow
+        if self.uses_builtin(live_id!(sample2d)) || self.uses_builtin(live_id!(sample2dOES)) {
+            writeln!(self.string, "fn sample2d(tex: texture_2d<f32>, smp: sampler, pos: vec2<f32>) -> vec4<f32> {{return textureSample(tex, smp, pos);}}").unwrap();
+            writeln!(self.string, "fn sample2d_level(tex: texture_2d<f32>, smp: sampler, pos: vec2<f32>) -> vec4<f32> {{return textureSampleLevel(tex, smp, pos, 0.0);}}").unwrap();
+        }
+        if self.uses_builtin(live_id!(sample2d_rt)) {
+            writeln!(self.string, "fn sample2d_rt(tex: texture_2d<f32>, smp: sampler, pos: vec2<f32>) -> vec4<f32> {{return textureSample(tex, smp, pos);}}").unwrap();
+            writeln!(self.string, "fn sample2d_rt_level(tex: texture_2d<f32>, smp: sampler, pos: vec2<f32>) -> vec4<f32> {{return textureSampleLevel(tex, smp, pos, 0.0);}}").unwrap();
+        }
+        if self.uses_builtin(live_id!(mod)) {
+            for (x, y) in [("float", "float"), ("vec2", "vec2"), ("vec3", "vec3"), ("vec4", "vec4"), ("vec2", "float"), ("vec3", "float"), ("vec4", "float")] {
+                writeln!(
+                    self.string,
+                    "fn mod_{0}_{1}(x: {2}, y: {3}) -> {2} {{return x - y * floor(x / y);}}",
+                    x,
+                    y,
+                    wgsl_ty_name(x),
+                    wgsl_ty_name(y)
+                ).unwrap();
+            }
+        }
+        for ty in ["vec2", "vec3", "vec4"] {
+            let wty = wgsl_ty_name(ty);
+            if self.uses_builtin(live_id!(clamp)) {
+                writeln!(self.string, "fn clamp_{0}_float_float(x: {1}, a: f32, b: f32) -> {1} {{return clamp(x, {1}(a), {1}(b));}}", ty, wty).unwrap();
+            }
+            if self.uses_builtin(live_id!(min)) {
+                writeln!(self.string, "fn min_{0}_float(x: {1}, y: f32) -> {1} {{return min(x, {1}(y));}}", ty, wty).unwrap();
+            }
+            if self.uses_builtin(live_id!(max)) {
+                writeln!(self.string, "fn max_{0}_float(x: {1}, y: f32) -> {1} {{return max(x, {1}(y));}}", ty, wty).unwrap();
+            }
+            if self.uses_builtin(live_id!(step)) {
+                writeln!(self.string, "fn step_float_{0}(e: f32, x: {1}) -> {1} {{return step({1}(e), x);}}", ty, wty).unwrap();
+            }
+            if self.uses_builtin(live_id!(smoothstep)) {
+                writeln!(self.string, "fn smoothstep_float_float_{0}(a: f32, b: f32, x: {1}) -> {1} {{return smoothstep({1}(a), {1}(b), x);}}", ty, wty).unwrap();
+            }
+        }
+        for (ident, op) in [
+            (live_id!(lessThan), "<"),
+            (live_id!(lessThanEqual), "<="),
+            (live_id!(greaterThan), ">"),
+            (live_id!(greaterThanEqual), ">="),
+            (live_id!(equal), "=="),
+            (live_id!(notEqual), "!="),
+        ] {
+            if self.uses_builtin(ident) {
+                for (ty, bty) in [("vec2", "bvec2"), ("vec3", "bvec3"), ("vec4", "bvec4"), ("ivec2", "bvec2"), ("ivec3", "bvec3"), ("ivec4", "bvec4")] {
+                    writeln!(
+                        self.string,
+                        "fn {0}_{1}(x: {2}, y: {2}) -> {3} {{return x {4} y;}}",
+                        ident,
+                        ty,
+                        wgsl_ty_name(ty),
+                        wgsl_ty_name(bty),
+                        op
+                    ).unwrap();
+                }
+            }
+        }
+        if self.uses_builtin(live_id!(not)) {
+            for ty in ["bvec2", "bvec3", "bvec4"] {
+                writeln!(self.string, "fn not_{0}(x: {1}) -> {1} {{return !x;}}", ty, wgsl_ty_name(ty)).unwrap();
+            }
+        }
+        if self.uses_builtin(live_id!(matrixCompMult)) {
+            writeln!(self.string, "fn matrixCompMult_mat2(x: mat2x2<f32>, y: mat2x2<f32>) -> mat2x2<f32> {{return mat2x2<f32>(x[0] * y[0], x[1] * y[1]);}}").unwrap();
+            writeln!(self.string, "fn matrixCompMult_mat3(x: mat3x3<f32>, y: mat3x3<f32>) -> mat3x3<f32> {{return mat3x3<f32>(x[0] * y[0], x[1] * y[1], x[2] * y[2]);}}").unwrap();
+            writeln!(self.string, "fn matrixCompMult_mat4(x: mat4x4<f32>, y: mat4x4<f32>) -> mat4x4<f32> {{return mat4x4<f32>(x[0] * y[0], x[1] * y[1], x[2] * y[2], x[3] * y[3]);}}").unwrap();
+        }
+        writeln!(self.string).unwrap();
+    }
+
+    fn generate_private_decls(&mut self) {
+        // like the GLSL backend all fields are module level variables, filled in by the entrypoints
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            write!(self.string, "var<private> ").unwrap();
+            self.write_var_decl(live_ref, ty);
+            writeln!(self.string, ";").unwrap();
+        }
+        for field in &self.draw_shader_def.fields {
+            match field.kind {
+                DrawShaderFieldKind::Geometry {..} |
+                DrawShaderFieldKind::Instance {..} |
+                DrawShaderFieldKind::Varying {..} |
+                DrawShaderFieldKind::Uniform {..} => {
+                    write!(self.string, "var<private> ").unwrap();
+                    self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                    writeln!(self.string, ";").unwrap();
+                }
+                _ => ()
+            }
+        }
+        writeln!(self.string).unwrap();
+    }
+
+    fn generate_struct_def(&mut self, struct_ptr: StructPtr, struct_def: &StructDef) {
+        writeln!(self.string, "struct {} {{", struct_ptr).unwrap();
+        for field in &struct_def.fields {
+            write!(self.string, "    ").unwrap();
+            self.write_var_decl(&DisplayStructField(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+            writeln!(self.string, ",").unwrap();
+        }
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_io_structs(&mut self, packed_geometries_slots: usize, packed_instances_slots: usize, packed_varyings_slots: usize) {
+        let mut location = 0;
+        if packed_geometries_slots + packed_instances_slots > 0 {
+            writeln!(self.string, "struct VertexInput {{").unwrap();
+            location = self.generate_packed_var_decls("packed_geometry", packed_geometries_slots, location);
+            self.generate_packed_var_decls("packed_instance", packed_instances_slots, location);
+            writeln!(self.string, "}}").unwrap();
+        }
+        writeln!(self.string, "struct Varyings {{").unwrap();
+        writeln!(self.string, "    @builtin(position) position: vec4<f32>,").unwrap();
+        self.generate_packed_var_decls("packed_varying", packed_varyings_slots, 0);
+        writeln!(self.string, "}}").unwrap();
+        writeln!(self.string).unwrap();
+    }
+
+    fn generate_packed_var_decls(&mut self, packed_var_name: &str, mut packed_vars_size: usize, mut location: usize) -> usize {
+        let mut packed_var_index = 0;
+        while packed_vars_size > 0 {
+            let packed_var_size = packed_vars_size.min(4);
+            writeln!(
+                self.string,
+                "    @location({}) {}_{}: {},",
+                location,
+                packed_var_name,
+                packed_var_index,
+                match packed_var_size {
+                    1 => "f32",
+                    2 => "vec2<f32>",
+                    3 => "vec3<f32>",
+                    _ => "vec4<f32>",
+                }
+            ).unwrap();
+            packed_vars_size -= packed_var_size;
+            packed_var_index += 1;
+            location += 1;
+        }
+        location
+    }
+
+    fn generate_vertex_main(&mut self, packed_geometries_slots: usize, packed_instances_slots: usize, packed_varyings_slots: usize) {
+        writeln!(self.string, "@vertex").unwrap();
+        if packed_geometries_slots + packed_instances_slots > 0 {
+            writeln!(self.string, "fn vertex_main(input: VertexInput) -> Varyings {{").unwrap();
+        }
+        else {
+            writeln!(self.string, "fn vertex_main() -> Varyings {{").unwrap();
+        }
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+
+        let mut slot = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Geometry {..} = field.kind {
+                slot = self.unpack_var(field.ident, field.ty_expr.ty.borrow().as_ref().unwrap(), "input.packed_geometry", packed_geometries_slots, slot);
+            }
+        }
+        let mut slot = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Instance {..} = field.kind {
+                slot = self.unpack_var(field.ident, field.ty_expr.ty.borrow().as_ref().unwrap(), "input.packed_instance", packed_instances_slots, slot);
+            }
+        }
+        writeln!(self.string).unwrap();
+
+        let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
+        writeln!(self.string, "    var varyings: Varyings;").unwrap();
+        writeln!(self.string, "    varyings.position = {}();", DisplayFnName(vertex_def.fn_ptr, vertex_def.ident)).unwrap();
+
+        let mut slot = 0;
+        for field in &self.draw_shader_def.fields {
+            match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    slot = self.pack_var(field.ident, field.ty_expr.ty.borrow().as_ref().unwrap(), "varyings.packed_varying", packed_varyings_slots, slot);
+                }
+                DrawShaderFieldKind::Varying {..} => {
+                    slot = self.pack_var(field.ident, field.ty_expr.ty.borrow().as_ref().unwrap(), "varyings.packed_varying", packed_varyings_slots, slot);
+                }
+                _ => ()
+            }
+        }
+        writeln!(self.string, "    return varyings;").unwrap();
+        writeln!(self.string, "}}").unwrap();
+        writeln!(self.string).unwrap();
+    }
+
+    fn generate_pixel_main(&mut self, packed_varyings_slots: usize) {
+        writeln!(self.string, "@fragment").unwrap();
+        writeln!(self.string, "fn pixel_main(varyings: Varyings) -> @location(0) vec4<f32> {{").unwrap();
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+
+        let mut slot = 0;
+        for field in &self.draw_shader_def.fields {
+            match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    slot = self.unpack_var(field.ident, field.ty_expr.ty.borrow().as_ref().unwrap(), "varyings.packed_varying", packed_varyings_slots, slot);
+                }
+                DrawShaderFieldKind::Varying {..} => {
+                    slot = self.unpack_var(field.ident, field.ty_expr.ty.borrow().as_ref().unwrap(), "varyings.packed_varying", packed_varyings_slots, slot);
+                }
+                _ => ()
+            }
+        }
+        writeln!(self.string).unwrap();
+
+        let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
+        writeln!(self.string, "    return {}();", DisplayFnName(pixel_def.fn_ptr, pixel_def.ident)).unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_uniform_block_unpack(&mut self) {
+        for (ident, vec) in self.fields_as_uniform_blocks {
+            let table = format!("{}_table", ident);
+            let mut slots = 0;
+            for (index, _item) in vec {
+                let field = &self.draw_shader_def.fields[*index];
+                let ty = field.ty_expr.ty.borrow();
+                let ty = ty.as_ref().unwrap();
+                for slot in 0..ty.slots() {
+                    write!(self.string, "    ").unwrap();
+                    write_var_slot(self.string, &DisplayDsIdent(field.ident), ty, slot);
+                    write!(self.string, " = ").unwrap();
+                    write_table_slot(self.string, &table, ty, slots + slot);
+                    writeln!(self.string, ";").unwrap();
+                }
+                slots += ty.slots();
+            }
+        }
+    }
+
+    fn generate_live_unpack(&mut self) {
+        let mut slots = 0;
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            for slot in 0..ty.slots() {
+                write!(self.string, "    ").unwrap();
+                write_var_slot(self.string, live_ref, ty, slot);
+                write!(self.string, " = ").unwrap();
+                write_table_slot(self.string, "live_table", ty, slots + slot);
+                writeln!(self.string, ";").unwrap();
+            }
+            slots += ty.slots();
+        }
+    }
+
+    // WGSL can't assign to multi component swizzles, so (un)packing goes one component at a time
+    fn unpack_var(&mut self, ident: Ident, ty: &Ty, packed_var_name: &str, packed_vars_size: usize, slot: usize) -> usize {
+        for var_slot in 0..ty.slots() {
+            write!(self.string, "    ").unwrap();
+            write_var_slot(self.string, &DisplayDsIdent(ident), ty, var_slot);
+            write!(self.string, " = ").unwrap();
+            write_packed_slot(self.string, packed_var_name, packed_vars_size, slot + var_slot);
+            writeln!(self.string, ";").unwrap();
+        }
+        slot + ty.slots()
+    }
+
+    fn pack_var(&mut self, ident: Ident, ty: &Ty, packed_var_name: &str, packed_vars_size: usize, slot: usize) -> usize {
+        for var_slot in 0..ty.slots() {
+            write!(self.string, "    ").unwrap();
+            write_packed_slot(self.string, packed_var_name, packed_vars_size, slot + var_slot);
+            write!(self.string, " = ").unwrap();
+            write_var_slot(self.string, &DisplayDsIdent(ident), ty, var_slot);
+            writeln!(self.string, ";").unwrap();
+        }
+        slot + ty.slots()
+    }
+
+    fn calc_live_slots(&self) -> usize {
+        let mut slots = 0;
+        for (_, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            slots += ty.slots();
+        }
+        slots
+    }
+
+    fn compute_packed_geometries_slots(&self) -> usize {
+        let mut packed_attributes_size = 0;
+        for field in &self.draw_shader_def.fields {
+            packed_attributes_size += match field.kind {
+                DrawShaderFieldKind::Geometry {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        packed_attributes_size
+    }
+
+    fn compute_packed_instances_slots(&self) -> usize {
+        let mut packed_instances_size = 0;
+        for field in &self.draw_shader_def.fields {
+            packed_instances_size += match field.kind {
+                DrawShaderFieldKind::Instance {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        packed_instances_size
+    }
+
+    fn compute_packed_varyings_slots(&self) -> usize {
+        let mut packed_varyings_size = 0;
+        for field in &self.draw_shader_def.fields {
+            packed_varyings_size += match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    field.ty_expr.ty.borrow().as_ref().unwrap().slots()
+                }
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    field.ty_expr.ty.borrow().as_ref().unwrap().slots()
+                }
+                DrawShaderFieldKind::Varying {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        packed_varyings_size
+    }
+
+    fn write_var_decl(&mut self, ident: &dyn fmt::Display, ty: &Ty) {
+        self.backend_writer.write_var_decl(self.string, "", false, false, ident, ty);
+    }
+}
+
+fn vec4_count(slots: usize) -> usize {
+    (slots + 3) >> 2
+}
+
+fn wgsl_ty_name(ty: &str) -> &'static str {
+    match ty {
+        "float" => "f32",
+        "vec2" => "vec2<f32>",
+        "vec3" => "vec3<f32>",
+        "vec4" => "vec4<f32>",
+        "ivec2" => "vec2<i32>",
+        "ivec3" => "vec3<i32>",
+        "ivec4" => "vec4<i32>",
+        "bvec2" => "vec2<bool>",
+        "bvec3" => "vec3<bool>",
+        "bvec4" => "vec4<bool>",
+        _ => panic!("no wgsl type for {}", ty)
+    }
+}
+
+fn write_component(string: &mut String, index: usize) {
+    write!(string, ".{}", ["x", "y", "z", "w"][index]).unwrap();
+}
+
+fn write_var_slot(string: &mut String, ident: &dyn fmt::Display, ty: &Ty, slot: usize) {
+    write!(string, "{}", ident).unwrap();
+    match ty {
+        Ty::Mat2 => write!(string, "[{}][{}]", slot / 2, slot % 2).unwrap(),
+        Ty::Mat3 => write!(string, "[{}][{}]", slot / 3, slot % 3).unwrap(),
+        Ty::Mat4 => write!(string, "[{}][{}]", slot / 4, slot % 4).unwrap(),
+        _ => if ty.slots() > 1 {
+            write_component(string, slot);
+        }
+    }
+}
+
+fn write_packed_slot(string: &mut String, packed_var_name: &str, packed_vars_size: usize, slot: usize) {
+    let index = slot >> 2;
+    write!(string, "{}_{}", packed_var_name, index).unwrap();
+    if (packed_vars_size - (index << 2)).min(4) > 1 {
+        write_component(string, slot & 3);
+    }
+}
+
+fn write_table_slot(string: &mut String, table: &str, ty: &Ty, slot: usize) {
+    let convert = match ty {
+        Ty::Bool | Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => "bool",
+        Ty::Int | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => "i32",
+        _ => ""
+    };
+    if convert == "bool" {
+        write!(string, "{}[{}]", table, slot >> 2).unwrap();
+        write_component(string, slot & 3);
+        write!(string, " > 0.5").unwrap();
+        return
+    }
+    write!(string, "{}({}[{}]", convert, table, slot >> 2).unwrap();
+    write_component(string, slot & 3);
+    write!(string, ")").unwrap();
+}
+
+struct WgslBackendWriter<'a> {
+    pub shader_registry: &'a ShaderRegistry,
+    pub const_table: &'a DrawShaderConstTable,
+    pub uniform_samples: &'a BTreeSet<TokenSpan>
+}
+
+impl<'a> WgslBackendWriter<'a> {
+    fn write_ty(&self, string: &mut String, ty: &Ty) {
+        match *ty {
+            Ty::Void => panic!("void is not a WGSL type"),
+            Ty::Array {ref elem_ty, len} => {
+                write!(string, "array<").unwrap();
+                self.write_ty(string, elem_ty);
+                write!(string, ", {}>", len).unwrap();
+            }
+            Ty::Struct(struct_ptr) => {
+                write!(string, "{}", struct_ptr).unwrap();
+            }
+            Ty::Enum(_) => {
+                write!(string, "f32").unwrap();
+            }
+            Ty::Texture2D => self.write_ty_lit(string, TyLit::Texture2D),
+            Ty::TextureOES => self.write_ty_lit(string, TyLit::TextureOES),
+            _ => self.write_ty_lit(string, ty.maybe_ty_lit().unwrap())
+        }
+    }
+}
+
+impl<'a> BackendWriter for WgslBackendWriter<'a> {
+    fn get_struct_cons_type(&self) -> StructConsType {
+        StructConsType::Paren
+    }
+
+    fn enum_is_float(&self) -> bool {
+        true
+    }
+
+    fn needs_mul_fn_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn needs_unpack_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn const_table_is_vec4(&self) -> bool {
+        true
+    }
+
+    fn use_cons_fn(&self, what: &str) -> bool {
+        // WGSL can't construct a matrix from a matrix of another size
+        matches!(
+            what,
+            "consfn_mat2_mat3" | "consfn_mat2_mat4" | "consfn_mat3_mat2" |
+            "consfn_mat3_mat4" | "consfn_mat4_mat2" | "consfn_mat4_mat3"
+        )
+    }
+
+    fn write_var_decl(
+        &self,
+        string: &mut String,
+        sep: &'static str,
+        is_inout: bool,
+        _is_packed: bool,
+        ident: &dyn fmt::Display,
+        ty: &Ty,
+    ) -> bool {
+        match *ty {
+            Ty::DrawShader(_) | Ty::ClosureDef {..} | Ty::ClosureDecl => {
+                return false
+            }
+            _ => ()
+        }
+        write!(string, "{}{}: ", sep, ident).unwrap();
+        if is_inout {
+            write!(string, "ptr<function, ").unwrap();
+            self.write_ty(string, ty);
+            write!(string, ">").unwrap();
+        }
+        else {
+            self.write_ty(string, ty);
+        }
+        true
+    }
+
+    fn write_call_expr_hidden_args(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn write_fn_def_hidden_params(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn generate_live_value_prefix(&self, _string: &mut String) {
+    }
+
+    fn generate_draw_shader_field_expr(&self, string: &mut String, field_ident: Ident, _ty: &Ty) {
+        write!(string, "{}", &DisplayDsIdent(field_ident)).unwrap();
+    }
+
+    fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit) {
+        write!(
+            string,
+            "{}",
+            match ty_lit {
+                TyLit::Bool => "bool",
+                TyLit::Int => "i32",
+                TyLit::Float => "f32",
+                TyLit::Bvec2 => "vec2<bool>",
+                TyLit::Bvec3 => "vec3<bool>",
+                TyLit::Bvec4 => "vec4<bool>",
+                TyLit::Ivec2 => "vec2<i32>",
+                TyLit::Ivec3 => "vec3<i32>",
+                TyLit::Ivec4 => "vec4<i32>",
+                TyLit::Vec2 => "vec2<f32>",
+                TyLit::Vec3 => "vec3<f32>",
+                TyLit::Vec4 => "vec4<f32>",
+                TyLit::Mat2 => "mat2x2<f32>",
+                TyLit::Mat3 => "mat3x3<f32>",
+                TyLit::Mat4 => "mat4x4<f32>",
+                // external OES textures are plain 2d textures outside of Android
+                TyLit::Texture2D | TyLit::TextureOES => "texture_2d<f32>",
+            }
+        )
+            .unwrap();
+    }
+
+    fn write_texture_sampler_arg(&self, string: &mut String, field_ident: Ident) {
+        write!(string, ", {}_sampler", DisplayDsIdent(field_ident)).unwrap();
+    }
+
+    fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
+        match ident {
+            Ident(live_id!(atan)) => {
+                if arg_exprs.len() == 2 {
+                    write!(string, "atan2").unwrap();
+                }
+                else {
+                    write!(string, "atan").unwrap();
+                }
+            }
+            Ident(live_id!(mod)) => {
+                write!(
+                    string,
+                    "mod_{}_{}",
+                    arg_exprs[0].ty.borrow().as_ref().unwrap(),
+                    arg_exprs[1].ty.borrow().as_ref().unwrap()
+                ).unwrap();
+            }
+            Ident(live_id!(lessThan)) | Ident(live_id!(lessThanEqual)) |
+            Ident(live_id!(greaterThan)) | Ident(live_id!(greaterThanEqual)) |
+            Ident(live_id!(equal)) | Ident(live_id!(notEqual)) |
+            Ident(live_id!(not)) | Ident(live_id!(matrixCompMult)) => {
+                write!(string, "{}_{}", ident, arg_exprs[0].ty.borrow().as_ref().unwrap()).unwrap();
+            }
+            Ident(live_id!(clamp)) | Ident(live_id!(min)) | Ident(live_id!(max)) |
+            Ident(live_id!(step)) | Ident(live_id!(smoothstep)) => {
+                // the GLSL overloads that mix scalars and vectors go through a splatting helper
+                let first_ty = arg_exprs[0].ty.borrow().clone();
+                if arg_exprs.iter().all( | arg_expr | *arg_expr.ty.borrow() == first_ty) {
+                    write!(string, "{}", ident).unwrap();
+                }
+                else {
+                    write!(string, "{}", ident).unwrap();
+                    for arg_expr in arg_exprs {
+                        write!(string, "_{}", arg_expr.ty.borrow().as_ref().unwrap()).unwrap();
+                    }
+                }
+            }
+            Ident(live_id!(dFdx)) => {
+                write!(string, "dpdx").unwrap();
+            }
+            Ident(live_id!(dFdy)) => {
+                write!(string, "dpdy").unwrap();
+            }
+            Ident(live_id!(inversesqrt)) => {
+                write!(string, "inverseSqrt").unwrap();
+            }
+            Ident(live_id!(faceforward)) => {
+                write!(string, "faceForward").unwrap();
+            }
+            Ident(live_id!(sample2d)) | Ident(live_id!(sample2d_rt)) | Ident(live_id!(sample2dOES)) => {
+                let name = if ident == Ident(live_id!(sample2d_rt)) {"sample2d_rt"} else {"sample2d"};
+                if self.uniform_samples.contains(&arg_exprs[0].span) {
+                    write!(string, "{}", name).unwrap();
+                }
+                else {
+                    write!(string, "{}_level", name).unwrap();
+                }
+            }
+            _ => {
+                write!(string, "{}", ident).unwrap()
+            }
+        }
+    }
+
+    fn write_fn_def_start(&self, string: &mut String, ident: &dyn fmt::Display, _return_ty: &Ty) {
+        write!(string, "fn {}(", ident).unwrap();
+    }
+
+    fn write_fn_def_end(&self, string: &mut String, return_ty: &Ty) {
+        if let Ty::Void = return_ty {
+            write!(string, ") ").unwrap();
+        }
+        else {
+            write!(string, ") -> ").unwrap();
+            self.write_ty(string, return_ty);
+            write!(string, " ").unwrap();
+        }
+    }
+
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        write!(string, "var ").unwrap();
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+
+    fn use_select_for_cond_expr(&self) -> bool {
+        true
+    }
+
+    fn inout_params_are_pointers(&self) -> bool {
+        true
+    }
+
+    fn assign_is_stmt(&self) -> bool {
+        true
+    }
+    
+    fn params_are_immutable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,1216 @@
+// A CPU reference interpreter for analysed draw shaders. It walks the same AST the
+// backends generate code from, so it can be used to render shaders headless and
+// compare the result against golden images without a GPU. Constructs it can't run
+// come back as errors, after one the interpreter is left mid call and should be dropped.
+use {
+    std::{
+        rc::Rc,
+        collections::BTreeMap,
+    },
+    crate::{
+        makepad_live_compiler::{
+            LiveError,
+            LiveErrorOrigin,
+            live_error_origin,
+            TokenSpan
+        },
+        makepad_live_id::*,
+        makepad_math::*,
+        shader_ast::*,
+        shader_registry::ShaderRegistry,
+        swizzle::Swizzle,
+    }
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShaderValue {
+    Void,
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    Bvec(usize, [bool; 4]),
+    Ivec(usize, [i32; 4]),
+    Vec(usize, [f32; 4]),
+    // column major, like GLSL
+    Mat(usize, [f32; 16]),
+    Array(Vec<ShaderValue>),
+    Struct(StructPtr, Vec<ShaderValue>),
+    Texture(usize),
+    DrawShader,
+    Closure(Rc<ShaderClosure>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShaderLocal {
+    ident: Ident,
+    // loop variables don't carry a shadow, they match any
+    shadow: Option<ScopeSymShadow>,
+    value: ShaderValue,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ShaderClosure {
+    fn_ptr: FnPtr,
+    closure_def_index: ClosureDefIndex,
+    captured: Vec<ShaderLocal>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Shape {
+    Scalar,
+    Vec(usize),
+    Mat(usize),
+}
+
+impl Shape {
+    fn len(self) -> usize {
+        match self {
+            Shape::Scalar => 1,
+            Shape::Vec(n) => n,
+            Shape::Mat(n) => n * n,
+        }
+    }
+}
+
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(ShaderValue),
+}
+
+fn unsupported(span: TokenSpan, message: String) -> LiveError {
+    LiveError {
+        origin: live_error_origin!(),
+        span: span.into(),
+        message,
+        notes: Vec::new(),
+    }
+}
+
+pub trait ShaderSampler {
+    fn sample2d(&self, texture: usize, pos: Vec2) -> Vec4;
+    fn sample2d_rt(&self, texture: usize, pos: Vec2) -> Vec4;
+}
+
+// dFdx/dFdy are computed by running the neighbouring pixels first and recording
+// the arguments of every derivative call, the pixel itself then subtracts them.
+pub enum ShaderDerivatives {
+    None,
+    Record(Vec<ShaderValue>),
+    Apply {dx: Vec<ShaderValue>, dy: Vec<ShaderValue>},
+}
+
+pub struct ShaderInterpreter<'a> {
+    pub shader_registry: &'a ShaderRegistry,
+    pub draw_shader_def: &'a DrawShaderDef,
+    pub const_table: Option<&'a DrawShaderConstTable>,
+    pub live_values: &'a BTreeMap<ValuePtr, ShaderValue>,
+    pub sampler: &'a dyn ShaderSampler,
+    // one value per draw_shader_def field
+    pub fields: Vec<ShaderValue>,
+    pub derivatives: ShaderDerivatives,
+    derivative_index: usize,
+    fn_def: Option<&'a FnDef>,
+    locals: Vec<ShaderLocal>,
+}
+
+impl<'a> ShaderInterpreter<'a> {
+    pub fn new(
+        shader_registry: &'a ShaderRegistry,
+        draw_shader_def: &'a DrawShaderDef,
+        const_table: Option<&'a DrawShaderConstTable>,
+        live_values: &'a BTreeMap<ValuePtr, ShaderValue>,
+        sampler: &'a dyn ShaderSampler,
+    ) -> Self {
+        let mut texture_index = 0;
+        let fields = draw_shader_def.fields.iter().map( | field | {
+            match field.ty_expr.ty.borrow().as_ref().unwrap() {
+                Ty::Texture2D | Ty::TextureOES => {
+                    texture_index += 1;
+                    ShaderValue::Texture(texture_index - 1)
+                }
+                ty => ShaderValue::zero(ty, shader_registry)
+            }
+        }).collect();
+        Self {
+            shader_registry,
+            draw_shader_def,
+            const_table,
+            live_values,
+            sampler,
+            fields,
+            derivatives: ShaderDerivatives::None,
+            derivative_index: 0,
+            fn_def: None,
+            locals: Vec::new(),
+        }
+    }
+
+    pub fn field_index(&self, ident: Ident) -> Option<usize> {
+        self.draw_shader_def.fields.iter().position( | field | field.ident == ident)
+    }
+
+    pub fn field(&self, ident: Ident) -> Option<&ShaderValue> {
+        self.field_index(ident).map( | index | &self.fields[index])
+    }
+
+    pub fn set_field(&mut self, ident: Ident, value: ShaderValue) {
+        if let Some(index) = self.field_index(ident) {
+            self.fields[index] = value;
+        }
+    }
+
+    pub fn uses_derivatives(&self) -> bool {
+        self.draw_shader_def.pixel_fns.borrow().iter().any( | fn_ptr | {
+            let fn_def = self.shader_registry.all_fns.get(fn_ptr).unwrap();
+            let builtin_deps = fn_def.builtin_deps.borrow();
+            let builtin_deps = builtin_deps.as_ref().unwrap();
+            builtin_deps.contains(&Ident(live_id!(dFdx))) || builtin_deps.contains(&Ident(live_id!(dFdy)))
+        })
+    }
+
+    pub fn vertex(&mut self) -> Result<Vec4, LiveError> {
+        Ok(self.call_draw_shader_method(Ident(live_id!(vertex)), Vec::new())?.to_vec4())
+    }
+
+    pub fn pixel(&mut self) -> Result<Vec4, LiveError> {
+        self.derivative_index = 0;
+        Ok(self.call_draw_shader_method(Ident(live_id!(pixel)), Vec::new())?.to_vec4())
+    }
+
+    pub fn call_draw_shader_method(&mut self, ident: Ident, args: Vec<ShaderValue>) -> Result<ShaderValue, LiveError> {
+        let fn_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, ident).ok_or_else( || {
+            unsupported(TokenSpan::default(), format!("draw shader has no {} method", ident))
+        }) ?;
+        Ok(self.call_fn(fn_def, args)?.0)
+    }
+
+    // returns the return value and the final values of the params that got args
+    fn call_fn(&mut self, fn_def: &'a FnDef, args: Vec<ShaderValue>) -> Result<(ShaderValue, Vec<ShaderValue>), LiveError> {
+        // draw shader methods get called without their self arg
+        let skip = fn_def.params.len() - args.len();
+        let mut locals = Vec::new();
+        for param in &fn_def.params[0..skip] {
+            locals.push(ShaderLocal {ident: param.ident, shadow: param.shadow.get(), value: ShaderValue::DrawShader});
+        }
+        for (param, value) in fn_def.params[skip..].iter().zip(args) {
+            locals.push(ShaderLocal {ident: param.ident, shadow: param.shadow.get(), value});
+        }
+        let saved_locals = std::mem::replace(&mut self.locals, locals);
+        let saved_fn_def = self.fn_def.replace(fn_def);
+        let ret = match self.exec_block(&fn_def.block)? {
+            Flow::Return(value) => value,
+            _ => ShaderValue::Void
+        };
+        let locals = std::mem::replace(&mut self.locals, saved_locals);
+        self.fn_def = saved_fn_def;
+        Ok((ret, locals.into_iter().take(fn_def.params.len()).skip(skip).map( | local | local.value).collect()))
+    }
+
+    fn call_closure(&mut self, closure: &ShaderClosure, args: Vec<ShaderValue>) -> Result<ShaderValue, LiveError> {
+        let fn_def = self.shader_registry.all_fns.get(&closure.fn_ptr).unwrap();
+        let closure_def = &fn_def.closure_defs[closure.closure_def_index.0];
+        let mut locals = closure.captured.clone();
+        for (param, value) in closure_def.params.iter().zip(args) {
+            locals.push(ShaderLocal {ident: param.ident, shadow: param.shadow.get(), value});
+        }
+        let saved_locals = std::mem::replace(&mut self.locals, locals);
+        let saved_fn_def = self.fn_def.replace(fn_def);
+        let ret = match &closure_def.kind {
+            ClosureDefKind::Expr(expr) => self.eval_expr(expr)?,
+            ClosureDefKind::Block(block) => match self.exec_block(block)? {
+                Flow::Return(value) => value,
+                _ => ShaderValue::Void
+            }
+        };
+        self.locals = saved_locals;
+        self.fn_def = saved_fn_def;
+        Ok(ret)
+    }
+
+    fn exec_block(&mut self, block: &'a Block) -> Result<Flow, LiveError> {
+        let locals_len = self.locals.len();
+        let mut flow = Flow::Next;
+        for stmt in &block.stmts {
+            flow = self.exec_stmt(stmt)?;
+            if !matches!(flow, Flow::Next) {
+                break;
+            }
+        }
+        self.locals.truncate(locals_len);
+        Ok(flow)
+    }
+
+    fn exec_stmt(&mut self, stmt: &'a Stmt) -> Result<Flow, LiveError> {
+        Ok(match stmt {
+            Stmt::Break {..} => Flow::Break,
+            Stmt::Continue {..} => Flow::Continue,
+            Stmt::For {ident, from_expr, to_expr, step_expr, block, ..} => {
+                let const_int = | expr: &Expr | expr.const_val.borrow().as_ref().unwrap().as_ref().unwrap().to_int().unwrap();
+                let from = const_int(from_expr);
+                let to = const_int(to_expr);
+                let step = if let Some(step_expr) = step_expr {
+                    const_int(step_expr)
+                } else if from < to {
+                    1
+                } else {
+                    -1
+                };
+                // same bounds as the generated code
+                let mut index = if from <= to {from} else {from - 1};
+                loop {
+                    if from <= to && index >= to || from > to && index < to {
+                        break;
+                    }
+                    self.locals.push(ShaderLocal {ident: *ident, shadow: None, value: ShaderValue::Int(index)});
+                    let flow = self.exec_block(block)?;
+                    self.locals.pop();
+                    match flow {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        _ => ()
+                    }
+                    index += step;
+                }
+                Flow::Next
+            }
+            Stmt::If {expr, block_if_true, block_if_false, ..} => {
+                if self.eval_expr(expr)?.to_bool() {
+                    self.exec_block(block_if_true)?
+                }
+                else if let Some(block_if_false) = block_if_false {
+                    self.exec_block(block_if_false)?
+                }
+                else {
+                    Flow::Next
+                }
+            }
+            Stmt::Match {expr, matches, ..} => {
+                let value = self.eval_expr(expr)?.to_f32();
+                for match_item in matches {
+                    if (value - match_item.enum_value.get().unwrap() as f32).abs() < 0.5 {
+                        return self.exec_block(&match_item.block)
+                    }
+                }
+                Flow::Next
+            }
+            Stmt::Let {ty, shadow, ident, expr, ..} => {
+                let value = if let Some(expr) = expr {
+                    self.eval_expr(expr)?
+                }
+                else {
+                    ShaderValue::zero(ty.borrow().as_ref().unwrap(), self.shader_registry)
+                };
+                self.locals.push(ShaderLocal {ident: *ident, shadow: shadow.get(), value});
+                Flow::Next
+            }
+            Stmt::Return {expr, ..} => {
+                Flow::Return(if let Some(expr) = expr {
+                    self.eval_expr(expr)?
+                }
+                else {
+                    ShaderValue::Void
+                })
+            }
+            Stmt::Block {block, ..} => self.exec_block(block)?,
+            Stmt::Expr {expr, ..} => {
+                self.eval_expr(expr)?;
+                Flow::Next
+            }
+        })
+    }
+
+    fn find_local(&self, ident: Ident, shadow: ScopeSymShadow) -> usize {
+        self.locals.iter().rposition( | local | {
+            local.ident == ident && (local.shadow.is_none() || local.shadow == Some(shadow))
+        }).unwrap()
+    }
+
+    fn const_value(&self, val: &Val, const_index: Option<usize>) -> ShaderValue {
+        // read through the const table so live edited constants are honored
+        if let (Some(const_table), Some(index), Some(fn_def)) = (self.const_table, const_index, self.fn_def) {
+            if let Some(offset) = const_table.offsets.get(&fn_def.fn_ptr) {
+                let table = &const_table.table[offset + index..];
+                match val {
+                    Val::Float(_) => return ShaderValue::Float(table[0]),
+                    Val::Vec4(_) => return ShaderValue::Vec(4, [table[0], table[1], table[2], table[3]]),
+                    _ => ()
+                }
+            }
+        }
+        ShaderValue::from_val(val)
+    }
+
+    fn eval_args(&mut self, arg_exprs: &'a [Expr]) -> Result<Vec<ShaderValue>, LiveError> {
+        arg_exprs.iter().map( | arg_expr | self.eval_expr(arg_expr)).collect()
+    }
+
+    fn eval_call(&mut self, fn_def: &'a FnDef, arg_exprs: &'a [Expr]) -> Result<ShaderValue, LiveError> {
+        let args = self.eval_args(arg_exprs)?;
+        let (ret, params) = self.call_fn(fn_def, args)?;
+        let skip = fn_def.params.len() - arg_exprs.len();
+        for ((arg_expr, param), value) in arg_exprs.iter().zip(&fn_def.params[skip..]).zip(params) {
+            if param.is_inout {
+                self.assign(arg_expr, value)?;
+            }
+        }
+        Ok(ret)
+    }
+
+    pub fn eval_expr(&mut self, expr: &'a Expr) -> Result<ShaderValue, LiveError> {
+        if let Some(Some(val)) = expr.const_val.borrow().as_ref() {
+            return Ok(self.const_value(val, expr.const_index.get()))
+        }
+        Ok(match &expr.kind {
+            ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => {
+                if self.eval_expr(expr)?.to_bool() {
+                    self.eval_expr(expr_if_true)?
+                }
+                else {
+                    self.eval_expr(expr_if_false)?
+                }
+            }
+            ExprKind::Bin {op, left_expr, right_expr, ..} => self.eval_bin_expr(*op, left_expr, right_expr)?,
+            ExprKind::Un {op, expr, ..} => {
+                let value = self.eval_expr(expr)?;
+                match op {
+                    UnOp::Not => ShaderValue::Bool(!value.to_bool()),
+                    UnOp::Neg => if value.is_int() {
+                        value.map_ints(&[], | v | v[0].wrapping_neg())
+                    }
+                    else {
+                        ShaderValue::map_floats(&[&value], | v | -v[0])
+                    }
+                }
+            }
+            ExprKind::Field {expr, field_ident, ..} => {
+                if let Some(Ty::DrawShader(_)) = expr.ty.borrow().as_ref() {
+                    return Ok(self.fields[self.field_index(*field_ident).unwrap()].clone())
+                }
+                self.eval_expr(expr)?.field(*field_ident, self.shader_registry)
+            }
+            ExprKind::Index {expr, index_expr, ..} => {
+                let index = self.eval_expr(index_expr)?.to_i32() as usize;
+                self.eval_expr(expr)?.index(index)
+            }
+            ExprKind::MethodCall {ident, arg_exprs, ..} => {
+                let shader_registry = self.shader_registry;
+                match arg_exprs[0].ty.borrow().as_ref().unwrap() {
+                    Ty::Struct(struct_ptr) => {
+                        let fn_def = shader_registry.struct_method_decl_from_ident(
+                            shader_registry.structs.get(struct_ptr).unwrap(),
+                            *ident
+                        ).unwrap();
+                        self.eval_call(fn_def, arg_exprs)?
+                    }
+                    Ty::DrawShader(shader_ptr) => {
+                        let fn_def = shader_registry.draw_shader_method_decl_from_ident(
+                            shader_registry.draw_shader_defs.get(shader_ptr).unwrap(),
+                            *ident
+                        ).unwrap();
+                        self.eval_call(fn_def, &arg_exprs[1..])?
+                    }
+                    ty => return Err(unsupported(expr.span, format!("method {} called on {:?}", ident, ty))),
+                }
+            }
+            ExprKind::PlainCall {fn_ptr, arg_exprs, param_index, ..} => {
+                if let Some(param_index) = param_index.get() {
+                    let closure = match &self.locals[param_index].value {
+                        ShaderValue::Closure(closure) => closure.clone(),
+                        _ => return Err(unsupported(expr.span, "closure param doesn't hold a closure".into()))
+                    };
+                    let args = self.eval_args(arg_exprs)?;
+                    self.call_closure(&closure, args)?
+                }
+                else {
+                    let fn_def = self.shader_registry.all_fns.get(&fn_ptr.unwrap()).unwrap();
+                    self.eval_call(fn_def, arg_exprs)?
+                }
+            }
+            ExprKind::BuiltinCall {ident, arg_exprs, ..} => {
+                let args = self.eval_args(arg_exprs)?;
+                self.eval_builtin(expr.span, *ident, args)?
+            }
+            ExprKind::ClosureDef(closure_def_index) => ShaderValue::Closure(Rc::new(ShaderClosure {
+                fn_ptr: self.fn_def.unwrap().fn_ptr,
+                closure_def_index: *closure_def_index,
+                captured: self.locals.clone(),
+            })),
+            ExprKind::ConsCall {ty_lit, arg_exprs, ..} => {
+                let args = self.eval_args(arg_exprs)?;
+                ShaderValue::construct(*ty_lit, &args)
+            }
+            ExprKind::StructCons {struct_ptr, args, ..} => {
+                let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+                let mut values = Vec::new();
+                for field in &struct_def.fields {
+                    let arg = args.iter().find( | (ident, _) | field.ident == *ident).unwrap();
+                    values.push(self.eval_expr(&arg.1)?);
+                }
+                ShaderValue::Struct(*struct_ptr, values)
+            }
+            ExprKind::Var {kind, ..} => match kind.get().unwrap() {
+                VarKind::Local {ident, shadow} | VarKind::MutLocal {ident, shadow} => {
+                    self.locals[self.find_local(ident, shadow)].value.clone()
+                }
+                VarKind::LiveValue(value_ptr) => {
+                    if let Some(value) = self.live_values.get(&value_ptr) {
+                        value.clone()
+                    }
+                    else {
+                        ShaderValue::zero(expr.ty.borrow().as_ref().unwrap(), self.shader_registry)
+                    }
+                }
+            }
+            ExprKind::Lit {lit, ..} => match lit {
+                Lit::Bool(v) => ShaderValue::Bool(*v),
+                Lit::Int(v) => ShaderValue::Int(*v),
+                Lit::Float(v) => ShaderValue::Float(*v),
+                Lit::Color(v) => Vec4::from_u32(*v).into(),
+            },
+        })
+    }
+
+    fn eval_bin_expr(&mut self, op: BinOp, left_expr: &'a Expr, right_expr: &'a Expr) -> Result<ShaderValue, LiveError> {
+        let arith_op = match op {
+            BinOp::Assign => {
+                let value = self.eval_expr(right_expr)?;
+                self.assign(left_expr, value.clone())?;
+                return Ok(value)
+            }
+            BinOp::Or => return Ok(ShaderValue::Bool(self.eval_expr(left_expr)?.to_bool() || self.eval_expr(right_expr)?.to_bool())),
+            BinOp::And => return Ok(ShaderValue::Bool(self.eval_expr(left_expr)?.to_bool() && self.eval_expr(right_expr)?.to_bool())),
+            BinOp::AddAssign => BinOp::Add,
+            BinOp::SubAssign => BinOp::Sub,
+            BinOp::MulAssign => BinOp::Mul,
+            BinOp::DivAssign => BinOp::Div,
+            op => op
+        };
+        let left = self.eval_expr(left_expr)?;
+        let right = self.eval_expr(right_expr)?;
+        let value = match arith_op {
+            BinOp::Eq => ShaderValue::Bool(left == right),
+            BinOp::Ne => ShaderValue::Bool(left != right),
+            BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                let (l, r) = (left.to_f32(), right.to_f32());
+                ShaderValue::Bool(match arith_op {
+                    BinOp::Lt => l < r,
+                    BinOp::Le => l <= r,
+                    BinOp::Gt => l > r,
+                    _ => l >= r,
+                })
+            }
+            _ => ShaderValue::arith(arith_op, &left, &right)
+        };
+        if !matches!(op, BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign) {
+            return Ok(value)
+        }
+        self.assign(left_expr, value.clone())?;
+        Ok(value)
+    }
+
+    fn assign(&mut self, expr: &'a Expr, value: ShaderValue) -> Result<(), LiveError> {
+        match &expr.kind {
+            ExprKind::Var {kind, ..} => match kind.get().unwrap() {
+                VarKind::Local {ident, shadow} | VarKind::MutLocal {ident, shadow} => {
+                    let index = self.find_local(ident, shadow);
+                    self.locals[index].value = value;
+                }
+                VarKind::LiveValue(_) => return Err(unsupported(expr.span, "live values can't be assigned to".into()))
+            }
+            ExprKind::Field {expr: base_expr, field_ident, ..} => {
+                if let Some(Ty::DrawShader(_)) = base_expr.ty.borrow().as_ref() {
+                    let index = self.field_index(*field_ident).unwrap();
+                    self.fields[index] = value;
+                    return Ok(())
+                }
+                let mut base = self.eval_expr(base_expr)?;
+                base.set_field(*field_ident, value, self.shader_registry);
+                self.assign(base_expr, base)?;
+            }
+            ExprKind::Index {expr: base_expr, index_expr, ..} => {
+                let index = self.eval_expr(index_expr)?.to_i32() as usize;
+                let mut base = self.eval_expr(base_expr)?;
+                base.set_index(index, value);
+                self.assign(base_expr, base)?;
+            }
+            _ => return Err(unsupported(expr.span, "expression can't be assigned to".into()))
+        }
+        Ok(())
+    }
+
+    fn eval_builtin(&mut self, span: TokenSpan, ident: Ident, args: Vec<ShaderValue>) -> Result<ShaderValue, LiveError> {
+        fn floats(args: &[ShaderValue], f: impl Fn(&[f32]) -> f32) -> ShaderValue {
+            let refs: Vec<&ShaderValue> = args.iter().collect();
+            ShaderValue::map_floats(&refs, f)
+        }
+        fn compare(args: &[ShaderValue], f: impl Fn(f32, f32) -> bool) -> ShaderValue {
+            let (shape, a) = args[0].float_parts();
+            let (_, b) = args[1].float_parts();
+            let mut out = [false; 4];
+            for i in 0..shape.len() {
+                out[i] = f(a[i], b[i]);
+            }
+            ShaderValue::Bvec(shape.len(), out)
+        }
+        fn bools(value: &ShaderValue) -> (usize, [bool; 4]) {
+            match value {
+                ShaderValue::Bvec(n, v) => (*n, *v),
+                _ => panic!()
+            }
+        }
+        Ok(match ident.0 {
+            live_id!(abs) => if args[0].is_int() {
+                args[0].map_ints(&[], | v | v[0].wrapping_abs())
+            } else {
+                floats(&args, | v | v[0].abs())
+            },
+            live_id!(sign) => if args[0].is_int() {
+                args[0].map_ints(&[], | v | v[0].signum())
+            } else {
+                floats(&args, | v | if v[0] > 0.0 {1.0} else if v[0] < 0.0 {-1.0} else {0.0})
+            },
+            live_id!(min) => if args[0].is_int() {
+                args[0].map_ints(&args[1..], | v | v[0].min(v[1]))
+            } else {
+                floats(&args, | v | v[0].min(v[1]))
+            },
+            live_id!(max) => if args[0].is_int() {
+                args[0].map_ints(&args[1..], | v | v[0].max(v[1]))
+            } else {
+                floats(&args, | v | v[0].max(v[1]))
+            },
+            live_id!(clamp) => if args[0].is_int() {
+                args[0].map_ints(&args[1..], | v | v[0].max(v[1]).min(v[2]))
+            } else {
+                floats(&args, | v | v[0].max(v[1]).min(v[2]))
+            },
+            live_id!(acos) => floats(&args, | v | v[0].acos()),
+            live_id!(asin) => floats(&args, | v | v[0].asin()),
+            live_id!(atan) => if args.len() == 2 {
+                floats(&args, | v | v[0].atan2(v[1]))
+            } else {
+                floats(&args, | v | v[0].atan())
+            },
+            live_id!(sin) => floats(&args, | v | v[0].sin()),
+            live_id!(cos) => floats(&args, | v | v[0].cos()),
+            live_id!(tan) => floats(&args, | v | v[0].tan()),
+            live_id!(ceil) => floats(&args, | v | v[0].ceil()),
+            live_id!(floor) => floats(&args, | v | v[0].floor()),
+            live_id!(fract) => floats(&args, | v | v[0] - v[0].floor()),
+            live_id!(degrees) => floats(&args, | v | v[0].to_degrees()),
+            live_id!(radians) => floats(&args, | v | v[0].to_radians()),
+            live_id!(exp) => floats(&args, | v | v[0].exp()),
+            live_id!(exp2) => floats(&args, | v | v[0].exp2()),
+            live_id!(log) => floats(&args, | v | v[0].ln()),
+            live_id!(log2) => floats(&args, | v | v[0].log2()),
+            live_id!(sqrt) => floats(&args, | v | v[0].sqrt()),
+            live_id!(inversesqrt) => floats(&args, | v | 1.0 / v[0].sqrt()),
+            live_id!(pow) => floats(&args, | v | v[0].powf(v[1])),
+            live_id!(mod) => floats(&args, | v | v[0] - v[1] * (v[0] / v[1]).floor()),
+            live_id!(mix) => floats(&args, | v | v[0] * (1.0 - v[2]) + v[1] * v[2]),
+            live_id!(step) => floats(&args, | v | if v[1] < v[0] {0.0} else {1.0}),
+            live_id!(smoothstep) => floats(&args, | v | {
+                let t = ((v[2] - v[0]) / (v[1] - v[0])).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            }),
+            live_id!(matrixCompMult) => floats(&args, | v | v[0] * v[1]),
+            live_id!(length) => ShaderValue::Float(args[0].dot(&args[0]).sqrt()),
+            live_id!(distance) => {
+                let delta = ShaderValue::arith(BinOp::Sub, &args[0], &args[1]);
+                ShaderValue::Float(delta.dot(&delta).sqrt())
+            }
+            live_id!(dot) => ShaderValue::Float(args[0].dot(&args[1])),
+            live_id!(normalize) => {
+                let length = args[0].dot(&args[0]).sqrt();
+                floats(&args, | v | v[0] / length)
+            }
+            live_id!(cross) => {
+                let (_, a) = args[0].float_parts();
+                let (_, b) = args[1].float_parts();
+                ShaderValue::Vec(3, [
+                    a[1] * b[2] - a[2] * b[1],
+                    a[2] * b[0] - a[0] * b[2],
+                    a[0] * b[1] - a[1] * b[0],
+                    0.0
+                ])
+            }
+            live_id!(reflect) => {
+                let d = args[0].dot(&args[1]);
+                floats(&args, | v | v[0] - 2.0 * d * v[1])
+            }
+            live_id!(refract) => {
+                let d = args[1].dot(&args[0]);
+                let eta = args[2].to_f32();
+                let k = 1.0 - eta * eta * (1.0 - d * d);
+                if k < 0.0 {
+                    floats(&args[0..1], | _ | 0.0)
+                }
+                else {
+                    floats(&args[0..2], | v | eta * v[0] - (eta * d + k.sqrt()) * v[1])
+                }
+            }
+            live_id!(faceforward) => {
+                if args[2].dot(&args[1]) < 0.0 {
+                    args[0].clone()
+                }
+                else {
+                    floats(&args[0..1], | v | -v[0])
+                }
+            }
+            live_id!(transpose) => {
+                let (shape, m) = args[0].float_parts();
+                let n = if let Shape::Mat(n) = shape {n} else {panic!()};
+                let mut out = [0.0; 16];
+                for c in 0..n {
+                    for r in 0..n {
+                        out[r * n + c] = m[c * n + r];
+                    }
+                }
+                ShaderValue::Mat(n, out)
+            }
+            live_id!(inverse) => args[0].inverse(),
+            live_id!(lessThan) => compare(&args, | a, b | a < b),
+            live_id!(lessThanEqual) => compare(&args, | a, b | a <= b),
+            live_id!(greaterThan) => compare(&args, | a, b | a > b),
+            live_id!(greaterThanEqual) => compare(&args, | a, b | a >= b),
+            live_id!(equal) => if let ShaderValue::Bvec(n, a) = args[0] {
+                let (_, b) = bools(&args[1]);
+                ShaderValue::Bvec(n, [a[0] == b[0], a[1] == b[1], a[2] == b[2], a[3] == b[3]])
+            } else {
+                compare(&args, | a, b | a == b)
+            },
+            live_id!(notEqual) => if let ShaderValue::Bvec(n, a) = args[0] {
+                let (_, b) = bools(&args[1]);
+                ShaderValue::Bvec(n, [a[0] != b[0], a[1] != b[1], a[2] != b[2], a[3] != b[3]])
+            } else {
+                compare(&args, | a, b | a != b)
+            },
+            live_id!(any) => {
+                let (n, v) = bools(&args[0]);
+                ShaderValue::Bool(v[0..n].iter().any( | v | *v))
+            }
+            live_id!(all) => {
+                let (n, v) = bools(&args[0]);
+                ShaderValue::Bool(v[0..n].iter().all( | v | *v))
+            }
+            live_id!(not) => {
+                let (n, v) = bools(&args[0]);
+                ShaderValue::Bvec(n, [!v[0], !v[1], !v[2], !v[3]])
+            }
+            live_id!(dFdx) | live_id!(dFdy) => {
+                let index = self.derivative_index;
+                self.derivative_index += 1;
+                match &mut self.derivatives {
+                    ShaderDerivatives::None => floats(&args, | _ | 0.0),
+                    ShaderDerivatives::Record(log) => {
+                        log.push(args[0].clone());
+                        floats(&args, | _ | 0.0)
+                    }
+                    ShaderDerivatives::Apply {dx, dy} => {
+                        let log = if ident.0 == live_id!(dFdx) {dx} else {dy};
+                        if let Some(neighbour) = log.get(index) {
+                            ShaderValue::map_floats(&[neighbour, &args[0]], | v | v[0] - v[1])
+                        }
+                        else {
+                            floats(&args, | _ | 0.0)
+                        }
+                    }
+                }
+            }
+            live_id!(sample2d) | live_id!(sample2dOES) => {
+                self.sampler.sample2d(args[0].to_texture(), args[1].to_vec2()).into()
+            }
+            live_id!(sample2d_rt) => {
+                self.sampler.sample2d_rt(args[0].to_texture(), args[1].to_vec2()).into()
+            }
+            _ => return Err(unsupported(span, format!("builtin {} not supported by the shader interpreter", ident)))
+        })
+    }
+}
+
+impl ShaderValue {
+    pub fn zero(ty: &Ty, shader_registry: &ShaderRegistry) -> Self {
+        match ty {
+            Ty::Void | Ty::ClosureDecl | Ty::ClosureDef(_) => ShaderValue::Void,
+            Ty::Bool => ShaderValue::Bool(false),
+            Ty::Int => ShaderValue::Int(0),
+            Ty::Float | Ty::Enum(_) => ShaderValue::Float(0.0),
+            Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => ShaderValue::Bvec(ty.slots(), [false; 4]),
+            Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => ShaderValue::Ivec(ty.slots(), [0; 4]),
+            Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => ShaderValue::Vec(ty.slots(), [0.0; 4]),
+            Ty::Mat2 => ShaderValue::Mat(2, [0.0; 16]),
+            Ty::Mat3 => ShaderValue::Mat(3, [0.0; 16]),
+            Ty::Mat4 => ShaderValue::Mat(4, [0.0; 16]),
+            Ty::Texture2D | Ty::TextureOES => ShaderValue::Texture(0),
+            Ty::Array {elem_ty, len} => ShaderValue::Array(vec![Self::zero(elem_ty, shader_registry); *len]),
+            Ty::Struct(struct_ptr) => {
+                let struct_def = shader_registry.structs.get(struct_ptr).unwrap();
+                ShaderValue::Struct(*struct_ptr, struct_def.fields.iter().map( | field | {
+                    Self::zero(field.ty_expr.ty.borrow().as_ref().unwrap(), shader_registry)
+                }).collect())
+            }
+            Ty::DrawShader(_) => ShaderValue::DrawShader,
+        }
+    }
+
+    pub fn from_val(val: &Val) -> Self {
+        match val {
+            Val::Bool(v) => ShaderValue::Bool(*v),
+            Val::Int(v) => ShaderValue::Int(*v),
+            Val::Float(v) => ShaderValue::Float(*v),
+            Val::Vec4(v) => (*v).into(),
+        }
+    }
+
+    // reads a value the way it is packed in instance, geometry and uniform buffers
+    pub fn from_slots(ty: &Ty, slots: &[f32]) -> Self {
+        let n = ty.slots();
+        match ty {
+            Ty::Bool => ShaderValue::Bool(slots[0] != 0.0),
+            Ty::Int => ShaderValue::Int(slots[0] as i32),
+            Ty::Float | Ty::Enum(_) => ShaderValue::Float(slots[0]),
+            Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => {
+                let mut v = [false; 4];
+                for i in 0..n {
+                    v[i] = slots[i] != 0.0;
+                }
+                ShaderValue::Bvec(n, v)
+            }
+            Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => {
+                let mut v = [0; 4];
+                for i in 0..n {
+                    v[i] = slots[i] as i32;
+                }
+                ShaderValue::Ivec(n, v)
+            }
+            Ty::Vec2 | Ty::Vec3 | Ty::Vec4 => {
+                let mut v = [0.0; 4];
+                v[0..n].copy_from_slice(&slots[0..n]);
+                ShaderValue::Vec(n, v)
+            }
+            Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+                let mut v = [0.0; 16];
+                v[0..n].copy_from_slice(&slots[0..n]);
+                ShaderValue::Mat(if n == 4 {2} else if n == 9 {3} else {4}, v)
+            }
+            Ty::Array {elem_ty, len} => {
+                let elem_slots = elem_ty.slots();
+                ShaderValue::Array((0..*len).map( | i | {
+                    Self::from_slots(elem_ty, &slots[i * elem_slots..])
+                }).collect())
+            }
+            _ => ShaderValue::Void
+        }
+    }
+
+    // interpolates varyings across a triangle, non float values are taken from the first vertex
+    pub fn barycentric(values: [&ShaderValue; 3], weights: [f32; 3]) -> Self {
+        match values[0] {
+            ShaderValue::Float(_) | ShaderValue::Vec(..) | ShaderValue::Mat(..) => {
+                Self::map_floats(&values, | v | v[0] * weights[0] + v[1] * weights[1] + v[2] * weights[2])
+            }
+            ShaderValue::Array(items) => ShaderValue::Array((0..items.len()).map( | i | {
+                Self::barycentric([&values[0].index(i), &values[1].index(i), &values[2].index(i)], weights)
+            }).collect()),
+            value => value.clone()
+        }
+    }
+
+    pub fn to_bool(&self) -> bool {
+        match self {
+            ShaderValue::Bool(v) => *v,
+            ShaderValue::Int(v) => *v != 0,
+            ShaderValue::Float(v) => *v != 0.0,
+            _ => panic!()
+        }
+    }
+
+    pub fn to_f32(&self) -> f32 {
+        match self {
+            ShaderValue::Bool(v) => if *v {1.0} else {0.0},
+            ShaderValue::Int(v) => *v as f32,
+            ShaderValue::Float(v) => *v,
+            _ => panic!()
+        }
+    }
+
+    pub fn to_i32(&self) -> i32 {
+        match self {
+            ShaderValue::Bool(v) => *v as i32,
+            ShaderValue::Int(v) => *v,
+            ShaderValue::Float(v) => *v as i32,
+            _ => panic!()
+        }
+    }
+
+    pub fn to_vec2(&self) -> Vec2 {
+        let (_, v) = self.float_parts();
+        vec2(v[0], v[1])
+    }
+
+    pub fn to_vec4(&self) -> Vec4 {
+        let (_, v) = self.float_parts();
+        vec4(v[0], v[1], v[2], v[3])
+    }
+
+    pub fn to_texture(&self) -> usize {
+        match self {
+            ShaderValue::Texture(index) => *index,
+            _ => panic!()
+        }
+    }
+
+    fn is_int(&self) -> bool {
+        matches!(self, ShaderValue::Int(_) | ShaderValue::Ivec(..))
+    }
+
+    fn float_parts(&self) -> (Shape, [f32; 16]) {
+        let mut out = [0.0; 16];
+        match self {
+            ShaderValue::Bool(_) | ShaderValue::Int(_) | ShaderValue::Float(_) => {
+                out[0] = self.to_f32();
+                (Shape::Scalar, out)
+            }
+            ShaderValue::Bvec(n, v) => {
+                for i in 0..*n {
+                    out[i] = if v[i] {1.0} else {0.0};
+                }
+                (Shape::Vec(*n), out)
+            }
+            ShaderValue::Ivec(n, v) => {
+                for i in 0..*n {
+                    out[i] = v[i] as f32;
+                }
+                (Shape::Vec(*n), out)
+            }
+            ShaderValue::Vec(n, v) => {
+                out[0..*n].copy_from_slice(&v[0..*n]);
+                (Shape::Vec(*n), out)
+            }
+            ShaderValue::Mat(n, v) => (Shape::Mat(*n), *v),
+            _ => panic!()
+        }
+    }
+
+    fn from_float_parts(shape: Shape, parts: [f32; 16]) -> Self {
+        match shape {
+            Shape::Scalar => ShaderValue::Float(parts[0]),
+            Shape::Vec(n) => ShaderValue::Vec(n, [parts[0], parts[1], parts[2], parts[3]]),
+            Shape::Mat(n) => ShaderValue::Mat(n, parts),
+        }
+    }
+
+    // applies f per component, scalar args are splatted like GLSL does
+    fn map_floats(args: &[&ShaderValue], f: impl Fn(&[f32]) -> f32) -> Self {
+        let mut parts = [(Shape::Scalar, [0.0; 16]); 3];
+        let mut shape = Shape::Scalar;
+        for (i, arg) in args.iter().enumerate() {
+            parts[i] = arg.float_parts();
+            if shape == Shape::Scalar {
+                shape = parts[i].0;
+            }
+        }
+        let mut out = [0.0; 16];
+        let mut lane = [0.0; 3];
+        for (c, out) in out.iter_mut().enumerate().take(shape.len()) {
+            for (lane, part) in lane.iter_mut().zip(&parts[0..args.len()]) {
+                *lane = if part.0 == Shape::Scalar {part.1[0]} else {part.1[c]};
+            }
+            *out = f(&lane[0..args.len()]);
+        }
+        Self::from_float_parts(shape, out)
+    }
+
+    fn int_parts(&self) -> (usize, [i32; 4]) {
+        match self {
+            ShaderValue::Int(v) => (0, [*v; 4]),
+            ShaderValue::Ivec(n, v) => (*n, *v),
+            _ => panic!()
+        }
+    }
+
+    fn map_ints(&self, rest: &[ShaderValue], f: impl Fn(&[i32]) -> i32) -> Self {
+        let mut parts = [(0, [0; 4]); 3];
+        parts[0] = self.int_parts();
+        let mut n = parts[0].0;
+        for (i, arg) in rest.iter().enumerate() {
+            parts[i + 1] = arg.int_parts();
+            n = n.max(parts[i + 1].0);
+        }
+        let mut out = [0; 4];
+        let mut lane = [0; 3];
+        for (c, out) in out.iter_mut().enumerate().take(n.max(1)) {
+            for (lane, part) in lane.iter_mut().zip(&parts[0..rest.len() + 1]) {
+                *lane = part.1[if part.0 == 0 {0} else {c}];
+            }
+            *out = f(&lane[0..rest.len() + 1]);
+        }
+        if n == 0 {ShaderValue::Int(out[0])} else {ShaderValue::Ivec(n, out)}
+    }
+
+    fn dot(&self, other: &ShaderValue) -> f32 {
+        let (shape, a) = self.float_parts();
+        let (_, b) = other.float_parts();
+        (0..shape.len()).map( | i | a[i] * b[i]).sum()
+    }
+
+    fn arith(op: BinOp, left: &ShaderValue, right: &ShaderValue) -> Self {
+        if let BinOp::Mul = op {
+            match (left, right) {
+                (ShaderValue::Mat(n, a), ShaderValue::Mat(_, b)) => {
+                    let mut out = [0.0; 16];
+                    for c in 0..*n {
+                        for r in 0..*n {
+                            out[c * n + r] = (0..*n).map( | k | a[k * n + r] * b[c * n + k]).sum();
+                        }
+                    }
+                    return ShaderValue::Mat(*n, out)
+                }
+                (ShaderValue::Mat(n, m), ShaderValue::Vec(_, v)) => {
+                    let mut out = [0.0; 4];
+                    for r in 0..*n {
+                        out[r] = (0..*n).map( | c | m[c * n + r] * v[c]).sum();
+                    }
+                    return ShaderValue::Vec(*n, out)
+                }
+                (ShaderValue::Vec(_, v), ShaderValue::Mat(n, m)) => {
+                    let mut out = [0.0; 4];
+                    for c in 0..*n {
+                        out[c] = (0..*n).map( | r | v[r] * m[c * n + r]).sum();
+                    }
+                    return ShaderValue::Vec(*n, out)
+                }
+                _ => ()
+            }
+        }
+        if left.is_int() && right.is_int() {
+            return left.map_ints(std::slice::from_ref(right), | v | match op {
+                BinOp::Add => v[0].wrapping_add(v[1]),
+                BinOp::Sub => v[0].wrapping_sub(v[1]),
+                BinOp::Mul => v[0].wrapping_mul(v[1]),
+                _ => v[0].checked_div(v[1]).unwrap_or(0),
+            })
+        }
+        Self::map_floats(&[left, right], | v | match op {
+            BinOp::Add => v[0] + v[1],
+            BinOp::Sub => v[0] - v[1],
+            BinOp::Mul => v[0] * v[1],
+            _ => v[0] / v[1],
+        })
+    }
+
+    // the GLSL constructors, components of all args are concatenated
+    pub fn construct(ty_lit: TyLit, args: &[ShaderValue]) -> Self {
+        let mut comps = [0.0; 16];
+        let mut len = 0;
+        for arg in args {
+            let (shape, parts) = arg.float_parts();
+            for part in &parts[0..shape.len()] {
+                if len < 16 {
+                    comps[len] = *part;
+                    len += 1;
+                }
+            }
+        }
+        let splat = args.len() == 1 && len == 1;
+        let n = match ty_lit {
+            TyLit::Bvec2 | TyLit::Ivec2 | TyLit::Vec2 | TyLit::Mat2 => 2,
+            TyLit::Bvec3 | TyLit::Ivec3 | TyLit::Vec3 | TyLit::Mat3 => 3,
+            TyLit::Bvec4 | TyLit::Ivec4 | TyLit::Vec4 | TyLit::Mat4 => 4,
+            _ => 1
+        };
+        let comp = | i: usize | if splat {comps[0]} else {comps[i]};
+        match ty_lit {
+            TyLit::Bool => ShaderValue::Bool(comps[0] != 0.0),
+            TyLit::Int => ShaderValue::Int(comps[0] as i32),
+            TyLit::Float => ShaderValue::Float(comps[0]),
+            TyLit::Bvec2 | TyLit::Bvec3 | TyLit::Bvec4 => {
+                let mut v = [false; 4];
+                for (i, v) in v.iter_mut().enumerate().take(n) {
+                    *v = comp(i) != 0.0;
+                }
+                ShaderValue::Bvec(n, v)
+            }
+            TyLit::Ivec2 | TyLit::Ivec3 | TyLit::Ivec4 => {
+                let mut v = [0; 4];
+                for (i, v) in v.iter_mut().enumerate().take(n) {
+                    *v = comp(i) as i32;
+                }
+                ShaderValue::Ivec(n, v)
+            }
+            TyLit::Vec2 | TyLit::Vec3 | TyLit::Vec4 => {
+                let mut v = [0.0; 4];
+                for (i, v) in v.iter_mut().enumerate().take(n) {
+                    *v = comp(i);
+                }
+                ShaderValue::Vec(n, v)
+            }
+            TyLit::Mat2 | TyLit::Mat3 | TyLit::Mat4 => {
+                let mut v = [0.0; 16];
+                if let [ShaderValue::Mat(m, src)] = args {
+                    // matrix from matrix, the rest is filled from the identity
+                    for c in 0..n {
+                        for r in 0..n {
+                            v[c * n + r] = if c < *m && r < *m {src[c * m + r]} else if c == r {1.0} else {0.0};
+                        }
+                    }
+                }
+                else if splat {
+                    for i in 0..n {
+                        v[i * n + i] = comps[0];
+                    }
+                }
+                else {
+                    v[0..n * n].copy_from_slice(&comps[0..n * n]);
+                }
+                ShaderValue::Mat(n, v)
+            }
+            TyLit::Texture2D | TyLit::TextureOES => panic!()
+        }
+    }
+
+    fn field(&self, field_ident: Ident, shader_registry: &ShaderRegistry) -> Self {
+        match self {
+            ShaderValue::Struct(struct_ptr, values) => {
+                let struct_def = shader_registry.structs.get(struct_ptr).unwrap();
+                let index = struct_def.fields.iter().position( | field | field.ident == field_ident).unwrap();
+                values[index].clone()
+            }
+            _ => {
+                let swizzle = Swizzle::parse(field_ident).unwrap();
+                if swizzle.len() == 1 {
+                    return self.index(*swizzle.iter().next().unwrap())
+                }
+                let n = swizzle.len();
+                match self {
+                    ShaderValue::Bvec(_, v) => {
+                        let mut out = [false; 4];
+                        for (i, index) in swizzle.iter().enumerate() {
+                            out[i] = v[*index];
+                        }
+                        ShaderValue::Bvec(n, out)
+                    }
+                    ShaderValue::Ivec(_, v) => {
+                        let mut out = [0; 4];
+                        for (i, index) in swizzle.iter().enumerate() {
+                            out[i] = v[*index];
+                        }
+                        ShaderValue::Ivec(n, out)
+                    }
+                    ShaderValue::Vec(_, v) => {
+                        let mut out = [0.0; 4];
+                        for (i, index) in swizzle.iter().enumerate() {
+                            out[i] = v[*index];
+                        }
+                        ShaderValue::Vec(n, out)
+                    }
+                    _ => panic!()
+                }
+            }
+        }
+    }
+
+    fn set_field(&mut self, field_ident: Ident, value: ShaderValue, shader_registry: &ShaderRegistry) {
+        if let ShaderValue::Struct(struct_ptr, values) = self {
+            let struct_def = shader_registry.structs.get(struct_ptr).unwrap();
+            let index = struct_def.fields.iter().position( | field | field.ident == field_ident).unwrap();
+            values[index] = value;
+            return
+        }
+        let swizzle = Swizzle::parse(field_ident).unwrap();
+        if swizzle.len() == 1 {
+            self.set_index(*swizzle.iter().next().unwrap(), value);
+            return
+        }
+        for (i, index) in swizzle.iter().enumerate() {
+            self.set_index(*index, value.index(i));
+        }
+    }
+
+    fn index(&self, index: usize) -> Self {
+        match self {
+            ShaderValue::Bvec(_, v) => ShaderValue::Bool(v[index]),
+            ShaderValue::Ivec(_, v) => ShaderValue::Int(v[index]),
+            ShaderValue::Vec(_, v) => ShaderValue::Float(v[index]),
+            ShaderValue::Mat(n, v) => {
+                let mut out = [0.0; 4];
+                out[0..*n].copy_from_slice(&v[index * n..index * n + n]);
+                ShaderValue::Vec(*n, out)
+            }
+            ShaderValue::Array(items) => items[index].clone(),
+            _ => panic!()
+        }
+    }
+
+    fn set_index(&mut self, index: usize, value: ShaderValue) {
+        match self {
+            ShaderValue::Bvec(_, v) => v[index] = value.to_bool(),
+            ShaderValue::Ivec(_, v) => v[index] = value.to_i32(),
+            ShaderValue::Vec(_, v) => v[index] = value.to_f32(),
+            ShaderValue::Mat(n, v) => {
+                let (_, parts) = value.float_parts();
+                v[index * *n..index * *n + *n].copy_from_slice(&parts[0..*n]);
+            }
+            ShaderValue::Array(items) => items[index] = value,
+            _ => panic!()
+        }
+    }
+
+    fn inverse(&self) -> Self {
+        let (shape, m) = self.float_parts();
+        let n = if let Shape::Mat(n) = shape {n} else {panic!()};
+        // gauss-jordan on the rows of [m | identity]
+        let mut a = [[0.0f32; 8]; 4];
+        for r in 0..n {
+            for c in 0..n {
+                a[r][c] = m[c * n + r];
+            }
+            a[r][n + r] = 1.0;
+        }
+        for col in 0..n {
+            let pivot = (col..n).max_by( | x, y | a[*x][col].abs().partial_cmp(&a[*y][col].abs()).unwrap()).unwrap();
+            a.swap(col, pivot);
+            let p = a[col][col];
+            if p == 0.0 {
+                return ShaderValue::Mat(n, [0.0; 16])
+            }
+            for v in &mut a[col][0..2 * n] {
+                *v /= p;
+            }
+            let pivot_row = a[col];
+            for (r, row) in a.iter_mut().enumerate().take(n) {
+                if r != col {
+                    let f = row[col];
+                    for (v, p) in row[0..2 * n].iter_mut().zip(&pivot_row) {
+                        *v -= f * p;
+                    }
+                }
+            }
+        }
+        let mut out = [0.0; 16];
+        for r in 0..n {
+            for c in 0..n {
+                out[c * n + r] = a[r][n + c];
+            }
+        }
+        ShaderValue::Mat(n, out)
+    }
+}
+
+impl From<Vec2> for ShaderValue {
+    fn from(v: Vec2) -> Self {ShaderValue::Vec(2, [v.x, v.y, 0.0, 0.0])}
+}
+
+impl From<Vec3> for ShaderValue {
+    fn from(v: Vec3) -> Self {ShaderValue::Vec(3, [v.x, v.y, v.z, 0.0])}
+}
+
+impl From<Vec4> for ShaderValue {
+    fn from(v: Vec4) -> Self {ShaderValue::Vec(4, [v.x, v.y, v.z, v.w])}
+}
+
+impl From<Mat4> for ShaderValue {
+    fn from(m: Mat4) -> Self {ShaderValue::Mat(4, m.v)}
+}
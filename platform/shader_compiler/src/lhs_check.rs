@@ -68,8 +68,9 @@ impl<'a> LhsChecker<'a> {
             ExprKind::Var {
                 span,
                 ref kind,
+                ident,
                 ..
-            } => self.lhs_check_var_expr(span, kind),
+            } => self.lhs_check_var_expr(span, kind, ident),
             ExprKind::Lit {span, lit} => self.lhs_check_lit_expr(span, lit),
         }
     }
@@ -82,6 +83,7 @@ impl<'a> LhsChecker<'a> {
             origin: live_error_origin!(),
             span:span.into(),
             message: String::from("expression is not a valid left hand side"),
+            notes: Vec::new(),
         });
     }
     
@@ -96,6 +98,7 @@ impl<'a> LhsChecker<'a> {
             origin: live_error_origin!(),
             span:span.into(),
             message: String::from("expression is not a valid left hand side"),
+            notes: Vec::new(),
         });
     }
     
@@ -110,6 +113,7 @@ impl<'a> LhsChecker<'a> {
             origin:live_error_origin!(),
             span:span.into(),
             message: String::from("expression is not a valid left hand side"),
+            notes: Vec::new(),
         });
     }
     
@@ -118,6 +122,7 @@ impl<'a> LhsChecker<'a> {
             origin:live_error_origin!(),
             span:span.into(),
             message: String::from("expression is not a valid left hand side"),
+            notes: Vec::new(),
         });
     }
     
@@ -129,6 +134,7 @@ impl<'a> LhsChecker<'a> {
             origin:live_error_origin!(),
             span:span.into(),
             message: String::from("expression is not a valid left hand side"),
+            notes: Vec::new(),
         });
     }
     
@@ -151,6 +157,7 @@ impl<'a> LhsChecker<'a> {
                             origin:live_error_origin!(),
                             span:span.into(),
                             message: String::from("Can only assign to varying values for shader self"),
+                            notes: Vec::new(),
                         })
                     }
                 }
@@ -180,6 +187,7 @@ impl<'a> LhsChecker<'a> {
             origin:live_error_origin!(),
             span:span.into(),
             message: String::from("expression is not a valid left hand side"),
+            notes: Vec::new(),
         });
     }
     
@@ -187,16 +195,22 @@ impl<'a> LhsChecker<'a> {
         &mut self,
         span: TokenSpan,
         kind: &Cell<Option<VarKind >>,
+        ident: Option<Ident>,
     ) -> Result<(), LiveError> {
         if let VarKind::MutLocal{..} = kind.get().unwrap(){
             Ok(())
         }
         else{
-            Err(LiveError {
+            let mut err = LiveError {
                 origin:live_error_origin!(),
                 span:span.into(),
                 message: String::from("expression is not a valid left hand side"),
-            })
+                notes: Vec::new(),
+            };
+            if let Some(scopesym) = ident.and_then( | ident | self.scopes.find_sym_on_scopes(ident, span)) {
+                err = err.with_note(scopesym.span, format!("`{}` is declared here and can't be assigned to", scopesym.sym.ident));
+            }
+            Err(err)
         }
     }
     /*
@@ -210,6 +224,7 @@ impl<'a> LhsChecker<'a> {
         return Err(LiveError {
             span,
             message: String::from("liveid is not a valid left hand side"),
+            notes: Vec::new(),
         });
     }*/
     
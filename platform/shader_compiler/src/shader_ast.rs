@@ -677,6 +677,7 @@ impl Scopes {
         
         if let Some(item) = self.scopes.last_mut().unwrap().get_mut(&ident) {
             item.sym.shadow = ScopeSymShadow(item.sym.shadow.0 + 1);
+            item.span = span;
             item.kind = sym_kind;
             item.sym.shadow
         }
@@ -1121,7 +1122,8 @@ impl Ty {
                         return Err(LiveError {
                             origin: live_error_origin!(),
                             message: format!("Expression return type does not resolve to a shader {:?}", v),
-                            span: nodes[index].origin.token_id().unwrap().into()
+                            span: nodes[index].origin.token_id().unwrap().into(),
+                            notes: Vec::new()
                         })
                     }
                 }
@@ -1139,7 +1141,8 @@ impl Ty {
                     return Err(LiveError {
                         origin: live_error_origin!(),
                         message: format!("Id does not resolve to a shader type {}", id),
-                        span: nodes[index].origin.token_id().unwrap().into()
+                        span: nodes[index].origin.token_id().unwrap().into(),
+                        notes: Vec::new()
                     })
                 }
             }
@@ -1154,7 +1157,8 @@ impl Ty {
             _ => return Err(LiveError {
                 origin: live_error_origin!(),
                 message: format!("Live value {:?} does not resolve to a shader type", nodes[index].value),
-                span: nodes[index].origin.token_id().unwrap().into()
+                span: nodes[index].origin.token_id().unwrap().into(),
+                notes: Vec::new()
             })
         })
     }
@@ -106,6 +106,7 @@ impl<'a> ShaderParser<'a> {
             origin,
             span: self.token_with_span.span.into(),
             message,
+            notes: Vec::new()
         }
     }
     
@@ -1435,6 +1436,7 @@ impl SpanTracker {
                 end: parser.token_end(),
             }.into(),
             message,
+            notes: Vec::new()
         }
     }
 }
\ No newline at end of file
@@ -207,7 +207,8 @@ impl ShaderRegistry {
                                 LiveError {
                                     origin: live_error_origin!(),
                                     message: format!("Type of eval result not valid for shader"),
-                                    span: nodes[index].origin.token_id().unwrap().into()
+                                    span: nodes[index].origin.token_id().unwrap().into(),
+                                    notes: Vec::new()
                                 }
                             );
                         }
@@ -425,7 +426,8 @@ impl ShaderRegistry {
                                     return Err(LiveError {
                                         origin: live_error_origin!(),
                                         span: prop.origin.token_id().unwrap().into(),
-                                        message: format!("Unexpected DSL node")
+                                        message: format!("Unexpected DSL node"),
+                                        notes: Vec::new()
                                     })
                                     /*
                                     let def = parser.expect_field(Ident(id), VarDefPtr(prop_ptr)) ?;
@@ -451,7 +453,8 @@ impl ShaderRegistry {
                                 return Err(LiveError {
                                     origin: live_error_origin!(),
                                     span: prop.origin.token_id().unwrap().into(),
-                                    message: format!("Type not found for struct field {}", type_name)
+                                    message: format!("Type not found for struct field {}", type_name),
+                                    notes: Vec::new()
                                 })
                             }
                         },
@@ -459,7 +462,8 @@ impl ShaderRegistry {
                             return Err(LiveError {
                                 origin: live_error_origin!(),
                                 span: prop.origin.token_id().unwrap().into(),
-                                message: format!("Cannot use {:?} in struct", prop.value)
+                                message: format!("Cannot use {:?} in struct", prop.value),
+                                notes: Vec::new()
                             })
                         }
                     }
@@ -552,7 +556,8 @@ impl ShaderRegistry {
                                 return Err(LiveError {
                                     origin: live_error_origin!(),
                                     span: prop.origin.token_id().unwrap().into(),
-                                    message: format!("Can only support field colon : values don't use =")
+                                    message: format!("Can only support field colon : values don't use ="),
+                                    notes: Vec::new()
                                 })
                             }
                             if prop.id == live_id!(size){
@@ -662,7 +667,8 @@ impl ShaderRegistry {
                                     return Err(LiveError {
                                         origin: live_error_origin!(),
                                         span: first_def.into(),
-                                        message: format!("Unexpected variable prefix {:?}", before)
+                                        message: format!("Unexpected variable prefix {:?}", before),
+                                        notes: Vec::new()
                                     })
                                 }
                             };
@@ -711,7 +717,8 @@ impl ShaderRegistry {
                                     return Err(LiveError {
                                         origin: live_error_origin!(),
                                         span: token.span.into(),
-                                        message: format!("Unexpected in shader body {}", token)
+                                        message: format!("Unexpected in shader body {}", token),
+                                        notes: Vec::new()
                                     })
                                     /*
                                     let decl = parser.expect_self_decl(Ident(prop.id), prop_ptr) ?;
@@ -760,7 +767,8 @@ impl ShaderRegistry {
                             return Err(LiveError {
                                 origin: live_error_origin!(),
                                 span: field_a.span.into(),
-                                message: format!("Field double declaration  {}",field_a.ident)
+                                message: format!("Field double declaration  {}",field_a.ident),
+                                notes: Vec::new()
                             })
                         }
                     }
@@ -772,7 +780,8 @@ impl ShaderRegistry {
                     return Err(LiveError {
                         origin: live_error_origin!(),
                         span: class_node.origin.token_id().unwrap().into(),
-                        message: format!("analyse_draw_shader missing vertex method")
+                        message: format!("analyse_draw_shader missing vertex method"),
+                        notes: Vec::new()
                     })
                 }
                 
@@ -780,7 +789,8 @@ impl ShaderRegistry {
                     return Err(LiveError {
                         origin: live_error_origin!(),
                         span: class_node.origin.token_id().unwrap().into(),
-                        message: format!("analyse_draw_shader missing pixel method")
+                        message: format!("analyse_draw_shader missing pixel method"),
+                        notes: Vec::new()
                     })
                 }
                 
@@ -803,7 +813,8 @@ impl ShaderRegistry {
             x => return Err(LiveError {
                 origin: live_error_origin!(),
                 span: class_node.origin.token_id().unwrap().into(),
-                message: format!("analyse_draw_shader could not find shader class {:?}", x)
+                message: format!("analyse_draw_shader could not find shader class {:?}", x),
+                notes: Vec::new()
             })
         }
     }
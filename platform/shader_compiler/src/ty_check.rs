@@ -11,11 +11,22 @@ use{
         shader_ast::*,
         lhs_check::LhsChecker,
         swizzle::Swizzle,
-        util::CommaSep,
+        util::{CommaSep, did_you_mean},
         shader_registry::ShaderRegistry
     }
 };
 
+const MAX_CANDIDATE_NOTES: usize = 8;
+
+pub fn with_suggestion(err: LiveError, span: TokenSpan, suggestion: Option<LiveId>) -> LiveError {
+    if let Some(suggestion) = suggestion {
+        err.with_note(span, format!("did you mean `{}`?", suggestion))
+    }
+    else {
+        err
+    }
+}
+
 #[derive(Clone)]
 pub struct TyChecker<'a> {
     pub scopes: &'a Scopes,
@@ -72,23 +83,30 @@ impl<'a> TyChecker<'a> {
         Ok(ty_lit.to_ty())
     }
     
+    // expected_span points at whatever imposed the expected type, like a type annotation
     pub fn ty_check_expr_with_expected_ty(
         &mut self,
         span: TokenSpan,
         expr: &Expr,
         expected_ty: &Ty,
+        expected_span: Option<TokenSpan>,
     ) -> Result<Ty, LiveError> {
         let actual_ty = self.ty_check_expr(expr) ?;
         if &actual_ty != expected_ty {
-            return Err(LiveError {
+            let mut err = LiveError {
                 origin: live_error_origin!(),
                 span:span.into(),
                 message: format!(
-                    "can't match expected type `{}` with actual type `{}",
+                    "can't match expected type `{}` with actual type `{}`",
                     expected_ty,
                     actual_ty
                 ),
-            });
+                notes: Vec::new(),
+            };
+            if let Some(expected_span) = expected_span {
+                err = err.with_note(expected_span, format!("expected `{}` because of this", expected_ty));
+            }
+            return Err(err.with_note(expr.span, format!("found `{}` here", actual_ty)));
         }
         Ok(actual_ty)
     }
@@ -181,9 +199,9 @@ impl<'a> TyChecker<'a> {
         expr_if_true: &Expr,
         expr_if_false: &Expr,
     ) -> Result<Ty, LiveError> {
-        self.ty_check_expr_with_expected_ty(span, expr, &Ty::Bool) ?;
+        self.ty_check_expr_with_expected_ty(span, expr, &Ty::Bool, None) ?;
         let ty_if_true = self.ty_check_expr(expr_if_true) ?;
-        self.ty_check_expr_with_expected_ty(span, expr_if_false, &ty_if_true) ?;
+        self.ty_check_expr_with_expected_ty(span, expr_if_false, &ty_if_true, Some(expr_if_true.span)) ?;
         Ok(ty_if_true)
     }
     
@@ -356,13 +374,29 @@ impl<'a> TyChecker<'a> {
             origin: live_error_origin!(),
             span:span.into(),
             message: format!(
-                "can't apply binary operator `{}` to operands of type `{}` and `{}",
+                "can't apply binary operator `{}` to operands of type `{}` and `{}`",
                 op,
                 left_ty,
                 right_ty
             )
                 .into(),
-        })
+            notes: self.var_decl_note(left_expr).into_iter().collect(),
+        }
+            .with_note(left_expr.span, format!("this is `{}`", left_ty))
+            .with_note(right_expr.span, format!("this is `{}`", right_ty)))
+    }
+
+    // points at the declaration of a local variable, if expr is one
+    pub fn var_decl_note(&self, expr: &Expr) -> Option<LiveErrorNote> {
+        if let ExprKind::Var {ident: Some(ident), span, ..} = &expr.kind {
+            if let Some(scopesym) = self.scopes.find_sym_on_scopes(*ident, *span) {
+                return Some(LiveErrorNote {
+                    span: scopesym.span.into(),
+                    message: format!("`{}` declared here", ident)
+                })
+            }
+        }
+        None
     }
     
     fn ty_check_un_expr(&mut self, span: TokenSpan, op: UnOp, expr: &Expr) -> Result<Ty, LiveError> {
@@ -390,6 +424,7 @@ impl<'a> TyChecker<'a> {
                 ty
             )
                 .into(),
+            notes: Vec::new(),
         })
     }
     
@@ -415,6 +450,7 @@ impl<'a> TyChecker<'a> {
                             origin: live_error_origin!(),
                             span,
                             message: format!("Cannot pass closures to closures, please implement"),
+                            notes: Vec::new(),
                         })
                     }
                     outer_param_index.set(Some(*param_index));
@@ -428,6 +464,7 @@ impl<'a> TyChecker<'a> {
             origin: live_error_origin!(),
             span,
             message: format!("Closure call `{}` is not defined on", ident),
+            notes: Vec::new(),
         })
     }
     */
@@ -457,6 +494,7 @@ impl<'a> TyChecker<'a> {
                                 origin: live_error_origin!(),
                                 span:span.into(),
                                 message: format!("Cannot pass closures to closures, please implement"),
+                                notes: Vec::new(),
                             })
                         }
                         outer_param_index.set(Some(*param_index));
@@ -477,11 +515,17 @@ impl<'a> TyChecker<'a> {
             // lets return the right ty
             return Ok(fn_def.return_ty.borrow().clone().unwrap())
         }
-        return Err(LiveError {
+        let ident = ident.unwrap();
+        let err = LiveError {
             origin: live_error_origin!(),
             span:span.into(),
-            message: format!("Function not found {}", ident.unwrap()),
-        }) 
+            message: format!("Function not found {}", ident),
+            notes: Vec::new(),
+        };
+        let candidates = self.shader_registry.builtins.keys()
+            .chain(self.scopes.scopes.iter().flat_map( | scope | scope.keys()))
+            .map( | ident | ident.0);
+        return Err(with_suggestion(err, span, did_you_mean(ident.0, candidates)))
     }
     
     fn ty_check_method_call_expr(
@@ -514,6 +558,7 @@ impl<'a> TyChecker<'a> {
                         origin: live_error_origin!(),
                         span:span.into(),
                         message: format!("shader method `{}` is not type checked `{}`", ident, ty),
+                        notes: Vec::new(),
                     });
                 }
             },
@@ -537,16 +582,26 @@ impl<'a> TyChecker<'a> {
                         origin: live_error_origin!(),
                         span:span.into(),
                         message: format!("struct method `{}` is not type checked `{}`", ident, ty),
+                        notes: Vec::new(),
                     });
                 }
             },
             _ => ()
         }
-        Err(LiveError {
+        let methods = match ty {
+            Ty::DrawShader(shader_ptr) => &self.shader_registry.draw_shader_defs.get(&shader_ptr).unwrap().methods,
+            Ty::Struct(struct_ptr) => &self.shader_registry.structs.get(&struct_ptr).unwrap().methods,
+            _ => &Vec::new()
+        };
+        let candidates = methods.iter().map( | fn_ptr | self.shader_registry.all_fns.get(fn_ptr).unwrap().ident.0);
+        let suggestion = did_you_mean(ident.0, candidates);
+        let err = LiveError {
             origin: live_error_origin!(),
             span:span.into(),
             message: format!("method `{}` is not defined on type `{}`", ident, ty),
-        })
+            notes: Vec::new(),
+        };
+        Err(with_suggestion(err, span, suggestion))
     }
     
     
@@ -579,11 +634,26 @@ impl<'a> TyChecker<'a> {
             )
                 .unwrap();
             let mut sep = "";
-            for arg_ty in arg_tys {
+            for arg_ty in &arg_tys {
                 write!(message, "{}{}", sep, arg_ty).unwrap();
                 sep = ", ";
             }
-            LiveError {origin: live_error_origin!(), span:span.into(), message}
+            // list the overloads, the ones taking the same number of args first
+            let mut candidates = builtin.return_tys.iter().map( | (param_tys, return_ty) | {
+                (
+                    param_tys.len() != arg_tys.len(),
+                    format!("candidate: `{}({}) -> {}`", ident, CommaSep(param_tys), return_ty)
+                )
+            }).collect::<Vec<_ >> ();
+            candidates.sort();
+            let mut err = LiveError {origin: live_error_origin!(), span:span.into(), message, notes: Vec::new()};
+            for (_, candidate) in candidates.iter().take(MAX_CANDIDATE_NOTES) {
+                err = err.with_note(span, candidate.clone());
+            }
+            if candidates.len() > MAX_CANDIDATE_NOTES {
+                err = err.with_note(span, format!("and {} other candidates", candidates.len() - MAX_CANDIDATE_NOTES));
+            }
+            err
         }) ? .clone())
     }
     
@@ -599,8 +669,9 @@ impl<'a> TyChecker<'a> {
            Err(err)=> Err(LiveError {
                 origin: live_error_origin!(),
                 span:span.into(),
-                message: format!("function: `{}`: {}", self.shader_registry.fn_ident_from_ptr(self.live_registry, fn_ptr), err.message)
-            }),
+                message: format!("function: `{}`: {}", self.shader_registry.fn_ident_from_ptr(self.live_registry, fn_ptr), err.message),
+                notes: err.notes
+            }.with_note(fn_def.span, format!("`{}` defined here", fn_def.ident))),
             Ok(closure_args)=>{
                 if closure_args.len()>0{
                     let mut ci = self.scopes.closure_sites.borrow_mut();
@@ -608,7 +679,8 @@ impl<'a> TyChecker<'a> {
                         return Err(LiveError {
                             origin: live_error_origin!(),
                             span:span.into(),
-                            message: format!("Closures not supported here {}", self.shader_registry.fn_ident_from_ptr(self.live_registry, fn_ptr))
+                            message: format!("Closures not supported here {}", self.shader_registry.fn_ident_from_ptr(self.live_registry, fn_ptr)),
+                            notes: Vec::new()
                         });
                     }
                     closure_site_index.unwrap().set(Some(ci.len()));
@@ -639,6 +711,7 @@ impl<'a> TyChecker<'a> {
                     arg_exprs.len(),
                 )
                     .into(),
+                notes: Vec::new(),
             });
         }
         if arg_exprs.len() > params.len() {
@@ -651,6 +724,7 @@ impl<'a> TyChecker<'a> {
                     arg_exprs.len()
                 )
                     .into(),
+                notes: Vec::new(),
             });
         }
         let mut closure_args= Vec::new();
@@ -682,8 +756,11 @@ impl<'a> TyChecker<'a> {
                         param_index + 1,
                         param_ty,
                         arg_ty,
-                    ).into()
-                });
+                    ).into(),
+                    notes: Vec::new()
+                }
+                    .with_note(arg_expr.span, format!("found `{}` here", arg_ty))
+                    .with_note(param.span, format!("parameter `{}` declared as `{}`", param.ident, param_ty)));
             }
             if param.is_inout {
                 self.lhs_checker().lhs_check_expr(arg_expr) ?;
@@ -721,6 +798,7 @@ impl<'a> TyChecker<'a> {
                     origin: live_error_origin!(),
                     span:span.into(),
                     message: format!("field `{}` is not defined on type `{}`", field_ident, ty),
+                    notes: Vec::new(),
                 }) ?;
                 Ok(match ty {
                     Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => match swizzle.len() {
@@ -748,23 +826,36 @@ impl<'a> TyChecker<'a> {
                 })
             }
             Ty::Struct(struct_ptr) => {
-                Ok(self.shader_registry.structs.get(&struct_ptr) .unwrap() .find_field(field_ident) .ok_or(LiveError {
-                    origin: live_error_origin!(),
-                    span:span.into(),
-                    message: format!("field `{}` is not defined on type `{:?}`", field_ident, struct_ptr),
+                let struct_def = self.shader_registry.structs.get(&struct_ptr).unwrap();
+                Ok(struct_def.find_field(field_ident) .ok_or_else( || {
+                    let suggestion = did_you_mean(field_ident.0, struct_def.fields.iter().map( | field | field.ident.0));
+                    let err = LiveError {
+                        origin: live_error_origin!(),
+                        span:span.into(),
+                        message: format!("field `{}` is not defined on type `{:?}`", field_ident, struct_ptr),
+                        notes: Vec::new(),
+                    }.with_note(struct_def.span, "struct defined here".to_string());
+                    with_suggestion(err, span, suggestion)
                 }) ? .ty_expr .ty .borrow() .as_ref() .unwrap() .clone())
             },
             Ty::DrawShader(shader_ptr) => {
-                Ok(self.shader_registry.draw_shader_defs.get(&shader_ptr).unwrap().find_field(field_ident) .ok_or(LiveError {
-                    origin: live_error_origin!(),
-                    span:span.into(),
-                    message: format!("field `{}` is not defined on shader `{:?}`", field_ident, shader_ptr),
+                let draw_shader_def = self.shader_registry.draw_shader_defs.get(&shader_ptr).unwrap();
+                Ok(draw_shader_def.find_field(field_ident) .ok_or_else( || {
+                    let suggestion = did_you_mean(field_ident.0, draw_shader_def.fields.iter().map( | field | field.ident.0));
+                    let err = LiveError {
+                        origin: live_error_origin!(),
+                        span:span.into(),
+                        message: format!("field `{}` is not defined on shader `{:?}`", field_ident, shader_ptr),
+                        notes: Vec::new(),
+                    };
+                    with_suggestion(err, span, suggestion)
                 }) ? .ty_expr .ty .borrow() .as_ref() .unwrap() .clone())
             }
             _ => Err(LiveError {
                 origin: live_error_origin!(),
                 span:span.into(),
                 message: format!("can't access field on value of type `{}`", ty).into(),
+                notes: Vec::new(),
             }),
         }
     }
@@ -789,6 +880,7 @@ impl<'a> TyChecker<'a> {
                     origin: live_error_origin!(),
                     span:span.into(),
                     message: format!("can't index into value of type `{}`", ty).into(),
+                    notes: Vec::new(),
                 })
             }
         };
@@ -797,6 +889,7 @@ impl<'a> TyChecker<'a> {
                 origin: live_error_origin!(),
                 span:span.into(),
                 message: "index is not an integer".into(),
+                notes: Vec::new(),
             });
         }
         Ok(elem_ty)
@@ -846,7 +939,8 @@ impl<'a> TyChecker<'a> {
                             actual_slots,
                             expected_slots,
                         )
-                            .into()
+                            .into(),
+                        notes: Vec::new()
                     });
                 }
                 if actual_slots > expected_slots {
@@ -860,6 +954,7 @@ impl<'a> TyChecker<'a> {
                             actual_slots,
                         )
                             .into(),
+                        notes: Vec::new(),
                     });
                 }
                 Ok(ty.clone())
@@ -873,6 +968,7 @@ impl<'a> TyChecker<'a> {
                     CommaSep(&arg_tys)
                 )
                     .into(),
+                notes: Vec::new(),
             }),
         }
     }
@@ -906,6 +1002,7 @@ impl<'a> TyChecker<'a> {
                                 origin: live_error_origin!(),
                                 span:span.into(),
                                 message: format!("`{}` is a closure and cannot be used as a variable", ident),
+                                notes: Vec::new(),
                             })
                         }
                     }
@@ -924,15 +1021,21 @@ impl<'a> TyChecker<'a> {
                     origin: live_error_origin!(),
                     span:span.into(),
                     message: format!("`{}` implement using functions as closure args", ident.unwrap()),
+                    notes: Vec::new(),
                 })
             }
             VarResolve::NotFound => {
                  
-                return Err(LiveError {
+                let ident = ident.unwrap();
+                let candidates = self.scopes.scopes.iter().flat_map( | scope | scope.keys()).map( | ident | ident.0);
+                let suggestion = did_you_mean(ident.0, candidates);
+                let err = LiveError {
                     origin: live_error_origin!(),
                     span:span.into(),
-                    message: format!("`{}` is not defined in this scope", ident.unwrap()),
-                })
+                    message: format!("`{}` is not defined in this scope", ident),
+                    notes: Vec::new(),
+                };
+                return Err(with_suggestion(err, span, suggestion))
             }
         }
     }
@@ -957,15 +1060,19 @@ impl<'a> TyChecker<'a> {
                         origin: live_error_origin!(),
                         span:span.into(),
                         message: format!("field `{}` is the wrong type {} instead of {}", ident, my_ty.as_ref().unwrap(), field_ty.as_ref().unwrap()),
+                        notes: Vec::new(),
                     })
                 }
             }
             else {
-                return Err(LiveError {
+                let suggestion = did_you_mean(ident.0, struct_decl.fields.iter().map( | field | field.ident.0));
+                let err = LiveError {
                     origin: live_error_origin!(),
                     span:span.into(),
                     message: format!("`{}` is not a valid struct field", ident),
-                })
+                    notes: Vec::new(),
+                };
+                return Err(with_suggestion(err, span, suggestion))
             }
         }
         // if we are missing idents or have doubles, error
@@ -975,6 +1082,7 @@ impl<'a> TyChecker<'a> {
                     origin: live_error_origin!(),
                     span:span.into(),
                     message: format!("`{}` field is missing", field.ident),
+                    notes: Vec::new(),
                 })
             }
         }
@@ -985,6 +1093,7 @@ impl<'a> TyChecker<'a> {
                         origin: live_error_origin!(),
                         span:span.into(),
                         message: format!("`{}` field is duplicated", args[i].0),
+                        notes: Vec::new(),
                     })
                 }
             }
@@ -1,4 +1,7 @@
-use std::fmt;
+use {
+    std::fmt,
+    crate::makepad_live_id::LiveId,
+};
 
 pub struct CommaSep<'a, T>(pub &'a [T]);
 
@@ -16,3 +19,100 @@ where
     }
 }
 
+
+// finds the candidate closest to ident by edit distance, for 'did you mean' notes
+pub fn did_you_mean<I>(ident: LiveId, candidates: I) -> Option<LiveId>
+where
+    I: IntoIterator<Item = LiveId>,
+{
+    let ident_str = interned_string(ident)?;
+    let max_dist = (ident_str.chars().count() / 3).max(1);
+    let mut best = None;
+    for candidate in candidates {
+        if candidate == ident {
+            continue;
+        }
+        // ids that were never interned only print as hex, there is nothing to suggest
+        let candidate_str = match interned_string(candidate) {
+            Some(candidate_str) => candidate_str,
+            None => continue
+        };
+        let dist = edit_distance(&ident_str, &candidate_str);
+        match best {
+            Some((best_dist, _)) if dist >= best_dist => (),
+            _ if dist <= max_dist => best = Some((dist, candidate)),
+            _ => ()
+        }
+    }
+    best.map( | (_, candidate) | candidate)
+}
+
+fn interned_string(id: LiveId) -> Option<String> {
+    id.as_string( | s | s.map( | s | s.to_string()))
+}
+
+// levenshtein distance where swapping two adjacent chars also counts as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] {0} else {1};
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_swaps_as_one() {
+        assert_eq!(edit_distance("color", "color"), 0);
+        assert_eq!(edit_distance("color", "colour"), 1);
+        assert_eq!(edit_distance("colour", "color"), 1);
+        assert_eq!(edit_distance("pos", "pso"), 1);
+        assert_eq!(edit_distance("rect_pos", "rect_size"), 4);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("sdf", ""), 3);
+    }
+
+    #[test]
+    fn did_you_mean_picks_the_closest_interned_candidate() {
+        let ids = | names: &[&str] | names.iter().map( | name | LiveId::from_str_with_lut(name).unwrap()).collect::<Vec<_>>();
+        let candidates = ids(&["rect_pos", "rect_size", "colour", "color_hover"]);
+        let ident = LiveId::from_str_with_lut("color").unwrap();
+        assert_eq!(did_you_mean(ident, candidates.iter().cloned()), Some(candidates[2]));
+        let ident = LiveId::from_str_with_lut("rect_pso").unwrap();
+        assert_eq!(did_you_mean(ident, candidates.iter().cloned()), Some(candidates[0]));
+        // too far from everything, and the ident itself is not a suggestion
+        let ident = LiveId::from_str_with_lut("gradient").unwrap();
+        assert_eq!(did_you_mean(ident, candidates.iter().cloned()), None);
+        assert_eq!(did_you_mean(candidates[1], candidates[1..2].iter().cloned()), None);
+        // short names allow a single edit
+        let ident = LiveId::from_str_with_lut("xy").unwrap();
+        assert_eq!(did_you_mean(ident, ids(&["xz", "zw"])), Some(ids(&["xz"])[0]));
+    }
+
+    #[test]
+    fn did_you_mean_skips_ids_that_were_never_interned() {
+        // ids made without the lut have no string to compare, even when their hash is close
+        let ident = LiveId::from_str_with_lut("shade").unwrap();
+        assert_eq!(did_you_mean(ident, [LiveId::from_str("shadf")]), None);
+        assert_eq!(did_you_mean(LiveId::from_str("shadf"), [ident]), None);
+        let interned = LiveId::from_str_with_lut("shaded").unwrap();
+        assert_eq!(did_you_mean(ident, [LiveId::from_str("shadf"), interned]), Some(interned));
+    }
+}
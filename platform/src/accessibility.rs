@@ -0,0 +1,408 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_math::{Rect, dvec2},
+        draw_list::DrawListId,
+        cx::Cx,
+    }
+};
+
+/// What a widget is to a screen reader.
+#[derive(Clone, Copy, Debug, PartialEq, SerJson, DeJson)]
+pub enum AccessRole {
+    Window,
+    Label,
+    Button,
+    CheckBox,
+    TextInput,
+    Slider,
+    SpinButton,
+    DateEditor,
+    List,
+    Table,
+    Tree,
+}
+
+#[derive(Clone, Debug, PartialEq, SerJson, DeJson)]
+pub enum AccessValue {
+    None,
+    Text(String),
+    Number {value: f64, min: f64, max: f64, step: f64},
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, SerJson, DeJson)]
+pub struct AccessState {
+    pub focusable: bool,
+    pub focused: bool,
+    pub checked: Option<bool>,
+    pub disabled: bool,
+    pub read_only: bool,
+}
+
+/// The semantic description of a single widget. Widgets hand these out from
+/// `Widget::access_node` and the draw tree nests them in draw order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessNode {
+    pub role: AccessRole,
+    pub name: String,
+    pub value: AccessValue,
+    pub state: AccessState,
+    /// Window relative for everything but the window node, which holds its screen rect
+    pub rect: Rect,
+}
+
+impl AccessNode {
+    pub fn new(role: AccessRole, name: &str) -> Self {
+        Self {
+            role,
+            name: name.to_string(),
+            value: AccessValue::None,
+            state: AccessState::default(),
+            rect: Rect::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum AccessItem {
+    Begin(u64, AccessNode),
+    End,
+    Child(DrawListId),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CxAccessList {
+    pub items: Vec<AccessItem>
+}
+
+#[derive(Clone, Copy, Debug)]
+enum AccessSlot {
+    List(DrawListId, usize),
+    Root(usize),
+}
+
+#[derive(Clone, Debug)]
+struct CxAccessRoot {
+    id: u64,
+    node: AccessNode,
+    draw_list_id: Option<DrawListId>,
+    redraw_id: u64,
+}
+
+/// The accessibility tree is kept per drawlist just like the nav tree, so a drawlist
+/// that doesn't redraw keeps its nodes and only the redrawn parts get replaced.
+/// Nodes that open before any drawlist exists (windows) are kept as roots.
+#[derive(Default)]
+pub struct CxAccessibility {
+    lists: Vec<CxAccessList>,
+    roots: Vec<CxAccessRoot>,
+    stack: Vec<(u64, AccessSlot)>,
+    redraw_id: u64,
+    changed: bool,
+}
+
+impl CxAccessibility {
+    fn list_mut(&mut self, draw_list_id: DrawListId) -> &mut CxAccessList {
+        let index = draw_list_id.index();
+        if index >= self.lists.len() {
+            self.lists.resize(index + 1, CxAccessList::default());
+        }
+        &mut self.lists[index]
+    }
+
+    fn list(&self, draw_list_id: DrawListId) -> Option<&CxAccessList> {
+        self.lists.get(draw_list_id.index())
+    }
+
+    pub fn begin_draw(&mut self, redraw_id: u64) {
+        self.stack.clear();
+        self.redraw_id = redraw_id;
+    }
+
+    /// Drops the windows that didn't draw this cycle, called after the draw event.
+    pub fn end_draw(&mut self) {
+        let redraw_id = self.redraw_id;
+        if self.roots.iter().any( | root | root.redraw_id == redraw_id) {
+            let len = self.roots.len();
+            self.roots.retain( | root | root.redraw_id == redraw_id);
+            if self.roots.len() != len {
+                self.changed = true;
+            }
+        }
+    }
+
+    pub fn clear_list(&mut self, draw_list_id: DrawListId) {
+        self.list_mut(draw_list_id).items.clear();
+        self.changed = true;
+    }
+
+    pub fn push_item(&mut self, draw_list_id: DrawListId, item: AccessItem) {
+        self.list_mut(draw_list_id).items.push(item);
+        self.changed = true;
+    }
+
+    /// Hands a drawlist without a parent (the main drawlist of a window) to the root node that is open.
+    pub fn attach_root_list(&mut self, draw_list_id: DrawListId) {
+        if let Some((_, AccessSlot::Root(index))) = self.stack.last() {
+            self.roots[*index].draw_list_id = Some(draw_list_id);
+        }
+    }
+
+    /// Opens a node in `draw_list_id`, or as a root when there is no drawlist yet.
+    /// A widget that stepped out of its draw and gets re-entered is already open, so that is a no-op.
+    pub fn begin_node(&mut self, draw_list_id: Option<DrawListId>, id: u64, node: AccessNode) {
+        if self.stack.iter().any( | (open, _) | *open == id) {
+            return
+        }
+        let slot = if let Some(draw_list_id) = draw_list_id {
+            let list = self.list_mut(draw_list_id);
+            list.items.push(AccessItem::Begin(id, node));
+            AccessSlot::List(draw_list_id, list.items.len() - 1)
+        }
+        else {
+            let redraw_id = self.redraw_id;
+            if let Some(index) = self.roots.iter().position( | root | root.id == id) {
+                let root = &mut self.roots[index];
+                root.node = node;
+                root.redraw_id = redraw_id;
+                AccessSlot::Root(index)
+            }
+            else {
+                self.roots.push(CxAccessRoot {id, node, draw_list_id: None, redraw_id});
+                AccessSlot::Root(self.roots.len() - 1)
+            }
+        };
+        self.stack.push((id, slot));
+        self.changed = true;
+    }
+
+    /// Closes the node `id` if it's the innermost open one, replacing its content with
+    /// `node` since things like the rect are only known once the widget has drawn.
+    pub fn end_node(&mut self, id: u64, node: AccessNode) {
+        if self.stack.last().map( | (open, _) | *open) != Some(id) {
+            return
+        }
+        let (_, slot) = self.stack.pop().unwrap();
+        match slot {
+            AccessSlot::List(draw_list_id, index) => {
+                let list = self.list_mut(draw_list_id);
+                if let Some(AccessItem::Begin(_, old)) = list.items.get_mut(index) {
+                    *old = node;
+                }
+                list.items.push(AccessItem::End);
+            }
+            AccessSlot::Root(index) => {
+                self.roots[index].node = node;
+            }
+        }
+        self.changed = true;
+    }
+
+    /// Returns whether anything changed since the last call.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::replace(&mut self.changed, false)
+    }
+
+    pub fn tree(&self) -> Vec<AccessTreeNode> {
+        let mut out = Vec::new();
+        for root in &self.roots {
+            let mut node = AccessTreeNode::new(root.id, &root.node);
+            if let Some(draw_list_id) = root.draw_list_id {
+                self.collect_list(draw_list_id, &mut node.children);
+            }
+            out.push(node);
+        }
+        out
+    }
+
+    fn collect_list(&self, draw_list_id: DrawListId, out: &mut Vec<AccessTreeNode>) {
+        let list = if let Some(list) = self.list(draw_list_id) {list} else {return};
+        let mut stack: Vec<AccessTreeNode> = Vec::new();
+        for item in &list.items {
+            match item {
+                AccessItem::Begin(id, node) => {
+                    stack.push(AccessTreeNode::new(*id, node));
+                }
+                AccessItem::End => if let Some(node) = stack.pop() {
+                    stack.last_mut().map_or(&mut *out, | parent | &mut parent.children).push(node);
+                }
+                AccessItem::Child(child_id) => {
+                    let children = stack.last_mut().map_or(&mut *out, | parent | &mut parent.children);
+                    self.collect_list(*child_id, children);
+                }
+            }
+        }
+        // nodes that stayed open close at the end of their drawlist
+        while let Some(node) = stack.pop() {
+            stack.last_mut().map_or(&mut *out, | parent | &mut parent.children).push(node);
+        }
+    }
+}
+
+/// A resolved node of the accessibility tree, as exported to the os and dumped as json.
+#[derive(Clone, Debug, PartialEq, SerJson, DeJson)]
+pub struct AccessTreeNode {
+    pub id: u64,
+    pub role: AccessRole,
+    pub name: String,
+    pub value: AccessValue,
+    pub state: AccessState,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub children: Vec<AccessTreeNode>,
+}
+
+impl AccessTreeNode {
+    fn new(id: u64, node: &AccessNode) -> Self {
+        Self {
+            id,
+            role: node.role,
+            name: node.name.clone(),
+            value: node.value.clone(),
+            state: node.state,
+            x: node.rect.pos.x,
+            y: node.rect.pos.y,
+            width: node.rect.size.x,
+            height: node.rect.size.y,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn rect(&self) -> Rect {
+        Rect {
+            pos: dvec2(self.x, self.y),
+            size: dvec2(self.width, self.height),
+        }
+    }
+}
+
+impl Cx {
+    /// The accessibility tree as it stands after the last draw, one root per window.
+    pub fn access_tree(&self) -> Vec<AccessTreeNode> {
+        self.accessibility.tree()
+    }
+
+    pub fn access_tree_json(&self) -> String {
+        self.access_tree().serialize_json()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::draw_list::CxDrawListPool,
+    };
+
+    fn node(role: AccessRole, name: &str) -> AccessNode {
+        AccessNode::new(role, name)
+    }
+
+    fn names(nodes: &[AccessTreeNode]) -> Vec<&str> {
+        nodes.iter().map( | node | node.name.as_str()).collect()
+    }
+
+    #[test]
+    fn builds_tree_across_drawlists() {
+        let mut pool = CxDrawListPool::default();
+        let main_list = pool.alloc();
+        let child_list = pool.alloc();
+        let main_id = main_list.id();
+        let child_id = child_list.id();
+
+        let mut access = CxAccessibility::default();
+        access.begin_draw(1);
+        access.begin_node(None, 1, node(AccessRole::Window, "window"));
+        access.attach_root_list(main_id);
+        access.begin_node(Some(main_id), 2, node(AccessRole::List, "list"));
+        access.push_item(main_id, AccessItem::Child(child_id));
+        access.begin_node(Some(child_id), 3, node(AccessRole::Button, "ok"));
+        let mut done = node(AccessRole::Button, "ok");
+        done.rect = Rect {pos: dvec2(1.0, 2.0), size: dvec2(30.0, 10.0)};
+        access.end_node(3, done);
+        access.end_node(2, node(AccessRole::List, "list"));
+        // left open, it closes at the end of the drawlist
+        access.begin_node(Some(main_id), 4, node(AccessRole::Label, "status"));
+        access.end_node(1, node(AccessRole::Window, "window"));
+        access.end_draw();
+
+        let tree = access.tree();
+        assert_eq!(names(&tree), ["window"]);
+        assert_eq!(names(&tree[0].children), ["list", "status"]);
+        let button = &tree[0].children[0].children[0];
+        assert_eq!(button.name, "ok");
+        assert_eq!(button.rect(), Rect {pos: dvec2(1.0, 2.0), size: dvec2(30.0, 10.0)});
+        assert!(access.take_changed());
+        assert!(!access.take_changed());
+    }
+
+    #[test]
+    fn reentered_node_stays_single() {
+        let mut pool = CxDrawListPool::default();
+        let list = pool.alloc();
+        let mut access = CxAccessibility::default();
+        access.begin_draw(1);
+        access.begin_node(None, 1, node(AccessRole::Window, "window"));
+        access.attach_root_list(list.id());
+        access.begin_node(Some(list.id()), 2, node(AccessRole::Tree, "tree"));
+        access.begin_node(Some(list.id()), 2, node(AccessRole::Tree, "tree"));
+        access.end_node(2, node(AccessRole::Tree, "tree"));
+        // only the innermost open node can be closed
+        access.end_node(7, node(AccessRole::Label, "stray"));
+        access.end_node(1, node(AccessRole::Window, "window"));
+        let tree = access.tree();
+        assert_eq!(names(&tree[0].children), ["tree"]);
+        assert!(tree[0].children[0].children.is_empty());
+    }
+
+    #[test]
+    fn redrawn_list_replaces_its_nodes_and_stale_windows_drop() {
+        let mut pool = CxDrawListPool::default();
+        let list = pool.alloc();
+        let mut access = CxAccessibility::default();
+        access.begin_draw(1);
+        access.begin_node(None, 1, node(AccessRole::Window, "one"));
+        access.attach_root_list(list.id());
+        access.begin_node(Some(list.id()), 2, node(AccessRole::Label, "old"));
+        access.end_node(2, node(AccessRole::Label, "old"));
+        access.end_node(1, node(AccessRole::Window, "one"));
+        access.begin_node(None, 9, node(AccessRole::Window, "two"));
+        access.end_node(9, node(AccessRole::Window, "two"));
+        access.end_draw();
+        assert_eq!(names(&access.tree()), ["one", "two"]);
+
+        // the second draw only redraws window one and its list
+        access.begin_draw(2);
+        access.begin_node(None, 1, node(AccessRole::Window, "one"));
+        access.clear_list(list.id());
+        access.begin_node(Some(list.id()), 3, node(AccessRole::Label, "new"));
+        access.end_node(3, node(AccessRole::Label, "new"));
+        access.end_node(1, node(AccessRole::Window, "one"));
+        access.end_draw();
+        let tree = access.tree();
+        assert_eq!(names(&tree), ["one"]);
+        assert_eq!(names(&tree[0].children), ["new"]);
+    }
+
+    #[test]
+    fn tree_json_round_trip() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        let mut button = node(AccessRole::CheckBox, "agree");
+        button.state.checked = Some(true);
+        button.state.focusable = true;
+        button.value = AccessValue::Number {value: 0.5, min: 0.0, max: 1.0, step: 0.1};
+        cx.accessibility.begin_draw(1);
+        cx.accessibility.begin_node(None, 5, button.clone());
+        cx.accessibility.end_node(5, button);
+        cx.accessibility.end_draw();
+
+        let json = cx.access_tree_json();
+        assert!(json.contains("\"name\":\"agree\""));
+        let tree: Vec<AccessTreeNode> = DeJson::deserialize_json(&json).unwrap();
+        assert_eq!(tree, cx.access_tree());
+        assert_eq!(tree[0].state.checked, Some(true));
+        assert_eq!(tree[0].role, AccessRole::CheckBox);
+    }
+}
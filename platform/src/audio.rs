@@ -15,7 +15,9 @@ pub struct AudioDeviceId(pub LiveId);
 #[derive(Clone, Copy, Debug)]
 pub struct AudioInfo{
     pub device_id: AudioDeviceId,
-    pub time: Option<AudioTime>
+    pub time: Option<AudioTime>,
+    /// The rate the device runs at, in frames per second
+    pub sample_rate: f64,
 }
 
 #[derive(Clone, Debug)]
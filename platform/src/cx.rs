@@ -21,6 +21,7 @@ use {
         os::{CxOs},
         debug::Debug,
         performance_stats::PerformanceStats,
+        accessibility::CxAccessibility,
         event::{
             DrawEvent,
             CxFingers,
@@ -81,6 +82,7 @@ pub struct Cx {
     pub fingers: CxFingers,
     pub (crate) ime_area: Area,
     pub (crate) drag_drop: CxDragDrop,
+    pub accessibility: CxAccessibility,
     
     pub (crate) platform_ops: Vec<CxOsOp>,
     
@@ -237,6 +239,7 @@ impl Cx {
             keyboard: Default::default(),
             fingers: Default::default(),
             drag_drop: Default::default(),
+            accessibility: Default::default(),
             ime_area: Default::default(),
             platform_ops: Default::default(),
             studio_web_socket: None,
@@ -290,6 +290,18 @@ impl DrawVars {
                             err.message,
                             crate::log::LogLevel::Error
                         );
+                        // notes become their own log items so each one can be jumped to
+                        for note in err.notes {
+                            crate::log::log_with_level(
+                                &note.file,
+                                note.span.start.line,
+                                note.span.start.column,
+                                note.span.end.line,
+                                note.span.end.column,
+                                format!("note: {}", note.message),
+                                crate::log::LogLevel::Log
+                            );
+                        }
                     }
                     else{
                         log!("Error {}", err);
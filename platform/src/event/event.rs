@@ -156,6 +156,7 @@ pub enum Event {
     TextInput(TextInputEvent),
     TextCopy(TextClipboardEvent),
     TextCut(TextClipboardEvent),
+    TextComposition(TextCompositionEvent),
 
     Drag(DragEvent),
     Drop(DropEvent),
@@ -246,6 +247,7 @@ impl Event{
             48=>"MouseLeave",
             49=>"Actions",
             50=>"BackPressed",
+            52=>"TextComposition",
 
             #[cfg(target_arch = "wasm32")]
             51=>"ToWasmMsg",
@@ -315,6 +317,7 @@ impl Event{
             Self::MouseLeave(_)=>48,
             Self::Actions(_)=>49,
             Self::BackPressed=>50,
+            Self::TextComposition(_)=>52,
 
             #[cfg(target_arch = "wasm32")]
             Self::ToWasmMsg(_)=>51,
@@ -332,6 +335,7 @@ pub enum Hit{
     TextInput(TextInputEvent),
     TextCopy(TextClipboardEvent),
     TextCut(TextClipboardEvent),
+    TextComposition(TextCompositionEvent),
 
     FingerScroll(FingerScrollEvent),
     FingerDown(FingerDownEvent),
@@ -21,7 +21,7 @@ use {
         makepad_math::*,
         makepad_live_id::{FromLiveId, live_id, live_id_num},
         event::{
-            event::{Event, Hit}
+            event::{Event, Hit, TriggerHitEvent}
         },
         window::WindowId,
         cx::Cx,
@@ -656,6 +656,11 @@ impl Event {
                     return Hit::KeyDown(kd.clone())
                 }
             },
+            Event::Trigger(te) => {
+                if let Some(triggers) = te.triggers.get(&area) {
+                    return Hit::Trigger(TriggerHitEvent(triggers.clone()))
+                }
+            },
             Event::KeyUp(ku) => {
                 if cx.keyboard.has_key_focus(area) {
                     return Hit::KeyUp(ku.clone())
@@ -676,6 +681,11 @@ impl Event {
                     return Hit::TextCut(tc.clone());
                 }
             },
+            Event::TextComposition(tc) => {
+                if cx.keyboard.has_key_focus(area) {
+                    return Hit::TextComposition(tc.clone());
+                }
+            },
             Event::Scroll(e) => {
                 let digit_id = live_id!(mouse).into();
                 
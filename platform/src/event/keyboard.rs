@@ -48,6 +48,10 @@ impl CxKeyboard {
         self.key_focus == focus_area
     }
 
+    pub fn key_focus(&self) -> Area {
+        self.key_focus
+    }
+
     pub fn set_text_ime_dismissed(&mut self) {
         self.text_ime_dismissed = true;
     }
@@ -128,6 +132,15 @@ pub struct TextInputEvent {
     pub was_paste: bool
 }
 
+/// The text an input method is still composing (preedit), shown inline until it commits
+/// as a `TextInputEvent`. An empty `text` ends the composition.
+#[derive(Clone, Debug, Default, SerBin, DeBin, SerJson, DeJson, PartialEq)]
+pub struct TextCompositionEvent {
+    pub text: String,
+    /// cursor position within `text`, in chars
+    pub cursor: usize,
+}
+
 #[derive(Clone, Debug)]
 pub struct TextClipboardEvent {
     pub response: Rc<RefCell<Option<String>>>
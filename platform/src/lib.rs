@@ -20,6 +20,8 @@ pub mod live_atomic;
 pub mod thread;
 pub mod audio;
 pub mod midi;
+pub mod midi_ump;
+pub mod midi_mpe;
 pub mod video;
 pub mod scope;
 
@@ -43,6 +45,8 @@ mod debug;
 mod component_map;
 mod component_list;
 mod performance_stats;
+mod accessibility;
+mod software_render;
 pub mod studio;
 
 pub mod web_socket;
@@ -150,6 +154,8 @@ pub use {
             InstanceArea
         },
         midi::*,
+        midi_ump::*,
+        midi_mpe::*,
         audio::*,
         thread::*,
         video::*,
@@ -189,6 +195,7 @@ pub use {
             KeyEvent,
             KeyFocusEvent,
             TextInputEvent,
+            TextCompositionEvent,
             TextClipboardEvent,
             WindowCloseRequestedEvent,
             WindowClosedEvent,
@@ -229,7 +236,21 @@ pub use {
             Texture,
             TextureId,
             TextureFormat,
-            TextureSize
+            TextureSize,
+            TextureSampler,
+            TextureFilter,
+            TextureWrap,
+            TextureRect,
+        },
+        software_render::SoftwareImage,
+        accessibility::{
+            AccessRole,
+            AccessValue,
+            AccessState,
+            AccessNode,
+            AccessItem,
+            AccessTreeNode,
+            CxAccessibility,
         },
         live_prims::{
             LiveDependency,
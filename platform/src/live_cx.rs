@@ -161,7 +161,8 @@ impl Cx {
             let err = LiveError {
                 origin,
                 message,
-                span: (*token_id).into()
+                span: (*token_id).into(),
+                notes: Vec::new()
             };
             #[cfg(not(lines))]
             line_nr_error_once();
@@ -1,5 +1,6 @@
 use {
     crate::{
+        midi_ump::Ump,
         os::{OsMidiOutput,OsMidiInput},
         makepad_live_id::{LiveId, FromLiveId},
     }
@@ -60,6 +61,12 @@ impl MidiInput {
     pub fn receive(&mut self) -> Option<(MidiPortId, MidiData)> {
         self.0.as_mut().unwrap().receive()
     }
+    
+    /// Like `receive` but also returns messages that don't fit in a `MidiData`, such as SysEx.
+    /// Use either this or `receive`, messages taken by one aren't seen by the other.
+    pub fn receive_message(&mut self) -> Option<(MidiPortId, MidiMessage)> {
+        self.0.as_mut().unwrap().receive_message()
+    }
 }
 
 #[derive(Default)]
@@ -71,6 +78,11 @@ impl MidiOutput {
         let output = self.0.as_ref().unwrap();
         output.send(port, data);
     } 
+    
+    pub fn send_message(&self, port: Option<MidiPortId>, message: &MidiMessage) {
+        let output = self.0.as_ref().unwrap();
+        output.send_message(port, message);
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)] 
@@ -283,6 +295,11 @@ impl MidiData {
         self.data[0] & 0xf
     }
     
+    /// The number of data bytes following the status byte of this message
+    pub fn data_len(&self) -> usize {
+        midi_data_len(self.data[0])
+    }
+    
     pub fn decode(&self) -> MidiEvent {
         let status = self.status();
         let channel = self.channel();
@@ -333,3 +350,305 @@ impl MidiData {
         }
     }
 }
+
+/// A decoded MIDI message of any length. Unlike `MidiData` this can carry SysEx
+/// and MIDI 2.0 packets, and channel messages have their values unpacked.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MidiMessage {
+    NoteOff {channel: u8, note: u8, velocity: u8},
+    /// Never has a velocity of 0, those are decoded as `NoteOff`
+    NoteOn {channel: u8, note: u8, velocity: u8},
+    PolyAftertouch {channel: u8, note: u8, pressure: u8},
+    ControlChange {channel: u8, control: u8, value: u8},
+    ProgramChange {channel: u8, program: u8},
+    ChannelAftertouch {channel: u8, pressure: u8},
+    /// 14 bits, 8192 is the center
+    PitchBend {channel: u8, bend: u16},
+    /// The bytes between the 0xF0 and 0xF7 framing bytes
+    SysEx(Vec<u8>),
+    /// System common and realtime messages, clock, start, stop, song position etc.
+    System(MidiData),
+    /// A MIDI 2.0 packet, kept whole so its higher resolution values aren't lost
+    Ump(Ump),
+}
+
+/// The number of data bytes following a status byte
+pub fn midi_data_len(status: u8) -> usize {
+    match status {
+        0x80..=0xBF | 0xE0..=0xEF | 0xF2 => 2,
+        0xC0..=0xDF | 0xF1 | 0xF3 => 1,
+        _ => 0
+    }
+}
+
+impl MidiMessage {
+    pub const PITCH_BEND_CENTER: u16 = 8192;
+    
+    /// Decodes one complete message starting with a status byte, use `MidiParser` for byte streams
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let status = *bytes.first()?;
+        if status < 0x80 {
+            return None
+        }
+        if status == 0xF0 {
+            let data = &bytes[1..];
+            let end = data.iter().position( | b | *b == 0xF7).unwrap_or(data.len());
+            if data[..end].iter().any( | b | *b >= 0x80) {
+                return None
+            }
+            return Some(Self::SysEx(data[..end].to_vec()))
+        }
+        let len = midi_data_len(status);
+        if bytes.len() < len + 1 || bytes[1..len + 1].iter().any( | b | *b >= 0x80) {
+            return None
+        }
+        let mut data = [status, 0, 0];
+        data[1..len + 1].copy_from_slice(&bytes[1..len + 1]);
+        Some(MidiData {data}.into())
+    }
+    
+    /// Appends the wire encoding of this message, `Ump` packets without a MIDI 1.0 form write nothing
+    pub fn write_bytes(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::SysEx(data) => {
+                out.push(0xF0);
+                out.extend_from_slice(data);
+                out.push(0xF7);
+            }
+            Self::System(data) => {
+                out.extend_from_slice(&data.data[0..data.data_len() + 1]);
+            }
+            Self::Ump(ump) => if let Some(message) = ump.to_midi1() {
+                message.write_bytes(out);
+            }
+            _ => if let Some(data) = self.to_midi_data() {
+                out.extend_from_slice(&data.data[0..data.data_len() + 1]);
+            }
+        }
+    }
+    
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_bytes(&mut out);
+        out
+    }
+    
+    /// The 3 byte form of this message, `None` for SysEx and MIDI 2.0 only packets
+    pub fn to_midi_data(&self) -> Option<MidiData> {
+        let data = match *self {
+            Self::NoteOff {channel, note, velocity} => [0x80 | channel, note, velocity],
+            Self::NoteOn {channel, note, velocity} => [0x90 | channel, note, velocity],
+            Self::PolyAftertouch {channel, note, pressure} => [0xA0 | channel, note, pressure],
+            Self::ControlChange {channel, control, value} => [0xB0 | channel, control, value],
+            Self::ProgramChange {channel, program} => [0xC0 | channel, program, 0],
+            Self::ChannelAftertouch {channel, pressure} => [0xD0 | channel, pressure, 0],
+            Self::PitchBend {channel, bend} => [0xE0 | channel, (bend & 0x7f) as u8, ((bend >> 7) & 0x7f) as u8],
+            Self::System(data) => return Some(data),
+            Self::SysEx(_) => return None,
+            Self::Ump(ump) => return ump.to_midi1().and_then( | m | m.to_midi_data())
+        };
+        Some(MidiData {data})
+    }
+    
+    pub fn channel(&self) -> Option<u8> {
+        match *self {
+            Self::NoteOff {channel, ..} |
+            Self::NoteOn {channel, ..} |
+            Self::PolyAftertouch {channel, ..} |
+            Self::ControlChange {channel, ..} |
+            Self::ProgramChange {channel, ..} |
+            Self::ChannelAftertouch {channel, ..} |
+            Self::PitchBend {channel, ..} => Some(channel),
+            _ => None
+        }
+    }
+    
+    /// Pitch bend as -1.0..1.0
+    pub fn pitch_bend_normalized(bend: u16) -> f32 {
+        (bend as f32 - Self::PITCH_BEND_CENTER as f32) / Self::PITCH_BEND_CENTER as f32
+    }
+}
+
+impl From<MidiData> for MidiMessage {
+    fn from(data: MidiData) -> Self {
+        let channel = data.channel();
+        let [_, d1, d2] = data.data;
+        match data.status() {
+            0x8 => Self::NoteOff {channel, note: d1, velocity: d2},
+            0x9 if d2 == 0 => Self::NoteOff {channel, note: d1, velocity: 0},
+            0x9 => Self::NoteOn {channel, note: d1, velocity: d2},
+            0xA => Self::PolyAftertouch {channel, note: d1, pressure: d2},
+            0xB => Self::ControlChange {channel, control: d1, value: d2},
+            0xC => Self::ProgramChange {channel, program: d1},
+            0xD => Self::ChannelAftertouch {channel, pressure: d1},
+            0xE => Self::PitchBend {channel, bend: ((d2 as u16) << 7) | d1 as u16},
+            _ => Self::System(data)
+        }
+    }
+}
+
+/// Reassembles messages from a MIDI byte stream, handling running status,
+/// realtime bytes interleaved with other messages and SysEx split over several chunks.
+#[derive(Default)]
+pub struct MidiParser {
+    running_status: Option<u8>,
+    message: Vec<u8>,
+    sysex: Option<Vec<u8>>,
+}
+
+impl MidiParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn feed(&mut self, bytes: &[u8], mut out: impl FnMut(MidiMessage)) {
+        for &byte in bytes {
+            match byte {
+                0xF8..=0xFF => out(MidiMessage::System(MidiData {data: [byte, 0, 0]})),
+                0xF0 => {
+                    self.message.clear();
+                    self.running_status = None;
+                    self.sysex = Some(Vec::new());
+                }
+                0xF7 => if let Some(sysex) = self.sysex.take() {
+                    out(MidiMessage::SysEx(sysex));
+                }
+                0x80..=0xF6 => {
+                    // any status byte other than realtime ends an unterminated sysex
+                    if let Some(sysex) = self.sysex.take() {
+                        out(MidiMessage::SysEx(sysex));
+                    }
+                    self.running_status = if byte < 0xF0 {Some(byte)} else {None};
+                    self.message.clear();
+                    self.message.push(byte);
+                    self.flush(&mut out);
+                }
+                _ => {
+                    if let Some(sysex) = &mut self.sysex {
+                        sysex.push(byte);
+                        continue;
+                    }
+                    if self.message.is_empty() {
+                        match self.running_status {
+                            Some(status) => self.message.push(status),
+                            None => continue
+                        }
+                    }
+                    self.message.push(byte);
+                    self.flush(&mut out);
+                }
+            }
+        }
+    }
+    
+    fn flush(&mut self, out: &mut impl FnMut(MidiMessage)) {
+        if self.message.len() == midi_data_len(self.message[0]) + 1 {
+            if let Some(message) = MidiMessage::from_bytes(&self.message) {
+                out(message);
+            }
+            self.message.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(parser: &mut MidiParser, bytes: &[u8]) -> Vec<MidiMessage> {
+        let mut out = Vec::new();
+        parser.feed(bytes, | message | out.push(message));
+        out
+    }
+
+    fn clock() -> MidiMessage {
+        MidiMessage::System(MidiData {data: [0xF8, 0, 0]})
+    }
+
+    #[test]
+    fn from_bytes_decodes_single_messages() {
+        assert_eq!(MidiMessage::from_bytes(&[0x93, 60, 100]), Some(MidiMessage::NoteOn {channel: 3, note: 60, velocity: 100}));
+        // a note on without velocity is a note off
+        assert_eq!(MidiMessage::from_bytes(&[0x93, 60, 0]), Some(MidiMessage::NoteOff {channel: 3, note: 60, velocity: 0}));
+        assert_eq!(MidiMessage::from_bytes(&[0xC1, 5]), Some(MidiMessage::ProgramChange {channel: 1, program: 5}));
+        assert_eq!(MidiMessage::from_bytes(&[0xE0, 0x00, 0x40]), Some(MidiMessage::PitchBend {channel: 0, bend: MidiMessage::PITCH_BEND_CENTER}));
+        assert_eq!(MidiMessage::from_bytes(&[0xF0, 0x7E, 0x01, 0xF7]), Some(MidiMessage::SysEx(vec![0x7E, 0x01])));
+        assert_eq!(MidiMessage::from_bytes(&[0xF0, 0x7E, 0x01]), Some(MidiMessage::SysEx(vec![0x7E, 0x01])));
+        // running status, short messages and status bytes in the data are refused
+        assert_eq!(MidiMessage::from_bytes(&[60, 100]), None);
+        assert_eq!(MidiMessage::from_bytes(&[0x90, 60]), None);
+        assert_eq!(MidiMessage::from_bytes(&[0x90, 60, 0x80]), None);
+        assert_eq!(MidiMessage::from_bytes(&[0xF0, 0x01, 0x90, 0xF7]), None);
+        assert_eq!(MidiMessage::from_bytes(&[]), None);
+    }
+
+    #[test]
+    fn messages_round_trip_through_bytes() {
+        let messages = [
+            MidiMessage::NoteOff {channel: 15, note: 0, velocity: 64},
+            MidiMessage::NoteOn {channel: 0, note: 127, velocity: 1},
+            MidiMessage::PolyAftertouch {channel: 2, note: 61, pressure: 90},
+            MidiMessage::ControlChange {channel: 9, control: 74, value: 127},
+            MidiMessage::ProgramChange {channel: 4, program: 100},
+            MidiMessage::ChannelAftertouch {channel: 5, pressure: 33},
+            MidiMessage::PitchBend {channel: 6, bend: 0x3FFF},
+            MidiMessage::SysEx(vec![0x43, 0x10, 0x4C, 0x00]),
+            MidiMessage::System(MidiData {data: [0xF2, 0x10, 0x20]}),
+            clock(),
+        ];
+        for message in messages {
+            let bytes = message.to_bytes();
+            assert_eq!(MidiMessage::from_bytes(&bytes).as_ref(), Some(&message), "{:x?}", bytes);
+            assert_eq!(parse(&mut MidiParser::new(), &bytes), vec![message]);
+        }
+    }
+
+    #[test]
+    fn parser_uses_running_status() {
+        let mut parser = MidiParser::new();
+        assert_eq!(parse(&mut parser, &[0x90, 60, 100, 62, 101, 64, 0]), vec![
+            MidiMessage::NoteOn {channel: 0, note: 60, velocity: 100},
+            MidiMessage::NoteOn {channel: 0, note: 62, velocity: 101},
+            MidiMessage::NoteOff {channel: 0, note: 64, velocity: 0},
+        ]);
+        // the running status carries over into the next chunk, as does a half message
+        assert_eq!(parse(&mut parser, &[65]), vec![]);
+        assert_eq!(parse(&mut parser, &[70, 0xC2, 7, 8]), vec![
+            MidiMessage::NoteOn {channel: 0, note: 65, velocity: 70},
+            MidiMessage::ProgramChange {channel: 2, program: 7},
+            MidiMessage::ProgramChange {channel: 2, program: 8},
+        ]);
+        // realtime bytes don't interrupt the message around them
+        assert_eq!(parse(&mut parser, &[0xB0, 7, 0xF8, 100]), vec![
+            clock(),
+            MidiMessage::ControlChange {channel: 0, control: 7, value: 100},
+        ]);
+        // system common messages clear the running status, so the data after them is dropped
+        assert_eq!(parse(&mut parser, &[0xF3, 5, 62, 100]), vec![
+            MidiMessage::System(MidiData {data: [0xF3, 5, 0]}),
+        ]);
+        // as are data bytes before any status
+        assert_eq!(parse(&mut MidiParser::new(), &[1, 2, 3, 0x80, 60, 0]), vec![
+            MidiMessage::NoteOff {channel: 0, note: 60, velocity: 0},
+        ]);
+    }
+
+    #[test]
+    fn parser_joins_sysex_split_over_chunks() {
+        let mut parser = MidiParser::new();
+        assert_eq!(parse(&mut parser, &[0x90, 60, 100, 0xF0, 1, 2]), vec![
+            MidiMessage::NoteOn {channel: 0, note: 60, velocity: 100},
+        ]);
+        assert_eq!(parse(&mut parser, &[3, 0xF8, 4]), vec![clock()]);
+        assert_eq!(parse(&mut parser, &[5, 0xF7, 61, 100]), vec![MidiMessage::SysEx(vec![1, 2, 3, 4, 5])]);
+        // the sysex cleared the running status
+        assert_eq!(parse(&mut parser, &[0x91, 61, 100]), vec![MidiMessage::NoteOn {channel: 1, note: 61, velocity: 100}]);
+        // a status byte ends a sysex that never got its 0xF7
+        assert_eq!(parse(&mut parser, &[0xF0, 9, 0x80, 61, 0]), vec![
+            MidiMessage::SysEx(vec![9]),
+            MidiMessage::NoteOff {channel: 0, note: 61, velocity: 0},
+        ]);
+        // and a stray 0xF7 is ignored
+        assert_eq!(parse(&mut parser, &[0xF7]), vec![]);
+    }
+}
@@ -0,0 +1,337 @@
+use crate::midi::MidiMessage;
+
+/// An MPE zone, a master channel plus a range of member channels that each carry one note
+/// so pitch bend, pressure and timbre apply per note.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MpeZone {
+    /// 0 for the lower zone, 15 for the upper zone
+    pub master_channel: u8,
+    /// 0 disables the zone
+    pub member_count: u8,
+    /// In semitones, for the member channels
+    pub pitch_bend_range: f32,
+    /// In semitones, for the master channel, this bend applies to all notes in the zone
+    pub master_pitch_bend_range: f32,
+}
+
+impl MpeZone {
+    pub const DEFAULT_PITCH_BEND_RANGE: f32 = 48.0;
+    pub const DEFAULT_MASTER_PITCH_BEND_RANGE: f32 = 2.0;
+
+    pub fn lower(member_count: u8) -> Self {
+        Self {
+            master_channel: 0,
+            member_count: member_count.min(15),
+            pitch_bend_range: Self::DEFAULT_PITCH_BEND_RANGE,
+            master_pitch_bend_range: Self::DEFAULT_MASTER_PITCH_BEND_RANGE,
+        }
+    }
+
+    pub fn upper(member_count: u8) -> Self {
+        Self {master_channel: 15, ..Self::lower(member_count)}
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.member_count > 0
+    }
+
+    pub fn is_master(&self, channel: u8) -> bool {
+        self.is_enabled() && channel == self.master_channel
+    }
+
+    pub fn contains_member(&self, channel: u8) -> bool {
+        if !self.is_enabled() {
+            return false
+        }
+        if self.master_channel == 0 {
+            channel >= 1 && channel <= self.member_count
+        }
+        else {
+            channel < 15 && channel >= 15 - self.member_count
+        }
+    }
+
+    pub fn contains(&self, channel: u8) -> bool {
+        self.is_master(channel) || self.contains_member(channel)
+    }
+}
+
+/// The expression of a single note
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MpeExpression {
+    /// In semitones, including the zone wide bend of the master channel
+    pub pitch_bend: f32,
+    /// 0.0..1.0, from channel pressure
+    pub pressure: f32,
+    /// 0.0..1.0, from CC74, 0.5 when not sent
+    pub timbre: f32,
+}
+
+impl Default for MpeExpression {
+    fn default() -> Self {
+        Self {pitch_bend: 0.0, pressure: 0.0, timbre: 0.5}
+    }
+}
+
+#[derive(Clone, Copy)]
+struct MpeChannel {
+    bend: f32,
+    bend_range: f32,
+    pressure: f32,
+    timbre: f32,
+    rpn: (u8, u8),
+}
+
+impl Default for MpeChannel {
+    fn default() -> Self {
+        Self {
+            bend: 0.0,
+            bend_range: 2.0,
+            pressure: 0.0,
+            timbre: 0.5,
+            rpn: (RPN_NULL, RPN_NULL)
+        }
+    }
+}
+
+const RPN_NULL: u8 = 127;
+const RPN_PITCH_BEND_RANGE: (u8, u8) = (0, 0);
+const RPN_MPE_CONFIGURATION: (u8, u8) = (0, 6);
+const CC_TIMBRE: u8 = 74;
+
+/// Tracks MPE zones and the expression state of all 16 channels. Zones are configured
+/// with `set_zone` or by the MPE configuration message a controller sends. Channels outside
+/// any zone behave like plain MIDI with a pitch bend range of 2 semitones.
+#[derive(Clone)]
+pub struct MpeState {
+    lower: MpeZone,
+    upper: MpeZone,
+    channels: [MpeChannel; 16],
+}
+
+impl Default for MpeState {
+    fn default() -> Self {
+        Self {
+            lower: MpeZone::lower(0),
+            upper: MpeZone::upper(0),
+            channels: [MpeChannel::default(); 16],
+        }
+    }
+}
+
+impl MpeState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn lower_zone(&self) -> &MpeZone {
+        &self.lower
+    }
+
+    pub fn upper_zone(&self) -> &MpeZone {
+        &self.upper
+    }
+
+    /// Sets the lower or upper zone depending on its master channel, shrinking the other zone if they overlap
+    pub fn set_zone(&mut self, zone: MpeZone) {
+        let member_count = zone.member_count.min(15);
+        if zone.master_channel == 0 {
+            self.lower = MpeZone {member_count, ..zone};
+            self.upper.member_count = self.upper.member_count.min(14 - member_count.min(14));
+        }
+        else {
+            self.upper = MpeZone {master_channel: 15, member_count, ..zone};
+            self.lower.member_count = self.lower.member_count.min(14 - member_count.min(14));
+        }
+        for channel in 0..16 {
+            let bend_range = match self.zone(channel) {
+                Some(zone) if zone.is_master(channel) => zone.master_pitch_bend_range,
+                Some(zone) => zone.pitch_bend_range,
+                None => 2.0
+            };
+            self.channels[channel as usize].bend_range = bend_range;
+        }
+    }
+
+    /// The zone a channel is the master or a member of
+    pub fn zone(&self, channel: u8) -> Option<&MpeZone> {
+        if self.lower.contains(channel) {
+            Some(&self.lower)
+        }
+        else if self.upper.contains(channel) {
+            Some(&self.upper)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Updates the channel state, returns true if the expression of any channel may have changed
+    pub fn handle(&mut self, message: &MidiMessage) -> bool {
+        match *message {
+            MidiMessage::PitchBend {channel, bend} => {
+                self.channels[channel as usize].bend = MidiMessage::pitch_bend_normalized(bend);
+                true
+            }
+            MidiMessage::ChannelAftertouch {channel, pressure} => {
+                self.channels[channel as usize].pressure = pressure as f32 / 127.0;
+                true
+            }
+            MidiMessage::ControlChange {channel, control: CC_TIMBRE, value} => {
+                self.channels[channel as usize].timbre = value as f32 / 127.0;
+                true
+            }
+            MidiMessage::ControlChange {channel, control: 101, value} => {
+                self.channels[channel as usize].rpn.0 = value;
+                false
+            }
+            MidiMessage::ControlChange {channel, control: 100, value} => {
+                self.channels[channel as usize].rpn.1 = value;
+                false
+            }
+            MidiMessage::ControlChange {channel, control: 6, value} => {
+                self.data_entry(channel, value, None)
+            }
+            MidiMessage::ControlChange {channel, control: 38, value} => {
+                self.data_entry(channel, self.channels[channel as usize].bend_range as u8, Some(value))
+            }
+            _ => false
+        }
+    }
+
+    fn data_entry(&mut self, channel: u8, msb: u8, lsb: Option<u8>) -> bool {
+        match self.channels[channel as usize].rpn {
+            RPN_PITCH_BEND_RANGE => {
+                let range = msb as f32 + lsb.unwrap_or(0) as f32 / 100.0;
+                let zone = self.zone(channel).copied();
+                match zone {
+                    Some(mut zone) => {
+                        if zone.is_master(channel) {
+                            zone.master_pitch_bend_range = range;
+                        }
+                        else {
+                            zone.pitch_bend_range = range;
+                        }
+                        self.set_zone(zone);
+                    }
+                    None => self.channels[channel as usize].bend_range = range
+                }
+                true
+            }
+            RPN_MPE_CONFIGURATION if lsb.is_none() && (channel == 0 || channel == 15) => {
+                let zone = if channel == 0 {MpeZone::lower(msb)} else {MpeZone::upper(msb)};
+                self.set_zone(zone);
+                true
+            }
+            _ => false
+        }
+    }
+
+    /// The expression of a note playing on a channel
+    pub fn expression(&self, channel: u8) -> MpeExpression {
+        let state = &self.channels[channel as usize & 0xf];
+        let mut pitch_bend = state.bend * state.bend_range;
+        if let Some(zone) = self.zone(channel) {
+            if zone.contains_member(channel) {
+                let master = &self.channels[zone.master_channel as usize];
+                pitch_bend += master.bend * master.bend_range;
+            }
+        }
+        MpeExpression {
+            pitch_bend,
+            pressure: state.pressure,
+            timbre: state.timbre,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpn(state: &mut MpeState, channel: u8, rpn: (u8, u8), msb: u8, lsb: Option<u8>) {
+        for (control, value) in [(101, rpn.0), (100, rpn.1), (6, msb)] {
+            state.handle(&MidiMessage::ControlChange {channel, control, value});
+        }
+        if let Some(value) = lsb {
+            state.handle(&MidiMessage::ControlChange {channel, control: 38, value});
+        }
+    }
+
+    #[test]
+    fn zones_assign_member_channels() {
+        let lower = MpeZone::lower(5);
+        assert!(lower.is_master(0));
+        assert!((1..=5).all( | c | lower.contains_member(c)));
+        assert!(!lower.contains_member(0) && !lower.contains_member(6));
+        let upper = MpeZone::upper(3);
+        assert!(upper.is_master(15));
+        assert!((12..=14).all( | c | upper.contains_member(c)));
+        assert!(!upper.contains_member(11) && !upper.contains_member(15));
+        // a disabled zone has no master either
+        assert!(!MpeZone::lower(0).contains(0));
+        assert_eq!(MpeZone::upper(20).member_count, 15);
+    }
+
+    #[test]
+    fn zones_shrink_each_other() {
+        let mut state = MpeState::new();
+        state.set_zone(MpeZone::upper(10));
+        assert_eq!(state.upper_zone().member_count, 10);
+        state.set_zone(MpeZone::lower(7));
+        // 7 members and two masters leave 7 for the upper zone
+        assert_eq!(state.upper_zone().member_count, 7);
+        assert_eq!(state.zone(7).map( | z | z.master_channel), Some(0));
+        assert_eq!(state.zone(8).map( | z | z.master_channel), Some(15));
+        // a lower zone over all channels disables the upper one
+        state.set_zone(MpeZone::lower(15));
+        assert_eq!(state.upper_zone().member_count, 0);
+        assert_eq!(state.zone(15).map( | z | z.master_channel), Some(0));
+        state.set_zone(MpeZone::lower(0));
+        assert_eq!(state.zone(3), None);
+    }
+
+    #[test]
+    fn configuration_messages_set_zones_and_ranges() {
+        let mut state = MpeState::new();
+        rpn(&mut state, 0, RPN_MPE_CONFIGURATION, 4, None);
+        assert_eq!(*state.lower_zone(), MpeZone::lower(4));
+        rpn(&mut state, 15, RPN_MPE_CONFIGURATION, 2, None);
+        assert_eq!(*state.upper_zone(), MpeZone::upper(2));
+        // the configuration message only counts on the master channels
+        rpn(&mut state, 3, RPN_MPE_CONFIGURATION, 9, None);
+        assert_eq!(state.lower_zone().member_count, 4);
+        
+        // a pitch bend range sent to a member channel sets it for the whole zone
+        rpn(&mut state, 2, RPN_PITCH_BEND_RANGE, 24, None);
+        assert_eq!(state.lower_zone().pitch_bend_range, 24.0);
+        rpn(&mut state, 0, RPN_PITCH_BEND_RANGE, 12, Some(50));
+        assert_eq!(state.lower_zone().master_pitch_bend_range, 12.5);
+        // outside any zone it stays with the channel
+        rpn(&mut state, 8, RPN_PITCH_BEND_RANGE, 7, None);
+        state.handle(&MidiMessage::PitchBend {channel: 8, bend: 0x3FFF});
+        assert!((state.expression(8).pitch_bend - 7.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn member_expression_adds_the_master_bend() {
+        let mut state = MpeState::new();
+        state.set_zone(MpeZone::lower(3));
+        assert_eq!(state.expression(1), MpeExpression::default());
+        
+        state.handle(&MidiMessage::PitchBend {channel: 1, bend: 0});
+        state.handle(&MidiMessage::PitchBend {channel: 0, bend: 8192 + 4096});
+        state.handle(&MidiMessage::ChannelAftertouch {channel: 1, pressure: 127});
+        state.handle(&MidiMessage::ControlChange {channel: 1, control: 74, value: 0});
+        let expression = state.expression(1);
+        // -48 semitones on the member and half of the master range of 2
+        assert_eq!(expression, MpeExpression {pitch_bend: -47.0, pressure: 1.0, timbre: 0.0});
+        // other members and the master only see their own state
+        assert_eq!(state.expression(2).pitch_bend, 1.0);
+        assert_eq!(state.expression(0).pitch_bend, 1.0);
+        assert_eq!(state.expression(5).pitch_bend, 0.0);
+        // messages that don't change expression say so
+        assert!(!state.handle(&MidiMessage::NoteOn {channel: 1, note: 60, velocity: 100}));
+        assert!(!state.handle(&MidiMessage::ControlChange {channel: 1, control: 1, value: 100}));
+    }
+}
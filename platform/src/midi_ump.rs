@@ -0,0 +1,434 @@
+use crate::midi::{MidiData, MidiMessage};
+
+/// A MIDI 2.0 Universal MIDI Packet of 1 to 4 words. Unused words are 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Ump {
+    pub words: [u32; 4],
+}
+
+pub const UMP_UTILITY: u8 = 0x0;
+pub const UMP_SYSTEM: u8 = 0x1;
+pub const UMP_MIDI1_CHANNEL_VOICE: u8 = 0x2;
+pub const UMP_SYSEX7: u8 = 0x3;
+pub const UMP_MIDI2_CHANNEL_VOICE: u8 = 0x4;
+pub const UMP_DATA128: u8 = 0x5;
+
+/// The status of a 7 bit SysEx packet, long messages are split over a start, continue and end packets
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UmpSysExStatus {
+    Complete,
+    Start,
+    Continue,
+    End,
+}
+
+/// MIDI 2.0 channel voice messages, carried by message type 0x4 packets
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Midi2ChannelVoice {
+    NoteOff {note: u8, velocity: u16, attribute_type: u8, attribute: u16},
+    NoteOn {note: u8, velocity: u16, attribute_type: u8, attribute: u16},
+    PolyPressure {note: u8, pressure: u32},
+    RegisteredPerNoteController {note: u8, index: u8, value: u32},
+    AssignablePerNoteController {note: u8, index: u8, value: u32},
+    PerNoteManagement {note: u8, flags: u8},
+    ControlChange {index: u8, value: u32},
+    RegisteredController {bank: u8, index: u8, value: u32},
+    AssignableController {bank: u8, index: u8, value: u32},
+    ProgramChange {program: u8, bank: Option<(u8, u8)>},
+    ChannelPressure {pressure: u32},
+    /// 32 bits, 0x8000_0000 is the center
+    PitchBend {bend: u32},
+    /// 32 bits, 0x8000_0000 is the center
+    PerNotePitchBend {note: u8, bend: u32},
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UmpMessage {
+    Utility,
+    System {group: u8, data: MidiData},
+    Midi1 {group: u8, message: MidiMessage},
+    SysEx7 {group: u8, status: UmpSysExStatus, data: Vec<u8>},
+    Midi2 {group: u8, channel: u8, message: Midi2ChannelVoice},
+    Other(Ump),
+}
+
+impl Ump {
+    pub fn new(words: &[u32]) -> Self {
+        let mut ump = Self::default();
+        ump.words[..words.len()].copy_from_slice(words);
+        ump
+    }
+
+    pub fn message_type(&self) -> u8 {
+        (self.words[0] >> 28) as u8
+    }
+
+    pub fn group(&self) -> u8 {
+        ((self.words[0] >> 24) & 0xf) as u8
+    }
+
+    /// The number of 32 bit words in a packet of the given message type
+    pub fn word_count_for(message_type: u8) -> usize {
+        match message_type {
+            0x0..=0x2 | 0x6 | 0x7 => 1,
+            0x3 | 0x4 | 0x8..=0xA => 2,
+            0xB | 0xC => 3,
+            _ => 4
+        }
+    }
+
+    pub fn word_count(&self) -> usize {
+        Self::word_count_for(self.message_type())
+    }
+
+    pub fn as_words(&self) -> &[u32] {
+        &self.words[..self.word_count()]
+    }
+
+    /// Splits the first packet off a word stream, returns `None` if the stream ends mid packet
+    pub fn from_words(words: &[u32]) -> Option<(Self, &[u32])> {
+        let len = Self::word_count_for((*words.first()? >> 28) as u8);
+        if words.len() < len {
+            return None
+        }
+        Some((Self::new(&words[..len]), &words[len..]))
+    }
+
+    pub fn decode(&self) -> UmpMessage {
+        let w = self.words;
+        let group = self.group();
+        let status = ((w[0] >> 16) & 0xff) as u8;
+        let b1 = ((w[0] >> 8) & 0x7f) as u8;
+        let b2 = (w[0] & 0x7f) as u8;
+        match self.message_type() {
+            UMP_UTILITY => UmpMessage::Utility,
+            UMP_SYSTEM => UmpMessage::System {group, data: MidiData {data: [status, b1, b2]}},
+            UMP_MIDI1_CHANNEL_VOICE if status >= 0x80 => UmpMessage::Midi1 {
+                group,
+                message: MidiData {data: [status, b1, b2]}.into()
+            },
+            UMP_SYSEX7 => {
+                let status = match (w[0] >> 20) & 0xf {
+                    0 => UmpSysExStatus::Complete,
+                    1 => UmpSysExStatus::Start,
+                    2 => UmpSysExStatus::Continue,
+                    _ => UmpSysExStatus::End
+                };
+                let len = (((w[0] >> 16) & 0xf) as usize).min(6);
+                let bytes = [(w[0] >> 8) as u8, w[0] as u8, (w[1] >> 24) as u8, (w[1] >> 16) as u8, (w[1] >> 8) as u8, w[1] as u8];
+                UmpMessage::SysEx7 {group, status, data: bytes[..len].iter().map( | b | b & 0x7f).collect()}
+            }
+            UMP_MIDI2_CHANNEL_VOICE => {
+                let channel = status & 0xf;
+                let note = b1;
+                let index = b2;
+                let message = match status >> 4 {
+                    0x0 => Midi2ChannelVoice::RegisteredPerNoteController {note, index: (w[0] & 0xff) as u8, value: w[1]},
+                    0x1 => Midi2ChannelVoice::AssignablePerNoteController {note, index: (w[0] & 0xff) as u8, value: w[1]},
+                    0x2 => Midi2ChannelVoice::RegisteredController {bank: b1, index, value: w[1]},
+                    0x3 => Midi2ChannelVoice::AssignableController {bank: b1, index, value: w[1]},
+                    0x6 => Midi2ChannelVoice::PerNotePitchBend {note, bend: w[1]},
+                    0x8 => Midi2ChannelVoice::NoteOff {note, velocity: (w[1] >> 16) as u16, attribute_type: (w[0] & 0xff) as u8, attribute: w[1] as u16},
+                    0x9 => Midi2ChannelVoice::NoteOn {note, velocity: (w[1] >> 16) as u16, attribute_type: (w[0] & 0xff) as u8, attribute: w[1] as u16},
+                    0xA => Midi2ChannelVoice::PolyPressure {note, pressure: w[1]},
+                    0xB => Midi2ChannelVoice::ControlChange {index: note, value: w[1]},
+                    0xC => Midi2ChannelVoice::ProgramChange {
+                        program: (w[1] >> 24) as u8 & 0x7f,
+                        bank: if w[0] & 1 != 0 {Some(((w[1] >> 8) as u8 & 0x7f, w[1] as u8 & 0x7f))} else {None}
+                    },
+                    0xD => Midi2ChannelVoice::ChannelPressure {pressure: w[1]},
+                    0xE => Midi2ChannelVoice::PitchBend {bend: w[1]},
+                    0xF => Midi2ChannelVoice::PerNoteManagement {note, flags: (w[0] & 0xff) as u8},
+                    _ => return UmpMessage::Other(*self)
+                };
+                UmpMessage::Midi2 {group, channel, message}
+            }
+            _ => UmpMessage::Other(*self)
+        }
+    }
+
+    /// The MIDI 1.0 equivalent of this packet, MIDI 2.0 values are scaled down.
+    /// SysEx packets and MIDI 2.0 only messages return `None`.
+    pub fn to_midi1(&self) -> Option<MidiMessage> {
+        match self.decode() {
+            UmpMessage::System {data, ..} => Some(MidiMessage::System(data)),
+            UmpMessage::Midi1 {message, ..} => Some(message),
+            UmpMessage::Midi2 {channel, message, ..} => message.to_midi1(channel),
+            _ => None
+        }
+    }
+}
+
+impl Midi2ChannelVoice {
+    pub fn to_midi1(&self, channel: u8) -> Option<MidiMessage> {
+        Some(match *self {
+            Self::NoteOff {note, velocity, ..} => MidiMessage::NoteOff {channel, note, velocity: (velocity >> 9) as u8},
+            // a MIDI 2.0 velocity of 0 is a valid note on, it can't map to 0
+            Self::NoteOn {note, velocity, ..} => MidiMessage::NoteOn {channel, note, velocity: ((velocity >> 9) as u8).max(1)},
+            Self::PolyPressure {note, pressure} => MidiMessage::PolyAftertouch {channel, note, pressure: (pressure >> 25) as u8},
+            Self::ControlChange {index, value} => MidiMessage::ControlChange {channel, control: index, value: (value >> 25) as u8},
+            Self::ProgramChange {program, ..} => MidiMessage::ProgramChange {channel, program},
+            Self::ChannelPressure {pressure} => MidiMessage::ChannelAftertouch {channel, pressure: (pressure >> 25) as u8},
+            Self::PitchBend {bend} => MidiMessage::PitchBend {channel, bend: (bend >> 18) as u16},
+            _ => return None
+        })
+    }
+
+    /// Upscales a MIDI 1.0 channel voice message using the min-center-max scaling from the UMP spec
+    pub fn from_midi1(message: &MidiMessage) -> Option<(u8, Self)> {
+        let channel = message.channel()?;
+        Some((channel, match *message {
+            MidiMessage::NoteOff {note, velocity, ..} => Self::NoteOff {note, velocity: scale_up(velocity as u32, 7, 16) as u16, attribute_type: 0, attribute: 0},
+            MidiMessage::NoteOn {note, velocity, ..} => Self::NoteOn {note, velocity: scale_up(velocity as u32, 7, 16) as u16, attribute_type: 0, attribute: 0},
+            MidiMessage::PolyAftertouch {note, pressure, ..} => Self::PolyPressure {note, pressure: scale_up(pressure as u32, 7, 32)},
+            MidiMessage::ControlChange {control, value, ..} => Self::ControlChange {index: control, value: scale_up(value as u32, 7, 32)},
+            MidiMessage::ProgramChange {program, ..} => Self::ProgramChange {program, bank: None},
+            MidiMessage::ChannelAftertouch {pressure, ..} => Self::ChannelPressure {pressure: scale_up(pressure as u32, 7, 32)},
+            MidiMessage::PitchBend {bend, ..} => Self::PitchBend {bend: scale_up(bend as u32, 14, 32)},
+            _ => return None
+        }))
+    }
+
+    pub fn to_ump(&self, group: u8, channel: u8) -> Ump {
+        let header = | opcode: u32, b1: u8, b2: u8 | {
+            ((UMP_MIDI2_CHANNEL_VOICE as u32) << 28) | ((group as u32 & 0xf) << 24) | (opcode << 20)
+                | ((channel as u32 & 0xf) << 16) | ((b1 as u32) << 8) | b2 as u32
+        };
+        let words = match *self {
+            Self::RegisteredPerNoteController {note, index, value} => [header(0x0, note, index), value],
+            Self::AssignablePerNoteController {note, index, value} => [header(0x1, note, index), value],
+            Self::RegisteredController {bank, index, value} => [header(0x2, bank, index), value],
+            Self::AssignableController {bank, index, value} => [header(0x3, bank, index), value],
+            Self::PerNotePitchBend {note, bend} => [header(0x6, note, 0), bend],
+            Self::NoteOff {note, velocity, attribute_type, attribute} => [header(0x8, note, attribute_type), ((velocity as u32) << 16) | attribute as u32],
+            Self::NoteOn {note, velocity, attribute_type, attribute} => [header(0x9, note, attribute_type), ((velocity as u32) << 16) | attribute as u32],
+            Self::PolyPressure {note, pressure} => [header(0xA, note, 0), pressure],
+            Self::ControlChange {index, value} => [header(0xB, index, 0), value],
+            Self::ProgramChange {program, bank} => match bank {
+                Some((msb, lsb)) => [header(0xC, 0, 1), ((program as u32) << 24) | ((msb as u32) << 8) | lsb as u32],
+                None => [header(0xC, 0, 0), (program as u32) << 24]
+            },
+            Self::ChannelPressure {pressure} => [header(0xD, 0, 0), pressure],
+            Self::PitchBend {bend} => [header(0xE, 0, 0), bend],
+            Self::PerNoteManagement {note, flags} => [header(0xF, note, flags), 0],
+        };
+        Ump::new(&words)
+    }
+}
+
+impl MidiMessage {
+    /// Encodes this message as MIDI 1.0 protocol packets, SysEx is split over as many packets as it needs
+    pub fn to_ump(&self, group: u8) -> Vec<Ump> {
+        let group = (group as u32 & 0xf) << 24;
+        match self {
+            Self::Ump(ump) => vec![*ump],
+            Self::SysEx(data) => {
+                let chunks: Vec<&[u8]> = if data.is_empty() {vec![&[]]} else {data.chunks(6).collect()};
+                let last = chunks.len() - 1;
+                chunks.iter().enumerate().map( | (i, chunk) | {
+                    let status = match (i, last) {
+                        (0, 0) => 0,
+                        (0, _) => 1,
+                        (i, last) if i == last => 3,
+                        _ => 2
+                    };
+                    let mut bytes = [0u32; 6];
+                    for (b, c) in bytes.iter_mut().zip(chunk.iter()) {
+                        *b = *c as u32;
+                    }
+                    Ump::new(&[
+                        ((UMP_SYSEX7 as u32) << 28) | group | (status << 20) | ((chunk.len() as u32) << 16) | (bytes[0] << 8) | bytes[1],
+                        (bytes[2] << 24) | (bytes[3] << 16) | (bytes[4] << 8) | bytes[5]
+                    ])
+                }).collect()
+            }
+            _ => match self.to_midi_data() {
+                Some(data) => {
+                    let message_type = if data.data[0] >= 0xF0 {UMP_SYSTEM} else {UMP_MIDI1_CHANNEL_VOICE};
+                    vec![Ump::new(&[((message_type as u32) << 28) | group
+                        | ((data.data[0] as u32) << 16) | ((data.data[1] as u32) << 8) | data.data[2] as u32])]
+                }
+                None => vec![]
+            }
+        }
+    }
+}
+
+/// Reassembles SysEx messages that arrive split over several UMP packets
+#[derive(Default)]
+pub struct UmpSysExAssembler {
+    data: Vec<u8>,
+}
+
+impl UmpSysExAssembler {
+    /// Returns the complete message once its final packet arrives
+    pub fn push(&mut self, status: UmpSysExStatus, data: &[u8]) -> Option<MidiMessage> {
+        match status {
+            UmpSysExStatus::Complete => return Some(MidiMessage::SysEx(data.to_vec())),
+            UmpSysExStatus::Start => {
+                self.data.clear();
+                self.data.extend_from_slice(data);
+            }
+            UmpSysExStatus::Continue => self.data.extend_from_slice(data),
+            UmpSysExStatus::End => {
+                self.data.extend_from_slice(data);
+                return Some(MidiMessage::SysEx(std::mem::take(&mut self.data)))
+            }
+        }
+        None
+    }
+}
+
+/// Scales a value up to a larger bit depth so that the minimum, center and maximum
+/// of the source map exactly onto the minimum, center and maximum of the destination
+pub fn scale_up(value: u32, src_bits: u32, dst_bits: u32) -> u32 {
+    let scale_bits = dst_bits - src_bits;
+    let shifted = value << scale_bits;
+    if value <= 1 << (src_bits - 1) {
+        return shifted
+    }
+    // above the center the remaining bits are filled with a repeat of the source bits
+    let repeat_bits = src_bits - 1;
+    let mut repeat = value & ((1 << repeat_bits) - 1);
+    repeat = if scale_bits > repeat_bits {repeat << (scale_bits - repeat_bits)} else {repeat >> (repeat_bits - scale_bits)};
+    let mut result = shifted;
+    while repeat != 0 {
+        result |= repeat;
+        repeat >>= repeat_bits;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_up_keeps_min_center_and_max() {
+        assert_eq!(scale_up(0, 7, 16), 0);
+        assert_eq!(scale_up(64, 7, 16), 0x8000);
+        assert_eq!(scale_up(127, 7, 16), 0xFFFF);
+        assert_eq!(scale_up(127, 7, 32), 0xFFFF_FFFF);
+        assert_eq!(scale_up(0x2000, 14, 32), 0x8000_0000);
+        assert_eq!(scale_up(0x3FFF, 14, 32), 0xFFFF_FFFF);
+        // values keep their order
+        let scaled: Vec<u32> = (0..128).map( | v | scale_up(v, 7, 32)).collect();
+        assert!(scaled.windows(2).all( | w | w[0] < w[1]));
+    }
+
+    #[test]
+    fn midi2_channel_voice_round_trips() {
+        let messages = [
+            Midi2ChannelVoice::NoteOff {note: 60, velocity: 0x1234, attribute_type: 3, attribute: 0xBEEF},
+            Midi2ChannelVoice::NoteOn {note: 61, velocity: 0xFFFF, attribute_type: 0, attribute: 0},
+            Midi2ChannelVoice::PolyPressure {note: 62, pressure: 0x8000_0001},
+            Midi2ChannelVoice::RegisteredPerNoteController {note: 63, index: 7, value: 42},
+            Midi2ChannelVoice::AssignablePerNoteController {note: 64, index: 200, value: 43},
+            Midi2ChannelVoice::PerNoteManagement {note: 65, flags: 3},
+            Midi2ChannelVoice::ControlChange {index: 74, value: 0xFFFF_FFFF},
+            Midi2ChannelVoice::RegisteredController {bank: 0, index: 6, value: 44},
+            Midi2ChannelVoice::AssignableController {bank: 12, index: 34, value: 45},
+            Midi2ChannelVoice::ProgramChange {program: 9, bank: None},
+            Midi2ChannelVoice::ProgramChange {program: 9, bank: Some((1, 2))},
+            Midi2ChannelVoice::ChannelPressure {pressure: 7},
+            Midi2ChannelVoice::PitchBend {bend: 0x8000_0000},
+            Midi2ChannelVoice::PerNotePitchBend {note: 66, bend: 0x1234_5678},
+        ];
+        for message in messages {
+            let ump = message.to_ump(5, 11);
+            assert_eq!(ump.message_type(), UMP_MIDI2_CHANNEL_VOICE);
+            assert_eq!(ump.as_words().len(), 2);
+            assert_eq!(ump.decode(), UmpMessage::Midi2 {group: 5, channel: 11, message});
+        }
+    }
+
+    #[test]
+    fn midi1_messages_round_trip_through_midi2() {
+        for value in 0..128u8 {
+            let messages = [
+                MidiMessage::NoteOn {channel: 3, note: 60, velocity: value.max(1)},
+                MidiMessage::NoteOff {channel: 3, note: 60, velocity: value},
+                MidiMessage::PolyAftertouch {channel: 3, note: 60, pressure: value},
+                MidiMessage::ControlChange {channel: 3, control: 1, value},
+                MidiMessage::ProgramChange {channel: 3, program: value},
+                MidiMessage::ChannelAftertouch {channel: 3, pressure: value},
+                MidiMessage::PitchBend {channel: 3, bend: (value as u16) << 7 | value as u16},
+            ];
+            for message in messages {
+                let (channel, midi2) = Midi2ChannelVoice::from_midi1(&message).unwrap();
+                assert_eq!(midi2.to_midi1(channel), Some(message.clone()));
+                assert_eq!(midi2.to_ump(0, channel).to_midi1(), Some(message));
+            }
+        }
+        // the lowest MIDI 2.0 velocity still plays
+        let note_on = Midi2ChannelVoice::NoteOn {note: 60, velocity: 1, attribute_type: 0, attribute: 0};
+        assert_eq!(note_on.to_midi1(0), Some(MidiMessage::NoteOn {channel: 0, note: 60, velocity: 1}));
+        assert_eq!(Midi2ChannelVoice::PerNotePitchBend {note: 60, bend: 0}.to_midi1(0), None);
+    }
+
+    #[test]
+    fn midi1_packets_encode_and_decode() {
+        let note = MidiMessage::NoteOn {channel: 2, note: 60, velocity: 100};
+        let packets = note.to_ump(7);
+        assert_eq!(packets, vec![Ump::new(&[0x2792_3C64])]);
+        assert_eq!(packets[0].decode(), UmpMessage::Midi1 {group: 7, message: note.clone()});
+        let clock = MidiMessage::System(MidiData {data: [0xF8, 0, 0]});
+        assert_eq!(clock.to_ump(0)[0].decode(), UmpMessage::System {group: 0, data: MidiData {data: [0xF8, 0, 0]}});
+        // a ump message writes its MIDI 1.0 bytes when it has them
+        assert_eq!(MidiMessage::Ump(packets[0]).to_bytes(), vec![0x92, 60, 100]);
+        assert_eq!(MidiMessage::Ump(Midi2ChannelVoice::PerNoteManagement {note: 1, flags: 0}.to_ump(0, 0)).to_bytes(), vec![]);
+    }
+
+    #[test]
+    fn sysex_splits_over_packets_and_reassembles() {
+        let data: Vec<u8> = (0..14).collect();
+        let packets = MidiMessage::SysEx(data.clone()).to_ump(1);
+        assert_eq!(packets.len(), 3);
+        let mut assembler = UmpSysExAssembler::default();
+        let mut statuses = Vec::new();
+        let mut message = None;
+        for packet in &packets {
+            if let UmpMessage::SysEx7 {group, status, data} = packet.decode() {
+                assert_eq!(group, 1);
+                statuses.push(status);
+                message = assembler.push(status, &data);
+            }
+        }
+        assert_eq!(statuses, vec![UmpSysExStatus::Start, UmpSysExStatus::Continue, UmpSysExStatus::End]);
+        assert_eq!(message, Some(MidiMessage::SysEx(data)));
+        
+        // short and empty messages fit one complete packet
+        for data in [vec![], vec![0x7E, 0x7F, 0x06, 0x01]] {
+            let packets = MidiMessage::SysEx(data.clone()).to_ump(0);
+            assert_eq!(packets.len(), 1);
+            assert_eq!(packets[0].decode(), UmpMessage::SysEx7 {group: 0, status: UmpSysExStatus::Complete, data: data.clone()});
+            assert_eq!(assembler.push(UmpSysExStatus::Complete, &data), Some(MidiMessage::SysEx(data)));
+        }
+        // a new start drops an unfinished message
+        assembler.push(UmpSysExStatus::Start, &[1, 2]);
+        assembler.push(UmpSysExStatus::Start, &[3]);
+        assert_eq!(assembler.push(UmpSysExStatus::End, &[4]), Some(MidiMessage::SysEx(vec![3, 4])));
+    }
+
+    #[test]
+    fn packets_split_off_word_streams() {
+        let note = MidiMessage::NoteOn {channel: 0, note: 60, velocity: 100}.to_ump(0)[0];
+        let bend = Midi2ChannelVoice::PitchBend {bend: 0x8000_0000}.to_ump(0, 1);
+        let mut words = Vec::new();
+        words.extend_from_slice(bend.as_words());
+        words.extend_from_slice(note.as_words());
+        words.extend_from_slice(&[0xF000_0000, 1, 2, 3]);
+        words.extend_from_slice(&bend.as_words()[..1]);
+        
+        let (first, rest) = Ump::from_words(&words).unwrap();
+        assert_eq!(first, bend);
+        let (second, rest) = Ump::from_words(rest).unwrap();
+        assert_eq!(second, note);
+        let (third, rest) = Ump::from_words(rest).unwrap();
+        assert_eq!(third.word_count(), 4);
+        assert_eq!(third.decode(), UmpMessage::Other(third));
+        // the last packet is cut off
+        assert_eq!(Ump::from_words(rest), None);
+        assert_eq!(Ump::from_words(&[]), None);
+    }
+}
@@ -227,7 +227,8 @@ impl AudioUnitAccess {
                             if let Some(audio_input_cb) = &mut *audio_input_cb.lock().unwrap() {
                                 return audio_input_cb(AudioInfo{
                                     device_id, 
-                                    time: Some(time)
+                                    time: Some(time),
+                                    sample_rate: 48000.0,
                                 }, output)
                             }
                         });
@@ -295,7 +296,8 @@ impl AudioUnitAccess {
                             if let Some(audio_output_cb) = &mut *audio_output_cb.lock().unwrap() {
                                 audio_output_cb(AudioInfo{
                                     device_id, 
-                                    time:Some(time)
+                                    time:Some(time),
+                                    sample_rate: 48000.0,
                                 }, output)
                             }
                         });
@@ -5,6 +5,7 @@ use {
         thread::SignalToUI,
         makepad_live_id::{LiveId},
         midi::*,
+        midi_ump::*,
         os::apple::apple_sys::*,
         os::apple::apple_util::*,
         makepad_objc_sys::objc_block,
@@ -23,18 +24,34 @@ impl OsMidiInput {
         }
         None
     }
+    
+    pub fn receive_message(&mut self) -> Option<(MidiPortId, MidiMessage)> {
+        self.receive().map( | (port_id, data) | (port_id, data.into()))
+    }
 }
 
 impl OsMidiOutput {
     pub fn send(&self, port_id: Option<MidiPortId>, d: MidiData) {
+        let word = (0x20000000) | ((d.data[0] as u32) << 16) | ((d.data[1] as u32) << 8) | d.data[2] as u32;
+        self.send_words(port_id, kMIDIProtocol_1_0, &[word]);
+    }
+    
+    pub fn send_message(&self, port_id: Option<MidiPortId>, message: &MidiMessage) {
+        for ump in message.to_ump(0) {
+            let protocol = if ump.message_type() == UMP_MIDI2_CHANNEL_VOICE {kMIDIProtocol_2_0} else {kMIDIProtocol_1_0};
+            self.send_words(port_id, protocol, ump.as_words());
+        }
+    }
+    
+    fn send_words(&self, port_id: Option<MidiPortId>, protocol: MIDIProtocolID, ump: &[u32]) {
         let mut words = [0u32; 64];
-        words[0] = (0x20000000) | ((d.data[0] as u32) << 16) | ((d.data[1] as u32) << 8) | d.data[2] as u32;
+        words[..ump.len()].copy_from_slice(ump);
         let event_list = MIDIEventList {
-            protocol: kMIDIProtocol_1_0,
+            protocol,
             numPackets: 1,
             packet: [MIDIEventPacket {
                 timeStamp: 0,
-                wordCount: 1,
+                wordCount: ump.len() as u32,
                 words
             }]
         };
@@ -247,7 +247,8 @@ impl Cx {
             MacosEvent::Scroll(_) |
             MacosEvent::KeyDown(_) |
             MacosEvent::KeyUp(_) |
-            MacosEvent::TextInput(_) => {
+            MacosEvent::TextInput(_) |
+            MacosEvent::TextComposition(_) => {
                 self.os.keep_alive_counter = KEEP_ALIVE_COUNT;
             }
             MacosEvent::Timer(te) => {
@@ -375,6 +376,9 @@ impl Cx {
             MacosEvent::TextInput(e) => {
                 self.call_event_handler(&Event::TextInput(e))
             }
+            MacosEvent::TextComposition(e) => {
+                self.call_event_handler(&Event::TextComposition(e))
+            }
             MacosEvent::Drag(e) => {
                 self.call_event_handler(&Event::Drag(e));
                 self.drag_drop.cycle_drag();
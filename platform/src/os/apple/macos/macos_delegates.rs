@@ -507,7 +507,7 @@ pub fn define_cocoa_view_class() -> *const Class {
         }
     }
     
-    extern fn set_marked_text(this: &mut Object, _sel: Sel, string: ObjcId, _selected_range: NSRange, _replacement_range: NSRange) {
+    extern fn set_marked_text(this: &mut Object, _sel: Sel, string: ObjcId, selected_range: NSRange, _replacement_range: NSRange) {
         unsafe {
             let marked_text_ref: &mut ObjcId = this.get_mut_ivar("markedText");
             let _: () = msg_send![(*marked_text_ref), release];
@@ -519,16 +519,24 @@ pub fn define_cocoa_view_class() -> *const Class {
                 marked_text.init_with_string(string);
             };
             *marked_text_ref = marked_text;
+            // hand the preedit to the focused widget so it can draw it inline
+            let text = nsstring_to_string(marked_text.string());
+            let cw = get_cocoa_window(this);
+            cw.send_text_composition(text, selected_range.location as usize);
         }
     }
     
     extern fn unmark_text(this: &Object, _sel: Sel) {
         unsafe {
             let marked_text: ObjcId = *this.get_ivar("markedText");
+            let had_marked_text = marked_text.length() > 0;
             let mutable_string = marked_text.mutable_string();
             let _: () = msg_send![mutable_string, setString: get_apple_class_global().const_empty_string.as_id()];
             let input_context: ObjcId = msg_send![this, inputContext];
             let _: () = msg_send![input_context, discardMarkedText];
+            if had_marked_text {
+                get_cocoa_window(this).send_text_composition(String::new(), 0);
+            }
         }
     }
     
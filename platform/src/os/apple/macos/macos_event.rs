@@ -12,6 +12,7 @@ use crate::{
         WindowCloseRequestedEvent,
         WindowClosedEvent,
         TextInputEvent,
+        TextCompositionEvent,
         KeyEvent,
         DragEvent,
         DropEvent,
@@ -38,6 +39,7 @@ pub enum MacosEvent {
     WindowDragQuery(WindowDragQueryEvent),
     WindowCloseRequested(WindowCloseRequestedEvent),
     TextInput(TextInputEvent),
+    TextComposition(TextCompositionEvent),
     Drag(DragEvent),
     Drop(DropEvent),
     DragEnd,
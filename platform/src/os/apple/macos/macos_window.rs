@@ -35,6 +35,7 @@ use {
             WindowCloseRequestedEvent,
             WindowClosedEvent,
             TextInputEvent,
+            TextCompositionEvent,
             DragItem,
         },
     }
@@ -424,6 +425,13 @@ impl MacosWindow {
         }))
     }
     
+    pub fn send_text_composition(&mut self, text: String, cursor: usize) {
+        self.do_callback(MacosEvent::TextComposition(TextCompositionEvent {
+            text,
+            cursor
+        }))
+    }
+    
     #[cfg(target_os = "macos")]
     pub fn start_dragging(&mut self,items: Vec<DragItem>) {
         let ns_event: ObjcId =  unsafe{
@@ -30,6 +30,12 @@ use {
             Texture,
             TexturePixel,
             TextureFormat,
+            TextureSampler,
+            TextureFilter,
+            TextureWrap,
+            TextureRect,
+            TextureUpdated,
+            TextureAlloc,
         },
     },
     std::time::{Instant},
@@ -231,6 +237,18 @@ impl Cx {
                         ]};
                     }
                     
+                    let sampler_state = cxtexture.update_sampler_state(metal_cx);
+                    let () = unsafe {msg_send![
+                        encoder,
+                        setFragmentSamplerState: sampler_state
+                        atIndex: i as u64
+                    ]};
+                    let () = unsafe {msg_send![
+                        encoder,
+                        setVertexSamplerState: sampler_state
+                        atIndex: i as u64
+                    ]};
+                    
                 }
                 self.os.draw_calls_done += 1;
                 if let Some(inner) = geometry.os.index_buffer.get().cpu_read().inner.as_ref() {
@@ -817,7 +835,10 @@ struct MetalBufferInner {
 
 #[derive(Default)]
 pub struct CxOsTexture {
-    texture: Option<RcObjcId>
+    texture: Option<RcObjcId>,
+    sampler_state: Option<(TextureSampler, RcObjcId)>,
+    back_texture: Option<RcObjcId>,
+    back_texture_stale: Option<TextureRect>,
 }
 fn texture_pixel_to_mtl_pixel(pix:&TexturePixel)-> MTLPixelFormat {
      match pix{
@@ -832,13 +853,49 @@ fn texture_pixel_to_mtl_pixel(pix:&TexturePixel)-> MTLPixelFormat {
 }
 impl CxTexture {
     
+    fn update_sampler_state(&mut self, metal_cx: &MetalCx) -> ObjcId {
+        let sampler = self.sampler_or_default();
+        if let Some((current, sampler_state)) = &self.os.sampler_state {
+            if *current == sampler {
+                return sampler_state.as_id()
+            }
+        }
+        let mtl_filter = | filter | match filter {
+            TextureFilter::Nearest => MTLSamplerMinMagFilter::Nearest,
+            TextureFilter::Linear => MTLSamplerMinMagFilter::Linear,
+        };
+        let mtl_address_mode = | wrap | match wrap {
+            TextureWrap::ClampToEdge => MTLSamplerAddressMode::ClampToEdge,
+            TextureWrap::Repeat => MTLSamplerAddressMode::Repeat,
+            TextureWrap::MirroredRepeat => MTLSamplerAddressMode::MirrorRepeat,
+        };
+        let mip_filter = match sampler.mipmap_filter {
+            None => MTLSamplerMipFilter::NotMipmapped,
+            Some(TextureFilter::Nearest) => MTLSamplerMipFilter::Nearest,
+            Some(TextureFilter::Linear) => MTLSamplerMipFilter::Linear,
+        };
+        let descriptor = RcObjcId::from_owned(NonNull::new(unsafe {
+            msg_send![class!(MTLSamplerDescriptor), new]
+        }).unwrap());
+        let _: () = unsafe {msg_send![descriptor.as_id(), setMinFilter: mtl_filter(sampler.min_filter)]};
+        let _: () = unsafe {msg_send![descriptor.as_id(), setMagFilter: mtl_filter(sampler.mag_filter)]};
+        let _: () = unsafe {msg_send![descriptor.as_id(), setMipFilter: mip_filter]};
+        let _: () = unsafe {msg_send![descriptor.as_id(), setSAddressMode: mtl_address_mode(sampler.wrap_s)]};
+        let _: () = unsafe {msg_send![descriptor.as_id(), setTAddressMode: mtl_address_mode(sampler.wrap_t)]};
+        // metal accepts 1 to 16
+        let _: () = unsafe {msg_send![descriptor.as_id(), setMaxAnisotropy: sampler.max_anisotropy.max(1).min(16) as u64]};
+        let sampler_state: ObjcId = unsafe {msg_send![metal_cx.device, newSamplerStateWithDescriptor: descriptor.as_id()]};
+        let sampler_state = RcObjcId::from_owned(NonNull::new(sampler_state).unwrap());
+        let id = sampler_state.as_id();
+        self.os.sampler_state = Some((sampler, sampler_state));
+        id
+    }
+    
     fn update_vec_texture(
         &mut self,
         metal_cx: &MetalCx,
     ) {
-        if self.alloc_vec() {
-            let alloc = self.alloc.as_ref().unwrap();
-            
+        fn new_texture(metal_cx: &MetalCx, alloc: &TextureAlloc)->RcObjcId{
             let descriptor = RcObjcId::from_owned(NonNull::new(unsafe {
                 msg_send![class!(MTLTextureDescriptor), new]
             }).unwrap());
@@ -851,42 +908,50 @@ impl CxTexture {
             let _: () = unsafe {msg_send![descriptor.as_id(), setHeight: alloc.height as u64]};
             let _: () = unsafe{msg_send![descriptor.as_id(), setPixelFormat: texture_pixel_to_mtl_pixel(&alloc.pixel)]};
             let texture:ObjcId = unsafe{msg_send![metal_cx.device, newTextureWithDescriptor: descriptor]};
-            self.os.texture = Some(RcObjcId::from_owned(NonNull::new(texture).unwrap()));
+            RcObjcId::from_owned(NonNull::new(texture).unwrap())
         }
-        if self.check_updated(){
-            fn update_data(texture:&Option<RcObjcId>, width: usize, height: usize, bpp: u64, data: *const std::ffi::c_void){
-                let region = MTLRegion {
-                    origin: MTLOrigin {x: 0, y: 0, z: 0},
-                    size: MTLSize {width: width as u64, height: height as u64, depth: 1}
-                };
-                                            
-                let () = unsafe {msg_send![
-                    texture.as_ref().unwrap().as_id(),
-                    replaceRegion: region
-                    mipmapLevel: 0
-                    withBytes: data
-                    bytesPerRow: (width as u64) * bpp
-                ]};
-            }
-            
-            match &self.format{
-                TextureFormat::VecBGRAu8_32{width, height, data}=>{
-                    update_data(&self.os.texture, *width, *height, 4,  data.as_ptr() as *const std::ffi::c_void);
-                }
-                TextureFormat::VecRGBAf32{width, height, data}=>{
-                    update_data(&self.os.texture, *width, *height, 16,  data.as_ptr() as *const std::ffi::c_void);
-                }
-                TextureFormat::VecRu8{width, height, data, ..}=>{
-                    update_data(&self.os.texture, *width, *height, 1,  data.as_ptr() as *const std::ffi::c_void);
-                }
-                TextureFormat::VecRGu8{width, height, data, ..}=>{
-                    update_data(&self.os.texture, *width, *height, 2,  data.as_ptr() as *const std::ffi::c_void);
-                }
-                TextureFormat::VecRf32{width, height, data}=>{
-                    update_data(&self.os.texture, *width, *height, 4,  data.as_ptr() as *const std::ffi::c_void);
-                }
-                _=>panic!()
-            }
+        
+        fn update_data(texture:&RcObjcId, rect: TextureRect, data: &[u8], row_length: usize, texel_size: usize){
+            let region = MTLRegion {
+                origin: MTLOrigin {x: rect.x as u64, y: rect.y as u64, z: 0},
+                size: MTLSize {width: rect.width as u64, height: rect.height as u64, depth: 1}
+            };
+            let offset = (rect.y * row_length + rect.x) * texel_size;
+            let () = unsafe {msg_send![
+                texture.as_id(),
+                replaceRegion: region
+                mipmapLevel: 0
+                withBytes: data[offset..].as_ptr() as *const std::ffi::c_void
+                bytesPerRow: (row_length * texel_size) as u64
+            ]};
+        }
+        
+        if self.alloc_vec() {
+            self.os.texture = Some(new_texture(metal_cx, self.alloc.as_ref().unwrap()));
+            self.os.back_texture = None;
+        }
+        let (width, height) = self.format.vec_width_height().unwrap();
+        let rect = match self.take_updated(){
+            TextureUpdated::Empty => return,
+            TextureUpdated::Partial(rect) => rect,
+            TextureUpdated::Full => TextureRect::new(0, 0, width, height),
+        };
+        let (data, row_length, texel_size) = self.format.vec_texel_data().unwrap();
+        if self.streaming{
+            // write into a second texture and swap them, so we never touch the one a frame in flight
+            // may still be sampling. the back texture missed the previous update so it gets that region too
+            let (back_texture, upload) = match (self.os.back_texture.take(), self.os.back_texture_stale){
+                (Some(back_texture), Some(stale)) => (back_texture, rect.union(&stale)),
+                (Some(back_texture), None) => (back_texture, rect),
+                (None, _) => (new_texture(metal_cx, self.alloc.as_ref().unwrap()), TextureRect::new(0, 0, width, height))
+            };
+            update_data(&back_texture, upload, data, row_length, texel_size);
+            self.os.back_texture = self.os.texture.replace(back_texture);
+            self.os.back_texture_stale = Some(rect);
+        }
+        else{
+            self.os.back_texture = None;
+            update_data(self.os.texture.as_ref().unwrap(), rect, data, row_length, texel_size);
         }
     }
     
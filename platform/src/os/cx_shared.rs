@@ -196,6 +196,7 @@ impl Cx {
         let mut draw_event = DrawEvent::default();
         std::mem::swap(&mut draw_event, &mut self.new_draw_event);
         self.call_event_handler(&Event::Draw(draw_event));
+        self.accessibility.end_draw();
     }
 
     pub (crate) fn call_next_frame_event(&mut self, time: f64) {
@@ -17,15 +17,16 @@ struct AlsaAudioDesc {
     desc: AudioDeviceDesc,
 }
 
-struct AlsaAudioDevice {
+pub (crate) struct AlsaAudioDevice {
     device_handle: *mut snd_pcm_t,
     channel_count: usize,
     frame_count: usize,
+    sample_rate: usize,
     interleaved: Vec<f32>,
     _buffer_size: usize,
 }
 
-struct AlsaAudioDeviceRef {
+pub (crate) struct AlsaAudioDeviceRef {
     device_id: AudioDeviceId,
     is_terminated: bool,
 }
@@ -216,6 +217,7 @@ impl AlsaAudioAccess {
                                 AudioInfo {
                                     device_id,
                                     time: None,
+                                    sample_rate: device.sample_rate() as f64,
                                 },
                                 &audio_buffer
                             );
@@ -275,6 +277,7 @@ impl AlsaAudioAccess {
                                 AudioInfo {
                                     device_id,
                                     time: None,
+                                    sample_rate: device.sample_rate() as f64,
                                 },
                                 &mut audio_buffer
                             );
@@ -301,7 +304,7 @@ impl AlsaAudioAccess {
 
 
 impl AlsaAudioDevice {
-    fn new(device_name: &str, device_id: AudioDeviceId, direction: snd_pcm_stream_t) -> Result<(AlsaAudioDevice, AlsaAudioDeviceRef),
+    pub (crate) fn new(device_name: &str, device_id: AudioDeviceId, direction: snd_pcm_stream_t) -> Result<(AlsaAudioDevice, AlsaAudioDeviceRef),
         AlsaError> {
         unsafe {
             let mut handle: *mut snd_pcm_t = 0 as *mut _;
@@ -336,6 +339,7 @@ impl AlsaAudioDevice {
                 device_handle: handle,
                 channel_count: channel_count as usize,
                 frame_count: frame_count as usize,
+                sample_rate: rate as usize,
                 _buffer_size: buffer_size as usize,
             }, AlsaAudioDeviceRef {
                 device_id,
@@ -344,11 +348,24 @@ impl AlsaAudioDevice {
         }
     }
     
-    fn allocate_matching_buffer(&self) -> AudioBuffer {
+    pub (crate) fn allocate_matching_buffer(&self) -> AudioBuffer {
         AudioBuffer::new_with_size(self.frame_count, self.channel_count)
     }
     
-    fn write_output_buffer(&mut self, buffer: &AudioBuffer) -> Result<i32, AlsaError> {
+    pub (crate) fn sample_rate(&self) -> usize {
+        self.sample_rate
+    }
+    
+    /// The number of frames written but not yet played
+    pub (crate) fn delay_frames(&self) -> usize {
+        let mut delay = 0;
+        if unsafe {snd_pcm_delay(self.device_handle, &mut delay)} < 0 {
+            return 0
+        }
+        delay.max(0) as usize
+    }
+    
+    pub (crate) fn write_output_buffer(&mut self, buffer: &AudioBuffer) -> Result<i32, AlsaError> {
         unsafe {
             // interleave the audio buffer
             buffer.copy_to_interleaved(&mut self.interleaved);
@@ -378,6 +395,12 @@ impl AlsaAudioDevice {
 }
 
 
+impl Drop for AlsaAudioDevice {
+    fn drop(&mut self) {
+        unsafe {snd_pcm_close(self.device_handle);}
+    }
+}
+
 impl AlsaError {
     pub fn from(prefix: &str, err: i32) -> Result<i32, Self> {
         if err < 0 {
@@ -1,5 +1,6 @@
 #![allow(non_upper_case_globals)]
 use {
+    std::collections::HashMap,
     std::sync::{Arc, Mutex, mpsc},
     std::ffi::CStr,
     std::os::raw::{
@@ -20,7 +21,7 @@ use {
 #[derive(Clone)]
 pub struct OsMidiOutput(pub (crate) Arc<Mutex<AlsaMidiAccess >>);
 
-pub struct OsMidiInput(mpsc::Receiver<(MidiPortId, MidiData) >);
+pub struct OsMidiInput(mpsc::Receiver<(MidiPortId, MidiMessage) >);
 
 impl OsMidiOutput {
     pub fn send(&self, port_id: Option<MidiPortId>, d: MidiData) {
@@ -28,18 +29,29 @@ impl OsMidiOutput {
         // send some midi here
         let _ = self.0.lock().unwrap().send_midi(port_id, d);
     }
+    
+    pub fn send_message(&self, port_id: Option<MidiPortId>, message: &MidiMessage) {
+        self.0.lock().unwrap().send_bytes(port_id, &message.to_bytes());
+    }
 }
 
 impl OsMidiInput {
     pub fn receive(&mut self) -> Option<(MidiPortId, MidiData)> {
-        if let Ok((port_id, data)) = self.0.try_recv() {
-            return Some((port_id, data))
+        // skip what doesn't fit in a MidiData, like sysex
+        while let Ok((port_id, message)) = self.0.try_recv() {
+            if let Some(data) = message.to_midi_data() {
+                return Some((port_id, data))
+            }
         }
         None
     }
+    
+    pub fn receive_message(&mut self) -> Option<(MidiPortId, MidiMessage)> {
+        self.0.try_recv().ok()
+    }
 }
 
-type InputSenders = Arc<Mutex<Vec<mpsc::Sender<(MidiPortId, MidiData) >> >>;
+type InputSenders = Arc<Mutex<Vec<mpsc::Sender<(MidiPortId, MidiMessage) >> >>;
 
 #[derive(Clone)]
 pub struct AlsaMidiOutput {
@@ -302,70 +314,60 @@ impl AlsaMidiAccess {
         
         std::thread::spawn(move || unsafe {
             let in_client = midi_access_clone.lock().unwrap().client.as_ref().unwrap().in_client.clone();
+            // the decoder turns sequencer events back into midi bytes, sysex included
+            let mut decoder: *mut snd_midi_event_t = std::ptr::null_mut();
+            if snd_midi_event_new(256, &mut decoder) < 0 {
+                return
+            }
+            snd_midi_event_no_status(decoder, 1);
+            let mut buffer = vec![0u8; 256];
+            // long sysex arrives in several events, so keep a parser per source port
+            let mut parsers: HashMap<(i32, i32), MidiParser> = HashMap::new();
+            let mut messages = Vec::new();
             loop {
                 let mut ev: *mut snd_seq_event_t = 0 as *mut _;
                 snd_seq_event_input(in_client.0, &mut ev);
-                let msg = match (*ev).type_ {
+                match (*ev).type_ {
                     SND_SEQ_EVENT_PORT_SUBSCRIBED |
                     SND_SEQ_EVENT_PORT_UNSUBSCRIBED |
                     SND_SEQ_EVENT_CLIENT_CHANGE |
                     SND_SEQ_EVENT_CLIENT_START |
-                    SND_SEQ_EVENT_CLIENT_EXIT => None,
+                    SND_SEQ_EVENT_CLIENT_EXIT => continue,
                     SND_SEQ_EVENT_PORT_CHANGE |
                     SND_SEQ_EVENT_PORT_START |
                     SND_SEQ_EVENT_PORT_EXIT => {
                         change_signal_clone.set();
-                        None
+                        continue
                     },
-                    SND_SEQ_EVENT_NOTEON |
-                    SND_SEQ_EVENT_NOTEOFF => Some(MidiNote {
-                        is_on: (*ev).type_ == SND_SEQ_EVENT_NOTEON,
-                        channel: (*ev).data.note.channel,
-                        note_number: (*ev).data.note.note,
-                        velocity: (*ev).data.note.velocity
-                    }.into()),
-                    SND_SEQ_EVENT_KEYPRESS => Some(MidiAftertouch {
-                        channel: (*ev).data.note.channel,
-                        note_number: (*ev).data.note.note,
-                        velocity: (*ev).data.note.velocity
-                    }.into()),
-                    SND_SEQ_EVENT_CONTROLLER => Some(MidiControlChange {
-                        channel: (*ev).data.control.channel,
-                        param: (*ev).data.control.param as _,
-                        value: (*ev).data.control.value as _
-                    }.into()),
-                    SND_SEQ_EVENT_PGMCHANGE => Some(MidiProgramChange {
-                        channel: (*ev).data.control.channel,
-                        hi: (*ev).data.control.param as _,
-                        lo: (*ev).data.control.value as _
-                    }.into()),
-                    SND_SEQ_EVENT_CHANPRESS => Some(MidiChannelAftertouch {
-                        channel: (*ev).data.control.channel,
-                        value: (8192 + (*ev).data.control.value) as _
-                    }.into()),
-                    SND_SEQ_EVENT_PITCHBEND => Some(MidiPitchBend {
-                        channel: (*ev).data.control.channel,
-                        bend: (8192 + (*ev).data.control.value) as _
-                    }.into()),
-                    x => {
-                        println!("Unknown alsa midi event {}", x);
-                        None
-                    }
-                };
-                if let Some(msg) = msg {
-                    if let Some(port_id) = midi_access_clone.lock().unwrap().find_port(
-                        (*ev).source.client as i32,
-                        (*ev).source.port as i32
-                    ) {
-                        let mut senders = input_senders.lock().unwrap();
-                        senders.retain( | s | {
-                            s.send((port_id, msg)).is_ok()
-                        });
-                        if senders.len()>0 {
-                            // make sure our eventloop runs
-                            SignalToUI::set_ui_signal();
+                    SND_SEQ_EVENT_SYSEX => {
+                        let len = {(*ev).data.ext.len} as usize;
+                        if buffer.len() < len {
+                            buffer.resize(len, 0);
                         }
                     }
+                    _ => ()
+                }
+                let source = ((*ev).source.client as i32, (*ev).source.port as i32);
+                let port_id = if let Some(port_id) = midi_access_clone.lock().unwrap().find_port(source.0, source.1) {port_id} else {continue};
+                snd_midi_event_reset_decode(decoder);
+                let len = snd_midi_event_decode(decoder, buffer.as_mut_ptr(), buffer.len() as _, ev);
+                if len <= 0 {
+                    // not a midi event
+                    continue;
+                }
+                parsers.entry(source).or_default().feed(&buffer[0..len as usize], | message | messages.push(message));
+                if messages.is_empty() {
+                    continue;
+                }
+                let mut senders = input_senders.lock().unwrap();
+                for message in messages.drain(..) {
+                    senders.retain( | s | {
+                        s.send((port_id, message.clone())).is_ok()
+                    });
+                }
+                if !senders.is_empty() {
+                    // make sure our eventloop runs
+                    SignalToUI::set_ui_signal();
                 }
             }
         });
@@ -374,6 +376,10 @@ impl AlsaMidiAccess {
     }
     
     pub fn send_midi(&mut self, port_id: Option<MidiPortId>, d: MidiData) {
+        self.send_bytes(port_id, &d.data[0..d.data_len() + 1]);
+    }
+    
+    pub fn send_bytes(&mut self, port_id: Option<MidiPortId>, bytes: &[u8]) {
         if self.client.is_err() {
             return
         }
@@ -381,18 +387,25 @@ impl AlsaMidiAccess {
         unsafe {
             for port in &self.ports {
                 if port_id.is_none() || Some(port.desc.port_id) == port_id {
-                    // send to port
-                    let mut event: snd_seq_event_t = std::mem::zeroed();
+                    // send to port, a long sysex is encoded as several events
                     snd_midi_event_reset_encode(client.midi_send.0);
-                    let r = snd_midi_event_encode(client.midi_send.0, d.data.as_ptr(), 3, &mut event);
-                    if r!= 1{
-                        panic!("Unexpected result");
+                    let mut bytes = bytes;
+                    while !bytes.is_empty() {
+                        let mut event: snd_seq_event_t = std::mem::zeroed();
+                        let r = snd_midi_event_encode(client.midi_send.0, bytes.as_ptr(), bytes.len() as _, &mut event);
+                        if r <= 0 {
+                            break;
+                        }
+                        bytes = &bytes[r as usize..];
+                        if event.type_ == SND_SEQ_EVENT_NONE {
+                            continue;
+                        }
+                        event.source.port = port.port_id as _;
+                        event.dest.client = SND_SEQ_ADDRESS_SUBSCRIBERS as _;
+                        event.dest.port = SND_SEQ_ADDRESS_UNKNOWN as _;
+                        event.queue = SND_SEQ_QUEUE_DIRECT as _;
+                        snd_seq_event_output_direct(client.out_client.0, &mut event);
                     }
-                    event.source.port = port.port_id as _;
-                    event.dest.client = SND_SEQ_ADDRESS_SUBSCRIBERS as _;
-                    event.dest.port = SND_SEQ_ADDRESS_UNKNOWN as _;
-                    event.queue = SND_SEQ_QUEUE_DIRECT as _;
-                    snd_seq_event_output_direct(client.out_client.0, &mut event);                    
                 }
             }
         }
@@ -57,6 +57,8 @@ pub const SND_SEQ_EVENT_CONTROLLER: snd_seq_event_type = 10;
 pub const SND_SEQ_EVENT_PGMCHANGE: snd_seq_event_type = 11;
 pub const SND_SEQ_EVENT_CHANPRESS: snd_seq_event_type = 12;
 pub const SND_SEQ_EVENT_PITCHBEND: snd_seq_event_type = 13;
+pub const SND_SEQ_EVENT_SYSEX: snd_seq_event_type = 130;
+pub const SND_SEQ_EVENT_NONE: snd_seq_event_type = 255;
 
 pub const SND_SEQ_EVENT_CLIENT_START: snd_seq_event_type = 60;
 pub const SND_SEQ_EVENT_CLIENT_EXIT: snd_seq_event_type = 61;
@@ -402,6 +404,8 @@ extern "C" {
         buffer: *const c_void,
         size: snd_pcm_uframes_t,
     ) -> snd_pcm_sframes_t;
+    pub fn snd_pcm_delay(pcm: *mut snd_pcm_t, delayp: *mut snd_pcm_sframes_t) -> c_int;
+    pub fn snd_pcm_close(pcm: *mut snd_pcm_t) -> c_int;
     pub fn snd_pcm_hw_params_set_periods_near(
         pcm: *mut snd_pcm_t,
         params: *mut snd_pcm_hw_params_t,
@@ -526,6 +530,14 @@ extern "C" {
         count: c_long,
         ev: *mut snd_seq_event_t,
     ) -> c_long;
+    pub fn snd_midi_event_decode(
+        dev: *mut snd_midi_event_t,
+        buf: *mut c_uchar,
+        count: c_long,
+        ev: *const snd_seq_event_t,
+    ) -> c_long;
+    pub fn snd_midi_event_reset_decode(dev: *mut snd_midi_event_t);
+    pub fn snd_midi_event_no_status(dev: *mut snd_midi_event_t, on: c_int);
     pub fn snd_seq_event_output_direct(
         handle: *mut snd_seq_t,
         ev: *mut snd_seq_event_t,
@@ -26,6 +26,13 @@ impl OsMidiOutput {
     pub fn send(&self, port_id: Option<MidiPortId>, data: MidiData) {
         self.amidi.lock().unwrap().send_midi(port_id, data);
     }
+    
+    /// Messages that don't fit in a `MidiData`, such as SysEx, aren't sent yet on this backend
+    pub fn send_message(&self, port_id: Option<MidiPortId>, message: &MidiMessage) {
+        if let Some(data) = message.to_midi_data() {
+            self.send(port_id, data);
+        }
+    }
 }
 
 pub struct OsMidiInput {
@@ -42,6 +49,10 @@ impl OsMidiInput {
         }
         None
     }
+    
+    pub fn receive_message(&mut self) -> Option<(MidiPortId, MidiMessage)> {
+        self.receive().map( | (port_id, data) | (port_id, data.into()))
+    }
 }
 
 pub struct AndroidMidiOutput {
@@ -0,0 +1,594 @@
+//! Exports the accessibility tree over AT-SPI, the D-Bus protocol Linux screen readers use.
+//! The tree is snapshotted after every draw that changed it and served from a thread.
+
+use {
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex, mpsc},
+    },
+    super::dbus::*,
+    crate::{
+        makepad_math::*,
+        accessibility::{AccessTreeNode, AccessRole, AccessValue},
+    }
+};
+
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+const NULL_PATH: &str = "/org/a11y/atspi/null";
+const NODE_PATH: &str = "/org/a11y/atspi/accessible/";
+
+const ATSPI_ACCESSIBLE: &str = "org.a11y.atspi.Accessible";
+const ATSPI_APPLICATION: &str = "org.a11y.atspi.Application";
+const ATSPI_COMPONENT: &str = "org.a11y.atspi.Component";
+const ATSPI_VALUE: &str = "org.a11y.atspi.Value";
+const ATSPI_TEXT: &str = "org.a11y.atspi.Text";
+const ATSPI_EVENT_OBJECT: &str = "org.a11y.atspi.Event.Object";
+const DBUS_PROPERTIES: &str = "org.freedesktop.DBus.Properties";
+
+// AtspiRole values
+const ROLE_CHECK_BOX: u32 = 7;
+const ROLE_DATE_EDITOR: u32 = 12;
+const ROLE_FRAME: u32 = 23;
+const ROLE_LABEL: u32 = 29;
+const ROLE_LIST: u32 = 31;
+const ROLE_PUSH_BUTTON: u32 = 43;
+const ROLE_SLIDER: u32 = 51;
+const ROLE_SPIN_BUTTON: u32 = 52;
+const ROLE_TABLE: u32 = 55;
+const ROLE_TREE: u32 = 65;
+const ROLE_APPLICATION: u32 = 75;
+const ROLE_ENTRY: u32 = 79;
+
+// AtspiStateType values
+const STATE_CHECKED: u32 = 4;
+const STATE_EDITABLE: u32 = 7;
+const STATE_ENABLED: u32 = 8;
+const STATE_FOCUSABLE: u32 = 11;
+const STATE_FOCUSED: u32 = 12;
+const STATE_SENSITIVE: u32 = 24;
+const STATE_SHOWING: u32 = 25;
+const STATE_SINGLE_LINE: u32 = 26;
+const STATE_VISIBLE: u32 = 30;
+const STATE_CHECKABLE: u32 = 41;
+const STATE_READ_ONLY: u32 = 42;
+
+// AtspiCoordType and AtspiComponentLayer values
+const COORD_TYPE_SCREEN: u32 = 0;
+const COORD_TYPE_WINDOW: u32 = 1;
+const LAYER_WIDGET: u32 = 3;
+const LAYER_WINDOW: u32 = 7;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum AtspiTarget {
+    App,
+    Node(u64),
+}
+
+struct AtspiNode {
+    node: AccessTreeNode,
+    parent: Option<u64>,
+    children: Vec<u64>,
+    /// screen position of the window the node sits in
+    origin: DVec2,
+}
+
+#[derive(Default)]
+struct AtspiTree {
+    app_name: String,
+    bus_name: String,
+    registry: (String, String),
+    roots: Vec<u64>,
+    nodes: HashMap<u64, AtspiNode>,
+}
+
+type SharedTree = Arc<Mutex<AtspiTree>>;
+
+/// The UI side of the AT-SPI export. The bus handshake, the registration and serving
+/// the screen reader all happen on the bridge thread, the UI thread only hands it snapshots.
+pub struct AtspiBridge {
+    updates: mpsc::Sender<Vec<AccessTreeNode>>,
+}
+
+impl AtspiBridge {
+    /// Starts the bridge thread. It stops by itself when accessibility isn't enabled on the
+    /// desktop, which is the common case, after which `update` returns false.
+    pub fn start(app_name: &str) -> Self {
+        let (updates, updates_rx) = mpsc::channel::<Vec<AccessTreeNode>>();
+        let app_name = app_name.to_string();
+        std::thread::spawn(move || {
+            let (tree, sender) = match Self::connect(&app_name) {
+                Ok(Some(bridge)) => bridge,
+                Ok(None) => return,
+                Err(err) => {
+                    crate::error!("Accessibility bridge failed to start: {}", err.0);
+                    return
+                }
+            };
+            while let Ok(mut roots) = updates_rx.recv() {
+                // only the latest snapshot matters
+                while let Ok(newer) = updates_rx.try_recv() {
+                    roots = newer;
+                }
+                let signals = tree.lock().unwrap().update(roots);
+                for signal in signals {
+                    if sender.send(&signal).is_err() {
+                        return
+                    }
+                }
+            }
+        });
+        Self {updates}
+    }
+
+    /// Hands a new snapshot of the tree to the bridge thread, returns false once the bridge has stopped.
+    pub fn update(&self, roots: Vec<AccessTreeNode>) -> bool {
+        self.updates.send(roots).is_ok()
+    }
+
+    /// Connects to the accessibility bus, registers the application and starts serving calls.
+    fn connect(app_name: &str) -> Result<Option<(SharedTree, DbusSender)>, DbusError> {
+        let mut session = DbusConnection::session()?;
+        let enabled = session.call(DbusMessage::method_call(
+            "org.a11y.Bus",
+            "/org/a11y/bus",
+            DBUS_PROPERTIES,
+            "Get",
+            vec![DbusValue::String("org.a11y.Status".into()), DbusValue::String("IsEnabled".into())]
+        ))?;
+        if enabled.body.first() != Some(&DbusValue::variant(DbusValue::Bool(true))) {
+            return Ok(None)
+        }
+        let address = session.call(DbusMessage::method_call(
+            "org.a11y.Bus",
+            "/org/a11y/bus",
+            "org.a11y.Bus",
+            "GetAddress",
+            vec![]
+        ))?;
+        let address = address.body.first().and_then( | v | v.as_str()).unwrap_or("").to_string();
+        let mut conn = DbusConnection::open(&address)?;
+
+        let tree = Arc::new(Mutex::new(AtspiTree {
+            app_name: app_name.to_string(),
+            bus_name: conn.unique_name.clone(),
+            ..Default::default()
+        }));
+        let embed = conn.call(DbusMessage::method_call(
+            "org.a11y.atspi.Registry",
+            ROOT_PATH,
+            "org.a11y.atspi.Socket",
+            "Embed",
+            vec![DbusValue::Struct(vec![
+                DbusValue::String(conn.unique_name.clone()),
+                DbusValue::ObjectPath(ROOT_PATH.into())
+            ])]
+        ))?;
+        if let Some(DbusValue::Struct(parent)) = embed.body.first() {
+            if let [bus, path] = parent.as_slice() {
+                if let (Some(bus), Some(path)) = (bus.as_str(), path.as_str()) {
+                    tree.lock().unwrap().registry = (bus.to_string(), path.to_string());
+                }
+            }
+        }
+
+        // from here on the connection only serves calls, which can be far apart
+        conn.set_read_timeout(None)?;
+        let sender = conn.sender();
+        std::thread::spawn({
+            let tree = tree.clone();
+            let sender = sender.clone();
+            move || {
+                while let Ok(msg) = conn.read_message() {
+                    if msg.msg_type != DBUS_METHOD_CALL {
+                        continue;
+                    }
+                    let reply = tree.lock().unwrap().handle_call(&msg);
+                    if sender.send(&reply).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(Some((tree, sender)))
+    }
+}
+
+fn flatten(mut node: AccessTreeNode, parent: Option<u64>, origin: DVec2, nodes: &mut HashMap<u64, AtspiNode>) {
+    let children = std::mem::take(&mut node.children);
+    let id = node.id;
+    nodes.insert(id, AtspiNode {
+        node,
+        parent,
+        children: children.iter().map( | child | child.id).collect(),
+        origin,
+    });
+    for child in children {
+        flatten(child, Some(id), origin, nodes);
+    }
+}
+
+fn target_path(target: AtspiTarget) -> String {
+    match target {
+        AtspiTarget::App => ROOT_PATH.to_string(),
+        AtspiTarget::Node(id) => format!("{}{}", NODE_PATH, id)
+    }
+}
+
+fn event(target: AtspiTarget, member: &str, detail: &str, detail1: i32, value: DbusValue) -> DbusMessage {
+    DbusMessage::signal(&target_path(target), ATSPI_EVENT_OBJECT, member, vec![
+        DbusValue::String(detail.into()),
+        DbusValue::Int32(detail1),
+        DbusValue::Int32(0),
+        DbusValue::variant(value),
+        DbusValue::Array("{sv}".into(), vec![]),
+    ])
+}
+
+fn state_changed(target: AtspiTarget, state: &str, on: bool) -> DbusMessage {
+    event(target, "StateChanged", state, on as i32, DbusValue::Int32(0))
+}
+
+fn property_changed(target: AtspiTarget, property: &str, value: DbusValue) -> DbusMessage {
+    event(target, "PropertyChange", property, 0, value)
+}
+
+fn children_changed(tree: &AtspiTree, target: AtspiTarget, old: &[u64], new: &[u64], signals: &mut Vec<DbusMessage>) {
+    for (index, id) in old.iter().enumerate() {
+        if !new.contains(id) {
+            signals.push(event(target, "ChildrenChanged", "remove", index as i32, tree.object_ref(AtspiTarget::Node(*id))));
+        }
+    }
+    for (index, id) in new.iter().enumerate() {
+        if !old.contains(id) {
+            signals.push(event(target, "ChildrenChanged", "add", index as i32, tree.object_ref(AtspiTarget::Node(*id))));
+        }
+    }
+}
+
+impl AtspiTree {
+    /// Swaps in a new snapshot of the tree, returns the signals that tell the screen reader what changed.
+    fn update(&mut self, roots: Vec<AccessTreeNode>) -> Vec<DbusMessage> {
+        let mut nodes = HashMap::new();
+        let mut root_ids = Vec::new();
+        for root in roots {
+            root_ids.push(root.id);
+            let origin = dvec2(root.x, root.y);
+            flatten(root, None, origin, &mut nodes);
+        }
+
+        let mut signals = Vec::new();
+        if self.roots != root_ids {
+            children_changed(self, AtspiTarget::App, &self.roots, &root_ids, &mut signals);
+        }
+        for (id, new) in &nodes {
+            let target = AtspiTarget::Node(*id);
+            if let Some(old) = self.nodes.get(id) {
+                if old.children != new.children {
+                    children_changed(self, target, &old.children, &new.children, &mut signals);
+                }
+                if old.node.state.focused != new.node.state.focused {
+                    signals.push(state_changed(target, "focused", new.node.state.focused));
+                }
+                if old.node.state.checked != new.node.state.checked {
+                    signals.push(state_changed(target, "checked", new.node.state.checked == Some(true)));
+                }
+                if old.node.name != new.node.name {
+                    signals.push(property_changed(target, "accessible-name", DbusValue::String(new.node.name.clone())));
+                }
+                if old.node.value != new.node.value {
+                    if let AccessValue::Number {value, ..} = new.node.value {
+                        signals.push(property_changed(target, "accessible-value", DbusValue::Double(value)));
+                    }
+                }
+            }
+            else if new.node.state.focused {
+                signals.push(state_changed(target, "focused", true));
+            }
+        }
+        self.roots = root_ids;
+        self.nodes = nodes;
+        signals
+    }
+
+    fn resolve(&self, path: &str) -> Option<AtspiTarget> {
+        if path == ROOT_PATH {
+            return Some(AtspiTarget::App)
+        }
+        let id = path.strip_prefix(NODE_PATH)?.parse().ok()?;
+        if self.nodes.contains_key(&id) {
+            Some(AtspiTarget::Node(id))
+        }
+        else {
+            None
+        }
+    }
+
+    fn object_ref(&self, target: AtspiTarget) -> DbusValue {
+        DbusValue::Struct(vec![
+            DbusValue::String(self.bus_name.clone()),
+            DbusValue::ObjectPath(target_path(target))
+        ])
+    }
+
+    fn null_ref(&self) -> DbusValue {
+        DbusValue::Struct(vec![
+            DbusValue::String(self.bus_name.clone()),
+            DbusValue::ObjectPath(NULL_PATH.into())
+        ])
+    }
+
+    fn children(&self, target: AtspiTarget) -> &[u64] {
+        match target {
+            AtspiTarget::App => &self.roots,
+            AtspiTarget::Node(id) => &self.nodes[&id].children
+        }
+    }
+
+    fn parent(&self, target: AtspiTarget) -> DbusValue {
+        match target {
+            AtspiTarget::App => DbusValue::Struct(vec![
+                DbusValue::String(self.registry.0.clone()),
+                DbusValue::ObjectPath(self.registry.1.clone())
+            ]),
+            AtspiTarget::Node(id) => match self.nodes[&id].parent {
+                Some(parent) => self.object_ref(AtspiTarget::Node(parent)),
+                None => self.object_ref(AtspiTarget::App)
+            }
+        }
+    }
+
+    fn index_in_parent(&self, target: AtspiTarget) -> i32 {
+        let id = if let AtspiTarget::Node(id) = target {id} else {return -1};
+        let siblings = match self.nodes[&id].parent {
+            Some(parent) => &self.nodes[&parent].children,
+            None => &self.roots
+        };
+        siblings.iter().position( | sibling | *sibling == id).map_or(-1, | i | i as i32)
+    }
+
+    fn name(&self, target: AtspiTarget) -> String {
+        match target {
+            AtspiTarget::App => self.app_name.clone(),
+            AtspiTarget::Node(id) => self.nodes[&id].node.name.clone()
+        }
+    }
+
+    fn role(&self, target: AtspiTarget) -> (u32, &'static str) {
+        let node = if let AtspiTarget::Node(id) = target {&self.nodes[&id].node} else {
+            return (ROLE_APPLICATION, "application")
+        };
+        match node.role {
+            AccessRole::Window => (ROLE_FRAME, "frame"),
+            AccessRole::Label => (ROLE_LABEL, "label"),
+            AccessRole::Button => (ROLE_PUSH_BUTTON, "push button"),
+            AccessRole::CheckBox => (ROLE_CHECK_BOX, "check box"),
+            AccessRole::TextInput => (ROLE_ENTRY, "entry"),
+            AccessRole::Slider => (ROLE_SLIDER, "slider"),
+            AccessRole::SpinButton => (ROLE_SPIN_BUTTON, "spin button"),
+            AccessRole::DateEditor => (ROLE_DATE_EDITOR, "date editor"),
+            AccessRole::List => (ROLE_LIST, "list"),
+            AccessRole::Table => (ROLE_TABLE, "table"),
+            AccessRole::Tree => (ROLE_TREE, "tree"),
+        }
+    }
+
+    fn states(&self, target: AtspiTarget) -> DbusValue {
+        let mut states = Vec::new();
+        if let AtspiTarget::Node(id) = target {
+            let node = &self.nodes[&id].node;
+            states.extend_from_slice(&[STATE_VISIBLE, STATE_SHOWING]);
+            if !node.state.disabled {
+                states.extend_from_slice(&[STATE_ENABLED, STATE_SENSITIVE]);
+            }
+            if node.state.focusable {
+                states.push(STATE_FOCUSABLE);
+            }
+            if node.state.focused {
+                states.push(STATE_FOCUSED);
+            }
+            if let Some(checked) = node.state.checked {
+                states.push(STATE_CHECKABLE);
+                if checked {
+                    states.push(STATE_CHECKED);
+                }
+            }
+            if node.role == AccessRole::TextInput {
+                states.push(STATE_SINGLE_LINE);
+                states.push(if node.state.read_only {STATE_READ_ONLY} else {STATE_EDITABLE});
+            }
+        }
+        let mut bits = [0u32; 2];
+        for state in states {
+            bits[(state / 32) as usize] |= 1 << (state % 32);
+        }
+        DbusValue::Array("u".into(), vec![DbusValue::Uint32(bits[0]), DbusValue::Uint32(bits[1])])
+    }
+
+    fn interfaces(&self, target: AtspiTarget) -> Vec<&'static str> {
+        let mut interfaces = vec![ATSPI_ACCESSIBLE];
+        match target {
+            AtspiTarget::App => interfaces.push(ATSPI_APPLICATION),
+            AtspiTarget::Node(id) => {
+                interfaces.push(ATSPI_COMPONENT);
+                match self.nodes[&id].node.role {
+                    AccessRole::Slider | AccessRole::SpinButton => interfaces.push(ATSPI_VALUE),
+                    AccessRole::TextInput | AccessRole::Label | AccessRole::DateEditor => interfaces.push(ATSPI_TEXT),
+                    _ => ()
+                }
+            }
+        }
+        interfaces
+    }
+
+    fn extents(&self, target: AtspiTarget, coord_type: u32) -> Rect {
+        let id = if let AtspiTarget::Node(id) = target {id} else {return Rect::default()};
+        let node = &self.nodes[&id];
+        let mut rect = node.node.rect();
+        if node.parent.is_none() {
+            // the window node already holds its screen rect
+            if coord_type != COORD_TYPE_SCREEN {
+                rect.pos = DVec2::default();
+            }
+            return rect
+        }
+        match coord_type {
+            COORD_TYPE_SCREEN => rect.pos += node.origin,
+            COORD_TYPE_WINDOW => (),
+            _ => if let Some(parent) = node.parent {
+                let parent = &self.nodes[&parent];
+                if parent.parent.is_some() {
+                    rect.pos -= parent.node.rect().pos;
+                }
+            }
+        }
+        rect
+    }
+
+    fn text(&self, target: AtspiTarget) -> String {
+        let node = if let AtspiTarget::Node(id) = target {&self.nodes[&id].node} else {return String::new()};
+        match &node.value {
+            AccessValue::Text(text) => text.clone(),
+            _ => node.name.clone()
+        }
+    }
+
+    fn accessible_at_point(&self, target: AtspiTarget, pos: DVec2, coord_type: u32) -> DbusValue {
+        for child in self.children(target) {
+            let child = AtspiTarget::Node(*child);
+            if self.extents(child, coord_type).contains(pos) {
+                let inner = self.accessible_at_point(child, pos, coord_type);
+                if inner == self.null_ref() {
+                    return self.object_ref(child)
+                }
+                return inner
+            }
+        }
+        self.null_ref()
+    }
+
+    fn property(&self, target: AtspiTarget, interface: &str, name: &str) -> Option<DbusValue> {
+        let node = if let AtspiTarget::Node(id) = target {Some(&self.nodes[&id].node)} else {None};
+        Some(match (interface, name) {
+            (ATSPI_ACCESSIBLE, "Name") => DbusValue::String(self.name(target)),
+            (ATSPI_ACCESSIBLE, "Description") => DbusValue::String(String::new()),
+            (ATSPI_ACCESSIBLE, "Parent") => self.parent(target),
+            (ATSPI_ACCESSIBLE, "ChildCount") => DbusValue::Int32(self.children(target).len() as i32),
+            (ATSPI_ACCESSIBLE, "Locale") => DbusValue::String(std::env::var("LANG").unwrap_or_default()),
+            (ATSPI_ACCESSIBLE, "AccessibleId") => DbusValue::String(match target {
+                AtspiTarget::App => String::new(),
+                AtspiTarget::Node(id) => id.to_string()
+            }),
+            (ATSPI_APPLICATION, "ToolkitName") if node.is_none() => DbusValue::String("makepad".into()),
+            (ATSPI_APPLICATION, "Version") if node.is_none() => DbusValue::String(env!("CARGO_PKG_VERSION").into()),
+            (ATSPI_APPLICATION, "AtspiVersion") if node.is_none() => DbusValue::String("2.1".into()),
+            (ATSPI_APPLICATION, "Id") if node.is_none() => DbusValue::Int32(0),
+            (ATSPI_VALUE, _) => match node.map( | node | &node.value) {
+                Some(AccessValue::Number {value, min, max, step}) => DbusValue::Double(match name {
+                    "CurrentValue" => *value,
+                    "MinimumValue" => *min,
+                    "MaximumValue" => *max,
+                    "MinimumIncrement" => *step,
+                    _ => return None
+                }),
+                _ => return None
+            },
+            (ATSPI_TEXT, "CharacterCount") => DbusValue::Int32(self.text(target).chars().count() as i32),
+            (ATSPI_TEXT, "CaretOffset") => DbusValue::Int32(self.text(target).chars().count() as i32),
+            _ => return None
+        })
+    }
+
+    fn all_properties(&self, target: AtspiTarget, interface: &str) -> DbusValue {
+        let names: &[&str] = match interface {
+            ATSPI_ACCESSIBLE => &["Name", "Description", "Parent", "ChildCount", "Locale", "AccessibleId"],
+            ATSPI_APPLICATION => &["ToolkitName", "Version", "AtspiVersion", "Id"],
+            ATSPI_VALUE => &["CurrentValue", "MinimumValue", "MaximumValue", "MinimumIncrement"],
+            ATSPI_TEXT => &["CharacterCount", "CaretOffset"],
+            _ => &[]
+        };
+        DbusValue::Array("{sv}".into(), names.iter().filter_map( | name | {
+            self.property(target, interface, name).map( | value | {
+                DbusValue::dict_entry(DbusValue::String(name.to_string()), DbusValue::variant(value))
+            })
+        }).collect())
+    }
+
+    fn handle_call(&self, msg: &DbusMessage) -> DbusMessage {
+        let target = if let Some(target) = msg.path.as_deref().and_then( | path | self.resolve(path)) {target} else {
+            return DbusMessage::error(msg, "org.freedesktop.DBus.Error.UnknownObject", "no such accessible")
+        };
+        let arg_str = | i: usize | msg.body.get(i).and_then( | v | v.as_str()).unwrap_or("");
+        let arg_int = | i: usize | msg.body.get(i).and_then( | v | v.as_i64()).unwrap_or(0);
+        let interface = msg.interface.as_deref().unwrap_or("");
+        let member = msg.member.as_deref().unwrap_or("");
+        let body = match (interface, member) {
+            (DBUS_PROPERTIES, "Get") => match self.property(target, arg_str(0), arg_str(1)) {
+                Some(value) => vec![DbusValue::variant(value)],
+                None => return DbusMessage::error(msg, "org.freedesktop.DBus.Error.UnknownProperty", arg_str(1))
+            },
+            (DBUS_PROPERTIES, "GetAll") => vec![self.all_properties(target, arg_str(0))],
+            // the registry assigns the application id, nothing to keep
+            (DBUS_PROPERTIES, "Set") => vec![],
+            ("org.freedesktop.DBus.Peer", "Ping") => vec![],
+            (ATSPI_ACCESSIBLE, "GetChildAtIndex") => {
+                let child = self.children(target).get(arg_int(0) as usize);
+                vec![child.map_or_else( || self.null_ref(), | child | self.object_ref(AtspiTarget::Node(*child)))]
+            }
+            (ATSPI_ACCESSIBLE, "GetChildren") => vec![DbusValue::Array("(so)".into(), self.children(target).iter().map( | child | {
+                self.object_ref(AtspiTarget::Node(*child))
+            }).collect())],
+            (ATSPI_ACCESSIBLE, "GetIndexInParent") => vec![DbusValue::Int32(self.index_in_parent(target))],
+            (ATSPI_ACCESSIBLE, "GetRelationSet") => vec![DbusValue::Array("(ua(so))".into(), vec![])],
+            (ATSPI_ACCESSIBLE, "GetRole") => vec![DbusValue::Uint32(self.role(target).0)],
+            (ATSPI_ACCESSIBLE, "GetRoleName") | (ATSPI_ACCESSIBLE, "GetLocalizedRoleName") => {
+                vec![DbusValue::String(self.role(target).1.into())]
+            }
+            (ATSPI_ACCESSIBLE, "GetState") => vec![self.states(target)],
+            (ATSPI_ACCESSIBLE, "GetAttributes") => vec![DbusValue::Array("{ss}".into(), vec![
+                DbusValue::dict_entry(DbusValue::String("toolkit".into()), DbusValue::String("makepad".into()))
+            ])],
+            (ATSPI_ACCESSIBLE, "GetApplication") => vec![self.object_ref(AtspiTarget::App)],
+            (ATSPI_ACCESSIBLE, "GetInterfaces") => vec![DbusValue::Array("s".into(), self.interfaces(target).iter().map( | i | {
+                DbusValue::String(i.to_string())
+            }).collect())],
+            (ATSPI_COMPONENT, "GetExtents") => {
+                let rect = self.extents(target, arg_int(0) as u32);
+                vec![DbusValue::Struct(vec![
+                    DbusValue::Int32(rect.pos.x as i32),
+                    DbusValue::Int32(rect.pos.y as i32),
+                    DbusValue::Int32(rect.size.x as i32),
+                    DbusValue::Int32(rect.size.y as i32),
+                ])]
+            }
+            (ATSPI_COMPONENT, "GetPosition") => {
+                let rect = self.extents(target, arg_int(0) as u32);
+                vec![DbusValue::Int32(rect.pos.x as i32), DbusValue::Int32(rect.pos.y as i32)]
+            }
+            (ATSPI_COMPONENT, "GetSize") => {
+                let rect = self.extents(target, COORD_TYPE_WINDOW);
+                vec![DbusValue::Int32(rect.size.x as i32), DbusValue::Int32(rect.size.y as i32)]
+            }
+            (ATSPI_COMPONENT, "Contains") => {
+                let pos = dvec2(arg_int(0) as f64, arg_int(1) as f64);
+                vec![DbusValue::Bool(self.extents(target, arg_int(2) as u32).contains(pos))]
+            }
+            (ATSPI_COMPONENT, "GetAccessibleAtPoint") => {
+                let pos = dvec2(arg_int(0) as f64, arg_int(1) as f64);
+                vec![self.accessible_at_point(target, pos, arg_int(2) as u32)]
+            }
+            (ATSPI_COMPONENT, "GetLayer") => {
+                let is_window = matches!(target, AtspiTarget::Node(id) if self.nodes[&id].parent.is_none());
+                vec![DbusValue::Uint32(if is_window {LAYER_WINDOW} else {LAYER_WIDGET})]
+            }
+            (ATSPI_TEXT, "GetText") => {
+                let text = self.text(target);
+                let count = text.chars().count() as i64;
+                let start = arg_int(0).clamp(0, count);
+                let end = if arg_int(1) < 0 {count} else {arg_int(1).clamp(start, count)};
+                vec![DbusValue::String(text.chars().skip(start as usize).take((end - start) as usize).collect())]
+            }
+            _ => return DbusMessage::error(msg, "org.freedesktop.DBus.Error.UnknownMethod", member)
+        };
+        DbusMessage::method_return(msg, body)
+    }
+}
@@ -0,0 +1,388 @@
+use std::ops::Range;
+
+/// A video stream of an AVI file, `frames` are byte ranges of the encoded frames in the file data
+pub struct AviVideoStream {
+    pub codec: [u8; 4],
+    pub width: usize,
+    pub height: usize,
+    /// In seconds
+    pub frame_duration: f64,
+    pub frames: Vec<Range<usize >>,
+}
+
+/// An audio stream of an AVI file, `chunks` are byte ranges of the sample data in the file data
+pub struct AviAudioStream {
+    pub format_tag: u16,
+    pub channel_count: usize,
+    pub sample_rate: usize,
+    pub bits_per_sample: usize,
+    pub block_align: usize,
+    pub chunks: Vec<Range<usize >>,
+}
+
+pub struct AviFile {
+    pub video: Option<AviVideoStream>,
+    pub audio: Option<AviAudioStream>,
+}
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+enum AviStream {
+    Video(AviVideoStream),
+    Audio(AviAudioStream),
+    Other,
+}
+
+struct StreamHeader {
+    fcc_type: [u8; 4],
+    handler: [u8; 4],
+    scale: u32,
+    rate: u32,
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(pos..pos + 2)?.try_into().ok() ?))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok() ?))
+}
+
+fn fourcc_at(data: &[u8], pos: usize) -> Option<[u8; 4]> {
+    data.get(pos..pos + 4)?.try_into().ok()
+}
+
+/// Walks the chunks of a RIFF list, calling back with the fourcc, the payload range and the list type for LIST chunks
+fn for_each_chunk(data: &[u8], range: Range<usize>, f: &mut dyn FnMut([u8; 4], Range<usize>, Option<[u8; 4]>)) {
+    let mut pos = range.start;
+    let end = range.end.min(data.len());
+    while pos + 8 <= end {
+        let (id, size) = if let (Some(id), Some(size)) = (fourcc_at(data, pos), u32_at(data, pos + 4)) {(id, size as usize)} else {break};
+        let start = pos + 8;
+        let stop = (start + size).min(end);
+        if &id == b"LIST" || &id == b"RIFF" {
+            f(id, (start + 4).min(stop)..stop, fourcc_at(data, start));
+        }
+        else {
+            f(id, start..stop, None);
+        }
+        // chunks are padded to an even size
+        pos = start + size + (size & 1);
+    }
+}
+
+impl AviFile {
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"AVI " {
+            return Err("Not an AVI file".to_string())
+        }
+        let mut streams = Vec::new();
+        let mut movi = Vec::new();
+        // OpenDML files continue in extra RIFF AVIX chunks, each with their own movi list
+        for_each_chunk(data, 0..data.len(), &mut | _, range, list_type | {
+            if list_type != Some(*b"AVI ") && list_type != Some(*b"AVIX") {
+                return
+            }
+            for_each_chunk(data, range, &mut | _, range, list_type | {
+                match list_type.as_ref() {
+                    Some(b"hdrl") => for_each_chunk(data, range, &mut | _, range, list_type | {
+                        if list_type == Some(*b"strl") {
+                            streams.push(parse_stream(data, range));
+                        }
+                    }),
+                    Some(b"movi") => movi.push(range),
+                    _ => ()
+                }
+            });
+        });
+        for range in movi {
+            collect_chunks(data, range, &mut streams);
+        }
+        let mut video = None;
+        let mut audio = None;
+        for stream in streams {
+            match stream {
+                AviStream::Video(stream) if video.is_none() => video = Some(stream),
+                AviStream::Audio(stream) if audio.is_none() => audio = Some(stream),
+                _ => ()
+            }
+        }
+        if video.is_none() && audio.is_none() {
+            return Err("AVI file has no audio or video streams".to_string())
+        }
+        Ok(Self {video, audio})
+    }
+
+    /// In seconds
+    pub fn duration(&self) -> f64 {
+        let video = self.video.as_ref().map( | v | v.frames.len() as f64 * v.frame_duration).unwrap_or(0.0);
+        let audio = self.audio.as_ref().map( | a | a.frame_count() as f64 / a.sample_rate.max(1) as f64).unwrap_or(0.0);
+        video.max(audio)
+    }
+}
+
+fn parse_stream(data: &[u8], range: Range<usize>) -> AviStream {
+    let mut header = None;
+    let mut format = None;
+    for_each_chunk(data, range, &mut | id, range, _ | {
+        match &id {
+            b"strh" => header = parse_stream_header(data, range.start),
+            b"strf" => format = Some(range),
+            _ => ()
+        }
+    });
+    let (header, format) = if let (Some(header), Some(format)) = (header, format) {(header, format)} else {
+        return AviStream::Other
+    };
+    let pos = format.start;
+    match &header.fcc_type {
+        b"vids" => {
+            let width = u32_at(data, pos + 4).unwrap_or(0) as i32;
+            let height = u32_at(data, pos + 8).unwrap_or(0) as i32;
+            let codec = fourcc_at(data, pos + 16).unwrap_or(header.handler);
+            AviStream::Video(AviVideoStream {
+                codec,
+                width: width.unsigned_abs() as usize,
+                height: height.unsigned_abs() as usize,
+                frame_duration: if header.rate > 0 {header.scale as f64 / header.rate as f64} else {1.0 / 30.0},
+                frames: Vec::new(),
+            })
+        }
+        b"auds" => {
+            let mut format_tag = u16_at(data, pos).unwrap_or(0);
+            if format_tag == WAVE_FORMAT_EXTENSIBLE {
+                // the sub format guid starts with the actual format tag
+                format_tag = u16_at(data, pos + 24).unwrap_or(0);
+            }
+            AviStream::Audio(AviAudioStream {
+                format_tag,
+                channel_count: u16_at(data, pos + 2).unwrap_or(0) as usize,
+                sample_rate: u32_at(data, pos + 4).unwrap_or(0) as usize,
+                block_align: u16_at(data, pos + 12).unwrap_or(0) as usize,
+                bits_per_sample: u16_at(data, pos + 14).unwrap_or(0) as usize,
+                chunks: Vec::new(),
+            })
+        }
+        _ => AviStream::Other
+    }
+}
+
+fn parse_stream_header(data: &[u8], pos: usize) -> Option<StreamHeader> {
+    Some(StreamHeader {
+        fcc_type: fourcc_at(data, pos) ?,
+        handler: fourcc_at(data, pos + 4) ?,
+        scale: u32_at(data, pos + 20) ?,
+        rate: u32_at(data, pos + 24) ?,
+    })
+}
+
+fn collect_chunks(data: &[u8], range: Range<usize>, streams: &mut Vec<AviStream>) {
+    for_each_chunk(data, range, &mut | id, range, list_type | {
+        if list_type == Some(*b"rec ") {
+            collect_chunks(data, range, streams);
+            return
+        }
+        // chunk ids are the stream number as two digits followed by the kind of data
+        let index = if let Ok(index) = std::str::from_utf8(&id[0..2]).unwrap_or("").parse::<usize>() {index} else {
+            return
+        };
+        match streams.get_mut(index) {
+            Some(AviStream::Video(stream)) if &id[2..4] == b"dc" || &id[2..4] == b"db" => {
+                // empty chunks repeat the previous frame
+                if range.is_empty() {
+                    if let Some(last) = stream.frames.last().cloned() {
+                        stream.frames.push(last);
+                    }
+                }
+                else {
+                    stream.frames.push(range);
+                }
+            }
+            Some(AviStream::Audio(stream)) if &id[2..4] == b"wb" => stream.chunks.push(range),
+            _ => ()
+        }
+    });
+}
+
+impl AviAudioStream {
+    fn bytes_per_frame(&self) -> usize {
+        self.block_align.max(self.channel_count * self.bits_per_sample / 8).max(1)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.chunks.iter().map( | c | c.len()).sum::<usize>() / self.bytes_per_frame()
+    }
+
+    /// Decodes the PCM data to one f32 vector per channel
+    pub fn decode(&self, data: &[u8]) -> Result<Vec<Vec<f32>>, String> {
+        let sample_bytes = self.bits_per_sample / 8;
+        let convert: fn(&[u8]) -> f32 = match (self.format_tag, self.bits_per_sample) {
+            (WAVE_FORMAT_PCM, 8) => | b | (b[0] as f32 - 128.0) / 128.0,
+            (WAVE_FORMAT_PCM, 16) => | b | i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            (WAVE_FORMAT_PCM, 24) => | b | (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8388608.0,
+            (WAVE_FORMAT_PCM, 32) => | b | i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2147483648.0,
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => | b | f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            (format_tag, bits) => return Err(format!("Unsupported AVI audio format {} with {} bits", format_tag, bits))
+        };
+        if self.channel_count == 0 {
+            return Err("AVI audio stream has no channels".to_string())
+        }
+        let bytes_per_frame = self.bytes_per_frame();
+        let mut channels = vec![Vec::with_capacity(self.frame_count()); self.channel_count];
+        let mut pending = Vec::new();
+        for chunk in &self.chunks {
+            // frames can straddle chunk boundaries
+            pending.extend_from_slice(&data[chunk.clone()]);
+            let whole = pending.len() / bytes_per_frame * bytes_per_frame;
+            for frame in pending[0..whole].chunks_exact(bytes_per_frame) {
+                for (channel, samples) in channels.iter_mut().enumerate() {
+                    let pos = channel * sample_bytes;
+                    samples.push(convert(&frame[pos..pos + sample_bytes]));
+                }
+            }
+            pending.drain(0..whole);
+        }
+        Ok(channels)
+    }
+}
+
+/// Webcams and AVI files often leave the huffman tables out of MJPEG frames. An AVI1 marker
+/// after the start of image makes the jpeg decoder fall back to the standard tables.
+pub fn mjpeg_with_avi1_marker(frame: &[u8], out: &mut Vec<u8>) {
+    out.clear();
+    out.extend_from_slice(&[0xff, 0xd8, 0xff, 0xe0, 0x00, 0x07, b'A', b'V', b'I', b'1', 0x00]);
+    if frame.len() >= 2 {
+        out.extend_from_slice(&frame[2..]);
+    }
+}
+
+#[cfg(test)]
+pub (crate) mod tests {
+    use super::*;
+
+    pub fn chunk(id: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        out.extend_from_slice(payload);
+        if payload.len() & 1 == 1 {
+            out.push(0);
+        }
+        out
+    }
+
+    pub fn list(id: &[u8; 4], list_type: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut payload = list_type.to_vec();
+        for c in chunks {
+            payload.extend_from_slice(c);
+        }
+        chunk(id, &payload)
+    }
+
+    fn strh(fcc_type: &[u8; 4], handler: &[u8; 4], scale: u32, rate: u32) -> Vec<u8> {
+        let mut payload = vec![0; 56];
+        payload[0..4].copy_from_slice(fcc_type);
+        payload[4..8].copy_from_slice(handler);
+        payload[20..24].copy_from_slice(&scale.to_le_bytes());
+        payload[24..28].copy_from_slice(&rate.to_le_bytes());
+        chunk(b"strh", &payload)
+    }
+
+    /// A stream list for an MJPEG video stream at `rate` frames per second
+    pub fn video_strl(width: u32, height: u32, rate: u32) -> Vec<u8> {
+        let mut bitmap_info = vec![0; 40];
+        bitmap_info[0..4].copy_from_slice(&40u32.to_le_bytes());
+        bitmap_info[4..8].copy_from_slice(&width.to_le_bytes());
+        // bottom up bitmaps have a negative height
+        bitmap_info[8..12].copy_from_slice(&(-(height as i32)).to_le_bytes());
+        bitmap_info[16..20].copy_from_slice(b"MJPG");
+        list(b"LIST", b"strl", &[strh(b"vids", b"mjpg", 1, rate), chunk(b"strf", &bitmap_info)])
+    }
+
+    fn pcm_strl(channel_count: u16, sample_rate: u32) -> Vec<u8> {
+        let mut wave_format = Vec::new();
+        wave_format.extend_from_slice(&WAVE_FORMAT_PCM.to_le_bytes());
+        wave_format.extend_from_slice(&channel_count.to_le_bytes());
+        wave_format.extend_from_slice(&sample_rate.to_le_bytes());
+        wave_format.extend_from_slice(&(sample_rate * channel_count as u32 * 2).to_le_bytes());
+        wave_format.extend_from_slice(&(channel_count * 2).to_le_bytes());
+        wave_format.extend_from_slice(&16u16.to_le_bytes());
+        list(b"LIST", b"strl", &[strh(b"auds", &[0; 4], 1, sample_rate), chunk(b"strf", &wave_format)])
+    }
+
+    /// An AVI file with the stream lists in its header, then the movi chunks and an idx1 index
+    pub fn avi_file(strls: &[Vec<u8>], movi: &[Vec<u8>]) -> Vec<u8> {
+        let mut hdrl = vec![chunk(b"avih", &[0; 56])];
+        hdrl.extend_from_slice(strls);
+        list(b"RIFF", b"AVI ", &[
+            list(b"LIST", b"hdrl", &hdrl),
+            list(b"LIST", b"movi", movi),
+            chunk(b"idx1", &[b'0', b'0', b'd', b'c', 0x10, 0, 0, 0, 4, 0, 0, 0, 3, 0, 0, 0]),
+        ])
+    }
+
+    fn frame_bytes<'a>(data: &'a [u8], stream: &AviVideoStream) -> Vec<&'a [u8]> {
+        stream.frames.iter().map( | range | &data[range.clone()]).collect()
+    }
+
+    #[test]
+    fn parses_streams_and_movi_chunks() {
+        let data = avi_file(&[video_strl(32, 16, 25), pcm_strl(2, 8000)], &[
+            chunk(b"00dc", &[1, 2, 3]),
+            chunk(b"01wb", &[1, 0, 2, 0, 3, 0]),
+            // an empty frame repeats the last one
+            chunk(b"00dc", &[]),
+            list(b"LIST", b"rec ", &[chunk(b"00db", &[4, 5]), chunk(b"01wb", &[4, 0, 5, 0])]),
+            chunk(b"JUNK", &[0; 5]),
+            // chunks of streams that aren't in the header are skipped
+            chunk(b"07dc", &[9]),
+        ]);
+        let avi = AviFile::parse(&data).unwrap();
+        let video = avi.video.as_ref().unwrap();
+        assert_eq!(&video.codec, b"MJPG");
+        assert_eq!((video.width, video.height), (32, 16));
+        assert_eq!(video.frame_duration, 1.0 / 25.0);
+        assert_eq!(frame_bytes(&data, video), [&[1, 2, 3][..], &[1, 2, 3], &[4, 5]]);
+
+        let audio = avi.audio.as_ref().unwrap();
+        assert_eq!((audio.channel_count, audio.sample_rate, audio.bits_per_sample), (2, 8000, 16));
+        assert_eq!(audio.frame_count(), 2);
+        // the second frame straddles the chunks
+        let scale = 1.0 / 32768.0;
+        assert_eq!(audio.decode(&data).unwrap(), [vec![scale, 3.0 * scale], vec![2.0 * scale, 4.0 * scale]]);
+        assert_eq!(avi.duration(), 3.0 / 25.0);
+    }
+
+    #[test]
+    fn follows_opendml_avix_riffs() {
+        let mut data = avi_file(&[video_strl(8, 8, 30)], &[chunk(b"00dc", &[1])]);
+        data.extend_from_slice(&list(b"RIFF", b"AVIX", &[list(b"LIST", b"movi", &[chunk(b"00dc", &[2, 2])])]));
+        let avi = AviFile::parse(&data).unwrap();
+        assert_eq!(frame_bytes(&data, avi.video.as_ref().unwrap()), [&[1][..], &[2, 2]]);
+    }
+
+    #[test]
+    fn survives_truncated_files() {
+        assert!(AviFile::parse(b"RIFF").is_err());
+        assert!(AviFile::parse(&list(b"RIFF", b"WAVE", &[])).is_err());
+        let data = avi_file(&[video_strl(8, 8, 30)], &[chunk(b"00dc", &[1; 10]), chunk(b"00dc", &[2; 10])]);
+        let movi = data.windows(4).position( | w | w == b"movi").unwrap();
+        let mut frame_count = 0;
+        for len in 0..=data.len() {
+            let data = &data[0..len];
+            match AviFile::parse(data) {
+                Ok(avi) => {
+                    // frames cut short end with the data, and the ones before them stay
+                    let video = avi.video.unwrap();
+                    assert!(video.frames.iter().all( | range | range.end <= data.len()));
+                    assert!(video.frames.len() >= frame_count);
+                    frame_count = video.frames.len();
+                }
+                // only a header cut short loses the stream
+                Err(_) => assert!(len < movi)
+            }
+        }
+        assert_eq!(frame_count, 2);
+    }
+}
@@ -0,0 +1,759 @@
+//! A small D-Bus client, just enough of the wire protocol to serve objects
+//! on a bus (used for the AT-SPI accessibility export).
+
+use {
+    std::{
+        io::{Read, Write},
+        os::unix::net::{UnixStream, SocketAddr},
+        os::linux::net::SocketAddrExt,
+        sync::{Arc, Mutex},
+        sync::atomic::{AtomicU32, Ordering},
+        time::{Duration, Instant},
+    },
+    super::libc_sys,
+};
+
+pub const DBUS_METHOD_CALL: u8 = 1;
+pub const DBUS_METHOD_RETURN: u8 = 2;
+pub const DBUS_ERROR: u8 = 3;
+pub const DBUS_SIGNAL: u8 = 4;
+
+pub const DBUS_FLAG_NO_REPLY_EXPECTED: u8 = 1;
+
+/// How long a blocking `call` waits for its reply before giving up
+pub const DBUS_CALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+// the limits from the D-Bus specification
+const MAX_MESSAGE_LEN: usize = 1 << 27;
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug)]
+pub struct DbusError(pub String);
+
+impl From<std::io::Error> for DbusError {
+    fn from(err: std::io::Error) -> Self {
+        DbusError(err.to_string())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DbusValue {
+    Byte(u8),
+    Bool(bool),
+    Int32(i32),
+    Uint32(u32),
+    Int64(i64),
+    Uint64(u64),
+    Double(f64),
+    String(String),
+    ObjectPath(String),
+    Signature(String),
+    /// The element signature is kept so empty arrays still know their type
+    Array(String, Vec<DbusValue>),
+    Struct(Vec<DbusValue>),
+    DictEntry(Box<DbusValue>, Box<DbusValue>),
+    Variant(Box<DbusValue>),
+}
+
+impl DbusValue {
+    pub fn signature(&self) -> String {
+        match self {
+            Self::Byte(_) => "y".into(),
+            Self::Bool(_) => "b".into(),
+            Self::Int32(_) => "i".into(),
+            Self::Uint32(_) => "u".into(),
+            Self::Int64(_) => "x".into(),
+            Self::Uint64(_) => "t".into(),
+            Self::Double(_) => "d".into(),
+            Self::String(_) => "s".into(),
+            Self::ObjectPath(_) => "o".into(),
+            Self::Signature(_) => "g".into(),
+            Self::Array(elem, _) => format!("a{}", elem),
+            Self::Struct(fields) => format!("({})", fields.iter().map( | v | v.signature()).collect::<String>()),
+            Self::DictEntry(k, v) => format!("{{{}{}}}", k.signature(), v.signature()),
+            Self::Variant(_) => "v".into(),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) | Self::ObjectPath(s) | Self::Signature(s) => Some(s),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Byte(v) => Some(*v as i64),
+            Self::Int32(v) => Some(*v as i64),
+            Self::Uint32(v) => Some(*v as i64),
+            Self::Int64(v) => Some(*v),
+            Self::Uint64(v) => Some(*v as i64),
+            _ => None
+        }
+    }
+
+    pub fn variant(value: DbusValue) -> Self {
+        Self::Variant(Box::new(value))
+    }
+
+    pub fn dict_entry(key: DbusValue, value: DbusValue) -> Self {
+        Self::DictEntry(Box::new(key), Box::new(value))
+    }
+}
+
+fn alignment(code: u8) -> usize {
+    match code {
+        b'n' | b'q' => 2,
+        b'b' | b'i' | b'u' | b'h' | b's' | b'o' | b'a' => 4,
+        b'x' | b't' | b'd' | b'(' | b'{' => 8,
+        _ => 1
+    }
+}
+
+/// Splits the first complete type off a signature.
+fn split_type(sig: &str) -> Result<(&str, &str), DbusError> {
+    let bytes = sig.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'a' => {
+                i += 1;
+                continue;
+            }
+            b'(' | b'{' => depth += 1,
+            b')' | b'}' => depth -= 1,
+            _ => ()
+        }
+        i += 1;
+        if depth == 0 {
+            return Ok((&sig[..i], &sig[i..]))
+        }
+    }
+    Err(DbusError(format!("incomplete signature {}", sig)))
+}
+
+#[derive(Default)]
+struct DbusWriter {
+    buf: Vec<u8>
+}
+
+impl DbusWriter {
+    fn pad(&mut self, align: usize) {
+        let len = self.buf.len().div_ceil(align) * align;
+        self.buf.resize(len, 0);
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.pad(4);
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn u64(&mut self, v: u64) {
+        self.pad(8);
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn string(&mut self, s: &str) {
+        self.u32(s.len() as u32);
+        self.buf.extend_from_slice(s.as_bytes());
+        self.buf.push(0);
+    }
+
+    fn signature(&mut self, s: &str) {
+        self.buf.push(s.len() as u8);
+        self.buf.extend_from_slice(s.as_bytes());
+        self.buf.push(0);
+    }
+
+    fn value(&mut self, value: &DbusValue) {
+        match value {
+            DbusValue::Byte(v) => self.buf.push(*v),
+            DbusValue::Bool(v) => self.u32(*v as u32),
+            DbusValue::Int32(v) => self.u32(*v as u32),
+            DbusValue::Uint32(v) => self.u32(*v),
+            DbusValue::Int64(v) => self.u64(*v as u64),
+            DbusValue::Uint64(v) => self.u64(*v),
+            DbusValue::Double(v) => self.u64(v.to_bits()),
+            DbusValue::String(s) | DbusValue::ObjectPath(s) => self.string(s),
+            DbusValue::Signature(s) => self.signature(s),
+            DbusValue::Array(elem, items) => {
+                self.u32(0);
+                let len_at = self.buf.len() - 4;
+                // the padding to the first element doesn't count towards the length
+                self.pad(alignment(elem.as_bytes().first().copied().unwrap_or(b'y')));
+                let start = self.buf.len();
+                for item in items {
+                    self.value(item);
+                }
+                let len = (self.buf.len() - start) as u32;
+                self.buf[len_at..len_at + 4].copy_from_slice(&len.to_le_bytes());
+            }
+            DbusValue::Struct(fields) => {
+                self.pad(8);
+                for field in fields {
+                    self.value(field);
+                }
+            }
+            DbusValue::DictEntry(k, v) => {
+                self.pad(8);
+                self.value(k);
+                self.value(v);
+            }
+            DbusValue::Variant(v) => {
+                self.signature(&v.signature());
+                self.value(v);
+            }
+        }
+    }
+}
+
+struct DbusReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+    depth: usize,
+}
+
+impl<'a> DbusReader<'a> {
+    fn align(&mut self, align: usize) {
+        self.pos = self.pos.div_ceil(align) * align;
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DbusError> {
+        if self.pos + len > self.buf.len() {
+            return Err(DbusError("message truncated".into()))
+        }
+        let slice = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> Result<u16, DbusError> {
+        self.align(2);
+        let b: [u8; 2] = self.take(2)?.try_into().unwrap();
+        Ok(if self.big_endian {u16::from_be_bytes(b)} else {u16::from_le_bytes(b)})
+    }
+
+    fn u32(&mut self) -> Result<u32, DbusError> {
+        self.align(4);
+        let b: [u8; 4] = self.take(4)?.try_into().unwrap();
+        Ok(if self.big_endian {u32::from_be_bytes(b)} else {u32::from_le_bytes(b)})
+    }
+
+    fn u64(&mut self) -> Result<u64, DbusError> {
+        self.align(8);
+        let b: [u8; 8] = self.take(8)?.try_into().unwrap();
+        Ok(if self.big_endian {u64::from_be_bytes(b)} else {u64::from_le_bytes(b)})
+    }
+
+    fn string(&mut self) -> Result<String, DbusError> {
+        let len = self.u32()? as usize;
+        let s = String::from_utf8_lossy(self.take(len)?).to_string();
+        self.take(1)?;
+        Ok(s)
+    }
+
+    fn signature(&mut self) -> Result<String, DbusError> {
+        let len = self.take(1)?[0] as usize;
+        let s = String::from_utf8_lossy(self.take(len)?).to_string();
+        self.take(1)?;
+        Ok(s)
+    }
+
+    /// Reads one value of the single complete type `sig`.
+    fn value(&mut self, sig: &str) -> Result<DbusValue, DbusError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(DbusError("values nested too deep".into()))
+        }
+        self.depth += 1;
+        let value = self.value_inner(sig);
+        self.depth -= 1;
+        value
+    }
+
+    fn value_inner(&mut self, sig: &str) -> Result<DbusValue, DbusError> {
+        let malformed = || DbusError(format!("malformed signature {:?}", sig));
+        let code = *sig.as_bytes().first().ok_or_else(malformed)?;
+        // containers have to hold something, an empty struct would make an array read forever
+        let inner = match code {
+            b'(' if sig.len() > 2 && sig.ends_with(')') => &sig[1..sig.len() - 1],
+            b'{' if sig.len() > 2 && sig.ends_with('}') => &sig[1..sig.len() - 1],
+            b'a' if sig.len() > 1 => &sig[1..],
+            b'(' | b'{' | b'a' => return Err(malformed()),
+            _ => ""
+        };
+        Ok(match code {
+            b'y' => DbusValue::Byte(self.take(1)?[0]),
+            b'b' => DbusValue::Bool(self.u32()? != 0),
+            b'n' => DbusValue::Int32(self.u16()? as i16 as i32),
+            b'q' => DbusValue::Uint32(self.u16()? as u32),
+            b'i' => DbusValue::Int32(self.u32()? as i32),
+            b'u' | b'h' => DbusValue::Uint32(self.u32()?),
+            b'x' => DbusValue::Int64(self.u64()? as i64),
+            b't' => DbusValue::Uint64(self.u64()?),
+            b'd' => DbusValue::Double(f64::from_bits(self.u64()?)),
+            b's' => DbusValue::String(self.string()?),
+            b'o' => DbusValue::ObjectPath(self.string()?),
+            b'g' => DbusValue::Signature(self.signature()?),
+            b'a' => {
+                let len = self.u32()? as usize;
+                let elem = inner;
+                self.align(alignment(elem.as_bytes()[0]));
+                let end = self.pos + len;
+                if end > self.buf.len() {
+                    return Err(DbusError("message truncated".into()))
+                }
+                let mut items = Vec::new();
+                while self.pos < end {
+                    items.push(self.value(elem)?);
+                }
+                DbusValue::Array(elem.to_string(), items)
+            }
+            b'(' => {
+                self.align(8);
+                let mut inner = inner;
+                let mut fields = Vec::new();
+                while !inner.is_empty() {
+                    let (ty, rest) = split_type(inner)?;
+                    fields.push(self.value(ty)?);
+                    inner = rest;
+                }
+                DbusValue::Struct(fields)
+            }
+            b'{' => {
+                self.align(8);
+                let (key_ty, rest) = split_type(inner)?;
+                let (value_ty, _) = split_type(rest)?;
+                let key = self.value(key_ty)?;
+                let value = self.value(value_ty)?;
+                DbusValue::dict_entry(key, value)
+            }
+            b'v' => {
+                let sig = self.signature()?;
+                DbusValue::variant(self.value(&sig)?)
+            }
+            code => return Err(DbusError(format!("unsupported type code {}", code as char)))
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DbusMessage {
+    pub msg_type: u8,
+    pub flags: u8,
+    pub serial: u32,
+    pub path: Option<String>,
+    pub interface: Option<String>,
+    pub member: Option<String>,
+    pub error_name: Option<String>,
+    pub reply_serial: Option<u32>,
+    pub destination: Option<String>,
+    pub sender: Option<String>,
+    pub body: Vec<DbusValue>,
+}
+
+impl DbusMessage {
+    pub fn method_call(destination: &str, path: &str, interface: &str, member: &str, body: Vec<DbusValue>) -> Self {
+        Self {
+            msg_type: DBUS_METHOD_CALL,
+            destination: Some(destination.into()),
+            path: Some(path.into()),
+            interface: Some(interface.into()),
+            member: Some(member.into()),
+            body,
+            ..Default::default()
+        }
+    }
+
+    pub fn signal(path: &str, interface: &str, member: &str, body: Vec<DbusValue>) -> Self {
+        Self {
+            msg_type: DBUS_SIGNAL,
+            flags: DBUS_FLAG_NO_REPLY_EXPECTED,
+            path: Some(path.into()),
+            interface: Some(interface.into()),
+            member: Some(member.into()),
+            body,
+            ..Default::default()
+        }
+    }
+
+    pub fn method_return(call: &DbusMessage, body: Vec<DbusValue>) -> Self {
+        Self {
+            msg_type: DBUS_METHOD_RETURN,
+            flags: DBUS_FLAG_NO_REPLY_EXPECTED,
+            reply_serial: Some(call.serial),
+            destination: call.sender.clone(),
+            body,
+            ..Default::default()
+        }
+    }
+
+    pub fn error(call: &DbusMessage, error_name: &str, text: &str) -> Self {
+        Self {
+            msg_type: DBUS_ERROR,
+            flags: DBUS_FLAG_NO_REPLY_EXPECTED,
+            error_name: Some(error_name.into()),
+            reply_serial: Some(call.serial),
+            destination: call.sender.clone(),
+            body: vec![DbusValue::String(text.into())],
+            ..Default::default()
+        }
+    }
+
+    pub fn signature(&self) -> String {
+        self.body.iter().map( | v | v.signature()).collect()
+    }
+
+    fn encode(&self, serial: u32) -> Vec<u8> {
+        let mut body = DbusWriter::default();
+        for value in &self.body {
+            body.value(value);
+        }
+        fn field(code: u8, value: DbusValue) -> DbusValue {
+            DbusValue::Struct(vec![DbusValue::Byte(code), DbusValue::variant(value)])
+        }
+        let mut fields = Vec::new();
+        if let Some(path) = &self.path {
+            fields.push(field(1, DbusValue::ObjectPath(path.clone())));
+        }
+        if let Some(interface) = &self.interface {
+            fields.push(field(2, DbusValue::String(interface.clone())));
+        }
+        if let Some(member) = &self.member {
+            fields.push(field(3, DbusValue::String(member.clone())));
+        }
+        if let Some(error_name) = &self.error_name {
+            fields.push(field(4, DbusValue::String(error_name.clone())));
+        }
+        if let Some(reply_serial) = self.reply_serial {
+            fields.push(field(5, DbusValue::Uint32(reply_serial)));
+        }
+        if let Some(destination) = &self.destination {
+            fields.push(field(6, DbusValue::String(destination.clone())));
+        }
+        let signature = self.signature();
+        if !signature.is_empty() {
+            fields.push(field(8, DbusValue::Signature(signature)));
+        }
+        let mut out = DbusWriter::default();
+        out.buf.extend_from_slice(&[b'l', self.msg_type, self.flags, 1]);
+        out.u32(body.buf.len() as u32);
+        out.u32(serial);
+        out.value(&DbusValue::Array("(yv)".into(), fields));
+        out.pad(8);
+        out.buf.extend_from_slice(&body.buf);
+        out.buf
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self, DbusError> {
+        let mut reader = DbusReader {buf, pos: 0, big_endian: buf.first() == Some(&b'B'), depth: 0};
+        let fixed = reader.take(4)?;
+        let mut msg = DbusMessage {
+            msg_type: fixed[1],
+            flags: fixed[2],
+            ..Default::default()
+        };
+        reader.u32()?;
+        msg.serial = reader.u32()?;
+        let mut signature = String::new();
+        if let DbusValue::Array(_, fields) = reader.value("a(yv)")? {
+            for field in fields {
+                if let DbusValue::Struct(field) = field {
+                    let (code, value) = if let [code, DbusValue::Variant(value)] = field.as_slice() {(code, value)} else {continue};
+                    match code {
+                        DbusValue::Byte(1) => msg.path = value.as_str().map( | s | s.to_string()),
+                        DbusValue::Byte(2) => msg.interface = value.as_str().map( | s | s.to_string()),
+                        DbusValue::Byte(3) => msg.member = value.as_str().map( | s | s.to_string()),
+                        DbusValue::Byte(4) => msg.error_name = value.as_str().map( | s | s.to_string()),
+                        DbusValue::Byte(5) => msg.reply_serial = value.as_i64().map( | v | v as u32),
+                        DbusValue::Byte(6) => msg.destination = value.as_str().map( | s | s.to_string()),
+                        DbusValue::Byte(7) => msg.sender = value.as_str().map( | s | s.to_string()),
+                        DbusValue::Byte(8) => signature = value.as_str().unwrap_or("").to_string(),
+                        _ => ()
+                    }
+                }
+            }
+        }
+        reader.align(8);
+        let mut sig = signature.as_str();
+        while !sig.is_empty() {
+            let (ty, rest) = split_type(sig)?;
+            msg.body.push(reader.value(ty)?);
+            sig = rest;
+        }
+        Ok(msg)
+    }
+}
+
+/// The writing half of a connection, it can be cloned into other threads to send signals.
+#[derive(Clone)]
+pub struct DbusSender {
+    stream: Arc<Mutex<UnixStream>>,
+    serial: Arc<AtomicU32>,
+}
+
+impl DbusSender {
+    pub fn send(&self, msg: &DbusMessage) -> Result<u32, DbusError> {
+        let serial = self.serial.fetch_add(1, Ordering::Relaxed);
+        let data = msg.encode(serial);
+        self.stream.lock().unwrap().write_all(&data)?;
+        Ok(serial)
+    }
+}
+
+pub struct DbusConnection {
+    stream: UnixStream,
+    sender: DbusSender,
+    pub unique_name: String,
+}
+
+impl DbusConnection {
+    pub fn session() -> Result<Self, DbusError> {
+        let address = std::env::var("DBUS_SESSION_BUS_ADDRESS").unwrap_or_else( | _ | {
+            format!("unix:path=/run/user/{}/bus", unsafe {libc_sys::getuid()})
+        });
+        Self::open(&address)
+    }
+
+    /// Connects to the first address in a D-Bus address list that works, authenticates and says hello.
+    pub fn open(address: &str) -> Result<Self, DbusError> {
+        let mut last_err = DbusError(format!("no usable address in {}", address));
+        for address in address.split(';') {
+            match Self::connect(address) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(DBUS_CALL_TIMEOUT))?;
+                    let mut conn = Self {
+                        sender: DbusSender {
+                            stream: Arc::new(Mutex::new(stream.try_clone()?)),
+                            serial: Arc::new(AtomicU32::new(1)),
+                        },
+                        stream,
+                        unique_name: String::new(),
+                    };
+                    conn.authenticate()?;
+                    let reply = conn.call(DbusMessage::method_call(
+                        "org.freedesktop.DBus",
+                        "/org/freedesktop/DBus",
+                        "org.freedesktop.DBus",
+                        "Hello",
+                        vec![]
+                    ))?;
+                    conn.unique_name = reply.body.first().and_then( | v | v.as_str()).unwrap_or("").to_string();
+                    return Ok(conn)
+                }
+                Err(err) => last_err = err
+            }
+        }
+        Err(last_err)
+    }
+
+    fn connect(address: &str) -> Result<UnixStream, DbusError> {
+        let params = address.strip_prefix("unix:").ok_or_else( || DbusError(format!("unsupported transport {}", address)))?;
+        for param in params.split(',') {
+            if let Some((key, value)) = param.split_once('=') {
+                match key {
+                    "path" => return Ok(UnixStream::connect(unescape(value))?),
+                    "abstract" => {
+                        let addr = SocketAddr::from_abstract_name(unescape(value).as_bytes())?;
+                        return Ok(UnixStream::connect_addr(&addr)?)
+                    }
+                    _ => ()
+                }
+            }
+        }
+        Err(DbusError(format!("no socket in {}", address)))
+    }
+
+    fn authenticate(&mut self) -> Result<(), DbusError> {
+        let uid = unsafe {libc_sys::getuid()}.to_string();
+        let hex_uid: String = uid.bytes().map( | b | format!("{:02x}", b)).collect();
+        self.stream.write_all(format!("\0AUTH EXTERNAL {}\r\n", hex_uid).as_bytes())?;
+        // read the reply a byte at a time so nothing of the binary stream gets buffered away
+        let mut line = Vec::new();
+        let mut byte = [0u8];
+        while !line.ends_with(b"\r\n") {
+            self.stream.read_exact(&mut byte)?;
+            line.push(byte[0]);
+        }
+        if !line.starts_with(b"OK") {
+            return Err(DbusError(format!("authentication failed: {}", String::from_utf8_lossy(&line).trim())))
+        }
+        self.stream.write_all(b"BEGIN\r\n")?;
+        Ok(())
+    }
+
+    pub fn sender(&self) -> DbusSender {
+        self.sender.clone()
+    }
+
+    pub fn read_message(&mut self) -> Result<DbusMessage, DbusError> {
+        let mut fixed = [0u8; 16];
+        self.stream.read_exact(&mut fixed)?;
+        let read_u32 = | at: usize | {
+            let b: [u8; 4] = fixed[at..at + 4].try_into().unwrap();
+            if fixed[0] == b'B' {u32::from_be_bytes(b)} else {u32::from_le_bytes(b)}
+        };
+        let body_len = read_u32(4) as usize;
+        let fields_len = read_u32(12) as usize;
+        if body_len > MAX_MESSAGE_LEN || fields_len > MAX_MESSAGE_LEN {
+            return Err(DbusError("message too long".into()))
+        }
+        let header_len = (16 + fields_len).div_ceil(8) * 8;
+        let mut buf = vec![0u8; header_len + body_len];
+        buf[0..16].copy_from_slice(&fixed);
+        self.stream.read_exact(&mut buf[16..])?;
+        DbusMessage::decode(&buf)
+    }
+
+    /// Sets how long `read_message` blocks, `None` waits forever. Connections start out
+    /// with `DBUS_CALL_TIMEOUT` so a stalled bus can't hang the handshake.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> Result<(), DbusError> {
+        Ok(self.stream.set_read_timeout(timeout)?)
+    }
+
+    /// Sends a method call and blocks for its reply, anything else that arrives meanwhile is dropped.
+    /// Gives up after `DBUS_CALL_TIMEOUT`.
+    pub fn call(&mut self, msg: DbusMessage) -> Result<DbusMessage, DbusError> {
+        let serial = self.sender.send(&msg)?;
+        let deadline = Instant::now() + DBUS_CALL_TIMEOUT;
+        loop {
+            if Instant::now() > deadline {
+                return Err(DbusError(format!("no reply to {}", msg.member.as_deref().unwrap_or(""))))
+            }
+            let reply = self.read_message()?;
+            if reply.reply_serial != Some(serial) {
+                continue;
+            }
+            if reply.msg_type == DBUS_ERROR {
+                let text = reply.body.first().and_then( | v | v.as_str()).unwrap_or("");
+                return Err(DbusError(format!("{} {}", reply.error_name.unwrap_or_default(), text)))
+            }
+            return Ok(reply)
+        }
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 3 <= bytes.len() {
+            if let Ok(b) = u8::from_str_radix(&value[i + 1..i + 3], 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_call() -> DbusMessage {
+        let mut msg = DbusMessage::method_call("org.a11y.atspi.Registry", "/org/a11y/atspi/accessible/root", "org.a11y.atspi.Socket", "Embed", vec![
+            DbusValue::Byte(7),
+            DbusValue::Bool(true),
+            DbusValue::Int32(-3),
+            DbusValue::Uint64(1 << 40),
+            DbusValue::Double(0.25),
+            DbusValue::Struct(vec![DbusValue::String(":1.42".into()), DbusValue::ObjectPath("/a/b".into())]),
+            DbusValue::Array("{sv}".into(), vec![
+                DbusValue::dict_entry(DbusValue::String("name".into()), DbusValue::variant(DbusValue::String("ok".into()))),
+                DbusValue::dict_entry(DbusValue::String("count".into()), DbusValue::variant(DbusValue::Int64(-9))),
+            ]),
+            DbusValue::Array("u".into(), vec![]),
+            DbusValue::Signature("a(so)".into()),
+        ]);
+        msg.flags = DBUS_FLAG_NO_REPLY_EXPECTED;
+        msg
+    }
+
+    fn read(sig: &str, buf: &[u8]) -> Result<DbusValue, DbusError> {
+        DbusReader {buf, pos: 0, big_endian: false, depth: 0}.value(sig)
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let msg = sample_call();
+        let data = msg.encode(12);
+        let decoded = DbusMessage::decode(&data).unwrap();
+        assert_eq!(decoded.serial, 12);
+        assert_eq!(decoded.msg_type, DBUS_METHOD_CALL);
+        assert_eq!(decoded.flags, DBUS_FLAG_NO_REPLY_EXPECTED);
+        assert_eq!(decoded.path, msg.path);
+        assert_eq!(decoded.interface, msg.interface);
+        assert_eq!(decoded.member, msg.member);
+        assert_eq!(decoded.destination, msg.destination);
+        assert_eq!(decoded.body, msg.body);
+    }
+
+    #[test]
+    fn reply_round_trip() {
+        let mut call = sample_call();
+        call.serial = 5;
+        call.sender = Some(":1.9".into());
+        let error = DbusMessage::error(&call, "org.freedesktop.DBus.Error.UnknownMethod", "nope");
+        let decoded = DbusMessage::decode(&error.encode(6)).unwrap();
+        assert_eq!(decoded.msg_type, DBUS_ERROR);
+        assert_eq!(decoded.reply_serial, Some(5));
+        assert_eq!(decoded.destination.as_deref(), Some(":1.9"));
+        assert_eq!(decoded.error_name.as_deref(), Some("org.freedesktop.DBus.Error.UnknownMethod"));
+        assert_eq!(decoded.body, vec![DbusValue::String("nope".into())]);
+    }
+
+    #[test]
+    fn truncated_message_is_an_error() {
+        let data = sample_call().encode(1);
+        for len in 0..data.len() {
+            assert!(DbusMessage::decode(&data[..len]).is_err(), "decoded a message cut at {}", len);
+        }
+    }
+
+    #[test]
+    fn malformed_signatures_are_errors() {
+        let buf = [0u8; 64];
+        for sig in ["", "a", "(", "()", "{}", "{s", "(ii", "{s}", "z"] {
+            assert!(read(sig, &buf).is_err(), "read a value for {:?}", sig);
+        }
+        assert!(split_type("a").is_err());
+        assert!(split_type("(i").is_err());
+        assert_eq!(split_type("a(so)u").unwrap(), ("a(so)", "u"));
+    }
+
+    #[test]
+    fn malformed_values_are_errors() {
+        // a variant with an empty signature
+        assert!(read("v", &[0, 0]).is_err());
+        // a variant claiming a bare array
+        assert!(read("v", &[1, b'a', 0, 0, 0, 0, 0, 0]).is_err());
+        // an array longer than the message
+        assert!(read("ay", &[200, 0, 0, 0, 1, 2]).is_err());
+        // a string without its terminator
+        assert!(read("s", &[3, 0, 0, 0, b'a', b'b', b'c']).is_err());
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        // a chain of variants, each holding the next one
+        let mut buf = Vec::new();
+        for _ in 0..100 {
+            buf.extend_from_slice(&[1, b'v', 0]);
+        }
+        assert!(read("v", &buf).is_err());
+    }
+
+    #[test]
+    fn unescapes_addresses() {
+        assert_eq!(unescape("/run/user/1000/bus"), "/run/user/1000/bus");
+        assert_eq!(unescape("/tmp/dbus%2dtest"), "/tmp/dbus-test");
+        assert_eq!(unescape("/tmp/bad%zz"), "/tmp/bad%zz");
+    }
+}
@@ -297,6 +297,24 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     direct_app.timers.stop_timer(timer_id);
                 },
+                CxOsOp::PrepareVideoPlayback(video_id, source, texture_handle, autoplay, is_looping) => {
+                    self.os.media.prepare_video_playback(video_id, source, texture_handle, autoplay, is_looping);
+                },
+                CxOsOp::BeginVideoPlayback(video_id) | CxOsOp::ResumeVideoPlayback(video_id) => {
+                    self.os.media.set_video_playing(video_id, true);
+                },
+                CxOsOp::PauseVideoPlayback(video_id) => {
+                    self.os.media.set_video_playing(video_id, false);
+                },
+                CxOsOp::MuteVideoPlayback(video_id) => {
+                    self.os.media.set_video_muted(video_id, true);
+                },
+                CxOsOp::UnmuteVideoPlayback(video_id) => {
+                    self.os.media.set_video_muted(video_id, false);
+                },
+                CxOsOp::CleanupVideoPlaybackResources(video_id) => {
+                    self.os.media.cleanup_video_playback(video_id);
+                },
                 _ => ()
             }
         }
@@ -1,10 +1,13 @@
 
 use {
+    std::collections::HashMap,
     std::sync::{Arc, Mutex},
     self::super::{
         alsa_audio::AlsaAudioAccess,
         pulse_audio::PulseAudioAccess,
         alsa_midi::*,
+        v4l2_video::V4l2Access,
+        video_playback::LinuxVideoPlayer,
     },
     crate::{
         cx::Cx,
@@ -14,6 +17,7 @@ use {
         midi::*,
         video::*,
         media_api::CxMediaApi,
+        makepad_live_id::LiveId,
     }
 };
 
@@ -35,6 +39,13 @@ impl Cx {
                 descs,
             }));
         }
+        if self.os.media.v4l2_change.check_and_clear() {
+            let descs = self.os.media.v4l2().lock().unwrap().get_updated_descs();
+            self.call_event_handler(&Event::VideoInputs(VideoInputsEvent {
+                descs,
+            }));
+        }
+        self.handle_video_playback_signals();
     }
 }
 
@@ -45,6 +56,11 @@ pub struct CxLinuxMedia {
     pub (crate) audio_change: SignalToUI,
     pub (crate) alsa_midi: Option<Arc<Mutex<AlsaMidiAccess >> >,
     pub (crate) alsa_midi_change: SignalToUI,
+    pub (crate) v4l2: Option<Arc<Mutex<V4l2Access >> >,
+    pub (crate) v4l2_change: SignalToUI,
+    pub (crate) video_players: HashMap<LiveId, LinuxVideoPlayer>,
+    pub (crate) video_change: SignalToUI,
+    pub (crate) video_released: Vec<LiveId>,
 }
 
 impl CxLinuxMedia {
@@ -68,6 +84,13 @@ impl CxLinuxMedia {
         }
         self.alsa_midi.as_ref().unwrap().clone()
     }
+    
+    pub fn v4l2(&mut self) -> Arc<Mutex<V4l2Access >> {
+        if self.v4l2.is_none() {
+            self.v4l2 = Some(V4l2Access::new(self.v4l2_change.clone()));
+        }
+        self.v4l2.as_ref().unwrap().clone()
+    }
 
 
 }
@@ -111,10 +134,12 @@ impl CxMediaApi for Cx {
         *self.os.media.alsa_audio().lock().unwrap().audio_input_cb[index].lock().unwrap() = Some(f);
     }    
     
-    fn video_input_box(&mut self, _index: usize, _f: VideoInputFn){
+    fn video_input_box(&mut self, index: usize, f: VideoInputFn){
+        *self.os.media.v4l2().lock().unwrap().video_input_cb[index].lock().unwrap() = Some(f);
     }
     
-    fn use_video_input(&mut self, _inputs: &[(VideoInputId, VideoFormatId)]) {
+    fn use_video_input(&mut self, inputs: &[(VideoInputId, VideoFormatId)]) {
+        self.os.media.v4l2().lock().unwrap().use_video_input(inputs);
     }
 }
 
@@ -29,6 +29,18 @@ pub mod select_timer;
 pub mod pulse_audio; 
 #[cfg(not(target_os="android"))]
 pub mod pulse_sys;
+#[cfg(not(target_os="android"))]
+pub mod v4l2_sys;
+#[cfg(not(target_os="android"))]
+pub mod v4l2_video;
+#[cfg(not(target_os="android"))]
+pub mod avi_demux;
+#[cfg(not(target_os="android"))]
+pub mod video_playback;
+#[cfg(not(target_os="android"))]
+pub mod dbus;
+#[cfg(not(target_os="android"))]
+pub mod atspi;
 
 #[cfg(not(target_os="android"))]
 mod web_socket;
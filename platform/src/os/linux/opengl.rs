@@ -5,13 +5,14 @@ use {
         mem,
         ptr,
         ffi::{c_char, CStr},
+        sync::OnceLock,
     },
     self::super::gl_sys,
     crate::{
         makepad_live_id::*,
         makepad_shader_compiler::generate_glsl,
         cx::{Cx, OsType, OsType::Android},
-        texture::{Texture, TextureFormat, TexturePixel, CxTexture},
+        texture::{Texture, TextureFormat, TexturePixel, CxTexture, TextureSampler, TextureFilter, TextureWrap, TextureRect, TextureUpdated},
         makepad_math::{Mat4, DVec2, Vec4},
         pass::{PassClearColor, PassClearDepth, PassId},
         draw_list::DrawListId,
@@ -20,6 +21,12 @@ use {
     },
 };
 
+// On Android video frames come from a SurfaceTexture, elsewhere they are uploaded by the software video player
+#[cfg(target_os = "android")]
+const VIDEO_TEXTURE_TARGET: gl_sys::types::GLenum = gl_sys::TEXTURE_EXTERNAL_OES;
+#[cfg(not(target_os = "android"))]
+const VIDEO_TEXTURE_TARGET: gl_sys::types::GLenum = gl_sys::TEXTURE_2D;
+
 impl Cx {
     
     pub (crate) fn render_view(
@@ -217,15 +224,18 @@ impl Cx {
                         // get the loc
                         gl_sys::ActiveTexture(gl_sys::TEXTURE0 + i as u32);
                         if let Some(texture) = cxtexture.os.gl_texture {
-                            // Video playback with SurfaceTexture requires TEXTURE_EXTERNAL_OES, for any other format we assume regular 2D textures
+                            // Video playback with SurfaceTexture requires TEXTURE_EXTERNAL_OES on Android, for any other format we assume regular 2D textures
                             match cxtexture.format {
-                                TextureFormat::VideoRGB => gl_sys::BindTexture(gl_sys::TEXTURE_EXTERNAL_OES, texture),
-                                _ => gl_sys::BindTexture(gl_sys::TEXTURE_2D, texture)     
+                                TextureFormat::VideoRGB => gl_sys::BindTexture(VIDEO_TEXTURE_TARGET, texture),
+                                _ => {
+                                    gl_sys::BindTexture(gl_sys::TEXTURE_2D, texture);
+                                    cxtexture.update_gl_sampler();
+                                }
                             }
                         }
                         else {
                             match cxtexture.format {
-                                TextureFormat::VideoRGB => gl_sys::BindTexture(gl_sys::TEXTURE_EXTERNAL_OES, 0),
+                                TextureFormat::VideoRGB => gl_sys::BindTexture(VIDEO_TEXTURE_TARGET, 0),
                                 _ => gl_sys::BindTexture(gl_sys::TEXTURE_2D, 0)     
                             }
                         }
@@ -765,7 +775,12 @@ impl CxOsDrawShader {
         // This seems like a driver bug (no confirmation from Qualcomm yet).
         // Therefore we're disabling the external texture support for Adreno until this is fixed.
         let is_vendor_adreno = get_gl_string(gl_sys::RENDERER).contains("Adreno"); 
-        if is_external_texture_supported && !is_vendor_adreno && !is_emulator {
+        if !cfg!(target_os = "android") {
+            // Outside of Android video frames are uploaded into regular 2D textures
+            maybe_ext_tex_extension_import = "#define samplerExternalOES sampler2D\n".to_string();
+            maybe_ext_tex_extension_sampler = "vec4 sample2dOES(sampler2D sampler, vec2 pos){{ return texture2D(sampler, vec2(pos.x, pos.y));}}".to_string();
+        }
+        else if is_external_texture_supported && !is_vendor_adreno && !is_emulator {
             maybe_ext_tex_extension_import = "#extension GL_OES_EGL_image_external : require\n".to_string();
             maybe_ext_tex_extension_sampler = "vec4 sample2dOES(samplerExternalOES sampler, vec2 pos){{ return texture2D(sampler, vec2(pos.x, pos.y));}}".to_string();
         }
@@ -812,6 +827,27 @@ impl CxOsDrawShader {
 }
 
 
+/// Uploads a decoded BGRA video frame into the gl texture of a VideoRGB texture
+#[cfg(not(target_os = "android"))]
+pub fn upload_video_frame(gl_texture: u32, width: usize, height: usize, data: &[u32]) {
+    unsafe {
+        gl_sys::BindTexture(gl_sys::TEXTURE_2D, gl_texture);
+        gl_sys::PixelStorei(gl_sys::UNPACK_ALIGNMENT, 4);
+        gl_sys::TexImage2D(
+            gl_sys::TEXTURE_2D,
+            0,
+            gl_sys::BGRA as i32,
+            width as i32,
+            height as i32,
+            0,
+            gl_sys::BGRA,
+            gl_sys::UNSIGNED_BYTE,
+            data.as_ptr() as *const _
+        );
+        gl_sys::BindTexture(gl_sys::TEXTURE_2D, 0);
+    }
+}
+
 fn get_gl_string(key: gl_sys::types::GLenum) -> String {
     unsafe {
         let string_ptr = gl_sys::GetString(key) as *const c_char;
@@ -822,6 +858,47 @@ fn get_gl_string(key: gl_sys::types::GLenum) -> String {
     }
 }
 
+/// What texture uploads can use, GLES2 has neither unpack row lengths nor pixel buffers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct GlUploadCaps {
+    /// UNPACK_ROW_LENGTH and the UNPACK_SKIP_* parameters
+    unpack_subimage: bool,
+    /// PIXEL_UNPACK_BUFFER
+    pixel_buffers: bool,
+}
+
+impl GlUploadCaps {
+    // every context of a process is of the same kind, so this is only asked once
+    fn get() -> Self {
+        static CAPS: OnceLock<GlUploadCaps> = OnceLock::new();
+        *CAPS.get_or_init( || Self::from_strings(&get_gl_string(gl_sys::VERSION), &get_gl_string(gl_sys::EXTENSIONS)))
+    }
+    
+    // GL_VERSION reads "OpenGL ES 3.2 ..." on GLES and "4.6 ..." on desktop GL
+    fn from_strings(version: &str, extensions: &str) -> Self {
+        let (is_gles, number) = match version.strip_prefix("OpenGL ES") {
+            Some(rest) => (true, rest.trim_start_matches(|c: char| !c.is_ascii_digit())),
+            None => (false, version)
+        };
+        let major: u32 = number.split(|c: char| !c.is_ascii_digit()).next().and_then( | major | major.parse().ok()).unwrap_or(0);
+        let is_gles2 = is_gles && major < 3;
+        Self {
+            unpack_subimage: !is_gles2 || extensions.split_whitespace().any( | ext | ext == "GL_EXT_unpack_subimage"),
+            pixel_buffers: !is_gles2,
+        }
+    }
+}
+
+// the rows of `rect` tightly packed, for when the GL can't skip into the texture data by itself
+fn pack_rows(data: &[u8], row_length: usize, texel_size: usize, rect: TextureRect) -> Vec<u8> {
+    let mut packed = Vec::with_capacity(rect.width * rect.height * texel_size);
+    for row in rect.y..rect.y + rect.height {
+        let start = (row * row_length + rect.x) * texel_size;
+        packed.extend_from_slice(&data[start..start + rect.width * texel_size]);
+    }
+    packed
+}
+
 #[derive(Default, Clone)]
 pub struct OpenglAttribute {
     pub loc: u32,
@@ -898,11 +975,60 @@ impl CxOsDrawCall {
 pub struct CxOsTexture {
     pub gl_texture: Option<u32>,
     pub gl_renderbuffer: Option<u32>,
+    pub gl_sampler: Option<TextureSampler>,
+    pub gl_upload_buffers: [Option<u32>; 2],
+    pub gl_upload_index: usize,
 }
 
 impl CxTexture {
     
+    // sets the sampler as texture parameters when it changed, the texture has to be bound to TEXTURE_2D
+    fn update_gl_sampler(&mut self) {
+        let sampler = self.sampler_or_default();
+        if self.os.gl_sampler == Some(sampler) {
+            return
+        }
+        let gl_filter = | filter | match filter {
+            TextureFilter::Nearest => gl_sys::NEAREST,
+            TextureFilter::Linear => gl_sys::LINEAR,
+        };
+        let gl_wrap = | wrap | match wrap {
+            TextureWrap::ClampToEdge => gl_sys::CLAMP_TO_EDGE,
+            TextureWrap::Repeat => gl_sys::REPEAT,
+            TextureWrap::MirroredRepeat => gl_sys::MIRRORED_REPEAT,
+        };
+        // only the mip format has levels to select from, a mipmap filter on anything else leaves the texture incomplete
+        let mipmap_filter = if let TextureFormat::VecMipBGRAu8_32{..} = self.format {sampler.mipmap_filter} else {None};
+        let min_filter = match (sampler.min_filter, mipmap_filter) {
+            (filter, None) => gl_filter(filter),
+            (TextureFilter::Nearest, Some(TextureFilter::Nearest)) => gl_sys::NEAREST_MIPMAP_NEAREST,
+            (TextureFilter::Linear, Some(TextureFilter::Nearest)) => gl_sys::LINEAR_MIPMAP_NEAREST,
+            (TextureFilter::Nearest, Some(TextureFilter::Linear)) => gl_sys::NEAREST_MIPMAP_LINEAR,
+            (TextureFilter::Linear, Some(TextureFilter::Linear)) => gl_sys::LINEAR_MIPMAP_LINEAR,
+        };
+        unsafe {
+            gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, min_filter as i32);
+            gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_filter(sampler.mag_filter) as i32);
+            gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_WRAP_S, gl_wrap(sampler.wrap_s) as i32);
+            gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_WRAP_T, gl_wrap(sampler.wrap_t) as i32);
+        }
+        let had_anisotropy = self.os.gl_sampler.is_some_and(|s| s.max_anisotropy > 1);
+        if sampler.max_anisotropy > 1 || had_anisotropy {
+            // an extension on GLES and most desktop drivers, Mesa's llvmpipe included
+            let available_extensions = get_gl_string(gl_sys::EXTENSIONS);
+            if available_extensions.split_whitespace().any(|ext| ext == "GL_EXT_texture_filter_anisotropic" || ext == "GL_ARB_texture_filter_anisotropic") {
+                let mut max_anisotropy = 1.0f32;
+                unsafe {
+                    gl_sys::GetFloatv(gl_sys::MAX_TEXTURE_MAX_ANISOTROPY_EXT, &mut max_anisotropy);
+                    gl_sys::TexParameterf(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAX_ANISOTROPY_EXT, (sampler.max_anisotropy as f32).min(max_anisotropy));
+                }
+            }
+        }
+        self.os.gl_sampler = Some(sampler);
+    }
+    
     pub fn update_vec_texture(&mut self) {
+        let mut reallocated = false;
         if self.alloc_vec() {
             self.free_resources();
             if self.os.gl_texture.is_none() { 
@@ -912,120 +1038,142 @@ impl CxTexture {
                     self.os.gl_texture = Some(gl_texture.assume_init());
                 }
             }
+            reallocated = true;
         }
-        if self.check_updated(){
-            unsafe{
-                gl_sys::BindTexture(gl_sys::TEXTURE_2D, self.os.gl_texture.unwrap());
-                gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_WRAP_S, gl_sys::CLAMP_TO_EDGE as i32);
-                gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_WRAP_T, gl_sys::CLAMP_TO_EDGE as i32);
-            }                       
-            match &self.format{
-                TextureFormat::VecBGRAu8_32{width, height, data}=>unsafe{
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::TexImage2D(
+        let updated = self.take_updated();
+        if updated == TextureUpdated::Empty{
+            return
+        }
+        let (internal_format, format, data_type) = match &self.format{
+            TextureFormat::VecBGRAu8_32{..} |
+            TextureFormat::VecMipBGRAu8_32{..}=>(gl_sys::BGRA, gl_sys::BGRA, gl_sys::UNSIGNED_BYTE),
+            TextureFormat::VecRGBAf32{..}=>(gl_sys::RGBA, gl_sys::RGBA, gl_sys::FLOAT),
+            TextureFormat::VecRu8{..}=>(gl_sys::R8, gl_sys::RED, gl_sys::UNSIGNED_BYTE),
+            TextureFormat::VecRGu8{..}=>(gl_sys::RG, gl_sys::RG, gl_sys::UNSIGNED_BYTE),
+            TextureFormat::VecRf32{..}=>(gl_sys::RED, gl_sys::RED, gl_sys::FLOAT),
+            _=>{panic!()}
+        };
+        let (width, height) = self.format.vec_width_height().unwrap();
+        let (data, row_length, texel_size) = self.format.vec_texel_data().unwrap();
+        // a freshly allocated texture needs all of its storage specified, otherwise we only send what changed
+        let rect = match updated{
+            _ if reallocated => None,
+            TextureUpdated::Partial(rect) => Some(rect),
+            _ => Some(TextureRect::new(0, 0, width, height)),
+        };
+        let caps = GlUploadCaps::get();
+        unsafe{
+            gl_sys::BindTexture(gl_sys::TEXTURE_2D, self.os.gl_texture.unwrap());
+            gl_sys::PixelStorei(gl_sys::UNPACK_ALIGNMENT, 1);
+            if caps.unpack_subimage{
+                gl_sys::PixelStorei(gl_sys::UNPACK_ROW_LENGTH, row_length as i32);
+            }
+            match rect{
+                Some(rect) if !caps.unpack_subimage => {
+                    // without row lengths or skips we send whole rows, repacked when the data has a wider stride
+                    let rows = TextureRect::new(0, rect.y, width, rect.height);
+                    let packed;
+                    let pixels = if row_length == width{
+                        &data[rect.y * width * texel_size..(rect.y + rect.height) * width * texel_size]
+                    }
+                    else{
+                        packed = pack_rows(data, row_length, texel_size, rows);
+                        &packed
+                    };
+                    gl_sys::TexSubImage2D(
                         gl_sys::TEXTURE_2D,
                         0,
-                        gl_sys::BGRA as i32,
-                        *width as i32,
-                        *height as i32,
                         0,
-                        gl_sys::BGRA,
-                        gl_sys::UNSIGNED_BYTE,
-                        data.as_ptr() as *const _
+                        rows.y as i32,
+                        rows.width as i32,
+                        rows.height as i32,
+                        format,
+                        data_type,
+                        pixels.as_ptr() as *const _
                     );
                 }
-                TextureFormat::VecMipBGRAu8_32{width, height, data, max_level}=>unsafe{
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR_MIPMAP_LINEAR as i32);
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::TexImage2D(
-                        gl_sys::TEXTURE_2D,
-                        0,
-                        gl_sys::BGRA as i32,
-                        *width as i32,
-                        *height as i32,
-                        0,
-                        gl_sys::BGRA,
-                        gl_sys::UNSIGNED_BYTE,
-                        data.as_ptr() as *const _
+                Some(rect) if self.streaming && caps.pixel_buffers => {
+                    // alternate between two pixel buffers so the driver can still be copying
+                    // the previous frame out of one while we fill the other
+                    let index = self.os.gl_upload_index;
+                    self.os.gl_upload_index = 1 - index;
+                    let gl_buffer = *self.os.gl_upload_buffers[index].get_or_insert_with(||{
+                        let mut gl_buffer = std::mem::MaybeUninit::uninit();
+                        gl_sys::GenBuffers(1, gl_buffer.as_mut_ptr());
+                        gl_buffer.assume_init()
+                    });
+                    let start = rect.y * row_length * texel_size;
+                    let end = (start + rect.height * row_length * texel_size).min(data.len());
+                    gl_sys::BindBuffer(gl_sys::PIXEL_UNPACK_BUFFER, gl_buffer);
+                    gl_sys::BufferData(
+                        gl_sys::PIXEL_UNPACK_BUFFER,
+                        (end - start) as gl_sys::types::GLsizeiptr,
+                        data[start..end].as_ptr() as *const _,
+                        gl_sys::STREAM_DRAW
                     );
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_BASE_LEVEL, 0);
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAX_LEVEL, max_level.unwrap_or(1000) as i32);
-                    gl_sys::GenerateMipmap(gl_sys::TEXTURE_2D);  
-                },
-                TextureFormat::VecRGBAf32{width, height, data}=>unsafe{
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::TexImage2D(
+                    gl_sys::PixelStorei(gl_sys::UNPACK_SKIP_PIXELS, rect.x as i32);
+                    gl_sys::TexSubImage2D(
                         gl_sys::TEXTURE_2D,
                         0,
-                        gl_sys::RGBA as i32,
-                        *width as i32,
-                        *height as i32,
-                        0,
-                        gl_sys::RGBA,
-                        gl_sys::FLOAT,
-                        data.as_ptr() as *const _
+                        rect.x as i32,
+                        rect.y as i32,
+                        rect.width as i32,
+                        rect.height as i32,
+                        format,
+                        data_type,
+                        ptr::null()
                     );
-                },
-                TextureFormat::VecRu8{width, height, data, unpack_row_length}=>unsafe{
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::PixelStorei(gl_sys::UNPACK_ALIGNMENT, 1);
-                    if let Some(row_length) = unpack_row_length {
-                        gl_sys::PixelStorei(gl_sys::UNPACK_ROW_LENGTH, *row_length as i32);
-                    }
-                    gl_sys::TexImage2D(
+                    gl_sys::BindBuffer(gl_sys::PIXEL_UNPACK_BUFFER, 0);
+                    gl_sys::PixelStorei(gl_sys::UNPACK_SKIP_PIXELS, 0);
+                }
+                Some(rect) => {
+                    gl_sys::PixelStorei(gl_sys::UNPACK_SKIP_PIXELS, rect.x as i32);
+                    gl_sys::PixelStorei(gl_sys::UNPACK_SKIP_ROWS, rect.y as i32);
+                    gl_sys::TexSubImage2D(
                         gl_sys::TEXTURE_2D,
                         0,
-                        gl_sys::R8 as i32,
-                        *width as i32,
-                        *height as i32,
-                        0,
-                        gl_sys::RED,
-                        gl_sys::UNSIGNED_BYTE,
+                        rect.x as i32,
+                        rect.y as i32,
+                        rect.width as i32,
+                        rect.height as i32,
+                        format,
+                        data_type,
                         data.as_ptr() as *const _
                     );
-                },
-                TextureFormat::VecRGu8{width, height, data, unpack_row_length}=>unsafe{
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::PixelStorei(gl_sys::UNPACK_ALIGNMENT, 1);
-                    if let Some(row_length) = unpack_row_length {
-                        gl_sys::PixelStorei(gl_sys::UNPACK_ROW_LENGTH, *row_length as i32);
+                    gl_sys::PixelStorei(gl_sys::UNPACK_SKIP_PIXELS, 0);
+                    gl_sys::PixelStorei(gl_sys::UNPACK_SKIP_ROWS, 0);
+                }
+                None => {
+                    let packed;
+                    let pixels = if row_length == width || caps.unpack_subimage{
+                        data
                     }
+                    else{
+                        packed = pack_rows(data, row_length, texel_size, TextureRect::new(0, 0, width, height));
+                        &packed
+                    };
                     gl_sys::TexImage2D(
                         gl_sys::TEXTURE_2D,
                         0,
-                        gl_sys::RG as i32,
-                        *width as i32,
-                        *height as i32,
-                        0,
-                        gl_sys::RG,
-                        gl_sys::UNSIGNED_BYTE,
-                        data.as_ptr() as *const _
-                    );
-                },
-                TextureFormat::VecRf32{width, height, data}=>unsafe{
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
-                    gl_sys::TexImage2D(
-                        gl_sys::TEXTURE_2D,
-                        0,
-                        gl_sys::RED as i32,
-                        *width as i32,
-                        *height as i32,
+                        internal_format as i32,
+                        width as i32,
+                        height as i32,
                         0,
-                        gl_sys::RED,
-                        gl_sys::FLOAT,
-                        data.as_ptr() as *const _
+                        format,
+                        data_type,
+                        pixels.as_ptr() as *const _
                     );
-                },
-                _=>{panic!()}
+                }
             }
-            unsafe{
-                gl_sys::BindTexture(gl_sys::TEXTURE_2D, 0);
+            if caps.unpack_subimage{
+                gl_sys::PixelStorei(gl_sys::UNPACK_ROW_LENGTH, 0);
+            }
+            if let TextureFormat::VecMipBGRAu8_32{max_level, ..} = &self.format{
+                gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_BASE_LEVEL, 0);
+                gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAX_LEVEL, max_level.unwrap_or(1000) as i32);
+                gl_sys::GenerateMipmap(gl_sys::TEXTURE_2D);  
             }
+            gl_sys::BindTexture(gl_sys::TEXTURE_2D, 0);
         }
     }
 
@@ -1044,15 +1192,15 @@ impl CxTexture {
         }
         if self.check_initial() {
             unsafe{
-                gl_sys::BindTexture(gl_sys::TEXTURE_EXTERNAL_OES, self.os.gl_texture.unwrap());
+                gl_sys::BindTexture(VIDEO_TEXTURE_TARGET, self.os.gl_texture.unwrap());
         
-                gl_sys::TexParameteri(gl_sys::TEXTURE_EXTERNAL_OES, gl_sys::TEXTURE_WRAP_S, gl_sys::CLAMP_TO_EDGE as i32);
-                gl_sys::TexParameteri(gl_sys::TEXTURE_EXTERNAL_OES, gl_sys::TEXTURE_WRAP_T, gl_sys::CLAMP_TO_EDGE as i32);
+                gl_sys::TexParameteri(VIDEO_TEXTURE_TARGET, gl_sys::TEXTURE_WRAP_S, gl_sys::CLAMP_TO_EDGE as i32);
+                gl_sys::TexParameteri(VIDEO_TEXTURE_TARGET, gl_sys::TEXTURE_WRAP_T, gl_sys::CLAMP_TO_EDGE as i32);
 
-                gl_sys::TexParameteri(gl_sys::TEXTURE_EXTERNAL_OES, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR as i32);
-                gl_sys::TexParameteri(gl_sys::TEXTURE_EXTERNAL_OES, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
+                gl_sys::TexParameteri(VIDEO_TEXTURE_TARGET, gl_sys::TEXTURE_MIN_FILTER, gl_sys::LINEAR as i32);
+                gl_sys::TexParameteri(VIDEO_TEXTURE_TARGET, gl_sys::TEXTURE_MAG_FILTER, gl_sys::LINEAR as i32);
         
-                gl_sys::BindTexture(gl_sys::TEXTURE_EXTERNAL_OES, 0);
+                gl_sys::BindTexture(VIDEO_TEXTURE_TARGET, 0);
 
                 assert_eq!(gl_sys::GetError(), 0, "UPDATE VIDEO TEXTURE ERROR {}", self.os.gl_texture.unwrap());
             }
@@ -1074,8 +1222,6 @@ impl CxTexture {
             unsafe{gl_sys::BindTexture(gl_sys::TEXTURE_2D, self.os.gl_texture.unwrap())};
             match &alloc.pixel {
                 TexturePixel::BGRAu8 => unsafe{
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::NEAREST as i32);
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::NEAREST as i32);
                     gl_sys::TexImage2D(
                         gl_sys::TEXTURE_2D,
                         0,
@@ -1089,8 +1235,6 @@ impl CxTexture {
                     );
                 },
                 TexturePixel::RGBAf16 => unsafe{
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::NEAREST as i32);
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::NEAREST as i32);
                     gl_sys::TexImage2D(
                         gl_sys::TEXTURE_2D,
                         0,
@@ -1104,8 +1248,6 @@ impl CxTexture {
                     );
                 }
                 TexturePixel::RGBAf32 => unsafe{
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MIN_FILTER, gl_sys::NEAREST as i32);
-                    gl_sys::TexParameteri(gl_sys::TEXTURE_2D, gl_sys::TEXTURE_MAG_FILTER, gl_sys::NEAREST as i32);
                     gl_sys::TexImage2D(
                         gl_sys::TEXTURE_2D,
                         0,
@@ -1163,6 +1305,12 @@ impl CxTexture {
         if let Some(gl_texture) = self.os.gl_texture.take(){
             unsafe{gl_sys::DeleteTextures(1, &gl_texture)};
         }
+        self.os.gl_sampler = None;
+        for gl_buffer in &mut self.os.gl_upload_buffers{
+            if let Some(gl_buffer) = gl_buffer.take(){
+                unsafe{gl_sys::DeleteBuffers(1, &gl_buffer)};
+            }
+        }
         if let Some(gl_renderbuffer) = self.os.gl_renderbuffer.take(){
             unsafe{gl_sys::DeleteRenderbuffers(1, &gl_renderbuffer)};
         }
@@ -1236,3 +1384,28 @@ impl OpenglBuffer {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upload_caps_from_version() {
+        let desktop = GlUploadCaps::from_strings("4.6 (Core Profile) Mesa 24.0.5", "");
+        assert_eq!(desktop, GlUploadCaps {unpack_subimage: true, pixel_buffers: true});
+        let gles3 = GlUploadCaps::from_strings("OpenGL ES 3.2 v1.r32p1", "");
+        assert_eq!(gles3, GlUploadCaps {unpack_subimage: true, pixel_buffers: true});
+        let gles2 = GlUploadCaps::from_strings("OpenGL ES 2.0 build 1.13", "GL_OES_texture_npot");
+        assert_eq!(gles2, GlUploadCaps {unpack_subimage: false, pixel_buffers: false});
+        let gles2_ext = GlUploadCaps::from_strings("OpenGL ES 2.0", "GL_OES_texture_npot GL_EXT_unpack_subimage");
+        assert_eq!(gles2_ext, GlUploadCaps {unpack_subimage: true, pixel_buffers: false});
+    }
+
+    #[test]
+    fn pack_rows_drops_the_stride() {
+        // 3 texels of 2 bytes per row, of which the texture uses 2
+        let data = [1, 1, 2, 2, 9, 9, 3, 3, 4, 4, 9, 9, 5, 5, 6, 6, 9, 9];
+        assert_eq!(pack_rows(&data, 3, 2, TextureRect::new(0, 1, 2, 2)), [3, 3, 4, 4, 5, 5, 6, 6]);
+        assert_eq!(pack_rows(&data, 3, 2, TextureRect::new(1, 0, 1, 1)), [2, 2]);
+    }
+}
@@ -0,0 +1,250 @@
+#![allow(non_camel_case_types)]
+
+use {
+    std::os::raw::{
+        c_ulong,
+        c_void,
+    },
+    super::libc_sys::timeval,
+};
+
+pub const V4L2_CAP_VIDEO_CAPTURE: u32 = 0x00000001;
+pub const V4L2_CAP_STREAMING: u32 = 0x04000000;
+pub const V4L2_CAP_DEVICE_CAPS: u32 = 0x80000000;
+
+pub const V4L2_BUF_TYPE_VIDEO_CAPTURE: u32 = 1;
+pub const V4L2_MEMORY_MMAP: u32 = 1;
+pub const V4L2_FIELD_ANY: u32 = 0;
+
+pub const V4L2_FRMSIZE_TYPE_DISCRETE: u32 = 1;
+pub const V4L2_FRMIVAL_TYPE_DISCRETE: u32 = 1;
+
+pub const V4L2_CAP_TIMEPERFRAME: u32 = 0x1000;
+
+pub const fn v4l2_fourcc(a: u8, b: u8, c: u8, d: u8) -> u32 {
+    (a as u32) | ((b as u32) << 8) | ((c as u32) << 16) | ((d as u32) << 24)
+}
+
+pub const V4L2_PIX_FMT_YUYV: u32 = v4l2_fourcc(b'Y', b'U', b'Y', b'V');
+pub const V4L2_PIX_FMT_MJPEG: u32 = v4l2_fourcc(b'M', b'J', b'P', b'G');
+pub const V4L2_PIX_FMT_JPEG: u32 = v4l2_fourcc(b'J', b'P', b'E', b'G');
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_capability {
+    pub driver: [u8; 16],
+    pub card: [u8; 32],
+    pub bus_info: [u8; 32],
+    pub version: u32,
+    pub capabilities: u32,
+    pub device_caps: u32,
+    pub reserved: [u32; 3],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_fmtdesc {
+    pub index: u32,
+    pub type_: u32,
+    pub flags: u32,
+    pub description: [u8; 32],
+    pub pixelformat: u32,
+    pub mbus_code: u32,
+    pub reserved: [u32; 3],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_pix_format {
+    pub width: u32,
+    pub height: u32,
+    pub pixelformat: u32,
+    pub field: u32,
+    pub bytesperline: u32,
+    pub sizeimage: u32,
+    pub colorspace: u32,
+    pub priv_: u32,
+    pub flags: u32,
+    pub ycbcr_enc: u32,
+    pub quantization: u32,
+    pub xfer_func: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union v4l2_format__bindgen_ty_1 {
+    pub pix: v4l2_pix_format,
+    // the other members hold pointers, so the union is pointer aligned
+    pub raw_data: [c_ulong; 200 / std::mem::size_of::<c_ulong>()],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_format {
+    pub type_: u32,
+    pub fmt: v4l2_format__bindgen_ty_1,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_requestbuffers {
+    pub count: u32,
+    pub type_: u32,
+    pub memory: u32,
+    pub capabilities: u32,
+    pub flags: u8,
+    pub reserved: [u8; 3],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_timecode {
+    pub type_: u32,
+    pub flags: u32,
+    pub frames: u8,
+    pub seconds: u8,
+    pub minutes: u8,
+    pub hours: u8,
+    pub userbits: [u8; 4],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union v4l2_buffer__bindgen_ty_1 {
+    pub offset: u32,
+    pub userptr: c_ulong,
+    pub planes: *mut c_void,
+    pub fd: i32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_buffer {
+    pub index: u32,
+    pub type_: u32,
+    pub bytesused: u32,
+    pub flags: u32,
+    pub field: u32,
+    pub timestamp: timeval,
+    pub timecode: v4l2_timecode,
+    pub sequence: u32,
+    pub memory: u32,
+    pub m: v4l2_buffer__bindgen_ty_1,
+    pub length: u32,
+    pub reserved2: u32,
+    pub request_fd: i32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_fract {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_captureparm {
+    pub capability: u32,
+    pub capturemode: u32,
+    pub timeperframe: v4l2_fract,
+    pub extendedmode: u32,
+    pub readbuffers: u32,
+    pub reserved: [u32; 4],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union v4l2_streamparm__bindgen_ty_1 {
+    pub capture: v4l2_captureparm,
+    pub raw_data: [u8; 200],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_streamparm {
+    pub type_: u32,
+    pub parm: v4l2_streamparm__bindgen_ty_1,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_frmsize_discrete {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_frmsize_stepwise {
+    pub min_width: u32,
+    pub max_width: u32,
+    pub step_width: u32,
+    pub min_height: u32,
+    pub max_height: u32,
+    pub step_height: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union v4l2_frmsizeenum__bindgen_ty_1 {
+    pub discrete: v4l2_frmsize_discrete,
+    pub stepwise: v4l2_frmsize_stepwise,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_frmsizeenum {
+    pub index: u32,
+    pub pixel_format: u32,
+    pub type_: u32,
+    pub size: v4l2_frmsizeenum__bindgen_ty_1,
+    pub reserved: [u32; 2],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_frmival_stepwise {
+    pub min: v4l2_fract,
+    pub max: v4l2_fract,
+    pub step: v4l2_fract,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union v4l2_frmivalenum__bindgen_ty_1 {
+    pub discrete: v4l2_fract,
+    pub stepwise: v4l2_frmival_stepwise,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct v4l2_frmivalenum {
+    pub index: u32,
+    pub pixel_format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub type_: u32,
+    pub interval: v4l2_frmivalenum__bindgen_ty_1,
+    pub reserved: [u32; 2],
+}
+
+const IOC_WRITE: c_ulong = 1;
+const IOC_READ: c_ulong = 2;
+
+const fn ioc<T>(dir: c_ulong, nr: c_ulong) -> c_ulong {
+    (dir << 30) | ((std::mem::size_of::<T>() as c_ulong) << 16) | ((b'V' as c_ulong) << 8) | nr
+}
+
+pub const VIDIOC_QUERYCAP: c_ulong = ioc::<v4l2_capability>(IOC_READ, 0);
+pub const VIDIOC_ENUM_FMT: c_ulong = ioc::<v4l2_fmtdesc>(IOC_READ | IOC_WRITE, 2);
+pub const VIDIOC_S_FMT: c_ulong = ioc::<v4l2_format>(IOC_READ | IOC_WRITE, 5);
+pub const VIDIOC_REQBUFS: c_ulong = ioc::<v4l2_requestbuffers>(IOC_READ | IOC_WRITE, 8);
+pub const VIDIOC_QUERYBUF: c_ulong = ioc::<v4l2_buffer>(IOC_READ | IOC_WRITE, 9);
+pub const VIDIOC_QBUF: c_ulong = ioc::<v4l2_buffer>(IOC_READ | IOC_WRITE, 15);
+pub const VIDIOC_DQBUF: c_ulong = ioc::<v4l2_buffer>(IOC_READ | IOC_WRITE, 17);
+pub const VIDIOC_STREAMON: c_ulong = ioc::<i32>(IOC_WRITE, 18);
+pub const VIDIOC_STREAMOFF: c_ulong = ioc::<i32>(IOC_WRITE, 19);
+pub const VIDIOC_S_PARM: c_ulong = ioc::<v4l2_streamparm>(IOC_READ | IOC_WRITE, 22);
+pub const VIDIOC_ENUM_FRAMESIZES: c_ulong = ioc::<v4l2_frmsizeenum>(IOC_READ | IOC_WRITE, 74);
+pub const VIDIOC_ENUM_FRAMEINTERVALS: c_ulong = ioc::<v4l2_frmivalenum>(IOC_READ | IOC_WRITE, 75);
@@ -0,0 +1,753 @@
+use {
+    std::sync::{Arc, Mutex},
+    std::sync::atomic::{AtomicBool, Ordering},
+    std::thread::JoinHandle,
+    std::time::{Duration, Instant},
+    std::ffi::CString,
+    std::os::raw::{c_int, c_ulong, c_void},
+    self::super::{
+        libc_sys,
+        v4l2_sys::*,
+        avi_demux::mjpeg_with_avi1_marker,
+    },
+    crate::{
+        makepad_live_id::*,
+        thread::SignalToUI,
+        video::*,
+    },
+    makepad_zune_jpeg::{
+        JpegDecoder,
+        makepad_zune_core::{
+            colorspace::ColorSpace,
+            options::DecoderOptions,
+        },
+    },
+};
+
+/// Points to a file of concatenated jpeg frames, which then shows up as an extra video input
+/// playing at 30 fps. Useful to test video input without a camera or the `vivid` driver.
+pub const VIDEO_INPUT_FILE_ENV: &str = "MAKEPAD_VIDEO_INPUT_FILE";
+
+const NUM_BUFFERS: u32 = 4;
+
+#[derive(Clone)]
+enum V4l2Source {
+    Device(String),
+    File(String),
+}
+
+#[derive(Clone, Copy)]
+struct V4l2Format {
+    format_id: VideoFormatId,
+    pixelformat: u32,
+    interval: Option<(u32, u32)>,
+}
+
+struct V4l2Input {
+    source: V4l2Source,
+    desc: VideoInputDesc,
+    v4l2_formats: Vec<V4l2Format>,
+}
+
+struct V4l2Capture {
+    input_id: VideoInputId,
+    format_id: VideoFormatId,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl V4l2Capture {
+    fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Polls the device nodes for hotplugs until it is stopped
+struct V4l2Observer {
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl V4l2Observer {
+    fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            // wakes it from its poll interval so the join doesn't wait for it
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+pub struct V4l2Access {
+    pub video_input_cb: [Arc<Mutex<Option<VideoInputFn> > >; MAX_VIDEO_DEVICE_INDEX],
+    inputs: Vec<V4l2Input>,
+    captures: Vec<V4l2Capture>,
+    observer: V4l2Observer,
+}
+
+impl V4l2Access {
+    pub fn new(change_signal: SignalToUI) -> Arc<Mutex<Self >> {
+        change_signal.set();
+        Arc::new(Mutex::new(Self {
+            video_input_cb: Default::default(),
+            inputs: Default::default(),
+            captures: Default::default(),
+            observer: Self::observe_device_changes(change_signal),
+        }))
+    }
+
+    pub fn use_video_input(&mut self, inputs: &[(VideoInputId, VideoFormatId)]) {
+        self.captures.retain_mut( | d | {
+            if inputs.contains(&(d.input_id, d.format_id)) {
+                true
+            }
+            else {
+                d.stop();
+                false
+            }
+        });
+        for (index, d) in inputs.iter().enumerate() {
+            if self.captures.iter().any( | v | v.input_id == d.0 && v.format_id == d.1) {
+                continue
+            }
+            let input = if let Some(input) = self.inputs.iter().find( | v | v.desc.input_id == d.0) {input} else {
+                crate::error!("Video input {:?} not found", d.0);
+                continue
+            };
+            let v4l2_format = input.v4l2_formats.iter().find( | v | v.format_id == d.1);
+            let format = input.desc.formats.iter().find( | v | v.format_id == d.1);
+            let (v4l2_format, format) = if let (Some(a), Some(b)) = (v4l2_format, format) {(*a, *b)} else {
+                crate::error!("Video format {:?} not found on {}", d.1, input.desc.name);
+                continue
+            };
+            let capture_cb = self.video_input_cb[index].clone();
+            let running = Arc::new(AtomicBool::new(true));
+            let running_clone = running.clone();
+            let source = input.source.clone();
+            let thread = std::thread::spawn(move || {
+                let result = match &source {
+                    V4l2Source::Device(path) => capture_device(path, v4l2_format, format, &capture_cb, &running_clone),
+                    V4l2Source::File(path) => capture_file(path, format, &capture_cb, &running_clone),
+                };
+                if let Err(err) = result {
+                    crate::error!("Video capture failed: {}", err);
+                }
+            });
+            self.captures.push(V4l2Capture {
+                input_id: d.0,
+                format_id: d.1,
+                running,
+                thread: Some(thread)
+            });
+        }
+    }
+
+    pub fn get_updated_descs(&mut self) -> Vec<VideoInputDesc> {
+        let mut inputs = Vec::new();
+        for path in video_device_paths() {
+            if let Some(input) = query_device(&path) {
+                // devices with several nodes show up once per node, keep the first
+                if !inputs.iter().any( | v: &V4l2Input | v.desc.input_id == input.desc.input_id) {
+                    inputs.push(input);
+                }
+            }
+        }
+        if let Ok(path) = std::env::var(VIDEO_INPUT_FILE_ENV) {
+            if let Some(input) = query_file(&path) {
+                inputs.push(input);
+            }
+        }
+        self.inputs = inputs;
+        self.inputs.iter().map( | v | v.desc.clone()).collect()
+    }
+
+    fn observe_device_changes(change_signal: SignalToUI) -> V4l2Observer {
+        let running = Arc::new(AtomicBool::new(true));
+        let running_clone = running.clone();
+        // there is no udev here, so poll the device nodes for hotplugs
+        let thread = std::thread::spawn(move || {
+            let mut last = video_device_paths();
+            loop {
+                std::thread::park_timeout(Duration::from_secs(1));
+                if !running_clone.load(Ordering::SeqCst) {
+                    break
+                }
+                let paths = video_device_paths();
+                if paths != last {
+                    last = paths;
+                    change_signal.set();
+                }
+            }
+        });
+        V4l2Observer {
+            running,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for V4l2Access {
+    fn drop(&mut self) {
+        self.observer.stop();
+        for capture in &mut self.captures {
+            capture.stop();
+        }
+    }
+}
+
+fn video_device_paths() -> Vec<String> {
+    let mut paths = Vec::new();
+    if let Ok(dir) = std::fs::read_dir("/dev") {
+        for entry in dir.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(num) = name.strip_prefix("video") {
+                if num.parse::<u32>().is_ok() {
+                    paths.push(format!("/dev/{}", name));
+                }
+            }
+        }
+    }
+    paths.sort_by_key( | v | v[10..].parse::<u32>().unwrap_or(0));
+    paths
+}
+
+fn cstr_to_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position( | v | *v == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[0..end]).to_string()
+}
+
+fn format_id_for(width: usize, height: usize, pixel_format: VideoPixelFormat, frame_rate: Option<f64>) -> VideoFormatId {
+    LiveId::from_str(&format!("{} {} {:?} {}", width, height, pixel_format, frame_rate.unwrap_or(0.0))).into()
+}
+
+unsafe fn xioctl<T>(fd: c_int, request: c_ulong, arg: &mut T) -> c_int {
+    loop {
+        let ret = libc_sys::ioctl(fd, request, arg as *mut T as *mut c_void);
+        if ret != -1 || std::io::Error::last_os_error().raw_os_error() != Some(libc_sys::EINTR) {
+            return ret
+        }
+    }
+}
+
+fn open_device(path: &str) -> Option<c_int> {
+    let path = CString::new(path).ok() ?;
+    let fd = unsafe {libc_sys::open(path.as_ptr(), libc_sys::O_RDWR | libc_sys::O_NONBLOCK)};
+    if fd < 0 {None} else {Some(fd)}
+}
+
+fn query_device(path: &str) -> Option<V4l2Input> {
+    let fd = open_device(path) ?;
+    let input = unsafe {query_device_fd(path, fd)};
+    unsafe {libc_sys::close(fd);}
+    input
+}
+
+unsafe fn query_device_fd(path: &str, fd: c_int) -> Option<V4l2Input> {
+    let mut cap: v4l2_capability = std::mem::zeroed();
+    if xioctl(fd, VIDIOC_QUERYCAP, &mut cap) < 0 {
+        return None
+    }
+    let caps = if cap.capabilities & V4L2_CAP_DEVICE_CAPS != 0 {cap.device_caps} else {cap.capabilities};
+    if caps & V4L2_CAP_VIDEO_CAPTURE == 0 || caps & V4L2_CAP_STREAMING == 0 {
+        return None
+    }
+    let name = cstr_to_string(&cap.card);
+    let bus_info = cstr_to_string(&cap.bus_info);
+
+    let mut formats = Vec::new();
+    let mut v4l2_formats = Vec::new();
+    let mut fmt_index = 0;
+    loop {
+        let mut fmtdesc: v4l2_fmtdesc = std::mem::zeroed();
+        fmtdesc.index = fmt_index;
+        fmtdesc.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        if xioctl(fd, VIDIOC_ENUM_FMT, &mut fmtdesc) < 0 {
+            break;
+        }
+        fmt_index += 1;
+        let pixel_format = match fmtdesc.pixelformat {
+            V4L2_PIX_FMT_YUYV => VideoPixelFormat::YUY2,
+            V4L2_PIX_FMT_MJPEG | V4L2_PIX_FMT_JPEG => VideoPixelFormat::MJPEG,
+            _ => continue
+        };
+        for (width, height) in query_frame_sizes(fd, fmtdesc.pixelformat) {
+            let intervals = query_frame_intervals(fd, fmtdesc.pixelformat, width, height);
+            let intervals = if intervals.is_empty() {vec![None]} else {intervals.into_iter().map(Some).collect()};
+            for interval in intervals {
+                let frame_rate = interval.map( | (num, den): (u32, u32) | den as f64 / num as f64);
+                let format_id = format_id_for(width as usize, height as usize, pixel_format, frame_rate);
+                if formats.iter().any( | v: &VideoFormat | v.format_id == format_id) {
+                    continue
+                }
+                v4l2_formats.push(V4l2Format {
+                    format_id,
+                    pixelformat: fmtdesc.pixelformat,
+                    interval,
+                });
+                formats.push(VideoFormat {
+                    format_id,
+                    width: width as usize,
+                    height: height as usize,
+                    pixel_format,
+                    frame_rate
+                });
+            }
+        }
+    }
+    if formats.is_empty() {
+        return None
+    }
+    Some(V4l2Input {
+        source: V4l2Source::Device(path.to_string()),
+        desc: VideoInputDesc {
+            input_id: LiveId::from_str(&format!("{} {}", bus_info, name)).into(),
+            name,
+            formats
+        },
+        v4l2_formats
+    })
+}
+
+unsafe fn query_frame_sizes(fd: c_int, pixelformat: u32) -> Vec<(u32, u32)> {
+    let mut sizes = Vec::new();
+    let mut index = 0;
+    loop {
+        let mut frmsize: v4l2_frmsizeenum = std::mem::zeroed();
+        frmsize.index = index;
+        frmsize.pixel_format = pixelformat;
+        if xioctl(fd, VIDIOC_ENUM_FRAMESIZES, &mut frmsize) < 0 {
+            break;
+        }
+        if frmsize.type_ == V4L2_FRMSIZE_TYPE_DISCRETE {
+            sizes.push((frmsize.size.discrete.width, frmsize.size.discrete.height));
+            index += 1;
+        }
+        else {
+            // stepwise or continuous, only offer the largest size
+            sizes.push((frmsize.size.stepwise.max_width, frmsize.size.stepwise.max_height));
+            break;
+        }
+    }
+    sizes
+}
+
+unsafe fn query_frame_intervals(fd: c_int, pixelformat: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+    let mut intervals = Vec::new();
+    let mut index = 0;
+    loop {
+        let mut frmival: v4l2_frmivalenum = std::mem::zeroed();
+        frmival.index = index;
+        frmival.pixel_format = pixelformat;
+        frmival.width = width;
+        frmival.height = height;
+        if xioctl(fd, VIDIOC_ENUM_FRAMEINTERVALS, &mut frmival) < 0 {
+            break;
+        }
+        let fract = if frmival.type_ == V4L2_FRMIVAL_TYPE_DISCRETE {
+            frmival.interval.discrete
+        }
+        else {
+            frmival.interval.stepwise.min
+        };
+        if fract.numerator != 0 && fract.denominator != 0 {
+            intervals.push((fract.numerator, fract.denominator));
+        }
+        if frmival.type_ != V4L2_FRMIVAL_TYPE_DISCRETE {
+            break;
+        }
+        index += 1;
+    }
+    intervals
+}
+
+struct MmapBuffer {
+    ptr: *mut c_void,
+    length: usize,
+}
+
+struct V4l2Stream {
+    fd: c_int,
+    buffers: Vec<MmapBuffer>,
+    streaming: bool,
+}
+
+impl Drop for V4l2Stream {
+    fn drop(&mut self) {
+        unsafe {
+            if self.streaming {
+                let mut buf_type = V4L2_BUF_TYPE_VIDEO_CAPTURE as c_int;
+                xioctl(self.fd, VIDIOC_STREAMOFF, &mut buf_type);
+            }
+            for buffer in &self.buffers {
+                libc_sys::munmap(buffer.ptr, buffer.length);
+            }
+            libc_sys::close(self.fd);
+        }
+    }
+}
+
+fn os_error(what: &str) -> String {
+    format!("{} failed: {}", what, std::io::Error::last_os_error())
+}
+
+fn capture_device(
+    path: &str,
+    v4l2_format: V4l2Format,
+    mut format: VideoFormat,
+    capture_cb: &Arc<Mutex<Option<VideoInputFn> > >,
+    running: &AtomicBool
+) -> Result<(), String> {
+    let fd = open_device(path).ok_or_else( || os_error(&format!("Opening {}", path))) ?;
+    let mut stream = V4l2Stream {fd, buffers: Vec::new(), streaming: false};
+    unsafe {
+        let mut fmt: v4l2_format = std::mem::zeroed();
+        fmt.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        fmt.fmt.pix.width = format.width as u32;
+        fmt.fmt.pix.height = format.height as u32;
+        fmt.fmt.pix.pixelformat = v4l2_format.pixelformat;
+        fmt.fmt.pix.field = V4L2_FIELD_ANY;
+        if xioctl(fd, VIDIOC_S_FMT, &mut fmt) < 0 {
+            return Err(os_error("VIDIOC_S_FMT"))
+        }
+        let pix = fmt.fmt.pix;
+        if pix.width as usize != format.width || pix.height as usize != format.height {
+            crate::log!("Video format not correct got {} x {} for {:?}", pix.width, pix.height, format);
+            format.width = pix.width as usize;
+            format.height = pix.height as usize;
+        }
+
+        if let Some((numerator, denominator)) = v4l2_format.interval {
+            let mut parm: v4l2_streamparm = std::mem::zeroed();
+            parm.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+            parm.parm.capture.timeperframe = v4l2_fract {numerator, denominator};
+            // not all drivers can change the frame rate, the stream still works if this fails
+            xioctl(fd, VIDIOC_S_PARM, &mut parm);
+        }
+
+        let mut req: v4l2_requestbuffers = std::mem::zeroed();
+        req.count = NUM_BUFFERS;
+        req.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        req.memory = V4L2_MEMORY_MMAP;
+        if xioctl(fd, VIDIOC_REQBUFS, &mut req) < 0 || req.count == 0 {
+            return Err(os_error("VIDIOC_REQBUFS"))
+        }
+        for index in 0..req.count {
+            let mut buf: v4l2_buffer = std::mem::zeroed();
+            buf.index = index;
+            buf.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+            buf.memory = V4L2_MEMORY_MMAP;
+            if xioctl(fd, VIDIOC_QUERYBUF, &mut buf) < 0 {
+                return Err(os_error("VIDIOC_QUERYBUF"))
+            }
+            let ptr = libc_sys::mmap(
+                std::ptr::null_mut(),
+                buf.length as usize,
+                libc_sys::PROT_READ | libc_sys::PROT_WRITE,
+                libc_sys::MAP_SHARED,
+                fd,
+                buf.m.offset as _
+            );
+            if ptr == libc_sys::MAP_FAILED {
+                return Err(os_error("mmap"))
+            }
+            stream.buffers.push(MmapBuffer {ptr, length: buf.length as usize});
+            if xioctl(fd, VIDIOC_QBUF, &mut buf) < 0 {
+                return Err(os_error("VIDIOC_QBUF"))
+            }
+        }
+        let mut buf_type = V4L2_BUF_TYPE_VIDEO_CAPTURE as c_int;
+        if xioctl(fd, VIDIOC_STREAMON, &mut buf_type) < 0 {
+            return Err(os_error("VIDIOC_STREAMON"))
+        }
+        stream.streaming = true;
+
+        let mut converter = FrameConverter::default();
+        while running.load(Ordering::Relaxed) {
+            let mut fds: libc_sys::fd_set = std::mem::zeroed();
+            libc_sys::FD_ZERO(&mut fds);
+            libc_sys::FD_SET(fd, &mut fds);
+            let mut timeout = libc_sys::timeval {tv_sec: 0, tv_usec: 100_000};
+            let ret = libc_sys::select(fd + 1, &mut fds, std::ptr::null_mut(), std::ptr::null_mut(), &mut timeout);
+            if ret < 0 {
+                if std::io::Error::last_os_error().raw_os_error() == Some(libc_sys::EINTR) {
+                    continue;
+                }
+                return Err(os_error("select"))
+            }
+            if ret == 0 {
+                continue;
+            }
+            let mut buf: v4l2_buffer = std::mem::zeroed();
+            buf.type_ = V4L2_BUF_TYPE_VIDEO_CAPTURE;
+            buf.memory = V4L2_MEMORY_MMAP;
+            if xioctl(fd, VIDIOC_DQBUF, &mut buf) < 0 {
+                if std::io::Error::last_os_error().raw_os_error() == Some(libc_sys::EAGAIN) {
+                    continue;
+                }
+                return Err(os_error("VIDIOC_DQBUF"))
+            }
+            let mapped = &stream.buffers[buf.index as usize];
+            let data = std::slice::from_raw_parts(mapped.ptr as *const u8, (buf.bytesused as usize).min(mapped.length));
+            if let Some(cb) = &mut *capture_cb.lock().unwrap() {
+                match v4l2_format.pixelformat {
+                    V4L2_PIX_FMT_YUYV => converter.deliver_yuyv(data, pix.bytesperline as usize, format, cb),
+                    _ => converter.deliver_mjpeg(data, format, cb),
+                }
+            }
+            if xioctl(fd, VIDIOC_QBUF, &mut buf) < 0 {
+                return Err(os_error("VIDIOC_QBUF"))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn query_file(path: &str) -> Option<V4l2Input> {
+    let data = std::fs::read(path).ok() ?;
+    let first = split_jpeg_frames(&data).next() ?;
+    let mut decoder = JpegDecoder::new(first);
+    decoder.decode_headers().ok() ?;
+    let info = decoder.info() ?;
+    let frame_rate = Some(30.0);
+    let format_id = format_id_for(info.width as usize, info.height as usize, VideoPixelFormat::MJPEG, frame_rate);
+    Some(V4l2Input {
+        source: V4l2Source::File(path.to_string()),
+        desc: VideoInputDesc {
+            input_id: LiveId::from_str(path).into(),
+            name: format!("File {}", path),
+            formats: vec![VideoFormat {
+                format_id,
+                width: info.width as usize,
+                height: info.height as usize,
+                pixel_format: VideoPixelFormat::MJPEG,
+                frame_rate
+            }]
+        },
+        v4l2_formats: vec![V4l2Format {
+            format_id,
+            pixelformat: V4L2_PIX_FMT_MJPEG,
+            interval: Some((1, 30))
+        }]
+    })
+}
+
+fn capture_file(
+    path: &str,
+    format: VideoFormat,
+    capture_cb: &Arc<Mutex<Option<VideoInputFn> > >,
+    running: &AtomicBool
+) -> Result<(), String> {
+    let data = std::fs::read(path).map_err( | e | format!("Reading {} failed: {}", path, e)) ?;
+    let frame_time = Duration::from_secs_f64(1.0 / format.frame_rate.unwrap_or(30.0));
+    let mut converter = FrameConverter::default();
+    let mut next = Instant::now();
+    while running.load(Ordering::Relaxed) {
+        for frame in split_jpeg_frames(&data) {
+            if !running.load(Ordering::Relaxed) {
+                break;
+            }
+            if let Some(cb) = &mut *capture_cb.lock().unwrap() {
+                converter.deliver_mjpeg(frame, format, cb);
+            }
+            next += frame_time;
+            let now = Instant::now();
+            if next > now {
+                std::thread::sleep(next - now);
+            }
+            else {
+                next = now;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Splits a stream of concatenated jpeg images on their start and end of image markers
+fn split_jpeg_frames(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        while pos + 1 < data.len() && !(data[pos] == 0xff && data[pos + 1] == 0xd8) {
+            pos += 1;
+        }
+        if pos + 1 >= data.len() {
+            return None
+        }
+        let start = pos;
+        let mut end = pos + 2;
+        while end + 1 < data.len() {
+            // a frame ends at an end of image marker followed by the next frame or the end of the data
+            if data[end] == 0xff && data[end + 1] == 0xd9 && (end + 2 == data.len() || data[end + 2] == 0xff) {
+                end += 2;
+                pos = end;
+                return Some(&data[start..end])
+            }
+            end += 1;
+        }
+        pos = data.len();
+        Some(&data[start..])
+    })
+}
+
+/// Turns captured frames into YUY2, packed as one u32 per two pixels like the other platforms deliver it
+#[derive(Default)]
+struct FrameConverter {
+    jpeg: Vec<u8>,
+    ycbcr: Vec<u8>,
+    yuy2: Vec<u32>,
+}
+
+impl FrameConverter {
+    fn deliver_yuyv(&mut self, data: &[u8], bytes_per_line: usize, mut format: VideoFormat, cb: &mut VideoInputFn) {
+        let row_bytes = format.width * 2;
+        if data.len() < bytes_per_line * format.height || bytes_per_line < row_bytes {
+            return
+        }
+        format.pixel_format = VideoPixelFormat::YUY2;
+        if bytes_per_line == row_bytes && data.as_ptr().align_offset(4) == 0 {
+            // mmap buffers are page aligned so this can go out without a copy
+            let data = unsafe {std::slice::from_raw_parts(data.as_ptr() as *const u32, row_bytes * format.height / 4)};
+            cb(VideoBufferRef {format, data: VideoBufferRefData::U32(data)});
+            return
+        }
+        self.yuy2.clear();
+        for row in data.chunks(bytes_per_line).take(format.height) {
+            self.yuy2.extend(row[0..row_bytes].chunks_exact(4).map( | v | u32::from_le_bytes([v[0], v[1], v[2], v[3]])));
+        }
+        cb(VideoBufferRef {format, data: VideoBufferRefData::U32(&self.yuy2)});
+    }
+
+    fn deliver_mjpeg(&mut self, data: &[u8], mut format: VideoFormat, cb: &mut VideoInputFn) {
+        if data.len() < 4 || data[0] != 0xff || data[1] != 0xd8 {
+            return
+        }
+        mjpeg_with_avi1_marker(data, &mut self.jpeg);
+
+        let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::YCbCr);
+        let mut decoder = JpegDecoder::new_with_options(&self.jpeg[..], options);
+        if let Err(err) = decoder.decode_headers() {
+            crate::error!("Video frame jpeg header error {:?}", err);
+            return
+        }
+        let info = decoder.info().unwrap();
+        let (width, height) = (info.width as usize, info.height as usize);
+        self.ycbcr.resize(decoder.output_buffer_size().unwrap_or(width * height * 3), 0);
+        if let Err(err) = decoder.decode_into(&mut self.ycbcr) {
+            crate::error!("Video frame jpeg decode error {:?}", err);
+            return
+        }
+        self.yuy2.clear();
+        for row in self.ycbcr.chunks_exact(width * 3).take(height) {
+            self.yuy2.extend(row.chunks_exact(6).map( | v | {
+                let cb = (v[1] as u32 + v[4] as u32) >> 1;
+                let cr = (v[2] as u32 + v[5] as u32) >> 1;
+                v[0] as u32 | (cb << 8) | ((v[3] as u32) << 16) | (cr << 24)
+            }));
+        }
+        format.width = width & !1;
+        format.height = height;
+        format.pixel_format = VideoPixelFormat::YUY2;
+        cb(VideoBufferRef {format, data: VideoBufferRefData::U32(&self.yuy2)});
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jpeg(body: &[u8]) -> Vec<u8> {
+        let mut data = vec![0xff, 0xd8];
+        data.extend_from_slice(body);
+        data.extend_from_slice(&[0xff, 0xd9]);
+        data
+    }
+
+    #[test]
+    fn splits_concatenated_jpegs() {
+        let first = jpeg(&[0xff, 0xe0, 1, 2, 3]);
+        // an end of image marker inside the data that isn't followed by a marker doesn't end the frame
+        let second = jpeg(&[0xff, 0xdb, 0xff, 0xd9, 7, 8]);
+        let mut data = vec![0, 1, 2];
+        data.extend_from_slice(&first);
+        data.extend_from_slice(&second);
+        let frames: Vec<&[u8]> = split_jpeg_frames(&data).collect();
+        assert_eq!(frames, [&first[..], &second[..]]);
+
+        // a truncated last frame comes out as it is
+        data.extend_from_slice(&[0xff, 0xd8, 9, 9]);
+        let frames: Vec<&[u8]> = split_jpeg_frames(&data).collect();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2], [0xff, 0xd8, 9, 9]);
+
+        assert_eq!(split_jpeg_frames(&[]).count(), 0);
+        assert_eq!(split_jpeg_frames(&[0xff, 0xd9, 1, 2, 0xff]).count(), 0);
+    }
+
+    fn yuyv_format(width: usize, height: usize) -> VideoFormat {
+        VideoFormat {
+            format_id: LiveId(1).into(),
+            width,
+            height,
+            pixel_format: VideoPixelFormat::YUY2,
+            frame_rate: None
+        }
+    }
+
+    // the format and pixels of every frame the converter hands out
+    fn deliver(data: &[u8], bytes_per_line: usize, format: VideoFormat) -> Vec<(VideoFormat, Vec<u32>)> {
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        let frames_clone = frames.clone();
+        let mut cb: VideoInputFn = Box::new(move | buffer | {
+            if let VideoBufferRefData::U32(data) = buffer.data {
+                frames_clone.lock().unwrap().push((buffer.format, data.to_vec()));
+            }
+        });
+        FrameConverter::default().deliver_yuyv(data, bytes_per_line, format, &mut cb);
+        let frames = frames.lock().unwrap().clone();
+        frames
+    }
+
+    #[test]
+    fn yuyv_rows_drop_their_stride_padding() {
+        // 4x2 pixels is 8 bytes a row, padded to 12
+        let mut data = Vec::new();
+        for row in 0..2u8 {
+            data.extend((0..8).map( | i | row * 16 + i));
+            data.extend_from_slice(&[0xee; 4]);
+        }
+        let frames = deliver(&data, 12, yuyv_format(4, 2));
+        assert_eq!(frames.len(), 1);
+        let (format, pixels) = &frames[0];
+        assert_eq!(format.pixel_format, VideoPixelFormat::YUY2);
+        assert_eq!((format.width, format.height), (4, 2));
+        assert_eq!(pixels, &[0x03020100, 0x07060504, 0x13121110, 0x17161514]);
+
+        // packed rows go out as they are
+        let packed: Vec<u32> = vec![0x03020100, 0x07060504, 0x13121110, 0x17161514];
+        let bytes: Vec<u8> = packed.iter().flat_map( | v | v.to_le_bytes()).collect();
+        assert_eq!(deliver(&bytes, 8, yuyv_format(4, 2))[0].1, packed);
+        // also from an unaligned buffer
+        let mut unaligned = vec![0];
+        unaligned.extend_from_slice(&bytes);
+        assert_eq!(deliver(&unaligned[1..], 8, yuyv_format(4, 2))[0].1, packed);
+    }
+
+    #[test]
+    fn short_yuyv_frames_are_skipped() {
+        assert!(deliver(&[0; 20], 12, yuyv_format(4, 2)).is_empty());
+        // a stride shorter than a row can't be right
+        assert!(deliver(&[0; 16], 6, yuyv_format(4, 2)).is_empty());
+    }
+
+    #[test]
+    fn hotplug_observer_stops_without_waiting_out_its_interval() {
+        let start = Instant::now();
+        let mut observer = V4l2Access::observe_device_changes(SignalToUI::new());
+        observer.stop();
+        assert!(observer.thread.is_none());
+        assert!(start.elapsed() < Duration::from_millis(900));
+    }
+}
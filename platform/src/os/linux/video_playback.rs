@@ -0,0 +1,447 @@
+use {
+    std::sync::{Arc, Mutex},
+    std::sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    std::time::{Duration, Instant},
+    self::super::{
+        alsa_audio::AlsaAudioDevice,
+        alsa_sys::SND_PCM_STREAM_PLAYBACK,
+        avi_demux::*,
+        linux_media::CxLinuxMedia,
+        opengl::upload_video_frame,
+    },
+    crate::{
+        cx::Cx,
+        audio::AudioDeviceId,
+        event::{
+            Event,
+            VideoSource,
+            VideoPlaybackPreparedEvent,
+            VideoTextureUpdatedEvent,
+            VideoPlaybackCompletedEvent,
+            VideoPlaybackResourcesReleasedEvent,
+            VideoDecodingErrorEvent,
+        },
+        makepad_live_id::*,
+        thread::SignalToUI,
+    },
+    makepad_zune_jpeg::{
+        JpegDecoder,
+        makepad_zune_core::{
+            colorspace::ColorSpace,
+            options::DecoderOptions,
+        },
+    },
+};
+
+// Video playback on Linux is a software pipeline for MJPEG in AVI files, with optional PCM audio.
+// A player thread decodes frames and publishes each one when the playback clock reaches it,
+// the UI thread then uploads it into the VideoRGB texture of the Video widget.
+// The clock is the position of the audio output when there is audio, the wall clock otherwise.
+
+enum PlayerEvent {
+    Prepared {width: u32, height: u32, duration: u128},
+    Completed,
+    Error(String),
+}
+
+#[derive(Default)]
+struct PublishedFrame {
+    width: usize,
+    height: usize,
+    data: Vec<u32>,
+    fresh: bool,
+}
+
+struct PlayerShared {
+    playing: AtomicBool,
+    muted: AtomicBool,
+    stop: AtomicBool,
+    audio_clock_active: AtomicBool,
+    /// Microseconds of media the audio output has played, counting up across loops
+    audio_clock: AtomicU64,
+    frame: Mutex<PublishedFrame>,
+    events: Mutex<Vec<PlayerEvent >>,
+    signal: SignalToUI,
+}
+
+impl PlayerShared {
+    fn new(signal: SignalToUI) -> Arc<Self> {
+        Arc::new(Self {
+            playing: AtomicBool::new(false),
+            muted: AtomicBool::new(false),
+            stop: AtomicBool::new(false),
+            audio_clock_active: AtomicBool::new(false),
+            audio_clock: AtomicU64::new(0),
+            frame: Default::default(),
+            events: Default::default(),
+            signal,
+        })
+    }
+
+    fn send(&self, event: PlayerEvent) {
+        self.events.lock().unwrap().push(event);
+        self.signal.set();
+    }
+}
+
+enum PlayerSource {
+    Memory(Vec<u8>),
+    File(String),
+}
+
+pub struct LinuxVideoPlayer {
+    texture_handle: u32,
+    shared: Arc<PlayerShared>,
+}
+
+impl LinuxVideoPlayer {
+    fn start(texture_handle: u32, source: PlayerSource, signal: SignalToUI, autoplay: bool, should_loop: bool) -> Self {
+        let shared = PlayerShared::new(signal);
+        let shared_clone = shared.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = run_player(source, &shared_clone, autoplay, should_loop) {
+                shared_clone.send(PlayerEvent::Error(err));
+            }
+        });
+        Self {texture_handle, shared}
+    }
+}
+
+impl Drop for LinuxVideoPlayer {
+    fn drop(&mut self) {
+        // the player thread sees the stop within a few milliseconds and ends on its own,
+        // joining it here would hold up the UI thread until its current decode is done
+        self.shared.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+impl CxLinuxMedia {
+    pub (crate) fn prepare_video_playback(&mut self, video_id: LiveId, source: VideoSource, texture_handle: u32, autoplay: bool, should_loop: bool) {
+        let source = match source {
+            VideoSource::InMemory(data) => PlayerSource::Memory(data.to_vec()),
+            VideoSource::Filesystem(path) => PlayerSource::File(path),
+            VideoSource::Network(url) => {
+                let shared = PlayerShared::new(self.video_change.clone());
+                shared.send(PlayerEvent::Error(format!("Network video sources are not supported on Linux: {}", url)));
+                self.video_players.insert(video_id, LinuxVideoPlayer {texture_handle, shared});
+                return
+            }
+        };
+        // replacing a player stops the old one
+        let player = LinuxVideoPlayer::start(texture_handle, source, self.video_change.clone(), autoplay, should_loop);
+        self.video_players.insert(video_id, player);
+    }
+
+    pub (crate) fn set_video_playing(&mut self, video_id: LiveId, playing: bool) {
+        if let Some(player) = self.video_players.get(&video_id) {
+            player.shared.playing.store(playing, Ordering::SeqCst);
+        }
+    }
+
+    pub (crate) fn set_video_muted(&mut self, video_id: LiveId, muted: bool) {
+        if let Some(player) = self.video_players.get(&video_id) {
+            player.shared.muted.store(muted, Ordering::SeqCst);
+        }
+    }
+
+    pub (crate) fn cleanup_video_playback(&mut self, video_id: LiveId) {
+        self.video_players.remove(&video_id);
+        self.video_released.push(video_id);
+        self.video_change.set();
+    }
+}
+
+impl Cx {
+    pub (crate) fn handle_video_playback_signals(&mut self) {
+        if !self.os.media.video_change.check_and_clear() {
+            return
+        }
+        let mut to_dispatch = Vec::new();
+        for (video_id, player) in &self.os.media.video_players {
+            let video_id = *video_id;
+            for event in player.shared.events.lock().unwrap().drain(..) {
+                to_dispatch.push(match event {
+                    PlayerEvent::Prepared {width, height, duration} => Event::VideoPlaybackPrepared(VideoPlaybackPreparedEvent {
+                        video_id,
+                        video_width: width,
+                        video_height: height,
+                        duration
+                    }),
+                    PlayerEvent::Completed => Event::VideoPlaybackCompleted(VideoPlaybackCompletedEvent {video_id}),
+                    PlayerEvent::Error(error) => Event::VideoDecodingError(VideoDecodingErrorEvent {video_id, error}),
+                });
+            }
+            let mut frame = player.shared.frame.lock().unwrap();
+            if frame.fresh {
+                frame.fresh = false;
+                upload_video_frame(player.texture_handle, frame.width, frame.height, &frame.data);
+                to_dispatch.push(Event::VideoTextureUpdated(VideoTextureUpdatedEvent {video_id}));
+            }
+        }
+        for video_id in self.os.media.video_released.drain(..) {
+            to_dispatch.push(Event::VideoPlaybackResourcesReleased(VideoPlaybackResourcesReleasedEvent {video_id}));
+        }
+        for event in to_dispatch {
+            self.call_event_handler(&event);
+        }
+    }
+}
+
+/// Keeps time while playing, used when there is no audio output to follow
+#[derive(Default)]
+struct WallClock {
+    time: f64,
+    last: Option<Instant>,
+}
+
+impl WallClock {
+    fn update(&mut self, playing: bool) -> f64 {
+        let now = Instant::now();
+        if playing {
+            if let Some(last) = self.last {
+                self.time += (now - last).as_secs_f64();
+            }
+            self.last = Some(now);
+        }
+        else {
+            self.last = None;
+        }
+        self.time
+    }
+}
+
+fn run_player(source: PlayerSource, shared: &Arc<PlayerShared>, autoplay: bool, should_loop: bool) -> Result<(), String> {
+    let data = match source {
+        PlayerSource::Memory(data) => data,
+        PlayerSource::File(path) => std::fs::read(&path).map_err( | e | format!("Cannot read video {}: {}", path, e)) ?
+    };
+    let avi = AviFile::parse(&data) ?;
+    let video = avi.video.as_ref().ok_or_else( || "AVI file has no video stream".to_string()) ?;
+    if !video.codec.eq_ignore_ascii_case(b"MJPG") {
+        return Err(format!("Unsupported AVI video codec {}, only MJPEG is supported", String::from_utf8_lossy(&video.codec)))
+    }
+    if video.frames.is_empty() {
+        return Err("AVI file has no video frames".to_string())
+    }
+    let duration = avi.duration();
+
+    let audio_thread = match &avi.audio {
+        Some(audio) => match audio.decode(&data) {
+            Ok(channels) => {
+                let shared = shared.clone();
+                let sample_rate = audio.sample_rate;
+                Some(std::thread::spawn(move || run_audio(channels, sample_rate, duration, should_loop, &shared)))
+            }
+            Err(err) => {
+                crate::error!("Video audio not played: {}", err);
+                None
+            }
+        }
+        None => None
+    };
+
+    shared.send(PlayerEvent::Prepared {
+        width: video.width as u32,
+        height: video.height as u32,
+        duration: (duration * 1000.0) as u128
+    });
+    if autoplay {
+        shared.playing.store(true, Ordering::SeqCst);
+    }
+
+    let mut decoder = MjpegDecoder::default();
+    let mut clock = WallClock::default();
+    let mut index = 0;
+    let mut loop_base = 0.0;
+    let mut decoded = None;
+    let mut completed = false;
+    while !shared.stop.load(Ordering::Relaxed) {
+        let playing = shared.playing.load(Ordering::Relaxed);
+        let mut now = clock.update(playing);
+        if shared.audio_clock_active.load(Ordering::Relaxed) {
+            // follow the audio, and keep the wall clock in step in case the audio goes away
+            now = shared.audio_clock.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+            clock.time = now;
+        }
+        if !playing || completed {
+            std::thread::sleep(Duration::from_millis(5));
+            continue;
+        }
+        if index >= video.frames.len() {
+            if should_loop {
+                index = 0;
+                loop_base += duration;
+            }
+            else if now >= loop_base + duration {
+                completed = true;
+                shared.playing.store(false, Ordering::SeqCst);
+                shared.send(PlayerEvent::Completed);
+            }
+            else {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            continue;
+        }
+        let due = loop_base + index as f64 * video.frame_duration;
+        // when we fall behind by more than a frame, skip ahead instead of decoding everything
+        let behind = ((now - due) / video.frame_duration).floor();
+        if behind >= 1.0 && index + 1 < video.frames.len() {
+            index = (index + behind as usize).min(video.frames.len() - 1);
+            continue;
+        }
+        if decoded != Some(index) {
+            decoded = Some(index);
+            if let Err(err) = decoder.decode(&data[video.frames[index].clone()]) {
+                crate::error!("Video frame {} not decoded: {}", index, err);
+                index += 1;
+            }
+            continue;
+        }
+        if now < due {
+            std::thread::sleep(Duration::from_secs_f64((due - now).min(0.005)));
+            continue;
+        }
+        decoder.publish(shared);
+        index += 1;
+    }
+    if let Some(audio_thread) = audio_thread {
+        let _ = audio_thread.join();
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct MjpegDecoder {
+    jpeg: Vec<u8>,
+    width: usize,
+    height: usize,
+    bgra: Vec<u32>,
+}
+
+impl MjpegDecoder {
+    fn decode(&mut self, frame: &[u8]) -> Result<(), String> {
+        mjpeg_with_avi1_marker(frame, &mut self.jpeg);
+        let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::BGRA);
+        let mut decoder = JpegDecoder::new_with_options(&self.jpeg[..], options);
+        decoder.decode_headers().map_err( | e | format!("{:?}", e)) ?;
+        let info = decoder.info().ok_or_else( || "no image info".to_string()) ?;
+        self.width = info.width as usize;
+        self.height = info.height as usize;
+        self.bgra.resize(self.width * self.height, 0);
+        let bytes = unsafe {std::slice::from_raw_parts_mut(self.bgra.as_mut_ptr() as *mut u8, self.bgra.len() * 4)};
+        decoder.decode_into(bytes).map_err( | e | format!("{:?}", e))
+    }
+
+    fn publish(&mut self, shared: &PlayerShared) {
+        let mut frame = shared.frame.lock().unwrap();
+        frame.width = self.width;
+        frame.height = self.height;
+        std::mem::swap(&mut frame.data, &mut self.bgra);
+        frame.fresh = true;
+        drop(frame);
+        shared.signal.set();
+    }
+}
+
+fn run_audio(channels: Vec<Vec<f32>>, sample_rate: usize, duration: f64, should_loop: bool, shared: &PlayerShared) {
+    let mut device = match AlsaAudioDevice::new("default", AudioDeviceId(LiveId(0)), SND_PCM_STREAM_PLAYBACK) {
+        Ok((device, _)) => device,
+        Err(err) => {
+            crate::error!("Video audio output not available, using the wall clock: {:?}", err);
+            return
+        }
+    };
+    let mut buffer = device.allocate_matching_buffer();
+    let device_rate = device.sample_rate() as f64;
+    let step = sample_rate as f64 / device_rate;
+    let duration_frames = duration * sample_rate as f64;
+    // source frames consumed, counting up across loops
+    let mut position = 0.0f64;
+    // device frames written in total, and the media frames among them
+    let mut total_written = 0u64;
+    let mut media_written = 0u64;
+    // for each write still queued in the device, where it ends and whether it carried media
+    let mut queued = std::collections::VecDeque::new();
+    let mut clock = 0u64;
+    shared.audio_clock_active.store(true, Ordering::SeqCst);
+    while !shared.stop.load(Ordering::Relaxed) {
+        buffer.zero();
+        let mut count = 0;
+        if shared.playing.load(Ordering::Relaxed) {
+            let muted = shared.muted.load(Ordering::Relaxed);
+            let frame_count = buffer.frame_count();
+            while count < frame_count && (should_loop || position < duration_frames) {
+                if !muted {
+                    let pos = if should_loop {position % duration_frames} else {position};
+                    let index = pos as usize;
+                    let fract = (pos - index as f64) as f32;
+                    for out in 0..buffer.channel_count() {
+                        let samples = &channels[out.min(channels.len() - 1)];
+                        let a = samples.get(index).copied().unwrap_or(0.0);
+                        let b = samples.get(index + 1).copied().unwrap_or(a);
+                        buffer.channel_mut(out)[count] = a + (b - a) * fract;
+                    }
+                }
+                position += step;
+                count += 1;
+            }
+        }
+        if let Err(err) = device.write_output_buffer(&buffer) {
+            crate::error!("Video audio output failed, using the wall clock: {:?}", err);
+            break;
+        }
+        // media frames go first in a buffer, the rest is silence
+        let frame_count = buffer.frame_count() as u64;
+        if count > 0 {
+            queued.push_back((total_written + count as u64, media_written + count as u64, true));
+        }
+        total_written += frame_count;
+        media_written += count as u64;
+        queued.push_back((total_written, media_written, false));
+
+        // what has been heard is what was written minus what is still queued in the device
+        let played = total_written.saturating_sub(device.delay_frames() as u64);
+        while queued.front().is_some_and( | v | v.0 <= played) {
+            queued.pop_front();
+        }
+        let media_played = match queued.front() {
+            Some((end, media_end, true)) => media_end - (end - played),
+            Some((_, media_end, false)) => *media_end,
+            None => media_written
+        };
+        clock = clock.max((media_played as f64 * 1_000_000.0 / device_rate) as u64);
+        shared.audio_clock.store(clock, Ordering::Relaxed);
+    }
+    shared.audio_clock_active.store(false, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        super::super::avi_demux::tests::{avi_file, chunk, video_strl},
+    };
+
+    #[test]
+    fn dropping_a_player_does_not_wait_for_its_thread() {
+        // the frame isn't a jpeg, but the player only decodes once it plays
+        let data = avi_file(&[video_strl(16, 16, 30)], &[chunk(b"00dc", &[0xff, 0xd8, 0xff, 0xd9])]);
+        let player = LinuxVideoPlayer::start(0, PlayerSource::Memory(data), SignalToUI::new(), false, false);
+        let shared = player.shared.clone();
+        let start = Instant::now();
+        while shared.events.lock().unwrap().is_empty() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(matches!(shared.events.lock().unwrap()[..], [PlayerEvent::Prepared {width: 16, height: 16, ..}]));
+
+        let start = Instant::now();
+        drop(player);
+        assert!(start.elapsed() < Duration::from_millis(5));
+        assert!(shared.stop.load(Ordering::SeqCst));
+        // the detached thread lets go of the shared state once it has stopped
+        while Arc::strong_count(&shared) > 1 && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+}
@@ -11,7 +11,8 @@ use {
         x11::xlib_event::*,
         x11::xlib_app::*,
         x11::x11_sys,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        atspi::AtspiBridge,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi}, 
@@ -138,6 +139,7 @@ impl Cx {
                     self.call_draw_event();
                     self.os.opengl_cx.as_ref().unwrap().make_current();
                     self.opengl_compile_shaders();
+                    self.update_atspi();
                 }
                 // ok here we send out to all our childprocesses
                 
@@ -222,6 +224,23 @@ impl Cx {
     pub(crate) fn handle_networking_events(&mut self) {
     }
     
+    // the bridge only starts once something has drawn, and stays off when the desktop has no screen reader
+    fn update_atspi(&mut self) {
+        if !self.os.atspi_started {
+            self.os.atspi_started = true;
+            let app_name = std::env::current_exe().ok()
+                .and_then( | exe | exe.file_stem().map( | name | name.to_string_lossy().to_string()))
+                .unwrap_or_default();
+            self.os.atspi = Some(AtspiBridge::start(&app_name));
+        }
+        if self.os.atspi.is_some() && self.accessibility.take_changed() {
+            let tree = self.access_tree();
+            if self.os.atspi.as_ref().is_some_and( | atspi | !atspi.update(tree)) {
+                self.os.atspi = None;
+            }
+        }
+    }
+    
     pub (crate) fn handle_repaint(&mut self, opengl_windows: &mut Vec<OpenglWindow>) {
         self.os.opengl_cx.as_ref().unwrap().make_current();
         let mut passes_todo = Vec::new();
@@ -339,14 +358,26 @@ impl Cx {
                 CxOsOp::HttpRequest{request_id:_, request:_} => {
                     todo!()
                 },
-                CxOsOp::PrepareVideoPlayback(_, _, _, _, _) => todo!(),
-                CxOsOp::BeginVideoPlayback(_) => todo!(),
-                CxOsOp::PauseVideoPlayback(_) => todo!(),
-                CxOsOp::ResumeVideoPlayback(_) => todo!(),
-                CxOsOp::MuteVideoPlayback(_) => todo!(),
-                CxOsOp::UnmuteVideoPlayback(_) => todo!(),
-                CxOsOp::CleanupVideoPlaybackResources(_) => todo!(),
-                CxOsOp::UpdateVideoSurfaceTexture(_) => todo!(),
+                CxOsOp::PrepareVideoPlayback(video_id, source, texture_handle, autoplay, is_looping) => {
+                    self.os.media.prepare_video_playback(video_id, source, texture_handle, autoplay, is_looping);
+                },
+                CxOsOp::BeginVideoPlayback(video_id) | CxOsOp::ResumeVideoPlayback(video_id) => {
+                    self.os.media.set_video_playing(video_id, true);
+                },
+                CxOsOp::PauseVideoPlayback(video_id) => {
+                    self.os.media.set_video_playing(video_id, false);
+                },
+                CxOsOp::MuteVideoPlayback(video_id) => {
+                    self.os.media.set_video_muted(video_id, true);
+                },
+                CxOsOp::UnmuteVideoPlayback(video_id) => {
+                    self.os.media.set_video_muted(video_id, false);
+                },
+                CxOsOp::CleanupVideoPlaybackResources(video_id) => {
+                    self.os.media.cleanup_video_playback(video_id);
+                },
+                CxOsOp::UpdateVideoSurfaceTexture(_) => {
+                },
 
                 CxOsOp::SaveFileDialog(_) => todo!(),
                 CxOsOp::SelectFileDialog(_) => todo!(),
@@ -382,5 +413,7 @@ pub struct CxOs {
     pub (crate) start_time: Option<Instant>,
     // HACK(eddyb) generalize this to EGL, properly.
     pub(super) opengl_cx: Option<OpenglCx>,
+    pub(crate) atspi: Option<AtspiBridge>,
+    pub(crate) atspi_started: bool,
 }
 
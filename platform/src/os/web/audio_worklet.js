@@ -66,7 +66,8 @@ class AudioWorklet extends AudioWorkletProcessor {
             let frames = outputs[0][0].length;
             let channels = outputs[0].length;
 
-            let output_ptr = context.exports.wasm_audio_output_entrypoint(context.context_ptr, frames, channels);
+            // sampleRate is the global of the worklet scope, the rate the context actually runs at
+            let output_ptr = context.exports.wasm_audio_output_entrypoint(context.context_ptr, frames, channels, sampleRate);
             
             if (context.buffer_ref_len_check != context.memory.buffer.byteLength) {
                 context.f32 = new Float32Array(context.memory.buffer);
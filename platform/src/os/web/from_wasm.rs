@@ -230,6 +230,40 @@ pub struct FromWasmAllocTextureImage2D_Ru8 {
     pub data: WasmPtrU8
 }
 
+#[allow(non_camel_case_types)]
+#[derive(FromWasm)]
+pub struct FromWasmUpdateTextureSubImage2D_BGRAu8_32 {
+    pub texture_id: usize,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub stride: usize,
+    pub data: WasmPtrU32
+}
+
+#[allow(non_camel_case_types)]
+#[derive(FromWasm)]
+pub struct FromWasmUpdateTextureSubImage2D_Ru8 {
+    pub texture_id: usize,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub stride: usize,
+    pub data: WasmPtrU8
+}
+
+#[derive(FromWasm)]
+pub struct FromWasmTextureSampler {
+    pub texture_id: usize,
+    pub min_filter: u32,
+    pub mag_filter: u32,
+    pub wrap_s: u32,
+    pub wrap_t: u32,
+    pub max_anisotropy: u32
+}
+
 #[derive(FromWasm, Default)]
 pub struct WColorTarget {
     pub texture_id: usize,
@@ -297,7 +331,7 @@ pub struct FromWasmUseMidiInputs {
 #[derive(FromWasm)]
 pub struct FromWasmSendMidiOutput {
     pub uid: String,
-    pub data: u32
+    pub data: WasmDataU8
 }
 
 #[derive(FromWasm)]
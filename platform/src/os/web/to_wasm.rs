@@ -672,7 +672,7 @@ pub struct ToWasmWebSocketBinary {
 #[derive(ToWasm)]
 pub struct ToWasmMidiInputData {
     pub uid: String,
-    pub data: u32,
+    pub data: WasmDataU8,
 }
 
 #[derive(ToWasm)]
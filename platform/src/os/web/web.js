@@ -364,24 +364,31 @@ export class WasmWebBrowser extends WasmBridge {
             for (let uid of args.input_uids) {
                 if (input.uid == uid) {
                     input.port.onmidimessage = (e) => {
-                        let data = e.data;
                         this.to_wasm.ToWasmMidiInputData({
                             uid,
-                            data: (data[0] << 16) | (data[1] << 8) | data[2],
+                            data: e.data,
                         });
                         this.do_wasm_pump();
                     }
                     continue outer;
                 }
             }
-            input.onmidimessage = undefined
+            input.port.onmidimessage = undefined
         }
     }
     
     FromWasmSendMidiOutput(args){
+        let data = this.clone_data_u8(args.data);
+        this.free_data_u8(args.data);
         for (let output of this.midi_outputs) {
             if(output.uid == args.uid){
-                output.port.send([(data>>16)&0xff,(data>>8)&0xff,(data>>0)&0xff]);
+                try {
+                    output.port.send(data);
+                }
+                catch (e) {
+                    // sysex throws when access was granted without it
+                    console.error("Cannot send midi", e);
+                }
             }
         }
     }
@@ -391,7 +398,8 @@ export class WasmWebBrowser extends WasmBridge {
             return this.reload_midi_ports();
         }
         if (navigator.requestMIDIAccess) {
-            navigator.requestMIDIAccess().then((midi) => {
+            // ask for sysex first, and fall back to plain midi when the user or browser refuses
+            navigator.requestMIDIAccess({sysex: true}).catch(() => navigator.requestMIDIAccess()).then((midi) => {
                 this.reload_midi_ports = () => {
                     this.midi_inputs.length = 0;
                     this.midi_outputs.length = 0;
@@ -585,6 +585,9 @@ impl CxOsApi for Cx {
             FromWasmAllocVao::to_js_code(),
             FromWasmAllocTextureImage2D_BGRAu8_32::to_js_code(),
             FromWasmAllocTextureImage2D_Ru8::to_js_code(),
+            FromWasmUpdateTextureSubImage2D_BGRAu8_32::to_js_code(),
+            FromWasmUpdateTextureSubImage2D_Ru8::to_js_code(),
+            FromWasmTextureSampler::to_js_code(),
             FromWasmBeginRenderTexture::to_js_code(),
             FromWasmBeginRenderCanvas::to_js_code(),
             FromWasmSetDefaultDepthAndBlendMode::to_js_code(),
@@ -124,7 +124,7 @@ impl WebAudioAccess {
 
 #[export_name = "wasm_audio_output_entrypoint"]
 #[cfg(target_arch = "wasm32")]
-pub unsafe extern "C" fn wasm_audio_output_entrypoint(context_ptr: u32, frames: u32, channels: u32) -> u32 {
+pub unsafe extern "C" fn wasm_audio_output_entrypoint(context_ptr: u32, frames: u32, channels: u32, sample_rate: f64) -> u32 {
     let wa = context_ptr as *const Mutex<WebAudioAccess>;
     let (output_fn, mut output_buffer, device_id) = {
         let mut wa = (*wa).lock().unwrap();
@@ -137,7 +137,7 @@ pub unsafe extern "C" fn wasm_audio_output_entrypoint(context_ptr: u32, frames:
     let mut output_fn = output_fn.lock().unwrap();
     
     if let Some(output_fn) = &mut *output_fn {
-        output_fn(AudioInfo {device_id, time: None}, &mut output_buffer);
+        output_fn(AudioInfo {device_id, time: None, sample_rate}, &mut output_buffer);
     }
     let ptr = output_buffer.data.as_ptr();
     
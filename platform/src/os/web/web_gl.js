@@ -428,6 +428,8 @@ export class WasmWebGL extends WasmWebBrowser {
         let data_array = new Uint8Array(this.memory.buffer, args.data.ptr, args.width * args.height * 4);
         //agdconsole.log(args.width, args.height);
         gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, args.width, args.height, 0, gl.RGBA, gl.UNSIGNED_BYTE, data_array);
+        gl_tex._width = args.width;
+        gl_tex._height = args.height;
         this.textures[args.texture_id] = gl_tex;
     }
     
@@ -444,9 +446,71 @@ export class WasmWebGL extends WasmWebBrowser {
         let data_array = new Uint8Array(this.memory.buffer, args.data.ptr, args.width * args.height);
         //agdconsole.log(args.width, args.height);
         gl.texImage2D(gl.TEXTURE_2D, 0, gl.LUMINANCE, args.width, args.height, 0, gl.LUMINANCE, gl.UNSIGNED_BYTE, data_array);
+        gl_tex._width = args.width;
+        gl_tex._height = args.height;
         this.textures[args.texture_id] = gl_tex;
     }
 
+    FromWasmUpdateTextureSubImage2D_BGRAu8_32(args) {
+        var gl = this.gl;
+        var gl_tex = this.textures[args.texture_id];
+        if (!gl_tex) return;
+        
+        gl.bindTexture(gl.TEXTURE_2D, gl_tex)
+        this.update_texture_sub_image(args, 4, gl.RGBA);
+    }
+    
+    FromWasmUpdateTextureSubImage2D_Ru8(args) {
+        var gl = this.gl;
+        var gl_tex = this.textures[args.texture_id];
+        if (!gl_tex) return;
+        
+        gl.bindTexture(gl.TEXTURE_2D, gl_tex)
+        gl.pixelStorei(gl.UNPACK_ALIGNMENT, 1);
+        this.update_texture_sub_image(args, 1, gl.LUMINANCE);
+        gl.pixelStorei(gl.UNPACK_ALIGNMENT, 4);
+    }
+    
+    update_texture_sub_image(args, texel_size, format) {
+        var gl = this.gl;
+        let row_bytes = args.stride * texel_size;
+        let start = args.data.ptr + args.y * row_bytes + args.x * texel_size;
+        if (args.width == args.stride) {
+            let data_array = new Uint8Array(this.memory.buffer, start, args.width * args.height * texel_size);
+            gl.texSubImage2D(gl.TEXTURE_2D, 0, args.x, args.y, args.width, args.height, format, gl.UNSIGNED_BYTE, data_array);
+        }
+        else {
+            // WebGL1 has no UNPACK_ROW_LENGTH, so a region narrower than the image goes up row by row
+            for (let row = 0; row < args.height; row ++) {
+                let data_array = new Uint8Array(this.memory.buffer, start + row * row_bytes, args.width * texel_size);
+                gl.texSubImage2D(gl.TEXTURE_2D, 0, args.x, args.y + row, args.width, 1, format, gl.UNSIGNED_BYTE, data_array);
+            }
+        }
+    }
+    
+    FromWasmTextureSampler(args) {
+        var gl = this.gl;
+        var gl_tex = this.textures[args.texture_id];
+        if (!gl_tex) return;
+        
+        let filters = [gl.NEAREST, gl.LINEAR];
+        let wraps = [gl.CLAMP_TO_EDGE, gl.REPEAT, gl.MIRRORED_REPEAT];
+        // WebGL1 can only repeat power of two textures, anything else stays clamped
+        let is_pow2 = (v) => v > 0 && (v & (v - 1)) == 0;
+        let can_repeat = is_pow2(gl_tex._width) && is_pow2(gl_tex._height);
+        
+        gl.bindTexture(gl.TEXTURE_2D, gl_tex)
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MAG_FILTER, filters[args.mag_filter])
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, filters[args.min_filter])
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_S, can_repeat ? wraps[args.wrap_s] : gl.CLAMP_TO_EDGE)
+        gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_T, can_repeat ? wraps[args.wrap_t] : gl.CLAMP_TO_EDGE)
+        if (this.EXT_texture_filter_anisotropic) {
+            let ext = this.EXT_texture_filter_anisotropic;
+            let max = gl.getParameter(ext.MAX_TEXTURE_MAX_ANISOTROPY_EXT);
+            gl.texParameterf(gl.TEXTURE_2D, ext.TEXTURE_MAX_ANISOTROPY_EXT, Math.max(1, Math.min(args.max_anisotropy, max)));
+        }
+    }
+    
     FromWasmBeginRenderTexture(args) {
         if(this.xr !== undefined){
             this.xr.in_xr_pass = false;
@@ -565,6 +629,7 @@ export class WasmWebGL extends WasmWebBrowser {
         this.OES_vertex_array_object = gl.getExtension('OES_vertex_array_object')
         this.OES_element_index_uint = gl.getExtension("OES_element_index_uint")
         this.ANGLE_instanced_arrays = gl.getExtension('ANGLE_instanced_arrays')
+        this.EXT_texture_filter_anisotropic = gl.getExtension('EXT_texture_filter_anisotropic') || gl.getExtension('WEBKIT_EXT_texture_filter_anisotropic')
         
         // check uniform count
         var max_vertex_uniforms = gl.getParameter(gl.MAX_VERTEX_UNIFORM_VECTORS);
@@ -13,7 +13,7 @@ use {
         draw_vars::DRAW_CALL_TEXTURE_SLOTS,
         cx::Cx,
         draw_list::DrawListId,
-        texture::TextureFormat,
+        texture::{TextureFormat, TextureSampler, TextureFilter, TextureWrap, TextureUpdated},
         pass::{PassId, PassClearColor, PassClearDepth},
     },
 };
@@ -81,8 +81,36 @@ impl Cx {
                     
                     let cxtexture = &mut self.textures[texture_id];
                     if cxtexture.format.is_vec(){
-                        if cxtexture.alloc_vec(){}
-                        if cxtexture.check_updated(){
+                        let reallocated = cxtexture.alloc_vec();
+                        let updated = cxtexture.take_updated();
+                        if let (TextureUpdated::Partial(rect), false) = (updated, reallocated){
+                            match &cxtexture.format{
+                                TextureFormat::VecBGRAu8_32{width, data, ..}=>{
+                                    self.os.from_wasm(FromWasmUpdateTextureSubImage2D_BGRAu8_32 {
+                                        texture_id: texture_id.0,
+                                        x: rect.x,
+                                        y: rect.y,
+                                        width: rect.width,
+                                        height: rect.height,
+                                        stride: *width,
+                                        data: WasmPtrU32::new(&data)
+                                    });
+                                }
+                                TextureFormat::VecRu8{width, data, unpack_row_length, ..}=>{
+                                    self.os.from_wasm(FromWasmUpdateTextureSubImage2D_Ru8 {
+                                        texture_id: texture_id.0,
+                                        x: rect.x,
+                                        y: rect.y,
+                                        width: rect.width,
+                                        height: rect.height,
+                                        stride: unpack_row_length.unwrap_or(*width),
+                                        data: WasmPtrU8::new(&data)
+                                    });
+                                }
+                                x=>panic!("Texture format not implemented for webGL {:?}", x)
+                            }
+                        }
+                        else if updated != TextureUpdated::Empty{
                             match &cxtexture.format{
                                 TextureFormat::VecBGRAu8_32{width, height, data}=>{
                                     self.os.from_wasm(FromWasmAllocTextureImage2D_BGRAu8_32 {
@@ -102,8 +130,31 @@ impl Cx {
                                 }
                                 x=>panic!("Texture format not implemented for webGL {:?}", x)
                             }
+                            // the upload resets the texture to linear and clamped
+                            cxtexture.os.sampler = Some(TextureSampler::default());
                         }
                     }
+                    let sampler = cxtexture.sampler_or_default();
+                    if cxtexture.os.sampler != Some(sampler) {
+                        let filter = | filter | match filter {
+                            TextureFilter::Nearest => 0,
+                            TextureFilter::Linear => 1
+                        };
+                        let wrap = | wrap | match wrap {
+                            TextureWrap::ClampToEdge => 0,
+                            TextureWrap::Repeat => 1,
+                            TextureWrap::MirroredRepeat => 2
+                        };
+                        self.os.from_wasm(FromWasmTextureSampler {
+                            texture_id: texture_id.0,
+                            min_filter: filter(sampler.min_filter),
+                            mag_filter: filter(sampler.mag_filter),
+                            wrap_s: wrap(sampler.wrap_s),
+                            wrap_t: wrap(sampler.wrap_t),
+                            max_anisotropy: sampler.max_anisotropy as u32,
+                        });
+                        cxtexture.os.sampler = Some(sampler);
+                    }
                 }
                 
                 let geometry_id = if let Some(geometry_id) = draw_call.geometry_id {geometry_id}
@@ -264,7 +315,11 @@ impl Cx {
         
         for (index, color_texture) in self.passes[pass_id].color_textures.iter().enumerate() {
             let size = pass_size * dpi_factor;
-            self.textures[color_texture.texture.texture_id()].alloc_render(size.x as usize, size.y as usize);
+            let cxtexture = &mut self.textures[color_texture.texture.texture_id()];
+            if cxtexture.alloc_render(size.x as usize, size.y as usize) {
+                // resizing recreates the texture as linear and clamped
+                cxtexture.os.sampler = Some(TextureSampler::default());
+            }
             match color_texture.clear_color {
                 PassClearColor::InitWith(clear_color) => {
                     color_targets[index] = WColorTarget{
@@ -447,6 +502,7 @@ pub struct CxOsDrawShader {
 
 #[derive(Clone, Default)]
 pub struct CxOsTexture {
+    pub sampler: Option<TextureSampler>,
 }
 
 #[derive(Clone, Default)]
@@ -6,7 +6,7 @@ use {
     },
     crate::{
         makepad_live_id::*,
-        makepad_wasm_bridge::{FromWasmMsg},
+        makepad_wasm_bridge::{FromWasmMsg, WasmDataU8},
         midi::*,
         thread::SignalToUI,
         os::web::CxOs,
@@ -14,30 +14,41 @@ use {
 };
 
 pub struct OsMidiOutput {
-    sender: mpsc::Sender<(Option<MidiPortId>, MidiData)>
+    sender: mpsc::Sender<(Option<MidiPortId>, MidiMessage)>
 }
 
-pub struct OsMidiInput(mpsc::Receiver<(MidiPortId, MidiData) >);
+pub struct OsMidiInput(mpsc::Receiver<(MidiPortId, MidiMessage) >);
 
 impl OsMidiInput {
     pub fn receive(&mut self) -> Option<(MidiPortId, MidiData)> {
-        if let Ok((port_id, data)) = self.0.try_recv() {
-            return Some((port_id, data))
+        // skip what doesn't fit in a MidiData, like sysex
+        while let Ok((port_id, message)) = self.0.try_recv() {
+            if let Some(data) = message.to_midi_data() {
+                return Some((port_id, data))
+            }
         }
         None
     }
+    
+    pub fn receive_message(&mut self) -> Option<(MidiPortId, MidiMessage)> {
+        self.0.try_recv().ok()
+    }
 }
 impl OsMidiOutput {
     pub fn send(&self, port_id: Option<MidiPortId>, d: MidiData) {
-        let _ = self.sender.send((port_id, d));
+        self.send_message(port_id, &d.into());
+    }
+    
+    pub fn send_message(&self, port_id: Option<MidiPortId>, message: &MidiMessage) {
+        let _ = self.sender.send((port_id, message.clone()));
         SignalToUI::set_ui_signal();
     }
 }
 
 #[derive(Default)]
 pub struct WebMidiAccess {
-    output_receivers: Vec<mpsc::Receiver<(Option<MidiPortId>, MidiData) >>,
-    input_senders: Vec<mpsc::Sender<(MidiPortId, MidiData) >>,
+    output_receivers: Vec<mpsc::Receiver<(Option<MidiPortId>, MidiMessage) >>,
+    input_senders: Vec<mpsc::Sender<(MidiPortId, MidiMessage) >>,
     change_signal: SignalToUI,
     ports: Vec<WebMidiPort>,
 }
@@ -66,7 +77,8 @@ impl WebMidiAccess {
     }
     
     pub fn create_midi_output(&mut self) -> MidiOutput {
-        let (send, _recv) = mpsc::channel();
+        let (send, recv) = mpsc::channel();
+        self.output_receivers.push(recv);
         MidiOutput(Some(OsMidiOutput {
             sender: send
         }))
@@ -95,10 +107,12 @@ impl WebMidiAccess {
     
     pub fn to_wasm_midi_input_data(&mut self, tw:ToWasmMidiInputData){
         if let Some(port) = self.ports.iter().find(|v| v.uid == tw.uid){
-            let data = MidiData{data:[((tw.data>>16)&0xff) as u8,((tw.data>>8)&0xff) as u8,((tw.data>>0)&0xff) as u8]};
-            self.input_senders.retain(|send|{
-                send.send((port.desc.port_id, data)).is_ok()
-            })
+            // web midi delivers one complete message per event, sysex included
+            if let Some(message) = MidiMessage::from_bytes(&tw.data.into_vec_u8()) {
+                self.input_senders.retain(|send|{
+                    send.send((port.desc.port_id, message.clone())).is_ok()
+                })
+            }
         }
     }
         
@@ -130,12 +144,13 @@ impl WebMidiAccess {
         self.output_receivers.retain( | recv | {
             loop {
                 match recv.try_recv() {
-                    Ok((port_id, d)) => {
+                    Ok((port_id, message)) => {
+                        let bytes = message.to_bytes();
                         for port in ports {
-                            if port_id.is_none() || Some(port.desc.port_id) == port_id {
+                            if port.desc.port_type.is_output() && (port_id.is_none() || Some(port.desc.port_id) == port_id) {
                                 from_wasm.from_wasm(FromWasmSendMidiOutput {
                                     uid: port.uid.clone(),
-                                    data: (d.data[0] as u32) << 16 | (d.data[1] as u32) << 8 | (d.data[2] as u32) << 0
+                                    data: WasmDataU8::from_vec_u8(bytes.clone())
                                 })
                             }
                         }
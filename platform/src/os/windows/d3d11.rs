@@ -16,7 +16,12 @@ use crate::{
         TextureFormat,
         TexturePixel,
         TextureId,
-        CxTexture
+        CxTexture,
+        TextureSampler,
+        TextureFilter,
+        TextureWrap,
+        TextureRect,
+        TextureUpdated,
     },  
     windows::{
         core::{
@@ -75,6 +80,10 @@ use crate::{
                     D3D11_MAP_WRITE_DISCARD,
                     D3D11_QUERY_DESC,
                     D3D11_QUERY_EVENT,
+                    D3D11_SAMPLER_DESC,
+                    D3D11_FILTER,
+                    D3D11_TEXTURE_ADDRESS_MODE,
+                    D3D11_BOX,
                     ID3D11Device,
                     ID3D11DeviceContext,
                     ID3D11RenderTargetView,
@@ -91,6 +100,7 @@ use crate::{
                     D3D11CreateDevice,
                     ID3D11Resource,
                     ID3D11Query,
+                    ID3D11SamplerState,
                 },
                 Direct3D::{
                     Fxc::D3DCompile,
@@ -282,6 +292,9 @@ impl Cx {
                             d3d11_cx.context.PSSetShaderResources(i as u32, None);
                             d3d11_cx.context.VSSetShaderResources(i as u32, None);
                         }
+                        let sampler_state = cxtexture.update_sampler_state(d3d11_cx);
+                        d3d11_cx.context.PSSetSamplers(i as u32, Some(&[Some(sampler_state.clone())]));
+                        d3d11_cx.context.VSSetSamplers(i as u32, Some(&[Some(sampler_state)]));
                     }
                 }
                 //if self.passes[pass_id].debug{
@@ -795,61 +808,102 @@ pub struct CxOsTexture {
     shader_resource_view: Option<ID3D11ShaderResourceView >,
     render_target_view: Option<ID3D11RenderTargetView >,
     depth_stencil_view: Option<ID3D11DepthStencilView >,
+    sampler_state: Option<(TextureSampler, ID3D11SamplerState)>,
+    dynamic: bool,
 }
 
 impl CxTexture {
     
+    fn update_sampler_state(&mut self, d3d11_cx: &D3d11Cx) -> ID3D11SamplerState {
+        let mut sampler = self.sampler_or_default();
+        if self.sampler.is_none() {
+            // the HLSL shaders always declared a repeating sampler, textures without one keep it
+            sampler.wrap_s = TextureWrap::Repeat;
+            sampler.wrap_t = TextureWrap::Repeat;
+        }
+        if let Some((current, sampler_state)) = &self.os.sampler_state {
+            if *current == sampler {
+                return sampler_state.clone()
+            }
+        }
+        // D3D11_FILTER packs the mip, mag and min filters as bits, linear sets them
+        let filter = if sampler.max_anisotropy > 1 {
+            0x55 // D3D11_FILTER_ANISOTROPIC
+        }
+        else {
+            let linear = | filter: Option<TextureFilter>, bit | if let Some(TextureFilter::Linear) = filter {bit} else {0};
+            linear(sampler.mipmap_filter, 0x1) | linear(Some(sampler.mag_filter), 0x4) | linear(Some(sampler.min_filter), 0x10)
+        };
+        let address_mode = | wrap | D3D11_TEXTURE_ADDRESS_MODE(match wrap {
+            TextureWrap::Repeat => 1, // D3D11_TEXTURE_ADDRESS_WRAP
+            TextureWrap::MirroredRepeat => 2, // D3D11_TEXTURE_ADDRESS_MIRROR
+            TextureWrap::ClampToEdge => 3, // D3D11_TEXTURE_ADDRESS_CLAMP
+        });
+        let sampler_desc = D3D11_SAMPLER_DESC {
+            Filter: D3D11_FILTER(filter),
+            AddressU: address_mode(sampler.wrap_s),
+            AddressV: address_mode(sampler.wrap_t),
+            AddressW: address_mode(TextureWrap::ClampToEdge),
+            MipLODBias: 0.0,
+            MaxAnisotropy: (sampler.max_anisotropy as u32).max(1).min(16),
+            ComparisonFunc: D3D11_COMPARISON_ALWAYS,
+            BorderColor: [0.0; 4],
+            MinLOD: 0.0,
+            MaxLOD: if sampler.mipmap_filter.is_some() {f32::MAX} else {0.0},
+        };
+        let mut sampler_state = None;
+        unsafe {d3d11_cx.device.CreateSamplerState(&sampler_desc, Some(&mut sampler_state)).unwrap()};
+        let sampler_state = sampler_state.unwrap();
+        self.os.sampler_state = Some((sampler, sampler_state.clone()));
+        sampler_state
+    }
+    
     pub fn update_vec_texture(
         &mut self,
         d3d11_cx: &D3d11Cx,
     ) {
-        // TODO maybe we can update the data instead of making a new texture?
-        if self.alloc_vec(){}
-        if self.check_updated(){
-            fn get_descs(format: DXGI_FORMAT, width: usize, height: usize, bpp: usize, data: *const std::ffi::c_void)->(D3D11_SUBRESOURCE_DATA,D3D11_TEXTURE2D_DESC) {
-                let sub_data = D3D11_SUBRESOURCE_DATA {
-                    pSysMem: data,
-                    SysMemPitch: (width * bpp) as u32,
-                    SysMemSlicePitch: 0
-                };
-                                            
-                let texture_desc = D3D11_TEXTURE2D_DESC {
-                    Width: width as u32,
-                    Height: height as u32,
-                    MipLevels: 1,
-                    ArraySize: 1,
-                    Format: format,
-                    SampleDesc: DXGI_SAMPLE_DESC {
-                        Count: 1,
-                        Quality: 0
-                    },
-                    Usage: D3D11_USAGE_DEFAULT,
-                    BindFlags: D3D11_BIND_SHADER_RESOURCE.0 as u32,
-                    CPUAccessFlags: 0,
-                    MiscFlags: 0,
-                };
-                (sub_data,texture_desc)
-            }
-            
-            let (sub_data, texture_desc) = match &self.format{
-                TextureFormat::VecBGRAu8_32{width, height, data}=>{
-                    get_descs(DXGI_FORMAT_B8G8R8A8_UNORM, *width, *height, 4, data.as_ptr() as *const _)
-                }
-                TextureFormat::VecRGBAf32{width, height, data}=>{
-                    get_descs(DXGI_FORMAT_R32G32B32A32_FLOAT, *width, *height, 16, data.as_ptr() as *const _)
-                }
-                TextureFormat::VecRu8{width, height, data, ..}=>{
-                    get_descs(DXGI_FORMAT_R8_UNORM, *width, *height, 1, data.as_ptr() as *const _)
-                }
-                TextureFormat::VecRGu8{width, height, data, ..}=>{
-                    get_descs(DXGI_FORMAT_R8G8_UNORM, *width, *height, 1, data.as_ptr() as *const _)
-                }
-                TextureFormat::VecRf32{width, height, data}=>{
-                    get_descs(DXGI_FORMAT_R32_FLOAT, *width, *height, 4, data.as_ptr() as *const _)
-                }
-                _=>panic!()
+        let reallocated = self.alloc_vec();
+        let (width, height) = self.format.vec_width_height().unwrap();
+        let rect = match self.take_updated(){
+            TextureUpdated::Empty => return,
+            TextureUpdated::Partial(rect) => rect,
+            TextureUpdated::Full => TextureRect::new(0, 0, width, height),
+        };
+        let format = match &self.format{
+            TextureFormat::VecBGRAu8_32{..} => DXGI_FORMAT_B8G8R8A8_UNORM,
+            TextureFormat::VecRGBAf32{..} => DXGI_FORMAT_R32G32B32A32_FLOAT,
+            TextureFormat::VecRu8{..} => DXGI_FORMAT_R8_UNORM,
+            TextureFormat::VecRGu8{..} => DXGI_FORMAT_R8G8_UNORM,
+            TextureFormat::VecRf32{..} => DXGI_FORMAT_R32_FLOAT,
+            _=>panic!()
+        };
+        let (data, row_length, texel_size) = self.format.vec_texel_data().unwrap();
+        let row_pitch = row_length * texel_size;
+        // streaming textures are dynamic so we can map them with discard, the driver then hands us
+        // fresh memory while the gpu is still reading the previous frame
+        let dynamic = self.streaming;
+        
+        if reallocated || self.os.texture.is_none() || self.os.dynamic != dynamic{
+            let sub_data = D3D11_SUBRESOURCE_DATA {
+                pSysMem: data.as_ptr() as *const _,
+                SysMemPitch: row_pitch as u32,
+                SysMemSlicePitch: 0
+            };
+            let texture_desc = D3D11_TEXTURE2D_DESC {
+                Width: width as u32,
+                Height: height as u32,
+                MipLevels: 1,
+                ArraySize: 1,
+                Format: format,
+                SampleDesc: DXGI_SAMPLE_DESC {
+                    Count: 1,
+                    Quality: 0
+                },
+                Usage: if dynamic {D3D11_USAGE_DYNAMIC} else {D3D11_USAGE_DEFAULT},
+                BindFlags: D3D11_BIND_SHADER_RESOURCE.0 as u32,
+                CPUAccessFlags: if dynamic {D3D11_CPU_ACCESS_WRITE.0 as u32} else {0},
+                MiscFlags: 0,
             };
-                                        
             let mut texture = None;
             unsafe {d3d11_cx.device.CreateTexture2D(&texture_desc, Some(&sub_data), Some(&mut texture)).unwrap()};
             let resource: ID3D11Resource = texture.clone().unwrap().cast().unwrap();
@@ -857,6 +911,43 @@ impl CxTexture {
             unsafe {d3d11_cx.device.CreateShaderResourceView(&resource, None, Some(&mut shader_resource_view)).unwrap()};
             self.os.texture = texture;
             self.os.shader_resource_view = shader_resource_view;
+            self.os.dynamic = dynamic;
+        }
+        else if dynamic{
+            // a discarded map has no previous contents, so the whole image goes up
+            let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+            let p_mapped : *mut _ = &mut mapped;
+            unsafe {
+                d3d11_cx.context.Map(self.os.texture.as_ref().unwrap(),
+                        0, D3D11_MAP_WRITE_DISCARD, 0, Some(p_mapped)).unwrap();
+                for y in 0..height{
+                    std::ptr::copy_nonoverlapping(
+                        data[y * row_pitch..].as_ptr(),
+                        (mapped.pData as *mut u8).add(y * mapped.RowPitch as usize),
+                        width * texel_size
+                    );
+                }
+                d3d11_cx.context.Unmap(self.os.texture.as_ref().unwrap(), 0);
+            }
+        }
+        else{
+            let dst_box = D3D11_BOX{
+                left: rect.x as u32,
+                top: rect.y as u32,
+                front: 0,
+                right: (rect.x + rect.width) as u32,
+                bottom: (rect.y + rect.height) as u32,
+                back: 1
+            };
+            let offset = rect.y * row_pitch + rect.x * texel_size;
+            unsafe {d3d11_cx.context.UpdateSubresource(
+                self.os.texture.as_ref().unwrap(),
+                0,
+                Some(&dst_box),
+                data[offset..].as_ptr() as *const _,
+                row_pitch as u32,
+                0
+            )};
         }
     }
     
@@ -37,6 +37,13 @@ impl OsMidiOutput {
     pub fn send(&self, port_id: Option<MidiPortId>, d: MidiData) {
         let _ =  self.0.lock().unwrap().event_sender.send(WinRTMidiEvent::SendMidi(port_id, d));
     }
+    
+    /// Messages that don't fit in a `MidiData`, such as SysEx, aren't sent yet on this backend
+    pub fn send_message(&self, port_id: Option<MidiPortId>, message: &MidiMessage) {
+        if let Some(d) = message.to_midi_data() {
+            self.send(port_id, d);
+        }
+    }
 }
 
 impl OsMidiInput {
@@ -46,6 +53,10 @@ impl OsMidiInput {
         }
         None
     }
+    
+    pub fn receive_message(&mut self) -> Option<(MidiPortId, MidiMessage)> {
+        self.receive().map( | (port_id, data) | (port_id, data.into()))
+    }
 }
 
 type InputSenders = Arc<Mutex<Vec<mpsc::Sender<(MidiPortId, MidiData) >> >>;
@@ -0,0 +1,487 @@
+// Renders passes on the CPU through the shader interpreter. This is a reference
+// renderer for headless golden image tests, it follows what the OpenGL backend
+// does: LEQUAL depth testing, premultiplied alpha blending and nested passes
+// rendered into their color textures first.
+use {
+    std::collections::BTreeMap,
+    crate::{
+        makepad_math::*,
+        makepad_live_compiler::LiveError,
+        makepad_shader_compiler::{
+            ValuePtr,
+            DrawShaderFieldKind,
+            shader_ast::Ident,
+            interpret::{ShaderInterpreter, ShaderValue, ShaderSampler, ShaderDerivatives},
+        },
+        cx::Cx,
+        pass::{PassId, PassClearColor, PassClearDepth, CxPassParent},
+        draw_list::DrawListId,
+        draw_shader::DrawShaderInputs,
+        texture::{Texture, TextureId, TextureFormat, TextureSampler, TextureFilter, TextureWrap},
+    }
+};
+
+#[derive(Clone, Debug)]
+pub struct SoftwareImage {
+    pub width: usize,
+    pub height: usize,
+    // premultiplied rgba, top row first
+    pub data: Vec<Vec4>,
+}
+
+impl SoftwareImage {
+    pub fn new(width: usize, height: usize, color: Vec4) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![color; width * height]
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Vec4 {
+        self.data[y * self.width + x]
+    }
+
+    pub fn to_rgba_u8(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.data.len() * 4);
+        for color in &self.data {
+            for c in [color.x, color.y, color.z, color.w] {
+                out.push((c.clamp(0.0, 1.0) * 255.0 + 0.5) as u8);
+            }
+        }
+        out
+    }
+
+    // the largest per channel difference, None if the sizes differ
+    pub fn max_difference(&self, other: &SoftwareImage) -> Option<f32> {
+        if self.width != other.width || self.height != other.height {
+            return None
+        }
+        let mut max = 0.0f32;
+        for (a, b) in self.data.iter().zip(&other.data) {
+            max = max.max((a.x - b.x).abs()).max((a.y - b.y).abs()).max((a.z - b.z).abs()).max((a.w - b.w).abs());
+        }
+        Some(max)
+    }
+
+    fn sample(&self, sampler: &TextureSampler, pos: Vec2) -> Vec4 {
+        sample_texels(sampler, self.width, self.height, pos, | x, y | self.pixel(x, y))
+    }
+}
+
+// filters and wraps like the GPU does with the texture's sampler. There are no mip levels
+// on the CPU so minification can't be told apart and the mag filter is used throughout
+fn sample_texels(sampler: &TextureSampler, width: usize, height: usize, pos: Vec2, fetch: impl Fn(usize, usize) -> Vec4) -> Vec4 {
+    if width == 0 || height == 0 {
+        return Vec4::default()
+    }
+    let wrap = | c: f32, size: usize, wrap: TextureWrap | {
+        let (c, size) = (c as i64, size as i64);
+        (match wrap {
+            TextureWrap::ClampToEdge => c.max(0).min(size - 1),
+            TextureWrap::Repeat => c.rem_euclid(size),
+            TextureWrap::MirroredRepeat => {
+                let c = c.rem_euclid(size * 2);
+                if c < size {c} else {size * 2 - 1 - c}
+            }
+        }) as usize
+    };
+    let texel = | x: f32, y: f32 | fetch(wrap(x, width, sampler.wrap_s), wrap(y, height, sampler.wrap_t));
+    match sampler.mag_filter {
+        TextureFilter::Nearest => {
+            texel((pos.x * width as f32).floor(), (pos.y * height as f32).floor())
+        }
+        TextureFilter::Linear => {
+            let x = pos.x * width as f32 - 0.5;
+            let y = pos.y * height as f32 - 0.5;
+            let (x0, y0) = (x.floor(), y.floor());
+            let (fx, fy) = (x - x0, y - y0);
+            let top = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1.0, y0) * fx;
+            let bottom = texel(x0, y0 + 1.0) * (1.0 - fx) + texel(x0 + 1.0, y0 + 1.0) * fx;
+            top * (1.0 - fy) + bottom * fy
+        }
+    }
+}
+
+fn sample_texture_format(format: &TextureFormat, sampler: &TextureSampler, pos: Vec2) -> Vec4 {
+    fn bgra(v: u32) -> Vec4 {
+        vec4(
+            ((v >> 16) & 0xff) as f32 / 255.0,
+            ((v >> 8) & 0xff) as f32 / 255.0,
+            (v & 0xff) as f32 / 255.0,
+            (v >> 24) as f32 / 255.0
+        )
+    }
+    match format {
+        TextureFormat::VecBGRAu8_32 {width, height, data} |
+        TextureFormat::VecMipBGRAu8_32 {width, height, data, ..} => {
+            sample_texels(sampler, *width, *height, pos, | x, y | bgra(data[y * width + x]))
+        }
+        TextureFormat::VecRGBAf32 {width, height, data} => {
+            sample_texels(sampler, *width, *height, pos, | x, y | {
+                let i = (y * width + x) * 4;
+                vec4(data[i], data[i + 1], data[i + 2], data[i + 3])
+            })
+        }
+        TextureFormat::VecRu8 {width, height, data, unpack_row_length} => {
+            let stride = unpack_row_length.unwrap_or(*width);
+            sample_texels(sampler, *width, *height, pos, | x, y | {
+                vec4(data[y * stride + x] as f32 / 255.0, 0.0, 0.0, 1.0)
+            })
+        }
+        TextureFormat::VecRGu8 {width, height, data, unpack_row_length} => {
+            let stride = unpack_row_length.unwrap_or(*width);
+            sample_texels(sampler, *width, *height, pos, | x, y | {
+                let i = (y * stride + x) * 2;
+                vec4(data[i] as f32 / 255.0, data[i + 1] as f32 / 255.0, 0.0, 1.0)
+            })
+        }
+        TextureFormat::VecRf32 {width, height, data} => {
+            sample_texels(sampler, *width, *height, pos, | x, y | vec4(data[y * width + x], 0.0, 0.0, 1.0))
+        }
+        _ => Vec4::default()
+    }
+}
+
+struct SoftwareSampler<'a> {
+    cx: &'a Cx,
+    texture_slots: &'a [Option<Texture>],
+    targets: &'a [(TextureId, SoftwareImage)],
+}
+
+impl<'a> SoftwareSampler<'a> {
+    fn sample(&self, slot: usize, pos: Vec2, is_rt: bool) -> Vec4 {
+        let texture_id = if let Some(Some(texture)) = self.texture_slots.get(slot) {
+            texture.texture_id()
+        }
+        else {
+            return Vec4::default()
+        };
+        let cxtexture = &self.cx.textures[texture_id];
+        let sampler = cxtexture.sampler_or_default();
+        // render targets are stored bottom up on the GPU, sample2d_rt flips that back
+        if let Some((_, image)) = self.targets.iter().find( | (id, _) | *id == texture_id) {
+            return image.sample(&sampler, if is_rt {pos} else {vec2(pos.x, 1.0 - pos.y)})
+        }
+        sample_texture_format(&cxtexture.format, &sampler, if is_rt {vec2(pos.x, 1.0 - pos.y)} else {pos})
+    }
+}
+
+impl<'a> ShaderSampler for SoftwareSampler<'a> {
+    fn sample2d(&self, texture: usize, pos: Vec2) -> Vec4 {
+        self.sample(texture, pos, false)
+    }
+
+    fn sample2d_rt(&self, texture: usize, pos: Vec2) -> Vec4 {
+        self.sample(texture, pos, true)
+    }
+}
+
+struct SoftwareTarget {
+    image: SoftwareImage,
+    depth: Option<Vec<f32 >>,
+}
+
+struct SoftwareVertex {
+    clip: Vec4,
+    fields: Vec<ShaderValue>,
+}
+
+fn set_inputs(interpreter: &mut ShaderInterpreter, inputs: &DrawShaderInputs, data: &[f32]) {
+    for input in &inputs.inputs {
+        interpreter.set_field(Ident(input.id), ShaderValue::from_slots(&input.ty, &data[input.offset..]));
+    }
+}
+
+fn edge(a: Vec3, b: Vec3, x: f32, y: f32) -> f32 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+fn rasterize_triangle(
+    interpreter: &mut ShaderInterpreter,
+    target: &mut SoftwareTarget,
+    vertices: [&SoftwareVertex; 3],
+    interpolated: &[bool],
+    uses_derivatives: bool,
+) -> Result<(), LiveError> {
+    let width = target.image.width;
+    let height = target.image.height;
+    // to window space, with the first row at the top of the image
+    let mut screen = [Vec3::default(); 3];
+    let mut inv_w = [0.0; 3];
+    for i in 0..3 {
+        let clip = vertices[i].clip;
+        if clip.w <= 0.0 {
+            return Ok(())
+        }
+        inv_w[i] = 1.0 / clip.w;
+        screen[i] = vec3(
+            (clip.x * inv_w[i] + 1.0) * 0.5 * width as f32,
+            (1.0 - clip.y * inv_w[i]) * 0.5 * height as f32,
+            clip.z * inv_w[i]
+        );
+    }
+    // nothing gets culled, so flip clockwise triangles around
+    let vertices = if edge(screen[0], screen[1], screen[2].x, screen[2].y) < 0.0 {
+        screen.swap(1, 2);
+        inv_w.swap(1, 2);
+        [vertices[0], vertices[2], vertices[1]]
+    }
+    else {
+        vertices
+    };
+    let area = edge(screen[0], screen[1], screen[2].x, screen[2].y);
+    if area <= 0.0 {
+        return Ok(())
+    }
+    let edges = [(screen[1], screen[2]), (screen[2], screen[0]), (screen[0], screen[1])];
+    // top-left fill rule so shared edges are drawn exactly once
+    let top_left = edges.map( | (a, b) | (a.y == b.y && b.x > a.x) || b.y < a.y);
+    let weights_at = | x: f32, y: f32 | {
+        let mut w = [0.0; 3];
+        for i in 0..3 {
+            w[i] = edge(edges[i].0, edges[i].1, x, y) / area * inv_w[i];
+        }
+        let sum = w[0] + w[1] + w[2];
+        w.map( | w | w / sum)
+    };
+    let interpolate = | interpreter: &mut ShaderInterpreter, weights: [f32; 3] | {
+        for (index, field) in interpreter.fields.iter_mut().enumerate() {
+            *field = if interpolated[index] {
+                ShaderValue::barycentric([&vertices[0].fields[index], &vertices[1].fields[index], &vertices[2].fields[index]], weights)
+            }
+            else {
+                vertices[0].fields[index].clone()
+            };
+        }
+    };
+
+    let min_x = screen.iter().fold(f32::INFINITY, | m, s | m.min(s.x)).max(0.0) as usize;
+    let max_x = (screen.iter().fold(f32::NEG_INFINITY, | m, s | m.max(s.x)).ceil().max(0.0) as usize).min(width);
+    let min_y = screen.iter().fold(f32::INFINITY, | m, s | m.min(s.y)).max(0.0) as usize;
+    let max_y = (screen.iter().fold(f32::NEG_INFINITY, | m, s | m.max(s.y)).ceil().max(0.0) as usize).min(height);
+
+    for py in min_y..max_y {
+        let y = py as f32 + 0.5;
+        for px in min_x..max_x {
+            let x = px as f32 + 0.5;
+            let mut b = [0.0; 3];
+            let mut inside = true;
+            for i in 0..3 {
+                let e = edge(edges[i].0, edges[i].1, x, y);
+                if e < 0.0 || e == 0.0 && !top_left[i] {
+                    inside = false;
+                    break;
+                }
+                b[i] = e / area;
+            }
+            if !inside {
+                continue;
+            }
+            let z = b[0] * screen[0].z + b[1] * screen[1].z + b[2] * screen[2].z;
+            if !(-1.0..=1.0).contains(&z) {
+                continue;
+            }
+            let depth = z * 0.5 + 0.5;
+            let index = py * width + px;
+            if let Some(depth_buffer) = &target.depth {
+                if depth > depth_buffer[index] {
+                    continue;
+                }
+            }
+            if uses_derivatives {
+                // run the right and the upper neighbour first, GL's y axis points up
+                interpolate(interpreter, weights_at(x + 1.0, y));
+                interpreter.derivatives = ShaderDerivatives::Record(Vec::new());
+                interpreter.pixel()?;
+                let dx = std::mem::replace(&mut interpreter.derivatives, ShaderDerivatives::Record(Vec::new()));
+                interpolate(interpreter, weights_at(x, y - 1.0));
+                interpreter.pixel()?;
+                let dy = std::mem::replace(&mut interpreter.derivatives, ShaderDerivatives::None);
+                if let (ShaderDerivatives::Record(dx), ShaderDerivatives::Record(dy)) = (dx, dy) {
+                    interpreter.derivatives = ShaderDerivatives::Apply {dx, dy};
+                }
+            }
+            interpolate(interpreter, weights_at(x, y));
+            let color = interpreter.pixel()?;
+            interpreter.derivatives = ShaderDerivatives::None;
+
+            let src = vec4(
+                color.x.clamp(0.0, 1.0),
+                color.y.clamp(0.0, 1.0),
+                color.z.clamp(0.0, 1.0),
+                color.w.clamp(0.0, 1.0)
+            );
+            let dst = target.image.data[index];
+            target.image.data[index] = src + dst * (1.0 - src.w);
+            if let Some(depth_buffer) = &mut target.depth {
+                depth_buffer[index] = depth;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Cx {
+    /// Renders a pass, and the passes nested in it, on the CPU with the shader interpreter.
+    pub fn software_render_pass(&mut self, pass_id: PassId) -> SoftwareImage {
+        let mut targets = Vec::new();
+        self.software_render_pass_inner(pass_id, &mut targets)
+    }
+
+    fn software_render_pass_inner(&mut self, pass_id: PassId, targets: &mut Vec<(TextureId, SoftwareImage)>) -> SoftwareImage {
+        // child passes go first so their color textures can be sampled
+        let child_pass_ids: Vec<PassId> = self.passes.id_iter().filter( | id | {
+            matches!(self.passes[*id].parent, CxPassParent::Pass(parent_id) if parent_id == pass_id)
+        }).collect();
+        for child_pass_id in child_pass_ids {
+            let image = self.software_render_pass_inner(child_pass_id, targets);
+            for color_texture in &self.passes[child_pass_id].color_textures {
+                targets.push((color_texture.texture.texture_id(), image.clone()));
+            }
+        }
+
+        let dpi_factor = self.passes[pass_id].dpi_factor.unwrap_or(1.0);
+        let (pass_rect, draw_list_id) = match (self.get_pass_rect(pass_id, dpi_factor), self.passes[pass_id].main_draw_list_id) {
+            (Some(pass_rect), Some(draw_list_id)) => (pass_rect, draw_list_id),
+            _ => return SoftwareImage::new(0, 0, Vec4::default())
+        };
+        self.passes[pass_id].set_matrix(pass_rect.pos, pass_rect.size);
+        self.passes[pass_id].set_dpi_factor(dpi_factor);
+
+        let pass = &self.passes[pass_id];
+        let size = pass_rect.size * dpi_factor;
+        let (width, height) = (size.x as usize, size.y as usize);
+        let clear_color = match pass.color_textures.first().map( | color_texture | &color_texture.clear_color) {
+            Some(PassClearColor::InitWith(color)) | Some(PassClearColor::ClearWith(color)) => *color,
+            None => pass.clear_color
+        };
+        let clear_depth = match pass.clear_depth {
+            PassClearDepth::InitWith(depth) | PassClearDepth::ClearWith(depth) => depth
+        };
+        let mut target = SoftwareTarget {
+            image: SoftwareImage::new(width, height, clear_color),
+            depth: pass.depth_texture.as_ref().map( | _ | vec![clear_depth; width * height]),
+        };
+
+        let mut zbias = 0.0;
+        let zbias_step = pass.zbias_step;
+        self.software_render_view(pass_id, draw_list_id, &mut zbias, zbias_step, &mut target, targets);
+        target.image
+    }
+
+    fn software_render_view(
+        &mut self,
+        pass_id: PassId,
+        draw_list_id: DrawListId,
+        zbias: &mut f32,
+        zbias_step: f32,
+        target: &mut SoftwareTarget,
+        targets: &[(TextureId, SoftwareImage)],
+    ) {
+        let draw_items_len = self.draw_lists[draw_list_id].draw_items.len();
+        self.draw_lists[draw_list_id].uniform_view_transform(&Mat4::identity());
+
+        for draw_item_id in 0..draw_items_len {
+            if let Some(sub_list_id) = self.draw_lists[draw_list_id].draw_items[draw_item_id].kind.sub_list() {
+                self.software_render_view(pass_id, sub_list_id, zbias, zbias_step, target, targets);
+                continue;
+            }
+            let draw_item = &mut self.draw_lists[draw_list_id].draw_items[draw_item_id];
+            if let Some(draw_call) = draw_item.kind.draw_call_mut() {
+                draw_call.draw_uniforms.set_zbias(*zbias);
+                *zbias += zbias_step;
+                self.software_render_draw_call(pass_id, draw_list_id, draw_item_id, target, targets);
+            }
+        }
+    }
+
+    fn software_render_draw_call(
+        &self,
+        pass_id: PassId,
+        draw_list_id: DrawListId,
+        draw_item_id: usize,
+        target: &mut SoftwareTarget,
+        targets: &[(TextureId, SoftwareImage)],
+    ) {
+        let draw_list = &self.draw_lists[draw_list_id];
+        let draw_item = &draw_list.draw_items[draw_item_id];
+        let draw_call = draw_item.kind.draw_call().unwrap();
+        let mapping = &self.draw_shaders.shaders[draw_call.draw_shader.draw_shader_id].mapping;
+
+        let draw_shader_def = if let Some(draw_shader_def) = self.shader_registry.draw_shader_defs.get(&draw_call.draw_shader.draw_shader_ptr) {
+            draw_shader_def
+        }
+        else {
+            return
+        };
+        let geometry = if let Some(geometry_id) = draw_call.geometry_id {
+            &self.geometries[geometry_id]
+        }
+        else {
+            return
+        };
+        if mapping.instances.total_slots == 0 || mapping.geometries.total_slots == 0 {
+            return
+        }
+
+        let live_values: BTreeMap<ValuePtr, ShaderValue> = mapping.live_uniforms.inputs.iter().map( | input | {
+            (ValuePtr(input.live_ptr.unwrap()), ShaderValue::from_slots(&input.ty, &mapping.live_uniforms_buf[input.offset..]))
+        }).collect();
+        let sampler = SoftwareSampler {
+            cx: self,
+            texture_slots: &draw_call.texture_slots,
+            targets
+        };
+        let mut interpreter = ShaderInterpreter::new(
+            &self.shader_registry,
+            draw_shader_def,
+            Some(&mapping.const_table),
+            &live_values,
+            &sampler
+        );
+        set_inputs(&mut interpreter, &mapping.pass_uniforms, self.passes[pass_id].pass_uniforms.as_slice());
+        set_inputs(&mut interpreter, &mapping.view_uniforms, draw_list.draw_list_uniforms.as_slice());
+        set_inputs(&mut interpreter, &mapping.draw_uniforms, draw_call.draw_uniforms.as_slice());
+        set_inputs(&mut interpreter, &mapping.user_uniforms, &draw_call.user_uniforms);
+
+        // these are the fields that reach the pixel shader as interpolated varyings
+        let interpolated: Vec<bool> = draw_shader_def.fields.iter().map( | field | {
+            matches!(field.kind, DrawShaderFieldKind::Varying {..} | DrawShaderFieldKind::Geometry {..})
+        }).collect();
+        let uses_derivatives = interpreter.uses_derivatives();
+
+        for instance in draw_item.instances.as_ref().unwrap().chunks_exact(mapping.instances.total_slots) {
+            set_inputs(&mut interpreter, &mapping.instances, instance);
+            let instance_fields = interpreter.fields.clone();
+            let vertices: Result<Vec<SoftwareVertex>, LiveError> = geometry.vertices.chunks_exact(mapping.geometries.total_slots).map( | vertex | {
+                interpreter.fields.clone_from(&instance_fields);
+                set_inputs(&mut interpreter, &mapping.geometries, vertex);
+                let clip = interpreter.vertex()?;
+                Ok(SoftwareVertex {clip, fields: interpreter.fields.clone()})
+            }).collect();
+            let vertices = match vertices {
+                Ok(vertices) => vertices,
+                Err(err) => return self.software_render_error(err)
+            };
+            for triangle in geometry.indices.chunks_exact(3) {
+                let vertex = | index: u32 | &vertices[index as usize];
+                if let Err(err) = rasterize_triangle(
+                    &mut interpreter,
+                    target,
+                    [vertex(triangle[0]), vertex(triangle[1]), vertex(triangle[2])],
+                    &interpolated,
+                    uses_derivatives
+                ) {
+                    return self.software_render_error(err)
+                }
+            }
+        }
+    }
+
+    // a shader the interpreter can't run skips the rest of its draw call instead of taking the renderer down
+    fn software_render_error(&self, err: LiveError) {
+        let live_registry = self.live_registry.borrow();
+        error!("software render skipped a draw call: {}", live_registry.live_error_to_live_file_error(err));
+    }
+}
@@ -77,6 +77,53 @@ impl TextureSize{
 }
 
 
+/// A region of a texture in texels, used to upload only the part of a Vec texture that changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextureRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl TextureRect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {x, y, width, height}
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+    
+    pub fn union(&self, other: &TextureRect) -> TextureRect {
+        if self.is_empty() {
+            return *other
+        }
+        if other.is_empty() {
+            return *self
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        TextureRect {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+    
+    pub fn clip(&self, width: usize, height: usize) -> TextureRect {
+        let x = self.x.min(width);
+        let y = self.y.min(height);
+        TextureRect {
+            x,
+            y,
+            width: (self.x + self.width).min(width) - x,
+            height: (self.y + self.height).min(height) - y,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum TextureFormat {
     Unknown,
@@ -103,10 +150,27 @@ pub(crate) struct TextureAlloc{
     pub height: usize,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextureUpdated{
+    Empty,
+    Partial(TextureRect),
+    Full,
+}
+
+impl TextureUpdated{
+    fn merge(self, other:TextureUpdated)->TextureUpdated{
+        match (self, other){
+            (Self::Full, _) | (_, Self::Full) => Self::Full,
+            (Self::Empty, other) | (other, Self::Empty) => other,
+            (Self::Partial(a), Self::Partial(b)) => Self::Partial(a.union(&b)),
+        }
+    }
+}
+
 #[allow(unused)]    
 #[derive(Clone, Debug)]
 pub enum TextureCategory{
-    Vec{updated:bool},
+    Vec{updated:TextureUpdated},
     Render{initial:bool},
     DepthBuffer{initial:bool},
     Shared{initial:bool},
@@ -140,34 +204,40 @@ pub(crate) enum TexturePixel{
 }
 
 impl CxTexture{
-    pub(crate) fn set_updated(&mut self, up:bool){
+    pub(crate) fn set_updated(&mut self, up:TextureUpdated){
         if let Some(alloc) = &mut self.alloc{
             if let TextureCategory::Vec{updated} = &mut alloc.category{
-                *updated = up
+                *updated = updated.merge(up)
             }
         }
     }
     
-    pub(crate) fn check_updated(&mut self)->bool{
+    pub(crate) fn take_updated(&mut self)->TextureUpdated{
         if let Some(alloc) = &mut self.alloc{
             if let TextureCategory::Vec{updated} = &mut alloc.category{
-                let u = *updated;
-                *updated = false;
-                if u{ // check our buffer sizes
+                let u = std::mem::replace(updated, TextureUpdated::Empty);
+                if u != TextureUpdated::Empty{ // check our buffer sizes
                     match &self.format{
                         TextureFormat::VecBGRAu8_32{width, height, data}=>{
                             if width * height != data.len(){
                                 error!("Texture buffer size incorrect {}*{} != {}", width, height, data.len());
-                                return false
+                                return TextureUpdated::Empty
                             }
                         }
                         _=>()
                     }
                 }
+                if let TextureUpdated::Partial(rect) = u{
+                    let rect = rect.clip(alloc.width, alloc.height);
+                    if rect.is_empty(){
+                        return TextureUpdated::Empty
+                    }
+                    return TextureUpdated::Partial(rect)
+                }
                 return u
             }
         }
-        false
+        TextureUpdated::Empty
     }
     
     pub fn set_initial(&mut self, init:bool){
@@ -242,6 +312,24 @@ impl CxTexture{
         false
     }
 
+    // textures without a sampler keep what the backends always used for them
+    pub(crate) fn sampler_or_default(&self)->TextureSampler{
+        if let Some(sampler) = self.sampler{
+            return sampler
+        }
+        match &self.format{
+            TextureFormat::VecMipBGRAu8_32{..}=>TextureSampler{
+                mipmap_filter: Some(TextureFilter::Linear),
+                ..Default::default()
+            },
+            TextureFormat::RenderBGRAu8{..} |
+            TextureFormat::RenderRGBAf16{..} |
+            TextureFormat::RenderRGBAf32{..} |
+            TextureFormat::SharedBGRAu8{..}=>TextureSampler::nearest(),
+            _=>TextureSampler::default()
+        }
+    }
+    
     #[cfg(any(target_os = "android", target_os = "linux"))]
     #[allow(unused)]
     pub(crate) fn alloc_video(&mut self)->bool{
@@ -310,43 +398,60 @@ impl TextureFormat{
         }
     }
     
+    // the texel data of a Vec format as bytes, with the row length in texels and the size of a texel
+    #[allow(unused)]
+    pub(crate) fn vec_texel_data(&self)->Option<(&[u8], usize, usize)>{
+        fn as_bytes<T>(data:&[T])->&[u8]{
+            unsafe{std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data))}
+        }
+        match self{
+            Self::VecBGRAu8_32{width, data, .. }=>Some((as_bytes(data), *width, 4)),
+            Self::VecMipBGRAu8_32{width, data, ..}=>Some((as_bytes(data), *width, 4)),
+            Self::VecRGBAf32{width, data, ..}=>Some((as_bytes(data), *width, 16)),
+            Self::VecRu8{width, data, unpack_row_length, ..}=>Some((as_bytes(data), unpack_row_length.unwrap_or(*width), 1)),
+            Self::VecRGu8{width, data, unpack_row_length, ..}=>Some((as_bytes(data), unpack_row_length.unwrap_or(*width), 2)),
+            Self::VecRf32{width, data, ..}=>Some((as_bytes(data), *width, 4)),
+            _=>None
+        }
+    }
+    
     pub(crate) fn as_vec_alloc(&self)->Option<TextureAlloc>{
         match self{
             Self::VecBGRAu8_32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::BGRAu8,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             Self::VecMipBGRAu8_32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::BGRAu8,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             Self::VecRGBAf32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::RGBAf32,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             Self::VecRu8{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::Ru8,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             Self::VecRGu8{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::RGu8,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             Self::VecRf32{width,height,..}=>Some(TextureAlloc{
                 width:*width,
                 height:*height,
                 pixel:TexturePixel::Rf32,
-                category: TextureCategory::Vec{updated:true}
+                category: TextureCategory::Vec{updated:TextureUpdated::Full}
             }),
             _=>None
         }
@@ -441,6 +546,66 @@ impl TextureFormat{
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextureWrap {
+    ClampToEdge,
+    Repeat,
+    MirroredRepeat,
+}
+
+/// How a texture is filtered and addressed when a shader samples it.
+/// Textures without a sampler keep the backend defaults: linear and clamped (repeating on D3D11),
+/// trilinear for `VecMipBGRAu8_32` and nearest for render targets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureSampler {
+    pub min_filter: TextureFilter,
+    pub mag_filter: TextureFilter,
+    /// Filter between mip levels, `None` only samples the base level.
+    /// Ignored for textures that have no mip levels.
+    pub mipmap_filter: Option<TextureFilter>,
+    pub wrap_s: TextureWrap,
+    pub wrap_t: TextureWrap,
+    /// 1 disables anisotropic filtering, backends clamp it to what the device supports
+    pub max_anisotropy: u8,
+}
+
+impl Default for TextureSampler {
+    fn default() -> Self {
+        Self {
+            min_filter: TextureFilter::Linear,
+            mag_filter: TextureFilter::Linear,
+            mipmap_filter: None,
+            wrap_s: TextureWrap::ClampToEdge,
+            wrap_t: TextureWrap::ClampToEdge,
+            max_anisotropy: 1,
+        }
+    }
+}
+
+impl TextureSampler {
+    pub fn nearest() -> Self {
+        Self {
+            min_filter: TextureFilter::Nearest,
+            mag_filter: TextureFilter::Nearest,
+            ..Default::default()
+        }
+    }
+    
+    pub fn with_wrap(self, wrap: TextureWrap) -> Self {
+        Self {
+            wrap_s: wrap,
+            wrap_t: wrap,
+            ..self
+        }
+    }
+}
+
 impl Default for TextureFormat {
     fn default() -> Self {
         TextureFormat::Unknown
@@ -461,12 +626,20 @@ impl Texture {
         &mut cx.textures[self.texture_id()].format
     }
     
+    pub fn set_sampler(&self, cx: &mut Cx, sampler: TextureSampler) {
+        cx.textures[self.texture_id()].sampler = Some(sampler);
+    }
+    
+    pub fn get_sampler(&self, cx: &Cx) -> Option<TextureSampler> {
+        cx.textures[self.texture_id()].sampler
+    }
+    
     pub fn swap_vec_u32(&self, cx: &mut Cx, image: &mut Vec<u32>) {
         let cxtexture = &mut cx.textures[self.texture_id()];
         match &mut cxtexture.format{
             TextureFormat::VecBGRAu8_32{data,..} => {
                 std::mem::swap(data, image);
-                cxtexture.set_updated(true);
+                cxtexture.set_updated(TextureUpdated::Full);
             }
             _=>{
                 panic!("Not the correct texture desc for u32 image buffer")
@@ -479,7 +652,7 @@ impl Texture {
         match &mut cxtexture.format{
             TextureFormat::VecRu8{data,..} | TextureFormat::VecRGu8 { data, ..} => {
                 std::mem::swap(data, image);
-                cxtexture.set_updated(true);
+                cxtexture.set_updated(TextureUpdated::Full);
             },
             _=>{
                 panic!("Not the correct texture desc for u8 image buffer")
@@ -492,22 +665,171 @@ impl Texture {
         match &mut cxtexture.format{
             TextureFormat::VecRf32{data,..} => {
                 std::mem::swap(data, image);
-                cxtexture.set_updated(true);
+                cxtexture.set_updated(TextureUpdated::Full);
             }
             TextureFormat::VecRGBAf32{data,..} => {
                 std::mem::swap(data, image);
-                cxtexture.set_updated(true);
+                cxtexture.set_updated(TextureUpdated::Full);
+            }
+            _=>{
+                panic!("Not the correct texture desc for f32 image buffer")
+            }
+        }
+    }
+    
+    /// Marks a region of a Vec texture as changed after writing to its data through `get_format`,
+    /// so the next upload only sends that region.
+    pub fn set_dirty_rect(&self, cx: &mut Cx, rect: TextureRect) {
+        let cxtexture = &mut cx.textures[self.texture_id()];
+        if !cxtexture.format.is_vec(){
+            panic!("Dirty rects are only supported on Vec textures")
+        }
+        cxtexture.set_updated(TextureUpdated::Partial(rect));
+    }
+    
+    /// Copies a tightly packed image into a region of a Vec texture and marks that region dirty.
+    pub fn update_sub_image_u32(&self, cx: &mut Cx, rect: TextureRect, image: &[u32]) {
+        let cxtexture = &mut cx.textures[self.texture_id()];
+        match &mut cxtexture.format{
+            TextureFormat::VecBGRAu8_32{width, height, data} |
+            TextureFormat::VecMipBGRAu8_32{width, height, data, ..} => {
+                copy_sub_image(data, *width, *height, *width, 1, rect, image);
+                cxtexture.set_updated(TextureUpdated::Partial(rect));
+            }
+            _=>{
+                panic!("Not the correct texture desc for u32 image buffer")
+            }
+        }
+    }
+    
+    pub fn update_sub_image_u8(&self, cx: &mut Cx, rect: TextureRect, image: &[u8]) {
+        let cxtexture = &mut cx.textures[self.texture_id()];
+        match &mut cxtexture.format{
+            TextureFormat::VecRu8{width, height, data, unpack_row_length} => {
+                copy_sub_image(data, *width, *height, unpack_row_length.unwrap_or(*width), 1, rect, image);
+                cxtexture.set_updated(TextureUpdated::Partial(rect));
+            }
+            TextureFormat::VecRGu8{width, height, data, unpack_row_length} => {
+                copy_sub_image(data, *width, *height, unpack_row_length.unwrap_or(*width), 2, rect, image);
+                cxtexture.set_updated(TextureUpdated::Partial(rect));
+            }
+            _=>{
+                panic!("Not the correct texture desc for u8 image buffer")
+            }
+        }
+    }
+    
+    pub fn update_sub_image_f32(&self, cx: &mut Cx, rect: TextureRect, image: &[f32]) {
+        let cxtexture = &mut cx.textures[self.texture_id()];
+        match &mut cxtexture.format{
+            TextureFormat::VecRf32{width, height, data} => {
+                copy_sub_image(data, *width, *height, *width, 1, rect, image);
+                cxtexture.set_updated(TextureUpdated::Partial(rect));
+            }
+            TextureFormat::VecRGBAf32{width, height, data} => {
+                copy_sub_image(data, *width, *height, *width, 4, rect, image);
+                cxtexture.set_updated(TextureUpdated::Partial(rect));
             }
             _=>{
                 panic!("Not the correct texture desc for f32 image buffer")
             }
         }
     }
+    
+    /// Streaming textures are updated every frame, backends double buffer their uploads
+    /// so writing the next frame doesn't wait on the GPU still reading the previous one.
+    pub fn set_streaming(&self, cx: &mut Cx, streaming: bool) {
+        cx.textures[self.texture_id()].streaming = streaming;
+    }
+}
+
+// copies a tightly packed image of rect size into texture data with the given row stride, in texels
+fn copy_sub_image<T:Copy>(data:&mut [T], width:usize, height:usize, stride:usize, channels:usize, rect:TextureRect, image:&[T]){
+    if image.len() < rect.width * rect.height * channels{
+        error!("Sub image buffer size incorrect {}*{}*{} > {}", rect.width, rect.height, channels, image.len());
+        return
+    }
+    let clipped = rect.clip(width, height);
+    for row in 0..clipped.height{
+        let src = (row * rect.width) * channels;
+        let dst = ((clipped.y + row) * stride + clipped.x) * channels;
+        let len = clipped.width * channels;
+        if dst + len > data.len(){
+            error!("Texture buffer size incorrect {} < {}", data.len(), dst + len);
+            return
+        }
+        data[dst..dst + len].copy_from_slice(&image[src..src + len]);
+    }
 }
 
 #[derive(Default)]
 pub struct CxTexture {
     pub (crate) format: TextureFormat,
     pub (crate) alloc: Option<TextureAlloc>,
+    pub (crate) sampler: Option<TextureSampler>,
+    pub (crate) streaming: bool,
     pub os: CxOsTexture,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_union() {
+        let a = TextureRect::new(2, 3, 4, 5);
+        let b = TextureRect::new(10, 1, 2, 2);
+        assert_eq!(a.union(&b), TextureRect::new(2, 1, 10, 7));
+        assert_eq!(b.union(&a), a.union(&b));
+        // touching rects merge into one
+        assert_eq!(a.union(&TextureRect::new(6, 3, 1, 5)), TextureRect::new(2, 3, 5, 5));
+        // a rect inside the other changes nothing
+        assert_eq!(a.union(&TextureRect::new(3, 4, 1, 1)), a);
+        // empty rects don't stretch the union to their position
+        assert_eq!(a.union(&TextureRect::new(0, 0, 0, 9)), a);
+        assert_eq!(TextureRect::default().union(&b), b);
+    }
+
+    #[test]
+    fn rect_clip() {
+        let rect = TextureRect::new(6, 6, 8, 2);
+        assert_eq!(rect.clip(10, 10), TextureRect::new(6, 6, 4, 2));
+        assert_eq!(rect.clip(100, 100), rect);
+        let outside = TextureRect::new(12, 2, 3, 3).clip(10, 10);
+        assert_eq!(outside, TextureRect::new(10, 2, 0, 3));
+        assert!(outside.is_empty());
+    }
+
+    #[test]
+    fn copy_sub_image_rows() {
+        let mut data = vec![0u8; 4 * 3];
+        copy_sub_image(&mut data, 4, 3, 4, 1, TextureRect::new(1, 1, 2, 2), &[1, 2, 3, 4]);
+        assert_eq!(data, [
+            0, 0, 0, 0,
+            0, 1, 2, 0,
+            0, 3, 4, 0,
+        ]);
+    }
+
+    #[test]
+    fn copy_sub_image_stride_and_channels() {
+        // two channels and a row stride of 3 texels for a 2 texel wide texture
+        let mut data = vec![0u8; 3 * 2 * 2];
+        copy_sub_image(&mut data, 2, 2, 3, 2, TextureRect::new(1, 0, 1, 2), &[1, 2, 3, 4]);
+        assert_eq!(data, [
+            0, 0, 1, 2, 0, 0,
+            0, 0, 3, 4, 0, 0,
+        ]);
+    }
+
+    #[test]
+    fn copy_sub_image_clips_to_texture() {
+        let mut data = vec![0u32; 3 * 3];
+        // only the top left texel of the 2x2 image lands inside
+        copy_sub_image(&mut data, 3, 3, 3, 1, TextureRect::new(2, 2, 2, 2), &[7, 8, 9, 10]);
+        assert_eq!(data, [0, 0, 0, 0, 0, 0, 0, 0, 7]);
+        // an image smaller than its rect is refused
+        copy_sub_image(&mut data, 3, 3, 3, 1, TextureRect::new(0, 0, 2, 2), &[1, 2, 3]);
+        assert_eq!(data, [0, 0, 0, 0, 0, 0, 0, 0, 7]);
+    }
+}
@@ -1,6 +1,7 @@
 use proc_macro::{TokenStream};
 
 use makepad_micro_proc_macro::{TokenBuilder, TokenParser, error};
+use makepad_live_id::LiveId;
 
 pub fn derive_widget_impl(input: TokenStream) ->  TokenStream {
     let mut out = TokenStream::new();
@@ -424,3 +425,82 @@ pub fn derive_widget_set_impl(input: TokenStream) -> TokenStream {
     parser.unexpected()
 }
 
+
+pub fn derive_data_model_impl(input: TokenStream) -> TokenStream {
+    let mut tb = TokenBuilder::new();
+    let mut parser = TokenParser::new(input);
+    let _main_attribs = parser.eat_attributes();
+    parser.eat_ident("pub");
+    if parser.eat_ident("struct") {
+        let struct_name = parser.expect_any_ident().unwrap();
+        let generic = parser.eat_generic();
+        let types = parser.eat_all_types();
+        let where_clause = parser.eat_where_clause(None);
+        
+        let fields = if let Some(_types) = types {
+            return error("DataModel needs a struct with named fields")
+        }
+        else if let Some(fields) = parser.eat_all_struct_fields() {
+            fields
+        }
+        else {
+            return error("Unexpected field form")
+        };
+        // #[rust] fields stay out of the data
+        let fields: Vec<_> = fields.iter().filter( | field | !field.attrs.iter().any( | a | a.name == "rust")).collect();
+        
+        tb.add("impl").stream(generic.clone());
+        tb.add("DataModel for").ident(&struct_name).stream(generic.clone()).stream(where_clause.clone()).add("{");
+        
+        tb.add("    fn data_write(&self, nodes: &mut LiveNodeVec) {");
+        for field in &fields {
+            tb.add("        DataField::field_write(&self.").ident(&field.name).add(", LiveId(").suf_u64(LiveId::from_str(&field.name).0).add("), nodes);");
+        }
+        tb.add("    }");
+        
+        tb.add("    fn data_read(&mut self, nodes: &[LiveNode], index: usize) -> bool {");
+        tb.add("        let mut changed = false;");
+        for field in &fields {
+            tb.add("        if let Some(index) = nodes.child_by_name(index, LiveId(").suf_u64(LiveId::from_str(&field.name).0).add(").as_field()) {");
+            tb.add("            changed = DataField::field_read(&mut self.").ident(&field.name).add(", nodes, index) || changed;");
+            tb.add("        }");
+        }
+        tb.add("        changed");
+        tb.add("    }");
+        
+        tb.add("    fn data_validate(&self, path: &mut Vec<LiveId>, errors: &mut Vec<DataError>) {");
+        for field in &fields {
+            tb.add("        path.push(LiveId(").suf_u64(LiveId::from_str(&field.name).0).add("));");
+            tb.add("        DataField::field_validate(&self.").ident(&field.name).add(", path, errors);");
+            for attr in field.attrs.iter().filter( | a | a.name == "validate") {
+                let Some(validator) = &attr.args else {
+                    return error("validate needs a function, as in #[validate(not_empty)]")
+                };
+                tb.add("        if let Err(message) = ").stream(Some(validator.clone())).add("(&self.").ident(&field.name).add(") {");
+                tb.add("            errors.push(DataError {path: path.clone(), message});");
+                tb.add("        }");
+            }
+            tb.add("        path.pop();");
+        }
+        tb.add("    }");
+        tb.add("}");
+        
+        // so the struct can sit in a field or a Vec of another model
+        tb.add("impl").stream(generic.clone());
+        tb.add("DataField for").ident(&struct_name).stream(generic).stream(where_clause).add("{");
+        tb.add("    fn field_write(&self, id: LiveId, nodes: &mut LiveNodeVec) {");
+        tb.add("        nodes.open_object(id);");
+        tb.add("        DataModel::data_write(self, nodes);");
+        tb.add("        nodes.close();");
+        tb.add("    }");
+        tb.add("    fn field_read(&mut self, nodes: &[LiveNode], index: usize) -> bool {");
+        tb.add("        DataModel::data_read(self, nodes, index)");
+        tb.add("    }");
+        tb.add("    fn field_validate(&self, path: &mut Vec<LiveId>, errors: &mut Vec<DataError>) {");
+        tb.add("        DataModel::data_validate(self, path, errors)");
+        tb.add("    }");
+        tb.add("}");
+        return tb.end();
+    }
+    parser.unexpected()
+}
@@ -51,3 +51,11 @@ pub fn derive_widget_register(input: TokenStream) -> TokenStream {
 pub fn derive_widget_set(input: TokenStream) -> TokenStream {
     derive_widget_set_impl(input)
 }
+
+#[proc_macro_derive(DataModel, attributes(
+    validate,
+    rust
+))]
+pub fn derive_data_model(input: TokenStream) -> TokenStream {
+    derive_data_model_impl(input)
+}
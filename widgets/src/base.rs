@@ -9,8 +9,12 @@ live_design!{
     import crate::window::WindowBase;
     import crate::multi_window::MultiWindowBase;
     import crate::drop_down::DropDownBase;
+    import crate::date_picker::DatePickerBase;
+    import crate::time_picker::TimePickerBase;
     import crate::file_tree::FileTreeBase;
     import crate::file_tree::FileTreeNodeBase;
+    import crate::tree_view::TreeViewBase;
+    import crate::tree_view::TreeViewRowBase;
     import crate::fold_button::FoldButtonBase;
     import crate::fold_header::FoldHeaderBase;
     import crate::image::ImageBase;
@@ -23,6 +27,8 @@ live_design!{
     import crate::link_label::LinkLabelBase;
     import crate::portal_list::PortalListBase;
     import crate::flat_list::FlatListBase;
+    import crate::data_grid::DataGridBase;
+    import crate::data_grid::DataGridRowBase;
     import crate::scroll_bars::ScrollBarsBase;
     import crate::view::ViewBase;
     import crate::nav_control::NavControlBase;
@@ -33,6 +39,7 @@ live_design!{
     import crate::scroll_bars::ScrollBarsBase;
     import crate::slide_panel::SlidePanelBase;
     import crate::slider::SliderBase;
+    import crate::number_input::NumberInputBase;
     import crate::slides_view::SlidesViewBase;
     import crate::splitter::SplitterBase;
     import crate::tab::TabBase;
@@ -909,6 +916,8 @@ live_design!{
     WindowBase = <WindowBase> {}
     DesktopButtonBase = <DesktopButtonBase> {}
     DropDownBase = <DropDownBase> {}
+    DatePickerBase = <DatePickerBase> {}
+    TimePickerBase = <TimePickerBase> {}
     FileTreeBase = <FileTreeBase> {}
     FileTreeNodeBase = <FileTreeNodeBase> {}
     FoldButtonBase = <FoldButtonBase> {}
@@ -921,6 +930,10 @@ live_design!{
     LinkLabelBase = <LinkLabelBase> {}
     PortalListBase = <PortalListBase> {}
     FlatListBase = <FlatListBase>{}
+    DataGridBase = <DataGridBase> {}
+    DataGridRowBase = <DataGridRowBase> {}
+    TreeViewBase = <TreeViewBase> {}
+    TreeViewRowBase = <TreeViewRowBase> {}
     NavControlBase = <NavControlBase> {}
     PopupMenuBase = <PopupMenuBase> {}
     PopupMenuItemBase = <PopupMenuItemBase> {}
@@ -929,6 +942,7 @@ live_design!{
     ScrollBarsBase = <ScrollBarsBase> {}
     SlidePanelBase = <SlidePanelBase> {}   
     SliderBase = <SliderBase>{}
+    NumberInputBase = <NumberInputBase>{}
     SlidesViewBase = <SlidesViewBase>{}
     SplitterBase = <SplitterBase>{}
     TabBase = <TabBase>{}
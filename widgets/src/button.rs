@@ -36,8 +36,16 @@ pub struct Button {
     #[live(true)]
     grab_key_focus: bool,
 
+    /// Position in the Tab order, 0 keeps draw order and a negative index leaves the button out
+    #[live]
+    tab_index: i64,
+
     #[live]
     pub text: RcStringMut,
+
+    /// Set by a Return or Space press, only the release that follows it clicks
+    #[rust]
+    key_pressed: bool,
 }
 
 impl Widget for Button {
@@ -61,6 +69,15 @@ impl Widget for Button {
             Hit::FingerHoverOut(_) => {
                 self.animator_play(cx, id!(hover.off));
             }
+            Hit::Trigger(te) if te.0.iter().any(|t| t.id == live_id!(nav_focus)) => {
+                self.animator_play(cx, id!(nav_focus.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animator_play(cx, id!(nav_focus.off));
+                self.handle_key_focus_lost(cx, &scope.path);
+            }
+            Hit::KeyDown(ke) => self.handle_key_down(cx, &scope.path, &ke),
+            Hit::KeyUp(ke) => self.handle_key_up(cx, &scope.path, &ke),
             Hit::FingerUp(fe) => {
                 if fe.is_over {
                     cx.widget_action(uid, &scope.path, ButtonAction::Clicked(fe.modifiers));
@@ -85,8 +102,17 @@ impl Widget for Button {
         self.draw_text
             .draw_walk(cx, self.label_walk, Align::default(), self.text.as_ref());
         self.draw_bg.end(cx);
+        cx.add_nav_stop_with_tab_index(self.draw_bg.area(), NavRole::Button, self.tab_index);
         DrawStep::done()
     }
+    
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::Button, self.text.as_ref());
+        node.state.focusable = self.tab_index >= 0;
+        node.state.focused = cx.has_key_focus(self.draw_bg.area());
+        node.rect = self.draw_bg.area().rect(cx);
+        Some(node)
+    }
 
     fn text(&self) -> String {
         self.text.as_ref().to_string()
@@ -98,6 +124,33 @@ impl Widget for Button {
 }
 
 impl Button {
+    fn handle_key_down(&mut self, cx: &mut Cx, path: &HeapLiveIdPath, ke: &KeyEvent) {
+        if ke.is_repeat || !matches!(ke.key_code, KeyCode::ReturnKey | KeyCode::Space) {
+            return
+        }
+        self.key_pressed = true;
+        cx.widget_action(self.widget_uid(), path, ButtonAction::Pressed(ke.modifiers));
+        self.animator_play(cx, id!(hover.pressed));
+    }
+
+    fn handle_key_up(&mut self, cx: &mut Cx, path: &HeapLiveIdPath, ke: &KeyEvent) {
+        // a key pressed before the focus got here isn't ours to release
+        if !self.key_pressed || !matches!(ke.key_code, KeyCode::ReturnKey | KeyCode::Space) {
+            return
+        }
+        self.key_pressed = false;
+        cx.widget_action(self.widget_uid(), path, ButtonAction::Clicked(ke.modifiers));
+        cx.widget_action(self.widget_uid(), path, ButtonAction::Released(ke.modifiers));
+        self.animator_play(cx, id!(hover.off));
+    }
+
+    fn handle_key_focus_lost(&mut self, cx: &mut Cx, path: &HeapLiveIdPath) {
+        if self.key_pressed {
+            self.key_pressed = false;
+            cx.widget_action(self.widget_uid(), path, ButtonAction::Released(KeyModifiers::default()));
+            self.animator_play(cx, id!(hover.off));
+        }
+    }
         
     pub fn draw_button(&mut self, cx: &mut Cx2d, label:&str) {
         self.draw_bg.begin(cx, self.walk, self.layout);
@@ -105,6 +158,7 @@ impl Button {
         self.draw_text
         .draw_walk(cx, self.label_walk, Align::default(), label);
         self.draw_bg.end(cx);
+        cx.add_nav_stop_with_tab_index(self.draw_bg.area(), NavRole::Button, self.tab_index);
     }
     
     
@@ -227,3 +281,54 @@ impl ButtonSet {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_button(f: impl FnOnce(&mut Cx, &mut Button)) {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let mut button = Button::new(&mut cx);
+        f(&mut cx, &mut button);
+    }
+
+    fn key(key_code: KeyCode) -> KeyEvent {
+        KeyEvent {key_code, is_repeat: false, modifiers: KeyModifiers::default(), time: 0.0}
+    }
+
+    #[test]
+    fn key_release_clicks_only_after_a_press() {
+        with_button( | cx, button | {
+            let path = HeapLiveIdPath::default();
+            // the press went to another widget, the release alone does nothing
+            let actions = cx.capture_actions( | cx | button.handle_key_up(cx, &path, &key(KeyCode::ReturnKey)));
+            assert!(actions.is_empty());
+
+            let actions = cx.capture_actions( | cx | button.handle_key_down(cx, &path, &key(KeyCode::Space)));
+            assert!(button.pressed(&actions));
+            let actions = cx.capture_actions( | cx | button.handle_key_up(cx, &path, &key(KeyCode::Space)));
+            assert!(button.clicked(&actions));
+            let last = actions.last().and_then( | a | a.as_widget_action()).unwrap();
+            assert!(matches!(last.cast(), ButtonAction::Released(_)));
+
+            // one press clicks once
+            let actions = cx.capture_actions( | cx | button.handle_key_up(cx, &path, &key(KeyCode::Space)));
+            assert!(actions.is_empty());
+        })
+    }
+
+    #[test]
+    fn losing_focus_releases_without_a_click() {
+        with_button( | cx, button | {
+            let path = HeapLiveIdPath::default();
+            cx.capture_actions( | cx | button.handle_key_down(cx, &path, &key(KeyCode::ReturnKey)));
+            let actions = cx.capture_actions( | cx | button.handle_key_focus_lost(cx, &path));
+            assert!(button.released(&actions));
+            assert!(!button.clicked(&actions));
+            let actions = cx.capture_actions( | cx | button.handle_key_up(cx, &path, &key(KeyCode::ReturnKey)));
+            assert!(actions.is_empty());
+        })
+    }
+}
@@ -49,6 +49,8 @@ pub struct CheckBox {
     #[live] text: RcStringMut,
     
     #[live] bind: String,
+    
+    #[live] tab_index: i64,
 }
 
 #[derive(Clone, Debug, DefaultNone)]
@@ -71,6 +73,19 @@ impl CheckBox {
         self.draw_icon.draw_walk(cx, self.icon_walk);
         self.draw_text.draw_walk(cx, self.label_walk, self.label_align, self.text.as_ref());
         self.draw_check.end(cx);
+        cx.add_nav_stop_with_tab_index(self.draw_check.area(), NavRole::CheckBox, self.tab_index);
+    }
+    
+    fn toggle(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        if self.animator_in_state(cx, id!(selected.on)) {
+            self.animator_play(cx, id!(selected.off));
+            cx.widget_action(uid, &scope.path, CheckBoxAction::Change(false));
+        }
+        else {
+            self.animator_play(cx, id!(selected.on));
+            cx.widget_action(uid, &scope.path, CheckBoxAction::Change(true));
+        }
     }
 }
 
@@ -95,7 +110,6 @@ impl Widget for CheckBox {
     }
     
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
-        let uid = self.widget_uid();
         self.animator_handle_event(cx, event);
                 
         match event.hits(cx, self.draw_check.area()) {
@@ -107,15 +121,17 @@ impl Widget for CheckBox {
                 self.animator_play(cx, id!(hover.off));
             },
             Hit::FingerDown(_fe) => {
-                if self.animator_in_state(cx, id!(selected.on)) {
-                    self.animator_play(cx, id!(selected.off));
-                    cx.widget_action(uid, &scope.path, CheckBoxAction::Change(false));
-                }
-                else {
-                    self.animator_play(cx, id!(selected.on));
-                    cx.widget_action(uid, &scope.path, CheckBoxAction::Change(true));
-                }
+                self.toggle(cx, scope);
             },
+            Hit::Trigger(te) if te.0.iter().any(|t| t.id == live_id!(nav_focus)) => {
+                self.animator_play(cx, id!(nav_focus.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animator_play(cx, id!(nav_focus.off));
+            }
+            Hit::KeyDown(ke) if !ke.is_repeat && matches!(ke.key_code, KeyCode::ReturnKey | KeyCode::Space) => {
+                self.toggle(cx, scope);
+            }
             Hit::FingerUp(_fe) => {
                                 
             }
@@ -131,6 +147,15 @@ impl Widget for CheckBox {
         DrawStep::done()
     }
     
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::CheckBox, self.text.as_ref());
+        node.state.focusable = self.tab_index >= 0;
+        node.state.focused = cx.has_key_focus(self.draw_check.area());
+        node.state.checked = Some(self.animator_in_state(cx, id!(selected.on)));
+        node.rect = self.draw_check.area().rect(cx);
+        Some(node)
+    }
+    
     fn text(&self) -> String {
         self.text.as_ref().to_string()
     }
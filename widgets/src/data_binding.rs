@@ -1,12 +1,16 @@
 use crate::{
     makepad_platform::*,
     widget::*,
+    data_model::DataError,
 };
 
 #[derive(Debug)]
 pub struct DataBindingStore {
     pub nodes: Vec<LiveNode>,
     mutated_by: Vec<WidgetUid>,
+    // the paths changed since the widgets were last updated, `None` updates every binding
+    dirty: Option<Vec<Vec<LiveId>>>,
+    errors: Vec<DataError>,
 }
 
 impl DataBindingStore {
@@ -17,6 +21,8 @@ impl DataBindingStore {
         Self {
             nodes,
             mutated_by: Vec::new(),
+            dirty: None,
+            errors: Vec::new(),
         }
     }
     
@@ -24,6 +30,8 @@ impl DataBindingStore {
         Self {
             nodes,
             mutated_by: Vec::new(),
+            dirty: None,
+            errors: Vec::new(),
         }
     }
     
@@ -42,10 +50,56 @@ impl DataBindingStore {
         self.nodes.read_field_value(data_id).is_some()
     }
     
+    pub fn is_mutated(&self) -> bool {
+        !self.mutated_by.is_empty()
+    }
+    
+    /// Limits `changes_to_widgets` to the bindings below `dirty`, `None` lets every binding through
+    pub fn set_dirty(&mut self, dirty: Option<Vec<Vec<LiveId>>>) {
+        self.dirty = dirty;
+    }
+    
+    pub fn add_dirty(&mut self, paths: &[Vec<LiveId>]) {
+        if let Some(dirty) = &mut self.dirty {
+            dirty.extend(paths.iter().cloned());
+        }
+    }
+    
+    pub fn is_dirty(&self, data_id: &[LiveId]) -> bool {
+        match &self.dirty {
+            Some(dirty) => dirty.iter().any( | path | data_id.starts_with(path) || path.starts_with(data_id)),
+            None => true
+        }
+    }
+    
+    /// Forgets the pending changes once every widget has seen them
+    pub fn clear_pending(&mut self) {
+        self.dirty = Some(Vec::new());
+        self.mutated_by.clear();
+    }
+    
+    pub fn errors(&self) -> &[DataError] {
+        &self.errors
+    }
+    
+    /// The paths whose error appears, changes or goes away become dirty so their widgets hear about it
+    pub fn set_errors(&mut self, errors: Vec<DataError>) {
+        let changed: Vec<Vec<LiveId>> = errors.iter().chain(self.errors.iter())
+            .filter( | error | self.error(&error.path) != errors.iter().find( | e | e.path == error.path).map( | e | e.message.as_str()))
+            .map( | error | error.path.clone())
+            .collect();
+        self.add_dirty(&changed);
+        self.errors = errors;
+    }
+    
+    pub fn error(&self, data_id: &[LiveId]) -> Option<&str> {
+        self.errors.iter().find( | error | error.path == data_id).map( | error | error.message.as_str())
+    }
 }
 
 enum Direction<'a> {
-    DataToWidgets(&'a DataBindingStore),
+    // the flag skips the bindings that aren't dirty
+    DataToWidgets(&'a DataBindingStore, bool),
     WidgetsToData(&'a Actions, &'a mut DataBindingStore)
 }
 
@@ -54,15 +108,29 @@ pub struct DataBindingMap<'a> {
     pub cx: &'a mut Cx,
     direction: Direction<'a>,
     pub ui: &'a WidgetRef,
+    // goes in front of every data path, for binding list items
+    prefix: Vec<LiveId>,
 }
 
 impl DataBindingStore {
     pub fn data_to_widgets<'a>(&'a self, cx: &'a mut Cx,  ui: &'a WidgetRef) -> DataBindingMap {
         DataBindingMap {
             debug_missing: false,
-            direction: Direction::DataToWidgets(self),
+            direction: Direction::DataToWidgets(self, false),
             cx,
             ui,
+            prefix: Vec::new(),
+        }
+    }
+    
+    /// Like `data_to_widgets` but only for the bindings whose data is dirty
+    pub fn changes_to_widgets<'a>(&'a self, cx: &'a mut Cx,  ui: &'a WidgetRef) -> DataBindingMap<'a> {
+        DataBindingMap {
+            debug_missing: false,
+            direction: Direction::DataToWidgets(self, true),
+            cx,
+            ui,
+            prefix: Vec::new(),
         }
     }
     
@@ -72,6 +140,7 @@ impl DataBindingStore {
             direction: Direction::WidgetsToData(actions, self),
             cx,
             ui,
+            prefix: Vec::new(),
         }
     }
 }
@@ -82,15 +151,27 @@ impl<'a> DataBindingMap<'a> {
         self
     }
     
+    pub fn with_prefix(mut self, prefix: &[LiveId]) -> Self {
+        self.prefix = prefix.to_vec();
+        self
+    }
+    
     pub fn is_data_to_widgets(&self) -> bool {
-        if let Direction::DataToWidgets(_) = self.direction {true}else {false}
+        matches!(self.direction, Direction::DataToWidgets(_,_))
     }
     
     pub fn is_widgets_to_data(&self) -> bool {
         if let Direction::WidgetsToData(_,_) = self.direction {true}else {false}
     }
     
+    fn data_path(&self, data_id: &[LiveId]) -> Vec<LiveId> {
+        let mut path = self.prefix.clone();
+        path.extend_from_slice(data_id);
+        path
+    }
+    
     pub fn bind(&mut self, data_id: &[LiveId], widgets: &[&[LiveId]]) {
+        let data_id = &self.data_path(data_id);
         // alright so. we have a direction.
         match &mut self.direction{
             Direction::WidgetsToData(actions, store) =>{
@@ -103,7 +184,11 @@ impl<'a> DataBindingMap<'a> {
                     }
                 }
             }
-            Direction::DataToWidgets(store)=>{
+            Direction::DataToWidgets(store, only_dirty)=>{
+                if *only_dirty && !store.is_dirty(data_id) {
+                    return
+                }
+                let error = store.error(data_id);
                 let mut any_found = false;
                 for widget in self.ui.widgets(widgets).iter() {
                     any_found = true;
@@ -111,6 +196,7 @@ impl<'a> DataBindingMap<'a> {
                     if !store.mutated_by.contains(&uid) {
                         widget.data_to_widget(self.cx, &store.nodes, data_id);
                     }
+                    widget.set_data_error(self.cx, error);
                 }
                 if !any_found && self.debug_missing {
                     log!("No widgets found for databinding {:?}", widgets);
@@ -119,9 +205,26 @@ impl<'a> DataBindingMap<'a> {
         }
     }
     
+    /// Redraws the widgets that show the collection at `data_id` when it or one of its items is dirty
+    pub fn bind_list(&mut self, data_id: &[LiveId], widgets: &[&[LiveId]]) {
+        let data_id = &self.data_path(data_id);
+        if let Direction::DataToWidgets(store, only_dirty) = &self.direction{
+            if *only_dirty && !store.is_dirty(data_id) {
+                return
+            }
+            for widget in self.ui.widgets(widgets).iter() {
+                widget.redraw(self.cx);
+            }
+        }
+    }
+    
     pub fn apply<F>(&mut self, data: &[LiveId], widget_val: &[&[LiveId]; 2], map: F)
     where F: FnOnce(LiveValue) -> LiveValue {
-        if let Direction::DataToWidgets(store) = &self.direction{
+        let data = &self.data_path(data);
+        if let Direction::DataToWidgets(store, only_dirty) = &self.direction{
+            if *only_dirty && !store.is_dirty(data) {
+                return
+            }
             if let Some(v) = store.nodes.read_field_value(data) {
                 let mut ui_nodes = LiveNodeVec::new();
                 ui_nodes.write_field_value(widget_val[1], map(v.clone()));
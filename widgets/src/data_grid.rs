@@ -0,0 +1,922 @@
+use {
+    std::ops::Range,
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        widget::*,
+        portal_list::PortalList,
+        scroll_bar::{ScrollBar, ScrollAxis, ScrollBarAction},
+    }
+};
+
+live_design!{
+    DrawDataGridHeader = {{DrawDataGridHeader}} {}
+    DrawDataGridRow = {{DrawDataGridRow}} {}
+    DrawSortIndicator = {{DrawSortIndicator}} {}
+    DataGridRowBase = {{DataGridRow}} {}
+    DataGridBase = {{DataGrid}} {}
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawDataGridHeader {
+    #[deref] draw_super: DrawQuad,
+    #[live] hover: f32,
+    #[live] pressed: f32,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawDataGridRow {
+    #[deref] draw_super: DrawQuad,
+    #[live] is_even: f32,
+    #[live] selected: f32,
+    #[live] hover: f32,
+    #[live] focussed: f32,
+    #[live] cursor: f32,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawSortIndicator {
+    #[deref] draw_super: DrawQuad,
+    #[live] descending: f32,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct DataGridRow {
+    #[live] draw_bg: DrawDataGridRow,
+    #[layout] layout: Layout,
+}
+
+/// Feeds a `DataGrid` while it draws. Only the rows in view are asked for,
+/// so the data behind it can be as large as it likes.
+pub trait DataGridProvider {
+    fn row_count(&self) -> usize;
+
+    /// Puts the value of one cell into the widget made from its template, usually with `set_text`.
+    fn fill_cell(&mut self, cx: &mut Cx, row: usize, column: LiveId, cell: &WidgetRef);
+
+    /// Picks another cell template for a single cell, `None` keeps the one of the column.
+    fn cell_template(&self, _row: usize, _column: LiveId) -> Option<LiveId> {
+        None
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DataGridColumn {
+    pub id: LiveId,
+    pub title: String,
+    /// The cell template from the `live_design!` of the grid
+    pub template: LiveId,
+    pub width: f64,
+    pub min_width: f64,
+    pub sortable: bool,
+    pub resizable: bool,
+}
+
+impl DataGridColumn {
+    pub fn new(id: LiveId, title: &str, template: LiveId, width: f64) -> Self {
+        Self {
+            id,
+            title: title.to_string(),
+            template,
+            width,
+            min_width: 24.0,
+            sortable: true,
+            resizable: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortDirection {
+    Ascending,
+    Descending
+}
+
+/// In `Multi` control or command toggles single rows and shift selects ranges
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum DataGridSelectionMode {
+    None,
+    Single,
+    #[pick] Multi,
+}
+
+/// The selected rows, kept as sorted ranges so selecting a million rows stays cheap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DataGridSelection {
+    ranges: Vec<Range<usize>>,
+}
+
+impl DataGridSelection {
+    pub fn contains(&self, row: usize) -> bool {
+        let index = self.ranges.partition_point( | r | r.end <= row);
+        self.ranges.get(index).is_some_and( | r | r.start <= row)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.iter().map( | r | r.len()).sum()
+    }
+
+    pub fn ranges(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = usize> + '_ {
+        self.ranges.iter().flat_map( | r | r.clone())
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Replaces the selection with a single row
+    pub fn select(&mut self, row: usize) {
+        self.ranges.clear();
+        self.ranges.push(row..row + 1);
+    }
+
+    pub fn insert_range(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return
+        }
+        self.ranges.push(range);
+        self.ranges.sort_by_key( | r | r.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(self.ranges.len());
+        for range in self.ranges.drain(..) {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range)
+            }
+        }
+        self.ranges = merged;
+    }
+
+    pub fn remove_range(&mut self, range: Range<usize>) {
+        let mut kept = Vec::with_capacity(self.ranges.len() + 1);
+        for r in self.ranges.drain(..) {
+            if r.end <= range.start || r.start >= range.end {
+                kept.push(r);
+                continue
+            }
+            if r.start < range.start {
+                kept.push(r.start..range.start);
+            }
+            if r.end > range.end {
+                kept.push(range.end..r.end);
+            }
+        }
+        self.ranges = kept;
+    }
+
+    pub fn toggle(&mut self, row: usize) {
+        if self.contains(row) {
+            self.remove_range(row..row + 1);
+        }
+        else {
+            self.insert_range(row..row + 1);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum HeaderDrag {
+    Resize {column: usize, start_width: f64},
+    // a press becomes a column move once it has a drop target
+    Press {column: usize, target: Option<usize>},
+}
+
+#[derive(Clone)]
+enum DataGridDrawState {
+    Rows
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum DataGridAction {
+    Sort {column: LiveId, direction: SortDirection},
+    ColumnResized {column: LiveId, width: f64},
+    ColumnMoved {column: LiveId, from: usize, to: usize},
+    SelectionChanged,
+    RowActivated(usize),
+    None
+}
+
+#[derive(Live, Widget)]
+pub struct DataGrid {
+    #[redraw] #[rust] area: Area,
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+
+    #[live] list: PortalList,
+    #[live] scroll_bar: ScrollBar,
+    #[live] row: Option<LivePtr>,
+
+    #[live] draw_header: DrawDataGridHeader,
+    #[live] draw_header_text: DrawText,
+    #[live] draw_sort: DrawSortIndicator,
+    #[live] draw_drop_marker: DrawColor,
+    #[live] header_layout: Layout,
+    #[live] cell_layout: Layout,
+    #[live] sort_indicator_walk: Walk,
+
+    #[live] header_height: f64,
+    #[live] row_height: f64,
+    #[live] resize_handle_width: f64,
+    #[live] min_drag_distance: f64,
+    /// The number of leading columns that stay put when the grid scrolls sideways
+    #[live] frozen_columns: usize,
+    #[live] selection_mode: DataGridSelectionMode,
+    #[live(true)] reorderable: bool,
+    /// The grid is a single Tab stop, the arrow keys move between its rows
+    #[live] tab_index: i64,
+
+    #[rust] templates: ComponentMap<LiveId, LivePtr>,
+    #[rust] cells: ComponentMap<(usize, LiveId, LiveId), WidgetRef>,
+    #[rust] rows: ComponentMap<usize, DataGridRow>,
+    #[rust] columns: Vec<DataGridColumn>,
+    #[rust] sort: Option<(LiveId, SortDirection)>,
+    #[rust] selection: DataGridSelection,
+    #[rust] anchor_row: Option<usize>,
+    #[rust] cursor_row: Option<usize>,
+    #[rust] hover_row: Option<usize>,
+    #[rust] hover_column: Option<usize>,
+    #[rust] header_drag: Option<HeaderDrag>,
+    #[rust] scroll_x: f64,
+    #[rust] row_count: usize,
+    #[rust] rtl: bool,
+    #[rust] header_area: Area,
+    // the header cell of every column in the last draw, in column order
+    #[rust] header_cells: Vec<Area>,
+    #[rust] draw_state: DrawStateWrap<DataGridDrawState>,
+}
+
+impl LiveHook for DataGrid {
+    fn before_apply(&mut self, _cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if let ApplyFrom::UpdateFromDoc {..} = apply.from {
+            self.templates.clear();
+        }
+    }
+
+    // the instances in the grid are its cell templates
+    fn apply_value_instance(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        if nodes[index].is_instance_prop() {
+            if let Some(live_ptr) = apply.from.to_live_ptr(cx, index){
+                let id = nodes[index].id;
+                self.templates.insert(id, live_ptr);
+                for ((_, _, templ_id), cell) in self.cells.iter_mut() {
+                    if *templ_id == id {
+                        cell.apply(cx, apply, index, nodes);
+                    }
+                }
+            }
+        }
+        else {
+            cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+        }
+        nodes.skip_node(index)
+    }
+
+    fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) {
+        if let Some(index) = nodes.child_by_name(index, live_id!(row).as_field()) {
+            for row in self.rows.values_mut() {
+                row.apply(cx, apply, index, nodes);
+            }
+        }
+        self.area.redraw(cx);
+    }
+}
+
+impl DataGrid {
+    pub fn set_columns(&mut self, cx: &mut Cx, columns: Vec<DataGridColumn>) {
+        self.columns = columns;
+        self.header_drag = None;
+        self.hover_column = None;
+        self.area.redraw(cx);
+    }
+
+    pub fn columns(&self) -> &[DataGridColumn] {
+        &self.columns
+    }
+
+    /// Shows a sort indicator without emitting a sort action
+    pub fn set_sort(&mut self, cx: &mut Cx, sort: Option<(LiveId, SortDirection)>) {
+        self.sort = sort;
+        self.area.redraw(cx);
+    }
+
+    pub fn sort(&self) -> Option<(LiveId, SortDirection)> {
+        self.sort
+    }
+
+    pub fn selection(&self) -> &DataGridSelection {
+        &self.selection
+    }
+
+    pub fn set_selection(&mut self, cx: &mut Cx, selection: DataGridSelection) {
+        self.selection = selection;
+        self.area.redraw(cx);
+    }
+
+    fn frozen_count(&self) -> usize {
+        self.frozen_columns.min(self.columns.len())
+    }
+
+    fn scrolled_layout(&self) -> Layout {
+        Layout {
+            scroll: dvec2(self.scroll_x, 0.0),
+            ..Layout::flow_right()
+        }
+    }
+
+    /// Draws the rows in view, call it when the grid returns a draw step.
+    pub fn draw_rows(&mut self, cx: &mut Cx2d, scope: &mut Scope, provider: &mut dyn DataGridProvider) {
+        self.row_count = provider.row_count();
+        self.list.set_item_range(cx, 0, self.row_count);
+        let focussed = cx.has_key_focus(self.area);
+        while let Some(row) = self.list.next_visible_item(cx) {
+            // leaving the turtle empty past the last row ends the list
+            if row >= self.row_count {
+                continue
+            }
+            let item = self.rows.get_or_insert(cx, row, | cx | DataGridRow::new_from_ptr(cx, self.row));
+            item.draw_bg.is_even = if row % 2 == 0 {1.0} else {0.0};
+            item.draw_bg.selected = if self.selection.contains(row) {1.0} else {0.0};
+            item.draw_bg.hover = if self.hover_row == Some(row) {1.0} else {0.0};
+            item.draw_bg.focussed = if focussed {1.0} else {0.0};
+            item.draw_bg.cursor = if self.cursor_row == Some(row) {1.0} else {0.0};
+            item.draw_bg.begin(cx, Walk::size(Size::Fill, Size::Fixed(self.row_height)), item.layout);
+
+            let frozen = self.frozen_count();
+            for index in 0..frozen {
+                self.draw_cell(cx, scope, provider, row, index);
+            }
+            cx.begin_turtle(Walk::fill(), self.scrolled_layout());
+            for index in frozen..self.columns.len() {
+                self.draw_cell(cx, scope, provider, row, index);
+            }
+            cx.end_turtle();
+
+            if let Some(item) = self.rows.get_mut(&row) {
+                item.draw_bg.end(cx);
+            }
+        }
+        self.rows.retain_visible();
+        self.cells.retain_visible();
+    }
+
+    fn draw_cell(&mut self, cx: &mut Cx2d, scope: &mut Scope, provider: &mut dyn DataGridProvider, row: usize, index: usize) {
+        let column = &self.columns[index];
+        let (column_id, width) = (column.id, column.width);
+        let template = provider.cell_template(row, column_id).unwrap_or(column.template);
+        cx.begin_turtle(Walk::size(Size::Fixed(width), Size::Fill), self.cell_layout);
+        if let Some(ptr) = self.templates.get(&template) {
+            let cell = self.cells.get_or_insert(cx, (row, column_id, template), | cx | {
+                WidgetRef::new_from_ptr(cx, Some(*ptr))
+            }).clone();
+            provider.fill_cell(cx, row, column_id, &cell);
+            cell.draw_all(cx, scope);
+        }
+        else {
+            warning!("Template not found: {template}. Did you add it to the <DataGrid> instance in `live_design!{{}}`?");
+        }
+        cx.end_turtle();
+    }
+
+    fn draw_header(&mut self, cx: &mut Cx2d) {
+        cx.begin_turtle(Walk::size(Size::Fill, Size::Fixed(self.header_height)), Layout::flow_right());
+        self.header_cells.clear();
+        let frozen = self.frozen_count();
+        for index in 0..frozen {
+            self.draw_header_cell(cx, index);
+        }
+        cx.begin_turtle(Walk::fill(), self.scrolled_layout());
+        for index in frozen..self.columns.len() {
+            self.draw_header_cell(cx, index);
+        }
+        cx.end_turtle();
+        cx.end_turtle_with_area(&mut self.header_area);
+    }
+
+    fn draw_header_cell(&mut self, cx: &mut Cx2d, index: usize) {
+        let column = &self.columns[index];
+        let pressed = matches!(self.header_drag, Some(HeaderDrag::Press {column, ..}) if column == index);
+        self.draw_header.hover = if self.hover_column == Some(index) {1.0} else {0.0};
+        self.draw_header.pressed = if pressed {1.0} else {0.0};
+        self.draw_header.begin(cx, Walk::size(Size::Fixed(column.width), Size::Fill), self.header_layout);
+        // the title takes what the sort indicator leaves
+        if let Some(mut dw) = cx.defer_walk(Walk::fill_fit()) {
+            match self.sort {
+                Some((id, direction)) if id == column.id => {
+                    self.draw_sort.descending = if direction == SortDirection::Descending {1.0} else {0.0};
+                    self.draw_sort.draw_walk(cx, self.sort_indicator_walk);
+                }
+                _ => ()
+            }
+            let walk = dw.resolve(cx);
+            self.draw_header_text.draw_walk(cx, walk, Align::default(), &column.title);
+        }
+        self.draw_header.end(cx);
+        self.header_cells.push(self.draw_header.area());
+    }
+
+    fn draw_drop_marker(&mut self, cx: &mut Cx2d) {
+        let Some(HeaderDrag::Press {column, target: Some(target)}) = self.header_drag else {return};
+        let region = self.column_region(column);
+        let x = if target < region.end {
+            let rect = self.header_cells[target].rect(cx);
+            if self.rtl {rect.pos.x + rect.size.x} else {rect.pos.x}
+        }
+        else {
+            let rect = self.header_cells[target - 1].rect(cx);
+            if self.rtl {rect.pos.x} else {rect.pos.x + rect.size.x}
+        };
+        let header = self.header_area.rect(cx);
+        self.draw_drop_marker.draw_abs(cx, Rect {
+            pos: dvec2(x - 1.0, header.pos.y),
+            size: dvec2(2.0, header.size.y)
+        });
+    }
+
+    // columns only move within the frozen part or within the scrolling part
+    fn column_region(&self, column: usize) -> Range<usize> {
+        let frozen = self.frozen_count();
+        if column < frozen {0..frozen} else {frozen..self.columns.len()}
+    }
+
+    /// The column under `abs` in the header and whether `abs` is on its resize handle
+    fn header_hit(&self, cx: &Cx, abs: DVec2) -> Option<(usize, bool)> {
+        if !self.header_area.rect(cx).contains(abs) || self.header_cells.len() != self.columns.len() {
+            return None
+        }
+        let frozen = self.frozen_count();
+        let frozen_span = (0..frozen).fold(None, | span: Option<(f64, f64)>, index | {
+            let rect = self.header_cells[index].rect(cx);
+            let (start, end) = span.unwrap_or((rect.pos.x, rect.pos.x));
+            Some((start.min(rect.pos.x), end.max(rect.pos.x + rect.size.x)))
+        });
+        // scrolled columns slide underneath the frozen ones
+        let hidden = | index: usize | index >= frozen && frozen_span.is_some_and( | (start, end) | abs.x >= start && abs.x <= end);
+        let handle = self.resize_handle_width * 0.5;
+        for (index, area) in self.header_cells.iter().enumerate() {
+            let rect = area.rect(cx);
+            let edge = if self.rtl {rect.pos.x} else {rect.pos.x + rect.size.x};
+            if self.columns[index].resizable && !hidden(index) && (abs.x - edge).abs() <= handle {
+                return Some((index, true))
+            }
+        }
+        for (index, area) in self.header_cells.iter().enumerate() {
+            if !hidden(index) && area.rect(cx).contains(abs) {
+                return Some((index, false))
+            }
+        }
+        None
+    }
+
+    /// Where a dragged column would land, as the index it is inserted before
+    fn drop_target(&self, cx: &Cx, column: usize, x: f64) -> usize {
+        let region = self.column_region(column);
+        for index in region.clone() {
+            let rect = self.header_cells[index].rect(cx);
+            let center = rect.pos.x + rect.size.x * 0.5;
+            if (!self.rtl && x < center) || (self.rtl && x > center) {
+                return index
+            }
+        }
+        region.end
+    }
+
+    fn move_column(&mut self, from: usize, before: usize) -> Option<usize> {
+        let to = if before > from {before - 1} else {before};
+        if to == from {
+            return None
+        }
+        let column = self.columns.remove(from);
+        self.columns.insert(to, column);
+        Some(to)
+    }
+
+    fn page_rows(&self, cx: &Cx) -> usize {
+        let rect = self.area.rect(cx);
+        let header = self.header_area.rect(cx);
+        let height = rect.pos.y + rect.size.y - (header.pos.y + header.size.y);
+        (height / self.row_height).floor().max(1.0) as usize
+    }
+
+    fn scroll_row_into_view(&mut self, cx: &mut Cx, row: usize) {
+        let page = self.page_rows(cx);
+        let first = self.list.first_id();
+        if row < first {
+            self.list.set_first_id_and_scroll(row, 0.0);
+        }
+        else if row >= first + page {
+            self.list.set_first_id_and_scroll(row + 1 - page, 0.0);
+        }
+        else {
+            return
+        }
+        self.list.update_scroll_bar(cx);
+    }
+
+    fn select_row(&mut self, cx: &mut Cx, row: usize, modifiers: &KeyModifiers, uid: WidgetUid, scope: &mut Scope) {
+        let toggle = modifiers.control || modifiers.logo;
+        match self.selection_mode {
+            DataGridSelectionMode::None => return,
+            DataGridSelectionMode::Single => {
+                self.selection.select(row);
+                self.anchor_row = Some(row);
+            }
+            DataGridSelectionMode::Multi => {
+                if let Some(anchor) = self.anchor_row.filter( | _ | modifiers.shift) {
+                    if !toggle {
+                        self.selection.clear();
+                    }
+                    self.selection.insert_range(anchor.min(row)..anchor.max(row) + 1);
+                }
+                else if toggle {
+                    self.selection.toggle(row);
+                    self.anchor_row = Some(row);
+                }
+                else {
+                    self.selection.select(row);
+                    self.anchor_row = Some(row);
+                }
+            }
+        }
+        self.cursor_row = Some(row);
+        self.area.redraw(cx);
+        cx.widget_action(uid, &scope.path, DataGridAction::SelectionChanged);
+    }
+
+    fn handle_key_down(&mut self, cx: &mut Cx, ke: &KeyEvent, uid: WidgetUid, scope: &mut Scope) {
+        if self.row_count == 0 {
+            return
+        }
+        let last = self.row_count - 1;
+        let multi = self.selection_mode == DataGridSelectionMode::Multi;
+        let command = ke.modifiers.control || ke.modifiers.logo;
+        let row = match (self.cursor_row, ke.key_code) {
+            (_, KeyCode::ReturnKey) => {
+                if let Some(row) = self.cursor_row {
+                    cx.widget_action(uid, &scope.path, DataGridAction::RowActivated(row));
+                }
+                return
+            }
+            (_, KeyCode::KeyA) if multi && command => {
+                self.selection.clear();
+                self.selection.insert_range(0..self.row_count);
+                self.area.redraw(cx);
+                cx.widget_action(uid, &scope.path, DataGridAction::SelectionChanged);
+                return
+            }
+            (Some(row), KeyCode::Space) if multi && command => {
+                self.select_row(cx, row, &KeyModifiers {control: true, ..Default::default()}, uid, scope);
+                return
+            }
+            // the first key press picks the first row in view
+            (None, KeyCode::ArrowUp | KeyCode::ArrowDown) => self.list.first_id().min(last),
+            (Some(row), KeyCode::ArrowUp) => row.saturating_sub(1),
+            (Some(row), KeyCode::ArrowDown) => (row + 1).min(last),
+            (row, KeyCode::PageUp) => row.unwrap_or(0).saturating_sub(self.page_rows(cx)),
+            (row, KeyCode::PageDown) => (row.unwrap_or(0) + self.page_rows(cx)).min(last),
+            (_, KeyCode::Home) => 0,
+            (_, KeyCode::End) => last,
+            _ => return
+        };
+        self.select_row(cx, row, &KeyModifiers {shift: ke.modifiers.shift, ..Default::default()}, uid, scope);
+        self.scroll_row_into_view(cx, row);
+    }
+
+    fn handle_header_event(&mut self, cx: &mut Cx, event: &Event, uid: WidgetUid, scope: &mut Scope) {
+        match event.hits(cx, self.header_area) {
+            Hit::FingerHoverIn(fe) | Hit::FingerHoverOver(fe) => {
+                let hit = self.header_hit(cx, fe.abs);
+                if let Some((_, true)) = hit {
+                    cx.set_cursor(MouseCursor::ColResize);
+                }
+                else {
+                    cx.set_cursor(MouseCursor::Default);
+                }
+                let hover = hit.map( | (index, _) | index);
+                if hover != self.hover_column {
+                    self.hover_column = hover;
+                    self.area.redraw(cx);
+                }
+            }
+            Hit::FingerHoverOut(_) => {
+                self.hover_column = None;
+                self.area.redraw(cx);
+            }
+            Hit::FingerDown(fe) => {
+                self.header_drag = match self.header_hit(cx, fe.abs) {
+                    Some((column, true)) => Some(HeaderDrag::Resize {column, start_width: self.columns[column].width}),
+                    Some((column, false)) => Some(HeaderDrag::Press {column, target: None}),
+                    None => None
+                };
+                self.area.redraw(cx);
+            }
+            Hit::FingerMove(fe) => match self.header_drag {
+                Some(HeaderDrag::Resize {column, start_width}) => {
+                    cx.set_cursor(MouseCursor::ColResize);
+                    let delta = fe.abs.x - fe.abs_start.x;
+                    let delta = if self.rtl {-delta} else {delta};
+                    let width = (start_width + delta).max(self.columns[column].min_width);
+                    if width != self.columns[column].width {
+                        self.columns[column].width = width;
+                        self.area.redraw(cx);
+                    }
+                }
+                Some(HeaderDrag::Press {column, target}) if self.reorderable
+                    && (target.is_some() || (fe.abs.x - fe.abs_start.x).abs() >= self.min_drag_distance) => {
+                    let target = self.drop_target(cx, column, fe.abs.x);
+                    self.header_drag = Some(HeaderDrag::Press {column, target: Some(target)});
+                    self.area.redraw(cx);
+                }
+                _ => ()
+            }
+            Hit::FingerUp(fe) => {
+                match self.header_drag.take() {
+                    Some(HeaderDrag::Resize {column, ..}) => {
+                        let column = &self.columns[column];
+                        cx.widget_action(uid, &scope.path, DataGridAction::ColumnResized {column: column.id, width: column.width});
+                    }
+                    Some(HeaderDrag::Press {column, target: None}) if fe.is_over && self.columns[column].sortable => {
+                        let id = self.columns[column].id;
+                        let direction = match self.sort {
+                            Some((sorted, SortDirection::Ascending)) if sorted == id => SortDirection::Descending,
+                            _ => SortDirection::Ascending
+                        };
+                        self.sort = Some((id, direction));
+                        cx.widget_action(uid, &scope.path, DataGridAction::Sort {column: id, direction});
+                    }
+                    Some(HeaderDrag::Press {column, target: Some(target)}) => {
+                        if let Some(to) = self.move_column(column, target) {
+                            let id = self.columns[to].id;
+                            cx.widget_action(uid, &scope.path, DataGridAction::ColumnMoved {column: id, from: column, to});
+                        }
+                    }
+                    _ => ()
+                }
+                self.area.redraw(cx);
+            }
+            _ => ()
+        }
+    }
+}
+
+impl Widget for DataGrid {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+
+        let mut scroll_to = None;
+        self.scroll_bar.handle_event_with(cx, event, &mut | _cx, action | {
+            if let ScrollBarAction::Scroll {scroll_pos, ..} = action {
+                scroll_to = Some(scroll_pos);
+            }
+        });
+        self.scroll_bar.handle_scroll_event(cx, event, self.area, &mut | _cx, action | {
+            if let ScrollBarAction::Scroll {scroll_pos, ..} = action {
+                scroll_to = Some(scroll_pos);
+            }
+        });
+        if let Some(scroll_to) = scroll_to {
+            if scroll_to != self.scroll_x {
+                self.scroll_x = scroll_to;
+                self.area.redraw(cx);
+            }
+        }
+
+        for cell in self.cells.values_mut() {
+            let cell_uid = cell.widget_uid();
+            cx.group_widget_actions(uid, cell_uid, | cx | {
+                cell.handle_event(cx, event, scope)
+            });
+        }
+
+        self.handle_header_event(cx, event, uid, scope);
+
+        let mut pressed = None;
+        for (row, item) in self.rows.iter() {
+            match event.hits(cx, item.draw_bg.area()) {
+                Hit::FingerHoverIn(_) => {
+                    self.hover_row = Some(*row);
+                    self.area.redraw(cx);
+                }
+                Hit::FingerHoverOut(_) if self.hover_row == Some(*row) => {
+                    self.hover_row = None;
+                    self.area.redraw(cx);
+                }
+                Hit::FingerDown(fe) => {
+                    pressed = Some((*row, fe.modifiers, fe.tap_count));
+                }
+                _ => ()
+            }
+        }
+        if let Some((row, modifiers, tap_count)) = pressed {
+            cx.set_key_focus(self.area);
+            self.select_row(cx, row, &modifiers, uid, scope);
+            if tap_count == 2 {
+                cx.widget_action(uid, &scope.path, DataGridAction::RowActivated(row));
+            }
+        }
+
+        self.list.handle_event(cx, event, scope);
+
+        match event.hits(cx, self.area) {
+            Hit::KeyDown(ke) => self.handle_key_down(cx, &ke, uid, scope),
+            Hit::KeyFocus(_) | Hit::KeyFocusLost(_) => self.area.redraw(cx),
+            _ => ()
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.draw_state.begin(cx, DataGridDrawState::Rows) {
+            self.rtl = cx.turtle().is_rtl();
+            cx.begin_turtle(walk, self.layout);
+            self.draw_header(cx);
+            let list_walk = self.list.walk(cx);
+            // the list steps once, its rows come from draw_rows
+            if self.list.draw_walk(cx, scope, list_walk).is_step() {
+                return DrawStep::make_step()
+            }
+        }
+        if self.draw_state.get().is_some() {
+            let list_walk = self.list.walk(cx);
+            self.list.draw_walk_all(cx, scope, list_walk);
+            self.draw_drop_marker(cx);
+            let rect = cx.turtle().rect();
+            let total: f64 = self.columns.iter().map( | column | column.width).sum();
+            self.scroll_x = self.scroll_bar.draw_scroll_bar(cx, ScrollAxis::Horizontal, rect, dvec2(total, 0.0));
+            cx.end_turtle_with_area(&mut self.area);
+            cx.add_nav_stop_with_tab_index(self.area, NavRole::List, self.tab_index);
+            self.draw_state.end();
+        }
+        DrawStep::done()
+    }
+
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::Table, "");
+        node.state.focusable = self.tab_index >= 0;
+        node.state.focused = cx.has_key_focus(self.area);
+        node.rect = self.area.rect(cx);
+        Some(node)
+    }
+}
+
+impl DataGridRef {
+    pub fn set_columns(&self, cx: &mut Cx, columns: Vec<DataGridColumn>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_columns(cx, columns);
+        }
+    }
+
+    pub fn columns(&self) -> Vec<DataGridColumn> {
+        let Some(inner) = self.borrow() else {return Vec::new()};
+        inner.columns.clone()
+    }
+
+    pub fn set_sort(&self, cx: &mut Cx, sort: Option<(LiveId, SortDirection)>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_sort(cx, sort);
+        }
+    }
+
+    pub fn selection(&self) -> DataGridSelection {
+        let Some(inner) = self.borrow() else {return DataGridSelection::default()};
+        inner.selection.clone()
+    }
+
+    pub fn set_selection(&self, cx: &mut Cx, selection: DataGridSelection) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_selection(cx, selection);
+        }
+    }
+
+    pub fn sorted(&self, actions: &Actions) -> Option<(LiveId, SortDirection)> {
+        if let DataGridAction::Sort {column, direction} = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some((column, direction))
+        }
+        None
+    }
+
+    pub fn column_resized(&self, actions: &Actions) -> Option<(LiveId, f64)> {
+        if let DataGridAction::ColumnResized {column, width} = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some((column, width))
+        }
+        None
+    }
+
+    pub fn column_moved(&self, actions: &Actions) -> Option<(LiveId, usize, usize)> {
+        if let DataGridAction::ColumnMoved {column, from, to} = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some((column, from, to))
+        }
+        None
+    }
+
+    pub fn selection_changed(&self, actions: &Actions) -> bool {
+        matches!(actions.find_widget_action_cast(self.widget_uid()), DataGridAction::SelectionChanged)
+    }
+
+    pub fn row_activated(&self, actions: &Actions) -> Option<usize> {
+        if let DataGridAction::RowActivated(row) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(row)
+        }
+        None
+    }
+
+    /// The cells that emitted one of `actions`, with their row and column
+    pub fn cells_with_actions(&self, actions: &Actions) -> Vec<(usize, LiveId, WidgetRef)> {
+        let mut set = Vec::new();
+        let uid = self.widget_uid();
+        let Some(inner) = self.borrow() else {return set};
+        for action in actions {
+            if let Some(group) = action.as_widget_action().and_then( | action | action.group.as_ref()) {
+                if group.group_uid == uid {
+                    for ((row, column, _), cell) in inner.cells.iter() {
+                        if group.item_uid == cell.widget_uid() {
+                            set.push((*row, *column, cell.clone()))
+                        }
+                    }
+                }
+            }
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(ranges: &[(usize, usize)]) -> DataGridSelection {
+        let mut selection = DataGridSelection::default();
+        for (start, end) in ranges {
+            selection.insert_range(*start..*end);
+        }
+        selection
+    }
+
+    fn ranges(selection: &DataGridSelection) -> Vec<(usize, usize)> {
+        selection.ranges().iter().map( | r | (r.start, r.end)).collect()
+    }
+
+    #[test]
+    fn insert_merges_overlapping_and_adjacent_ranges() {
+        // out of order inserts stay sorted
+        assert_eq!(ranges(&selection(&[(10, 12), (2, 4)])), [(2, 4), (10, 12)]);
+        // overlapping and touching ranges merge, gaps of one row don't
+        assert_eq!(ranges(&selection(&[(2, 4), (3, 6)])), [(2, 6)]);
+        assert_eq!(ranges(&selection(&[(2, 4), (4, 6)])), [(2, 6)]);
+        assert_eq!(ranges(&selection(&[(2, 4), (5, 6)])), [(2, 4), (5, 6)]);
+        // one range can swallow several
+        assert_eq!(ranges(&selection(&[(2, 4), (6, 8), (10, 12), (3, 10)])), [(2, 12)]);
+        assert_eq!(ranges(&selection(&[(2, 4), (6, 8), (0, 20)])), [(0, 20)]);
+        // inserting what is already there changes nothing, empty ranges are ignored
+        assert_eq!(ranges(&selection(&[(2, 8), (3, 5)])), [(2, 8)]);
+        assert_eq!(ranges(&selection(&[(2, 4), (6, 6)])), [(2, 4)]);
+        assert!(selection(&[(5, 5)]).is_empty());
+    }
+
+    #[test]
+    fn remove_splits_and_trims_ranges() {
+        let mut s = selection(&[(0, 10)]);
+        s.remove_range(3..5);
+        assert_eq!(ranges(&s), [(0, 3), (5, 10)]);
+        // trims the ends of the ranges it overlaps and drops the ones it covers
+        let mut s = selection(&[(0, 4), (6, 8), (10, 14)]);
+        s.remove_range(2..12);
+        assert_eq!(ranges(&s), [(0, 2), (12, 14)]);
+        // ranges that only touch it are left alone
+        let mut s = selection(&[(0, 4), (6, 8)]);
+        s.remove_range(4..6);
+        assert_eq!(ranges(&s), [(0, 4), (6, 8)]);
+        let mut s = selection(&[(0, 4), (6, 8)]);
+        s.remove_range(0..100);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn toggle_and_queries() {
+        let mut s = selection(&[(2, 5)]);
+        s.toggle(3);
+        assert_eq!(ranges(&s), [(2, 3), (4, 5)]);
+        s.toggle(3);
+        assert_eq!(ranges(&s), [(2, 5)]);
+        s.toggle(5);
+        assert_eq!(ranges(&s), [(2, 6)]);
+        s.toggle(2);
+        assert_eq!(ranges(&s), [(3, 6)]);
+        s.toggle(8);
+        assert_eq!(ranges(&s), [(3, 6), (8, 9)]);
+        
+        assert_eq!(s.len(), 4);
+        assert_eq!(s.rows().collect::<Vec<_>>(), vec![3, 4, 5, 8]);
+        assert!((0..10).all( | row | s.contains(row) == [3, 4, 5, 8].contains(&row)));
+        s.select(1);
+        assert_eq!(ranges(&s), [(1, 2)]);
+        s.clear();
+        assert!(s.is_empty() && !s.contains(1));
+    }
+}
@@ -0,0 +1,479 @@
+use {
+    std::rc::Rc,
+    crate::{
+        makepad_platform::*,
+        widget::*,
+        data_binding::{DataBindingStore, DataBindingMap},
+    }
+};
+
+/// A field that failed its `#[validate(...)]` function, the message goes to the widgets bound to `path`
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataError {
+    pub path: Vec<LiveId>,
+    pub message: String,
+}
+
+/// A Rust struct that binds to widgets, usually through `#[derive(DataModel)]`.
+/// Every field is written as a node named after it, `Vec`s become objects with
+/// one child per item named `LiveId(index)` so `PortalList` items can bind to them.
+///
+/// Fields marked `#[rust]` are left out and `#[validate(f)]` calls `f(&field) -> Result<(), String>`.
+pub trait DataModel {
+    /// Writes the fields as children of the object that is open in `nodes`
+    fn data_write(&self, nodes: &mut LiveNodeVec);
+
+    /// Takes over the children of the object at `index`, returns whether anything changed
+    fn data_read(&mut self, nodes: &[LiveNode], index: usize) -> bool;
+
+    fn data_validate(&self, path: &mut Vec<LiveId>, errors: &mut Vec<DataError>);
+
+    fn to_data_nodes(&self) -> LiveNodeVec {
+        let mut nodes = LiveNodeVec::new();
+        nodes.open_object(LiveId(0));
+        self.data_write(&mut nodes);
+        nodes.close();
+        nodes
+    }
+
+    fn read_data_nodes(&mut self, nodes: &[LiveNode]) -> bool {
+        !nodes.is_empty() && self.data_read(nodes, 0)
+    }
+
+    fn validate(&self) -> Vec<DataError> {
+        let mut errors = Vec::new();
+        self.data_validate(&mut Vec::new(), &mut errors);
+        errors
+    }
+}
+
+/// The value of one field of a `DataModel`
+pub trait DataField {
+    fn field_write(&self, id: LiveId, nodes: &mut LiveNodeVec);
+
+    /// Reads the node at `index`, returns whether the value changed
+    fn field_read(&mut self, nodes: &[LiveNode], index: usize) -> bool;
+
+    fn field_validate(&self, _path: &mut Vec<LiveId>, _errors: &mut Vec<DataError>) {}
+}
+
+macro_rules!data_field_number {
+    ($ty: ty, $variant: ident, $wide: ty, $as: ident) => {
+        impl DataField for $ty {
+            fn field_write(&self, id: LiveId, nodes: &mut LiveNodeVec) {
+                nodes.push(LiveNode::from_id_value(id, LiveValue::$variant(*self as $wide)));
+            }
+
+            fn field_read(&mut self, nodes: &[LiveNode], index: usize) -> bool {
+                match nodes[index].value.$as() {
+                    Some(value) if value as $ty != *self => {
+                        *self = value as $ty;
+                        true
+                    }
+                    _ => false
+                }
+            }
+        }
+    }
+}
+
+data_field_number!(f64, Float64, f64, as_float);
+data_field_number!(f32, Float64, f64, as_float);
+data_field_number!(i64, Int64, i64, as_int);
+data_field_number!(i32, Int64, i64, as_int);
+data_field_number!(u32, Int64, i64, as_int);
+data_field_number!(u8, Int64, i64, as_int);
+data_field_number!(usize, Int64, i64, as_int);
+
+impl DataField for bool {
+    fn field_write(&self, id: LiveId, nodes: &mut LiveNodeVec) {
+        nodes.push(LiveNode::from_id_value(id, LiveValue::Bool(*self)));
+    }
+
+    fn field_read(&mut self, nodes: &[LiveNode], index: usize) -> bool {
+        match nodes[index].value.as_bool() {
+            Some(value) if value != *self => {
+                *self = value;
+                true
+            }
+            _ => false
+        }
+    }
+}
+
+impl DataField for String {
+    fn field_write(&self, id: LiveId, nodes: &mut LiveNodeVec) {
+        nodes.push(LiveNode::from_id_value(id, LiveValue::String(Rc::new(self.clone()))));
+    }
+
+    fn field_read(&mut self, nodes: &[LiveNode], index: usize) -> bool {
+        let value = match &nodes[index].value {
+            LiveValue::String(v) => v.as_str(),
+            LiveValue::Str(v) => v,
+            LiveValue::InlineString(v) => v.as_str(),
+            _ => return false
+        };
+        if value != self {
+            *self = value.to_string();
+            return true
+        }
+        false
+    }
+}
+
+impl DataField for LiveId {
+    fn field_write(&self, id: LiveId, nodes: &mut LiveNodeVec) {
+        nodes.push(LiveNode::from_id_value(id, LiveValue::Id(*self)));
+    }
+
+    fn field_read(&mut self, nodes: &[LiveNode], index: usize) -> bool {
+        match nodes[index].value {
+            LiveValue::Id(value) | LiveValue::BareEnum(value) if value != *self => {
+                *self = value;
+                true
+            }
+            _ => false
+        }
+    }
+}
+
+impl<T> DataField for Vec<T> where T: DataField + Default {
+    fn field_write(&self, id: LiveId, nodes: &mut LiveNodeVec) {
+        nodes.open_object(id);
+        for (index, item) in self.iter().enumerate() {
+            item.field_write(LiveId(index as u64), nodes);
+        }
+        nodes.close();
+    }
+
+    fn field_read(&mut self, nodes: &[LiveNode], index: usize) -> bool {
+        let mut changed = false;
+        let mut count = 0;
+        let mut child = nodes.first_child(index);
+        while let Some(index) = child {
+            if count == self.len() {
+                self.push(T::default());
+                changed = true;
+            }
+            changed |= self[count].field_read(nodes, index);
+            count += 1;
+            child = nodes.next_child(index);
+        }
+        if count < self.len() {
+            self.truncate(count);
+            changed = true;
+        }
+        changed
+    }
+
+    fn field_validate(&self, path: &mut Vec<LiveId>, errors: &mut Vec<DataError>) {
+        for (index, item) in self.iter().enumerate() {
+            path.push(LiveId(index as u64));
+            item.field_validate(path, errors);
+            path.pop();
+        }
+    }
+}
+
+/// The paths in `new` that differ from `old`. An object whose children were added
+/// or removed is reported as well, so a list bound to a `Vec` hears about it.
+pub fn data_changes(old: &[LiveNode], new: &[LiveNode]) -> Vec<Vec<LiveId>> {
+    let mut changes = Vec::new();
+    match (old.is_empty(), new.is_empty()) {
+        (true, true) => (),
+        (false, false) => diff_object(old, 0, new, 0, &mut Vec::new(), &mut changes),
+        _ => changes.push(Vec::new())
+    }
+    changes
+}
+
+fn diff_object(old: &[LiveNode], old_index: usize, new: &[LiveNode], new_index: usize, path: &mut Vec<LiveId>, changes: &mut Vec<Vec<LiveId>>) {
+    let mut old_child = old.first_child(old_index);
+    let mut new_child = new.first_child(new_index);
+    let mut matched = 0;
+    while let Some(new_at) = new_child {
+        let id = new[new_at].id;
+        // children keep their order, so the one at the same position is usually the match
+        let old_at = match old_child {
+            Some(old_at) if old[old_at].id == id => Some(old_at),
+            _ => old.child_by_name(old_index, id.as_field())
+        };
+        path.push(id);
+        match old_at {
+            Some(old_at) => {
+                matched += 1;
+                match (old[old_at].is_open(), new[new_at].is_open()) {
+                    (true, true) => diff_object(old, old_at, new, new_at, path, changes),
+                    (false, false) if old[old_at].value == new[new_at].value => (),
+                    _ => changes.push(path.clone())
+                }
+                old_child = old.next_child(old_at);
+            }
+            None => changes.push(path.clone())
+        }
+        path.pop();
+        new_child = new.next_child(new_at);
+    }
+    if matched != old.count_children(old_index) {
+        let mut old_child = old.first_child(old_index);
+        while let Some(old_at) = old_child {
+            let id = old[old_at].id;
+            if new.child_by_name(new_index, id.as_field()).is_none() {
+                path.push(id);
+                changes.push(path.clone());
+                path.pop();
+            }
+            old_child = old.next_child(old_at);
+        }
+    }
+    if matched != new.count_children(new_index) || matched != old.count_children(old_index) {
+        changes.push(path.clone());
+    }
+}
+
+/// Keeps a `DataModel` and the widgets bound to it in sync. Only the bindings whose
+/// data changed are pushed to their widgets, and validation errors follow the values.
+///
+/// Everything but `data_bind` and `item_to_widgets` works without a window, so a model
+/// and its change tracking can be tested on their own.
+pub struct DataModelStore<T: DataModel> {
+    pub model: T,
+    store: DataBindingStore,
+    // the nodes the widgets were last updated from
+    synced: LiveNodeVec,
+    changes: Vec<Vec<LiveId>>,
+}
+
+impl<T: DataModel> DataModelStore<T> {
+    pub fn new(model: T) -> Self {
+        let nodes = model.to_data_nodes();
+        let mut store = DataBindingStore::from_nodes(nodes.clone());
+        store.set_errors(model.validate());
+        store.set_dirty(None);
+        Self {
+            model,
+            store,
+            synced: nodes,
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn nodes(&self) -> &[LiveNode] {
+        &self.store.nodes
+    }
+
+    pub fn errors(&self) -> &[DataError] {
+        self.store.errors()
+    }
+
+    /// The paths that changed in the last `sync`
+    pub fn changes(&self) -> &[Vec<LiveId>] {
+        &self.changes
+    }
+
+    /// Whether the last `sync` changed `path` or something below it
+    pub fn is_changed(&self, path: &[LiveId]) -> bool {
+        self.changes.iter().any( | change | change.starts_with(path))
+    }
+
+    /// Takes over what the code changed in `model` and validates it again, returns the changed paths.
+    /// They stay pending for the widgets until the next `data_bind`.
+    pub fn sync(&mut self) -> &[Vec<LiveId>] {
+        let nodes = self.model.to_data_nodes();
+        self.changes = data_changes(&self.synced, &nodes);
+        self.store.add_dirty(&self.changes);
+        self.store.set_errors(self.model.validate());
+        self.store.nodes = nodes.clone();
+        self.synced = nodes;
+        &self.changes
+    }
+
+    /// Moves the values the widgets changed in `actions` into the model, then pushes
+    /// whatever changed since the last time to the widgets that depend on it.
+    pub fn data_bind<F>(&mut self, cx: &mut Cx, actions: &Actions, ui: &WidgetRef, f: F) where F: Fn(DataBindingMap) {
+        f(self.store.widgets_to_data(cx, actions, ui));
+        self.update_model();
+        f(self.store.changes_to_widgets(cx, ui));
+        self.store.clear_pending();
+    }
+
+    /// Like `data_bind` for the items of a `PortalList` that emitted `actions`,
+    /// the item at `index` binds below `path` followed by `LiveId(index)`.
+    /// Call it before `data_bind`, that one hands the changes to the rest of the widgets.
+    pub fn data_bind_items<F>(&mut self, cx: &mut Cx, actions: &Actions, items: &[(usize, WidgetRef)], path: &[LiveId], f: F) where F: Fn(DataBindingMap) {
+        for (index, item) in items {
+            f(self.store.widgets_to_data(cx, actions, item).with_prefix(&Self::item_path(path, *index)));
+        }
+        self.update_model();
+        for (index, item) in items {
+            f(self.store.changes_to_widgets(cx, item).with_prefix(&Self::item_path(path, *index)));
+        }
+    }
+
+    /// Fills a `PortalList` item from the `Vec` at `path`, call it for every item while the list draws
+    pub fn item_to_widgets<F>(&self, cx: &mut Cx, item: &WidgetRef, path: &[LiveId], index: usize, f: F) where F: FnOnce(DataBindingMap) {
+        f(self.store.data_to_widgets(cx, item).with_prefix(&Self::item_path(path, index)));
+    }
+
+    fn item_path(path: &[LiveId], index: usize) -> Vec<LiveId> {
+        let mut item_path = path.to_vec();
+        item_path.push(LiveId(index as u64));
+        item_path
+    }
+
+    fn update_model(&mut self) {
+        if self.store.is_mutated() {
+            self.model.read_data_nodes(&self.store.nodes);
+        }
+        self.sync();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::makepad_derive_widget::DataModel,
+        super::*,
+    };
+
+    fn not_empty(value: &str) -> Result<(), String> {
+        if value.is_empty() {Err("can't be empty".into())} else {Ok(())}
+    }
+
+    fn positive(value: &f64) -> Result<(), String> {
+        if *value > 0.0 {Ok(())} else {Err("must be positive".into())}
+    }
+
+    #[derive(DataModel, Clone, Debug, Default, PartialEq)]
+    struct Item {
+        #[validate(not_empty)] label: String,
+        done: bool,
+    }
+
+    #[derive(DataModel, Clone, Debug, Default, PartialEq)]
+    struct Size {
+        #[validate(positive)] width: f64,
+        height: f64,
+    }
+
+    #[derive(DataModel, Clone, Debug, Default, PartialEq)]
+    struct Form {
+        #[validate(not_empty)] name: String,
+        count: i32,
+        enabled: bool,
+        kind: LiveId,
+        size: Size,
+        items: Vec<Item>,
+        #[rust] cache: usize,
+    }
+
+    fn form() -> Form {
+        Form {
+            name: "makepad".into(),
+            count: 3,
+            enabled: true,
+            kind: live_id!(fancy),
+            size: Size {width: 10.0, height: 2.5},
+            items: vec![
+                Item {label: "one".into(), done: false},
+                Item {label: "two".into(), done: true},
+            ],
+            cache: 42,
+        }
+    }
+
+    #[test]
+    fn derive_round_trips_through_nodes() {
+        let nodes = form().to_data_nodes();
+        assert_eq!(nodes.read_field_value(&[live_id!(name)]), Some(&LiveValue::String(Rc::new("makepad".into()))));
+        assert_eq!(nodes.read_field_value(&[live_id!(size), live_id!(height)]), Some(&LiveValue::Float64(2.5)));
+        assert_eq!(nodes.read_field_value(&[live_id!(items), LiveId(1), live_id!(done)]), Some(&LiveValue::Bool(true)));
+        // #[rust] fields aren't data
+        assert_eq!(nodes.read_field_value(&[live_id!(cache)]), None);
+
+        let mut read = Form::default();
+        assert!(read.read_data_nodes(&nodes));
+        assert_eq!(read, Form {cache: 0, ..form()});
+        // reading the same nodes again changes nothing
+        assert!(!read.read_data_nodes(&nodes));
+
+        // a shorter list truncates the Vec
+        let mut short = form();
+        short.items.pop();
+        assert!(read.read_data_nodes(&short.to_data_nodes()));
+        assert_eq!(read.items, short.items);
+    }
+
+    #[test]
+    fn validate_reports_the_path_of_every_error() {
+        assert!(form().validate().is_empty());
+        let mut invalid = form();
+        invalid.name.clear();
+        invalid.size.width = 0.0;
+        invalid.items[1].label.clear();
+        assert_eq!(invalid.validate(), vec![
+            DataError {path: vec![live_id!(name)], message: "can't be empty".into()},
+            DataError {path: vec![live_id!(size), live_id!(width)], message: "must be positive".into()},
+            DataError {path: vec![live_id!(items), LiveId(1), live_id!(label)], message: "can't be empty".into()},
+        ]);
+    }
+
+    #[test]
+    fn set_errors_dirties_only_the_paths_whose_error_changed() {
+        let mut store = DataBindingStore::from_nodes(form().to_data_nodes());
+        let error = | path: &[LiveId], message: &str | DataError {path: path.to_vec(), message: message.into()};
+        store.clear_pending();
+        store.set_errors(vec![error(&[live_id!(name)], "can't be empty")]);
+        assert!(store.is_dirty(&[live_id!(name)]));
+        assert!(!store.is_dirty(&[live_id!(count)]));
+        assert_eq!(store.error(&[live_id!(name)]), Some("can't be empty"));
+
+        store.clear_pending();
+        store.set_errors(vec![error(&[live_id!(name)], "can't be empty")]);
+        assert!(!store.is_dirty(&[live_id!(name)]));
+        store.set_errors(vec![error(&[live_id!(name)], "too short")]);
+        assert!(store.is_dirty(&[live_id!(name)]));
+
+        // an error that goes away dirties its path too
+        store.clear_pending();
+        store.set_errors(Vec::new());
+        assert!(store.is_dirty(&[live_id!(name)]));
+        assert_eq!(store.error(&[live_id!(name)]), None);
+    }
+
+    #[test]
+    fn sync_tracks_what_the_code_changed() {
+        let mut store = DataModelStore::new(form());
+        // a new store updates every binding once
+        assert!(store.store.is_dirty(&[live_id!(count)]));
+        store.store.clear_pending();
+        assert!(store.sync().is_empty());
+
+        store.model.count = 4;
+        store.model.items[0].done = true;
+        assert_eq!(store.sync(), [
+            vec![live_id!(count)],
+            vec![live_id!(items), LiveId(0), live_id!(done)],
+        ]);
+        assert!(store.is_changed(&[live_id!(items)]));
+        assert!(!store.is_changed(&[live_id!(name)]));
+        assert!(store.store.is_dirty(&[live_id!(count)]));
+        // a binding to the whole list or to one of its fields hears about it
+        assert!(store.store.is_dirty(&[live_id!(items)]));
+        assert!(!store.store.is_dirty(&[live_id!(items), LiveId(1), live_id!(done)]));
+        assert!(!store.store.is_dirty(&[live_id!(enabled)]));
+
+        // an added item is reported on the list as well
+        store.store.clear_pending();
+        store.model.items.push(Item {label: "three".into(), done: false});
+        assert_eq!(store.sync(), [
+            vec![live_id!(items), LiveId(2)],
+            vec![live_id!(items)],
+        ]);
+
+        store.model.name.clear();
+        store.sync();
+        assert_eq!(store.errors(), [DataError {path: vec![live_id!(name)], message: "can't be empty".into()}]);
+        assert!(store.store.is_dirty(&[live_id!(name)]));
+    }
+}
@@ -0,0 +1,567 @@
+use {
+    std::{fmt, rc::Rc},
+    crate::{
+        makepad_derive_widget::*,
+        drop_down::PopupMenuGlobal,
+        popup_menu::{PopupMenu, PopupMenuAction},
+        makepad_draw::*,
+        widget::*,
+    }
+};
+
+live_design!{
+    DrawMonthButton = {{DrawMonthButton}} {}
+    DatePickerBase = {{DatePicker}} {}
+}
+
+/// A day in the proleptic Gregorian calendar, `month` and `day` count from 1
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Default for Date {
+    fn default() -> Self {
+        Self {year: 1970, month: 1, day: 1}
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        if (1..=12).contains(&month) && day >= 1 && day <= Self::days_in_month(year, month) {
+            return Some(Self {year, month, day})
+        }
+        None
+    }
+
+    /// Parses `YYYY-MM-DD`
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        Self::new(year, month, day)
+    }
+
+    /// The current date in UTC, platforms without a clock start at 1970-01-01
+    pub fn today() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Ok(since) = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+                return Self::from_days((since.as_secs() / 86400) as i64)
+            }
+        }
+        Self::default()
+    }
+
+    pub fn is_leap_year(year: i32) -> bool {
+        year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    }
+
+    pub fn days_in_month(year: i32, month: u32) -> u32 {
+        match month {
+            2 if Self::is_leap_year(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31
+        }
+    }
+
+    /// Days since 1970-01-01
+    pub fn to_days(&self) -> i64 {
+        // the year starts in march so the leap day comes last
+        let year = self.year as i64 - if self.month <= 2 {1} else {0};
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = self.month as i64;
+        let day_of_year = (153 * (if month > 2 {month - 3} else {month + 9}) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    pub fn from_days(days: i64) -> Self {
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days.rem_euclid(146097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let march_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * march_month + 2) / 5 + 1) as u32;
+        let month = if march_month < 10 {march_month + 3} else {march_month - 9} as u32;
+        let year = (year_of_era + era * 400) as i32 + if month <= 2 {1} else {0};
+        Self {year, month, day}
+    }
+
+    /// 0 is sunday
+    pub fn weekday(&self) -> u32 {
+        (self.to_days() + 4).rem_euclid(7) as u32
+    }
+
+    pub fn add_days(&self, days: i64) -> Self {
+        Self::from_days(self.to_days() + days)
+    }
+
+    /// Keeps the day where the new month has it, otherwise it becomes the last day of that month
+    pub fn add_months(&self, months: i32) -> Self {
+        let index = self.year as i64 * 12 + self.month as i64 - 1 + months as i64;
+        let year = index.div_euclid(12) as i32;
+        let month = index.rem_euclid(12) as u32 + 1;
+        Self {year, month, day: self.day.min(Self::days_in_month(year, month))}
+    }
+
+    pub fn first_of_month(&self) -> Self {
+        Self {day: 1, ..*self}
+    }
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawMonthButton {
+    #[deref] draw_super: DrawQuad,
+    #[live] next: f32,
+}
+
+#[derive(Live, Widget)]
+pub struct DatePicker {
+    #[animator] animator: Animator,
+
+    #[redraw] #[live] draw_bg: DrawQuad,
+    #[live] draw_text: DrawText,
+    #[live] draw_title: DrawText,
+    #[live] draw_weekday: DrawText,
+    #[live] draw_month_button: DrawMonthButton,
+
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+
+    /// The overlay the month grid is drawn in, its `menu_item` draws the days
+    #[live] popup_menu: Option<LivePtr>,
+    /// The size of one day in the month grid
+    #[live] cell_size: DVec2,
+    #[live] empty_message: String,
+    #[live] month_names: Vec<String>,
+    /// Starting on sunday
+    #[live] weekday_names: Vec<String>,
+    /// The weekday in the first column, 0 is sunday
+    #[live] first_day_of_week: u32,
+
+    #[live] tab_index: i64,
+
+    #[rust] date: Option<Date>,
+    // the day the keyboard is on, the grid shows its month
+    #[rust] cursor: Date,
+    #[rust] is_open: bool,
+    #[rust] prev_area: Area,
+    #[rust] next_area: Area,
+    #[rust] data_error: Option<String>,
+}
+
+impl LiveHook for DatePicker {
+    fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if self.popup_menu.is_none() || !apply.from.is_from_doc() {
+            return
+        }
+        let global = cx.global::<PopupMenuGlobal>().clone();
+        let mut map = global.map.borrow_mut();
+        map.retain( | k, _ | cx.live_registry.borrow().generation_valid(*k));
+        let popup_menu = self.popup_menu.unwrap();
+        map.get_or_insert(cx, popup_menu, | cx | {
+            PopupMenu::new_from_ptr(cx, Some(popup_menu))
+        });
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum DatePickerAction {
+    Changed(Date),
+    None
+}
+
+impl DatePicker {
+
+    pub fn set_open(&mut self, cx: &mut Cx) {
+        self.is_open = true;
+        self.draw_bg.apply_over(cx, live!{open: 1.0});
+        self.move_cursor(cx, self.date.unwrap_or_else(Date::today));
+        cx.sweep_lock(self.draw_bg.area());
+    }
+
+    pub fn set_closed(&mut self, cx: &mut Cx) {
+        self.is_open = false;
+        self.draw_bg.apply_over(cx, live!{open: 0.0});
+        self.draw_bg.redraw(cx);
+        cx.sweep_unlock(self.draw_bg.area());
+    }
+
+    fn move_cursor(&mut self, cx: &mut Cx, cursor: Date) {
+        self.cursor = cursor;
+        if let Some(popup_menu) = self.popup_menu {
+            let global = cx.global::<PopupMenuGlobal>().clone();
+            let mut map = global.map.borrow_mut();
+            if let Some(menu) = map.get_mut(&popup_menu) {
+                menu.init_select_item(LiveId(cursor.day as u64).into());
+                menu.redraw(cx);
+            }
+        }
+        self.draw_bg.redraw(cx);
+    }
+
+    fn select(&mut self, cx: &mut Cx, scope: &mut Scope, date: Date) {
+        self.date = Some(date);
+        cx.widget_action(self.widget_uid(), &scope.path, DatePickerAction::Changed(date));
+        self.set_closed(cx);
+    }
+
+    fn label(&self) -> String {
+        match self.date {
+            Some(date) => date.to_string(),
+            None => self.empty_message.clone()
+        }
+    }
+
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        self.draw_bg.begin(cx, walk, self.layout);
+        let label = self.label();
+        self.draw_text.draw_walk(cx, Walk::fit(), Align::default(), &label);
+        self.draw_bg.end(cx);
+
+        cx.add_nav_stop_with_tab_index(self.draw_bg.area(), NavRole::DropDown, self.tab_index);
+
+        if self.is_open && self.popup_menu.is_some() {
+            let global = cx.global::<PopupMenuGlobal>().clone();
+            let mut map = global.map.borrow_mut();
+            let popup_menu = map.get_mut(&self.popup_menu.unwrap()).unwrap();
+            popup_menu.begin(cx);
+            self.draw_month(cx, popup_menu);
+            let shift = dvec2(0.0, self.draw_bg.area().rect(cx).size.y);
+            popup_menu.end(cx, self.draw_bg.area(), shift);
+        }
+    }
+
+    fn draw_month(&mut self, cx: &mut Cx2d, popup_menu: &mut PopupMenu) {
+        let cell = self.cell_size;
+        let center = Align {x: 0.5, y: 0.5};
+
+        // the month and year between the buttons that page through them
+        cx.begin_turtle(Walk::fit(), Layout::flow_right());
+        self.draw_month_button.next = 0.0;
+        self.draw_month_button.draw_walk(cx, Walk::fixed_size(cell));
+        self.prev_area = self.draw_month_button.area();
+        let title = format!("{} {}", self.month_names.get(self.cursor.month as usize - 1).map(String::as_str).unwrap_or(""), self.cursor.year);
+        self.draw_title.draw_walk(cx, Walk::fixed(cell.x * 5.0, cell.y), center, &title);
+        self.draw_month_button.next = 1.0;
+        self.draw_month_button.draw_walk(cx, Walk::fixed_size(cell));
+        self.next_area = self.draw_month_button.area();
+        cx.end_turtle();
+
+        cx.begin_turtle(Walk::fit(), Layout::flow_right());
+        for column in 0..7 {
+            let weekday = (self.first_day_of_week + column) % 7;
+            let name = self.weekday_names.get(weekday as usize).map(String::as_str).unwrap_or("");
+            self.draw_weekday.draw_walk(cx, Walk::fixed_size(cell), center, name);
+        }
+        cx.end_turtle();
+
+        // always six weeks so the popup keeps its size while paging
+        let first = self.cursor.first_of_month();
+        let lead = (first.weekday() + 7 - self.first_day_of_week % 7) % 7;
+        let days = Date::days_in_month(first.year, first.month);
+        for week in 0..6 {
+            cx.begin_turtle(Walk::fit(), Layout::flow_right());
+            for column in 0..7 {
+                let day = (week * 7 + column) as i64 - lead as i64 + 1;
+                if day < 1 || day > days as i64 {
+                    cx.walk_turtle(Walk::fixed_size(cell));
+                    continue
+                }
+                cx.begin_turtle(Walk::fixed_size(cell), Layout::default());
+                popup_menu.draw_item(cx, LiveId(day as u64).into(), &day.to_string());
+                cx.end_turtle();
+            }
+            cx.end_turtle();
+        }
+    }
+}
+
+impl Widget for DatePicker {
+
+    fn widget_to_data(&self, _cx: &mut Cx, actions: &Actions, nodes: &mut LiveNodeVec, path: &[LiveId]) -> bool {
+        match actions.find_widget_action_cast(self.widget_uid()) {
+            DatePickerAction::Changed(date) => {
+                nodes.write_field_value(path, LiveValue::String(Rc::new(date.to_string())));
+                true
+            }
+            _ => false
+        }
+    }
+
+    fn data_to_widget(&mut self, cx: &mut Cx, nodes: &[LiveNode], path: &[LiveId]) {
+        let date = match nodes.read_field_value(path) {
+            Some(LiveValue::String(v)) => Date::parse(v),
+            Some(LiveValue::Str(v)) => Date::parse(v),
+            Some(LiveValue::InlineString(v)) => Date::parse(v.as_str()),
+            _ => return
+        };
+        if date != self.date {
+            self.date = date;
+            self.redraw(cx);
+        }
+    }
+
+    fn set_data_error(&mut self, cx: &mut Cx, error: Option<&str>) {
+        if self.data_error.as_deref() != error {
+            self.data_error = error.map( | e | e.to_string());
+            let invalid = if error.is_some() {1.0} else {0.0};
+            self.draw_bg.apply_over(cx, live!{invalid: (invalid)});
+            self.draw_bg.redraw(cx);
+        }
+    }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.animator_handle_event(cx, event);
+
+        if self.is_open && self.popup_menu.is_some() {
+            let global = cx.global::<PopupMenuGlobal>().clone();
+            let mut map = global.map.borrow_mut();
+            let menu = map.get_mut(&self.popup_menu.unwrap()).unwrap();
+            let mut selected = None;
+            menu.handle_event_with(cx, event, self.draw_bg.area(), &mut | _, action | {
+                if let PopupMenuAction::WasSelected(node_id) = action {
+                    selected = Some(node_id.0.0 as u32);
+                }
+            });
+            let mut months = 0;
+            for (area, dir) in [(self.prev_area, -1), (self.next_area, 1)] {
+                match event.hits_with_options(cx, area, HitOptions::new().with_sweep_area(self.draw_bg.area())) {
+                    Hit::FingerHoverIn(_) => cx.set_cursor(MouseCursor::Hand),
+                    Hit::FingerDown(_) => months = dir,
+                    _ => ()
+                }
+            }
+            // the picker itself toggles the popup on its own finger down
+            let outside = match event {
+                Event::MouseDown(e) => !menu.menu_contains_pos(cx, e.abs) && !self.draw_bg.area().rect(cx).contains(e.abs),
+                _ => false
+            };
+            drop(map);
+
+            if let Some(date) = selected.and_then( | day | Date::new(self.cursor.year, self.cursor.month, day)) {
+                self.select(cx, scope, date);
+            }
+            else if months != 0 {
+                self.move_cursor(cx, self.cursor.add_months(months));
+            }
+            else if outside {
+                self.set_closed(cx);
+                self.animator_play(cx, id!(hover.off));
+            }
+        }
+
+        match event.hits_with_sweep_area(cx, self.draw_bg.area(), self.draw_bg.area()) {
+            Hit::Trigger(te) if te.0.iter().any( | t | t.id == live_id!(nav_focus)) => {
+                self.animator_play(cx, id!(nav_focus.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animator_play(cx, id!(focus.off));
+                self.animator_play(cx, id!(nav_focus.off));
+                self.set_closed(cx);
+                self.animator_play(cx, id!(hover.off));
+            }
+            Hit::KeyFocus(_) => {
+                self.animator_play(cx, id!(focus.on));
+            }
+            Hit::KeyDown(ke) if self.is_open => {
+                let cursor = self.cursor;
+                match ke.key_code {
+                    KeyCode::ArrowLeft => self.move_cursor(cx, cursor.add_days(-1)),
+                    KeyCode::ArrowRight => self.move_cursor(cx, cursor.add_days(1)),
+                    KeyCode::ArrowUp => self.move_cursor(cx, cursor.add_days(-7)),
+                    KeyCode::ArrowDown => self.move_cursor(cx, cursor.add_days(7)),
+                    KeyCode::PageUp => self.move_cursor(cx, cursor.add_months(-1)),
+                    KeyCode::PageDown => self.move_cursor(cx, cursor.add_months(1)),
+                    KeyCode::Home => self.move_cursor(cx, cursor.first_of_month()),
+                    KeyCode::End => self.move_cursor(cx, Date {day: Date::days_in_month(cursor.year, cursor.month), ..cursor}),
+                    KeyCode::ReturnKey | KeyCode::Space if !ke.is_repeat => self.select(cx, scope, cursor),
+                    KeyCode::Escape => self.set_closed(cx),
+                    _ => ()
+                }
+            }
+            Hit::KeyDown(ke) => match ke.key_code {
+                KeyCode::ReturnKey | KeyCode::Space | KeyCode::ArrowDown if !ke.is_repeat && self.popup_menu.is_some() => {
+                    self.set_open(cx);
+                }
+                _ => ()
+            }
+            Hit::FingerDown(_) => {
+                cx.set_key_focus(self.draw_bg.area());
+                if self.is_open {
+                    self.set_closed(cx);
+                }
+                else if self.popup_menu.is_some() {
+                    self.set_open(cx);
+                }
+                self.animator_play(cx, id!(hover.pressed));
+            },
+            Hit::FingerHoverIn(_) => {
+                cx.set_cursor(MouseCursor::Hand);
+                self.animator_play(cx, id!(hover.on));
+            }
+            Hit::FingerHoverOut(_) => {
+                self.animator_play(cx, id!(hover.off));
+            }
+            Hit::FingerUp(fe) => {
+                if fe.is_over && fe.device.has_hovers() {
+                    self.animator_play(cx, id!(hover.on));
+                }
+                else {
+                    self.animator_play(cx, id!(hover.off));
+                }
+            }
+            _ => ()
+        };
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.draw_walk(cx, walk);
+        DrawStep::done()
+    }
+
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::DateEditor, "");
+        node.value = AccessValue::Text(self.label());
+        node.state.focusable = self.tab_index >= 0;
+        node.state.focused = cx.has_key_focus(self.draw_bg.area());
+        node.rect = self.draw_bg.area().rect(cx);
+        Some(node)
+    }
+
+    fn text(&self) -> String {
+        self.date.map( | date | date.to_string()).unwrap_or_default()
+    }
+
+    fn set_text(&mut self, v: &str) {
+        self.date = Date::parse(v);
+    }
+}
+
+impl DatePickerRef {
+    pub fn date(&self) -> Option<Date> {
+        self.borrow().and_then( | inner | inner.date)
+    }
+
+    pub fn set_date(&self, cx: &mut Cx, date: Option<Date>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            if inner.date != date {
+                inner.date = date;
+                inner.draw_bg.redraw(cx);
+            }
+        }
+    }
+
+    pub fn changed(&self, actions: &Actions) -> Option<Date> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let DatePickerAction::Changed(date) = item.cast() {
+                return Some(date)
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn leap_years_and_month_lengths() {
+        assert!(Date::is_leap_year(2024) && Date::is_leap_year(2000) && Date::is_leap_year(-4));
+        assert!(!Date::is_leap_year(2023) && !Date::is_leap_year(1900) && !Date::is_leap_year(2100));
+        assert_eq!(Date::days_in_month(2024, 2), 29);
+        assert_eq!(Date::days_in_month(1900, 2), 28);
+        assert_eq!(Date::days_in_month(2023, 4), 30);
+        assert_eq!(Date::days_in_month(2023, 12), 31);
+        assert_eq!(Date::new(2024, 2, 29), Some(Date {year: 2024, month: 2, day: 29}));
+        assert_eq!(Date::new(2023, 2, 29), None);
+        assert_eq!(Date::new(2023, 4, 31), None);
+        assert_eq!(Date::new(2023, 13, 1), None);
+        assert_eq!(Date::new(2023, 1, 0), None);
+    }
+
+    #[test]
+    fn days_round_trip_around_the_epoch() {
+        assert_eq!(Date::default().to_days(), 0);
+        assert_eq!(date(1969, 12, 31).to_days(), -1);
+        assert_eq!(date(2000, 3, 1).to_days(), 11017);
+        assert_eq!(date(2024, 2, 29).to_days(), 19782);
+        assert_eq!(date(1600, 1, 1).to_days(), -135140);
+        assert_eq!(Date::from_days(-1), date(1969, 12, 31));
+        assert_eq!(Date::from_days(19783), date(2024, 3, 1));
+        // every day follows the one before it, across leap days and 400 year eras
+        let mut prev = Date::from_days(-200_000);
+        for days in -199_999..200_000 {
+            let next = Date::from_days(days);
+            assert_eq!(next.to_days(), days);
+            let expected = if prev.day < Date::days_in_month(prev.year, prev.month) {
+                Date {day: prev.day + 1, ..prev}
+            }
+            else if prev.month < 12 {
+                date(prev.year, prev.month + 1, 1)
+            }
+            else {
+                date(prev.year + 1, 1, 1)
+            };
+            assert_eq!(next, expected);
+            prev = next;
+        }
+    }
+
+    #[test]
+    fn weekdays_start_on_sunday() {
+        // 1970-01-01 was a thursday
+        assert_eq!(Date::default().weekday(), 4);
+        assert_eq!(date(1969, 12, 28).weekday(), 0);
+        assert_eq!(date(2000, 1, 1).weekday(), 6);
+        assert_eq!(date(2024, 1, 1).weekday(), 1);
+        assert_eq!(date(1900, 3, 1).weekday(), 4);
+        assert_eq!(date(2024, 1, 1).add_days(-1).weekday(), 0);
+    }
+
+    #[test]
+    fn adding_months_clamps_to_the_month_end() {
+        assert_eq!(date(2024, 1, 31).add_months(1), date(2024, 2, 29));
+        assert_eq!(date(2023, 1, 31).add_months(1), date(2023, 2, 28));
+        assert_eq!(date(2024, 3, 31).add_months(-1), date(2024, 2, 29));
+        assert_eq!(date(2024, 5, 31).add_months(1), date(2024, 6, 30));
+        assert_eq!(date(2024, 2, 29).add_months(12), date(2025, 2, 28));
+        // across years in both directions
+        assert_eq!(date(2024, 12, 15).add_months(1), date(2025, 1, 15));
+        assert_eq!(date(2024, 1, 15).add_months(-13), date(2022, 12, 15));
+        assert_eq!(date(1, 1, 1).add_months(-1), date(0, 12, 1));
+        assert_eq!(date(2024, 6, 15).add_months(0), date(2024, 6, 15));
+        assert_eq!(date(2024, 6, 15).first_of_month(), date(2024, 6, 1));
+    }
+
+    #[test]
+    fn parses_iso_dates() {
+        assert_eq!(Date::parse("2024-02-29"), Some(date(2024, 2, 29)));
+        assert_eq!(Date::parse(" 2024-2-5 "), Some(date(2024, 2, 5)));
+        assert_eq!(Date::parse("2023-02-29"), None);
+        assert_eq!(Date::parse("2024-02"), None);
+        assert_eq!(Date::parse("2024/02/01"), None);
+        assert_eq!(Date::parse("yesterday"), None);
+        assert_eq!(date(812, 3, 4).to_string(), "0812-03-04");
+        assert_eq!(Date::parse(&date(2024, 11, 30).to_string()), Some(date(2024, 11, 30)));
+    }
+}
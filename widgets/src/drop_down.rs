@@ -44,12 +44,15 @@ pub struct DropDown {
     
     #[live] selected_item: usize,
     
+    #[live] tab_index: i64,
+    
     #[layout] layout: Layout,
 }
 
+/// The popup menus of all drop downs and date pickers, one per style
 #[derive(Default, Clone)]
-struct PopupMenuGlobal {
-    map: Rc<RefCell<ComponentMap<LivePtr, PopupMenu >> >
+pub (crate) struct PopupMenuGlobal {
+    pub (crate) map: Rc<RefCell<ComponentMap<LivePtr, PopupMenu >> >
 }
 
 #[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
@@ -128,7 +131,7 @@ impl DropDown {
         }
         self.draw_bg.end(cx);
         
-        cx.add_nav_stop(self.draw_bg.area(), NavRole::DropDown, Margin::default());
+        cx.add_nav_stop_with_tab_index(self.draw_bg.area(), NavRole::DropDown, self.tab_index);
         
         if self.is_open && self.popup_menu.is_some() {
             //cx.set_sweep_lock(self.draw_bg.area());
@@ -239,8 +242,12 @@ impl Widget for DropDown {
         }
                 
         match event.hits_with_sweep_area(cx, self.draw_bg.area(), self.draw_bg.area()) {
+            Hit::Trigger(te) if te.0.iter().any(|t| t.id == live_id!(nav_focus)) => {
+                self.animator_play(cx, id!(nav_focus.on));
+            }
             Hit::KeyFocusLost(_) => {
                 self.animator_play(cx, id!(focus.off));
+                self.animator_play(cx, id!(nav_focus.off));
                 self.set_closed(cx);
                 self.animator_play(cx, id!(hover.off));
                 self.draw_bg.redraw(cx);
@@ -265,6 +272,14 @@ impl Widget for DropDown {
                         self.draw_bg.redraw(cx);
                     }
                 },
+                KeyCode::ReturnKey | KeyCode::Space if !ke.is_repeat => {
+                    if self.is_open {
+                        self.set_closed(cx);
+                    }
+                    else if self.popup_menu.is_some() {
+                        self.set_open(cx);
+                    }
+                }
                 _ => ()
             }
             Hit::FingerDown(_fe) => {
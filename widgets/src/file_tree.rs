@@ -94,6 +94,9 @@ pub struct FileTree {
     
     #[live] draw_scroll_shadow: DrawScrollShadow,
     
+    /// One Tab stop for the whole tree, the arrow keys walk its nodes
+    #[live] tab_index: i64,
+    
     #[rust] draw_state: DrawStateWrap<()>,
     
     #[rust] dragging_node_id: Option<LiveId>,
@@ -104,6 +107,15 @@ pub struct FileTree {
     
     #[rust] count: usize,
     #[rust] stack: Vec<f64>,
+    // every node reachable in the last draw in tree order, used for keyboard navigation
+    #[rust] node_order: Vec<FileTreeOrderEntry>,
+}
+
+#[derive(Clone, Copy)]
+struct FileTreeOrderEntry {
+    node_id: LiveId,
+    depth: usize,
+    is_folder: bool,
 }
 
 impl LiveHook for FileTree {
@@ -178,6 +190,7 @@ impl FileTreeNode {
                 right: depth as f64 * 4.0,
                 bottom: 0.0,
             },
+            ..Default::default()
         }
     }
     
@@ -239,6 +252,7 @@ impl FileTree {
     pub fn begin(&mut self, cx: &mut Cx2d, walk: Walk) {
         self.scroll_bars.begin(cx, walk, self.layout);
         self.count = 0;
+        self.node_order.clear();
     }
     
     pub fn end(&mut self, cx: &mut Cx2d) {
@@ -255,6 +269,7 @@ impl FileTree {
         
         self.draw_scroll_shadow.draw(cx, dvec2(0., 0.));
         self.scroll_bars.end(cx);
+        cx.add_nav_stop_with_tab_index(self.scroll_bars.area(), NavRole::Tree, self.tab_index);
         
         let selected_node_id = self.selected_node_id;
         self.tree_nodes.retain_visible_and( | node_id, _ | Some(*node_id) == selected_node_id);
@@ -287,6 +302,7 @@ impl FileTree {
         
         if scale > 0.2 {
             self.count += 1;
+            self.node_order.push(FileTreeOrderEntry {node_id, depth: self.stack.len(), is_folder: true});
         }
         
         let is_open = self.open_nodes.contains(&node_id);
@@ -327,6 +343,7 @@ impl FileTree {
         
         if scale > 0.2 {
             self.count += 1;
+            self.node_order.push(FileTreeOrderEntry {node_id, depth: self.stack.len(), is_folder: false});
         }
         if self.should_node_draw(cx) {
             let file_node = self.file_node;
@@ -372,6 +389,88 @@ impl FileTree {
         }
     }
     
+    fn select_node(&mut self, cx: &mut Cx, node_id: LiveId) {
+        if let Some(last_selected) = self.selected_node_id {
+            if last_selected != node_id {
+                if let Some((node, _)) = self.tree_nodes.get_mut(&last_selected) {
+                    node.set_is_selected(cx, false, Animate::Yes);
+                    node.set_is_focussed(cx, false, Animate::Yes);
+                }
+            }
+        }
+        self.selected_node_id = Some(node_id);
+        if let Some((node, _)) = self.tree_nodes.get_mut(&node_id) {
+            node.set_is_selected(cx, true, Animate::Yes);
+            node.set_is_focussed(cx, true, Animate::Yes);
+        }
+        if let Some(index) = self.node_order.iter().position( | e | e.node_id == node_id) {
+            self.scroll_bars.scroll_into_view(cx, Rect {
+                pos: dvec2(0.0, index as f64 * self.node_height),
+                size: dvec2(0.0, self.node_height)
+            });
+        }
+        self.scroll_bars.redraw(cx);
+    }
+    
+    fn handle_key_down(&mut self, cx: &mut Cx, ke: &KeyEvent, uid: WidgetUid, scope: &mut Scope) {
+        let Some(index) = self.selected_node_id.and_then( | id | self.node_order.iter().position( | e | e.node_id == id)) else {
+            // nothing selected yet, any navigation key starts at the top
+            if let Some(first) = self.node_order.first() {
+                let node_id = first.node_id;
+                self.select_node(cx, node_id);
+            }
+            return
+        };
+        let entry = self.node_order[index];
+        let is_open = self.open_nodes.contains(&entry.node_id);
+        match ke.key_code {
+            KeyCode::ArrowDown => if let Some(next) = self.node_order.get(index + 1) {
+                let node_id = next.node_id;
+                self.select_node(cx, node_id);
+            }
+            KeyCode::ArrowUp if index > 0 => {
+                let node_id = self.node_order[index - 1].node_id;
+                self.select_node(cx, node_id);
+            }
+            KeyCode::Home => if let Some(first) = self.node_order.first() {
+                let node_id = first.node_id;
+                self.select_node(cx, node_id);
+            }
+            KeyCode::End => if let Some(last) = self.node_order.last() {
+                let node_id = last.node_id;
+                self.select_node(cx, node_id);
+            }
+            KeyCode::ArrowRight if entry.is_folder => {
+                if !is_open {
+                    self.set_folder_is_open(cx, entry.node_id, true, Animate::Yes);
+                }
+                else if let Some(child) = self.node_order.get(index + 1).filter( | e | e.depth > entry.depth) {
+                    let node_id = child.node_id;
+                    self.select_node(cx, node_id);
+                }
+            }
+            KeyCode::ArrowLeft => {
+                if entry.is_folder && is_open {
+                    self.set_folder_is_open(cx, entry.node_id, false, Animate::Yes);
+                }
+                else if let Some(parent) = self.node_order[..index].iter().rev().find( | e | e.depth < entry.depth) {
+                    let node_id = parent.node_id;
+                    self.select_node(cx, node_id);
+                }
+            }
+            KeyCode::ReturnKey | KeyCode::Space if !ke.is_repeat => {
+                if entry.is_folder {
+                    self.set_folder_is_open(cx, entry.node_id, !is_open, Animate::Yes);
+                    cx.widget_action(uid, &scope.path, FileTreeAction::FolderClicked(entry.node_id));
+                }
+                else {
+                    cx.widget_action(uid, &scope.path, FileTreeAction::FileClicked(entry.node_id));
+                }
+            }
+            _ => ()
+        }
+    }
+    
     pub fn start_dragging_file_node(
         &mut self,
         cx: &mut Cx,
@@ -440,6 +539,9 @@ impl Widget for FileTree {
         }
                 
         match event.hits(cx, self.scroll_bars.area()) {
+            Hit::KeyDown(ke) => {
+                self.handle_key_down(cx, &ke, uid, scope);
+            }
             Hit::KeyFocus(_) => {
                 if let Some(node_id) = self.selected_node_id {
                     self.tree_nodes.get_mut(&node_id).unwrap().0.set_is_focussed(cx, true, Animate::Yes);
@@ -3,6 +3,7 @@ use crate::{
     widget::*,
     makepad_derive_widget::*,
     makepad_draw::*,
+    view::View,
     scroll_bars::{ScrollBars}
 };
 
@@ -44,6 +45,8 @@ pub struct FlatList {
     #[live(true)] align_top_when_empty: bool,
     #[live(false)] grab_key_focus: bool,
     #[live(true)] drag_scrolling: bool,
+    /// The list takes one Tab stop and the arrow keys move focus between its items
+    #[live] tab_index: i64,
     
     #[rust(Vec2Index::X)] vec_index: Vec2Index,
     #[redraw] #[live] scroll_bars: ScrollBars,
@@ -52,6 +55,9 @@ pub struct FlatList {
     
     #[rust] templates: ComponentMap<LiveId, LivePtr>,
     #[rust] items: ComponentMap<LiveId, (LiveId,WidgetRef)>,
+    // item ids in the order of the last draw, used to move the key focus between them
+    #[rust] drawn_items: Vec<LiveId>,
+    #[rust] focused_item: Option<LiveId>,
     //#[rust(DragState::None)] drag_state: DragState,
     /*#[rust(ScrollState::Stopped)] scroll_state: ScrollState*/
 }
@@ -97,11 +103,71 @@ impl LiveHook for FlatList {
 impl FlatList {
     
     fn begin(&mut self, cx: &mut Cx2d, walk: Walk) {
+        self.drawn_items.clear();
         self.scroll_bars.begin(cx, walk, self.layout);
     }
     
     fn end(&mut self, cx: &mut Cx2d) {
         self.scroll_bars.end(cx);
+        cx.add_nav_stop_with_tab_index(self.scroll_bars.area(), NavRole::List, self.tab_index);
+    }
+    
+    // arrows move the key focus between items, page keys scroll a whole view
+    fn handle_key_down(&mut self, cx: &mut Cx, ke: &KeyEvent) {
+        let step = match ke.key_code {
+            KeyCode::ArrowDown | KeyCode::ArrowRight => 1,
+            KeyCode::ArrowUp | KeyCode::ArrowLeft => -1,
+            _ => 0
+        };
+        if step != 0 && self.focus_item_from(cx, step) {
+            return
+        }
+        // without items the arrows scroll a tenth of the view
+        let vi = self.vec_index;
+        let visible = self.scroll_bars.get_scroll_view_visible().index(vi);
+        let total = self.scroll_bars.get_scroll_view_total().index(vi);
+        let pos = self.scroll_bars.get_scroll_pos().index(vi);
+        let new_pos = match ke.key_code {
+            KeyCode::ArrowDown | KeyCode::ArrowRight => pos + visible * 0.1,
+            KeyCode::ArrowUp | KeyCode::ArrowLeft => pos - visible * 0.1,
+            KeyCode::PageDown => pos + visible,
+            KeyCode::PageUp => pos - visible,
+            KeyCode::Home => 0.0,
+            KeyCode::End => total,
+            _ => return
+        };
+        let mut scroll = self.scroll_bars.get_scroll_pos();
+        scroll.set_index(vi, new_pos);
+        if self.scroll_bars.set_scroll_pos(cx, scroll) {
+            self.scroll_bars.redraw(cx);
+        }
+    }
+
+    /// Moves the focus `step` items on from the focused one, the first press picks the first or last item.
+    /// Items that are views also take the key focus and get scrolled into view.
+    fn focus_item_from(&mut self, cx: &mut Cx, step: isize) -> bool {
+        let Some(last) = self.drawn_items.len().checked_sub(1) else {return false};
+        let current = self.focused_item.and_then( | id | self.drawn_items.iter().position( | item | *item == id));
+        let index = match current {
+            Some(index) => index.saturating_add_signed(step).min(last),
+            None if step > 0 => 0,
+            None => last
+        };
+        let id = self.drawn_items[index];
+        self.focused_item = Some(id);
+        let area = self.items.get(&id).and_then( | (_, item) | item.borrow::<View>().map( | view | view.area()));
+        if let Some(area) = area {
+            cx.set_key_focus(area);
+            self.scroll_bars.scroll_into_view_abs(cx, area.rect(cx));
+        }
+        true
+    }
+
+    // the item holding the key focus, if it is one of ours
+    fn item_with_key_focus(&self, cx: &Cx) -> Option<LiveId> {
+        self.items.iter().find_map( | (id, (_, item)) | {
+            item.borrow::<View>().filter( | view | cx.has_key_focus(view.area())).map( | _ | *id)
+        })
     }
 
     pub fn space_left(&self, cx:&mut Cx2d)->f64{
@@ -115,7 +181,11 @@ impl FlatList {
             let (_, entry) = self.items.get_or_insert(cx, id, | cx | {
                 (template, WidgetRef::new_from_ptr(cx, Some(*ptr)))
             });
-            Some(entry.clone())
+            let entry = entry.clone();
+            if self.draw_state.as_ref().is_some() {
+                self.drawn_items.push(id);
+            }
+            Some(entry)
         }
         else {
             warning!("Template not found: {template}. Did you add it to the <FlatList> instance in `live_design!{{}}`?");
@@ -139,6 +209,16 @@ impl Widget for FlatList {
 
         let uid = self.widget_uid();
         self.scroll_bars.handle_event(cx, event);
+        if let Hit::KeyDown(ke) = event.hits(cx, self.scroll_bars.area()) {
+            self.handle_key_down(cx, &ke);
+        }
+        else if let Event::KeyDown(ke) = event {
+            // keys pressed on a focused item move on from that item
+            if let Some(id) = self.item_with_key_focus(cx) {
+                self.focused_item = Some(id);
+                self.handle_key_down(cx, ke);
+            }
+        }
         /*
         let mut scroll_to = None;
         self.scroll_bars.handle_event_with(cx, event, &mut | _cx, action | {
@@ -338,3 +418,55 @@ impl FlatListSet {
         set
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_list(f: impl FnOnce(&mut Cx, &mut FlatList)) {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let mut list = FlatList::new(&mut cx);
+        f(&mut cx, &mut list);
+    }
+
+    fn key(cx: &mut Cx, list: &mut FlatList, key_code: KeyCode) {
+        let ke = KeyEvent {key_code, is_repeat: false, modifiers: KeyModifiers::default(), time: 0.0};
+        list.handle_key_down(cx, &ke);
+    }
+
+    #[test]
+    fn arrow_keys_move_focus_between_items() {
+        with_list( | cx, list | {
+            list.drawn_items = vec![live_id!(a), live_id!(b), live_id!(c)];
+            key(cx, list, KeyCode::ArrowDown);
+            assert_eq!(list.focused_item, Some(live_id!(a)));
+            key(cx, list, KeyCode::ArrowDown);
+            key(cx, list, KeyCode::ArrowRight);
+            assert_eq!(list.focused_item, Some(live_id!(c)));
+            // the ends hold on to the focus
+            key(cx, list, KeyCode::ArrowDown);
+            assert_eq!(list.focused_item, Some(live_id!(c)));
+            key(cx, list, KeyCode::ArrowUp);
+            key(cx, list, KeyCode::ArrowLeft);
+            key(cx, list, KeyCode::ArrowUp);
+            assert_eq!(list.focused_item, Some(live_id!(a)));
+            // a focused item that is no longer drawn starts over from the end the key points to
+            list.focused_item = Some(live_id!(gone));
+            key(cx, list, KeyCode::ArrowUp);
+            assert_eq!(list.focused_item, Some(live_id!(c)));
+            // page keys keep scrolling and leave the focus alone
+            key(cx, list, KeyCode::PageDown);
+            assert_eq!(list.focused_item, Some(live_id!(c)));
+        })
+    }
+
+    #[test]
+    fn arrow_keys_without_items_focus_nothing() {
+        with_list( | cx, list | {
+            key(cx, list, KeyCode::ArrowDown);
+            assert_eq!(list.focused_item, None);
+        })
+    }
+}
@@ -18,15 +18,23 @@ pub struct Label {
     #[live] padding: Padding,
     //margin: Margin,
     #[live] text: RcStringMut,
+    #[rust] area: Area,
 } 
 
 impl Widget for Label {
 
     fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk:Walk)->DrawStep{
-        self.draw_text.draw_walk(cx, walk.with_add_padding(self.padding), self.align, self.text.as_ref());
+        let rect = self.draw_text.draw_walk(cx, walk.with_add_padding(self.padding), self.align, self.text.as_ref());
+        cx.add_aligned_rect_area(&mut self.area, rect);
         DrawStep::done()
     }
     
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::Label, self.text.as_ref());
+        node.rect = self.area.rect(cx);
+        Some(node)
+    }
+    
     fn text(&self)->String{
         self.text.as_ref().to_string()
     }
@@ -34,4 +42,20 @@ impl Widget for Label {
     fn set_text(&mut self, v:&str){
         self.text.as_mut_empty().push_str(v);
     }
+    
+    fn data_to_widget(&mut self, cx: &mut Cx, nodes: &[LiveNode], path: &[LiveId]) {
+        let text = match nodes.read_field_value(path) {
+            Some(LiveValue::String(v)) => v.to_string(),
+            Some(LiveValue::Str(v)) => v.to_string(),
+            Some(LiveValue::InlineString(v)) => v.as_str().to_string(),
+            Some(LiveValue::Bool(v)) => format!("{}", v),
+            Some(LiveValue::Int64(v)) => format!("{}", v),
+            Some(v) if v.is_number_type() => format!("{}", v.as_float().unwrap_or(0.0)),
+            _ => return
+        };
+        if text != self.text.as_ref() {
+            self.set_text(&text);
+            self.redraw(cx);
+        }
+    }
 }
@@ -13,11 +13,15 @@ pub mod image_blend;
 pub mod icon;
 pub mod link_label;
 pub mod drop_down;
+pub mod date_picker;
+pub mod time_picker;
 pub mod popup_menu;
 pub mod check_box;
 pub mod radio_button;
 pub mod text_input;
+pub mod text_pattern;
 pub mod slider;
+pub mod number_input;
 pub mod scroll_bar;
 pub mod scroll_bars;
 pub mod splitter;
@@ -30,6 +34,7 @@ pub mod tab;
 pub mod tab_bar;
 pub mod tab_close_button;
 pub mod portal_list;
+pub mod data_grid;
 pub mod stack_navigation;
 pub mod expandable_panel;
 pub mod desktop_button;
@@ -48,6 +53,7 @@ pub mod page_flip;
 pub mod keyboard_view;
 pub mod flat_list;
 pub mod file_tree;
+pub mod tree_view;
 pub mod slides_view;
 pub mod color_picker;
 pub mod root;
@@ -64,6 +70,7 @@ pub mod touch_gesture;
 
 #[macro_use]
 pub mod data_binding;
+pub mod data_model;
 
 pub mod base;
 pub mod theme_desktop_dark;
@@ -80,6 +87,7 @@ pub mod designer_toolbox;
 
 pub use crate::{
     data_binding::{DataBindingStore, DataBindingMap},
+    data_model::{DataModel, DataField, DataError, DataModelStore, data_changes},
     button::*,
     view::*,
     image::*,
@@ -87,16 +95,21 @@ pub use crate::{
     icon::*,
     label::*,
     slider::*,
+    number_input::*,
     root::*,
     text_flow::*,
     html::*,
     check_box::*,
     drop_down::*,
+    date_picker::*,
+    time_picker::*,
     video::*,
     radio_button::*,
     text_input::*,
     link_label::*,
     portal_list::*,
+    data_grid::*,
+    tree_view::*,
     flat_list::*,
     page_flip::*,
     slide_panel::*,
@@ -145,6 +158,7 @@ pub fn live_design(cx: &mut Cx) {
     crate::base::live_design(cx);
     crate::theme_desktop_dark::live_design(cx);
     crate::slider::live_design(cx);
+    crate::number_input::live_design(cx);
     crate::label::live_design(cx);
     crate::nav_control::live_design(cx);
     crate::image::live_design(cx);
@@ -167,15 +181,19 @@ pub fn live_design(cx: &mut Cx) {
     crate::radio_button::live_design(cx);
     crate::popup_menu::live_design(cx);
     crate::drop_down::live_design(cx);
+    crate::date_picker::live_design(cx);
+    crate::time_picker::live_design(cx);
     crate::multi_window::live_design(cx);
     crate::portal_list::live_design(cx);
     crate::flat_list::live_design(cx);
+    crate::data_grid::live_design(cx);
     crate::slide_panel::live_design(cx);
     crate::tab::live_design(cx);
     crate::tab_bar::live_design(cx);
     crate::dock::live_design(cx);
     crate::color_picker::live_design(cx);
     crate::file_tree::live_design(cx);
+    crate::tree_view::live_design(cx);
     crate::slides_view::live_design(cx);
     crate::tab_close_button::live_design(cx);
     crate::keyboard_view::live_design(cx);
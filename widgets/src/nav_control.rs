@@ -33,37 +33,8 @@ impl NavControl {
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, root: DrawListId) {
         match event {
             Event::KeyDown(ke) => match ke.key_code {
-                KeyCode::Tab => {
-                    if ke.modifiers.shift {
-                        let mut prev_area = Area::Empty;
-                        if let Some((prev_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | cx, stop | {
-                            if cx.has_key_focus(stop.area) {
-                                return Some(prev_area);
-                            }
-                            prev_area = stop.area;
-                            None
-                        }) {
-                            if !prev_area.is_empty() {
-                                Self::send_trigger_to_scroll_stack(cx, scroll_stack);
-                                cx.set_key_focus(prev_area);
-                            }
-                        }
-                    }
-                    else {
-                        let mut next_stop = false;
-                        if let Some((next_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | cx, stop | {
-                            if next_stop {
-                                return Some(stop.area)
-                            }
-                            if cx.has_key_focus(stop.area) {
-                                next_stop = true;
-                            }
-                            None
-                        }) {
-                            Self::send_trigger_to_scroll_stack(cx, scroll_stack);
-                            cx.set_key_focus(next_area);
-                        }
-                    }
+                KeyCode::Tab if !ke.modifiers.control && !ke.modifiers.logo && !ke.modifiers.alt => {
+                    self.move_focus(cx, root, !ke.modifiers.shift);
                 }
                 _ => ()
             },
@@ -71,6 +42,37 @@ impl NavControl {
         }
     }
     
+    /// Moves key focus to the next or previous nav stop in Tab order, wrapping around at the ends.
+    pub fn move_focus(&mut self, cx: &mut Cx, root: DrawListId, forward: bool) {
+        let mut stops = Cx2d::nav_stops_in_tab_order(cx, root);
+        if stops.is_empty() {
+            return
+        }
+        let focus_area = cx.key_focus();
+        let focus = stops.iter().position( | (stop, _) | stop.area == focus_area).or_else( || {
+            // focus can sit on a part of a widget that is not a stop itself, like the text of a slider
+            if focus_area.is_empty() || !focus_area.is_valid(cx) {
+                return None
+            }
+            let center = focus_area.rect(cx).center();
+            stops.iter().position( | (stop, _) | stop.area.rect(cx).contains(center))
+        });
+        let index = match (focus, forward) {
+            (Some(i), true) => (i + 1) % stops.len(),
+            (Some(i), false) => (i + stops.len() - 1) % stops.len(),
+            (None, true) => 0,
+            (None, false) => stops.len() - 1,
+        };
+        let (stop, scroll_stack) = stops.swap_remove(index);
+        Self::send_trigger_to_scroll_stack(cx, scroll_stack);
+        // lets the widget know focus came from the keyboard so it can show its focus ring
+        cx.send_trigger(stop.area, Trigger {
+            id: live_id!(nav_focus),
+            from: Area::Empty
+        });
+        cx.set_key_focus(stop.area);
+    }
+    
     pub fn draw(&mut self, cx: &mut Cx2d) {
         if !self.draw_list.begin(cx, Walk::default()).is_redrawing() {
             return
@@ -0,0 +1,399 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        widget::*,
+        text_input::{TextInput, TextInputAction}
+    }
+};
+
+live_design!{
+    DrawNumberInput = {{DrawNumberInput}} {}
+    NumberInputBase = {{NumberInput}} {}
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawNumberInput {
+    #[deref] draw_super: DrawQuad,
+    #[live] stepper_width: f32,
+}
+
+#[derive(Live, Widget)]
+pub struct NumberInput {
+    #[redraw] #[live] draw_bg: DrawNumberInput,
+    #[live] draw_unit: DrawText,
+
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+    #[animator] animator: Animator,
+
+    #[live] text_input: TextInput,
+    /// Drawn after the value, e.g. `" dB"`, typing it back in is optional
+    #[live] unit: String,
+
+    #[live] precision: usize,
+    #[live] min: f64,
+    #[live] max: f64,
+    /// Values snap to `min` plus a multiple of the step, 0 moves in hundredths of the range
+    #[live] step: f64,
+    #[live] default: f64,
+    /// Pixels the finger moves for one step while dragging
+    #[live] drag_distance: f64,
+    /// The up and down buttons at the right edge
+    #[live] stepper_width: f64,
+
+    #[live] tab_index: i64,
+
+    #[rust] value: f64,
+    #[rust] dragging: Option<f64>,
+    #[rust] data_error: Option<String>,
+}
+
+impl LiveHook for NumberInput {
+    fn after_new_from_doc(&mut self, _cx: &mut Cx) {
+        self.value = self.snap(self.default);
+        self.update_text_input();
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum NumberInputAction {
+    StartDrag,
+    Changed(f64),
+    EndDrag,
+    None
+}
+
+impl NumberInput {
+
+    fn step_size(&self) -> f64 {
+        if self.step > 0.0 {self.step} else {(self.max - self.min) / 100.0}
+    }
+
+    fn snap(&self, value: f64) -> f64 {
+        let value = if self.step > 0.0 {
+            // a max that isn't on a step clamps to the last step below it
+            let last = ((self.max - self.min) / self.step + 1e-9).floor();
+            self.min + ((value - self.min) / self.step).round().min(last) * self.step
+        }
+        else {
+            value
+        };
+        value.max(self.min).min(self.max)
+    }
+
+    pub fn update_text_input(&mut self) {
+        self.text_input.text = format!("{:.*}", self.precision, self.value);
+        self.text_input.select_all();
+    }
+
+    pub fn update_text_input_and_redraw(&mut self, cx: &mut Cx) {
+        self.update_text_input();
+        self.text_input.redraw(cx);
+    }
+
+    fn set_value_action(&mut self, cx: &mut Cx, scope: &mut Scope, value: f64) {
+        let value = self.snap(value);
+        if value != self.value {
+            self.value = value;
+            self.update_text_input_and_redraw(cx);
+            cx.widget_action(self.widget_uid(), &scope.path, NumberInputAction::Changed(value));
+        }
+    }
+
+    /// Takes over typed text, the unit may be typed along with the number
+    fn commit_text(&mut self, cx: &mut Cx, scope: &mut Scope, text: &str) {
+        let text = text.trim();
+        let text = text.strip_suffix(self.unit.trim()).unwrap_or(text);
+        if let Ok(value) = text.trim().parse::<f64>() {
+            self.set_value_action(cx, scope, value);
+        }
+        self.text_input.read_only = true;
+        self.update_text_input_and_redraw(cx);
+    }
+
+    fn start_editing(&mut self, cx: &mut Cx) {
+        self.text_input.read_only = false;
+        self.text_input.set_key_focus(cx);
+        self.text_input.select_all();
+        self.text_input.redraw(cx);
+    }
+
+    fn handle_text_input_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for action in cx.capture_actions( | cx | self.text_input.handle_event(cx, event, scope)) {
+            match action.as_widget_action().cast() {
+                TextInputAction::KeyFocus => {
+                    self.animator_play(cx, id!(focus.on));
+                }
+                TextInputAction::KeyFocusLost => {
+                    self.animator_play(cx, id!(focus.off));
+                    let text = self.text_input.text.clone();
+                    self.commit_text(cx, scope, &text);
+                }
+                TextInputAction::Return(text) => {
+                    self.commit_text(cx, scope, &text);
+                    cx.set_key_focus(self.draw_bg.area());
+                }
+                TextInputAction::Escape => {
+                    self.text_input.read_only = true;
+                    self.update_text_input_and_redraw(cx);
+                    cx.set_key_focus(self.draw_bg.area());
+                }
+                _ => ()
+            }
+        }
+    }
+
+    pub fn draw_walk_number_input(&mut self, cx: &mut Cx2d, walk: Walk) {
+        self.draw_bg.stepper_width = self.stepper_width as f32;
+        self.draw_bg.begin(cx, walk, self.layout);
+        let text_walk = self.text_input.walk(cx);
+        self.text_input.draw_walk_text_input(cx, text_walk);
+        self.draw_unit.draw_walk(cx, Walk::fit(), Align::default(), &self.unit);
+        self.draw_bg.end(cx);
+        cx.add_nav_stop_with_tab_index(self.draw_bg.area(), NavRole::Slider, self.tab_index);
+    }
+}
+
+impl Widget for NumberInput {
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        self.animator_handle_event(cx, event);
+
+        // while editing the text input gets the finger first, otherwise a press starts a drag
+        let editing = !self.text_input.read_only;
+        if editing {
+            self.handle_text_input_event(cx, event, scope);
+        }
+
+        match event.hits(cx, self.draw_bg.area()) {
+            Hit::FingerHoverIn(_) => {
+                cx.set_cursor(MouseCursor::EwResize);
+                self.animator_play(cx, id!(hover.on));
+            }
+            Hit::FingerHoverOut(_) => {
+                self.animator_play(cx, id!(hover.off));
+            }
+            Hit::Trigger(te) if te.0.iter().any( | t | t.id == live_id!(nav_focus)) => {
+                self.animator_play(cx, id!(nav_focus.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animator_play(cx, id!(nav_focus.off));
+            }
+            Hit::KeyDown(ke) => {
+                let step = self.step_size();
+                match ke.key_code {
+                    KeyCode::ArrowUp | KeyCode::ArrowRight => self.set_value_action(cx, scope, self.value + step),
+                    KeyCode::ArrowDown | KeyCode::ArrowLeft => self.set_value_action(cx, scope, self.value - step),
+                    KeyCode::PageUp => self.set_value_action(cx, scope, self.value + step * 10.0),
+                    KeyCode::PageDown => self.set_value_action(cx, scope, self.value - step * 10.0),
+                    KeyCode::Home => self.set_value_action(cx, scope, self.min),
+                    KeyCode::End => self.set_value_action(cx, scope, self.max),
+                    KeyCode::ReturnKey | KeyCode::Space if !ke.is_repeat => self.start_editing(cx),
+                    _ => ()
+                }
+            }
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.draw_bg.area());
+                let rel = fe.abs - fe.rect.pos;
+                if rel.x >= fe.rect.size.x - self.stepper_width {
+                    let dir = if rel.y < fe.rect.size.y * 0.5 {1.0} else {-1.0};
+                    self.set_value_action(cx, scope, self.value + dir * self.step_size());
+                }
+                else {
+                    self.animator_play(cx, id!(drag.on));
+                    self.dragging = Some(self.value);
+                    cx.widget_action(uid, &scope.path, NumberInputAction::StartDrag);
+                }
+            }
+            Hit::FingerMove(fe) => {
+                if let Some(start) = self.dragging {
+                    // right and up both count up
+                    let rel = fe.abs - fe.abs_start;
+                    let steps = (rel.x - rel.y) / self.drag_distance.max(1.0);
+                    self.set_value_action(cx, scope, start + steps * self.step_size());
+                }
+            }
+            Hit::FingerUp(fe) => {
+                if self.dragging.take().is_some() {
+                    self.animator_play(cx, id!(drag.off));
+                    cx.widget_action(uid, &scope.path, NumberInputAction::EndDrag);
+                    // a click without dragging types a new value
+                    if fe.is_over && fe.was_tap() {
+                        self.start_editing(cx);
+                    }
+                }
+                if fe.is_over && fe.device.has_hovers() {
+                    self.animator_play(cx, id!(hover.on));
+                }
+                else {
+                    self.animator_play(cx, id!(hover.off));
+                }
+            }
+            _ => ()
+        }
+        if !editing {
+            self.handle_text_input_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.draw_walk_number_input(cx, walk);
+        DrawStep::done()
+    }
+
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::SpinButton, "");
+        node.value = AccessValue::Number {
+            value: self.value,
+            min: self.min,
+            max: self.max,
+            step: self.step,
+        };
+        node.state.focusable = self.tab_index >= 0;
+        node.state.focused = cx.has_key_focus(self.draw_bg.area());
+        node.rect = self.draw_bg.area().rect(cx);
+        Some(node)
+    }
+
+    fn widget_to_data(&self, _cx: &mut Cx, actions: &Actions, nodes: &mut LiveNodeVec, path: &[LiveId]) -> bool {
+        match actions.find_widget_action_cast(self.widget_uid()) {
+            NumberInputAction::Changed(v) => {
+                nodes.write_field_value(path, LiveValue::Float64(v));
+                true
+            }
+            _ => false
+        }
+    }
+
+    fn data_to_widget(&mut self, cx: &mut Cx, nodes: &[LiveNode], path: &[LiveId]) {
+        if let Some(value) = nodes.read_field_value(path).and_then( | v | v.as_float()) {
+            let value = self.snap(value);
+            if value != self.value {
+                self.value = value;
+                self.update_text_input_and_redraw(cx);
+            }
+        }
+    }
+
+    fn set_data_error(&mut self, cx: &mut Cx, error: Option<&str>) {
+        if self.data_error.as_deref() != error {
+            self.data_error = error.map( | e | e.to_string());
+            let invalid = if error.is_some() {1.0} else {0.0};
+            self.draw_bg.apply_over(cx, live!{invalid: (invalid)});
+            self.draw_bg.redraw(cx);
+        }
+    }
+
+    fn text(&self) -> String {
+        format!("{}", self.value)
+    }
+
+    fn set_text(&mut self, v: &str) {
+        if let Ok(v) = v.parse::<f64>() {
+            self.value = self.snap(v);
+            self.update_text_input()
+        }
+    }
+}
+
+impl NumberInputRef {
+    pub fn value(&self) -> Option<f64> {
+        self.borrow().map( | inner | inner.value)
+    }
+
+    pub fn set_value(&self, cx: &mut Cx, value: f64) {
+        if let Some(mut inner) = self.borrow_mut() {
+            let value = inner.snap(value);
+            if value != inner.value {
+                inner.value = value;
+                inner.update_text_input_and_redraw(cx);
+            }
+        }
+    }
+
+    pub fn changed(&self, actions: &Actions) -> Option<f64> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let NumberInputAction::Changed(v) = item.cast() {
+                return Some(v)
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_input(f: impl FnOnce(&mut Cx, &mut NumberInput)) {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let mut input = NumberInput::new(&mut cx);
+        input.min = -10.0;
+        input.max = 10.0;
+        input.step = 0.5;
+        input.precision = 1;
+        input.unit = " dB".to_string();
+        f(&mut cx, &mut input);
+    }
+
+    // the changes typing `text` makes, and the value after
+    fn commit(cx: &mut Cx, input: &mut NumberInput, text: &str) -> (Vec<f64>, f64) {
+        let actions = cx.capture_actions( | cx | input.commit_text(cx, &mut Scope::empty(), text));
+        let changes = actions.iter().filter_map( | action | match action.as_widget_action().cast() {
+            NumberInputAction::Changed(value) => Some(value),
+            _ => None
+        }).collect();
+        (changes, input.value)
+    }
+
+    #[test]
+    fn snaps_to_steps_from_min_and_clamps() {
+        with_input( | _, input | {
+            assert_eq!(input.snap(3.3), 3.5);
+            assert_eq!(input.snap(-3.3), -3.5);
+            assert_eq!(input.snap(25.0), 10.0);
+            assert_eq!(input.snap(-10.2), -10.0);
+            // steps count from min, not from zero
+            input.min = 1.0;
+            input.step = 2.0;
+            assert_eq!(input.snap(4.2), 5.0);
+            assert_eq!(input.snap(3.9), 3.0);
+            assert_eq!(input.snap(10.0), 9.0);
+            assert_eq!(input.snap(100.0), 9.0);
+            // without a step values are only clamped, and the arrows move in hundredths
+            input.step = 0.0;
+            assert_eq!(input.snap(4.37), 4.37);
+            assert_eq!(input.snap(-4.0), 1.0);
+            assert_eq!(input.step_size(), 0.09);
+        })
+    }
+
+    #[test]
+    fn typed_text_parses_with_or_without_the_unit() {
+        with_input( | cx, input | {
+            assert_eq!(commit(cx, input, "3"), (vec![3.0], 3.0));
+            assert_eq!(input.text_input.text, "3.0");
+            assert_eq!(commit(cx, input, " -4.2 dB "), (vec![-4.0], -4.0));
+            assert_eq!(commit(cx, input, "6dB"), (vec![6.0], 6.0));
+            // out of range input is clamped, the same value again changes nothing
+            assert_eq!(commit(cx, input, "1e3"), (vec![10.0], 10.0));
+            assert_eq!(commit(cx, input, "10.1"), (vec![], 10.0));
+            // text that isn't a number keeps the value and shows it again
+            input.text_input.text = "abc".to_string();
+            assert_eq!(commit(cx, input, "abc"), (vec![], 10.0));
+            assert_eq!(input.text_input.text, "10.0");
+            assert_eq!(commit(cx, input, "5 Hz"), (vec![], 10.0));
+            assert!(input.text_input.read_only);
+            // set_text goes through the same snapping, without an action
+            input.set_text("2.74");
+            assert_eq!(input.value, 2.5);
+            input.set_text("two");
+            assert_eq!(input.value, 2.5);
+        })
+    }
+}
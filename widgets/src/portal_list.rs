@@ -62,6 +62,8 @@ pub struct PortalList {
     #[live(true)] align_top_when_empty: bool,
     #[live(false)] grab_key_focus: bool,
     #[live(true)] drag_scrolling: bool,
+    /// One Tab stop for the list, the arrow keys scroll it an item at a time
+    #[live] tab_index: i64,
     #[rust] first_id: usize,
     #[rust] first_scroll: f64,
     #[rust(Vec2Index::X)] vec_index: Vec2Index,
@@ -299,6 +301,7 @@ impl PortalList {
         }
 
         cx.end_turtle_with_area(&mut self.area);
+        cx.add_nav_stop_with_tab_index(self.area, NavRole::List, self.tab_index);
     }
     
     pub fn next_visible_item(&mut self, cx: &mut Cx2d) -> Option<usize> {
@@ -319,7 +322,8 @@ impl PortalList {
                                 abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + self.first_scroll)),
                                 margin: Default::default(),
                                 width: Size::Fill,
-                                height: Size::Fit
+                                height: Size::Fit,
+                                ..Default::default()
                             }, layout);
                         }
                         Vec2Index::X => {
@@ -327,7 +331,8 @@ impl PortalList {
                                 abs_pos: Some(dvec2(viewport.pos.x + self.first_scroll, viewport.pos.y)),
                                 margin: Default::default(),
                                 width: Size::Fit,
-                                height: Size::Fill
+                                height: Size::Fill,
+                                ..Default::default()
                             }, layout);
                         }
                     }
@@ -360,7 +365,8 @@ impl PortalList {
                                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                                         margin: Default::default(),
                                         width: Size::Fill,
-                                        height: Size::Fit
+                                        height: Size::Fit,
+                                        ..Default::default()
                                     }, layout);
                                 }
                                 Vec2Index::X => {
@@ -368,7 +374,8 @@ impl PortalList {
                                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                                         margin: Default::default(),
                                         width: Size::Fit,
-                                        height: Size::Fill
+                                        height: Size::Fill,
+                                        ..Default::default()
                                     }, layout);
                                 }
                             }
@@ -399,7 +406,8 @@ impl PortalList {
                                 abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + pos + rect.size.index(vi))),
                                 margin: Default::default(),
                                 width: Size::Fill,
-                                height: Size::Fit
+                                height: Size::Fit,
+                                ..Default::default()
                             }, layout);
                         }
                         Vec2Index::X => {
@@ -407,7 +415,8 @@ impl PortalList {
                                 abs_pos: Some(dvec2(viewport.pos.x + pos + rect.size.index(vi), viewport.pos.y)),
                                 margin: Default::default(),
                                 width: Size::Fit,
-                                height: Size::Fill
+                                height: Size::Fill,
+                                ..Default::default()
                             }, layout);
                         }
                     }
@@ -441,7 +450,8 @@ impl PortalList {
                                     abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y + total_height)),
                                     margin: Default::default(),
                                     width: Size::Fill,
-                                    height: Size::Fit
+                                    height: Size::Fit,
+                                    ..Default::default()
                                 }, Layout::flow_down());
                                 return Some(last_index + 1);
                             }
@@ -466,7 +476,8 @@ impl PortalList {
                         abs_pos: Some(dvec2(viewport.pos.x, viewport.pos.y)),
                         margin: Default::default(),
                         width: Size::Fill,
-                        height: Size::Fit
+                        height: Size::Fit,
+                        ..Default::default()
                     }, Layout::flow_down());
                     
                     return Some(index - 1);
@@ -514,6 +525,15 @@ impl PortalList {
         }
     }
 
+    pub fn first_id(&self) -> usize {
+        self.first_id
+    }
+    
+    pub fn set_first_id_and_scroll(&mut self, id: usize, s: f64) {
+        self.first_id = id;
+        self.first_scroll = s;
+    }
+    
     /// Returns `true` if a widget already exists for the given `entry_id` and `template`.
     pub fn contains_item(&self, entry_id: usize, template: LiveId) -> bool {
         self.items.contains_key(&(entry_id, template))
@@ -788,6 +808,14 @@ impl Widget for PortalList {
         }
         DrawStep::done()
     }
+    
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::List, "");
+        node.state.focusable = self.tab_index >= 0;
+        node.state.focused = cx.has_key_focus(self.area);
+        node.rect = self.area.rect(cx);
+        Some(node)
+    }
 }
 
 impl PortalListRef {
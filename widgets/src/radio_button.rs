@@ -69,6 +69,8 @@ pub struct RadioButton {
     #[live] text: RcStringMut,
     
     #[live] bind: String,
+    
+    #[live] tab_index: i64,
 }
 
 #[derive(Clone, Debug, DefaultNone)]
@@ -97,8 +99,15 @@ impl RadioButton {
         }
         self.draw_text.draw_walk(cx, self.label_walk, self.label_align, self.text.as_ref());
         self.draw_radio.end(cx);
+        cx.add_nav_stop_with_tab_index(self.draw_radio.area(), NavRole::RadioButton, self.tab_index);
+    }
+    
+    fn select(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        if self.animator_in_state(cx, id!(selected.off)) {
+            self.animator_play(cx, id!(selected.on));
+            cx.widget_action(self.widget_uid(), &scope.path, RadioButtonAction::Clicked);
+        }
     }
-        
 }
 
 impl Widget for RadioButtonGroup {
@@ -119,7 +128,6 @@ impl Widget for RadioButtonGroup {
 impl Widget for RadioButton {
     
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
-        let uid = self.widget_uid();
         self.animator_handle_event(cx, event);
                 
         match event.hits(cx, self.draw_radio.area()) {
@@ -132,11 +140,17 @@ impl Widget for RadioButton {
                 self.animator_play(cx, id!(hover.off));
             },
             Hit::FingerDown(_fe) => {
-                if self.animator_in_state(cx, id!(selected.off)) {
-                    self.animator_play(cx, id!(selected.on));
-                    cx.widget_action(uid, &scope.path, RadioButtonAction::Clicked);
-                }
+                self.select(cx, scope);
             },
+            Hit::Trigger(te) if te.0.iter().any(|t| t.id == live_id!(nav_focus)) => {
+                self.animator_play(cx, id!(nav_focus.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animator_play(cx, id!(nav_focus.off));
+            }
+            Hit::KeyDown(ke) if !ke.is_repeat && matches!(ke.key_code, KeyCode::ReturnKey | KeyCode::Space) => {
+                self.select(cx, scope);
+            }
             Hit::FingerUp(_fe) => {
                                 
             }
@@ -34,6 +34,7 @@ pub struct ScrollBar {
     #[rust] scroll_target: f64,
     #[rust] scroll_delta: f64,
     #[rust] drag_point: Option<f64>, // the point in pixels where we are dragging
+    #[rust] rtl: bool, // a horizontal bar in a right to left view starts on the right
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
@@ -86,9 +87,33 @@ impl ScrollBar {
         changed
     }
     
+    // the normalized scroll position as drawn, mirrored for a right to left horizontal bar
+    fn get_drawn_scroll_pos(&self) -> (f64, f64) {
+        let (norm_scroll, norm_handle) = self.get_normalized_scroll_pos();
+        if self.is_mirrored() {
+            (1.0 - norm_scroll - norm_handle, norm_handle)
+        }
+        else {
+            (norm_scroll, norm_handle)
+        }
+    }
+    
+    fn is_mirrored(&self) -> bool {
+        self.rtl && matches!(self.axis, ScrollAxis::Horizontal)
+    }
+    
+    // finger position along the bar, measured from the side the scroll position starts at
+    fn finger_along_bar(&self, fe_abs: DVec2, rect: Rect) -> f64 {
+        let rel = fe_abs - rect.pos;
+        match self.axis {
+            ScrollAxis::Horizontal => if self.rtl {rect.size.x - rel.x} else {rel.x},
+            ScrollAxis::Vertical => rel.y
+        }
+    }
+    
     // writes the norm_scroll value into the shader
     pub fn update_shader_scroll_pos(&mut self, cx: &mut Cx) {
-        let (norm_scroll, _) = self.get_normalized_scroll_pos();
+        let (norm_scroll, _) = self.get_drawn_scroll_pos();
         self.draw_bar.apply_over(cx, live!{
             norm_scroll: (norm_scroll)
         });
@@ -231,7 +256,9 @@ impl ScrollBar {
                     ScrollAxis::Vertical => e.handled_y.get()
                 } {
                     let scroll = match self.axis {
-                        ScrollAxis::Horizontal => if self.use_vertical_finger_scroll {e.scroll.y}else {e.scroll.x},
+                        ScrollAxis::Horizontal => if self.use_vertical_finger_scroll {e.scroll.y}
+                        else if self.rtl {-e.scroll.x}
+                        else {e.scroll.x},
                         ScrollAxis::Vertical => e.scroll.y
                     };
                     if !self.smoothing.is_none() && e.is_mouse {
@@ -276,11 +303,7 @@ impl ScrollBar {
             match event.hits(cx, self.draw_bar.area()) {
                 Hit::FingerDown(fe) => {
                     self.animator_play(cx, id!(hover.pressed));
-                    let rel = fe.abs - fe.rect.pos;
-                    let rel = match self.axis {
-                        ScrollAxis::Horizontal => rel.x,
-                        ScrollAxis::Vertical => rel.y
-                    };
+                    let rel = self.finger_along_bar(fe.abs, fe.rect);
                     let (norm_scroll, norm_handle) = self.get_normalized_scroll_pos();
                     let bar_start = norm_scroll * self.scroll_size;
                     let bar_size = norm_handle * self.scroll_size;
@@ -311,25 +334,14 @@ impl ScrollBar {
                     return;
                 },
                 Hit::FingerMove(fe) => {
-                    let rel = fe.abs - fe.rect.pos;
+                    let rel = self.finger_along_bar(fe.abs, fe.rect);
                     // helper called by event code to scroll from a finger
                     if self.drag_point.is_none() {
                         // state should never occur.
                         //println!("Invalid state in scrollbar, fingerMove whilst drag_point is none")
                     }
-                    else {
-                        match self.axis {
-                            ScrollAxis::Horizontal => {
-                                if self.set_scroll_pos_from_finger(rel.x - self.drag_point.unwrap()){
-                                    dispatch_action(cx, self.make_scroll_action());
-                                }
-                            },
-                            ScrollAxis::Vertical => {
-                                if self.set_scroll_pos_from_finger(rel.y - self.drag_point.unwrap()){
-                                    dispatch_action(cx, self.make_scroll_action());
-                                }
-                            }
-                        }
+                    else if self.set_scroll_pos_from_finger(rel - self.drag_point.unwrap()){
+                        dispatch_action(cx, self.make_scroll_action());
                     }
                  },
                 _ => ()
@@ -340,6 +352,9 @@ impl ScrollBar {
     pub fn draw_scroll_bar(&mut self, cx: &mut Cx2d, axis: ScrollAxis, view_rect: Rect, view_total: DVec2) -> f64 {
         
         self.axis = axis;
+        self.rtl = cx.turtle().is_rtl();
+        // the bars are drawn over the view and should not move with its content
+        cx.mark_unaligned();
         
         match self.axis {
             ScrollAxis::Horizontal => {
@@ -355,15 +370,17 @@ impl ScrollBar {
                 self.scroll_pos = self.scroll_pos.min(self.view_total - self.view_visible).max(0.);
                 
                 if self.visible {
-                    let (norm_scroll, norm_handle) = self.get_normalized_scroll_pos();
+                    let (norm_scroll, norm_handle) = self.get_drawn_scroll_pos();
                     self.draw_bar.is_vertical = 0.0;
                     self.draw_bar.norm_scroll = norm_scroll as f32;
                     self.draw_bar.norm_handle = norm_handle as f32;
                     let scroll = cx.turtle().scroll();
+                    // in a right to left view the vertical bar sits on the left
+                    let left = view_rect.size.x - self.scroll_size - self.bar_side_margin;
                     self.draw_bar.draw_rel(
                         cx,
                         Rect {
-                            pos: dvec2(self.bar_side_margin, view_rect.size.y - self.bar_size) + scroll,
+                            pos: dvec2(if self.rtl {left} else {self.bar_side_margin}, view_rect.size.y - self.bar_size) + scroll,
                             size: dvec2(self.scroll_size, self.bar_size),
                         }
                     );
@@ -390,7 +407,7 @@ impl ScrollBar {
                     self.draw_bar.draw_rel(
                         cx,
                         Rect {
-                            pos: dvec2(view_rect.size.x - self.bar_size, self.bar_side_margin) + scroll,
+                            pos: dvec2(if self.rtl {0.0} else {view_rect.size.x - self.bar_size}, self.bar_side_margin) + scroll,
                             size: dvec2(self.bar_size, self.scroll_size)
                         }
                     );
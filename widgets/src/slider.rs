@@ -34,7 +34,8 @@ impl LiveHook for Slider{
 pub struct DrawSlider {
     #[deref] draw_super: DrawQuad,
     #[live] slide_pos: f32,
-    #[live] slider_type: SliderType
+    #[live] slider_type: SliderType,
+    #[live] rtl: f32
 }
 
 #[derive(Live, Widget)]
@@ -62,8 +63,11 @@ pub struct Slider {
     
     #[live] bind: String,
     
+    #[live] tab_index: i64,
+    
     #[rust] pub value: f64,
     #[rust] pub dragging: Option<f64>,
+    #[rust] rtl: bool,
 }
 
 #[derive(Clone, Debug, DefaultNone)]
@@ -116,16 +120,30 @@ impl Slider {
     
     pub fn draw_walk_slider(&mut self, cx: &mut Cx2d, walk: Walk) {
         self.draw_slider.slide_pos = self.value as f32;
+        self.rtl = cx.turtle().is_rtl();
+        self.draw_slider.rtl = if self.rtl {1.0} else {0.0};
         self.draw_slider.begin(cx, walk, self.layout);
         
+        let label_align = if self.rtl {self.label_align.mirrored_x()} else {self.label_align};
         if let Some(mut dw) = cx.defer_walk(self.label_walk) {
             //, (self.value*100.0) as usize);
             let walk = self.text_input.walk(cx);
             self.text_input.draw_walk_text_input(cx, walk);
-            self.draw_text.draw_walk(cx, dw.resolve(cx), self.label_align, &self.text);
+            self.draw_text.draw_walk(cx, dw.resolve(cx), label_align, &self.text);
         }
         
         self.draw_slider.end(cx);
+        cx.add_nav_stop_with_tab_index(self.draw_slider.area(), NavRole::Slider, self.tab_index);
+    }
+    
+    // moves the value one step from the keyboard, sliders without a step move a hundredth of their range
+    fn step_from_key(&mut self, cx: &mut Cx, scope: &mut Scope, dir: f64) {
+        let step = if self.step != 0.0 {self.step / (self.max - self.min)} else {0.01};
+        self.value = (self.value + dir * step).clamp(0.0, 1.0);
+        self.set_internal(self.to_external());
+        self.draw_slider.redraw(cx);
+        self.update_text_input_and_redraw(cx);
+        cx.widget_action(self.widget_uid(), &scope.path, SliderAction::Slide(self.to_external()));
     }
 }
 
@@ -150,9 +168,12 @@ impl Widget for Slider {
                     }
                     self.update_text_input_and_redraw(cx);
                     cx.widget_action(uid, &scope.path, SliderAction::TextSlide(self.to_external()));
+                    cx.set_key_focus(self.draw_slider.area());
                 }
                 TextInputAction::Escape => {
                     self.update_text_input_and_redraw(cx);
+                    // hand the keyboard back to the slider itself
+                    cx.set_key_focus(self.draw_slider.area());
                 }
                 _ => ()
             }
@@ -165,6 +186,26 @@ impl Widget for Slider {
             Hit::FingerHoverOut(_) => {
                 self.animator_play(cx, id!(hover.off));
             },
+            Hit::Trigger(te) if te.0.iter().any(|t| t.id == live_id!(nav_focus)) => {
+                self.animator_play(cx, id!(nav_focus.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animator_play(cx, id!(nav_focus.off));
+            }
+            Hit::KeyDown(ke) => match ke.key_code {
+                KeyCode::ArrowLeft => self.step_from_key(cx, scope, if self.rtl {1.0} else {-1.0}),
+                KeyCode::ArrowRight => self.step_from_key(cx, scope, if self.rtl {-1.0} else {1.0}),
+                KeyCode::ArrowDown => self.step_from_key(cx, scope, -1.0),
+                KeyCode::ArrowUp => self.step_from_key(cx, scope, 1.0),
+                KeyCode::ReturnKey | KeyCode::Space if !ke.is_repeat => {
+                    // start typing a value
+                    self.text_input.read_only = false;
+                    self.text_input.set_key_focus(cx);
+                    self.text_input.select_all();
+                    self.text_input.redraw(cx);
+                }
+                _ => ()
+            }
             Hit::FingerDown(_fe) => {
                 // cx.set_key_focus(self.slider.area());
                 self.text_input.read_only = true;
@@ -192,8 +233,10 @@ impl Widget for Slider {
             }
             Hit::FingerMove(fe) => {
                 let rel = fe.abs - fe.abs_start;
+                // a right to left slider grows towards the left
+                let rel_x = if self.rtl {-rel.x} else {rel.x};
                 if let Some(start_pos) = self.dragging {
-                    self.value = (start_pos + rel.x / fe.rect.size.x).max(0.0).min(1.0);
+                    self.value = (start_pos + rel_x / fe.rect.size.x).max(0.0).min(1.0);
                     self.set_internal(self.to_external());
                     self.draw_slider.redraw(cx);
                     self.update_text_input_and_redraw(cx);
@@ -209,6 +252,20 @@ impl Widget for Slider {
         DrawStep::done()
     }
     
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::Slider, &self.text);
+        node.value = AccessValue::Number {
+            value: self.to_external(),
+            min: self.min,
+            max: self.max,
+            step: self.step,
+        };
+        node.state.focusable = self.tab_index >= 0;
+        node.state.focused = cx.has_key_focus(self.draw_slider.area());
+        node.rect = self.draw_slider.area().rect(cx);
+        Some(node)
+    }
+    
     fn widget_to_data(&self, _cx: &mut Cx, actions:&Actions, nodes: &mut LiveNodeVec, path: &[LiveId])->bool{
         match actions.find_widget_action_cast(self.widget_uid()) {
             SliderAction::TextSlide(v) | SliderAction::Slide(v) => {
@@ -1,8 +1,10 @@
 use {
+    std::rc::Rc,
     crate::{
         makepad_derive_widget::*,
         makepad_draw::*,
         widget::*,
+        text_pattern::TextPattern,
     }
 };
 
@@ -35,7 +37,7 @@ pub struct DrawLabel {
     #[live] is_empty: f32,
 }
 
-#[derive(Live, LiveHook, Widget)]
+#[derive(Live, Widget)]
 pub struct TextInput {
     #[animator] animator: Animator,
     
@@ -43,6 +45,7 @@ pub struct TextInput {
     #[live] draw_select: DrawQuad,
     #[live] draw_cursor: DrawQuad,
     #[live] draw_text: DrawLabel,
+    #[live] draw_placeholder: DrawLabel,
     
     #[walk] walk: Walk,
     #[layout] layout: Layout,
@@ -58,11 +61,31 @@ pub struct TextInput {
     #[live] secret: bool,
     #[live] on_focus_select_all: bool,
     #[live] pub read_only: bool,
+    #[live] tab_index: i64,
     
     //#[live] label_walk: Walk,
     
     #[live] pub text: String,
     #[live] ascii_only: bool,
+    /// Return inserts a newline instead of returning, Ctrl/Cmd+Return still returns,
+    /// and the text scrolls to keep the cursor in view. Shift+Return inserts a newline either way
+    #[live] multiline: bool,
+    /// Maximum number of chars, 0 doesn't limit
+    #[live] max_length: usize,
+    /// Input mask, `9` takes a digit, `a` a letter, `*` either and `\` escapes.
+    /// Anything else is a literal that gets inserted while typing.
+    #[live] mask: String,
+    /// Regular expression the whole text has to match on return and when losing focus
+    #[live] pattern: String,
+    
+    #[rust] compiled_pattern: Option<TextPattern>,
+    #[rust] composition: String,
+    #[rust] composition_cursor: usize,
+    #[rust] cursor_x: Option<f64>,
+    #[rust] text_scroll: DVec2,
+    #[rust] scroll_to_cursor: bool,
+    #[rust] text_rect: Rect,
+    #[rust] view_rect: Rect,
     #[rust] double_tap_start: Option<(usize, usize)>,
     #[rust] undo_id: u64,
     
@@ -70,7 +93,21 @@ pub struct TextInput {
     #[rust] undo_stack: Vec<UndoItem>,
     #[rust] redo_stack: Vec<UndoItem>,
     #[rust] cursor_tail: usize,
-    #[rust] cursor_head: usize
+    #[rust] cursor_head: usize,
+    // the validation error of the bound data, shown as an error outline
+    #[rust] data_error: Option<String>,
+}
+
+impl LiveHook for TextInput {
+    fn after_apply(&mut self, _cx: &mut Cx, _apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        self.compiled_pattern = None;
+        if !self.pattern.is_empty() {
+            match TextPattern::new(&self.pattern) {
+                Ok(pattern) => self.compiled_pattern = Some(pattern),
+                Err(err) => error!("TextInput pattern {:?} is invalid: {}", self.pattern, err)
+            }
+        }
+    }
 }
 
 impl Widget for TextInput {
@@ -83,8 +120,12 @@ impl Widget for TextInput {
             Hit::KeyFocusLost(_) => {
                 self.animator_play(cx, id!(focus.off));
                 cx.hide_text_ime();
+                self.composition.clear();
                 //cx.widget_action(uid, &scope.path, TextInputAction::Return(self.text.clone()));
                 cx.widget_action(uid, &scope.path, TextInputAction::KeyFocusLost);
+                if !self.is_valid() {
+                    cx.widget_action(uid, &scope.path, TextInputAction::Invalid(self.text.clone()));
+                }
             }
             Hit::KeyFocus(_) => {
                 self.undo_id += 1;
@@ -97,9 +138,14 @@ impl Widget for TextInput {
                 cx.widget_action(uid, &scope.path, TextInputAction::KeyFocus);
             }
             Hit::TextInput(te) => {
+                self.composition.clear();
                 let mut input = String::new();
                 self.filter_input(&te.input, Some(&mut input));
+                if !self.multiline {
+                    input.retain( | c | c != '\n' && c != '\r');
+                }
                 if input.len() == 0 {
+                    self.draw_bg.redraw(cx);
                     return
                 }
                 let last_undo = self.last_undo.take();
@@ -117,7 +163,16 @@ impl Widget for TextInput {
                     // if this one follows a space, it still needs to eat it
                     self.create_undo(UndoGroup::TextInput(self.undo_id));
                 }
-                if self.change(cx, &input){self.push_change_action(uid, scope, cx)}
+                self.edit(cx, uid, scope, &input);
+            }
+            Hit::TextComposition(tc) => {
+                if self.read_only {
+                    return
+                }
+                self.composition = tc.text;
+                self.composition_cursor = tc.cursor.min(self.composition.chars().count());
+                self.scroll_to_cursor = true;
+                self.draw_bg.redraw(cx);
             }
             Hit::TextCopy(ce) => {
                 self.undo_id += 1;
@@ -128,7 +183,7 @@ impl Widget for TextInput {
                 if self.cursor_head != self.cursor_tail {
                     *tc.response.borrow_mut() = Some(self.selected_text());
                     self.create_undo(UndoGroup::Cut(self.undo_id));
-                    if self.change(cx, ""){self.push_change_action(uid, scope, cx)}
+                    self.edit(cx, uid, scope, "");
                 }
             }
             Hit::KeyDown(ke) => match ke.key_code {
@@ -136,14 +191,19 @@ impl Widget for TextInput {
                 KeyCode::Tab => {
                     // dispatch_action(cx, self, TextInputAction::Tab(key.mod_shift));
                 }
-                KeyCode::ReturnKey if ke.modifiers.shift => {
-                    if self.change(cx, "\n"){
-                        self.push_change_action(uid, scope, cx)
-                    }
+                KeyCode::ReturnKey if self.return_inserts_newline(&ke.modifiers) => {
+                    self.undo_id += 1;
+                    self.create_undo(UndoGroup::TextInput(self.undo_id));
+                    self.edit(cx, uid, scope, "\n");
                 },
                 KeyCode::ReturnKey => {
                     cx.hide_text_ime();
-                    cx.widget_action(uid, &scope.path, TextInputAction::Return(self.text.clone()));
+                    if self.is_valid() {
+                        cx.widget_action(uid, &scope.path, TextInputAction::Return(self.text.clone()));
+                    }
+                    else {
+                        cx.widget_action(uid, &scope.path, TextInputAction::Invalid(self.text.clone()));
+                    }
                 },
                 KeyCode::Escape => {
                     cx.widget_action(uid, &scope.path, TextInputAction::Escape);
@@ -168,87 +228,78 @@ impl Widget for TextInput {
                     self.cursor_head = self.text.chars().count();
                     self.draw_bg.redraw(cx);
                 }
-                KeyCode::ArrowLeft => if !ke.modifiers.logo {
-                                        
-                    self.undo_id += 1;
-                    if self.cursor_head>0 {
-                        self.cursor_head -= 1;
+                KeyCode::ArrowLeft => {
+                    let head = if ke.modifiers.logo {
+                        self.line_start(self.cursor_head)
                     }
-                    if !ke.modifiers.shift {
-                        self.cursor_tail = self.cursor_head;
+                    else if ke.modifiers.control || ke.modifiers.alt {
+                        self.word_left(self.cursor_head)
                     }
-                    self.draw_bg.redraw(cx);
+                    else {
+                        self.cursor_head.saturating_sub(1)
+                    };
+                    self.move_cursor(cx, head, ke.modifiers.shift);
                 },
-                KeyCode::ArrowRight => if !ke.modifiers.logo {
-                    self.undo_id += 1;
-                    if self.cursor_head < self.text.chars().count() {
-                        self.cursor_head += 1;
+                KeyCode::ArrowRight => {
+                    let head = if ke.modifiers.logo {
+                        self.line_end(self.cursor_head)
                     }
-                    if !ke.modifiers.shift {
-                        self.cursor_tail = self.cursor_head;
+                    else if ke.modifiers.control || ke.modifiers.alt {
+                        self.word_right(self.cursor_head)
                     }
-                    self.draw_bg.redraw(cx);
+                    else {
+                        self.cursor_head + 1
+                    };
+                    self.move_cursor(cx, head, ke.modifiers.shift);
                 }
                 KeyCode::ArrowDown => if !ke.modifiers.logo {
-                    self.undo_id += 1;
-                    // we need to figure out what is below our current cursor
-                    if let Some(pos) = self.draw_text.get_cursor_pos(cx, self.newline_indexes(), 0.0, self.cursor_head) {
-                        if let Some(pos) = self.draw_text.closest_offset(cx, self.newline_indexes(), dvec2(pos.x, pos.y + self.draw_text.get_line_spacing() * 1.5)) {
-                            self.cursor_head = pos;
-                            if !ke.modifiers.shift {
-                                self.cursor_tail = self.cursor_head;
-                            }
-                            self.draw_bg.redraw(cx);
-                        }
-                    }
+                    self.move_lines(cx, 1.0, ke.modifiers.shift);
                 },
                 KeyCode::ArrowUp => if !ke.modifiers.logo {
-                    self.undo_id += 1;
-                    // we need to figure out what is below our current cursor
-                    if let Some(pos) = self.draw_text.get_cursor_pos(cx, self.newline_indexes(), 0.0, self.cursor_head) {
-                        if let Some(pos) = self.draw_text.closest_offset(cx, self.newline_indexes(), dvec2(pos.x, pos.y - self.draw_text.get_line_spacing() * 0.5)) {
-                            self.cursor_head = pos;
-                            if !ke.modifiers.shift {
-                                self.cursor_tail = self.cursor_head;
-                            }
-                            self.draw_bg.redraw(cx);
-                        }
-                    }
+                    self.move_lines(cx, -1.0, ke.modifiers.shift);
                 },
-                KeyCode::Home => if !ke.modifiers.logo {
-                    self.undo_id += 1;
-                    self.cursor_head = 0;
-                    if !ke.modifiers.shift {
-                        self.cursor_tail = self.cursor_head;
-                    }
-                    self.draw_bg.redraw(cx);
+                KeyCode::PageDown if self.multiline => {
+                    self.move_lines(cx, self.page_lines(), ke.modifiers.shift);
+                },
+                KeyCode::PageUp if self.multiline => {
+                    self.move_lines(cx, -self.page_lines(), ke.modifiers.shift);
+                },
+                KeyCode::Home => {
+                    let head = if ke.modifiers.control || ke.modifiers.logo {0} else {self.line_start(self.cursor_head)};
+                    self.move_cursor(cx, head, ke.modifiers.shift);
                 }
-                KeyCode::End => if !ke.modifiers.logo {
-                    self.undo_id += 1;
-                    self.cursor_head = self.text.chars().count();
-                                        
-                    if !ke.modifiers.shift {
-                        self.cursor_tail = self.cursor_head;
+                KeyCode::End => {
+                    let head = if ke.modifiers.control || ke.modifiers.logo {
+                        self.text.chars().count()
                     }
-                    self.draw_bg.redraw(cx);
+                    else {
+                        self.line_end(self.cursor_head)
+                    };
+                    self.move_cursor(cx, head, ke.modifiers.shift);
                 }
                 KeyCode::Backspace => {
                     self.create_undo(UndoGroup::Backspace(self.undo_id));
                     if self.cursor_head == self.cursor_tail {
-                        if self.cursor_tail > 0 {
+                        if ke.modifiers.control || ke.modifiers.alt {
+                            self.cursor_tail = self.word_left(self.cursor_tail);
+                        }
+                        else if self.cursor_tail > 0 {
                             self.cursor_tail -= 1;
                         }
                     }
-                    if self.change(cx, ""){self.push_change_action(uid, scope, cx)}
+                    self.edit(cx, uid, scope, "");
                 }
                 KeyCode::Delete => {
                     self.create_undo(UndoGroup::Delete(self.undo_id));
                     if self.cursor_head == self.cursor_tail {
-                        if self.cursor_head < self.text.chars().count() {
+                        if ke.modifiers.control || ke.modifiers.alt {
+                            self.cursor_head = self.word_right(self.cursor_head);
+                        }
+                        else if self.cursor_head < self.text.chars().count() {
                             self.cursor_head += 1;
                         }
                     }
-                    if self.change(cx, ""){self.push_change_action(uid, scope, cx)}
+                    self.edit(cx, uid, scope, "");
                 }
                 _ => ()
             }
@@ -259,6 +310,17 @@ impl Widget for TextInput {
             Hit::FingerHoverOut(_) => {
                 self.animator_play(cx, id!(hover.off));
             },
+            Hit::FingerScroll(fs) if self.multiline => {
+                let max = self.text_rect.size - self.view_rect.size;
+                let scroll = dvec2(
+                    (self.text_scroll.x + fs.scroll.x).min(max.x).max(0.0),
+                    (self.text_scroll.y + fs.scroll.y).min(max.y).max(0.0)
+                );
+                if scroll != self.text_scroll {
+                    self.text_scroll = scroll;
+                    self.draw_bg.redraw(cx);
+                }
+            }
             Hit::FingerDown(fe) => {
                 cx.set_cursor(MouseCursor::Text);
                 self.set_key_focus(cx);
@@ -332,6 +394,7 @@ impl Widget for TextInput {
                         if pos != self.cursor_head {
                             self.cursor_head = pos;
                         }
+                        self.scroll_to_cursor = true;
                         self.draw_bg.redraw(cx);
                     }
                 }
@@ -345,6 +408,22 @@ impl Widget for TextInput {
         DrawStep::done()
     }
     
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::TextInput, &self.empty_message);
+        // a secret input only tells how long it is
+        node.value = if self.secret {
+            AccessValue::Text("*".repeat(self.text.chars().count()))
+        }
+        else {
+            AccessValue::Text(self.text.clone())
+        };
+        node.state.focusable = self.tab_index >= 0;
+        node.state.focused = cx.has_key_focus(self.draw_bg.area());
+        node.state.read_only = self.read_only;
+        node.rect = self.draw_bg.area().rect(cx);
+        Some(node)
+    }
+    
     
     fn text(&self) -> String {
         self.text.clone()
@@ -353,6 +432,42 @@ impl Widget for TextInput {
     fn set_text(&mut self, v: &str) {
         self.filter_input(&v, None);
     }
+    
+    fn widget_to_data(&self, _cx: &mut Cx, actions: &Actions, nodes: &mut LiveNodeVec, path: &[LiveId]) -> bool {
+        match actions.find_widget_action_cast(self.widget_uid()) {
+            TextInputAction::Change(v) => {
+                nodes.write_field_value(path, LiveValue::String(Rc::new(v)));
+                true
+            }
+            _ => false
+        }
+    }
+    
+    fn data_to_widget(&mut self, cx: &mut Cx, nodes: &[LiveNode], path: &[LiveId]) {
+        let value = match nodes.read_field_value(path) {
+            Some(LiveValue::String(v)) => v.to_string(),
+            Some(LiveValue::Str(v)) => v.to_string(),
+            Some(LiveValue::InlineString(v)) => v.as_str().to_string(),
+            Some(v) if v.is_number_type() => format!("{}", v.as_float().unwrap_or(0.0)),
+            _ => return
+        };
+        if value != self.text {
+            self.set_text(&value);
+            let len = self.text.chars().count();
+            self.cursor_head = self.cursor_head.min(len);
+            self.cursor_tail = self.cursor_tail.min(len);
+            self.draw_bg.redraw(cx);
+        }
+    }
+    
+    fn set_data_error(&mut self, cx: &mut Cx, error: Option<&str>) {
+        if self.data_error.as_deref() != error {
+            self.data_error = error.map( | e | e.to_string());
+            let invalid = if error.is_some() {1.0} else {0.0};
+            self.draw_bg.apply_over(cx, live!{invalid: (invalid)});
+            self.draw_bg.redraw(cx);
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, DefaultNone)]
@@ -362,6 +477,8 @@ pub enum TextInputAction {
     Escape,
     KeyFocus,
     KeyFocusLost,
+    /// The text failed the `mask` while typing or the `pattern` on return or losing focus
+    Invalid(String),
     None
 }
 
@@ -511,17 +628,220 @@ impl TextInput {
         }
     }
     
+    /// Moves the cursor one word to the left, skipping the whitespace and punctuation in front of it.
+    pub fn word_left(&self, index: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut index = index.min(chars.len());
+        while index > 0 && !chars[index - 1].is_alphanumeric() {
+            index -= 1;
+        }
+        while index > 0 && chars[index - 1].is_alphanumeric() {
+            index -= 1;
+        }
+        index
+    }
+    
+    /// Moves the cursor to the end of the next word.
+    pub fn word_right(&self, index: usize) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut index = index.min(chars.len());
+        while index < chars.len() && !chars[index].is_alphanumeric() {
+            index += 1;
+        }
+        while index < chars.len() && chars[index].is_alphanumeric() {
+            index += 1;
+        }
+        index
+    }
+    
+    fn return_inserts_newline(&self, modifiers: &KeyModifiers) -> bool {
+        modifiers.shift || (self.multiline && !(modifiers.control || modifiers.logo))
+    }
+    
+    fn line_start(&self, index: usize) -> usize {
+        self.text.chars().take(index).collect::<Vec<_>>().iter().rposition( | c | *c == '\n').map_or(0, | i | i + 1)
+    }
+    
+    fn line_end(&self, index: usize) -> usize {
+        let count = self.text.chars().count();
+        self.text.chars().skip(index).position( | c | c == '\n').map_or(count, | i | index + i)
+    }
+    
+    fn move_cursor(&mut self, cx: &mut Cx, head: usize, select: bool) {
+        self.undo_id += 1;
+        self.cursor_head = head.min(self.text.chars().count());
+        if !select {
+            self.cursor_tail = self.cursor_head;
+        }
+        self.cursor_x = None;
+        self.scroll_to_cursor = true;
+        self.draw_bg.redraw(cx);
+    }
+    
+    // moves the cursor up or down by a number of lines, keeping the column it started in
+    fn move_lines(&mut self, cx: &mut Cx, lines: f64, select: bool) {
+        let newlines = newline_indexes(&self.text);
+        let count = self.text.chars().count();
+        let (Some(pos), Some(first), Some(last)) = (
+            self.draw_text.get_cursor_pos(cx, newlines.clone(), 0.0, self.cursor_head),
+            self.draw_text.get_cursor_pos(cx, newlines.clone(), 0.0, 0),
+            self.draw_text.get_cursor_pos(cx, newlines.clone(), 0.0, count),
+        ) else {
+            return
+        };
+        let x = self.cursor_x.unwrap_or(pos.x);
+        let line_spacing = self.draw_text.get_line_spacing();
+        // aim for the middle of the target line
+        let y = pos.y + line_spacing * (lines + 0.5);
+        let head = if y < first.y {
+            0
+        }
+        else if y > last.y + line_spacing {
+            count
+        }
+        else if let Some(head) = self.draw_text.closest_offset(cx, newlines, dvec2(x, y)) {
+            head
+        }
+        else {
+            return
+        };
+        self.move_cursor(cx, head, select);
+        self.cursor_x = Some(x);
+    }
+    
+    fn page_lines(&self) -> f64 {
+        (self.view_rect.size.y / self.draw_text.get_line_spacing()).floor().max(1.0)
+    }
+    
+    /// Formats `text` against the mask, inserting its literals, and moves the char index `cursor` along.
+    /// Returns `None` when the text doesn't fit the mask.
+    fn apply_mask(&self, text: &str, cursor: usize) -> Option<(String, usize)> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut mask = self.mask.chars();
+        let mut out = String::new();
+        let mut out_len = 0;
+        let mut new_cursor = 0;
+        let mut i = 0;
+        while i < chars.len() {
+            let (slot, literal) = match mask.next()? {
+                '\\' => (mask.next()?, true),
+                c => (c, !matches!(c, '9' | 'a' | '*'))
+            };
+            if literal {
+                out.push(slot);
+                if chars[i] == slot {
+                    i += 1;
+                }
+            }
+            else {
+                let fits = match slot {
+                    '9' => chars[i].is_ascii_digit(),
+                    'a' => chars[i].is_alphabetic(),
+                    _ => chars[i].is_alphanumeric()
+                };
+                if !fits {
+                    return None
+                }
+                out.push(chars[i]);
+                i += 1;
+            }
+            out_len += 1;
+            if i <= cursor {
+                new_cursor = out_len;
+            }
+        }
+        Some((out, new_cursor))
+    }
+    
+    // the number of chars a filled in mask has, `\\` escapes the char after it, a backslash included
+    fn mask_len(&self) -> usize {
+        let mut mask = self.mask.chars();
+        let mut len = 0;
+        while let Some(c) = mask.next() {
+            if c == '\\' && mask.next().is_none() {
+                break
+            }
+            len += 1;
+        }
+        len
+    }
+    
+    /// The validation error pushed in by a data binding
+    pub fn data_error(&self) -> Option<&str> {
+        self.data_error.as_deref()
+    }
+    
+    /// Whether the text fills the mask and matches the pattern, empty text only has to match the pattern.
+    pub fn is_valid(&self) -> bool {
+        if !self.mask.is_empty() && !self.text.is_empty() && self.text.chars().count() != self.mask_len() {
+            return false
+        }
+        match &self.compiled_pattern {
+            Some(pattern) => pattern.is_match(&self.text),
+            None => true
+        }
+    }
+    
     pub fn push_change_action(&self, uid:WidgetUid, scope:&Scope, cx: &mut Cx){
         cx.widget_action(uid, &scope.path, TextInputAction::Change(self.text.clone()));
     }
     
-    pub fn change(&mut self, cx: &mut Cx, s: &str)->bool{
+    // replaces the selection, limited by max_length and formatted by the mask.
+    // Input the mask rejects leaves the text as it was and comes back as the error.
+    fn try_change(&mut self, s: &str) -> Result<bool, String> {
         if self.read_only {
-            return false
+            return Ok(false)
+        }
+        let mut s = s.to_string();
+        if self.max_length > 0 {
+            let (left, right) = self.sorted_cursor();
+            let kept = self.text.chars().count() - (right - left);
+            let room = self.max_length.saturating_sub(kept);
+            if s.chars().count() > room {
+                s = s.chars().take(room).collect();
+                if s.is_empty() && left == right {
+                    return Ok(false)
+                }
+            }
+        }
+        let old = (self.text.clone(), self.cursor_head, self.cursor_tail);
+        self.replace_text(&s);
+        if !self.mask.is_empty() {
+            match self.apply_mask(&self.text, self.cursor_head) {
+                Some((text, cursor)) => {
+                    self.text = text;
+                    self.cursor_head = cursor;
+                    self.cursor_tail = cursor;
+                }
+                None => {
+                    let rejected = std::mem::replace(&mut self.text, old.0);
+                    self.cursor_head = old.1;
+                    self.cursor_tail = old.2;
+                    return Err(rejected)
+                }
+            }
+        }
+        self.scroll_to_cursor = true;
+        Ok(true)
+    }
+    
+    pub fn change(&mut self, cx: &mut Cx, s: &str)->bool{
+        let changed = self.try_change(s) == Ok(true);
+        if changed {
+            self.draw_bg.redraw(cx);
+        }
+        changed
+    }
+    
+    fn edit(&mut self, cx: &mut Cx, uid: WidgetUid, scope: &Scope, s: &str) {
+        match self.try_change(s) {
+            Ok(true) => {
+                self.draw_bg.redraw(cx);
+                self.push_change_action(uid, scope, cx);
+            }
+            Ok(false) => (),
+            Err(text) => cx.widget_action(uid, &scope.path, TextInputAction::Invalid(text))
         }
-        self.replace_text(s);
-        self.draw_bg.redraw(cx);
-        true
     }
     
     pub fn set_key_focus(&self, cx: &mut Cx) {
@@ -544,9 +864,8 @@ impl TextInput {
             }
         }
         else if self.numeric_only {
-            let mut output = String::new();
             for c in input.chars() {
-                if c.is_ascii_digit() || c == '.' {
+                if c.is_ascii_digit() || c == '.' || c == '-' {
                     output.push(c);
                 }
                 else if c == ',' {
@@ -561,76 +880,126 @@ impl TextInput {
     }
 
     fn newline_indexes(&self) -> Vec<usize> {
-        let mut ret = Vec::new();
-        for (i, c) in self.text.chars().enumerate() {
-            if c == '\n' {
-                ret.push(i);
-            }
-        }
-        ret
+        newline_indexes(&self.text)
     }
     
     pub fn draw_walk_text_input(&mut self, cx: &mut Cx2d, walk: Walk) {
         
-        self.draw_bg.begin(cx, walk, self.layout);
+        let mut layout = self.layout.with_scroll(self.text_scroll);
+        // text scrolled out of a multiline input mustn't show outside of it
+        layout.clip_y |= self.multiline;
+        self.draw_bg.begin(cx, walk, layout);
         let turtle_rect = cx.turtle().rect();
+        // the turtle origin moves with the scroll, the view doesn't
+        self.view_rect = cx.turtle().padded_rect();
+        self.view_rect.pos += cx.turtle().scroll();
+        let rtl = cx.turtle().is_rtl();
+        let label_align = if rtl {self.label_align.mirrored_x()} else {self.label_align};
         
         // this makes sure selection goes behind the text
         self.draw_select.append_to_draw_call(cx);
         
-        if self.text.len() == 0 {
+        // the preedit of an input method is shown in place of the selection until it commits
+        let (left, right) = self.sorted_cursor();
+        let composing = !self.composition.is_empty();
+        let mut display = if composing {
+            let mut display: String = self.text.chars().take(left).collect();
+            display.push_str(&self.composition);
+            display.extend(self.text.chars().skip(right));
+            display
+        }
+        else {
+            self.text.clone()
+        };
+        if self.secret {
+            display = "*".repeat(display.chars().count());
+        }
+        let newlines = newline_indexes(&display);
+        // a multiline input lets its text grow and scrolls it instead
+        let text_walk = Walk::size(self.walk.width, if self.multiline {Size::Fit} else {self.walk.height});
+        
+        let empty = display.is_empty();
+        if empty {
             self.draw_text.is_empty = 1.0;
-            self.draw_text.draw_walk(cx, Walk::size(self.walk.width, self.walk.height), self.label_align, &self.empty_message);
+            self.draw_placeholder.is_empty = 1.0;
+            self.text_rect = self.draw_placeholder.draw_walk(cx, text_walk, label_align, &self.empty_message);
         }
         else {
             self.draw_text.is_empty = 0.0;
-            if self.secret {
-                self.draw_text.draw_walk(cx, Walk::size(
-                    self.walk.width,
-                    self.walk.height
-                ), self.label_align, &"*".repeat(self.text.len()));
-            }
-            else {
-                self.draw_text.draw_walk(cx, Walk::size(
-                    self.walk.width,
-                    self.walk.height
-                ), self.label_align, &self.text);
-            }
+            self.text_rect = self.draw_text.draw_walk(cx, text_walk, label_align, &display);
         }
+        let layout_text = if empty {&self.draw_placeholder} else {&self.draw_text};
         
         let mut turtle = cx.turtle().padded_rect_used();
         turtle.pos.y -= self.cursor_margin_top;
         turtle.size.y += self.cursor_margin_top + self.cursor_margin_bottom;
         // move the IME
-        let line_spacing = self.draw_text.get_line_spacing();
-        let top_drop = self.draw_text.get_font_size() * 0.2;
-        let head = self.draw_text.get_cursor_pos(cx, self.newline_indexes(), 0.0, self.cursor_head)
-            .unwrap_or(dvec2(turtle.pos.x, 0.0));
+        let line_spacing = layout_text.get_line_spacing();
+        let top_drop = layout_text.get_font_size() * 0.2;
+        let cursor = if composing {left + self.composition_cursor} else if empty {0} else {self.cursor_head};
+        let head = layout_text.get_cursor_pos(cx, newlines.clone(), 0.0, cursor)
+            .unwrap_or(dvec2(turtle.pos.x, self.view_rect.pos.y + top_drop));
         
-        if !self.read_only && self.cursor_head == self.cursor_tail {
+        if !self.read_only && (self.cursor_head == self.cursor_tail || composing) {
             self.draw_cursor.draw_abs(cx, Rect {
                 pos: dvec2(head.x - 0.5 * self.cursor_size, head.y - top_drop),
                 size: dvec2(self.cursor_size, line_spacing)
             });
         }
         
-        // draw selection rects
+        // draw selection rects, or underline the preedit
         
-        if self.cursor_head != self.cursor_tail {
-            let top_drop = self.draw_text.get_font_size() * 0.3;
-            let bottom_drop = self.draw_text.get_font_size() * 0.1;
-            
-            let (start, end) = self.sorted_cursor();
-            let rects = self.draw_text.get_selection_rects(cx, self.newline_indexes(), start, end, dvec2(0.0, -top_drop), dvec2(0.0, bottom_drop));
+        let top_drop_select = layout_text.get_font_size() * 0.3;
+        let bottom_drop = layout_text.get_font_size() * 0.1;
+        if composing {
+            let end = left + self.composition.chars().count();
+            let rects = self.draw_text.get_selection_rects(cx, newlines.clone(), left, end, dvec2(0.0, -top_drop_select), dvec2(0.0, bottom_drop));
+            for rect in rects {
+                self.draw_cursor.draw_abs(cx, Rect {
+                    pos: dvec2(rect.pos.x, rect.pos.y + rect.size.y - 2.0),
+                    size: dvec2(rect.size.x, 1.0)
+                });
+            }
+        }
+        else if self.cursor_head != self.cursor_tail {
+            let rects = self.draw_text.get_selection_rects(cx, newlines.clone(), left, right, dvec2(0.0, -top_drop_select), dvec2(0.0, bottom_drop));
             for rect in rects {
                 self.draw_select.draw_abs(cx, rect);
             }
         }
         self.draw_bg.end(cx);
         
+        // scroll the cursor into view, the next frame draws the result
+        if self.scroll_to_cursor {
+            self.scroll_to_cursor = false;
+            let view = self.view_rect;
+            let mut scroll = self.text_scroll;
+            let top = head.y - top_drop;
+            if top < view.pos.y {
+                scroll.y -= view.pos.y - top;
+            }
+            else if top + line_spacing > view.pos.y + view.size.y {
+                scroll.y += top + line_spacing - (view.pos.y + view.size.y);
+            }
+            // a mirrored turtle scrolls the other way, it keeps to its start
+            if !rtl {
+                if head.x < view.pos.x {
+                    scroll.x -= view.pos.x - head.x;
+                }
+                else if head.x + self.cursor_size > view.pos.x + view.size.x {
+                    scroll.x += head.x + self.cursor_size - (view.pos.x + view.size.x);
+                }
+            }
+            let scroll = dvec2(scroll.x.max(0.0), scroll.y.max(0.0));
+            if scroll != self.text_scroll {
+                self.text_scroll = scroll;
+                self.draw_bg.redraw(cx);
+            }
+        }
+        
         if  cx.has_key_focus(self.draw_bg.area()) {
             // ok so. if we have the IME we should inject a tracking point
-            let ime_x = self.draw_text.get_cursor_pos(cx, self.newline_indexes(), 0.5, self.cursor_head)
+            let ime_x = layout_text.get_cursor_pos(cx, newlines, 0.5, cursor)
                 .unwrap_or(dvec2(turtle.pos.x, 0.0)).x;
             
             if self.numeric_only {
@@ -642,8 +1011,18 @@ impl TextInput {
             }
         }
         
-        cx.add_nav_stop(self.draw_bg.area(), NavRole::TextInput, Margin::default())
+        cx.add_nav_stop_with_tab_index(self.draw_bg.area(), NavRole::TextInput, self.tab_index)
+    }
+}
+
+fn newline_indexes(text: &str) -> Vec<usize> {
+    let mut ret = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        if c == '\n' {
+            ret.push(i);
+        }
     }
+    ret
 }
 
 impl TextInputRef {
@@ -661,9 +1040,129 @@ impl TextInputRef {
         None
     }
     
+    /// The text that failed validation, if it did
+    pub fn invalid(&self, actions: &Actions) -> Option<String> {
+        if let TextInputAction::Invalid(val) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(val);
+        }
+        None
+    }
+    
+    pub fn is_valid(&self) -> bool {
+        if let Some(inner) = self.borrow() {
+            return inner.is_valid()
+        }
+        true
+    }
+    
     pub fn set_cursor(&self, head:usize, tail: usize){
         if let Some(mut inner) = self.borrow_mut(){
             inner.set_cursor(head, tail);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn with_input(f: impl FnOnce(&mut TextInput)) {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let mut input = TextInput::new(&mut cx);
+        f(&mut input);
+    }
+    
+    #[test]
+    fn words_skip_punctuation_and_whitespace() {
+        with_input( | input | {
+            input.text = "let foo_bar = baz(1, 2);".into();
+            assert_eq!(input.word_left(24), 21);
+            assert_eq!(input.word_left(21), 18);
+            // an underscore is punctuation too
+            assert_eq!(input.word_left(11), 8);
+            assert_eq!(input.word_left(3), 0);
+            assert_eq!(input.word_left(0), 0);
+            assert_eq!(input.word_right(0), 3);
+            assert_eq!(input.word_right(3), 7);
+            assert_eq!(input.word_right(7), 11);
+            assert_eq!(input.word_right(21), 22);
+            assert_eq!(input.word_right(22), 24);
+            assert_eq!(input.word_right(100), 24);
+        })
+    }
+    
+    #[test]
+    fn shift_return_always_inserts_a_newline() {
+        with_input( | input | {
+            let shift = KeyModifiers {shift: true, ..Default::default()};
+            let control = KeyModifiers {control: true, ..Default::default()};
+            assert!(input.return_inserts_newline(&shift));
+            assert!(!input.return_inserts_newline(&KeyModifiers::default()));
+            input.multiline = true;
+            assert!(input.return_inserts_newline(&shift));
+            assert!(input.return_inserts_newline(&KeyModifiers::default()));
+            assert!(!input.return_inserts_newline(&control));
+        })
+    }
+    
+    #[test]
+    fn mask_inserts_literals_and_rejects_misfits() {
+        with_input( | input | {
+            input.mask = "(999) 999-9999".into();
+            assert_eq!(input.apply_mask("555", 3), Some(("(555".into(), 4)));
+            assert_eq!(input.apply_mask("5551234", 7), Some(("(555) 123-4".into(), 11)));
+            // literals typed along are taken, not doubled
+            assert_eq!(input.apply_mask("(555) 1", 7), Some(("(555) 1".into(), 7)));
+            // the cursor moves with the text in front of it
+            assert_eq!(input.apply_mask("5551", 1), Some(("(555) 1".into(), 2)));
+            assert_eq!(input.apply_mask("55a", 3), None);
+            assert_eq!(input.apply_mask("55512345678", 11), None);
+            
+            input.mask = "a*\\\\9\\a".into();
+            // literals trailing the text wait for the next char
+            assert_eq!(input.apply_mask("x1", 2), Some(("x1".into(), 2)));
+            assert_eq!(input.apply_mask("x17", 3), Some(("x1\\7".into(), 4)));
+            assert_eq!(input.apply_mask("1", 1), None);
+        })
+    }
+    
+    #[test]
+    fn mask_len_counts_escaped_backslashes_once() {
+        with_input( | input | {
+            input.mask = "99-99".into();
+            assert_eq!(input.mask_len(), 5);
+            input.mask = "\\\\9\\\\a".into();
+            assert_eq!(input.mask_len(), 4);
+            input.mask = "9\\\\".into();
+            assert_eq!(input.mask_len(), 2);
+            // a dangling escape takes nothing
+            input.mask.push('\\');
+            assert_eq!(input.mask_len(), 2);
+        })
+    }
+    
+    #[test]
+    fn valid_text_fills_the_mask_and_matches_the_pattern() {
+        with_input( | input | {
+            assert!(input.is_valid());
+            input.mask = "9\\\\9".into();
+            input.text = "1\\".into();
+            assert!(!input.is_valid());
+            input.text = "1\\2".into();
+            assert!(input.is_valid());
+            // empty text only answers to the pattern
+            input.text.clear();
+            assert!(input.is_valid());
+            
+            input.mask.clear();
+            input.compiled_pattern = Some(TextPattern::new("[a-z]+@[a-z]+\\.com").unwrap());
+            assert!(!input.is_valid());
+            input.text = "me@example.com".into();
+            assert!(input.is_valid());
+            input.text = "me@example.org".into();
+            assert!(!input.is_valid());
+        })
+    }
+}
@@ -0,0 +1,326 @@
+/// A small backtracking regular expression matcher for validating text input.
+/// Supports literals, `.`, classes like `[a-z0-9_]` and `[^...]`, the escapes `\d \w \s`
+/// (and their negations), groups with `|` alternation and the quantifiers `* + ? {n} {n,} {n,m}`.
+/// Patterns always match the whole input, `^` and `$` are accepted but implied.
+/// Text longer than `MAX_MATCH_LEN` chars never matches.
+#[derive(Clone, Debug)]
+pub struct TextPattern {
+    root: PatternNode,
+}
+
+#[derive(Clone, Debug)]
+enum PatternNode {
+    Char(char),
+    Any,
+    Class {ranges: Vec<(char, char)>, negated: bool},
+    Group(Vec<Vec<PatternNode>>),
+    Repeat {node: Box<PatternNode>, min: usize, max: Option<usize>},
+    Start,
+    End,
+}
+
+/// Repeated groups backtrack by recursing once per repetition, past this length the stack could run out
+pub const MAX_MATCH_LEN: usize = 1024;
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+const SPACE: &[(char, char)] = &[(' ', ' '), ('\t', '\r')];
+
+struct PatternParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a >>,
+}
+
+impl<'a> PatternParser<'a> {
+    fn alternatives(&mut self) -> Result<Vec<Vec<PatternNode>>, String> {
+        let mut alts = vec![self.sequence()?];
+        while self.chars.peek() == Some(&'|') {
+            self.chars.next();
+            alts.push(self.sequence()?);
+        }
+        Ok(alts)
+    }
+
+    fn sequence(&mut self) -> Result<Vec<PatternNode>, String> {
+        let mut seq = Vec::new();
+        while let Some(c) = self.chars.peek().copied() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            self.chars.next();
+            let atom = match c {
+                '(' => {
+                    // non capturing groups are the only kind there is
+                    if self.chars.peek() == Some(&'?') {
+                        self.chars.next();
+                        if self.chars.next() != Some(':') {
+                            return Err("only (?: groups are supported".into())
+                        }
+                    }
+                    let alts = self.alternatives()?;
+                    if self.chars.next() != Some(')') {
+                        return Err("unclosed group".into())
+                    }
+                    PatternNode::Group(alts)
+                }
+                '[' => self.class()?,
+                '.' => PatternNode::Any,
+                '^' => PatternNode::Start,
+                '$' => PatternNode::End,
+                '\\' => self.escape()?,
+                '*' | '+' | '?' | '{' => return Err(format!("nothing to repeat before '{}'", c)),
+                c => PatternNode::Char(c),
+            };
+            seq.push(self.quantifier(atom)?);
+        }
+        Ok(seq)
+    }
+
+    fn escape(&mut self) -> Result<PatternNode, String> {
+        let c = self.chars.next().ok_or("pattern ends in an escape")?;
+        let class = | ranges: &[(char, char)], negated | PatternNode::Class {ranges: ranges.to_vec(), negated};
+        Ok(match c {
+            'd' => class(DIGIT, false),
+            'D' => class(DIGIT, true),
+            'w' => class(WORD, false),
+            'W' => class(WORD, true),
+            's' => class(SPACE, false),
+            'S' => class(SPACE, true),
+            'n' => PatternNode::Char('\n'),
+            't' => PatternNode::Char('\t'),
+            'r' => PatternNode::Char('\r'),
+            c => PatternNode::Char(c)
+        })
+    }
+
+    fn class(&mut self) -> Result<PatternNode, String> {
+        let mut ranges = Vec::new();
+        let negated = self.chars.peek() == Some(&'^');
+        if negated {
+            self.chars.next();
+        }
+        let mut first = true;
+        loop {
+            let c = self.chars.next().ok_or("unclosed character class")?;
+            let start = match c {
+                ']' if !first => break,
+                '\\' => match self.chars.next().ok_or("unclosed character class")? {
+                    'd' => {ranges.extend_from_slice(DIGIT); first = false; continue}
+                    'w' => {ranges.extend_from_slice(WORD); first = false; continue}
+                    's' => {ranges.extend_from_slice(SPACE); first = false; continue}
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    c if c.is_ascii_alphabetic() => return Err(format!("\\{} is not supported in a class", c)),
+                    c => c
+                },
+                c => c
+            };
+            first = false;
+            let mut ahead = self.chars.clone();
+            if ahead.next() == Some('-') && ahead.peek().is_some_and( | c | *c != ']') {
+                self.chars.next();
+                let end = self.chars.next().unwrap();
+                if end < start {
+                    return Err(format!("range {}-{} is reversed", start, end))
+                }
+                ranges.push((start, end));
+            }
+            else {
+                ranges.push((start, start));
+            }
+        }
+        Ok(PatternNode::Class {ranges, negated})
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let mut digits = String::new();
+        while let Some(c) = self.chars.peek().filter( | c | c.is_ascii_digit()) {
+            digits.push(*c);
+            self.chars.next();
+        }
+        digits.parse().ok()
+    }
+
+    fn quantifier(&mut self, atom: PatternNode) -> Result<PatternNode, String> {
+        let (min, max) = match self.chars.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.chars.next();
+                let min = self.number().ok_or("expected a number after {")?;
+                let max = if self.chars.peek() == Some(&',') {
+                    self.chars.next();
+                    self.number()
+                }
+                else {
+                    Some(min)
+                };
+                if self.chars.next() != Some('}') {
+                    return Err("unclosed {".into())
+                }
+                if max.is_some_and( | max | max < min) {
+                    return Err(format!("{{{},{}}} is reversed", min, max.unwrap()))
+                }
+                return self.lazy(atom, min, max)
+            }
+            _ => return Ok(atom)
+        };
+        self.chars.next();
+        self.lazy(atom, min, max)
+    }
+
+    // a trailing ? asks for a lazy repeat, which can't change a whole-input match
+    fn lazy(&mut self, atom: PatternNode, min: usize, max: Option<usize>) -> Result<PatternNode, String> {
+        if self.chars.peek() == Some(&'?') {
+            self.chars.next();
+        }
+        if matches!(atom, PatternNode::Start | PatternNode::End) {
+            return Err("anchors can't be repeated".into())
+        }
+        Ok(PatternNode::Repeat {node: Box::new(atom), min, max})
+    }
+}
+
+impl TextPattern {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let mut parser = PatternParser {chars: pattern.chars().peekable()};
+        let alts = parser.alternatives()?;
+        if parser.chars.next().is_some() {
+            return Err("unmatched )".into())
+        }
+        Ok(Self {root: PatternNode::Group(alts)})
+    }
+
+    /// Whether `text` matches the pattern as a whole, text over `MAX_MATCH_LEN` chars doesn't.
+    pub fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        if text.len() > MAX_MATCH_LEN {
+            return false
+        }
+        match_node(&self.root, &text, 0, &mut | pos | pos == text.len())
+    }
+}
+
+impl PatternNode {
+    fn is_single_char(&self) -> bool {
+        matches!(self, PatternNode::Char(_) | PatternNode::Any | PatternNode::Class {..})
+    }
+
+    fn takes_char(&self, c: char) -> bool {
+        match self {
+            PatternNode::Char(d) => c == *d,
+            PatternNode::Any => c != '\n',
+            PatternNode::Class {ranges, negated} => ranges.iter().any( | (start, end) | c >= *start && c <= *end) != *negated,
+            _ => false
+        }
+    }
+}
+
+fn match_seq(nodes: &[PatternNode], text: &[char], pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+    match nodes.split_first() {
+        None => next(pos),
+        Some((node, rest)) => match_node(node, text, pos, &mut | pos | match_seq(rest, text, pos, &mut *next))
+    }
+}
+
+fn match_node(node: &PatternNode, text: &[char], pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+    match node {
+        PatternNode::Char(_) | PatternNode::Any | PatternNode::Class {..} => {
+            text.get(pos).is_some_and( | c | node.takes_char(*c)) && next(pos + 1)
+        }
+        PatternNode::Group(alts) => alts.iter().any( | alt | match_seq(alt, text, pos, &mut *next)),
+        PatternNode::Repeat {node, min, max} if node.is_single_char() => {
+            // the common `\d+` or `.*` takes the longest run in a loop and gives it back a char at a time,
+            // so it doesn't recurse once per char
+            let limit = max.map_or(text.len(), | max | text.len().min(pos + max));
+            let mut end = pos;
+            while end < limit && node.takes_char(text[end]) {
+                end += 1;
+            }
+            (pos + min..=end).rev().any(next)
+        }
+        PatternNode::Repeat {node, min, max} => match_repeat(node, *min, *max, 0, text, pos, next),
+        PatternNode::Start => pos == 0 && next(pos),
+        PatternNode::End => pos == text.len() && next(pos),
+    }
+}
+
+fn match_repeat(node: &PatternNode, min: usize, max: Option<usize>, count: usize, text: &[char], pos: usize, next: &mut dyn FnMut(usize) -> bool) -> bool {
+    // greedy, try one more repetition before giving the rest of the pattern a go
+    // count never passes max, so reaching it is the only way to run out
+    if max != Some(count) && match_node(node, text, pos, &mut | after | {
+        // a repetition that consumes nothing would loop forever once the minimum is met
+        (after != pos || count < min) && match_repeat(node, min, max, count + 1, text, after, &mut *next)
+    }) {
+        return true
+    }
+    count >= min && next(pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        TextPattern::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn literals_and_classes() {
+        assert!(matches("abc", "abc"));
+        assert!(!matches("abc", "abcd"));
+        assert!(!matches("abc", "ab"));
+        assert!(matches("a.c", "abc"));
+        assert!(!matches("a.c", "a\nc"));
+        assert!(matches("[a-c_]+", "ab_c"));
+        assert!(!matches("[a-c_]+", "abd"));
+        assert!(matches("[^0-9]+", "ab"));
+        assert!(!matches("[^0-9]+", "a1"));
+        assert!(matches("[]a]+", "]a"));
+        assert!(matches("[a-]+", "a-"));
+        assert!(matches("[\\d.]+", "1.5"));
+        assert!(matches("\\d\\w\\s", "1_ "));
+        assert!(matches("\\D\\W\\S", "a-b"));
+        assert!(matches("\\.\\*", ".*"));
+        assert!(matches("^ab$", "ab"));
+    }
+
+    #[test]
+    fn groups_and_quantifiers() {
+        assert!(matches("(ab|cd)+", "abcdab"));
+        assert!(!matches("(ab|cd)+", ""));
+        assert!(matches("(?:ab)*", ""));
+        assert!(matches("colou?r", "color"));
+        assert!(matches("colou?r", "colour"));
+        assert!(matches("\\d{3}", "123"));
+        assert!(!matches("\\d{3}", "12"));
+        assert!(matches("\\d{2,}", "1234"));
+        assert!(!matches("\\d{2,3}", "1234"));
+        assert!(matches("a*?b", "aab"));
+        // backtracking gives chars back to what follows
+        assert!(matches("a*ab", "aaab"));
+        assert!(matches("(a|ab)c", "abc"));
+        assert!(matches(".*x.*", "abxcd"));
+        // empty repetitions don't loop forever
+        assert!(matches("(a*)*b", "aab"));
+        assert!(!matches("(a*)*b", "aac"));
+    }
+
+    #[test]
+    fn rejects_bad_patterns() {
+        for pattern in ["(ab", "ab)", "[ab", "*a", "a{2", "a{3,1}", "[z-a]", "(?=a)", "a\\", "^*", "[\\q]"] {
+            assert!(TextPattern::new(pattern).is_err(), "{:?} parsed", pattern);
+        }
+    }
+
+    #[test]
+    fn long_input_stays_on_the_stack() {
+        let text = "ab".repeat(MAX_MATCH_LEN / 2);
+        assert!(matches("(ab)*", &text));
+        assert!(matches("[ab]*", &text));
+        assert!(matches(".*b", &text));
+        let text = "a".repeat(MAX_MATCH_LEN + 1);
+        assert!(!matches("a*", &text));
+    }
+}
@@ -79,6 +79,7 @@ live_design! {
         pow(0.36, THEME_COLOR_CONTRAST))
     )
     THEME_COLOR_BG_UNFOCUSSED = (THEME_COLOR_BG_HIGHLIGHT * 0.85)
+    THEME_COLOR_FOCUS_RING = (THEME_COLOR_U_5)
     THEME_COLOR_APP_CAPTION_BAR = (THEME_COLOR_D_HIDDEN)
     THEME_COLOR_DRAG_QUAD = (THEME_COLOR_U_5)
 
@@ -981,8 +982,6 @@ live_design! {
     }
 
     Button = <ButtonBase> {
-        // TODO: NEEDS FOCUS STATE
-
         width: Fit, height: Fit,
         spacing: 7.5,
         align: {x: 0.5, y: 0.5},
@@ -1024,6 +1023,7 @@ live_design! {
         draw_bg: {
             instance hover: 0.0
             instance pressed: 0.0
+            instance nav_focus: 0.0
             uniform border_radius: (THEME_CORNER_RADIUS)
             instance bodytop: (THEME_COLOR_CTRL_DEFAULT)
             instance bodybottom: (THEME_COLOR_CTRL_HOVER)
@@ -1059,6 +1059,15 @@ live_design! {
                     THEME_BEVELING
                 )
 
+                sdf.box(
+                    0.5,
+                    0.5,
+                    self.rect_size.x - 1.0,
+                    self.rect_size.y - 1.0,
+                    self.border_radius + 0.5
+                )
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0)
+
                 return sdf.result
             }
         }
@@ -1096,6 +1105,17 @@ live_design! {
                     }
                 }
             }
+            nav_focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_bg: {nav_focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {nav_focus: 1.0}}
+                }
+            }
         }
     }
 
@@ -1177,6 +1197,15 @@ live_design! {
                     THEME_BEVELING
                 )
 
+                sdf.box(
+                    0.5,
+                    0.5,
+                    self.rect_size.x - 1.0,
+                    self.rect_size.y - 1.0,
+                    self.border_radius + 0.5
+                )
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0)
+
                 return sdf.result
             }
         }
@@ -1254,6 +1283,15 @@ live_design! {
                     THEME_BEVELING
                 )
 
+                sdf.box(
+                    0.5,
+                    0.5,
+                    self.rect_size.x - 1.0,
+                    self.rect_size.y - 1.0,
+                    self.border_radius + 0.5
+                )
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0)
+
                 return sdf.result
             }
         }
@@ -1271,6 +1309,7 @@ live_design! {
 
         draw_check: {
             uniform size: 7.5;
+            instance nav_focus: 0.0
             fn pixel(self) -> vec4 {
                 let sdf = Sdf2d::viewport(self.pos * self.rect_size)
                 match self.check_type {
@@ -1346,6 +1385,8 @@ live_design! {
                         sdf.fill(THEME_COLOR_D_HIDDEN);
                     }
                 }
+                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 2.0);
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0);
                 return sdf.result
             }
         }
@@ -1434,6 +1475,17 @@ live_design! {
                     }
                 }
             }
+            nav_focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_check: {nav_focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_check: {nav_focus: 1.0}}
+                }
+            }
             selected = {
                 default: off
                 off = {
@@ -2357,6 +2409,7 @@ live_design! {
         draw_bg: {
             instance hover: 0.0
             instance focus: 0.0
+            instance nav_focus: 0.0
             instance pressed: 0.0
             instance open: 0.0
             
@@ -2431,6 +2484,9 @@ live_design! {
 
                 sdf.fill(mix(THEME_COLOR_TEXT_DEFAULT, THEME_COLOR_TEXT_HOVER, self.hover));
 
+                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, self.border_radius + 0.5);
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0);
+
                 return sdf.result
             }
         }
@@ -2485,6 +2541,257 @@ live_design! {
                     }
                 }
             }
+            nav_focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_bg: {nav_focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {nav_focus: 1.0}}
+                }
+            }
+        }
+    }
+
+    DatePickerDay = <PopupMenuItem> {
+        width: Fill, height: Fill,
+        align: {x: 0.5, y: 0.5}
+        padding: 0.
+
+        draw_bg: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(1., 1., self.rect_size.x - 2., self.rect_size.y - 2., 2.);
+                sdf.fill(mix(
+                    mix(THEME_COLOR_U_HIDDEN, self.color_selected, self.hover),
+                    THEME_COLOR_CTRL_SELECTED,
+                    self.selected
+                ));
+                return sdf.result;
+            }
+        }
+    }
+
+    DatePicker = <DatePickerBase> {
+        width: Fit, height: Fit,
+        padding: <THEME_MSPACE_2> { left: (THEME_SPACE_2), right: 25. }
+        align: {x: 0., y: 0.}
+
+        cell_size: vec2(28., 22.)
+        empty_message: "Pick a date"
+        month_names: ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"]
+        weekday_names: ["Su", "Mo", "Tu", "We", "Th", "Fr", "Sa"]
+        first_day_of_week: 1
+
+        draw_text: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: (THEME_FONT_SIZE_P)
+            }
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+
+        draw_title: {
+            text_style: <THEME_FONT_BOLD> {
+                font_size: (THEME_FONT_SIZE_P)
+            }
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+
+        draw_weekday: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: (THEME_FONT_SIZE_P)
+            }
+            color: (THEME_COLOR_TEXT_INACTIVE)
+        }
+
+        draw_month_button: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let c = self.rect_size * 0.5;
+                let sz = 3.5;
+                let dir = mix(1.0, -1.0, self.next);
+                sdf.move_to(c.x + sz * 0.5 * dir, c.y - sz);
+                sdf.line_to(c.x - sz * 0.5 * dir, c.y);
+                sdf.line_to(c.x + sz * 0.5 * dir, c.y + sz);
+                sdf.stroke(THEME_COLOR_TEXT_DEFAULT, 1.25);
+                return sdf.result;
+            }
+        }
+
+        draw_bg: {
+            instance hover: 0.0
+            instance focus: 0.0
+            instance nav_focus: 0.0
+            instance pressed: 0.0
+            instance open: 0.0
+            instance invalid: 0.0
+
+            uniform border_radius: (THEME_CORNER_RADIUS)
+            instance bodytop: (THEME_COLOR_U_HIDDEN)
+            instance bodybottom: (THEME_COLOR_CTRL_HOVER)
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let body = mix(mix(self.bodytop, self.bodybottom, self.hover), self.bodybottom, max(self.focus, self.open));
+
+                sdf.box(1., 1., self.rect_size.x - 2.0, self.rect_size.y - 2.0, self.border_radius)
+                sdf.fill_keep(body)
+                sdf.stroke(
+                    mix(mix(THEME_COLOR_BEVEL_SHADOW, THEME_COLOR_BEVEL_LIGHT, self.hover), THEME_COLOR_ERROR, self.invalid),
+                    THEME_BEVELING * 1.5
+                )
+
+                // a little calendar page on the right
+                let x = self.rect_size.x - 17.0;
+                let y = self.rect_size.y * 0.5 - 5.0;
+                sdf.rect(x, y, 10.0, 10.0);
+                sdf.stroke(mix(THEME_COLOR_TEXT_DEFAULT, THEME_COLOR_TEXT_HOVER, self.hover), 1.0);
+                sdf.rect(x, y, 10.0, 3.0);
+                sdf.fill(mix(THEME_COLOR_TEXT_DEFAULT, THEME_COLOR_TEXT_HOVER, self.hover));
+
+                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, self.border_radius + 0.5);
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0);
+                return sdf.result
+            }
+        }
+
+        popup_menu: <PopupMenu> {
+            width: Fit,
+            menu_item: <DatePickerDay> {}
+        }
+
+        animator: {
+            hover = {
+                default: off,
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_bg: {pressed: 0.0, hover: 0.0}}
+                }
+                on = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_bg: {pressed: 0.0, hover: [{time: 0.0, value: 1.0}]}}
+                }
+                pressed = {
+                    from: {all: Forward {duration: 0.2}}
+                    apply: {draw_bg: {pressed: [{time: 0.0, value: 1.0}], hover: 1.0}}
+                }
+            }
+            focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.2}}
+                    apply: {draw_bg: {focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {focus: 1.0}}
+                }
+            }
+            nav_focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_bg: {nav_focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {nav_focus: 1.0}}
+                }
+            }
+        }
+    }
+
+    TimePicker = <TimePickerBase> {
+        width: Fit, height: Fit,
+        padding: <THEME_MSPACE_1> { left: (THEME_SPACE_2), right: (THEME_SPACE_2) }
+        align: {y: 0.5}
+        segment_layout: { padding: {left: 2., right: 2., top: 1., bottom: 1.} }
+
+        minute_step: 1
+        drag_distance: 8.0
+        am_label: "AM"
+        pm_label: "PM"
+
+        draw_text: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: (THEME_FONT_SIZE_P)
+            }
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+
+        draw_segment: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(0., 0., self.rect_size.x, self.rect_size.y, 1.5);
+                sdf.fill(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_BG_HIGHLIGHT_INLINE, self.selected));
+                return sdf.result;
+            }
+        }
+
+        draw_bg: {
+            instance hover: 0.0
+            instance focus: 0.0
+            instance nav_focus: 0.0
+            instance pressed: 0.0
+            instance invalid: 0.0
+            uniform border_radius: (THEME_CORNER_RADIUS)
+            instance bodytop: (THEME_COLOR_INSET_DEFAULT)
+            instance bodybottom: (THEME_COLOR_CTRL_ACTIVE)
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(1., 1., self.rect_size.x - 2.0, self.rect_size.y - 2.0, self.border_radius)
+                sdf.fill_keep(mix(self.bodytop, self.bodybottom, self.focus))
+                sdf.stroke(
+                    mix(mix(THEME_COLOR_BEVEL_SHADOW, THEME_COLOR_BEVEL_LIGHT, self.hover), THEME_COLOR_ERROR, self.invalid),
+                    THEME_BEVELING * 0.9
+                )
+                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, self.border_radius + 0.5);
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0);
+                return sdf.result
+            }
+        }
+
+        animator: {
+            hover = {
+                default: off,
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_bg: {pressed: 0.0, hover: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {pressed: 0.0, hover: 1.0}}
+                }
+                pressed = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {pressed: 1.0, hover: 1.0}}
+                }
+            }
+            focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.2}}
+                    apply: {draw_bg: {focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {focus: 1.0}}
+                }
+            }
+            nav_focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_bg: {nav_focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {nav_focus: 1.0}}
+                }
+            }
         }
     }
 
@@ -2901,7 +3208,6 @@ live_design! {
     }
 
     RadioButton = <RadioButtonBase> {
-        // TODO: adda  focus states
         width: Fit, height: 16.,
         align: { x: 0.0, y: 0.5 }
 
@@ -2915,6 +3221,7 @@ live_design! {
 
         draw_radio: {
             uniform size: 7.0;
+            instance nav_focus: 0.0
             // uniform color_active: (THEME_COLOR_U_2)
             // uniform color_inactive: (THEME_COLOR_D_4)
 
@@ -2986,6 +3293,8 @@ live_design! {
                         sdf.stroke(bot_gradient, THEME_BEVELING * 1.5)
                     }
                 }
+                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 2.0);
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0);
                 return sdf.result
             }
         }
@@ -3080,6 +3389,17 @@ live_design! {
                     }
                 }
             }
+            nav_focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_radio: {nav_focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_radio: {nav_focus: 1.0}}
+                }
+            }
         }
     }
 
@@ -3189,6 +3509,212 @@ live_design! {
         flow: Down
     }
 
+    DataGridRow = <DataGridRowBase> {
+        draw_bg: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.rect(0., 0., self.rect_size.x, self.rect_size.y);
+                sdf.fill(
+                    mix(
+                        mix(
+                            mix(THEME_COLOR_BG_EVEN, THEME_COLOR_BG_ODD, self.is_even),
+                            THEME_COLOR_CTRL_HOVER,
+                            self.hover
+                        ),
+                        mix(THEME_COLOR_BG_UNFOCUSSED, THEME_COLOR_CTRL_SELECTED, self.focussed),
+                        self.selected
+                    )
+                );
+                sdf.rect(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0);
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.cursor * self.focussed), 1.0);
+                return sdf.result
+            }
+        }
+    }
+
+    DataGrid = <DataGridBase> {
+        width: Fill, height: Fill,
+        flow: Down
+
+        header_height: (THEME_DATA_ITEM_HEIGHT + 2.0)
+        row_height: (THEME_DATA_ITEM_HEIGHT)
+        resize_handle_width: 6.0
+        min_drag_distance: 6.0
+        header_layout: {
+            align: {y: 0.5}
+            spacing: (THEME_SPACE_1)
+            padding: {left: (THEME_SPACE_2), right: (THEME_SPACE_2)}
+        }
+        cell_layout: {
+            align: {y: 0.5}
+            padding: {left: (THEME_SPACE_2), right: (THEME_SPACE_2)}
+        }
+        sort_indicator_walk: {width: 8, height: 8}
+
+        list: <PortalList> {
+            tab_index: -1
+            drag_scrolling: false
+        }
+        scroll_bar: <ScrollBar> {}
+        row: <DataGridRow> {}
+
+        draw_header: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.rect(0., 0., self.rect_size.x, self.rect_size.y);
+                sdf.fill(
+                    mix(
+                        mix(THEME_COLOR_BG_CONTAINER, THEME_COLOR_CTRL_HOVER, self.hover),
+                        THEME_COLOR_CTRL_PRESSED,
+                        self.pressed
+                    )
+                );
+                sdf.rect(self.rect_size.x - 1.0, 3.0, 1.0, self.rect_size.y - 6.0);
+                sdf.fill(THEME_COLOR_DIVIDER);
+                sdf.rect(0., self.rect_size.y - 1.0, self.rect_size.x, 1.0);
+                sdf.fill(THEME_COLOR_DIVIDER);
+                return sdf.result
+            }
+        }
+
+        draw_header_text: {
+            color: (THEME_COLOR_TEXT_DEFAULT)
+            text_style: <THEME_FONT_BOLD> {font_size: (THEME_FONT_SIZE_P)}
+            wrap: Ellipsis
+        }
+
+        draw_sort: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let c = self.rect_size * 0.5;
+                let sz = min(c.x, c.y) * 0.8;
+                // ascending points up, descending points down
+                let dir = 1.0 - 2.0 * self.descending;
+                sdf.move_to(c.x - sz, c.y + sz * 0.5 * dir);
+                sdf.line_to(c.x + sz, c.y + sz * 0.5 * dir);
+                sdf.line_to(c.x, c.y - sz * 0.5 * dir);
+                sdf.close_path();
+                sdf.fill(THEME_COLOR_TEXT_DEFAULT);
+                return sdf.result
+            }
+        }
+
+        draw_drop_marker: {color: (THEME_COLOR_FOCUS_RING)}
+
+        Text = <Label> {
+            width: Fill
+            draw_text: {wrap: Ellipsis}
+        }
+    }
+
+    TreeViewRow = <TreeViewRowBase> {
+        align: {y: 0.5}
+        padding: {left: (THEME_SPACE_1), right: (THEME_SPACE_2)}
+
+        draw_bg: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.rect(0., 0., self.rect_size.x, self.rect_size.y);
+                sdf.fill(
+                    mix(
+                        mix(
+                            mix(THEME_COLOR_BG_EVEN, THEME_COLOR_BG_ODD, self.is_even),
+                            THEME_COLOR_CTRL_HOVER,
+                            max(self.hover, self.drop_into)
+                        ),
+                        mix(THEME_COLOR_BG_UNFOCUSSED, THEME_COLOR_CTRL_SELECTED, self.focussed),
+                        self.selected
+                    )
+                );
+                sdf.rect(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0);
+                sdf.stroke(
+                    mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, max(self.cursor * self.focussed, self.drop_into)),
+                    1.0
+                );
+                return sdf.result
+            }
+        }
+
+        draw_expander: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let c = self.rect_size * 0.5;
+                let sz = min(c.x, c.y) * 0.45;
+                // points right when closed and down when open
+                sdf.rotate(self.is_open * 0.5 * PI, c.x, c.y);
+                sdf.move_to(c.x - sz * 0.5, c.y - sz);
+                sdf.line_to(c.x + sz * 0.5, c.y);
+                sdf.line_to(c.x - sz * 0.5, c.y + sz);
+                sdf.close_path();
+                sdf.fill(mix(THEME_COLOR_TEXT_DEFAULT, THEME_COLOR_TEXT_SELECTED, self.selected));
+                return sdf.result
+            }
+        }
+    }
+
+    TreeView = <TreeViewBase> {
+        width: Fill, height: Fill,
+        flow: Down
+
+        row_height: (THEME_DATA_ITEM_HEIGHT)
+        indent_width: 12.0
+        expander_walk: {width: 14, height: Fill}
+        min_drag_distance: 6.0
+
+        list: <PortalList> {
+            tab_index: -1
+            drag_scrolling: false
+        }
+        row: <TreeViewRow> {}
+        loading: <Label> {
+            text: "Loading..."
+            draw_text: {color: (THEME_COLOR_TEXT_INACTIVE)}
+        }
+
+        draw_drop_marker: {color: (THEME_COLOR_FOCUS_RING)}
+
+        Text = <Label> {
+            width: Fill
+            draw_text: {wrap: Ellipsis}
+        }
+    }
+
+    // the folders and files of a FileTree as TreeView rows, filled by setting the text of `name`
+    FileTreeView = <TreeView> {
+        Folder = <View> {
+            width: Fill, height: Fit
+            align: {y: 0.5}
+            spacing: 3.0
+            icon = <View> {
+                width: (THEME_DATA_ICON_WIDTH - 2), height: (THEME_DATA_ICON_HEIGHT)
+                show_bg: true
+                draw_bg: {
+                    fn pixel(self) -> vec4 {
+                        let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                        let w = self.rect_size.x;
+                        let h = self.rect_size.y;
+                        sdf.box(0. * w, 0.35 * h, 0.87 * w, 0.39 * h, 0.75);
+                        sdf.box(0. * w, 0.28 * h, 0.5 * w, 0.3 * h, 1.);
+                        sdf.union();
+                        return sdf.fill(THEME_COLOR_TEXT_DEFAULT);
+                    }
+                }
+            }
+            name = <Label> {
+                width: Fill
+                draw_text: {wrap: Ellipsis}
+            }
+        }
+        File = <View> {
+            width: Fill, height: Fit
+            align: {y: 0.5}
+            name = <Label> {
+                width: Fill
+                draw_text: {wrap: Ellipsis}
+            }
+        }
+    }
+
     CachedScrollXY = <CachedView> {
         scroll_bars: <ScrollBars> {show_scroll_x: true, show_scroll_y: true}
     }
@@ -3242,6 +3768,23 @@ live_design! {
             }
         }
 
+        draw_placeholder: {
+            instance hover: 0.0
+            instance focus: 0.0
+            wrap: Word,
+            text_style: <THEME_FONT_REGULAR> {
+                line_spacing: (THEME_FONT_LINE_SPACING),
+                font_size: (THEME_FONT_SIZE_P)
+            }
+            fn get_color(self) -> vec4 {
+                return mix(
+                    THEME_COLOR_TEXT_PLACEHOLDER,
+                    THEME_COLOR_TEXT_DEFAULT,
+                    self.hover * (1.0 - self.focus)
+                )
+            }
+        }
+
         draw_cursor: {
             instance focus: 0.0
             uniform border_radius: 0.5
@@ -3286,6 +3829,7 @@ live_design! {
             instance radius: (THEME_CORNER_RADIUS)
             instance hover: 0.0
             instance focus: 0.0
+            instance invalid: 0.0
             instance bodytop: (THEME_COLOR_INSET_DEFAULT)
             instance bodybottom: (THEME_COLOR_CTRL_ACTIVE)
 
@@ -3325,7 +3869,7 @@ live_design! {
                 sdf.fill_keep(body)
 
                 sdf.stroke(
-                    bot_gradient,
+                    mix(bot_gradient, THEME_COLOR_ERROR, self.invalid),
                     THEME_BEVELING * 0.9
                 )
 
@@ -3341,6 +3885,7 @@ live_design! {
                     apply: {
                         draw_select: {hover: 0.0}
                         draw_text: {hover: 0.0}
+                        draw_placeholder: {hover: 0.0}
                     }
                 }
                 on = {
@@ -3348,6 +3893,7 @@ live_design! {
                     apply: {
                         draw_select: {hover: 1.0}
                         draw_text: {hover: 1.0}
+                        draw_placeholder: {hover: 1.0}
                     }
                 }
             }
@@ -3360,6 +3906,7 @@ live_design! {
                         draw_bg: {focus: 0.0},
                         draw_select: {focus: 0.0}
                         draw_text: {focus: 0.0}
+                        draw_placeholder: {focus: 0.0}
                     }
                 }
                 on = {
@@ -3369,6 +3916,7 @@ live_design! {
                         draw_bg: {focus: 1.0},
                         draw_select: {focus: 1.0}
                         draw_text: {focus: 1.0}
+                        draw_placeholder: {focus: 1.0}
                     }
                 }
             }
@@ -3387,13 +3935,16 @@ live_design! {
             instance hover: float
             instance focus: float
             instance drag: float
+            instance nav_focus: float
 
             fn pixel(self) -> vec4 {
                 let slider_height = 3;
                 let nub_size = mix(3, 5, self.hover);
                 let nubbg_size = mix(0, 13, self.hover)
 
-                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                // right to left sliders fill from the right
+                let pos = vec2(mix(self.pos.x, 1.0 - self.pos.x, self.rtl), self.pos.y);
+                let sdf = Sdf2d::viewport(pos * self.rect_size)
 
                 let slider_bg_color = mix(mix(THEME_COLOR_AMOUNT_TRACK_DEFAULT, THEME_COLOR_AMOUNT_TRACK_HOVER, self.hover), THEME_COLOR_AMOUNT_TRACK_ACTIVE, self.focus);
                 let slider_color = mix(
@@ -3446,6 +3997,8 @@ live_design! {
 
                     }
                 }
+                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 2.0);
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0);
                 return sdf.result
             }
         }
@@ -3467,6 +4020,8 @@ live_design! {
             cursor_size: 2.0,
             empty_message: "0",
             numeric_only: true,
+            // the slider itself is the tab stop
+            tab_index: -1,
 
             label_align: {y: 0.},
             margin: { bottom: (THEME_SPACE_2), left: (THEME_SPACE_2) }
@@ -3552,6 +4107,17 @@ live_design! {
                     apply: {draw_slider: {drag: 1.0}}
                 }
             }
+            nav_focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_slider: {nav_focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_slider: {nav_focus: 1.0}}
+                }
+            }
         }
     }
 
@@ -3629,10 +4195,137 @@ live_design! {
                     1.
                 ); // Nub outline gradient
 
+                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, 2.0);
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0);
+
+                return sdf.result
+            }
+        }
+    }
+
+    NumberInput = <NumberInputBase> {
+        width: 100, height: Fit,
+        padding: <THEME_MSPACE_1> { left: (THEME_SPACE_2), right: 18. }
+        align: {y: 0.5}
+
+        min: 0.0, max: 100.0,
+        step: 1.0,
+        precision: 0,
+        drag_distance: 4.0
+        stepper_width: 14.0
+
+        draw_unit: {
+            text_style: <THEME_FONT_REGULAR> {
+                font_size: (THEME_FONT_SIZE_P)
+            }
+            color: (THEME_COLOR_TEXT_INACTIVE)
+        }
+
+        text_input: <TextInput> {
+            width: Fit, padding: 0.,
+            cursor_margin_bottom: (THEME_SPACE_1),
+            cursor_margin_top: (THEME_SPACE_1),
+            select_pad_edges: 3.0
+            cursor_size: 2.0,
+            empty_message: "0",
+            numeric_only: true,
+            // dragging changes the value, a click or Return starts typing
+            read_only: true,
+            // the number input itself is the tab stop
+            tab_index: -1,
+            draw_bg: {
+                fn pixel(self) -> vec4 {
+                    return vec4(0.0)
+                }
+            }
+        }
+
+        draw_bg: {
+            instance hover: 0.0
+            instance focus: 0.0
+            instance drag: 0.0
+            instance nav_focus: 0.0
+            instance invalid: 0.0
+            uniform border_radius: (THEME_CORNER_RADIUS)
+            instance bodytop: (THEME_COLOR_INSET_DEFAULT)
+            instance bodybottom: (THEME_COLOR_CTRL_ACTIVE)
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(1., 1., self.rect_size.x - 2.0, self.rect_size.y - 2.0, self.border_radius)
+                sdf.fill_keep(mix(self.bodytop, self.bodybottom, max(self.focus, self.drag)))
+                sdf.stroke(
+                    mix(mix(THEME_COLOR_BEVEL_SHADOW, THEME_COLOR_BEVEL_LIGHT, self.hover), THEME_COLOR_ERROR, self.invalid),
+                    THEME_BEVELING * 0.9
+                )
+
+                // the up and down arrows of the stepper
+                let c = vec2(self.rect_size.x - self.stepper_width * 0.5, self.rect_size.y * 0.5);
+                let sz = 2.5;
+                let color = mix(THEME_COLOR_TEXT_INACTIVE, THEME_COLOR_TEXT_HOVER, self.hover);
+                sdf.move_to(c.x - sz, c.y - 2.0);
+                sdf.line_to(c.x, c.y - 2.0 - sz);
+                sdf.line_to(c.x + sz, c.y - 2.0);
+                sdf.close_path();
+                sdf.fill(color);
+                sdf.move_to(c.x - sz, c.y + 2.0);
+                sdf.line_to(c.x, c.y + 2.0 + sz);
+                sdf.line_to(c.x + sz, c.y + 2.0);
+                sdf.close_path();
+                sdf.fill(color);
 
+                sdf.box(0.5, 0.5, self.rect_size.x - 1.0, self.rect_size.y - 1.0, self.border_radius + 0.5);
+                sdf.stroke(mix(THEME_COLOR_U_HIDDEN, THEME_COLOR_FOCUS_RING, self.nav_focus), 1.0);
                 return sdf.result
             }
         }
+
+        animator: {
+            hover = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.2}}
+                    apply: {draw_bg: {hover: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {hover: 1.0}}
+                }
+            }
+            focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_bg: {focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {focus: 1.0}}
+                }
+            }
+            drag = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_bg: {drag: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {drag: 1.0}}
+                }
+            }
+            nav_focus = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {draw_bg: {nav_focus: 0.0}}
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {draw_bg: {nav_focus: 1.0}}
+                }
+            }
+        }
     }
 
 
@@ -0,0 +1,488 @@
+use {
+    std::{fmt, rc::Rc},
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        widget::*,
+    }
+};
+
+live_design!{
+    DrawTimeSegment = {{DrawTimeSegment}} {}
+    TimePickerBase = {{TimePicker}} {}
+}
+
+/// A time of day, `hour` runs from 0 to 23
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+impl Time {
+    pub fn new(hour: u32, minute: u32, second: u32) -> Option<Self> {
+        if hour < 24 && minute < 60 && second < 60 {
+            return Some(Self {hour, minute, second})
+        }
+        None
+    }
+
+    /// Parses `HH:MM` or `HH:MM:SS`
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().splitn(3, ':');
+        let hour = parts.next()?.parse().ok()?;
+        let minute = parts.next()?.parse().ok()?;
+        let second = match parts.next() {
+            Some(second) => second.parse().ok()?,
+            None => 0
+        };
+        Self::new(hour, minute, second)
+    }
+
+    /// `HH:MM:SS`, or `HH:MM` without the seconds
+    pub fn format(&self, seconds: bool) -> String {
+        if seconds {
+            return self.to_string()
+        }
+        format!("{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TimeSegment {
+    Hour,
+    Minute,
+    Second,
+    Period,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawTimeSegment {
+    #[deref] draw_super: DrawQuad,
+    #[live] selected: f32,
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct TimePicker {
+    #[animator] animator: Animator,
+
+    #[redraw] #[live] draw_bg: DrawQuad,
+    #[live] draw_segment: DrawTimeSegment,
+    #[live] draw_text: DrawText,
+
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+    #[live] segment_layout: Layout,
+
+    #[live] show_seconds: bool,
+    /// Shows 1 to 12 with an AM/PM segment instead of 0 to 23
+    #[live] hour12: bool,
+    /// The minute segment moves in these steps from the keyboard and while dragging
+    #[live] minute_step: u32,
+    /// Pixels the finger moves up or down for one step while dragging a segment
+    #[live] drag_distance: f64,
+    #[live] am_label: String,
+    #[live] pm_label: String,
+
+    #[live] tab_index: i64,
+
+    #[rust] time: Time,
+    #[rust] segment: usize,
+    #[rust] segment_areas: Vec<Area>,
+    // the first digit typed into the segment, waiting for a second one
+    #[rust] typed: Option<u32>,
+    #[rust] drag_start: Option<Time>,
+    #[rust] data_error: Option<String>,
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum TimePickerAction {
+    Changed(Time),
+    None
+}
+
+impl TimePicker {
+
+    fn segments(&self) -> Vec<TimeSegment> {
+        let mut segments = vec![TimeSegment::Hour, TimeSegment::Minute];
+        if self.show_seconds {
+            segments.push(TimeSegment::Second);
+        }
+        if self.hour12 {
+            segments.push(TimeSegment::Period);
+        }
+        segments
+    }
+
+    fn segment_text(&self, segment: TimeSegment) -> String {
+        match segment {
+            TimeSegment::Hour if self.hour12 => format!("{:02}", (self.time.hour + 11) % 12 + 1),
+            TimeSegment::Hour => format!("{:02}", self.time.hour),
+            TimeSegment::Minute => format!("{:02}", self.time.minute),
+            TimeSegment::Second => format!("{:02}", self.time.second),
+            TimeSegment::Period if self.time.hour < 12 => self.am_label.clone(),
+            TimeSegment::Period => self.pm_label.clone(),
+        }
+    }
+
+    /// `time` with `segment` moved `steps` steps, wrapping around without touching the other segments
+    fn step_segment(&self, time: Time, segment: TimeSegment, steps: i64) -> Time {
+        let mut time = time;
+        match segment {
+            TimeSegment::Hour if self.hour12 => {
+                time.hour = (time.hour as i64 % 12 + steps).rem_euclid(12) as u32 + time.hour / 12 * 12;
+            }
+            TimeSegment::Hour => time.hour = (time.hour as i64 + steps).rem_euclid(24) as u32,
+            TimeSegment::Minute => {
+                let step = self.minute_step.clamp(1, 30);
+                let count = (59 / step + 1) as i64;
+                time.minute = ((time.minute / step) as i64 + steps).rem_euclid(count) as u32 * step;
+            }
+            TimeSegment::Second => time.second = (time.second as i64 + steps).rem_euclid(60) as u32,
+            TimeSegment::Period if steps.rem_euclid(2) == 1 => time.hour = (time.hour + 12) % 24,
+            TimeSegment::Period => ()
+        }
+        time
+    }
+
+    fn set_time_action(&mut self, cx: &mut Cx, scope: &mut Scope, time: Time) {
+        if time != self.time {
+            self.time = time;
+            cx.widget_action(self.widget_uid(), &scope.path, TimePickerAction::Changed(time));
+            self.draw_bg.redraw(cx);
+        }
+    }
+
+    fn select_segment(&mut self, cx: &mut Cx, segment: usize) {
+        self.segment = segment.min(self.segments().len() - 1);
+        self.typed = None;
+        self.draw_bg.redraw(cx);
+    }
+
+    fn type_digit(&mut self, cx: &mut Cx, scope: &mut Scope, digit: u32) {
+        let segment = self.segments()[self.segment];
+        let max = match segment {
+            TimeSegment::Hour if self.hour12 => 12,
+            TimeSegment::Hour => 23,
+            TimeSegment::Minute | TimeSegment::Second => 59,
+            TimeSegment::Period => return
+        };
+        let (value, done) = match self.typed.take() {
+            Some(first) => ((first * 10 + digit).min(max), true),
+            // a first digit that can't start a two digit value is the whole value
+            None => (digit, digit * 10 > max)
+        };
+        let mut time = self.time;
+        match segment {
+            TimeSegment::Hour if self.hour12 => time.hour = value % 12 + time.hour / 12 * 12,
+            TimeSegment::Hour => time.hour = value,
+            TimeSegment::Minute => time.minute = value,
+            _ => time.second = value,
+        }
+        self.set_time_action(cx, scope, time);
+        if done {
+            if self.segment + 1 < self.segments().len() {
+                self.select_segment(cx, self.segment + 1);
+            }
+        }
+        else {
+            self.typed = Some(digit);
+        }
+    }
+
+    pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        let focussed = cx.has_key_focus(self.draw_bg.area());
+        self.draw_bg.begin(cx, walk, self.layout);
+        self.segment_areas.clear();
+        for (index, segment) in self.segments().into_iter().enumerate() {
+            if index > 0 {
+                let separator = if segment == TimeSegment::Period {" "} else {":"};
+                self.draw_text.draw_walk(cx, Walk::fit(), Align::default(), separator);
+            }
+            self.draw_segment.selected = if focussed && index == self.segment {1.0} else {0.0};
+            self.draw_segment.begin(cx, Walk::fit(), self.segment_layout);
+            let text = self.segment_text(segment);
+            self.draw_text.draw_walk(cx, Walk::fit(), Align::default(), &text);
+            self.draw_segment.end(cx);
+            self.segment_areas.push(self.draw_segment.area());
+        }
+        self.draw_bg.end(cx);
+        cx.add_nav_stop_with_tab_index(self.draw_bg.area(), NavRole::TextInput, self.tab_index);
+    }
+}
+
+impl Widget for TimePicker {
+
+    fn widget_to_data(&self, _cx: &mut Cx, actions: &Actions, nodes: &mut LiveNodeVec, path: &[LiveId]) -> bool {
+        match actions.find_widget_action_cast(self.widget_uid()) {
+            TimePickerAction::Changed(time) => {
+                nodes.write_field_value(path, LiveValue::String(Rc::new(time.format(self.show_seconds))));
+                true
+            }
+            _ => false
+        }
+    }
+
+    fn data_to_widget(&mut self, cx: &mut Cx, nodes: &[LiveNode], path: &[LiveId]) {
+        let time = match nodes.read_field_value(path) {
+            Some(LiveValue::String(v)) => Time::parse(v),
+            Some(LiveValue::Str(v)) => Time::parse(v),
+            Some(LiveValue::InlineString(v)) => Time::parse(v.as_str()),
+            _ => return
+        };
+        if let Some(time) = time {
+            if time != self.time {
+                self.time = time;
+                self.redraw(cx);
+            }
+        }
+    }
+
+    fn set_data_error(&mut self, cx: &mut Cx, error: Option<&str>) {
+        if self.data_error.as_deref() != error {
+            self.data_error = error.map( | e | e.to_string());
+            let invalid = if error.is_some() {1.0} else {0.0};
+            self.draw_bg.apply_over(cx, live!{invalid: (invalid)});
+            self.draw_bg.redraw(cx);
+        }
+    }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.animator_handle_event(cx, event);
+
+        match event.hits(cx, self.draw_bg.area()) {
+            Hit::Trigger(te) if te.0.iter().any( | t | t.id == live_id!(nav_focus)) => {
+                self.animator_play(cx, id!(nav_focus.on));
+            }
+            Hit::KeyFocus(_) => {
+                self.animator_play(cx, id!(focus.on));
+                self.draw_bg.redraw(cx);
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animator_play(cx, id!(focus.off));
+                self.animator_play(cx, id!(nav_focus.off));
+                self.typed = None;
+                self.draw_bg.redraw(cx);
+            }
+            Hit::KeyDown(ke) => {
+                let segment = self.segments()[self.segment];
+                match ke.key_code {
+                    KeyCode::ArrowLeft if self.segment > 0 => self.select_segment(cx, self.segment - 1),
+                    KeyCode::ArrowRight => self.select_segment(cx, self.segment + 1),
+                    KeyCode::Home => self.select_segment(cx, 0),
+                    KeyCode::End => self.select_segment(cx, usize::MAX),
+                    KeyCode::ArrowUp => {
+                        self.typed = None;
+                        let time = self.step_segment(self.time, segment, 1);
+                        self.set_time_action(cx, scope, time);
+                    }
+                    KeyCode::ArrowDown => {
+                        self.typed = None;
+                        let time = self.step_segment(self.time, segment, -1);
+                        self.set_time_action(cx, scope, time);
+                    }
+                    KeyCode::KeyA | KeyCode::KeyP if segment == TimeSegment::Period => {
+                        let pm = ke.key_code == KeyCode::KeyP;
+                        if pm != (self.time.hour >= 12) {
+                            let time = self.step_segment(self.time, segment, 1);
+                            self.set_time_action(cx, scope, time);
+                        }
+                    }
+                    key_code => {
+                        if let Some(digit) = key_code.to_char(false).and_then( | c | c.to_digit(10)) {
+                            self.type_digit(cx, scope, digit);
+                        }
+                    }
+                }
+            }
+            Hit::FingerDown(fe) => {
+                cx.set_key_focus(self.draw_bg.area());
+                if let Some(index) = self.segment_areas.iter().position( | area | area.rect(cx).contains(fe.abs)) {
+                    self.select_segment(cx, index);
+                }
+                self.drag_start = Some(self.time);
+                self.animator_play(cx, id!(hover.pressed));
+            }
+            Hit::FingerMove(fe) => {
+                if let Some(start) = self.drag_start {
+                    let steps = ((fe.abs_start.y - fe.abs.y) / self.drag_distance.max(1.0)) as i64;
+                    let segment = self.segments()[self.segment];
+                    let time = self.step_segment(start, segment, steps);
+                    self.typed = None;
+                    self.set_time_action(cx, scope, time);
+                }
+            }
+            Hit::FingerHoverIn(_) => {
+                cx.set_cursor(MouseCursor::NsResize);
+                self.animator_play(cx, id!(hover.on));
+            }
+            Hit::FingerHoverOut(_) => {
+                self.animator_play(cx, id!(hover.off));
+            }
+            Hit::FingerUp(fe) => {
+                self.drag_start = None;
+                if fe.is_over && fe.device.has_hovers() {
+                    self.animator_play(cx, id!(hover.on));
+                }
+                else {
+                    self.animator_play(cx, id!(hover.off));
+                }
+            }
+            _ => ()
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.draw_walk(cx, walk);
+        DrawStep::done()
+    }
+
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::DateEditor, "");
+        node.value = AccessValue::Text(self.time.format(self.show_seconds));
+        node.state.focusable = self.tab_index >= 0;
+        node.state.focused = cx.has_key_focus(self.draw_bg.area());
+        node.rect = self.draw_bg.area().rect(cx);
+        Some(node)
+    }
+
+    fn text(&self) -> String {
+        self.time.format(self.show_seconds)
+    }
+
+    fn set_text(&mut self, v: &str) {
+        if let Some(time) = Time::parse(v) {
+            self.time = time;
+        }
+    }
+}
+
+impl TimePickerRef {
+    pub fn time(&self) -> Option<Time> {
+        self.borrow().map( | inner | inner.time)
+    }
+
+    pub fn set_time(&self, cx: &mut Cx, time: Time) {
+        if let Some(mut inner) = self.borrow_mut() {
+            if inner.time != time {
+                inner.time = time;
+                inner.draw_bg.redraw(cx);
+            }
+        }
+    }
+
+    pub fn changed(&self, actions: &Actions) -> Option<Time> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let TimePickerAction::Changed(time) = item.cast() {
+                return Some(time)
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32, second: u32) -> Time {
+        Time::new(hour, minute, second).unwrap()
+    }
+
+    fn with_picker(f: impl FnOnce(&mut Cx, &mut TimePicker)) {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let mut picker = TimePicker::new(&mut cx);
+        picker.minute_step = 1;
+        f(&mut cx, &mut picker);
+    }
+
+    fn type_digits(cx: &mut Cx, picker: &mut TimePicker, digits: &[u32]) {
+        for digit in digits {
+            picker.type_digit(cx, &mut Scope::empty(), *digit);
+        }
+    }
+
+    #[test]
+    fn parses_and_formats_times() {
+        assert_eq!(Time::parse("09:05"), Some(time(9, 5, 0)));
+        assert_eq!(Time::parse(" 23:59:59 "), Some(time(23, 59, 59)));
+        assert_eq!(Time::parse("7:3:1"), Some(time(7, 3, 1)));
+        assert_eq!(Time::parse("24:00"), None);
+        assert_eq!(Time::parse("12:60"), None);
+        assert_eq!(Time::parse("12:00:60"), None);
+        assert_eq!(Time::parse("12"), None);
+        assert_eq!(Time::parse("-1:00"), None);
+        assert_eq!(Time::parse("noon"), None);
+        assert_eq!(time(7, 3, 1).format(true), "07:03:01");
+        assert_eq!(time(7, 3, 1).format(false), "07:03");
+        assert_eq!(Time::parse(&time(18, 30, 5).to_string()), Some(time(18, 30, 5)));
+    }
+
+    #[test]
+    fn stepping_wraps_within_a_segment() {
+        with_picker( | _, picker | {
+            let t = time(23, 59, 59);
+            assert_eq!(picker.step_segment(t, TimeSegment::Hour, 1), time(0, 59, 59));
+            assert_eq!(picker.step_segment(t, TimeSegment::Minute, 1), time(23, 0, 59));
+            assert_eq!(picker.step_segment(t, TimeSegment::Second, 62), time(23, 59, 1));
+            assert_eq!(picker.step_segment(time(0, 0, 0), TimeSegment::Hour, -1), time(23, 0, 0));
+            // minutes move in steps and snap down onto them
+            picker.minute_step = 15;
+            assert_eq!(picker.step_segment(time(8, 50, 0), TimeSegment::Minute, 1), time(8, 0, 0));
+            assert_eq!(picker.step_segment(time(8, 20, 0), TimeSegment::Minute, 1), time(8, 30, 0));
+            assert_eq!(picker.step_segment(time(8, 0, 0), TimeSegment::Minute, -1), time(8, 45, 0));
+            // a 12 hour clock keeps the half of the day, the period flips it
+            picker.hour12 = true;
+            assert_eq!(picker.step_segment(time(11, 0, 0), TimeSegment::Hour, 1), time(0, 0, 0));
+            assert_eq!(picker.step_segment(time(23, 0, 0), TimeSegment::Hour, 1), time(12, 0, 0));
+            assert_eq!(picker.step_segment(time(12, 0, 0), TimeSegment::Hour, -1), time(23, 0, 0));
+            assert_eq!(picker.step_segment(time(9, 0, 0), TimeSegment::Period, 1), time(21, 0, 0));
+            assert_eq!(picker.step_segment(time(21, 0, 0), TimeSegment::Period, -3), time(9, 0, 0));
+            assert_eq!(picker.step_segment(time(21, 0, 0), TimeSegment::Period, 2), time(21, 0, 0));
+        })
+    }
+
+    #[test]
+    fn typed_digits_fill_and_clamp_segments() {
+        with_picker( | cx, picker | {
+            // two digits fill a segment and move on to the next one
+            type_digits(cx, picker, &[1, 4, 3, 0]);
+            assert_eq!(picker.time, time(14, 30, 0));
+            assert_eq!(picker.segment, 1);
+            // a digit that can't start a two digit value completes the segment alone
+            picker.select_segment(cx, 0);
+            type_digits(cx, picker, &[7]);
+            assert_eq!((picker.time, picker.segment), (time(7, 30, 0), 1));
+            // too large values clamp to the segment maximum
+            picker.select_segment(cx, 0);
+            type_digits(cx, picker, &[2, 9]);
+            assert_eq!(picker.time, time(23, 30, 0));
+            type_digits(cx, picker, &[5, 9]);
+            assert_eq!(picker.time, time(23, 59, 0));
+            
+            // on a 12 hour clock 12 is the first hour of the half day
+            picker.hour12 = true;
+            picker.select_segment(cx, 0);
+            type_digits(cx, picker, &[1, 2]);
+            assert_eq!(picker.time, time(12, 59, 0));
+            picker.select_segment(cx, 0);
+            type_digits(cx, picker, &[3]);
+            assert_eq!(picker.time, time(15, 59, 0));
+            assert_eq!(picker.segment_text(TimeSegment::Hour), "03");
+            // digits do nothing on the period
+            picker.select_segment(cx, 2);
+            type_digits(cx, picker, &[1]);
+            assert_eq!(picker.time, time(15, 59, 0));
+        })
+    }
+}
@@ -0,0 +1,926 @@
+use {
+    std::collections::HashSet,
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        widget::*,
+        portal_list::PortalList,
+    }
+};
+
+live_design!{
+    DrawTreeViewRow = {{DrawTreeViewRow}} {}
+    DrawTreeViewExpander = {{DrawTreeViewExpander}} {}
+    TreeViewRowBase = {{TreeViewRow}} {}
+    TreeViewBase = {{TreeView}} {}
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawTreeViewRow {
+    #[deref] draw_super: DrawQuad,
+    #[live] is_even: f32,
+    #[live] selected: f32,
+    #[live] hover: f32,
+    #[live] focussed: f32,
+    #[live] cursor: f32,
+    #[live] drop_into: f32,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]#[repr(C)]
+struct DrawTreeViewExpander {
+    #[deref] draw_super: DrawQuad,
+    #[live] is_open: f32,
+    #[live] selected: f32,
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct TreeViewRow {
+    #[live] draw_bg: DrawTreeViewRow,
+    #[live] draw_expander: DrawTreeViewExpander,
+    #[layout] layout: Layout,
+}
+
+/// Feeds a `TreeView` while it draws. Children are only asked for once their
+/// parent is expanded, so a provider can fetch them when it sees `TreeViewAction::Expanded`.
+pub trait TreeViewProvider {
+    /// Pushes the children of `parent`, or of the top level for `None`, into `out`.
+    /// Returns `false` while they are still loading, the tree then shows its `loading` row.
+    fn children(&mut self, cx: &mut Cx, parent: Option<LiveId>, out: &mut Vec<LiveId>) -> bool;
+
+    /// Whether the node can be expanded, also before its children are loaded
+    fn has_children(&self, node: LiveId) -> bool;
+
+    /// The row template from the `live_design!` of the tree that shows this node
+    fn row_template(&self, node: LiveId) -> LiveId;
+
+    /// Puts the node into the widget made from its template, usually with `set_text`.
+    fn fill_row(&mut self, cx: &mut Cx, node: LiveId, row: &WidgetRef);
+}
+
+/// Where dragged nodes land relative to the node they were dropped on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TreeViewDropPosition {
+    Before,
+    After,
+    /// As the last child of the target
+    Into,
+}
+
+/// In `Multi` control or command toggles single nodes and shift selects ranges
+#[derive(Copy, Clone, Debug, PartialEq, Live, LiveHook)]
+#[live_ignore]
+pub enum TreeViewSelectionMode {
+    None,
+    Single,
+    #[pick] Multi,
+}
+
+#[derive(Clone, Copy)]
+struct TreeViewEntry {
+    node: LiveId,
+    parent: Option<LiveId>,
+    depth: usize,
+    has_children: bool,
+    // stands in for the children of `parent` while they load, `node` is then the parent
+    is_loading: bool,
+}
+
+struct TreeViewPress {
+    node: LiveId,
+    // pressing a selected node keeps the selection for dragging until the finger goes up
+    select_on_up: bool,
+    dragging: bool,
+    target: Option<(LiveId, TreeViewDropPosition)>,
+}
+
+#[derive(Clone)]
+enum TreeViewDrawState {
+    Rows
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum TreeViewAction {
+    Expanded(LiveId),
+    Collapsed(LiveId),
+    SelectionChanged,
+    Activated(LiveId),
+    /// `nodes` are in tree order, the tree itself does not change until the provider does
+    Move {nodes: Vec<LiveId>, target: LiveId, position: TreeViewDropPosition},
+    None
+}
+
+#[derive(Live, Widget)]
+pub struct TreeView {
+    #[redraw] #[rust] area: Area,
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+
+    #[live] list: PortalList,
+    #[live] row: Option<LivePtr>,
+    #[live] loading: Option<LivePtr>,
+    #[live] draw_drop_marker: DrawColor,
+
+    #[live] row_height: f64,
+    #[live] indent_width: f64,
+    #[live] expander_walk: Walk,
+    #[live] min_drag_distance: f64,
+    #[live] selection_mode: TreeViewSelectionMode,
+    #[live(true)] reorderable: bool,
+    /// The tree is one Tab stop, arrows move the cursor and open or close nodes
+    #[live] tab_index: i64,
+
+    #[rust] templates: ComponentMap<LiveId, LivePtr>,
+    #[rust] contents: ComponentMap<(LiveId, LiveId), WidgetRef>,
+    #[rust] rows: ComponentMap<LiveId, TreeViewRow>,
+    #[rust] loading_rows: ComponentMap<LiveId, WidgetRef>,
+    // every row of the last draw in tree order, used for keyboard navigation and drops
+    #[rust] entries: Vec<TreeViewEntry>,
+    #[rust] expanded: HashSet<LiveId>,
+    #[rust] selected: HashSet<LiveId>,
+    #[rust] anchor: Option<LiveId>,
+    #[rust] cursor: Option<LiveId>,
+    #[rust] hover: Option<LiveId>,
+    #[rust] press: Option<TreeViewPress>,
+    #[rust] draw_state: DrawStateWrap<TreeViewDrawState>,
+}
+
+impl LiveHook for TreeView {
+    fn before_apply(&mut self, _cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if let ApplyFrom::UpdateFromDoc {..} = apply.from {
+            self.templates.clear();
+        }
+    }
+
+    // the instances in the tree are its row templates
+    fn apply_value_instance(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        if nodes[index].is_instance_prop() {
+            if let Some(live_ptr) = apply.from.to_live_ptr(cx, index){
+                let id = nodes[index].id;
+                self.templates.insert(id, live_ptr);
+                for ((_, templ_id), content) in self.contents.iter_mut() {
+                    if *templ_id == id {
+                        content.apply(cx, apply, index, nodes);
+                    }
+                }
+            }
+        }
+        else {
+            cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+        }
+        nodes.skip_node(index)
+    }
+
+    fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) {
+        if let Some(index) = nodes.child_by_name(index, live_id!(row).as_field()) {
+            for row in self.rows.values_mut() {
+                row.apply(cx, apply, index, nodes);
+            }
+        }
+        if let Some(index) = nodes.child_by_name(index, live_id!(loading).as_field()) {
+            for row in self.loading_rows.values_mut() {
+                row.apply(cx, apply, index, nodes);
+            }
+        }
+        self.area.redraw(cx);
+    }
+}
+
+impl TreeView {
+    pub fn is_expanded(&self, node: LiveId) -> bool {
+        self.expanded.contains(&node)
+    }
+
+    /// Opens or closes a node without emitting an action
+    pub fn set_expanded(&mut self, cx: &mut Cx, node: LiveId, is_expanded: bool) {
+        if is_expanded {
+            self.expanded.insert(node);
+        }
+        else {
+            self.expanded.remove(&node);
+            if self.cursor.is_some_and( | cursor | cursor != node && self.is_inside(cursor, &[node])) {
+                self.cursor = Some(node);
+            }
+        }
+        self.area.redraw(cx);
+    }
+
+    /// The selected nodes that were drawn last, in tree order
+    pub fn selected_nodes(&self) -> Vec<LiveId> {
+        self.entries.iter()
+            .filter( | entry | !entry.is_loading && self.selected.contains(&entry.node))
+            .map( | entry | entry.node)
+            .collect()
+    }
+
+    pub fn set_selection(&mut self, cx: &mut Cx, nodes: &[LiveId]) {
+        self.selected = nodes.iter().cloned().collect();
+        self.anchor = nodes.first().cloned();
+        self.cursor = nodes.first().cloned();
+        self.area.redraw(cx);
+    }
+
+    /// Draws the rows in view, call it when the tree returns a draw step.
+    pub fn draw_nodes(&mut self, cx: &mut Cx2d, scope: &mut Scope, provider: &mut dyn TreeViewProvider) {
+        let mut entries = std::mem::take(&mut self.entries);
+        entries.clear();
+        Self::collect_entries(cx, provider, &self.expanded, &mut entries, None, 0);
+        self.entries = entries;
+
+        self.list.set_item_range(cx, 0, self.entries.len());
+        let focussed = cx.has_key_focus(self.area);
+        let drop_into = match &self.press {
+            Some(TreeViewPress {dragging: true, target: Some((target, TreeViewDropPosition::Into)), ..}) => Some(*target),
+            _ => None
+        };
+        while let Some(index) = self.list.next_visible_item(cx) {
+            // leaving the turtle empty past the last row ends the list
+            let Some(entry) = self.entries.get(index).cloned() else {continue};
+            let indent = Walk::size(Size::Fixed(entry.depth as f64 * self.indent_width), Size::Fixed(0.0));
+            if entry.is_loading {
+                cx.begin_turtle(Walk::size(Size::Fill, Size::Fixed(self.row_height)), Layout::flow_right());
+                cx.walk_turtle(indent);
+                cx.walk_turtle(self.expander_walk);
+                let row = self.loading_rows.get_or_insert(cx, entry.node, | cx | {
+                    WidgetRef::new_from_ptr(cx, self.loading)
+                });
+                row.draw_all(cx, scope);
+                cx.end_turtle();
+                continue
+            }
+
+            let node = entry.node;
+            let row = self.rows.get_or_insert(cx, node, | cx | TreeViewRow::new_from_ptr(cx, self.row));
+            let selected = if self.selected.contains(&node) {1.0} else {0.0};
+            row.draw_bg.is_even = if index % 2 == 0 {1.0} else {0.0};
+            row.draw_bg.selected = selected;
+            row.draw_bg.hover = if self.hover == Some(node) {1.0} else {0.0};
+            row.draw_bg.focussed = if focussed {1.0} else {0.0};
+            row.draw_bg.cursor = if self.cursor == Some(node) {1.0} else {0.0};
+            row.draw_bg.drop_into = if drop_into == Some(node) {1.0} else {0.0};
+            row.draw_bg.begin(cx, Walk::size(Size::Fill, Size::Fixed(self.row_height)), row.layout);
+            cx.walk_turtle(indent);
+            if entry.has_children {
+                row.draw_expander.is_open = if self.expanded.contains(&node) {1.0} else {0.0};
+                row.draw_expander.selected = selected;
+                row.draw_expander.draw_walk(cx, self.expander_walk);
+            }
+            else {
+                cx.walk_turtle(self.expander_walk);
+            }
+
+            let template = provider.row_template(node);
+            if let Some(ptr) = self.templates.get(&template) {
+                let content = self.contents.get_or_insert(cx, (node, template), | cx | {
+                    WidgetRef::new_from_ptr(cx, Some(*ptr))
+                }).clone();
+                provider.fill_row(cx, node, &content);
+                content.draw_all(cx, scope);
+            }
+            else {
+                warning!("Template not found: {template}. Did you add it to the <TreeView> instance in `live_design!{{}}`?");
+            }
+            row.draw_bg.end(cx);
+        }
+        self.rows.retain_visible();
+        self.contents.retain_visible();
+        self.loading_rows.retain_visible();
+    }
+
+    fn collect_entries(
+        cx: &mut Cx,
+        provider: &mut dyn TreeViewProvider,
+        expanded: &HashSet<LiveId>,
+        entries: &mut Vec<TreeViewEntry>,
+        parent: Option<LiveId>,
+        depth: usize,
+    ) {
+        let mut children = Vec::new();
+        if !provider.children(cx, parent, &mut children) {
+            entries.push(TreeViewEntry {
+                node: parent.unwrap_or(LiveId(0)),
+                parent,
+                depth,
+                has_children: false,
+                is_loading: true,
+            });
+            return
+        }
+        for node in children {
+            let has_children = provider.has_children(node);
+            entries.push(TreeViewEntry {node, parent, depth, has_children, is_loading: false});
+            if has_children && expanded.contains(&node) {
+                Self::collect_entries(cx, provider, expanded, entries, Some(node), depth + 1);
+            }
+        }
+    }
+
+    fn draw_drop_marker(&mut self, cx: &mut Cx2d) {
+        let Some(TreeViewPress {dragging: true, target: Some((target, position)), ..}) = self.press else {return};
+        let (Some(row), Some(entry)) = (self.rows.get(&target), self.entry(target)) else {return};
+        let rect = row.draw_bg.area().rect(cx);
+        let y = match position {
+            TreeViewDropPosition::Before => rect.pos.y,
+            TreeViewDropPosition::After => rect.pos.y + rect.size.y,
+            // the row itself lights up
+            TreeViewDropPosition::Into => return
+        };
+        let indent = entry.depth as f64 * self.indent_width;
+        let pos = if cx.turtle().is_rtl() {rect.pos.x} else {rect.pos.x + indent};
+        self.draw_drop_marker.draw_abs(cx, Rect {
+            pos: dvec2(pos, y - 1.0),
+            size: dvec2((rect.size.x - indent).max(0.0), 2.0)
+        });
+    }
+
+    fn entry(&self, node: LiveId) -> Option<&TreeViewEntry> {
+        self.entries.iter().find( | entry | !entry.is_loading && entry.node == node)
+    }
+
+    fn entry_index(&self, node: LiveId) -> Option<usize> {
+        self.entries.iter().position( | entry | !entry.is_loading && entry.node == node)
+    }
+
+    /// Whether `node` is one of `nodes` or sits somewhere below one of them
+    fn is_inside(&self, node: LiveId, nodes: &[LiveId]) -> bool {
+        let mut current = Some(node);
+        while let Some(node) = current {
+            if nodes.contains(&node) {
+                return true
+            }
+            current = self.entry(node).and_then( | entry | entry.parent);
+        }
+        false
+    }
+
+    /// The nodes a drag from `node` carries, children of a dragged node move along with it
+    fn dragged_nodes(&self, node: LiveId) -> Vec<LiveId> {
+        if !self.selected.contains(&node) {
+            return vec![node]
+        }
+        let selected = self.selected_nodes();
+        selected.iter()
+            .filter( | node | !self.entry(**node).and_then( | entry | entry.parent).is_some_and( | parent | self.is_inside(parent, &selected)))
+            .cloned()
+            .collect()
+    }
+
+    fn drop_target(&self, cx: &Cx, abs: DVec2, dragged: &[LiveId]) -> Option<(LiveId, TreeViewDropPosition)> {
+        let (node, row) = self.rows.iter().find( | (_, row) | row.draw_bg.area().rect(cx).contains(abs))?;
+        let entry = self.entry(*node)?;
+        // a node can't end up inside itself
+        if self.is_inside(*node, dragged) {
+            return None
+        }
+        let rect = row.draw_bg.area().rect(cx);
+        let fraction = (abs.y - rect.pos.y) / rect.size.y.max(1.0);
+        let position = if entry.has_children {
+            if fraction < 0.25 {TreeViewDropPosition::Before}
+            else if fraction > 0.75 {TreeViewDropPosition::After}
+            else {TreeViewDropPosition::Into}
+        }
+        else if fraction < 0.5 {TreeViewDropPosition::Before}
+        else {TreeViewDropPosition::After};
+        Some((*node, position))
+    }
+
+    fn toggle_expanded(&mut self, cx: &mut Cx, node: LiveId, uid: WidgetUid, scope: &mut Scope) {
+        if self.expanded.contains(&node) {
+            self.set_expanded(cx, node, false);
+            cx.widget_action(uid, &scope.path, TreeViewAction::Collapsed(node));
+        }
+        else {
+            self.set_expanded(cx, node, true);
+            cx.widget_action(uid, &scope.path, TreeViewAction::Expanded(node));
+        }
+    }
+
+    fn select_node(&mut self, cx: &mut Cx, node: LiveId, modifiers: &KeyModifiers, uid: WidgetUid, scope: &mut Scope) {
+        let toggle = modifiers.control || modifiers.logo;
+        match self.selection_mode {
+            TreeViewSelectionMode::None => return,
+            TreeViewSelectionMode::Single => {
+                self.selected.clear();
+                self.selected.insert(node);
+                self.anchor = Some(node);
+            }
+            TreeViewSelectionMode::Multi => {
+                let range = self.anchor.filter( | _ | modifiers.shift)
+                    .and_then( | anchor | self.entry_index(anchor))
+                    .zip(self.entry_index(node));
+                if let Some((anchor, index)) = range {
+                    if !toggle {
+                        self.selected.clear();
+                    }
+                    for entry in &self.entries[anchor.min(index)..=anchor.max(index)] {
+                        if !entry.is_loading {
+                            self.selected.insert(entry.node);
+                        }
+                    }
+                }
+                else if toggle {
+                    if !self.selected.remove(&node) {
+                        self.selected.insert(node);
+                    }
+                    self.anchor = Some(node);
+                }
+                else {
+                    self.selected.clear();
+                    self.selected.insert(node);
+                    self.anchor = Some(node);
+                }
+            }
+        }
+        self.cursor = Some(node);
+        self.area.redraw(cx);
+        cx.widget_action(uid, &scope.path, TreeViewAction::SelectionChanged);
+    }
+
+    fn page_rows(&self, cx: &Cx) -> usize {
+        (self.area.rect(cx).size.y / self.row_height).floor().max(1.0) as usize
+    }
+
+    fn scroll_index_into_view(&mut self, cx: &mut Cx, index: usize) {
+        let page = self.page_rows(cx);
+        let first = self.list.first_id();
+        if index < first {
+            self.list.set_first_id_and_scroll(index, 0.0);
+        }
+        else if index >= first + page {
+            self.list.set_first_id_and_scroll(index + 1 - page, 0.0);
+        }
+        else {
+            return
+        }
+        self.list.update_scroll_bar(cx);
+    }
+
+    /// The nearest node row from `index` going in the direction of `step`, loading rows are skipped
+    fn node_index_from(&self, index: usize, step: isize) -> Option<usize> {
+        let mut index = index as isize;
+        loop {
+            let entry = self.entries.get(usize::try_from(index).ok()?)?;
+            if !entry.is_loading {
+                return Some(index as usize)
+            }
+            index += step.signum();
+        }
+    }
+
+    fn handle_key_down(&mut self, cx: &mut Cx, ke: &KeyEvent, uid: WidgetUid, scope: &mut Scope) {
+        let Some(last) = self.node_index_from(self.entries.len().saturating_sub(1), -1) else {return};
+        let multi = self.selection_mode == TreeViewSelectionMode::Multi;
+        let command = ke.modifiers.control || ke.modifiers.logo;
+        let current = self.cursor.and_then( | cursor | self.entry_index(cursor));
+        let index = match (current, ke.key_code) {
+            (Some(index), KeyCode::ReturnKey) => {
+                cx.widget_action(uid, &scope.path, TreeViewAction::Activated(self.entries[index].node));
+                return
+            }
+            (_, KeyCode::KeyA) if multi && command => {
+                self.selected = self.entries.iter().filter( | e | !e.is_loading).map( | e | e.node).collect();
+                self.area.redraw(cx);
+                cx.widget_action(uid, &scope.path, TreeViewAction::SelectionChanged);
+                return
+            }
+            (Some(index), KeyCode::Space) if multi && command => {
+                let node = self.entries[index].node;
+                self.select_node(cx, node, &KeyModifiers {control: true, ..Default::default()}, uid, scope);
+                return
+            }
+            (Some(index), KeyCode::ArrowRight) if self.entries[index].has_children => {
+                let node = self.entries[index].node;
+                if !self.expanded.contains(&node) {
+                    self.toggle_expanded(cx, node, uid, scope);
+                    return
+                }
+                match self.entries.get(index + 1) {
+                    Some(child) if !child.is_loading && child.parent == Some(node) => index + 1,
+                    _ => return
+                }
+            }
+            (Some(index), KeyCode::ArrowLeft) => {
+                let entry = self.entries[index];
+                if entry.has_children && self.expanded.contains(&entry.node) {
+                    self.toggle_expanded(cx, entry.node, uid, scope);
+                    return
+                }
+                match entry.parent.and_then( | parent | self.entry_index(parent)) {
+                    Some(parent) => parent,
+                    None => return
+                }
+            }
+            // the first key press picks the first row in view
+            (None, KeyCode::ArrowUp | KeyCode::ArrowDown) => {
+                match self.node_index_from(self.list.first_id().min(last), 1) {
+                    Some(index) => index,
+                    None => return
+                }
+            }
+            (Some(index), KeyCode::ArrowUp) => match index.checked_sub(1).and_then( | index | self.node_index_from(index, -1)) {
+                Some(index) => index,
+                None => return
+            }
+            (Some(index), KeyCode::ArrowDown) => self.node_index_from(index + 1, 1).unwrap_or(index),
+            (index, KeyCode::PageUp) => {
+                let index = index.unwrap_or(0).saturating_sub(self.page_rows(cx));
+                self.node_index_from(index, 1).unwrap_or(last)
+            }
+            (index, KeyCode::PageDown) => {
+                let index = (index.unwrap_or(0) + self.page_rows(cx)).min(last);
+                self.node_index_from(index, -1).unwrap_or(0)
+            }
+            (_, KeyCode::Home) => self.node_index_from(0, 1).unwrap_or(0),
+            (_, KeyCode::End) => last,
+            _ => return
+        };
+        let node = self.entries[index].node;
+        self.select_node(cx, node, &KeyModifiers {shift: ke.modifiers.shift, ..Default::default()}, uid, scope);
+        self.scroll_index_into_view(cx, index);
+    }
+
+    fn handle_row_event(&mut self, cx: &mut Cx, event: &Event, uid: WidgetUid, scope: &mut Scope) {
+        let mut hits = Vec::new();
+        for (node, row) in self.rows.iter() {
+            match event.hits(cx, row.draw_bg.area()) {
+                Hit::Nothing => (),
+                hit => hits.push((*node, hit, row.draw_expander.area().rect(cx))),
+            }
+        }
+        for (node, hit, expander) in hits {
+            match hit {
+                Hit::FingerHoverIn(_) => {
+                    self.hover = Some(node);
+                    self.area.redraw(cx);
+                }
+                Hit::FingerHoverOut(_) if self.hover == Some(node) => {
+                    self.hover = None;
+                    self.area.redraw(cx);
+                }
+                Hit::FingerDown(fe) => {
+                    cx.set_key_focus(self.area);
+                    let has_children = self.entry(node).is_some_and( | entry | entry.has_children);
+                    if has_children && expander.contains(fe.abs) {
+                        self.press = None;
+                        self.toggle_expanded(cx, node, uid, scope);
+                        continue
+                    }
+                    let modifiers = fe.modifiers;
+                    let select_on_up = self.selection_mode == TreeViewSelectionMode::Multi
+                        && self.selected.contains(&node)
+                        && !(modifiers.shift || modifiers.control || modifiers.logo);
+                    if !select_on_up {
+                        self.select_node(cx, node, &modifiers, uid, scope);
+                    }
+                    self.press = Some(TreeViewPress {node, select_on_up, dragging: false, target: None});
+                    if fe.tap_count == 2 {
+                        if has_children {
+                            self.toggle_expanded(cx, node, uid, scope);
+                        }
+                        cx.widget_action(uid, &scope.path, TreeViewAction::Activated(node));
+                    }
+                }
+                Hit::FingerMove(fe) => {
+                    let Some(press) = &self.press else {continue};
+                    if !self.reorderable || (!press.dragging && fe.abs.distance(&fe.abs_start) < self.min_drag_distance) {
+                        continue
+                    }
+                    let dragged = self.dragged_nodes(press.node);
+                    let target = self.drop_target(cx, fe.abs, &dragged);
+                    if let Some(press) = &mut self.press {
+                        press.dragging = true;
+                        press.target = target;
+                    }
+                    self.area.redraw(cx);
+                }
+                Hit::FingerUp(fe) => {
+                    let Some(press) = self.press.take() else {continue};
+                    if press.dragging {
+                        if let Some((target, position)) = press.target {
+                            let nodes = self.dragged_nodes(press.node);
+                            cx.widget_action(uid, &scope.path, TreeViewAction::Move {nodes, target, position});
+                        }
+                    }
+                    else if press.select_on_up && fe.is_over {
+                        self.select_node(cx, node, &fe.modifiers, uid, scope);
+                    }
+                    self.area.redraw(cx);
+                }
+                _ => ()
+            }
+        }
+    }
+}
+
+impl Widget for TreeView {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+
+        for content in self.contents.values_mut() {
+            let content_uid = content.widget_uid();
+            cx.group_widget_actions(uid, content_uid, | cx | {
+                content.handle_event(cx, event, scope)
+            });
+        }
+
+        self.handle_row_event(cx, event, uid, scope);
+
+        self.list.handle_event(cx, event, scope);
+
+        match event.hits(cx, self.area) {
+            Hit::KeyDown(ke) => self.handle_key_down(cx, &ke, uid, scope),
+            Hit::KeyFocus(_) | Hit::KeyFocusLost(_) => self.area.redraw(cx),
+            _ => ()
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.draw_state.begin(cx, TreeViewDrawState::Rows) {
+            cx.begin_turtle(walk, self.layout);
+            let list_walk = self.list.walk(cx);
+            // the list steps once, its rows come from draw_nodes
+            if self.list.draw_walk(cx, scope, list_walk).is_step() {
+                return DrawStep::make_step()
+            }
+        }
+        if self.draw_state.get().is_some() {
+            let list_walk = self.list.walk(cx);
+            self.list.draw_walk_all(cx, scope, list_walk);
+            self.draw_drop_marker(cx);
+            cx.end_turtle_with_area(&mut self.area);
+            cx.add_nav_stop_with_tab_index(self.area, NavRole::Tree, self.tab_index);
+            self.draw_state.end();
+        }
+        DrawStep::done()
+    }
+
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let mut node = AccessNode::new(AccessRole::Tree, "");
+        node.state.focusable = self.tab_index >= 0;
+        node.state.focused = cx.has_key_focus(self.area);
+        node.rect = self.area.rect(cx);
+        Some(node)
+    }
+}
+
+impl TreeViewRef {
+    pub fn is_expanded(&self, node: LiveId) -> bool {
+        self.borrow().is_some_and( | inner | inner.is_expanded(node))
+    }
+
+    pub fn set_expanded(&self, cx: &mut Cx, node: LiveId, is_expanded: bool) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_expanded(cx, node, is_expanded);
+        }
+    }
+
+    pub fn selected_nodes(&self) -> Vec<LiveId> {
+        let Some(inner) = self.borrow() else {return Vec::new()};
+        inner.selected_nodes()
+    }
+
+    pub fn set_selection(&self, cx: &mut Cx, nodes: &[LiveId]) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_selection(cx, nodes);
+        }
+    }
+
+    pub fn expanded(&self, actions: &Actions) -> Option<LiveId> {
+        if let TreeViewAction::Expanded(node) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(node)
+        }
+        None
+    }
+
+    pub fn collapsed(&self, actions: &Actions) -> Option<LiveId> {
+        if let TreeViewAction::Collapsed(node) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(node)
+        }
+        None
+    }
+
+    pub fn selection_changed(&self, actions: &Actions) -> bool {
+        matches!(actions.find_widget_action_cast(self.widget_uid()), TreeViewAction::SelectionChanged)
+    }
+
+    pub fn activated(&self, actions: &Actions) -> Option<LiveId> {
+        if let TreeViewAction::Activated(node) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(node)
+        }
+        None
+    }
+
+    pub fn moved(&self, actions: &Actions) -> Option<(Vec<LiveId>, LiveId, TreeViewDropPosition)> {
+        if let TreeViewAction::Move {nodes, target, position} = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some((nodes, target, position))
+        }
+        None
+    }
+
+    /// The row contents that emitted one of `actions`, with their node
+    pub fn rows_with_actions(&self, actions: &Actions) -> Vec<(LiveId, WidgetRef)> {
+        let mut set = Vec::new();
+        let uid = self.widget_uid();
+        let Some(inner) = self.borrow() else {return set};
+        for action in actions {
+            if let Some(group) = action.as_widget_action().and_then( | action | action.group.as_ref()) {
+                if group.group_uid == uid {
+                    for ((node, _), content) in inner.contents.iter() {
+                        if group.item_uid == content.widget_uid() {
+                            set.push((*node, content.clone()))
+                        }
+                    }
+                }
+            }
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        std::collections::HashMap,
+        super::*,
+    };
+
+    // a -> (a1 -> a1x), a2; b; c whose children are still loading
+    struct TestTree {
+        children: HashMap<Option<LiveId>, Vec<LiveId>>,
+    }
+
+    impl TestTree {
+        fn new() -> Self {
+            let mut children = HashMap::new();
+            children.insert(None, vec![live_id!(a), live_id!(b), live_id!(c)]);
+            children.insert(Some(live_id!(a)), vec![live_id!(a1), live_id!(a2)]);
+            children.insert(Some(live_id!(a1)), vec![live_id!(a1x)]);
+            Self {children}
+        }
+    }
+
+    impl TreeViewProvider for TestTree {
+        fn children(&mut self, _cx: &mut Cx, parent: Option<LiveId>, out: &mut Vec<LiveId>) -> bool {
+            match self.children.get(&parent) {
+                Some(children) => {
+                    out.extend_from_slice(children);
+                    true
+                }
+                None => false
+            }
+        }
+
+        fn has_children(&self, node: LiveId) -> bool {
+            self.children.contains_key(&Some(node)) || node == live_id!(c)
+        }
+
+        fn row_template(&self, _node: LiveId) -> LiveId {
+            live_id!(Text)
+        }
+
+        fn fill_row(&mut self, _cx: &mut Cx, _node: LiveId, _row: &WidgetRef) {}
+    }
+
+    fn with_tree(f: impl FnOnce(&mut Cx, &mut TreeView, &mut TestTree)) {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let mut tree = TreeView::new(&mut cx);
+        let mut provider = TestTree::new();
+        flatten(&mut cx, &mut tree, &mut provider);
+        f(&mut cx, &mut tree, &mut provider);
+    }
+
+    // what `draw_nodes` collects before it draws
+    fn flatten(cx: &mut Cx, tree: &mut TreeView, provider: &mut TestTree) {
+        tree.entries.clear();
+        TreeView::collect_entries(cx, provider, &tree.expanded, &mut tree.entries, None, 0);
+    }
+
+    fn rows(tree: &TreeView) -> Vec<(LiveId, usize, bool)> {
+        tree.entries.iter().map( | entry | (entry.node, entry.depth, entry.is_loading)).collect()
+    }
+
+    fn key(cx: &mut Cx, tree: &mut TreeView, key_code: KeyCode, modifiers: KeyModifiers) {
+        let ke = KeyEvent {key_code, is_repeat: false, modifiers, time: 0.0};
+        tree.handle_key_down(cx, &ke, WidgetUid(0), &mut Scope::empty());
+    }
+
+    fn click(cx: &mut Cx, tree: &mut TreeView, node: LiveId, modifiers: KeyModifiers) {
+        tree.select_node(cx, node, &modifiers, WidgetUid(0), &mut Scope::empty());
+    }
+
+    #[test]
+    fn flattens_expanded_nodes_in_tree_order() {
+        with_tree( | cx, tree, provider | {
+            assert_eq!(rows(tree), vec![
+                (live_id!(a), 0, false),
+                (live_id!(b), 0, false),
+                (live_id!(c), 0, false),
+            ]);
+            tree.set_expanded(cx, live_id!(a), true);
+            tree.set_expanded(cx, live_id!(a1), true);
+            flatten(cx, tree, provider);
+            assert_eq!(rows(tree), vec![
+                (live_id!(a), 0, false),
+                (live_id!(a1), 1, false),
+                (live_id!(a1x), 2, false),
+                (live_id!(a2), 1, false),
+                (live_id!(b), 0, false),
+                (live_id!(c), 0, false),
+            ]);
+            assert_eq!(tree.entries[2].parent, Some(live_id!(a1)));
+            // children that are still loading show one loading row
+            tree.set_expanded(cx, live_id!(c), true);
+            flatten(cx, tree, provider);
+            assert_eq!(tree.entries.last().map( | entry | (entry.node, entry.depth, entry.is_loading)), Some((live_id!(c), 1, true)));
+            // an expanded node below a collapsed one stays hidden
+            tree.set_expanded(cx, live_id!(a), false);
+            flatten(cx, tree, provider);
+            assert_eq!(rows(tree)[0..3], [
+                (live_id!(a), 0, false),
+                (live_id!(b), 0, false),
+                (live_id!(c), 0, false),
+            ]);
+            assert!(tree.is_expanded(live_id!(a1)));
+        })
+    }
+
+    #[test]
+    fn arrow_keys_expand_collapse_and_walk_the_tree() {
+        with_tree( | cx, tree, provider | {
+            let none = KeyModifiers::default();
+            key(cx, tree, KeyCode::ArrowDown, none);
+            assert_eq!(tree.cursor, Some(live_id!(a)));
+            // right opens a closed node, then moves to its first child
+            let actions = cx.capture_actions( | cx | key(cx, tree, KeyCode::ArrowRight, none));
+            assert!(matches!(actions.find_widget_action_cast(WidgetUid(0)), TreeViewAction::Expanded(node) if node == live_id!(a)));
+            flatten(cx, tree, provider);
+            key(cx, tree, KeyCode::ArrowRight, none);
+            assert_eq!(tree.cursor, Some(live_id!(a1)));
+            key(cx, tree, KeyCode::ArrowDown, none);
+            assert_eq!(tree.cursor, Some(live_id!(a2)));
+            // left on a leaf goes to the parent, then closes it
+            key(cx, tree, KeyCode::ArrowLeft, none);
+            assert_eq!(tree.cursor, Some(live_id!(a)));
+            let actions = cx.capture_actions( | cx | key(cx, tree, KeyCode::ArrowLeft, none));
+            assert!(matches!(actions.find_widget_action_cast(WidgetUid(0)), TreeViewAction::Collapsed(node) if node == live_id!(a)));
+            assert!(!tree.is_expanded(live_id!(a)));
+            key(cx, tree, KeyCode::End, none);
+            assert_eq!(tree.cursor, Some(live_id!(c)));
+        })
+    }
+
+    #[test]
+    fn collapsing_moves_the_cursor_out_of_the_hidden_rows() {
+        with_tree( | cx, tree, provider | {
+            tree.set_expanded(cx, live_id!(a), true);
+            tree.set_expanded(cx, live_id!(a1), true);
+            flatten(cx, tree, provider);
+            tree.set_selection(cx, &[live_id!(a1x)]);
+            tree.set_expanded(cx, live_id!(a), false);
+            assert_eq!(tree.cursor, Some(live_id!(a)));
+        })
+    }
+
+    #[test]
+    fn selects_single_nodes_ranges_and_toggles() {
+        with_tree( | cx, tree, provider | {
+            let shift = KeyModifiers {shift: true, ..Default::default()};
+            let control = KeyModifiers {control: true, ..Default::default()};
+            tree.set_expanded(cx, live_id!(a), true);
+            flatten(cx, tree, provider);
+            click(cx, tree, live_id!(a1), KeyModifiers::default());
+            assert_eq!(tree.selected_nodes(), vec![live_id!(a1)]);
+            click(cx, tree, live_id!(b), shift);
+            assert_eq!(tree.selected_nodes(), vec![live_id!(a1), live_id!(a2), live_id!(b)]);
+            click(cx, tree, live_id!(a2), control);
+            assert_eq!(tree.selected_nodes(), vec![live_id!(a1), live_id!(b)]);
+            // a shifted range starts at the last toggled node
+            click(cx, tree, live_id!(a), shift);
+            assert_eq!(tree.selected_nodes(), vec![live_id!(a), live_id!(a1), live_id!(a2)]);
+            click(cx, tree, live_id!(c), KeyModifiers::default());
+            assert_eq!(tree.selected_nodes(), vec![live_id!(c)]);
+
+            tree.selection_mode = TreeViewSelectionMode::Single;
+            click(cx, tree, live_id!(a), shift);
+            assert_eq!(tree.selected_nodes(), vec![live_id!(a)]);
+            tree.selection_mode = TreeViewSelectionMode::None;
+            click(cx, tree, live_id!(b), KeyModifiers::default());
+            assert_eq!(tree.selected_nodes(), vec![live_id!(a)]);
+        })
+    }
+
+    #[test]
+    fn drags_carry_selected_parents_without_their_children() {
+        with_tree( | cx, tree, provider | {
+            tree.set_expanded(cx, live_id!(a), true);
+            flatten(cx, tree, provider);
+            tree.set_selection(cx, &[live_id!(a), live_id!(a2), live_id!(b)]);
+            assert_eq!(tree.dragged_nodes(live_id!(b)), vec![live_id!(a), live_id!(b)]);
+            // dragging an unselected node only takes that node
+            assert_eq!(tree.dragged_nodes(live_id!(a1)), vec![live_id!(a1)]);
+            assert!(tree.is_inside(live_id!(a2), &[live_id!(a)]));
+            assert!(!tree.is_inside(live_id!(b), &[live_id!(a)]));
+        })
+    }
+}
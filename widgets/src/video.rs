@@ -7,12 +7,12 @@ live_design! {
     VideoBase = {{Video}} {}
 }
 
-/// Currently only supported on Android
+/// Currently only supported on Android and Linux. On Linux it plays MJPEG AVI files with optional PCM audio.
 
 /// DSL Usage
 /// 
 /// `source` - determines the source for the video playback, can be either:
-///  - `Network { url: "https://www.someurl.com/video.mkv" }`. On Android it supports: HLS, DASH, RTMP, RTSP, and progressive HTTP downloads
+///  - `Network { url: "https://www.someurl.com/video.mkv" }`. On Android it supports: HLS, DASH, RTMP, RTSP, and progressive HTTP downloads. Not supported on Linux.
 ///  - `Filesystem { path: "/storage/.../DCIM/Camera/video.mp4" }`. On Android it requires read permissions that must be granted at runtime.
 ///  - `Dependency { path: dep("crate://self/resources/video.mp4") }`. For in-memory videos loaded through LiveDependencies
 /// 
@@ -259,7 +259,7 @@ impl LiveHook for Video {
     fn after_new_from_doc(&mut self, cx: &mut Cx) {
         self.id = LiveId::unique();
 
-        #[cfg(target_os = "android")]
+        #[cfg(any(target_os = "android", target_os = "linux"))]
         {
             if self.video_texture.is_none() {
                 let new_texture = Texture::new_with_format(cx, TextureFormat::VideoRGB);
@@ -269,8 +269,8 @@ impl LiveHook for Video {
             self.draw_bg.draw_vars.set_texture(0, &texture);
         }
 
-        #[cfg(not(target_os = "android"))]
-        error!("Video Widget is currently only supported on Android.");
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        error!("Video Widget is currently only supported on Android and Linux.");
 
         match cx.os_type() {
             OsType::Android(params) => {
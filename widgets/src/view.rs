@@ -938,6 +938,7 @@ impl View {
                 Size::Fixed(view_size.y)
             },
             margin: walk.margin,
+            ..walk
         }
     }
 
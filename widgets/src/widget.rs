@@ -51,6 +51,8 @@ pub trait Widget: WidgetNode {
     
     fn widget_to_data(&self, _cx: &mut Cx, _actions: &Actions, _nodes: &mut LiveNodeVec, _path: &[LiveId]) -> bool {false}
     fn data_to_widget(&mut self, _cx: &mut Cx, _nodes: &[LiveNode], _path: &[LiveId]) {}
+    /// The validation error of the data the widget is bound to, `None` once it is valid again
+    fn set_data_error(&mut self, _cx: &mut Cx, _error: Option<&str>) {}
     
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep;
     
@@ -66,6 +68,12 @@ pub trait Widget: WidgetNode {
     fn is_visible(&self) -> bool {
         true
     }
+    
+    /// The node this widget adds to the accessibility tree. It's asked once before drawing to
+    /// open the node and once after so it can report its drawn rect, widgets without one are transparent.
+    fn access_node(&self, _cx: &Cx) -> Option<AccessNode> {
+        None
+    }
 
     fn draw_all(&mut self, cx: &mut Cx2d, scope: &mut Scope) {
         while self.draw(cx, scope).is_step() {};
@@ -158,6 +166,25 @@ pub struct WidgetRegistry {
 pub struct WidgetRefInner{ 
     pub widget: Box<dyn Widget >,
 }
+
+impl WidgetRefInner {
+    // a widget that steps out of its draw keeps its node open until it's done
+    fn begin_access_node(&self, cx: &mut Cx2d) -> bool {
+        if let Some(node) = self.widget.access_node(cx) {
+            cx.begin_access_node(self.widget.widget_uid().0, node);
+            return true
+        }
+        false
+    }
+    
+    fn end_access_node(&self, cx: &mut Cx2d, access: bool) {
+        if access {
+            if let Some(node) = self.widget.access_node(cx) {
+                cx.end_access_node(self.widget.widget_uid().0, node);
+            }
+        }
+    }
+}
 #[derive(Clone, Default)]
 pub struct WidgetRef(Rc<RefCell<Option<WidgetRefInner>>>);
 
@@ -400,6 +427,12 @@ impl WidgetRef {
         }
     }
     
+    pub fn set_data_error(&self, cx: &mut Cx, error: Option<&str>) {
+        if let Some(inner) = self.0.borrow_mut().as_mut() {
+            inner.widget.set_data_error(cx, error);
+        }
+    }
+    
     pub fn find_widgets(
         &mut self,
         path: &[LiveId],
@@ -427,30 +460,36 @@ impl WidgetRef {
     
     pub fn draw_walk(&self, cx: &mut Cx2d, scope:&mut Scope, walk: Walk) -> DrawStep {
         if let Some(inner) = self.0.borrow_mut().as_mut() {
-           if let Some(nd) = inner.widget.draw_walk(cx, scope, walk).step() {
+            let access = inner.begin_access_node(cx);
+            if let Some(nd) = inner.widget.draw_walk(cx, scope, walk).step() {
                 if nd.is_empty() {
                     return DrawStep::make_step_here(self.clone())
                 }
                 return DrawStep::make_step_here(nd);
             }
+            inner.end_access_node(cx, access);
         }
         DrawStep::done()
     }
     
     pub fn draw_walk_all(&self, cx: &mut Cx2d, scope:&mut Scope, walk: Walk)  {
         if let Some(inner) = self.0.borrow_mut().as_mut() {
-            inner.widget.draw_walk_all(cx, scope, walk)
+            let access = inner.begin_access_node(cx);
+            inner.widget.draw_walk_all(cx, scope, walk);
+            inner.end_access_node(cx, access);
         }
     }
     
     pub fn draw(&mut self, cx: &mut Cx2d, scope: &mut Scope) -> DrawStep{
         if let Some(inner) = self.0.borrow_mut().as_mut() {
-        if let Some(nd) = inner.widget.draw(cx, scope).step() {
+            let access = inner.begin_access_node(cx);
+            if let Some(nd) = inner.widget.draw(cx, scope).step() {
                 if nd.is_empty() {
                     return DrawStep::make_step_here(self.clone())
                 }
                 return DrawStep::make_step_here(nd);
             }
+            inner.end_access_node(cx, access);
         }
         DrawStep::done()
     }
@@ -478,7 +517,9 @@ impl WidgetRef {
     
     pub fn draw_all(&self, cx: &mut Cx2d, scope:&mut Scope) {
         if let Some(inner) = self.0.borrow_mut().as_mut() {
-            return inner.widget.draw_all(cx, scope)
+            let access = inner.begin_access_node(cx);
+            inner.widget.draw_all(cx, scope);
+            inner.end_access_node(cx, access);
         }
     }
     
@@ -318,4 +318,14 @@ impl Widget for Window {
         
         DrawStep::done()
     }
+    
+    fn access_node(&self, cx: &Cx) -> Option<AccessNode> {
+        let window = &cx.windows[self.window.window_id()];
+        let mut node = AccessNode::new(AccessRole::Window, &window.create_title);
+        node.rect = Rect {
+            pos: window.window_geom.position,
+            size: window.window_geom.inner_size
+        };
+        Some(node)
+    }
 }